@@ -0,0 +1,76 @@
+use minidom::Element;
+
+use crate::error::Error;
+use crate::options::ParseOptions;
+use crate::sds::{DataStreamCollection, SCAP12_NS};
+use crate::xccdf::{TestResult, XCCDF12_NS};
+
+pub const ARF_NS: &str = "http://scap.nist.gov/schema/asset-reporting-format/1.1";
+
+/// Asset Reporting Format collection written by a scan: the data stream
+/// collection that was requested and the XCCDF TestResults of the reports.
+/// Other reports, e.g. OVAL results, aren't modeled.
+#[derive(Debug)]
+pub struct ArfResults {
+    pub(crate) collection: DataStreamCollection,
+    pub(crate) test_results: Vec<TestResult>,
+}
+
+/// Content of the `report-request` or `report` elements of the section.
+fn contents<'a>(root: &'a Element, section: &str, item: &str) -> Vec<&'a Element> {
+    root.get_child(section, ARF_NS)
+        .into_iter()
+        .flat_map(|s| s.children().filter(|c| c.is(item, ARF_NS)))
+        .filter_map(|c| c.get_child("content", ARF_NS))
+        .flat_map(|content| content.children())
+        .collect()
+}
+
+impl ArfResults {
+    pub fn from_xml(root: &Element, opts: &ParseOptions) -> Result<ArfResults, Error> {
+        if !root.is("asset-report-collection", ARF_NS) {
+            return Err(Error::unexpected_element(
+                root.name(),
+                Some("arf:asset-report-collection"),
+            ));
+        }
+        let collection = contents(root, "report-requests", "report-request")
+            .into_iter()
+            .find(|c| c.is("data-stream-collection", SCAP12_NS))
+            .ok_or_else(|| Error::missing_element(root.name(), "ds:data-stream-collection"))?;
+        let collection = DataStreamCollection::from_xml_with_options(collection, opts)?;
+        let mut test_results = Vec::new();
+        for content in contents(root, "reports", "report") {
+            if content.is("TestResult", XCCDF12_NS) {
+                test_results.push(TestResult::from_xml(content, opts)?);
+            }
+        }
+        Ok(ArfResults {
+            collection,
+            test_results,
+        })
+    }
+
+    pub fn collection(&self) -> &DataStreamCollection {
+        &self.collection
+    }
+
+    pub fn test_results(&self) -> &[TestResult] {
+        &self.test_results
+    }
+
+    /// The TestResult with the ID, or the last one without an ID.
+    pub fn test_result(&self, id: Option<&str>) -> Result<&TestResult, String> {
+        match id {
+            Some(id) => self
+                .test_results
+                .iter()
+                .find(|t| t.id() == id)
+                .ok_or_else(|| format!("TestResult '{}' not found", id)),
+            None => self
+                .test_results
+                .last()
+                .ok_or_else(|| String::from("The ARF doesn't contain any TestResult")),
+        }
+    }
+}
@@ -0,0 +1,703 @@
+use minidom::Element;
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::xccdf::{Benchmark, Severity, Status, XCCDF12_NS};
+
+fn text_element(name: &str, text: &str) -> Element {
+    Element::builder(name, XCCDF12_NS).append(text).build()
+}
+
+fn optional_text(name: &str, text: &Option<String>) -> Option<Element> {
+    text.as_deref().map(|text| text_element(name, text))
+}
+
+/// Whether the ID has the XCCDF 1.2 form `xccdf_<namespace>_<kind>_<name>`.
+fn is_xccdf_id(id: &str, kind: &str) -> bool {
+    id.strip_prefix("xccdf_")
+        .and_then(|rest| rest.split_once(&format!("_{}_", kind)))
+        .is_some_and(|(namespace, name)| !namespace.is_empty() && !name.is_empty())
+}
+
+/// IDs of the built items by kind, and the problems found so far.
+#[derive(Default)]
+struct Check {
+    ids: Vec<(String, &'static str)>,
+    errors: Vec<Error>,
+}
+
+impl Check {
+    fn id(&mut self, id: &str, kind: &'static str) {
+        if !is_xccdf_id(id, kind) {
+            self.errors.push(Error::Content(format!(
+                "{} ID '{}' doesn't match 'xccdf_<namespace>_{}_<name>'",
+                kind, id, kind
+            )));
+        }
+        self.ids.push((id.to_string(), kind));
+    }
+
+    fn kinds(&self) -> HashMap<&str, Vec<&'static str>> {
+        let mut kinds: HashMap<&str, Vec<&'static str>> = HashMap::new();
+        for (id, kind) in self.ids.iter() {
+            kinds.entry(id.as_str()).or_default().push(kind);
+        }
+        kinds
+    }
+}
+
+/// Builder of an XCCDF `Rule`.
+#[derive(Debug, Clone)]
+pub struct RuleBuilder {
+    id: String,
+    selected: bool,
+    severity: Option<Severity>,
+    title: Option<String>,
+    description: Option<String>,
+    rationale: Option<String>,
+    /// System and text of the idents
+    idents: Vec<(String, String)>,
+    /// System and script of the fixes
+    fixes: Vec<(String, String)>,
+    /// System, `href` and `name` of the checks
+    checks: Vec<(String, String, Option<String>)>,
+}
+
+impl RuleBuilder {
+    pub fn new(id: impl Into<String>) -> RuleBuilder {
+        RuleBuilder {
+            id: id.into(),
+            selected: true,
+            severity: None,
+            title: None,
+            description: None,
+            rationale: None,
+            idents: Vec::new(),
+            fixes: Vec::new(),
+            checks: Vec::new(),
+        }
+    }
+
+    pub fn selected(mut self, selected: bool) -> RuleBuilder {
+        self.selected = selected;
+        self
+    }
+
+    pub fn severity(mut self, severity: Severity) -> RuleBuilder {
+        self.severity = Some(severity);
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> RuleBuilder {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> RuleBuilder {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn rationale(mut self, rationale: impl Into<String>) -> RuleBuilder {
+        self.rationale = Some(rationale.into());
+        self
+    }
+
+    /// Adds an ident, e.g. a CCE with the system `https://ncp.nist.gov/cce`.
+    pub fn ident(mut self, system: impl Into<String>, text: impl Into<String>) -> RuleBuilder {
+        self.idents.push((system.into(), text.into()));
+        self
+    }
+
+    /// Adds a fix, e.g. a shell script with the system `urn:xccdf:fix:script:sh`.
+    pub fn fix(mut self, system: impl Into<String>, script: impl Into<String>) -> RuleBuilder {
+        self.fixes.push((system.into(), script.into()));
+        self
+    }
+
+    /// Adds a check pointing to the content at `href`, e.g. an OVAL
+    /// definition given by `name`.
+    pub fn check(
+        mut self,
+        system: impl Into<String>,
+        href: impl Into<String>,
+        name: Option<&str>,
+    ) -> RuleBuilder {
+        self.checks
+            .push((system.into(), href.into(), name.map(|n| n.to_string())));
+        self
+    }
+
+    fn check_ids(&self, check: &mut Check) {
+        check.id(&self.id, "rule");
+    }
+
+    fn to_element(&self) -> Element {
+        let mut builder = Element::builder("Rule", XCCDF12_NS).attr("id", self.id.as_str());
+        if !self.selected {
+            builder = builder.attr("selected", "false");
+        }
+        if let Some(severity) = self.severity {
+            builder = builder.attr("severity", severity.as_str());
+        }
+        let idents = self.idents.iter().map(|(system, text)| {
+            Element::builder("ident", XCCDF12_NS)
+                .attr("system", system.as_str())
+                .append(text.as_str())
+                .build()
+        });
+        let fixes = self.fixes.iter().map(|(system, script)| {
+            Element::builder("fix", XCCDF12_NS)
+                .attr("system", system.as_str())
+                .append(script.as_str())
+                .build()
+        });
+        let checks = self.checks.iter().map(|(system, href, name)| {
+            let mut content_ref =
+                Element::builder("check-content-ref", XCCDF12_NS).attr("href", href.as_str());
+            if let Some(name) = name {
+                content_ref = content_ref.attr("name", name.as_str());
+            }
+            Element::builder("check", XCCDF12_NS)
+                .attr("system", system.as_str())
+                .append(content_ref.build())
+                .build()
+        });
+        builder
+            .append_all(optional_text("title", &self.title))
+            .append_all(optional_text("description", &self.description))
+            .append_all(optional_text("rationale", &self.rationale))
+            .append_all(idents)
+            .append_all(fixes)
+            .append_all(checks)
+            .build()
+    }
+}
+
+/// Builder of an XCCDF `Value`.
+#[derive(Debug, Clone)]
+pub struct ValueBuilder {
+    id: String,
+    value_type: Option<String>,
+    title: Option<String>,
+    description: Option<String>,
+    /// Selector and text of the values, the first one without a selector
+    /// is the default
+    values: Vec<(Option<String>, String)>,
+}
+
+impl ValueBuilder {
+    pub fn new(id: impl Into<String>) -> ValueBuilder {
+        ValueBuilder {
+            id: id.into(),
+            value_type: None,
+            title: None,
+            description: None,
+            values: Vec::new(),
+        }
+    }
+
+    /// `string`, `number` or `boolean`
+    pub fn value_type(mut self, value_type: impl Into<String>) -> ValueBuilder {
+        self.value_type = Some(value_type.into());
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> ValueBuilder {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> ValueBuilder {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Adds the default value.
+    pub fn value(mut self, value: impl Into<String>) -> ValueBuilder {
+        self.values.push((None, value.into()));
+        self
+    }
+
+    /// Adds a value profiles can choose with the selector.
+    pub fn option(mut self, selector: impl Into<String>, value: impl Into<String>) -> ValueBuilder {
+        self.values.push((Some(selector.into()), value.into()));
+        self
+    }
+
+    fn check_ids(&self, check: &mut Check) {
+        check.id(&self.id, "value");
+        if self.values.is_empty() {
+            check.errors.push(Error::Content(format!(
+                "Value '{}' doesn't have any value",
+                self.id
+            )));
+        }
+    }
+
+    fn to_element(&self) -> Element {
+        let mut builder = Element::builder("Value", XCCDF12_NS).attr("id", self.id.as_str());
+        if let Some(value_type) = &self.value_type {
+            builder = builder.attr("type", value_type.as_str());
+        }
+        let values = self.values.iter().map(|(selector, text)| {
+            let mut value = Element::builder("value", XCCDF12_NS);
+            if let Some(selector) = selector {
+                value = value.attr("selector", selector.as_str());
+            }
+            value.append(text.as_str()).build()
+        });
+        builder
+            .append_all(optional_text("title", &self.title))
+            .append_all(optional_text("description", &self.description))
+            .append_all(values)
+            .build()
+    }
+}
+
+/// Group or rule, kept in the order they were added.
+#[derive(Debug, Clone)]
+enum ItemBuilder {
+    Group(GroupBuilder),
+    Rule(RuleBuilder),
+}
+
+impl ItemBuilder {
+    fn check_ids(&self, check: &mut Check) {
+        match self {
+            ItemBuilder::Group(group) => group.check_ids(check),
+            ItemBuilder::Rule(rule) => rule.check_ids(check),
+        }
+    }
+
+    fn to_element(&self) -> Element {
+        match self {
+            ItemBuilder::Group(group) => group.to_element(),
+            ItemBuilder::Rule(rule) => rule.to_element(),
+        }
+    }
+}
+
+/// Builder of an XCCDF `Group` with its values, rules and nested groups.
+#[derive(Debug, Clone)]
+pub struct GroupBuilder {
+    id: String,
+    selected: bool,
+    title: Option<String>,
+    description: Option<String>,
+    values: Vec<ValueBuilder>,
+    items: Vec<ItemBuilder>,
+}
+
+impl GroupBuilder {
+    pub fn new(id: impl Into<String>) -> GroupBuilder {
+        GroupBuilder {
+            id: id.into(),
+            selected: true,
+            title: None,
+            description: None,
+            values: Vec::new(),
+            items: Vec::new(),
+        }
+    }
+
+    pub fn selected(mut self, selected: bool) -> GroupBuilder {
+        self.selected = selected;
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> GroupBuilder {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> GroupBuilder {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn value(mut self, value: ValueBuilder) -> GroupBuilder {
+        self.values.push(value);
+        self
+    }
+
+    pub fn group(mut self, group: GroupBuilder) -> GroupBuilder {
+        self.items.push(ItemBuilder::Group(group));
+        self
+    }
+
+    pub fn rule(mut self, rule: RuleBuilder) -> GroupBuilder {
+        self.items.push(ItemBuilder::Rule(rule));
+        self
+    }
+
+    fn check_ids(&self, check: &mut Check) {
+        check.id(&self.id, "group");
+        self.values.iter().for_each(|v| v.check_ids(check));
+        self.items.iter().for_each(|i| i.check_ids(check));
+    }
+
+    fn to_element(&self) -> Element {
+        let mut builder = Element::builder("Group", XCCDF12_NS).attr("id", self.id.as_str());
+        if !self.selected {
+            builder = builder.attr("selected", "false");
+        }
+        builder
+            .append_all(optional_text("title", &self.title))
+            .append_all(optional_text("description", &self.description))
+            .append_all(self.values.iter().map(ValueBuilder::to_element))
+            .append_all(self.items.iter().map(ItemBuilder::to_element))
+            .build()
+    }
+}
+
+/// Builder of an XCCDF `Profile`.
+#[derive(Debug, Clone)]
+pub struct ProfileBuilder {
+    id: String,
+    title: String,
+    description: Option<String>,
+    extends: Option<String>,
+    selects: Vec<(String, bool)>,
+    set_values: Vec<(String, String)>,
+}
+
+impl ProfileBuilder {
+    pub fn new(id: impl Into<String>, title: impl Into<String>) -> ProfileBuilder {
+        ProfileBuilder {
+            id: id.into(),
+            title: title.into(),
+            description: None,
+            extends: None,
+            selects: Vec::new(),
+            set_values: Vec::new(),
+        }
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> ProfileBuilder {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// ID of the profile this one inherits the selections from.
+    pub fn extends(mut self, profile_id: impl Into<String>) -> ProfileBuilder {
+        self.extends = Some(profile_id.into());
+        self
+    }
+
+    /// Selects or unselects a rule or a group.
+    pub fn select(mut self, idref: impl Into<String>, selected: bool) -> ProfileBuilder {
+        self.selects.push((idref.into(), selected));
+        self
+    }
+
+    pub fn set_value(
+        mut self,
+        idref: impl Into<String>,
+        value: impl Into<String>,
+    ) -> ProfileBuilder {
+        self.set_values.push((idref.into(), value.into()));
+        self
+    }
+
+    /// Checks that the selections point to items of the given kinds.
+    fn check_refs(&self, kinds: &HashMap<&str, Vec<&'static str>>, errors: &mut Vec<Error>) {
+        let refs = self
+            .extends
+            .iter()
+            .map(|idref| (idref, "extends", &["profile"][..]));
+        let selects = self
+            .selects
+            .iter()
+            .map(|(idref, _)| (idref, "select", &["rule", "group"][..]));
+        let set_values = self
+            .set_values
+            .iter()
+            .map(|(idref, _)| (idref, "set-value", &["value"][..]));
+        for (idref, what, expected) in refs.chain(selects).chain(set_values) {
+            let found = kinds
+                .get(idref.as_str())
+                .is_some_and(|k| k.iter().any(|kind| expected.contains(kind)));
+            if !found {
+                errors.push(Error::Content(format!(
+                    "{} of profile '{}' points to missing {} '{}'",
+                    what,
+                    self.id,
+                    expected.join(" or "),
+                    idref
+                )));
+            }
+        }
+    }
+
+    fn to_element(&self) -> Element {
+        let mut builder = Element::builder("Profile", XCCDF12_NS).attr("id", self.id.as_str());
+        if let Some(extends) = &self.extends {
+            builder = builder.attr("extends", extends.as_str());
+        }
+        let selects = self.selects.iter().map(|(idref, selected)| {
+            Element::builder("select", XCCDF12_NS)
+                .attr("idref", idref.as_str())
+                .attr("selected", if *selected { "true" } else { "false" })
+                .build()
+        });
+        let set_values = self.set_values.iter().map(|(idref, value)| {
+            Element::builder("set-value", XCCDF12_NS)
+                .attr("idref", idref.as_str())
+                .append(value.as_str())
+                .build()
+        });
+        builder
+            .append(text_element("title", &self.title))
+            .append_all(optional_text("description", &self.description))
+            .append_all(selects)
+            .append_all(set_values)
+            .build()
+    }
+}
+
+/// Builder of an XCCDF 1.2 `Benchmark`, for tools generating content. The
+/// elements are written in the order required by the schema, whatever the
+/// order of the calls.
+#[derive(Debug, Clone)]
+pub struct BenchmarkBuilder {
+    id: String,
+    status: Option<Status>,
+    title: Option<String>,
+    description: Option<String>,
+    platforms: Vec<String>,
+    version: Option<String>,
+    profiles: Vec<ProfileBuilder>,
+    values: Vec<ValueBuilder>,
+    items: Vec<ItemBuilder>,
+}
+
+impl BenchmarkBuilder {
+    pub fn new(id: impl Into<String>) -> BenchmarkBuilder {
+        BenchmarkBuilder {
+            id: id.into(),
+            status: None,
+            title: None,
+            description: None,
+            platforms: Vec::new(),
+            version: None,
+            profiles: Vec::new(),
+            values: Vec::new(),
+            items: Vec::new(),
+        }
+    }
+
+    pub fn status(mut self, status: Status) -> BenchmarkBuilder {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> BenchmarkBuilder {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> BenchmarkBuilder {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Adds a platform the benchmark applies to, e.g. a CPE name.
+    pub fn platform(mut self, idref: impl Into<String>) -> BenchmarkBuilder {
+        self.platforms.push(idref.into());
+        self
+    }
+
+    pub fn version(mut self, version: impl Into<String>) -> BenchmarkBuilder {
+        self.version = Some(version.into());
+        self
+    }
+
+    pub fn profile(mut self, profile: ProfileBuilder) -> BenchmarkBuilder {
+        self.profiles.push(profile);
+        self
+    }
+
+    pub fn value(mut self, value: ValueBuilder) -> BenchmarkBuilder {
+        self.values.push(value);
+        self
+    }
+
+    pub fn group(mut self, group: GroupBuilder) -> BenchmarkBuilder {
+        self.items.push(ItemBuilder::Group(group));
+        self
+    }
+
+    pub fn rule(mut self, rule: RuleBuilder) -> BenchmarkBuilder {
+        self.items.push(ItemBuilder::Rule(rule));
+        self
+    }
+
+    fn check(&self) -> Vec<Error> {
+        let mut check = Check::default();
+        check.id(&self.id, "benchmark");
+        if self.status.is_none() {
+            check
+                .errors
+                .push(Error::missing_element("Benchmark", "status"));
+        }
+        if self.version.is_none() {
+            check
+                .errors
+                .push(Error::missing_element("Benchmark", "version"));
+        }
+        for profile in self.profiles.iter() {
+            check.id(&profile.id, "profile");
+        }
+        self.values.iter().for_each(|v| v.check_ids(&mut check));
+        self.items.iter().for_each(|i| i.check_ids(&mut check));
+        let kinds = check.kinds();
+        let mut errors = Vec::new();
+        let mut reported = Vec::new();
+        for (id, _) in check.ids.iter() {
+            let used = &kinds[id.as_str()];
+            if used.len() > 1 && !reported.contains(&id) {
+                reported.push(id);
+                errors.push(Error::Content(format!(
+                    "ID '{}' is used by {}",
+                    id,
+                    used.join(", ")
+                )));
+            }
+        }
+        for profile in self.profiles.iter() {
+            profile.check_refs(&kinds, &mut errors);
+        }
+        check.errors.extend(errors);
+        check.errors
+    }
+
+    fn to_element(&self) -> Element {
+        let platforms = self.platforms.iter().map(|idref| {
+            Element::builder("platform", XCCDF12_NS)
+                .attr("idref", idref.as_str())
+                .build()
+        });
+        Element::builder("Benchmark", XCCDF12_NS)
+            .attr("id", self.id.as_str())
+            .append_all(self.status.map(|s| text_element("status", s.as_str())))
+            .append_all(optional_text("title", &self.title))
+            .append_all(optional_text("description", &self.description))
+            .append_all(platforms)
+            .append_all(optional_text("version", &self.version))
+            .append_all(self.profiles.iter().map(ProfileBuilder::to_element))
+            .append_all(self.values.iter().map(ValueBuilder::to_element))
+            .append_all(self.items.iter().map(ItemBuilder::to_element))
+            .build()
+    }
+
+    /// Checks the benchmark and returns it in the model. Fails with all the
+    /// problems found: IDs not in the XCCDF 1.2 form or used more than once,
+    /// a missing status or version, values without a value, and profiles
+    /// selecting missing items.
+    pub fn build(self) -> Result<Benchmark, Error> {
+        self.build_with_element().map(|(benchmark, _)| benchmark)
+    }
+
+    /// Like [`BenchmarkBuilder::build`], also returning the `Benchmark`
+    /// element, which can be written with [`crate::writer::write_document`].
+    pub fn build_with_element(self) -> Result<(Benchmark, Element), Error> {
+        let mut errors = self.check();
+        let el = self.to_element();
+        if !errors.is_empty() {
+            return Err(match errors.len() {
+                1 => errors.remove(0),
+                _ => Error::Multiple(errors),
+            });
+        }
+        let benchmark = Benchmark::from_xml(&el)?;
+        Ok((benchmark, el))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_benchmark() {
+        let builder = BenchmarkBuilder::new("xccdf_org.example_benchmark_b")
+            .version("1.0")
+            .title("Example")
+            .profile(
+                ProfileBuilder::new("xccdf_org.example_profile_p", "Profile")
+                    .select("xccdf_org.example_rule_r2", true)
+                    .set_value("xccdf_org.example_value_v", "077"),
+            )
+            .rule(RuleBuilder::new("xccdf_org.example_rule_r1").severity(Severity::High))
+            .group(
+                GroupBuilder::new("xccdf_org.example_group_g")
+                    .title("Group")
+                    .value(ValueBuilder::new("xccdf_org.example_value_v").value("027"))
+                    .rule(
+                        RuleBuilder::new("xccdf_org.example_rule_r2")
+                            .selected(false)
+                            .title("Rule 2")
+                            .ident("https://ncp.nist.gov/cce", "CCE-80000-0")
+                            .check(
+                                "http://oval.mitre.org/XMLSchema/oval-definitions-5",
+                                "oval.xml",
+                                Some("oval:x:def:1"),
+                            ),
+                    ),
+            )
+            .status(Status::Draft);
+        let (benchmark, el) = builder.clone().build_with_element().unwrap();
+        let names: Vec<&str> = el.children().map(|c| c.name()).collect();
+        assert_eq!(
+            names,
+            vec!["status", "title", "version", "Profile", "Rule", "Group"]
+        );
+        let profile = benchmark
+            .resolve_profile("xccdf_org.example_profile_p")
+            .unwrap();
+        let rules: Vec<&str> = benchmark
+            .selected_rules(&profile)
+            .iter()
+            .map(|r| r.id())
+            .collect();
+        assert_eq!(
+            rules,
+            vec!["xccdf_org.example_rule_r1", "xccdf_org.example_rule_r2"]
+        );
+        let built = builder.build().unwrap();
+        assert_eq!(built.id(), "xccdf_org.example_benchmark_b");
+        assert_eq!(built.iter_rules().count(), 2);
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let error = BenchmarkBuilder::new("xccdf_org.example_benchmark_b")
+            .status(Status::Draft)
+            .profile(
+                ProfileBuilder::new("xccdf_org.example_profile_p", "Profile")
+                    .select("xccdf_org.example_rule_missing", true),
+            )
+            .rule(RuleBuilder::new("xccdf_org.example_rule_r"))
+            .rule(RuleBuilder::new("xccdf_org.example_rule_r"))
+            .value(ValueBuilder::new("v"))
+            .build()
+            .unwrap_err();
+        let messages: Vec<String> = match error {
+            Error::Multiple(errors) => errors.iter().map(|e| e.to_string()).collect(),
+            error => panic!("unexpected error {}", error),
+        };
+        assert_eq!(messages.len(), 5);
+        assert!(messages[1].contains("doesn't match 'xccdf_<namespace>_value_<name>'"));
+        assert_eq!(messages[2], "Value 'v' doesn't have any value");
+        assert_eq!(
+            messages[3],
+            "ID 'xccdf_org.example_rule_r' is used by rule, rule"
+        );
+        assert_eq!(
+            messages[4],
+            "select of profile 'xccdf_org.example_profile_p' points to missing rule or group \
+             'xccdf_org.example_rule_missing'"
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use minidom::Element;
+use std::collections::HashMap;
+
+use crate::xccdf::{Benchmark, Group, IdentSystem, Rule, Severity};
+
+/// Strips the `xccdf_<reverse DNS>_<kind>_` prefix from an XCCDF 1.2 ID.
+fn short_id<'a>(id: &'a str, kind: &str) -> &'a str {
+    let marker = format!("_{}_", kind);
+    match id.find(&marker) {
+        Some(pos) if id.starts_with("xccdf_") => &id[pos + marker.len()..],
+        _ => id,
+    }
+}
+
+/// Maps an XCCDF rule result to the STIG Viewer finding status.
+fn ckl_status(result: Option<&str>) -> &'static str {
+    match result {
+        Some("pass") | Some("fixed") => "NotAFinding",
+        Some("fail") => "Open",
+        Some("notapplicable") => "Not_Applicable",
+        _ => "Not_Reviewed",
+    }
+}
+
+/// Maps a rule severity to the ones STIG Viewer accepts, informational
+/// rules are the lowest category. An unknown severity is left out.
+fn ckl_severity(severity: Severity) -> Option<&'static str> {
+    match severity {
+        Severity::Info | Severity::Low => Some("low"),
+        Severity::Medium => Some("medium"),
+        Severity::High => Some("high"),
+        Severity::Unknown => None,
+    }
+}
+
+fn text_element(name: &str, text: &str) -> Element {
+    Element::builder(name, "").append(text).build()
+}
+
+fn stig_data(attribute: &str, data: &str) -> Element {
+    Element::builder("STIG_DATA", "")
+        .append(text_element("VULN_ATTRIBUTE", attribute))
+        .append(text_element("ATTRIBUTE_DATA", data))
+        .build()
+}
+
+fn si_data(name: &str, data: &str) -> Element {
+    Element::builder("SI_DATA", "")
+        .append(text_element("SID_NAME", name))
+        .append(text_element("SID_DATA", data))
+        .build()
+}
+
+struct CklBuilder<'a> {
+    benchmark: &'a Benchmark,
+    results: HashMap<&'a str, &'a str>,
+    vulns: Vec<Element>,
+}
+
+impl<'a> CklBuilder<'a> {
+    fn add_group(&mut self, group: &Group) {
+        for rule in group.rules.iter() {
+            self.add_rule(rule, Some(group));
+        }
+        for child in group.groups.iter() {
+            self.add_group(child);
+        }
+    }
+
+    fn add_rule(&mut self, rule: &Rule, group: Option<&Group>) {
+        let vuln_num = match group {
+            Some(group) => short_id(&group.id, "group"),
+            None => short_id(&rule.id, "rule"),
+        };
+        let group_title = group
+            .and_then(|g| g.titles.first())
+            .map(|t| t.title.as_str())
+            .unwrap_or("");
+        let rule_title = rule.titles.first().map(|t| t.title.as_str()).unwrap_or("");
+        let rule_version = rule.version.as_ref().map(|v| v.text.as_str()).unwrap_or("");
+        let discussion = rule
+            .descriptions
+            .first()
+            .map(|d| self.benchmark.render(d))
+            .unwrap_or_default();
+        let check_content = rule.checks.first().map(|c| c.text.trim()).unwrap_or("");
+        let fix_text = rule
+            .fixtexts
+            .first()
+            .map(|f| f.text().trim().to_string())
+            .unwrap_or_default();
+        let mut vuln = Element::builder("VULN", "")
+            .append(stig_data("Vuln_Num", vuln_num))
+            .append_all(ckl_severity(rule.severity).map(|s| stig_data("Severity", s)))
+            .append(stig_data("Group_Title", group_title))
+            .append(stig_data("Rule_ID", short_id(&rule.id, "rule")))
+            .append(stig_data("Rule_Ver", rule_version))
+            .append(stig_data("Rule_Title", rule_title))
+            .append(stig_data("Vuln_Discuss", &discussion))
+            .append(stig_data("Check_Content", check_content))
+            .append(stig_data("Fix_Text", &fix_text))
+            .build();
+        for ident in rule.idents.iter() {
+            if ident.kind() == IdentSystem::Cci {
+                vuln.append_child(stig_data("CCI_REF", &ident.text));
+            }
+        }
+        let status = ckl_status(self.results.get(rule.id.as_ref()).copied());
+        vuln.append_child(text_element("STATUS", status));
+        for name in [
+            "FINDING_DETAILS",
+            "COMMENTS",
+            "SEVERITY_OVERRIDE",
+            "SEVERITY_JUSTIFICATION",
+        ] {
+            vuln.append_child(Element::bare(name, ""));
+        }
+        self.vulns.push(vuln);
+    }
+}
+
+/// Converts a benchmark into a DISA STIG Viewer checklist. When the ID of a
+/// TestResult is given, the rule results are mapped to the finding statuses,
+/// otherwise every finding is left as "Not_Reviewed".
+pub fn benchmark_to_ckl(
+    benchmark: &Benchmark,
+    test_result_id: Option<&str>,
+) -> Result<Element, String> {
+    let mut results = HashMap::new();
+    if let Some(test_result_id) = test_result_id {
+        let test_result = match benchmark
+            .test_results
+            .iter()
+            .find(|t| &*t.id == test_result_id)
+        {
+            Some(test_result) => test_result,
+            None => return Err(format!("TestResult '{}' not found", test_result_id)),
+        };
+        for rule_result in test_result.rule_results.iter() {
+            results.insert(rule_result.idref.as_ref(), rule_result.result.as_str());
+        }
+    }
+    let mut builder = CklBuilder {
+        benchmark,
+        results,
+        vulns: Vec::new(),
+    };
+    for rule in benchmark.rules.iter() {
+        builder.add_rule(rule, None);
+    }
+    for group in benchmark.groups.iter() {
+        builder.add_group(group);
+    }
+
+    let title = benchmark
+        .titles
+        .first()
+        .map(|t| t.title.as_str())
+        .unwrap_or("");
+    let stig_info = Element::builder("STIG_INFO", "")
+        .append(si_data("version", &benchmark.version.text))
+        .append(si_data("stigid", short_id(&benchmark.id, "benchmark")))
+        .append(si_data("title", title))
+        .build();
+    let istig = Element::builder("iSTIG", "")
+        .append(stig_info)
+        .append_all(builder.vulns)
+        .build();
+    let mut asset = Element::bare("ASSET", "");
+    asset.append_child(text_element("ROLE", "None"));
+    asset.append_child(text_element("ASSET_TYPE", "Computing"));
+    for name in [
+        "HOST_NAME",
+        "HOST_IP",
+        "HOST_MAC",
+        "HOST_FQDN",
+        "TARGET_KEY",
+    ] {
+        asset.append_child(Element::bare(name, ""));
+    }
+    asset.append_child(text_element("WEB_OR_DATABASE", "false"));
+    Ok(Element::builder("CHECKLIST", "")
+        .append(asset)
+        .append(Element::builder("STIGS", "").append(istig).build())
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xccdf::XCCDF12_NS;
+
+    #[test]
+    fn test_short_id() {
+        assert_eq!(
+            short_id("xccdf_mil.disa.stig_group_V-1234", "group"),
+            "V-1234"
+        );
+        assert_eq!(short_id("V-1234", "group"), "V-1234");
+    }
+
+    #[test]
+    fn test_benchmark_to_ckl() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"xccdf_mil.disa.stig_benchmark_X\">\
+             <status>accepted</status><version>1</version>\
+             <Group id=\"xccdf_mil.disa.stig_group_V-1\"><title>SRG-1</title>\
+             <Rule id=\"xccdf_mil.disa.stig_rule_SV-1r1_rule\" severity=\"high\"><title>Do it</title>\
+             <ident system=\"http://cyber.mil/cci\">CCI-000366</ident></Rule></Group>\
+             <TestResult id=\"xccdf_mil.disa.stig_testresult_1\" end-time=\"2022-01-01T00:00:00\">\
+             <rule-result idref=\"xccdf_mil.disa.stig_rule_SV-1r1_rule\"><result>fail</result></rule-result>\
+             </TestResult></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let ckl = benchmark_to_ckl(&benchmark, Some("xccdf_mil.disa.stig_testresult_1")).unwrap();
+        let vuln = ckl
+            .get_child("STIGS", "")
+            .and_then(|s| s.get_child("iSTIG", ""))
+            .and_then(|i| i.get_child("VULN", ""))
+            .unwrap();
+        assert_eq!(vuln.get_child("STATUS", "").unwrap().text(), "Open");
+        let data: Vec<String> = vuln
+            .children()
+            .filter_map(|c| c.get_child("ATTRIBUTE_DATA", ""))
+            .map(|d| d.text())
+            .collect();
+        assert_eq!(data[0], "V-1");
+        assert_eq!(data[1], "high");
+        assert!(data.contains(&String::from("CCI-000366")));
+        assert!(benchmark_to_ckl(&benchmark, Some("missing")).is_err());
+    }
+
+    #[test]
+    fn test_ckl_severity() {
+        assert_eq!(ckl_severity(Severity::Info), Some("low"));
+        assert_eq!(ckl_severity(Severity::High), Some("high"));
+        assert_eq!(ckl_severity(Severity::Unknown), None);
+    }
+}
@@ -0,0 +1,428 @@
+use minidom::{Element, Node};
+
+use sha2::{Digest, Sha256};
+
+use crate::dsig;
+use crate::matrix::CPE_DICT_NS;
+use crate::oval::OVAL_DEF_NS;
+use crate::sds::{CAT_NS, OCIL_NS, SCAP12_NS};
+use crate::trust::encode_base64;
+use crate::utils::{current_timestamp, insert_child_at, retain_children};
+use crate::writer;
+use crate::xccdf::XCCDF12_NS;
+
+/// Lists of component-refs of a data stream in the schema order.
+const SECTIONS: [&str; 4] = [
+    "dictionaries",
+    "checklists",
+    "checks",
+    "extended-components",
+];
+
+/// List of component-refs that references content with this root element.
+/// Content other than CPE dictionaries, XCCDF benchmarks, OVAL definitions
+/// and OCIL questionnaires is an extended component.
+fn section_for(content: &Element) -> &'static str {
+    match (content.name(), content.ns().as_str()) {
+        ("cpe-list", CPE_DICT_NS) => "dictionaries",
+        ("Benchmark", XCCDF12_NS) => "checklists",
+        ("oval_definitions", OVAL_DEF_NS) | ("ocil", OCIL_NS) => "checks",
+        _ => "extended-components",
+    }
+}
+
+/// Reverse DNS name of a `scap_<reverse DNS>_collection_<name>` collection.
+fn collection_namespace(root: &Element) -> &str {
+    root.attr("id")
+        .and_then(|id| id.strip_prefix("scap_")?.split_once("_collection_"))
+        .map_or("oscapxml", |(namespace, _)| namespace)
+}
+
+fn check_collection(root: &Element) -> Result<(), String> {
+    if root.is("data-stream-collection", SCAP12_NS) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Expected a source data stream collection, found '{}' in namespace '{}'",
+            root.name(),
+            root.ns()
+        ))
+    }
+}
+
+/// Whether a component, an extended component, a data stream or a
+/// component-ref already uses the ID.
+fn id_in_use(root: &Element, id: &str) -> bool {
+    root.children().any(|child| {
+        child.attr("id") == Some(id)
+            || (child.is("data-stream", SCAP12_NS)
+                && child
+                    .children()
+                    .flat_map(|section| section.children())
+                    .any(|component_ref| component_ref.attr("id") == Some(id)))
+    })
+}
+
+/// Position of the node following the last child element named one of
+/// `names`, or 0.
+fn position_after(el: &Element, names: &[&str]) -> usize {
+    el.nodes()
+        .enumerate()
+        .rfind(|(_, node)| matches!(node, Node::Element(child) if names.contains(&child.name())))
+        .map_or(0, |(index, _)| index + 1)
+}
+
+fn section_mut<'a>(data_stream: &'a mut Element, section: &str) -> &'a mut Element {
+    if data_stream.get_child(section, SCAP12_NS).is_none() {
+        let earlier = &SECTIONS[..SECTIONS.iter().position(|s| *s == section).unwrap()];
+        let index = position_after(data_stream, earlier);
+        insert_child_at(data_stream, index, Element::bare(section, SCAP12_NS));
+    }
+    data_stream.get_child_mut(section, SCAP12_NS).unwrap()
+}
+
+/// Maps `name` to the component-ref in the catalog of every checklist, so
+/// that check-content-refs using the file name resolve to the new
+/// component.
+fn map_in_catalogs(data_stream: &mut Element, name: &str, ref_id: &str, notes: &mut Vec<String>) {
+    let checklists = match data_stream.get_child_mut("checklists", SCAP12_NS) {
+        Some(checklists) => checklists,
+        None => return,
+    };
+    for checklist in checklists.children_mut() {
+        let checklist_id = checklist.attr("id").unwrap_or_default().to_string();
+        if checklist.get_child("catalog", CAT_NS).is_none() {
+            checklist.append_child(Element::bare("catalog", CAT_NS));
+        }
+        let catalog = checklist.get_child_mut("catalog", CAT_NS).unwrap();
+        if catalog
+            .children()
+            .any(|uri| uri.is("uri", CAT_NS) && uri.attr("name") == Some(name))
+        {
+            notes.push(format!(
+                "catalog of '{}' already maps '{}', left unchanged",
+                checklist_id, name
+            ));
+            continue;
+        }
+        catalog.append_child(
+            Element::builder("uri", CAT_NS)
+                .attr("name", name)
+                .attr("uri", format!("#{}", ref_id))
+                .build(),
+        );
+        notes.push(format!(
+            "mapped '{}' to '{}' in the catalog of '{}'",
+            name, ref_id, checklist_id
+        ));
+    }
+}
+
+/// Adds the content as a new component of the collection, referenced from
+/// the data stream with the given ID, or the first one. The IDs are derived
+/// from `name`, usually the file name of the content, which checklists can
+/// use to reference dictionaries and checks through their catalogs.
+pub fn add_component(
+    root: &mut Element,
+    content: Element,
+    name: &str,
+    data_stream: Option<&str>,
+) -> Result<Vec<String>, String> {
+    check_collection(root)?;
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(format!("'{}' can't be used in component IDs", name));
+    }
+    let section = section_for(&content);
+    let extended = section == "extended-components";
+    let namespace = collection_namespace(root);
+    let (wrapper, kind) = if extended {
+        ("extended-component", "ecomp")
+    } else {
+        ("component", "comp")
+    };
+    let component_id = format!("scap_{}_{}_{}", namespace, kind, name);
+    let ref_id = format!("scap_{}_cref_{}", namespace, name);
+    for id in [&component_id, &ref_id] {
+        if id_in_use(root, id) {
+            return Err(format!(
+                "'{}' is already used, replace the component instead",
+                id
+            ));
+        }
+    }
+
+    let mut notes = Vec::new();
+    let timestamp = current_timestamp();
+    let ds = root
+        .children_mut()
+        .filter(|child| child.is("data-stream", SCAP12_NS))
+        .find(|ds| data_stream.is_none_or(|id| ds.attr("id") == Some(id)))
+        .ok_or_else(|| match data_stream {
+            Some(id) => format!("There is no data stream '{}'", id),
+            None => String::from("The collection doesn't have any data stream"),
+        })?;
+    let ds_id = ds.attr("id").unwrap_or_default().to_string();
+    section_mut(ds, section).append_child(
+        Element::builder("component-ref", SCAP12_NS)
+            .attr("id", ref_id.as_str())
+            .attr("xlink:href", format!("#{}", component_id))
+            .build(),
+    );
+    notes.push(format!(
+        "added component-ref '{}' to the {} of data stream '{}'",
+        ref_id, section, ds_id
+    ));
+    if matches!(section, "dictionaries" | "checks") {
+        map_in_catalogs(ds, name, &ref_id, &mut notes);
+    }
+    ds.set_attr("timestamp", timestamp.as_str());
+
+    let mut preceding = vec!["data-stream", "component"];
+    if extended {
+        preceding.push("extended-component");
+    }
+    let index = position_after(root, &preceding);
+    let component = Element::builder(wrapper, SCAP12_NS)
+        .attr("id", component_id.as_str())
+        .attr("timestamp", timestamp)
+        .append(content)
+        .build();
+    insert_child_at(root, index, component);
+    notes.push(format!("added {} '{}'", wrapper, component_id));
+    Ok(notes)
+}
+
+/// Replaces the content of the component referenced by the component-ref
+/// with the new content, which must belong to the same list of the data
+/// stream. The timestamps of the component and of the data streams
+/// referencing it are regenerated.
+pub fn replace_component(
+    root: &mut Element,
+    ref_id: &str,
+    content: Element,
+) -> Result<Vec<String>, String> {
+    check_collection(root)?;
+    let (section, href) = root
+        .children()
+        .filter(|child| child.is("data-stream", SCAP12_NS))
+        .flat_map(|ds| ds.children())
+        .flat_map(|section| {
+            section
+                .children()
+                .map(move |component_ref| (section.name(), component_ref))
+        })
+        .find(|(_, component_ref)| component_ref.attr("id") == Some(ref_id))
+        .map(|(section, component_ref)| {
+            (
+                section.to_string(),
+                component_ref
+                    .attr("xlink:href")
+                    .unwrap_or_default()
+                    .to_string(),
+            )
+        })
+        .ok_or_else(|| format!("There is no component-ref '{}'", ref_id))?;
+    let component_id = match href.strip_prefix('#') {
+        Some(id) => id.to_string(),
+        None => {
+            return Err(format!(
+                "component-ref '{}' references the remote resource '{}', only local components can be replaced",
+                ref_id, href
+            ))
+        }
+    };
+    if section_for(&content) != section {
+        return Err(format!(
+            "'{}' content doesn't belong to the {} of the data stream, where component-ref '{}' is",
+            content.name(),
+            section,
+            ref_id
+        ));
+    }
+
+    let timestamp = current_timestamp();
+    let component = root
+        .children_mut()
+        .filter(|child| {
+            child.is("component", SCAP12_NS) || child.is("extended-component", SCAP12_NS)
+        })
+        .find(|child| child.attr("id") == Some(component_id.as_str()))
+        .ok_or_else(|| {
+            format!(
+                "component-ref '{}' doesn't point to a component of the collection",
+                ref_id
+            )
+        })?;
+    retain_children(component, |_| false);
+    component.append_child(content);
+    component.set_attr("timestamp", timestamp.as_str());
+    let mut notes = vec![format!("replaced the content of '{}'", component_id)];
+
+    for ds in root
+        .children_mut()
+        .filter(|child| child.is("data-stream", SCAP12_NS))
+    {
+        let references = ds
+            .children()
+            .flat_map(|section| section.children())
+            .any(|component_ref| component_ref.attr("xlink:href") == Some(href.as_str()));
+        if references {
+            ds.set_attr("timestamp", timestamp.as_str());
+            notes.push(format!(
+                "updated the timestamp of data stream '{}'",
+                ds.attr("id").unwrap_or_default()
+            ));
+        }
+    }
+    Ok(notes)
+}
+
+/// Base64 SHA-256 digest of the exclusive canonical form of the element of
+/// the collection, the `DigestValue` of a signature reference to it.
+pub fn digest(root: &Element, el: &Element) -> Result<String, String> {
+    let canonical = writer::canonicalize(root, el).map_err(|e| e.to_string())?;
+    Ok(encode_base64(&Sha256::digest(&canonical)))
+}
+
+/// Replaces the signatures of the collection by a signature template
+/// referencing every component and extended component with its digest,
+/// ready to be signed e.g. by `xmlsec1`. Existing signatures are removed,
+/// because the edited components don't match their digests anymore.
+pub fn add_signature_references(root: &mut Element) -> Result<Vec<String>, String> {
+    check_collection(root)?;
+    let mut notes = Vec::new();
+    for signature in root.children().filter(|c| dsig::is_signature(c)) {
+        notes.push(format!(
+            "removed signature '{}'",
+            signature.attr("Id").unwrap_or("without ID")
+        ));
+    }
+    retain_children(root, |child| !dsig::is_signature(child));
+    let mut digests = Vec::new();
+    for component in root.children().filter(|child| {
+        child.is("component", SCAP12_NS) || child.is("extended-component", SCAP12_NS)
+    }) {
+        let id = component.attr("id").unwrap_or_default().to_string();
+        digests.push((id, digest(root, component)?));
+    }
+    for (id, digest) in digests.iter() {
+        notes.push(format!("referenced '{}' with digest {}", id, digest));
+    }
+    root.append_child(dsig::signature_template(&digests));
+    Ok(notes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sds::DataStreamCollection;
+    use crate::utils::parse_date_time;
+
+    fn collection() -> Element {
+        format!(
+            "<data-stream-collection xmlns=\"{ns}\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             xmlns:cat=\"{cat}\" id=\"scap_org.example_collection_from_xccdf_b.xml\" \
+             schematron-version=\"1.3\">\
+             <data-stream id=\"d\" use-case=\"OTHER\" scap-version=\"1.3\">\
+             <checklists><component-ref id=\"cref-xccdf\" xlink:href=\"#xccdf\"/></checklists>\
+             </data-stream>\
+             <component id=\"xccdf\" timestamp=\"2022-01-01T00:00:00\">\
+             <Benchmark xmlns=\"{xccdf}\" id=\"xccdf_org.example_benchmark_b\">\
+             <status>draft</status><version>1</version></Benchmark></component>\
+             <extended-component id=\"ext\" timestamp=\"2022-01-01T00:00:00\"><foo xmlns=\"urn:example\"/>\
+             </extended-component></data-stream-collection>",
+            ns = SCAP12_NS,
+            cat = CAT_NS,
+            xccdf = XCCDF12_NS
+        )
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_add_signature_references() {
+        let mut root = collection();
+        add_signature_references(&mut root).unwrap();
+        let notes = add_signature_references(&mut root).unwrap();
+        assert_eq!(notes[0], "removed signature 'without ID'");
+        let signatures: Vec<&Element> = root.children().filter(|c| dsig::is_signature(c)).collect();
+        assert_eq!(signatures.len(), 1);
+        assert!(root.children().last().is_some_and(dsig::is_signature));
+
+        let signature = dsig::Signature::from_xml(signatures[0]).unwrap();
+        let uris: Vec<&str> = signature
+            .references
+            .iter()
+            .map(|r| r.uri.as_ref())
+            .collect();
+        assert_eq!(uris, vec!["#xccdf", "#ext"]);
+        let ext = root.get_child("extended-component", SCAP12_NS).unwrap();
+        assert_eq!(
+            signature.references[1].digest_value,
+            digest(&root, ext).unwrap()
+        );
+        assert_eq!(&*signature.references[1].digest_method, dsig::SHA256);
+    }
+
+    fn oval(version: &str) -> Element {
+        format!(
+            "<oval_definitions xmlns=\"{}\"><generator><schema_version>{}</schema_version>\
+             </generator></oval_definitions>",
+            OVAL_DEF_NS, version
+        )
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_add_and_replace_component() {
+        let mut root = collection();
+        add_component(&mut root, oval("5.11"), "oval.xml", None).unwrap();
+        assert!(add_component(&mut root, oval("5.11"), "oval.xml", None).is_err());
+
+        let names: Vec<&str> = root.children().map(|c| c.name()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "data-stream",
+                "component",
+                "component",
+                "extended-component"
+            ]
+        );
+        let sections: Vec<&str> = root
+            .children()
+            .next()
+            .unwrap()
+            .children()
+            .map(|c| c.name())
+            .collect();
+        assert_eq!(sections, vec!["checklists", "checks"]);
+
+        let collection = DataStreamCollection::from_xml(&root).unwrap();
+        let checklist = &collection.data_streams[0].checklists[0];
+        let href = checklist
+            .catalog
+            .as_ref()
+            .unwrap()
+            .resolve("oval.xml")
+            .unwrap();
+        assert_eq!(href, "#scap_org.example_cref_oval.xml");
+        assert!(collection
+            .component("scap_org.example_comp_oval.xml")
+            .is_some());
+
+        let ref_id = "scap_org.example_cref_oval.xml";
+        replace_component(&mut root, ref_id, oval("5.11.2")).unwrap();
+        let collection = DataStreamCollection::from_xml(&root).unwrap();
+        let component = collection
+            .component("scap_org.example_comp_oval.xml")
+            .unwrap();
+        assert_eq!(component.schema_version.as_deref(), Some("5.11.2"));
+        assert_ne!(
+            component.timestamp(),
+            parse_date_time("2022-01-01T00:00:00").ok()
+        );
+        assert!(replace_component(&mut root, "cref-xccdf", oval("5.11")).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use crate::matrix::csv_field;
+use crate::utils::string_enum;
+use crate::xccdf::{Benchmark, ResolvedProfile, Rule};
+
+string_enum!(
+    /// Compliance standard whose controls the rule references point to.
+    Standard {
+        Nist80053 => "nist-800-53",
+        Cis => "cis",
+        PciDss => "pci-dss",
+    }
+);
+
+impl Standard {
+    /// Recognizes the standard by the `href` of a reference.
+    pub fn from_href(href: &str) -> Option<Standard> {
+        let href = href.to_lowercase();
+        if href.contains("800-53") {
+            Some(Standard::Nist80053)
+        } else if href.contains("cisecurity.org") {
+            Some(Standard::Cis)
+        } else if href.contains("pcisecuritystandards.org") || href.contains("pci_dss") {
+            Some(Standard::PciDss)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ControlRule {
+    pub id: String,
+    pub title: String,
+    /// Texts of the idents, e.g. CCEs
+    pub idents: Vec<String>,
+}
+
+/// Rules referencing a control of the standard.
+#[derive(Debug, Serialize)]
+pub struct Control {
+    pub control: String,
+    pub rules: Vec<ControlRule>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ControlMapping {
+    pub standard: String,
+    pub controls: Vec<Control>,
+}
+
+/// Controls of the standard referenced by the rule. A reference can list
+/// more controls separated by commas, e.g. `AC-2(5),CM-6(a)`.
+fn rule_controls(rule: &Rule, standard: Standard) -> Vec<String> {
+    rule.references
+        .iter()
+        .filter(|r| r.href().and_then(Standard::from_href) == Some(standard))
+        .flat_map(|r| r.text().split(','))
+        .map(|control| control.trim().to_string())
+        .filter(|control| !control.is_empty())
+        .collect()
+}
+
+/// Maps the controls of the standard to the rules referencing them, only
+/// the rules selected by the profile if given. The controls are sorted.
+pub fn control_mapping(
+    benchmark: &Benchmark,
+    profile: Option<&ResolvedProfile>,
+    standard: Standard,
+) -> ControlMapping {
+    let rules = match profile {
+        Some(profile) => benchmark.selected_rules(profile),
+        None => benchmark.all_rules(),
+    };
+    let mut controls: BTreeMap<String, Vec<ControlRule>> = BTreeMap::new();
+    for rule in rules {
+        for control in rule_controls(rule, standard) {
+            let rules = controls.entry(control).or_default();
+            if rules.iter().any(|r| *r.id == *rule.id) {
+                continue;
+            }
+            rules.push(ControlRule {
+                id: rule.id.to_string(),
+                title: rule
+                    .titles
+                    .first()
+                    .map(|t| t.title.clone())
+                    .unwrap_or_default(),
+                idents: rule.idents.iter().map(|i| i.text().to_string()).collect(),
+            });
+        }
+    }
+    ControlMapping {
+        standard: standard.to_string(),
+        controls: controls
+            .into_iter()
+            .map(|(control, rules)| Control { control, rules })
+            .collect(),
+    }
+}
+
+impl ControlMapping {
+    /// One row per control and rule.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("control,rule,title,idents\n");
+        for control in self.controls.iter() {
+            for rule in control.rules.iter() {
+                let fields = [
+                    csv_field(&control.control),
+                    csv_field(&rule.id),
+                    csv_field(&rule.title),
+                    csv_field(&rule.idents.join(" ")),
+                ];
+                csv.push_str(&fields.join(","));
+                csv.push('\n');
+            }
+        }
+        csv
+    }
+
+    pub fn write_text<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "Standard: {}", self.standard)?;
+        for control in self.controls.iter() {
+            writeln!(out, "{}", control.control)?;
+            for rule in control.rules.iter() {
+                match rule.idents.is_empty() {
+                    true => writeln!(out, "  {} {}", rule.id, rule.title)?,
+                    false => writeln!(
+                        out,
+                        "  {} [{}] {}",
+                        rule.id,
+                        rule.idents.join(", "),
+                        rule.title
+                    )?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xccdf::XCCDF12_NS;
+    use minidom::Element;
+
+    #[test]
+    fn test_control_mapping() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Rule id=\"r1\"><title>R1</title><ident system=\"https://ncp.nist.gov/cce\">CCE-1</ident>\
+             <reference href=\"https://nvlpubs.nist.gov/NIST.SP.800-53r4.pdf\">CM-6(a), AC-2</reference>\
+             <reference href=\"https://www.cisecurity.org/benchmark/rhel/\">1.1.2</reference></Rule>\
+             <Rule id=\"r2\"><title>R2, \"quoted\"</title>\
+             <reference href=\"https://nvlpubs.nist.gov/NIST.SP.800-53r4.pdf\">AC-2</reference></Rule>\
+             </Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let mapping = control_mapping(&benchmark, None, Standard::Nist80053);
+        let controls: Vec<&str> = mapping
+            .controls
+            .iter()
+            .map(|c| c.control.as_str())
+            .collect();
+        assert_eq!(controls, vec!["AC-2", "CM-6(a)"]);
+        assert_eq!(
+            mapping.to_csv(),
+            "control,rule,title,idents\nAC-2,r1,R1,CCE-1\nAC-2,r2,\"R2, \"\"quoted\"\"\",\n\
+             CM-6(a),r1,R1,CCE-1\n"
+        );
+        let mapping = control_mapping(&benchmark, None, Standard::Cis);
+        assert_eq!(mapping.controls[0].control, "1.1.2");
+        assert!(control_mapping(&benchmark, None, Standard::PciDss)
+            .controls
+            .is_empty());
+    }
+}
@@ -0,0 +1,451 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Part of a CPE name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Part {
+    Application,
+    OperatingSystem,
+    Hardware,
+}
+
+impl Part {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Part::Application => "a",
+            Part::OperatingSystem => "o",
+            Part::Hardware => "h",
+        }
+    }
+}
+
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Part, String> {
+        match s {
+            "a" => Ok(Part::Application),
+            "o" => Ok(Part::OperatingSystem),
+            "h" => Ok(Part::Hardware),
+            _ => Err(format!("invalid part '{}', expected 'a', 'o' or 'h'", s)),
+        }
+    }
+}
+
+/// Value of a well-formed name (WFN) attribute.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Value {
+    /// Matches any value, an empty component or `*`
+    #[default]
+    Any,
+    /// The attribute doesn't apply, `-`
+    NotApplicable,
+    Value(String),
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Whether the value, used as a pattern, matches the target value.
+    fn matches(&self, target: &Value) -> bool {
+        match (self, target) {
+            (Value::Any, _) => true,
+            (Value::NotApplicable, Value::NotApplicable) => true,
+            (Value::Value(a), Value::Value(b)) => a.eq_ignore_ascii_case(b),
+            _ => false,
+        }
+    }
+}
+
+/// CPE name as a well-formed name, parsed from a CPE 2.2 URI
+/// (`cpe:/o:redhat:enterprise_linux:9`) or a CPE 2.3 formatted string
+/// (`cpe:2.3:o:redhat:enterprise_linux:9:*:*:*:*:*:*:*`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Cpe {
+    /// `None` if the part is ANY
+    pub(crate) part: Option<Part>,
+    pub(crate) vendor: Value,
+    pub(crate) product: Value,
+    pub(crate) version: Value,
+    pub(crate) update: Value,
+    pub(crate) edition: Value,
+    pub(crate) language: Value,
+    pub(crate) sw_edition: Value,
+    pub(crate) target_sw: Value,
+    pub(crate) target_hw: Value,
+    pub(crate) other: Value,
+}
+
+const URI_PREFIX: &str = "cpe:/";
+const FS_PREFIX: &str = "cpe:2.3:";
+
+/// Decodes a component of a CPE 2.2 URI.
+fn uri_value(component: &str) -> Result<Value, String> {
+    match component {
+        "" => return Ok(Value::Any),
+        "-" => return Ok(Value::NotApplicable),
+        _ => (),
+    }
+    let mut value = String::new();
+    let mut chars = component.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '%' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                match u8::from_str_radix(&hex, 16) {
+                    Ok(byte) if hex.len() == 2 && byte.is_ascii() => value.push(byte as char),
+                    _ => return Err(format!("invalid percent-encoding in '{}'", component)),
+                }
+            }
+            c if c.is_ascii_alphanumeric() || "._-~".contains(c) => value.push(c),
+            c => return Err(format!("invalid character '{}' in '{}'", c, component)),
+        }
+    }
+    Ok(Value::Value(value))
+}
+
+/// Splits a CPE 2.3 formatted string at the colons that aren't escaped.
+fn split_formatted(name: &str) -> Vec<&str> {
+    let mut components = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in name.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            ':' => {
+                components.push(&name[start..i]);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    components.push(&name[start..]);
+    components
+}
+
+/// Decodes a component of a CPE 2.3 formatted string.
+fn formatted_value(component: &str) -> Result<Value, String> {
+    match component {
+        "*" => return Ok(Value::Any),
+        "-" => return Ok(Value::NotApplicable),
+        "" => return Err(String::from("empty component")),
+        _ => (),
+    }
+    let mut value = String::new();
+    let mut chars = component.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(c) => value.push(c),
+                None => return Err(format!("trailing backslash in '{}'", component)),
+            },
+            c if c.is_ascii_graphic() => value.push(c),
+            c => return Err(format!("invalid character '{}' in '{}'", c, component)),
+        }
+    }
+    Ok(Value::Value(value))
+}
+
+fn part(value: Value) -> Result<Option<Part>, String> {
+    match value {
+        Value::Any => Ok(None),
+        Value::NotApplicable => Err(String::from("the part can't be '-'")),
+        Value::Value(part) => part.parse().map(Some),
+    }
+}
+
+impl Cpe {
+    fn parse_uri(name: &str) -> Result<Cpe, String> {
+        let components: Vec<&str> = name[URI_PREFIX.len()..].split(':').collect();
+        if components.len() > 7 {
+            return Err(format!("'{}' has more than 7 components", name));
+        }
+        let mut values = components
+            .iter()
+            .map(|c| uri_value(c))
+            .collect::<Result<Vec<Value>, String>>()?;
+        values.resize(7, Value::Any);
+        let mut cpe = Cpe {
+            part: part(values[0].clone())?,
+            vendor: values[1].clone(),
+            product: values[2].clone(),
+            version: values[3].clone(),
+            update: values[4].clone(),
+            edition: values[5].clone(),
+            language: values[6].clone(),
+            ..Cpe::default()
+        };
+        // The extended attributes of CPE 2.3 are packed in the edition as
+        // `~edition~sw_edition~target_sw~target_hw~other`.
+        if let Some(packed) = components.get(5).and_then(|e| e.strip_prefix('~')) {
+            let packed: Vec<&str> = packed.split('~').collect();
+            if packed.len() != 5 {
+                return Err(format!("invalid packed edition in '{}'", name));
+            }
+            cpe.edition = uri_value(packed[0])?;
+            cpe.sw_edition = uri_value(packed[1])?;
+            cpe.target_sw = uri_value(packed[2])?;
+            cpe.target_hw = uri_value(packed[3])?;
+            cpe.other = uri_value(packed[4])?;
+        }
+        Ok(cpe)
+    }
+
+    fn parse_formatted(name: &str) -> Result<Cpe, String> {
+        let components = split_formatted(&name[FS_PREFIX.len()..]);
+        if components.len() != 11 {
+            return Err(format!(
+                "'{}' has {} components instead of 11",
+                name,
+                components.len()
+            ));
+        }
+        let values = components
+            .iter()
+            .map(|c| formatted_value(c))
+            .collect::<Result<Vec<Value>, String>>()?;
+        let mut values = values.into_iter();
+        let mut next = || values.next().unwrap_or_default();
+        Ok(Cpe {
+            part: part(next())?,
+            vendor: next(),
+            product: next(),
+            version: next(),
+            update: next(),
+            edition: next(),
+            language: next(),
+            sw_edition: next(),
+            target_sw: next(),
+            target_hw: next(),
+            other: next(),
+        })
+    }
+
+    /// Whether the string looks like a CPE name of either binding.
+    pub fn is_cpe_name(name: &str) -> bool {
+        name.starts_with(URI_PREFIX) || name.starts_with(FS_PREFIX)
+    }
+
+    pub fn part(&self) -> Option<Part> {
+        self.part
+    }
+
+    pub fn vendor(&self) -> &Value {
+        &self.vendor
+    }
+
+    pub fn product(&self) -> &Value {
+        &self.product
+    }
+
+    pub fn version(&self) -> &Value {
+        &self.version
+    }
+
+    pub fn update(&self) -> &Value {
+        &self.update
+    }
+
+    pub fn edition(&self) -> &Value {
+        &self.edition
+    }
+
+    pub fn language(&self) -> &Value {
+        &self.language
+    }
+
+    pub fn sw_edition(&self) -> &Value {
+        &self.sw_edition
+    }
+
+    pub fn target_sw(&self) -> &Value {
+        &self.target_sw
+    }
+
+    pub fn target_hw(&self) -> &Value {
+        &self.target_hw
+    }
+
+    pub fn other(&self) -> &Value {
+        &self.other
+    }
+
+    fn attributes(&self) -> [&Value; 10] {
+        [
+            &self.vendor,
+            &self.product,
+            &self.version,
+            &self.update,
+            &self.edition,
+            &self.language,
+            &self.sw_edition,
+            &self.target_sw,
+            &self.target_hw,
+            &self.other,
+        ]
+    }
+
+    /// Whether the name, used as a pattern, matches the target name, e.g.
+    /// `cpe:/o:redhat:enterprise_linux` matches
+    /// `cpe:/o:redhat:enterprise_linux:8`. Values are compared
+    /// case-insensitively.
+    pub fn matches(&self, target: &Cpe) -> bool {
+        (self.part.is_none() || self.part == target.part)
+            && self
+                .attributes()
+                .iter()
+                .zip(target.attributes().iter())
+                .all(|(a, b)| a.matches(b))
+    }
+
+    /// Binds the name to a CPE 2.2 URI, the trailing ANY components are
+    /// left out.
+    pub fn to_uri(&self) -> String {
+        let encode = |value: &Value| match value {
+            Value::Any => String::new(),
+            Value::NotApplicable => String::from("-"),
+            Value::Value(value) => value
+                .chars()
+                .map(|c| match c.is_ascii_alphanumeric() || "._-~".contains(c) {
+                    true => c.to_string(),
+                    false => format!("%{:02x}", c as u32),
+                })
+                .collect(),
+        };
+        let extended = [
+            &self.sw_edition,
+            &self.target_sw,
+            &self.target_hw,
+            &self.other,
+        ];
+        let edition = match extended.iter().all(|v| **v == Value::Any) {
+            true => encode(&self.edition),
+            false => format!(
+                "~{}~{}~{}~{}~{}",
+                encode(&self.edition),
+                encode(&self.sw_edition),
+                encode(&self.target_sw),
+                encode(&self.target_hw),
+                encode(&self.other)
+            ),
+        };
+        let mut components = vec![
+            self.part
+                .map(|p| p.as_str().to_string())
+                .unwrap_or_default(),
+            encode(&self.vendor),
+            encode(&self.product),
+            encode(&self.version),
+            encode(&self.update),
+            edition,
+            encode(&self.language),
+        ];
+        while components.last().is_some_and(|c| c.is_empty()) {
+            components.pop();
+        }
+        format!("{}{}", URI_PREFIX, components.join(":"))
+    }
+
+    /// Binds the name to a CPE 2.3 formatted string.
+    pub fn to_formatted_string(&self) -> String {
+        let encode = |value: &Value| match value {
+            Value::Any => String::from("*"),
+            Value::NotApplicable => String::from("-"),
+            Value::Value(value) => value
+                .chars()
+                .map(|c| match c.is_ascii_alphanumeric() || "._-".contains(c) {
+                    true => c.to_string(),
+                    false => format!("\\{}", c),
+                })
+                .collect(),
+        };
+        let part = self.part.map_or("*", |p| p.as_str());
+        let mut components = vec![part.to_string()];
+        components.extend(self.attributes().iter().map(|v| encode(v)));
+        format!("{}{}", FS_PREFIX, components.join(":"))
+    }
+}
+
+impl FromStr for Cpe {
+    type Err = String;
+
+    /// Parses a CPE 2.3 formatted string or a CPE 2.2 URI.
+    fn from_str(name: &str) -> Result<Cpe, String> {
+        if name.starts_with(FS_PREFIX) {
+            Cpe::parse_formatted(name)
+        } else if name.starts_with(URI_PREFIX) {
+            Cpe::parse_uri(name)
+        } else {
+            Err(format!(
+                "'{}' doesn't start with '{}' or '{}'",
+                name, URI_PREFIX, FS_PREFIX
+            ))
+        }
+    }
+}
+
+impl fmt::Display for Cpe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_formatted_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpe() {
+        let uri: Cpe = "cpe:/o:redhat:enterprise_linux:9".parse().unwrap();
+        assert_eq!(uri.part(), Some(Part::OperatingSystem));
+        assert_eq!(uri.product().as_str(), Some("enterprise_linux"));
+        assert_eq!(uri.update(), &Value::Any);
+        let formatted: Cpe = "cpe:2.3:o:redhat:enterprise_linux:9:*:*:*:*:*:*:*"
+            .parse()
+            .unwrap();
+        assert_eq!(uri, formatted);
+        assert_eq!(formatted.to_uri(), "cpe:/o:redhat:enterprise_linux:9");
+        assert_eq!(
+            uri.to_string(),
+            "cpe:2.3:o:redhat:enterprise_linux:9:*:*:*:*:*:*:*"
+        );
+
+        let packed: Cpe = "cpe:/a:vendor:my%21app:1.0:-:~-~pro~linux~x64~:en"
+            .parse()
+            .unwrap();
+        assert_eq!(packed.product().as_str(), Some("my!app"));
+        assert_eq!(packed.edition(), &Value::NotApplicable);
+        assert_eq!(packed.target_hw().as_str(), Some("x64"));
+        assert_eq!(
+            packed.to_formatted_string(),
+            "cpe:2.3:a:vendor:my\\!app:1.0:-:-:en:pro:linux:x64:*"
+        );
+        assert_eq!(
+            packed.to_uri(),
+            "cpe:/a:vendor:my%21app:1.0:-:~-~pro~linux~x64~:en"
+        );
+        let escaped: Cpe = "cpe:2.3:a:vendor:a\\:b:*:*:*:*:*:*:*:*".parse().unwrap();
+        assert_eq!(escaped.product().as_str(), Some("a:b"));
+
+        let base: Cpe = "cpe:/o:redhat:enterprise_linux".parse().unwrap();
+        assert!(base.matches(&uri));
+        assert!(!uri.matches(&base));
+
+        for invalid in [
+            "cpe:/x:redhat",
+            "cpe:/o:red hat",
+            "cpe:/o:redhat:%zz",
+            "cpe:2.3:o:redhat:enterprise_linux:9",
+            "cpe:2.3:o:redhat:enterprise_linux:9:*:*:*:*:*:*:*\\",
+            "redhat",
+        ] {
+            assert!(invalid.parse::<Cpe>().is_err(), "{}", invalid);
+        }
+    }
+}
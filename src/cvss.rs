@@ -0,0 +1,234 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::utils::string_enum;
+
+string_enum!(
+    CvssVersion {
+        V2 => "2.0",
+        V3_0 => "3.0",
+        V3_1 => "3.1",
+    }
+);
+
+string_enum!(
+    /// Qualitative severity of a base score, ordered from the lowest.
+    #[derive(PartialOrd, Ord)]
+    CvssSeverity {
+        None => "none",
+        Low => "low",
+        Medium => "medium",
+        High => "high",
+        Critical => "critical",
+    }
+);
+
+/// Base metrics with the weights of their values.
+type Metrics = &'static [(&'static str, &'static [(&'static str, f64)])];
+
+const V2_METRICS: Metrics = &[
+    ("AV", &[("L", 0.395), ("A", 0.646), ("N", 1.0)]),
+    ("AC", &[("H", 0.35), ("M", 0.61), ("L", 0.71)]),
+    ("Au", &[("M", 0.45), ("S", 0.56), ("N", 0.704)]),
+    ("C", &[("N", 0.0), ("P", 0.275), ("C", 0.66)]),
+    ("I", &[("N", 0.0), ("P", 0.275), ("C", 0.66)]),
+    ("A", &[("N", 0.0), ("P", 0.275), ("C", 0.66)]),
+];
+
+/// The weights of PR are those of an unchanged scope.
+const V3_METRICS: Metrics = &[
+    ("AV", &[("N", 0.85), ("A", 0.62), ("L", 0.55), ("P", 0.2)]),
+    ("AC", &[("L", 0.77), ("H", 0.44)]),
+    ("PR", &[("N", 0.85), ("L", 0.62), ("H", 0.27)]),
+    ("UI", &[("N", 0.85), ("R", 0.62)]),
+    ("S", &[("U", 0.0), ("C", 0.0)]),
+    ("C", &[("H", 0.56), ("L", 0.22), ("N", 0.0)]),
+    ("I", &[("H", 0.56), ("L", 0.22), ("N", 0.0)]),
+    ("A", &[("H", 0.56), ("L", 0.22), ("N", 0.0)]),
+];
+
+/// Weights of the base metrics of the vector. Temporal and environmental
+/// metrics are allowed but ignored.
+fn weights<'a>(
+    metrics: &HashMap<&'a str, &'a str>,
+    table: Metrics,
+) -> Result<HashMap<&'static str, (&'a str, f64)>, String> {
+    let mut weights = HashMap::new();
+    for (name, values) in table.iter() {
+        let value = metrics
+            .get(name)
+            .ok_or_else(|| format!("missing base metric '{}'", name))?;
+        let weight = values
+            .iter()
+            .find(|(v, _)| v == value)
+            .map(|(_, w)| *w)
+            .ok_or_else(|| format!("invalid value '{}' of metric '{}'", value, name))?;
+        weights.insert(*name, (*value, weight));
+    }
+    Ok(weights)
+}
+
+/// Rounds up to one decimal as defined by CVSS v3.1.
+fn round_up(score: f64) -> f64 {
+    let int = (score * 100000.0).round() as i64;
+    match int % 10000 {
+        0 => int as f64 / 100000.0,
+        _ => (int / 10000 + 1) as f64 / 10.0,
+    }
+}
+
+fn v2_score(weights: &HashMap<&str, (&str, f64)>) -> f64 {
+    let w = |name: &str| weights[name].1;
+    let impact = 10.41 * (1.0 - (1.0 - w("C")) * (1.0 - w("I")) * (1.0 - w("A")));
+    let exploitability = 20.0 * w("AV") * w("AC") * w("Au");
+    let f = if impact == 0.0 { 0.0 } else { 1.176 };
+    ((0.6 * impact + 0.4 * exploitability - 1.5) * f * 10.0).round() / 10.0
+}
+
+fn v3_score(weights: &HashMap<&str, (&str, f64)>) -> f64 {
+    let w = |name: &str| weights[name].1;
+    let changed = weights["S"].0 == "C";
+    let privileges = match (weights["PR"].0, changed) {
+        ("L", true) => 0.68,
+        ("H", true) => 0.5,
+        _ => w("PR"),
+    };
+    let iss = 1.0 - (1.0 - w("C")) * (1.0 - w("I")) * (1.0 - w("A"));
+    let impact = match changed {
+        true => 7.52 * (iss - 0.029) - 3.25 * (iss - 0.02).powi(15),
+        false => 6.42 * iss,
+    };
+    let exploitability = 8.22 * w("AV") * w("AC") * privileges * w("UI");
+    if impact <= 0.0 {
+        0.0
+    } else if changed {
+        round_up((1.08 * (impact + exploitability)).min(10.0))
+    } else {
+        round_up((impact + exploitability).min(10.0))
+    }
+}
+
+/// CVSS base vector and its score, parsed from a vector such as
+/// `CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H` or `AV:N/AC:L/Au:N/C:P/I:P/A:P`
+/// (v2), optionally prefixed by the score like in OVAL advisories
+/// (`9.8/CVSS:3.1/...`). The score is computed if not given.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Cvss {
+    pub(crate) version: CvssVersion,
+    pub(crate) vector: String,
+    pub(crate) base_score: f64,
+}
+
+impl Cvss {
+    pub fn version(&self) -> CvssVersion {
+        self.version
+    }
+
+    pub fn vector(&self) -> &str {
+        &self.vector
+    }
+
+    pub fn base_score(&self) -> f64 {
+        self.base_score
+    }
+
+    /// Severity of the base score, v2 scores have no none and critical
+    /// ratings.
+    pub fn severity(&self) -> CvssSeverity {
+        let score = self.base_score;
+        match self.version {
+            CvssVersion::V2 if score < 4.0 => CvssSeverity::Low,
+            CvssVersion::V2 if score < 7.0 => CvssSeverity::Medium,
+            CvssVersion::V2 => CvssSeverity::High,
+            _ if score == 0.0 => CvssSeverity::None,
+            _ if score < 4.0 => CvssSeverity::Low,
+            _ if score < 7.0 => CvssSeverity::Medium,
+            _ if score < 9.0 => CvssSeverity::High,
+            _ => CvssSeverity::Critical,
+        }
+    }
+}
+
+impl FromStr for Cvss {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Cvss, String> {
+        let s = s.trim();
+        let (score, vector) = match s.split_once('/') {
+            Some((score, vector)) if score.parse::<f64>().is_ok() => {
+                (score.parse::<f64>().ok(), vector)
+            }
+            _ => (None, s),
+        };
+        let vector = vector.trim_start_matches('(').trim_end_matches(')');
+        let (version, metrics) = match vector.split_once('/') {
+            Some(("CVSS:3.0", metrics)) => (CvssVersion::V3_0, metrics),
+            Some(("CVSS:3.1", metrics)) => (CvssVersion::V3_1, metrics),
+            Some((prefix, _)) if prefix.starts_with("CVSS:") => {
+                return Err(format!("unsupported CVSS version '{}'", prefix))
+            }
+            _ => (CvssVersion::V2, vector),
+        };
+        let mut given = HashMap::new();
+        for metric in metrics.split('/') {
+            let (name, value) = metric
+                .split_once(':')
+                .ok_or_else(|| format!("invalid metric '{}'", metric))?;
+            if given.insert(name, value).is_some() {
+                return Err(format!("metric '{}' is given more than once", name));
+            }
+        }
+        let computed = match version {
+            CvssVersion::V2 => v2_score(&weights(&given, V2_METRICS)?),
+            _ => v3_score(&weights(&given, V3_METRICS)?),
+        };
+        if let Some(score) = score.filter(|s| !(0.0..=10.0).contains(s)) {
+            return Err(format!("score {} is out of range", score));
+        }
+        Ok(Cvss {
+            version,
+            vector: vector.to_string(),
+            base_score: score.unwrap_or(computed),
+        })
+    }
+}
+
+impl fmt::Display for Cvss {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.1}/{}", self.base_score, self.vector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_scores() {
+        let score = |vector: &str| vector.parse::<Cvss>().unwrap().base_score();
+        assert_eq!(score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"), 9.8);
+        assert_eq!(score("CVSS:3.1/AV:N/AC:L/PR:L/UI:N/S:C/C:H/I:H/A:H"), 9.9);
+        assert_eq!(score("CVSS:3.0/AV:L/AC:H/PR:H/UI:R/S:U/C:L/I:N/A:N"), 1.8);
+        assert_eq!(score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:N"), 0.0);
+        assert_eq!(score("AV:N/AC:L/Au:N/C:P/I:P/A:P"), 7.5);
+        assert_eq!(score("(AV:N/AC:M/Au:N/C:C/I:C/A:C)"), 9.3);
+
+        let cvss: Cvss = "8.1/CVSS:3.1/AV:N/AC:H/PR:N/UI:N/S:U/C:H/I:H/A:H/E:P"
+            .parse()
+            .unwrap();
+        assert_eq!(cvss.version(), CvssVersion::V3_1);
+        assert_eq!(cvss.severity(), CvssSeverity::High);
+        assert_eq!(
+            cvss.to_string(),
+            "8.1/CVSS:3.1/AV:N/AC:H/PR:N/UI:N/S:U/C:H/I:H/A:H/E:P"
+        );
+        assert!(CvssSeverity::Critical > CvssSeverity::High);
+        assert!("CVSS:3.1/AV:N/AC:L".parse::<Cvss>().is_err());
+        assert!("CVSS:3.1/AV:X/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"
+            .parse::<Cvss>()
+            .is_err());
+        assert!("CVSS:4.0/AV:N".parse::<Cvss>().is_err());
+    }
+}
@@ -0,0 +1,75 @@
+use minidom::Element;
+use std::sync::Arc;
+
+use crate::options::ParseOptions;
+use crate::utils::{get_attr, get_text};
+use crate::writer::DC_NS;
+
+pub const DCTERMS_NS: &str = "http://purl.org/dc/terms/";
+
+/// Dublin Core element such as `dc:title` or `dc:identifier`, or a DCMI
+/// term such as `dcterms:modified`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DcElement {
+    /// Local name of the element, e.g. `publisher`
+    pub(crate) name: String,
+    /// From the DCMI terms namespace instead of the element set
+    pub(crate) term: bool,
+    pub(crate) text: String,
+    pub(crate) lang: Option<Arc<str>>,
+    /// Encoding scheme given by `xsi:type`, e.g. `dcterms:W3CDTF`
+    pub(crate) scheme: Option<Arc<str>>,
+}
+
+impl DcElement {
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> DcElement {
+        DcElement {
+            name: el.name().to_string(),
+            term: el.has_ns(DCTERMS_NS),
+            text: get_text(el, opts),
+            lang: get_attr(el, "xml:lang"),
+            scheme: get_attr(el, "xsi:type"),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_term(&self) -> bool {
+        self.term
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn lang(&self) -> Option<&str> {
+        self.lang.as_deref()
+    }
+
+    pub fn scheme(&self) -> Option<&str> {
+        self.scheme.as_deref()
+    }
+}
+
+/// Whether the element is in the Dublin Core element set or terms namespace.
+pub fn is_dublin_core(el: &Element) -> bool {
+    el.has_ns(DC_NS) || el.has_ns(DCTERMS_NS)
+}
+
+/// Dublin Core children of an element, other children are skipped.
+pub fn parse_children(el: &Element, opts: &ParseOptions) -> Vec<DcElement> {
+    el.children()
+        .filter(|child| is_dublin_core(child))
+        .map(|child| DcElement::from_xml(child, opts))
+        .collect()
+}
+
+/// Text of the first element with the local name.
+pub fn first<'a>(elements: &'a [DcElement], name: &str) -> Option<&'a str> {
+    elements
+        .iter()
+        .find(|e| e.name == name)
+        .map(|e| e.text.as_str())
+}
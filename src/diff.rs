@@ -0,0 +1,593 @@
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+
+use crate::scoring::{self, ScoringModel};
+use crate::xccdf::{Benchmark, Profile, Rule, TestResult};
+
+/// Whether the result counts as passed when looking for regressions.
+fn passed(result: &str) -> bool {
+    matches!(result, "pass" | "fixed")
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResultChange {
+    pub(crate) rule: String,
+    pub(crate) title: String,
+    pub(crate) old_result: String,
+    pub(crate) new_result: String,
+}
+
+impl ResultChange {
+    pub fn rule(&self) -> &str {
+        &self.rule
+    }
+
+    pub fn old_result(&self) -> &str {
+        &self.old_result
+    }
+
+    pub fn new_result(&self) -> &str {
+        &self.new_result
+    }
+
+    /// A passing rule that doesn't pass anymore.
+    pub fn is_regression(&self) -> bool {
+        passed(&self.old_result) && !passed(&self.new_result)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RuleResultRow {
+    pub(crate) rule: String,
+    pub(crate) title: String,
+    pub(crate) result: String,
+}
+
+/// Differences between the rule results of two scans.
+#[derive(Debug, Serialize)]
+pub struct ResultsDiff {
+    pub(crate) old_test_result: String,
+    pub(crate) new_test_result: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) old_profile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) new_profile: Option<String>,
+    /// Rules evaluated in both scans with a different result
+    pub(crate) changed: Vec<ResultChange>,
+    /// Rules evaluated only in the new scan
+    pub(crate) added: Vec<RuleResultRow>,
+    /// Rules evaluated only in the old scan
+    pub(crate) removed: Vec<RuleResultRow>,
+    /// Scores in the default scoring model
+    pub(crate) old_score: f64,
+    pub(crate) new_score: f64,
+}
+
+impl ResultsDiff {
+    pub fn changed(&self) -> &[ResultChange] {
+        &self.changed
+    }
+
+    pub fn added(&self) -> &[RuleResultRow] {
+        &self.added
+    }
+
+    pub fn removed(&self) -> &[RuleResultRow] {
+        &self.removed
+    }
+
+    pub fn score_delta(&self) -> f64 {
+        self.new_score - self.old_score
+    }
+
+    pub fn regressions(&self) -> impl Iterator<Item = &ResultChange> {
+        self.changed.iter().filter(|c| c.is_regression())
+    }
+
+    pub fn write_text<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(
+            out,
+            "TestResults: {} -> {}",
+            self.old_test_result, self.new_test_result
+        )?;
+        if self.old_profile != self.new_profile {
+            writeln!(
+                out,
+                "Warning: the scans used different profiles: {} and {}",
+                self.old_profile.as_deref().unwrap_or("none"),
+                self.new_profile.as_deref().unwrap_or("none")
+            )?;
+        }
+        if !self.changed.is_empty() {
+            writeln!(out, "Changed results:")?;
+            for change in self.changed.iter() {
+                let transition = format!("{} -> {}", change.old_result, change.new_result);
+                writeln!(out, "  {:<30} {} {}", transition, change.rule, change.title)?;
+            }
+        }
+        for (heading, rows) in [
+            ("New rules:", &self.added),
+            ("Removed rules:", &self.removed),
+        ] {
+            if !rows.is_empty() {
+                writeln!(out, "{}", heading)?;
+                for row in rows.iter() {
+                    writeln!(out, "  {:<30} {} {}", row.result, row.rule, row.title)?;
+                }
+            }
+        }
+        writeln!(
+            out,
+            "Score: {:.2} -> {:.2} ({:+.2})",
+            self.old_score,
+            self.new_score,
+            self.score_delta()
+        )?;
+        Ok(())
+    }
+}
+
+fn rule_title(benchmark: &Benchmark, id: &str) -> String {
+    benchmark
+        .rule(id)
+        .and_then(|r| r.titles.first())
+        .map(|t| t.title.clone())
+        .unwrap_or_default()
+}
+
+/// Compares the rule results of two TestResults, each with the benchmark
+/// it was evaluated against, in the order of the new results.
+pub fn diff_results(old: (&Benchmark, &TestResult), new: (&Benchmark, &TestResult)) -> ResultsDiff {
+    let (old_benchmark, old_result) = old;
+    let (new_benchmark, new_result) = new;
+    let find = |test_result: &TestResult, id: &str| {
+        test_result
+            .rule_results
+            .iter()
+            .find(|r| &*r.idref == id)
+            .map(|r| r.result.clone())
+    };
+    let mut changed = Vec::new();
+    let mut added = Vec::new();
+    for rule_result in new_result.rule_results.iter() {
+        let id = &rule_result.idref;
+        match find(old_result, id) {
+            Some(old) if old != rule_result.result => changed.push(ResultChange {
+                rule: id.to_string(),
+                title: rule_title(new_benchmark, id),
+                old_result: old,
+                new_result: rule_result.result.clone(),
+            }),
+            Some(_) => {}
+            None => added.push(RuleResultRow {
+                rule: id.to_string(),
+                title: rule_title(new_benchmark, id),
+                result: rule_result.result.clone(),
+            }),
+        }
+    }
+    let removed = old_result
+        .rule_results
+        .iter()
+        .filter(|r| find(new_result, &r.idref).is_none())
+        .map(|r| RuleResultRow {
+            rule: r.idref.to_string(),
+            title: rule_title(old_benchmark, &r.idref),
+            result: r.result.clone(),
+        })
+        .collect();
+    let score = |benchmark, test_result| {
+        scoring::score(benchmark, test_result, ScoringModel::Default).value()
+    };
+    ResultsDiff {
+        old_test_result: old_result.id.to_string(),
+        new_test_result: new_result.id.to_string(),
+        old_profile: old_result.profile.as_deref().map(String::from),
+        new_profile: new_result.profile.as_deref().map(String::from),
+        changed,
+        added,
+        removed,
+        old_score: score(old_benchmark, old_result),
+        new_score: score(new_benchmark, new_result),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ItemRow {
+    pub(crate) id: String,
+    pub(crate) title: String,
+}
+
+/// Old and new value of a compared property.
+#[derive(Debug, Serialize)]
+pub struct FieldChange {
+    pub(crate) field: &'static str,
+    pub(crate) old: String,
+    pub(crate) new: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RuleChange {
+    pub(crate) id: String,
+    pub(crate) changes: Vec<FieldChange>,
+}
+
+impl RuleChange {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn fields(&self) -> Vec<&str> {
+        self.changes.iter().map(|c| c.field).collect()
+    }
+}
+
+/// Items newly selected and no longer selected by a profile.
+#[derive(Debug, Serialize)]
+pub struct SelectionChange {
+    pub(crate) profile: String,
+    pub(crate) selected: Vec<String>,
+    pub(crate) unselected: Vec<String>,
+}
+
+/// Default of a Value, `None` where the Value doesn't exist.
+#[derive(Debug, Serialize)]
+pub struct ValueChange {
+    pub(crate) id: String,
+    pub(crate) old: Option<String>,
+    pub(crate) new: Option<String>,
+}
+
+/// Differences between two versions of a benchmark.
+#[derive(Debug, Serialize)]
+pub struct ContentDiff {
+    pub(crate) old_version: String,
+    pub(crate) new_version: String,
+    pub(crate) added_rules: Vec<ItemRow>,
+    pub(crate) removed_rules: Vec<ItemRow>,
+    pub(crate) changed_rules: Vec<RuleChange>,
+    pub(crate) added_profiles: Vec<ItemRow>,
+    pub(crate) removed_profiles: Vec<ItemRow>,
+    pub(crate) changed_selections: Vec<SelectionChange>,
+    pub(crate) changed_values: Vec<ValueChange>,
+}
+
+impl ContentDiff {
+    pub fn added_rules(&self) -> &[ItemRow] {
+        &self.added_rules
+    }
+
+    pub fn removed_rules(&self) -> &[ItemRow] {
+        &self.removed_rules
+    }
+
+    pub fn changed_rules(&self) -> &[RuleChange] {
+        &self.changed_rules
+    }
+
+    pub fn changed_selections(&self) -> &[SelectionChange] {
+        &self.changed_selections
+    }
+
+    pub fn changed_values(&self) -> &[ValueChange] {
+        &self.changed_values
+    }
+
+    pub fn write_text<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "Version: {} -> {}", self.old_version, self.new_version)?;
+        let rows = [
+            ("Added rules:", "+", &self.added_rules),
+            ("Removed rules:", "-", &self.removed_rules),
+            ("Added profiles:", "+", &self.added_profiles),
+            ("Removed profiles:", "-", &self.removed_profiles),
+        ];
+        for (heading, mark, rows) in rows {
+            if !rows.is_empty() {
+                writeln!(out, "{}", heading)?;
+                for row in rows.iter() {
+                    writeln!(out, "  {} {} {}", mark, row.id, row.title)?;
+                }
+            }
+        }
+        if !self.changed_rules.is_empty() {
+            writeln!(out, "Changed rules:")?;
+            for rule in self.changed_rules.iter() {
+                writeln!(out, "  {}", rule.id)?;
+                for change in rule.changes.iter() {
+                    // Texts spanning lines would make the list unreadable
+                    if change.old.contains('\n') || change.new.contains('\n') {
+                        writeln!(out, "    {} changed", change.field)?;
+                    } else {
+                        writeln!(
+                            out,
+                            "    {}: {} -> {}",
+                            change.field, change.old, change.new
+                        )?;
+                    }
+                }
+            }
+        }
+        for selection in self.changed_selections.iter() {
+            writeln!(out, "Changed selections of profile {}:", selection.profile)?;
+            for id in selection.selected.iter() {
+                writeln!(out, "  + {}", id)?;
+            }
+            for id in selection.unselected.iter() {
+                writeln!(out, "  - {}", id)?;
+            }
+        }
+        if !self.changed_values.is_empty() {
+            writeln!(out, "Changed value defaults:")?;
+            for value in self.changed_values.iter() {
+                writeln!(
+                    out,
+                    "  {}: {} -> {}",
+                    value.id,
+                    value.old.as_deref().unwrap_or("(none)"),
+                    value.new.as_deref().unwrap_or("(none)")
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Properties of a rule compared between versions.
+fn rule_fields(rule: &Rule) -> [(&'static str, String); 4] {
+    let checks: Vec<&str> = rule
+        .checks
+        .iter()
+        .flat_map(|c| c.content_refs.iter())
+        .map(|r| r.name.as_deref().unwrap_or(&r.href))
+        .collect();
+    let fixtexts: Vec<String> = rule.fixtexts.iter().map(|f| f.text()).collect();
+    [
+        (
+            "title",
+            rule.titles
+                .first()
+                .map(|t| t.title.clone())
+                .unwrap_or_default(),
+        ),
+        ("severity", rule.severity.to_string()),
+        ("check", checks.join(", ")),
+        ("fixtext", fixtexts.join("\n")),
+    ]
+}
+
+fn item_row(rule: &Rule) -> ItemRow {
+    ItemRow {
+        id: rule.id.to_string(),
+        title: rule
+            .titles
+            .first()
+            .map(|t| t.title.clone())
+            .unwrap_or_default(),
+    }
+}
+
+fn selected_ids(benchmark: &Benchmark, profile: &str) -> Result<BTreeSet<String>, String> {
+    let resolved = benchmark
+        .resolve_profile(profile)
+        .map_err(|e| e.to_string())?;
+    Ok(resolved
+        .selected_ids()
+        .into_iter()
+        .map(String::from)
+        .collect())
+}
+
+/// Compares the rules, the profile selections and the Value defaults of two
+/// versions of a benchmark, in the order of the new version.
+pub fn diff_content(old: &Benchmark, new: &Benchmark) -> Result<ContentDiff, String> {
+    let old_rules = old.all_rules();
+    let new_rules = new.all_rules();
+    let mut added_rules = Vec::new();
+    let mut changed_rules = Vec::new();
+    for rule in new_rules.iter() {
+        let old_rule = match old.rule(&rule.id) {
+            Some(old_rule) => old_rule,
+            None => {
+                added_rules.push(item_row(rule));
+                continue;
+            }
+        };
+        let changes: Vec<FieldChange> = rule_fields(old_rule)
+            .into_iter()
+            .zip(rule_fields(rule))
+            .filter(|((_, old), (_, new))| old != new)
+            .map(|((field, old), (_, new))| FieldChange { field, old, new })
+            .collect();
+        if !changes.is_empty() {
+            changed_rules.push(RuleChange {
+                id: rule.id.to_string(),
+                changes,
+            });
+        }
+    }
+    let removed_rules = old_rules
+        .iter()
+        .filter(|r| new.rule(&r.id).is_none())
+        .map(|r| item_row(r))
+        .collect();
+
+    let profile_row = |p: &Profile| ItemRow {
+        id: p.id.to_string(),
+        title: p
+            .titles
+            .first()
+            .map(|t| t.title.clone())
+            .unwrap_or_default(),
+    };
+    let mut added_profiles = Vec::new();
+    let mut changed_selections = Vec::new();
+    for profile in new.profiles.iter() {
+        if old.find_profile(&profile.id).is_none() {
+            added_profiles.push(profile_row(profile));
+            continue;
+        }
+        let old_selected = selected_ids(old, &profile.id)?;
+        let new_selected = selected_ids(new, &profile.id)?;
+        let selected: Vec<String> = new_selected.difference(&old_selected).cloned().collect();
+        let unselected: Vec<String> = old_selected.difference(&new_selected).cloned().collect();
+        if !selected.is_empty() || !unselected.is_empty() {
+            changed_selections.push(SelectionChange {
+                profile: profile.id.to_string(),
+                selected,
+                unselected,
+            });
+        }
+    }
+    let removed_profiles = old
+        .profiles
+        .iter()
+        .filter(|p| new.find_profile(&p.id).is_none())
+        .map(profile_row)
+        .collect();
+
+    let default = |benchmark: &Benchmark, id: &str| {
+        benchmark
+            .value(id)
+            .and_then(|v| v.value_for(None))
+            .map(String::from)
+    };
+    let mut changed_values: Vec<ValueChange> = new
+        .iter_values()
+        .map(|v| ValueChange {
+            id: v.item.id.to_string(),
+            old: default(old, &v.item.id),
+            new: default(new, &v.item.id),
+        })
+        .filter(|v| v.old != v.new)
+        .collect();
+    changed_values.extend(
+        old.iter_values()
+            .filter(|v| new.value(&v.item.id).is_none())
+            .map(|v| ValueChange {
+                id: v.item.id.to_string(),
+                old: default(old, &v.item.id),
+                new: None,
+            }),
+    );
+
+    Ok(ContentDiff {
+        old_version: old.version.text.clone(),
+        new_version: new.version.text.clone(),
+        added_rules,
+        removed_rules,
+        changed_rules,
+        added_profiles,
+        removed_profiles,
+        changed_selections,
+        changed_values,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xccdf::XCCDF12_NS;
+    use minidom::Element;
+
+    fn benchmark(results: &[(&str, &str)]) -> Benchmark {
+        let rule_results: String = results
+            .iter()
+            .map(|(id, result)| {
+                format!(
+                    "<rule-result idref=\"{}\"><result>{}</result></rule-result>",
+                    id, result
+                )
+            })
+            .collect();
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Rule id=\"r1\"><title>R1</title></Rule><Rule id=\"r2\"><title>R2</title></Rule>\
+             <Rule id=\"r3\"><title>R3</title></Rule><Rule id=\"r4\"><title>R4</title></Rule>\
+             <TestResult id=\"t\" end-time=\"2024-01-01T00:00:00\">{}</TestResult></Benchmark>",
+            XCCDF12_NS, rule_results
+        )
+        .parse()
+        .unwrap();
+        Benchmark::from_xml(&el).unwrap()
+    }
+
+    #[test]
+    fn test_diff_content() {
+        let parse = |body: &str| {
+            let el: Element = format!(
+                "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status>{}</Benchmark>",
+                XCCDF12_NS, body
+            )
+            .parse()
+            .unwrap();
+            Benchmark::from_xml(&el).unwrap()
+        };
+        let old = parse(
+            "<version>1</version>\
+             <Profile id=\"p\"><title>P</title><select idref=\"r1\" selected=\"true\"/>\
+             <select idref=\"r2\" selected=\"true\"/></Profile>\
+             <Value id=\"v\"><value>027</value></Value>\
+             <Rule id=\"r1\" severity=\"low\"><title>R1</title>\
+             <check system=\"http://oval.mitre.org/XMLSchema/oval-definitions-5\">\
+             <check-content-ref href=\"oval.xml\" name=\"oval:x:def:1\"/></check></Rule>\
+             <Rule id=\"r2\"><title>R2</title></Rule>",
+        );
+        let new = parse(
+            "<version>2</version>\
+             <Profile id=\"p\"><title>P</title><select idref=\"r1\" selected=\"true\"/>\
+             <select idref=\"r3\" selected=\"true\"/></Profile>\
+             <Profile id=\"q\"><title>Q</title></Profile>\
+             <Value id=\"v\"><value>077</value></Value>\
+             <Rule id=\"r1\" severity=\"high\"><title>R1</title>\
+             <check system=\"http://oval.mitre.org/XMLSchema/oval-definitions-5\">\
+             <check-content-ref href=\"oval.xml\" name=\"oval:x:def:2\"/></check></Rule>\
+             <Rule id=\"r3\"><title>R3</title></Rule>",
+        );
+        let diff = diff_content(&old, &new).unwrap();
+        assert_eq!(diff.added_rules()[0].id, "r3");
+        assert_eq!(diff.removed_rules()[0].id, "r2");
+        assert_eq!(diff.changed_rules()[0].id(), "r1");
+        assert_eq!(diff.changed_rules()[0].fields(), vec!["severity", "check"]);
+        assert_eq!(diff.added_profiles[0].id, "q");
+        let selection = &diff.changed_selections()[0];
+        assert_eq!(
+            (selection.selected.clone(), selection.unselected.clone()),
+            (vec![String::from("r3")], vec![String::from("r2")])
+        );
+        let value = &diff.changed_values()[0];
+        assert_eq!(
+            (value.old.as_deref(), value.new.as_deref()),
+            (Some("027"), Some("077"))
+        );
+    }
+
+    #[test]
+    fn test_diff_results() {
+        let old = benchmark(&[
+            ("r1", "pass"),
+            ("r2", "fail"),
+            ("r3", "pass"),
+            ("r4", "notapplicable"),
+        ]);
+        let new = benchmark(&[("r1", "fail"), ("r2", "pass"), ("r4", "notapplicable")]);
+        let old_result = old.test_result(None).unwrap();
+        let new_result = new.test_result(None).unwrap();
+        let diff = diff_results((&old, old_result), (&new, new_result));
+        let changed: Vec<(&str, &str, &str)> = diff
+            .changed()
+            .iter()
+            .map(|c| (c.rule(), c.old_result(), c.new_result()))
+            .collect();
+        assert_eq!(
+            changed,
+            vec![("r1", "pass", "fail"), ("r2", "fail", "pass")]
+        );
+        let regressions: Vec<&str> = diff.regressions().map(|c| c.rule()).collect();
+        assert_eq!(regressions, vec!["r1"]);
+        assert!(diff.added().is_empty());
+        assert_eq!(diff.removed()[0].rule, "r3");
+        assert!((diff.old_score - 66.67).abs() < 0.01);
+        assert!((diff.score_delta() + 16.67).abs() < 0.01);
+    }
+}
@@ -0,0 +1,477 @@
+use minidom::quick_xml::events::Event;
+use minidom::quick_xml::Reader;
+use minidom::Element;
+
+use crate::arf::ARF_NS;
+use crate::matrix::CPE_DICT_NS;
+use crate::misc;
+use crate::options::ParseOptions;
+use crate::oval::OVAL_DEF_NS;
+use crate::sds::{DataStreamCollection, ScapVersion, SCAP12_NS};
+use crate::upgrade::XCCDF11_NS;
+#[cfg(feature = "validation")]
+use crate::validate;
+use crate::xccdf::{Benchmark, Tailoring, XCCDF12_NS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Ok,
+    Warning,
+    Error,
+}
+
+impl Level {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Level::Ok => "OK",
+            Level::Warning => "WARNING",
+            Level::Error => "ERROR",
+        }
+    }
+}
+
+/// Outcome of one probe.
+#[derive(Debug)]
+pub struct Finding {
+    pub probe: &'static str,
+    pub level: Level,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Diagnosis {
+    pub findings: Vec<Finding>,
+    /// Commands worth running next
+    pub suggestions: Vec<String>,
+}
+
+impl Diagnosis {
+    fn add(&mut self, probe: &'static str, level: Level, message: String) {
+        self.findings.push(Finding {
+            probe,
+            level,
+            message,
+        });
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.findings.iter().any(|f| f.level == Level::Error)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    Collection,
+    Benchmark,
+    Tailoring,
+    Oval,
+    CpeDictionary,
+}
+
+/// Recognized root elements, with a description and the kind if the
+/// element is supported.
+const ROOTS: [(&str, &str, &str, Option<Kind>); 7] = [
+    (
+        "data-stream-collection",
+        SCAP12_NS,
+        "SCAP source data stream collection",
+        Some(Kind::Collection),
+    ),
+    (
+        "Benchmark",
+        XCCDF12_NS,
+        "XCCDF 1.2 benchmark",
+        Some(Kind::Benchmark),
+    ),
+    ("Benchmark", XCCDF11_NS, "XCCDF 1.1 benchmark", None),
+    (
+        "Tailoring",
+        XCCDF12_NS,
+        "XCCDF 1.2 tailoring",
+        Some(Kind::Tailoring),
+    ),
+    (
+        "oval_definitions",
+        OVAL_DEF_NS,
+        "OVAL definitions",
+        Some(Kind::Oval),
+    ),
+    (
+        "cpe-list",
+        CPE_DICT_NS,
+        "CPE dictionary",
+        Some(Kind::CpeDictionary),
+    ),
+    ("asset-report-collection", ARF_NS, "ARF result", None),
+];
+
+pub(crate) fn describe(el: &Element) -> Option<(&'static str, Option<Kind>)> {
+    ROOTS
+        .iter()
+        .find(|(name, ns, _, _)| el.is(*name, *ns))
+        .map(|(_, _, description, kind)| (*description, *kind))
+}
+
+/// Line and column of a byte offset, both starting at 1.
+fn location(data: &[u8], position: usize) -> (usize, usize) {
+    let before = &data[..position.min(data.len())];
+    let line = before.iter().filter(|b| **b == b'\n').count() + 1;
+    let column = before.iter().rev().take_while(|b| **b != b'\n').count() + 1;
+    (line, column)
+}
+
+/// Checks that the document is well-formed, returns the problem with its
+/// line and column otherwise.
+fn check_well_formed(data: &[u8]) -> Result<usize, String> {
+    if let Err(error) = std::str::from_utf8(data) {
+        let (line, column) = location(data, error.valid_up_to());
+        return Err(format!("invalid UTF-8 at line {}, column {}", line, column));
+    }
+    let mut reader = Reader::from_reader(data);
+    let mut buf = Vec::new();
+    let mut depth = 0;
+    let mut elements = 0;
+    loop {
+        let start = reader.buffer_position();
+        let event = reader.read_event(&mut buf).map_err(|e| {
+            let (line, column) = location(data, reader.buffer_position());
+            format!("{} at line {}, column {}", e, line, column)
+        })?;
+        let root_closed = elements > 0 && depth == 0;
+        match event {
+            Event::Start(_) | Event::Empty(_) if root_closed => {
+                let (line, column) = location(data, start);
+                return Err(format!(
+                    "second root element at line {}, column {}",
+                    line, column
+                ));
+            }
+            Event::Start(_) => {
+                depth += 1;
+                elements += 1;
+            }
+            Event::Empty(_) => elements += 1,
+            Event::End(_) => depth -= 1,
+            Event::Eof if elements == 0 => return Err(String::from("no root element")),
+            Event::Eof if depth > 0 => {
+                return Err(format!(
+                    "unexpected end of file, {} elements not closed",
+                    depth
+                ))
+            }
+            Event::Eof => return Ok(elements),
+            _ => (),
+        }
+        buf.clear();
+    }
+}
+
+fn versions(root: &Element, kind: Kind) -> Vec<String> {
+    let mut versions = Vec::new();
+    match kind {
+        Kind::Collection => {
+            if let Some(version) = root.attr("schematron-version") {
+                versions.push(format!("schematron-version {}", version));
+            }
+            for data_stream in root.children().filter(|c| c.is("data-stream", SCAP12_NS)) {
+                versions.push(format!(
+                    "data stream '{}' SCAP {}",
+                    data_stream.attr("id").unwrap_or_default(),
+                    data_stream.attr("scap-version").unwrap_or("?")
+                ));
+            }
+        }
+        Kind::Benchmark | Kind::Tailoring => {
+            if let Some(version) = root.get_child("version", XCCDF12_NS) {
+                versions.push(format!("content version {}", version.text().trim()));
+            }
+        }
+        Kind::Oval => {
+            let generator = root.get_child("generator", OVAL_DEF_NS);
+            if let Some(version) = generator.and_then(|g| {
+                g.children()
+                    .find(|c| c.name() == "schema_version")
+                    .map(|v| v.text())
+            }) {
+                versions.push(format!("OVAL {}", version.trim()));
+            }
+        }
+        Kind::CpeDictionary => (),
+    }
+    versions
+}
+
+/// Lists the components of a collection and checks that the component
+/// references point to existing ones.
+fn inventory(root: &Element, diagnosis: &mut Diagnosis) {
+    let mut ids = Vec::new();
+    for component in root.children().filter(|c| c.is("component", SCAP12_NS)) {
+        let id = component.attr("id").unwrap_or_default();
+        ids.push(id);
+        let content = match component.children().next() {
+            Some(content) => match describe(content) {
+                Some((description, _)) => description.to_string(),
+                None => format!("unknown content '{}'", content.name()),
+            },
+            None => String::from("empty"),
+        };
+        diagnosis.add("components", Level::Ok, format!("'{}': {}", id, content));
+    }
+    let extended = root
+        .children()
+        .filter(|c| c.is("extended-component", SCAP12_NS))
+        .count();
+    if extended > 0 {
+        diagnosis.add(
+            "components",
+            Level::Ok,
+            format!("{} extended components", extended),
+        );
+    }
+    if ids.is_empty() {
+        diagnosis.add(
+            "components",
+            Level::Warning,
+            String::from("the collection doesn't have any component"),
+        );
+    }
+}
+
+/// Checks that the component-refs point to components and that the check
+/// content of the checklists resolves through their catalogs to components
+/// of the same data stream, which contain the OVAL definitions the rules
+/// check.
+fn references(collection: &DataStreamCollection, diagnosis: &mut Diagnosis) {
+    #[cfg(feature = "validation")]
+    for violation in validate::reference_violations(collection) {
+        diagnosis.add("references", Level::Error, violation.message);
+    }
+    let mut resolved = 0;
+    let mut unresolved = 0;
+    for ds in collection.data_streams() {
+        for checklist in ds.checklists() {
+            for resolution in collection.resolve_check_refs(ds, checklist) {
+                if resolution.component_id().is_some() {
+                    resolved += 1;
+                    continue;
+                }
+                unresolved += 1;
+                diagnosis.add(
+                    "references",
+                    Level::Warning,
+                    format!(
+                        "check content '{}' of checklist '{}' isn't in the data stream",
+                        resolution.href,
+                        checklist.id()
+                    ),
+                );
+            }
+        }
+    }
+    if unresolved == 0 {
+        diagnosis.add(
+            "references",
+            Level::Ok,
+            format!("{} check content references resolved", resolved),
+        );
+    }
+}
+
+fn parse(root: &Element, kind: Kind, opts: &ParseOptions) -> Option<Result<(), String>> {
+    let result = match kind {
+        Kind::Collection => DataStreamCollection::from_xml_with_options(root, opts).map(|_| ()),
+        Kind::Benchmark => Benchmark::from_xml_with_options(root, opts).map(|_| ()),
+        Kind::Tailoring => Tailoring::from_xml_with_options(root, opts).map(|_| ()),
+        Kind::Oval | Kind::CpeDictionary => return None,
+    };
+    Some(result.map_err(String::from))
+}
+
+fn suggest(root: &Element, kind: Kind, filepath: &str, diagnosis: &mut Diagnosis) {
+    let commands: Vec<String> = match kind {
+        Kind::Collection => vec![
+            format!("oscapxml {}", filepath),
+            format!("oscapxml list-rules {}", filepath),
+            format!("oscapxml signatures {}", filepath),
+        ],
+        Kind::Benchmark if root.get_child("TestResult", XCCDF12_NS).is_some() => {
+            vec![format!("oscapxml results summary {}", filepath)]
+        }
+        Kind::Benchmark => vec![
+            format!("oscapxml list-rules {}", filepath),
+            format!("oscapxml resolve {} resolved.xml", filepath),
+        ],
+        Kind::Tailoring => vec![format!(
+            "oscapxml tailoring apply <data stream> {}",
+            filepath
+        )],
+        Kind::Oval => vec![format!("oscapxml oval stats {}", filepath)],
+        Kind::CpeDictionary => Vec::new(),
+    };
+    diagnosis.suggestions.extend(commands);
+}
+
+/// Runs the probes on the document, each one only if the previous ones
+/// succeeded far enough. `filepath` is used in the suggested commands.
+pub fn diagnose(data: &[u8], filepath: &str) -> Diagnosis {
+    let mut diagnosis = Diagnosis::default();
+    match check_well_formed(data) {
+        Ok(elements) => diagnosis.add(
+            "well-formedness",
+            Level::Ok,
+            format!("well-formed XML with {} elements", elements),
+        ),
+        Err(error) => {
+            diagnosis.add("well-formedness", Level::Error, error);
+            return diagnosis;
+        }
+    }
+    let root = match misc::parse(data) {
+        Ok((root, _)) => root,
+        Err(error) => {
+            diagnosis.add("root element", Level::Error, error);
+            return diagnosis;
+        }
+    };
+    let kind = match describe(&root) {
+        Some((description, Some(kind))) => {
+            diagnosis.add("root element", Level::Ok, description.to_string());
+            kind
+        }
+        Some((description, None)) => {
+            diagnosis.add(
+                "root element",
+                Level::Error,
+                format!("{} isn't supported", description),
+            );
+            if root.is("Benchmark", XCCDF11_NS) {
+                diagnosis.suggestions.push(format!(
+                    "oscapxml upgrade xccdf {} upgraded.xml --reverse-dns <reverse DNS>",
+                    filepath
+                ));
+            }
+            return diagnosis;
+        }
+        None => {
+            let expected = ROOTS.iter().find(|(name, _, _, _)| *name == root.name());
+            let message = match expected {
+                Some((_, ns, _, _)) => format!(
+                    "'{}' in namespace '{}', expected '{}'",
+                    root.name(),
+                    root.ns(),
+                    ns
+                ),
+                None => format!("unknown root element '{}'", root.name()),
+            };
+            diagnosis.add("root element", Level::Error, message);
+            return diagnosis;
+        }
+    };
+    for version in versions(&root, kind) {
+        diagnosis.add("versions", Level::Ok, version);
+    }
+    if kind == Kind::Collection {
+        for data_stream in root.children().filter(|c| c.is("data-stream", SCAP12_NS)) {
+            let version = data_stream.attr("scap-version").unwrap_or_default();
+            if version.parse::<ScapVersion>().is_err() {
+                diagnosis.add(
+                    "versions",
+                    Level::Warning,
+                    format!("unknown SCAP version '{}'", version),
+                );
+            }
+        }
+        inventory(&root, &mut diagnosis);
+    }
+    match parse(&root, kind, &ParseOptions::default()) {
+        Some(Ok(())) => {
+            diagnosis.add("structure", Level::Ok, String::from("no problems found"));
+            if let Ok(collection) = DataStreamCollection::from_xml(&root) {
+                references(&collection, &mut diagnosis);
+                #[cfg(feature = "validation")]
+                for violation in validate::id_violations(&collection) {
+                    diagnosis.add("ids", Level::Error, violation.message);
+                }
+            }
+        }
+        Some(Err(error)) => {
+            diagnosis.add("structure", Level::Error, error);
+            diagnosis
+                .suggestions
+                .push(format!("oscapxml repair {} repaired.xml", filepath));
+            if kind == Kind::Collection
+                && parse(&root, kind, &ParseOptions::lenient()) == Some(Ok(()))
+            {
+                diagnosis
+                    .suggestions
+                    .push(format!("oscapxml --lenient {}", filepath));
+            }
+            return diagnosis;
+        }
+        None => diagnosis.add(
+            "structure",
+            Level::Ok,
+            String::from("not checked for this document type"),
+        ),
+    }
+    suggest(&root, kind, filepath, &mut diagnosis);
+    diagnosis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnose_malformed() {
+        let diagnosis = diagnose(b"<Benchmark>\n  <title>T</titel>\n</Benchmark>", "b.xml");
+        assert!(diagnosis.has_errors());
+        assert_eq!(diagnosis.findings.len(), 1);
+        assert_eq!(
+            diagnosis.findings[0].message,
+            "Expecting </title> found </titel> at line 2, column 13"
+        );
+
+        let diagnosis = diagnose(b"<Benchmark>\n<title>T</title>", "b.xml");
+        assert_eq!(
+            diagnosis.findings[0].message,
+            "unexpected end of file, 1 elements not closed"
+        );
+    }
+
+    #[test]
+    fn test_diagnose_structure() {
+        let xml = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><version>1</version></Benchmark>",
+            XCCDF12_NS
+        );
+        let diagnosis = diagnose(xml.as_bytes(), "b.xml");
+        let probes: Vec<_> = diagnosis
+            .findings
+            .iter()
+            .map(|f| (f.probe, f.level))
+            .collect();
+        assert_eq!(
+            probes,
+            vec![
+                ("well-formedness", Level::Ok),
+                ("root element", Level::Ok),
+                ("versions", Level::Ok),
+                ("structure", Level::Error),
+            ]
+        );
+        assert_eq!(
+            diagnosis.suggestions,
+            vec!["oscapxml repair b.xml repaired.xml"]
+        );
+
+        let diagnosis = diagnose(b"<Benchmark xmlns=\"urn:x\"/>", "b.xml");
+        assert_eq!(
+            diagnosis.findings[1].message,
+            format!(
+                "'Benchmark' in namespace 'urn:x', expected '{}'",
+                XCCDF12_NS
+            )
+        );
+    }
+}
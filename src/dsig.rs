@@ -0,0 +1,451 @@
+use minidom::Element;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+use crate::error::Error;
+use crate::trust::{self, Certificate};
+use crate::utils::*;
+use crate::writer;
+
+pub const DSIG_NS: &str = "http://www.w3.org/2000/09/xmldsig#";
+/// Namespace historically used for signatures in SCAP source data streams.
+pub const SCAP_DSIG_NS: &str = "http://scap.nist.gov/schema/xml-dsig/1.0";
+
+/// Exclusive XML canonicalization without comments
+pub const EXC_C14N: &str = "http://www.w3.org/2001/10/xml-exc-c14n#";
+pub const ENVELOPED_SIGNATURE: &str = "http://www.w3.org/2000/09/xmldsig#enveloped-signature";
+pub const SHA256: &str = "http://www.w3.org/2001/04/xmlenc#sha256";
+pub const SHA384: &str = "http://www.w3.org/2001/04/xmldsig-more#sha384";
+pub const SHA512: &str = "http://www.w3.org/2001/04/xmlenc#sha512";
+pub const RSA_SHA256: &str = "http://www.w3.org/2001/04/xmldsig-more#rsa-sha256";
+pub const RSA_SHA384: &str = "http://www.w3.org/2001/04/xmldsig-more#rsa-sha384";
+pub const RSA_SHA512: &str = "http://www.w3.org/2001/04/xmldsig-more#rsa-sha512";
+pub const ECDSA_SHA256: &str = "http://www.w3.org/2001/04/xmldsig-more#ecdsa-sha256";
+pub const ECDSA_SHA384: &str = "http://www.w3.org/2001/04/xmldsig-more#ecdsa-sha384";
+
+const DIGEST_METHODS: [&str; 4] = [
+    "http://www.w3.org/2000/09/xmldsig#sha1",
+    "http://www.w3.org/2001/04/xmlenc#sha256",
+    "http://www.w3.org/2001/04/xmldsig-more#sha384",
+    "http://www.w3.org/2001/04/xmlenc#sha512",
+];
+
+pub fn is_signature(el: &Element) -> bool {
+    el.is("Signature", DSIG_NS) || el.is("Signature", SCAP_DSIG_NS)
+}
+
+#[derive(Debug)]
+pub struct SignatureReference {
+    pub uri: Arc<str>,
+    pub transforms: Vec<Arc<str>>,
+    pub digest_method: Arc<str>,
+    pub digest_value: String,
+}
+
+fn find_by_id<'a>(el: &'a Element, id: &str, found: &mut Vec<&'a Element>) {
+    if el.attr("id") == Some(id) || el.attr("Id") == Some(id) {
+        found.push(el);
+    }
+    for child in el.children() {
+        find_by_id(child, id, found);
+    }
+}
+
+/// IDs of the element and of its descendants.
+fn collect_ids<'a>(el: &'a Element, ids: &mut HashSet<&'a str>) {
+    ids.extend(el.attr("id"));
+    for child in el.children() {
+        collect_ids(child, ids);
+    }
+}
+
+/// Exclusive canonical form of `el` as parsed in `root`, without the
+/// `excluded` enveloped signature.
+fn canonical_form(
+    root: &Element,
+    el: &Element,
+    excluded: Option<&Element>,
+) -> Result<Vec<u8>, String> {
+    writer::canonicalize_parsed(root, el, excluded).map_err(|e| e.to_string())
+}
+
+impl SignatureReference {
+    fn from_xml(el: &Element) -> Result<SignatureReference, Error> {
+        let ns = el.ns();
+        let uri = get_attr(el, "URI").unwrap_or_default();
+        let mut transforms = Vec::new();
+        if let Some(list) = el.get_child("Transforms", ns.as_str()) {
+            for transform in list.children().filter(|c| c.is("Transform", ns.as_str())) {
+                transforms.push(require_attr(transform, "Algorithm")?);
+            }
+        }
+        let digest_method = match el.get_child("DigestMethod", ns.as_str()) {
+            Some(method) => require_attr(method, "Algorithm")?,
+            None => return Err(Error::missing_element(el.name(), "DigestMethod")),
+        };
+        let digest_value = match el.get_child("DigestValue", ns.as_str()) {
+            Some(value) => normalize_whitespace(&value.text()),
+            None => return Err(Error::missing_element(el.name(), "DigestValue")),
+        };
+        Ok(SignatureReference {
+            uri,
+            transforms,
+            digest_method,
+            digest_value,
+        })
+    }
+
+    /// Checks the digest of the referenced element of `root` and returns
+    /// it. The ID has to be unique, otherwise the digest could be checked
+    /// against another element than the one the document is processed with.
+    fn verify<'a>(&self, root: &'a Element, signature: &Element) -> Result<&'a Element, String> {
+        let el = match self.uri.strip_prefix('#') {
+            _ if self.uri.is_empty() => root,
+            Some(id) => {
+                let mut found = Vec::new();
+                find_by_id(root, id, &mut found);
+                match found.as_slice() {
+                    [el] => *el,
+                    [] => return Err(String::from("referenced element not found")),
+                    _ => return Err(format!("ID '{}' is used by {} elements", id, found.len())),
+                }
+            }
+            None => return Err(String::from("only same-document references are supported")),
+        };
+        let mut enveloped = false;
+        for transform in self.transforms.iter() {
+            match transform.as_ref() {
+                ENVELOPED_SIGNATURE => enveloped = true,
+                EXC_C14N => (),
+                _ => return Err(format!("transform '{}' isn't supported", transform)),
+            }
+        }
+        // The enveloped transform removes the signature of the reference only
+        let canonical = canonical_form(root, el, Some(signature).filter(|_| enveloped))?;
+        let digest = match self.digest_method.as_ref() {
+            SHA256 => Sha256::digest(&canonical).to_vec(),
+            SHA384 => Sha384::digest(&canonical).to_vec(),
+            SHA512 => Sha512::digest(&canonical).to_vec(),
+            method => return Err(format!("digest method '{}' isn't supported", method)),
+        };
+        match trust::encode_base64(&digest) == self.digest_value {
+            true => Ok(el),
+            false => Err(String::from("digest doesn't match the referenced content")),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Signature {
+    pub id: Option<Arc<str>>,
+    pub canonicalization_method: Option<Arc<str>>,
+    pub signature_method: Arc<str>,
+    pub references: Vec<SignatureReference>,
+    pub signature_value: String,
+    pub certificates: Vec<String>,
+    /// The signed content, kept to verify the signature value
+    pub(crate) signed_info: Element,
+}
+
+impl Signature {
+    pub fn from_xml(el: &Element) -> Result<Signature, Error> {
+        if !is_signature(el) {
+            return Err(Error::unexpected_element(el.name(), Some("dsig:Signature")));
+        }
+        let ns = el.ns();
+        let id = get_attr(el, "Id");
+        let signed_info = match el.get_child("SignedInfo", ns.as_str()) {
+            Some(signed_info) => signed_info,
+            None => return Err(Error::missing_element(el.name(), "SignedInfo")),
+        };
+        let canonicalization_method =
+            match signed_info.get_child("CanonicalizationMethod", ns.as_str()) {
+                Some(method) => Some(require_attr(method, "Algorithm")?),
+                None => None,
+            };
+        let signature_method = match signed_info.get_child("SignatureMethod", ns.as_str()) {
+            Some(method) => require_attr(method, "Algorithm")?,
+            None => {
+                return Err(Error::missing_element(
+                    signed_info.name(),
+                    "SignatureMethod",
+                ))
+            }
+        };
+        let mut references = Vec::new();
+        for_each_child(signed_info, |child| {
+            if child.is("Reference", ns.as_str()) {
+                references.push(SignatureReference::from_xml(child)?);
+            }
+            Ok(())
+        })?;
+        let signature_value = match el.get_child("SignatureValue", ns.as_str()) {
+            Some(value) => value.text().split_whitespace().collect(),
+            None => return Err(Error::missing_element(el.name(), "SignatureValue")),
+        };
+        let mut certificates = Vec::new();
+        if let Some(key_info) = el.get_child("KeyInfo", ns.as_str()) {
+            for x509_data in key_info.children() {
+                if x509_data.is("X509Data", ns.as_str()) {
+                    for cert in x509_data.children() {
+                        if cert.is("X509Certificate", ns.as_str()) {
+                            certificates.push(cert.text().split_whitespace().collect());
+                        }
+                    }
+                }
+            }
+        }
+        Ok(Signature {
+            id,
+            canonicalization_method,
+            signature_method,
+            references,
+            signature_value,
+            certificates,
+            signed_info: signed_info.clone(),
+        })
+    }
+
+    /// The signing certificate, which is the first certificate in KeyInfo.
+    pub fn signer(&self) -> Option<Result<Certificate, String>> {
+        self.certificates
+            .first()
+            .map(|c| Certificate::from_base64(c))
+    }
+
+    /// The signing certificate followed by the other certificates of KeyInfo,
+    /// which can be the intermediate CAs it was issued by.
+    pub fn chain(&self) -> Result<Vec<Certificate>, String> {
+        self.certificates
+            .iter()
+            .map(|c| Certificate::from_base64(c))
+            .collect()
+    }
+
+    /// The signature element of `root` the signature was parsed from.
+    fn find_element<'a>(&self, root: &'a Element) -> Option<&'a Element> {
+        if is_signature(root) && root.children().any(|child| *child == self.signed_info) {
+            return Some(root);
+        }
+        root.children().find_map(|child| self.find_element(child))
+    }
+
+    /// Verifies the digests of the same-document references and the
+    /// signature value made with the key of `signer`, and that the elements
+    /// with the IDs of `signed` are within a verified reference. Only
+    /// exclusive canonicalization is supported, with the namespace prefixes
+    /// declared in the document.
+    pub fn verify(&self, root: &Element, signer: &Certificate, signed: &[&str]) -> Vec<String> {
+        let mut problems = Vec::new();
+        let element = match self.find_element(root) {
+            Some(element) => element,
+            None => return vec![String::from("signature isn't part of the document")],
+        };
+        let mut covered = HashSet::new();
+        for reference in self.references.iter() {
+            match reference.verify(root, element) {
+                Ok(el) => collect_ids(el, &mut covered),
+                Err(error) => problems.push(format!("reference '{}': {}", reference.uri, error)),
+            }
+        }
+        for id in signed.iter().filter(|id| !covered.contains(**id)) {
+            problems.push(format!("'{}' isn't covered by a verified reference", id));
+        }
+        match self.canonicalization_method.as_deref() {
+            Some(EXC_C14N) => (),
+            Some(method) => {
+                problems.push(format!(
+                    "canonicalization method '{}' isn't supported",
+                    method
+                ));
+                return problems;
+            }
+            None => {
+                problems.push(String::from(
+                    "signature doesn't have a canonicalization method",
+                ));
+                return problems;
+            }
+        }
+        let signed_info = element
+            .children()
+            .find(|child| **child == self.signed_info)
+            .unwrap_or(&self.signed_info);
+        let result = trust::decode_base64(&self.signature_value).and_then(|value| {
+            let signed_info = canonical_form(root, signed_info, None)?;
+            signer.verify(&self.signature_method, &signed_info, &value)
+        });
+        if let Err(error) = result {
+            problems.push(error);
+        }
+        problems
+    }
+
+    /// Checks the structure of the signature: it has to reference something,
+    /// use known digest algorithms and every same-document reference has to
+    /// point to one of `known_ids`. The cryptographic value isn't validated.
+    pub fn check_structure(&self, known_ids: &HashSet<&str>) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.references.is_empty() {
+            problems.push(String::from("signature doesn't contain any reference"));
+        }
+        if self.signature_value.is_empty() {
+            problems.push(String::from("signature value is empty"));
+        }
+        for reference in self.references.iter() {
+            if !DIGEST_METHODS.contains(&reference.digest_method.as_ref()) {
+                problems.push(format!(
+                    "reference '{}' uses unknown digest method '{}'",
+                    reference.uri, reference.digest_method
+                ));
+            }
+            if let Some(id) = reference.uri.strip_prefix('#') {
+                if !known_ids.contains(id) {
+                    problems.push(format!(
+                        "reference '{}' doesn't point to any element of the document",
+                        reference.uri
+                    ));
+                }
+            }
+        }
+        problems
+    }
+}
+
+fn algorithm(name: &str, algorithm: &str) -> Element {
+    Element::builder(name, DSIG_NS)
+        .attr("Algorithm", algorithm)
+        .build()
+}
+
+/// Template of a signature of the elements with the given IDs, with the
+/// SHA-256 digests of their exclusive canonical form. The
+/// signature value is left empty for the signing tool to fill in.
+pub fn signature_template(digests: &[(String, String)]) -> Element {
+    let references = digests.iter().map(|(id, digest)| {
+        Element::builder("Reference", DSIG_NS)
+            .attr("URI", format!("#{}", id))
+            .append(
+                Element::builder("Transforms", DSIG_NS)
+                    .append(algorithm("Transform", EXC_C14N))
+                    .build(),
+            )
+            .append(algorithm("DigestMethod", SHA256))
+            .append(
+                Element::builder("DigestValue", DSIG_NS)
+                    .append(digest.as_str())
+                    .build(),
+            )
+            .build()
+    });
+    Element::builder("Signature", DSIG_NS)
+        .append(
+            Element::builder("SignedInfo", DSIG_NS)
+                .append(algorithm("CanonicalizationMethod", EXC_C14N))
+                .append(algorithm("SignatureMethod", RSA_SHA256))
+                .append_all(references)
+                .build(),
+        )
+        .append(Element::bare("SignatureValue", DSIG_NS))
+        .build()
+}
+
+/// Parses the content of an `xccdf:signature` element, which wraps a single
+/// XML digital signature.
+pub fn from_xccdf_signature(el: &Element) -> Result<Signature, Error> {
+    match el.children().find(|c| is_signature(c)) {
+        Some(signature) => Signature::from_xml(signature),
+        None => Err(Error::missing_element(el.name(), "dsig:Signature")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature(uri: &str) -> Element {
+        format!(
+            "<Signature xmlns=\"{}\"><SignedInfo>\
+             <SignatureMethod Algorithm=\"http://www.w3.org/2001/04/xmldsig-more#rsa-sha256\"/>\
+             <Reference URI=\"{}\"><DigestMethod Algorithm=\"http://www.w3.org/2001/04/xmlenc#sha256\"/>\
+             <DigestValue>abc=</DigestValue></Reference></SignedInfo>\
+             <SignatureValue>\n  ZGVm\n</SignatureValue></Signature>",
+            DSIG_NS, uri
+        )
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_signature_from_xml() {
+        let signature = Signature::from_xml(&signature("#rule")).unwrap();
+        assert_eq!(signature.references.len(), 1);
+        assert_eq!(&*signature.references[0].uri, "#rule");
+        assert_eq!(signature.signature_value, "ZGVm");
+    }
+
+    fn reference(root: &Element, el: &Element, signature: &Element, uri: &str) -> Element {
+        let digest = Sha256::digest(canonical_form(root, el, Some(signature)).unwrap());
+        format!(
+            "<Reference xmlns=\"{}\" URI=\"{}\"><Transforms>\
+             <Transform Algorithm=\"{}\"/><Transform Algorithm=\"{}\"/></Transforms>\
+             <DigestMethod Algorithm=\"{}\"/><DigestValue>{}</DigestValue></Reference>",
+            DSIG_NS,
+            uri,
+            ENVELOPED_SIGNATURE,
+            EXC_C14N,
+            SHA256,
+            trust::encode_base64(&digest)
+        )
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_verify_reference() {
+        let text = format!(
+            "<collection xmlns=\"urn:example\"><component id=\"c\"><rule>x</rule>\
+             <d:Signature xmlns:d=\"{0}\" Id=\"item\"/></component>\
+             <d:Signature xmlns:d=\"{0}\" Id=\"own\"/></collection>",
+            DSIG_NS
+        );
+        let root: Element = text.parse().unwrap();
+        let component = root.children().next().unwrap();
+        let own = root.children().nth(1).unwrap();
+        // only the signature holding the reference is removed
+        let expected = format!(
+            "<collection xmlns=\"urn:example\"><component id=\"c\"><rule>x</rule>\
+             <d:Signature xmlns:d=\"{}\" Id=\"item\"></d:Signature></component></collection>",
+            DSIG_NS
+        );
+        let canonical = canonical_form(&root, &root, Some(own)).unwrap();
+        assert_eq!(String::from_utf8(canonical).unwrap(), expected);
+
+        let whole = SignatureReference::from_xml(&reference(&root, &root, own, "")).unwrap();
+        assert!(std::ptr::eq(whole.verify(&root, own).unwrap(), &root));
+        let by_id = SignatureReference::from_xml(&reference(&root, component, own, "#c")).unwrap();
+        assert!(std::ptr::eq(by_id.verify(&root, own).unwrap(), component));
+
+        // the ID has to be unique
+        let duplicate: Element = text
+            .replace("<rule>x</rule>", "<rule id=\"c\">x</rule>")
+            .parse()
+            .unwrap();
+        let own = duplicate.children().nth(1).unwrap();
+        assert_eq!(
+            by_id.verify(&duplicate, own).unwrap_err(),
+            "ID 'c' is used by 2 elements"
+        );
+    }
+
+    #[test]
+    fn test_check_structure() {
+        let known_ids: HashSet<&str> = ["rule"].into_iter().collect();
+        let good = Signature::from_xml(&signature("#rule")).unwrap();
+        assert!(good.check_structure(&known_ids).is_empty());
+        let dangling = Signature::from_xml(&signature("#other")).unwrap();
+        assert_eq!(dangling.check_structure(&known_ids).len(), 1);
+    }
+}
@@ -0,0 +1,223 @@
+use std::fmt;
+use std::io;
+use thiserror::Error;
+
+/// Error of loading or parsing SCAP content.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum Error {
+    /// A required attribute isn't present
+    #[error("Element '{element}' doesn't have required '{attribute}' attribute")]
+    MissingAttribute { element: String, attribute: String },
+    /// A required child element isn't present
+    #[error("Element '{element}' is missing '{child}' element")]
+    MissingElement { element: String, child: String },
+    /// An element isn't allowed at this place
+    #[error("Unexpected element '{element}'{}", expected_suffix(.expected))]
+    UnexpectedElement {
+        element: String,
+        expected: Option<String>,
+    },
+    /// An element is present more times than allowed
+    #[error("Element '{parent}' has duplicate '{element}' elements")]
+    DuplicateElement { parent: String, element: String },
+    /// The value of an attribute, or the text of an element if `attribute`
+    /// is `None`, isn't allowed
+    #[error("Element '{element}' {}, but {reason}", value_description(.attribute, .value))]
+    InvalidValue {
+        element: String,
+        attribute: Option<String>,
+        value: String,
+        reason: String,
+    },
+    /// The content is well-formed, but inconsistent, e.g. a reference
+    /// doesn't point anywhere
+    #[error("{0}")]
+    Content(String),
+    #[error("{message}")]
+    Io {
+        kind: io::ErrorKind,
+        message: String,
+    },
+    #[error("XML error: {0}")]
+    Xml(String),
+    /// All the problems found in the error collecting mode
+    #[error("{} problems found{}", .0.len(), problem_list(.0))]
+    Multiple(Vec<Error>),
+    /// The parse was cancelled through its cancellation token
+    #[error("Parsing was cancelled")]
+    Cancelled,
+    /// The parse didn't finish before its deadline
+    #[error("Parsing didn't finish before the deadline")]
+    DeadlineExceeded,
+    /// An error in the element at `path`, e.g.
+    /// `data-stream-collection/component[@id='c']/Benchmark/Profile[@id='p']/select[12]`
+    #[error("{path}: {error}")]
+    Located { path: String, error: Box<Error> },
+}
+
+fn expected_suffix(expected: &Option<String>) -> String {
+    match expected {
+        Some(expected) => format!(", expected {}", expected),
+        None => String::new(),
+    }
+}
+
+fn value_description(attribute: &Option<String>, value: &str) -> String {
+    match attribute {
+        Some(attribute) => format!("attribute '{}'='{}'", attribute, value),
+        None => format!("has value '{}'", value),
+    }
+}
+
+fn problem_list(errors: &[Error]) -> String {
+    errors.iter().map(|e| format!("\n  {}", e)).collect()
+}
+
+impl Error {
+    pub fn missing_attribute(element: &str, attribute: &str) -> Error {
+        Error::MissingAttribute {
+            element: element.to_string(),
+            attribute: attribute.to_string(),
+        }
+    }
+
+    pub fn missing_element(element: &str, child: &str) -> Error {
+        Error::MissingElement {
+            element: element.to_string(),
+            child: child.to_string(),
+        }
+    }
+
+    pub fn unexpected_element(element: &str, expected: Option<&str>) -> Error {
+        Error::UnexpectedElement {
+            element: element.to_string(),
+            expected: expected.map(|e| e.to_string()),
+        }
+    }
+
+    pub fn duplicate_element(parent: &str, element: &str) -> Error {
+        Error::DuplicateElement {
+            parent: parent.to_string(),
+            element: element.to_string(),
+        }
+    }
+
+    pub fn invalid_value(
+        element: &str,
+        attribute: Option<&str>,
+        value: &str,
+        reason: impl fmt::Display,
+    ) -> Error {
+        Error::InvalidValue {
+            element: element.to_string(),
+            attribute: attribute.map(|a| a.to_string()),
+            value: value.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Prepends a location step of the parent element to the error path.
+    pub fn within(self, step: &str) -> Error {
+        match self {
+            Error::Multiple(errors) => Error::Multiple(errors),
+            Error::Located { path, error } => Error::Located {
+                path: format!("{}/{}", step, path),
+                error,
+            },
+            error => Error::Located {
+                path: step.to_string(),
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// Whether the parse was aborted from outside rather than failing on the
+    /// content.
+    pub fn is_interruption(&self) -> bool {
+        matches!(self.cause(), Error::Cancelled | Error::DeadlineExceeded)
+    }
+
+    /// Path of the element where the error occurred, if known.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Error::Located { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// The error without its location.
+    pub fn cause(&self) -> &Error {
+        match self {
+            Error::Located { error, .. } => error,
+            error => error,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        Error::Io {
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<minidom::Error> for Error {
+    fn from(error: minidom::Error) -> Error {
+        Error::Xml(error.to_string())
+    }
+}
+
+impl From<minidom::quick_xml::Error> for Error {
+    fn from(error: minidom::quick_xml::Error) -> Error {
+        Error::Xml(error.to_string())
+    }
+}
+
+/// Lets the operations reporting plain messages use `?` on parser results.
+impl From<Error> for String {
+    fn from(error: Error) -> String {
+        error.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_messages() {
+        assert_eq!(
+            Error::missing_attribute("select", "idref").to_string(),
+            "Element 'select' doesn't have required 'idref' attribute"
+        );
+        assert_eq!(
+            Error::invalid_value(
+                "Rule",
+                Some("severity"),
+                "urgent",
+                "expected one of [\"low\"]"
+            )
+            .to_string(),
+            "Element 'Rule' attribute 'severity'='urgent', but expected one of [\"low\"]"
+        );
+        assert_eq!(
+            Error::Multiple(vec![
+                Error::unexpected_element("foo", Some("xccdf:Rule")),
+                Error::invalid_value("version", None, "", "it is empty").within("Rule"),
+            ])
+            .to_string(),
+            "2 problems found\n  Unexpected element 'foo', expected xccdf:Rule\n  \
+             Rule: Element 'version' has value '', but it is empty"
+        );
+        let error: Error = io::Error::new(io::ErrorKind::NotFound, "missing").into();
+        assert!(matches!(
+            error,
+            Error::Io {
+                kind: io::ErrorKind::NotFound,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,398 @@
+use serde_json::Value;
+use std::fmt::Write;
+
+use crate::locale::{preferred_text, Locale};
+use crate::report::count;
+use crate::richtext::escape_html;
+use crate::utils::format_date_time;
+use crate::xccdf::{Benchmark, ResolvedProfile, Rule, Severity, TestResult, Title};
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em auto; max-width: 70em; color: #222; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; vertical-align: top; }
+table.info th { width: 12em; background: #f4f4f4; }
+pre { background: #f4f4f4; padding: 0.6em; overflow-x: auto; }
+.score { font-size: 1.6em; font-weight: bold; }
+.result-pass, .result-fixed { color: #2e7d32; }
+.result-fail { color: #c62828; font-weight: bold; }
+.result-error, .result-unknown { color: #ef6c00; }
+.result-notapplicable, .result-notchecked, .result-notselected, .result-informational { color: #757575; }
+.severity-high { color: #c62828; }
+.severity-medium { color: #ef6c00; }
+.severity-low { color: #1565c0; }
+";
+
+/// Results whose rules get details with the remediation.
+const FAILED: [&str; 2] = ["fail", "error"];
+
+fn title<'a>(titles: &'a [Title], lang: Option<&str>) -> &'a str {
+    preferred_text(titles, lang).map_or("", |t| t.title.as_str())
+}
+
+fn info_row(html: &mut String, name: &str, value: &str) {
+    if !value.is_empty() {
+        writeln!(
+            html,
+            "<tr><th>{}</th><td>{}</td></tr>",
+            name,
+            escape_html(value)
+        )
+        .unwrap();
+    }
+}
+
+/// Description, fix text and fix scripts of a failed rule.
+fn rule_details(
+    html: &mut String,
+    benchmark: &Benchmark,
+    profile: Option<&ResolvedProfile>,
+    rule: &Rule,
+    severity: Severity,
+    lang: Option<&str>,
+) {
+    let plain_text = |idref: &str| benchmark.find_plain_text(idref).map(String::from);
+    writeln!(html, "<article id=\"rule-{}\">", escape_html(&rule.id)).unwrap();
+    writeln!(html, "<h3>{}</h3>", escape_html(title(&rule.titles, lang))).unwrap();
+    writeln!(
+        html,
+        "<p><code>{}</code>, severity <span class=\"severity-{}\">{}</span></p>",
+        escape_html(&rule.id),
+        severity,
+        severity
+    )
+    .unwrap();
+    if let Some(description) = preferred_text(&rule.descriptions, lang) {
+        writeln!(
+            html,
+            "<div>{}</div>",
+            description.content.to_html(plain_text)
+        )
+        .unwrap();
+    }
+    if rule.fixtexts.is_empty() && rule.fixes.is_empty() {
+        writeln!(html, "</article>").unwrap();
+        return;
+    }
+    writeln!(html, "<h4>Remediation</h4>").unwrap();
+    // the fix texts in other languages are translations of the chosen ones
+    let fixtext_lang = preferred_text(&rule.fixtexts, lang).and_then(|f| f.lang());
+    for fixtext in rule.fixtexts.iter().filter(|f| f.lang() == fixtext_lang) {
+        writeln!(html, "<div>{}</div>", fixtext.content.to_html(plain_text)).unwrap();
+    }
+    for fix in rule.fixes.iter() {
+        let script = profile
+            .and_then(|p| {
+                fix.substitute(|idref| benchmark.value_in_profile(idref, p), |_| None)
+                    .ok()
+            })
+            .unwrap_or_else(|| fix.text());
+        if let Some(system) = &fix.system {
+            writeln!(html, "<p>{}</p>", escape_html(system)).unwrap();
+        }
+        writeln!(
+            html,
+            "<pre>{}</pre>",
+            escape_html(script.trim_matches('\n'))
+        )
+        .unwrap();
+    }
+    writeln!(html, "</article>").unwrap();
+}
+
+/// Renders the TestResult as a standalone HTML page with the target, the
+/// score, the result of every rule and the remediation of the failed rules.
+/// The severities and the values in the fixes are those of the profile
+/// the scan was run with. The titles and texts are in the preferred
+/// language, by default the language of the benchmark.
+pub fn results_report(
+    benchmark: &Benchmark,
+    test_result: &TestResult,
+    lang: Option<&str>,
+) -> String {
+    let lang = lang.or(benchmark.lang());
+    let profile = test_result
+        .profile
+        .as_deref()
+        .and_then(|id| benchmark.resolve_profile(id).ok());
+    let severity = |rule: &Rule| match &profile {
+        Some(profile) => profile.severity(rule),
+        None => rule.severity,
+    };
+    let benchmark_title = match title(&benchmark.titles, lang) {
+        "" => benchmark.id.as_ref(),
+        title => title,
+    };
+    let lang_tag = lang.unwrap_or("en");
+    let dir = match Locale::from_tag(lang_tag).rtl {
+        true => "rtl",
+        false => "ltr",
+    };
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>").unwrap();
+    writeln!(
+        html,
+        "<html lang=\"{}\" dir=\"{}\">",
+        escape_html(lang_tag),
+        dir
+    )
+    .unwrap();
+    writeln!(html, "<head><meta charset=\"utf-8\">").unwrap();
+    writeln!(html, "<title>{}</title>", escape_html(benchmark_title)).unwrap();
+    writeln!(html, "<style>\n{}</style>\n</head>\n<body>", STYLE).unwrap();
+    writeln!(html, "<h1>{}</h1>", escape_html(benchmark_title)).unwrap();
+
+    writeln!(html, "<h2>Evaluation</h2>\n<table class=\"info\">").unwrap();
+    info_row(&mut html, "Benchmark", &benchmark.id);
+    info_row(&mut html, "Content version", &benchmark.version.text);
+    info_row(&mut html, "TestResult", &test_result.id);
+    if let Some(id) = &test_result.profile {
+        let profile_title = benchmark
+            .find_profile(id)
+            .map_or("", |p| title(&p.titles, lang));
+        match profile_title {
+            "" => info_row(&mut html, "Profile", id),
+            title => info_row(&mut html, "Profile", &format!("{} ({})", title, id)),
+        }
+    }
+    for (label, time) in [
+        ("Started", &test_result.start_time),
+        ("Finished", &test_result.end_time),
+    ] {
+        let time = time.as_ref().map(format_date_time).unwrap_or_default();
+        info_row(&mut html, label, &time);
+    }
+    info_row(&mut html, "Target", &test_result.targets.join(", "));
+    info_row(
+        &mut html,
+        "Addresses",
+        &test_result.target_addresses.join(", "),
+    );
+    info_row(
+        &mut html,
+        "User",
+        test_result.identity.as_deref().unwrap_or_default(),
+    );
+    for fact in test_result.target_facts.iter() {
+        info_row(&mut html, &escape_html(&fact.name), &fact.value);
+    }
+    writeln!(html, "</table>").unwrap();
+
+    writeln!(html, "<h2>Score</h2>").unwrap();
+    for score in test_result.scores.iter() {
+        writeln!(
+            html,
+            "<p><span class=\"score\">{:.2} / {:.2}</span> {}</p>",
+            score.value,
+            score.maximum,
+            escape_html(&score.system)
+        )
+        .unwrap();
+    }
+    let counts = count(test_result.rule_results.iter().map(|r| r.result.as_str()));
+    writeln!(html, "<ul>").unwrap();
+    for (result, count) in counts.iter() {
+        writeln!(
+            html,
+            "<li><span class=\"result-{}\">{}</span>: {}</li>",
+            result, result, count
+        )
+        .unwrap();
+    }
+    writeln!(html, "</ul>").unwrap();
+
+    writeln!(html, "<h2>Rules</h2>\n<table class=\"rules\">").unwrap();
+    writeln!(
+        html,
+        "<thead><tr><th>Rule</th><th>Severity</th><th>Result</th></tr></thead>\n<tbody>"
+    )
+    .unwrap();
+    let mut failed = Vec::new();
+    for rule_result in test_result.rule_results.iter() {
+        let rule = benchmark.rule(&rule_result.idref);
+        let severity = rule.map_or(Severity::Unknown, severity);
+        let id = escape_html(&rule_result.idref);
+        let rule_title = match rule.map_or("", |r| title(&r.titles, lang)) {
+            "" => id.clone(),
+            title => escape_html(title),
+        };
+        let rule_title = match (rule, FAILED.contains(&rule_result.result.as_str())) {
+            (Some(rule), true) => {
+                failed.push((rule, severity));
+                format!("<a href=\"#rule-{}\">{}</a>", id, rule_title)
+            }
+            _ => rule_title,
+        };
+        writeln!(
+            html,
+            "<tr><td>{}<br><code>{}</code></td><td class=\"severity-{}\">{}</td>\
+             <td class=\"result-{}\">{}</td></tr>",
+            rule_title, id, severity, severity, rule_result.result, rule_result.result
+        )
+        .unwrap();
+    }
+    writeln!(html, "</tbody>\n</table>").unwrap();
+
+    if !failed.is_empty() {
+        writeln!(html, "<h2>Failed rules</h2>").unwrap();
+        for (rule, severity) in failed {
+            rule_details(&mut html, benchmark, profile.as_ref(), rule, severity, lang);
+        }
+    }
+    writeln!(html, "</body>\n</html>").unwrap();
+    html
+}
+
+/// Fields of the objects in the order they first appear.
+fn columns(items: &[Value]) -> Vec<&str> {
+    let mut columns: Vec<&str> = Vec::new();
+    for name in items
+        .iter()
+        .filter_map(Value::as_object)
+        .flat_map(|o| o.keys())
+    {
+        if !columns.contains(&name.as_str()) {
+            columns.push(name);
+        }
+    }
+    columns
+}
+
+fn value_html(html: &mut String, value: &Value) {
+    match value {
+        Value::Null => {}
+        Value::String(text) => html.push_str(&escape_html(text)),
+        Value::Object(fields) => {
+            writeln!(html, "<table class=\"info\">").unwrap();
+            for (name, field) in fields {
+                write!(html, "<tr><th>{}</th><td>", escape_html(name)).unwrap();
+                value_html(html, field);
+                writeln!(html, "</td></tr>").unwrap();
+            }
+            writeln!(html, "</table>").unwrap();
+        }
+        Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_object) => {
+            let columns = columns(items);
+            writeln!(html, "<table>\n<thead><tr>").unwrap();
+            for column in columns.iter() {
+                write!(html, "<th>{}</th>", escape_html(column)).unwrap();
+            }
+            writeln!(html, "</tr></thead>\n<tbody>").unwrap();
+            for item in items {
+                html.push_str("<tr>");
+                for column in columns.iter() {
+                    html.push_str("<td>");
+                    value_html(html, &item[*column]);
+                    html.push_str("</td>");
+                }
+                writeln!(html, "</tr>").unwrap();
+            }
+            writeln!(html, "</tbody>\n</table>").unwrap();
+        }
+        Value::Array(items) => {
+            html.push_str("<ul>");
+            for item in items {
+                html.push_str("<li>");
+                value_html(html, item);
+                html.push_str("</li>");
+            }
+            html.push_str("</ul>");
+        }
+        other => html.push_str(&other.to_string()),
+    }
+}
+
+/// Renders any report of the command line tool as a page, objects become
+/// tables of their fields and lists of objects tables with a column per
+/// field.
+pub fn value_report(title: &str, value: &Value) -> String {
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>\n<html>").unwrap();
+    writeln!(html, "<head><meta charset=\"utf-8\">").unwrap();
+    writeln!(html, "<title>{}</title>", escape_html(title)).unwrap();
+    writeln!(html, "<style>\n{}</style>\n</head>\n<body>", STYLE).unwrap();
+    writeln!(html, "<h1>{}</h1>", escape_html(title)).unwrap();
+    value_html(&mut html, value);
+    writeln!(html, "</body>\n</html>").unwrap();
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xccdf::XCCDF12_NS;
+    use minidom::Element;
+
+    #[test]
+    fn test_results_report() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><title>Guide &amp; more</title>\
+             <version>1</version><Profile id=\"p\"><title>Strict</title>\
+             <refine-rule idref=\"r1\" severity=\"high\"/></Profile>\
+             <Value id=\"v\"><value>027</value></Value>\
+             <Rule id=\"r1\" severity=\"low\"><title>Set umask</title>\
+             <description>Set the <code>umask</code>.</description>\
+             <fixtext>Edit /etc/profile.</fixtext>\
+             <fix system=\"urn:xccdf:fix:script:sh\">umask <sub idref=\"v\"/></fix></Rule>\
+             <Rule id=\"r2\"><title>Other</title></Rule>\
+             <TestResult id=\"t\" end-time=\"2024-01-01T00:00:00\"><benchmark href=\"#b\"/>\
+             <profile idref=\"p\"/><target>host&lt;1&gt;</target><target-address>10.0.0.1</target-address>\
+             <rule-result idref=\"r1\"><result>fail</result></rule-result>\
+             <rule-result idref=\"r2\"><result>pass</result></rule-result>\
+             <score maximum=\"100\">50</score></TestResult></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let html = results_report(&benchmark, benchmark.test_result(None).unwrap(), None);
+        assert!(html.contains("<title>Guide &amp; more</title>"));
+        assert!(html.contains("<tr><th>Profile</th><td>Strict (p)</td></tr>"));
+        assert!(html.contains("<tr><th>Target</th><td>host&lt;1&gt;</td></tr>"));
+        assert!(html.contains("<span class=\"score\">50.00 / 100.00</span>"));
+        assert!(html.contains(
+            "<tr><td><a href=\"#rule-r1\">Set umask</a><br><code>r1</code></td>\
+             <td class=\"severity-high\">high</td><td class=\"result-fail\">fail</td></tr>"
+        ));
+        assert!(html.contains("<pre>umask 027</pre>"));
+        assert!(!html.contains("id=\"rule-r2\""));
+    }
+
+    #[test]
+    fn test_results_report_language() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\" xml:lang=\"en-US\"><status>draft</status>\
+             <title>Guide</title><title xml:lang=\"he\">מדריך</title><version>1</version>\
+             <Rule id=\"r1\"><title>Set umask</title><title xml:lang=\"he\">הגדר umask</title>\
+             <description>English</description><description xml:lang=\"he\">עברית</description>\
+             <fixtext xml:lang=\"en\">Edit it.</fixtext><fixtext xml:lang=\"he\">ערוך</fixtext></Rule>\
+             <TestResult id=\"t\" end-time=\"2022-01-01T00:00:00\"><benchmark href=\"#b\"/>\
+             <rule-result idref=\"r1\"><result>fail</result></rule-result></TestResult></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let test_result = benchmark.test_result(None).unwrap();
+        let html = results_report(&benchmark, test_result, Some("he-IL"));
+        assert!(html.contains("<html lang=\"he-IL\" dir=\"rtl\">"));
+        assert!(html.contains("<h1>מדריך</h1>"));
+        assert!(html.contains("<h3>הגדר umask</h3>"));
+        assert!(html.contains("<div>עברית</div>\n<h4>Remediation</h4>\n<div>ערוך</div>"));
+        let html = results_report(&benchmark, test_result, None);
+        assert!(html.contains("<html lang=\"en-US\" dir=\"ltr\">"));
+        assert!(html.contains("<div>English</div>\n<h4>Remediation</h4>\n<div>Edit it.</div>\n<"));
+    }
+    #[test]
+    fn test_value_report() {
+        let value = serde_json::json!({
+            "benchmark": "b & c",
+            "rules": [{"id": "r1", "severity": "high"}, {"id": "r2", "title": "R2"}],
+            "profiles": ["p1"],
+        });
+        let html = value_report("Stats", &value);
+        assert!(html.contains("<tr><th>benchmark</th><td>b &amp; c</td></tr>"));
+        assert!(html.contains("<th>id</th><th>severity</th><th>title</th>"));
+        assert!(html.contains("<tr><td>r2</td><td></td><td>R2</td></tr>"));
+        assert!(html.contains("<ul><li>p1</li></ul>"));
+    }
+}
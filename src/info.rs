@@ -0,0 +1,90 @@
+use serde::Serialize;
+
+use crate::oval::CveCoverage;
+
+#[derive(Debug, Serialize)]
+pub struct CollectionInfo {
+    pub document_type: String,
+    pub id: String,
+    pub data_streams: Vec<DataStreamInfo>,
+    /// Subjects of the certificates of the collection signatures
+    pub signers: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DataStreamInfo {
+    pub id: String,
+    pub use_case: String,
+    pub scap_version: String,
+    pub checklists: Vec<ChecklistInfo>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extended_components: Vec<ExtendedComponentInfo>,
+    /// CVEs covered by a vulnerability data stream
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub vulnerabilities: Vec<CveCoverage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChecklistInfo {
+    pub ref_id: String,
+    pub href: String,
+    pub component_id: Option<String>,
+    pub benchmark: Option<BenchmarkInfo>,
+    pub check_refs: Vec<CheckRefInfo>,
+}
+
+/// Extended component of a data stream with the root element of its
+/// payload.
+#[derive(Debug, Serialize)]
+pub struct ExtendedComponentInfo {
+    pub ref_id: String,
+    pub href: String,
+    pub component_id: Option<String>,
+    pub element_name: Option<String>,
+    pub element_ns: Option<String>,
+}
+
+/// `check-content-ref` href of the checklist and the component it resolves
+/// to through the catalog.
+#[derive(Debug, Serialize)]
+pub struct CheckRefInfo {
+    pub href: String,
+    pub resolved: String,
+    pub component_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BenchmarkInfo {
+    pub id: String,
+    pub profiles: Vec<ProfileInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProfileInfo {
+    pub id: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResolvedProfileInfo {
+    pub id: String,
+    pub title: Option<String>,
+    pub inheritance: Vec<String>,
+    pub selected: Vec<String>,
+    pub unselected: Vec<String>,
+    pub values: Vec<SetValueInfo>,
+    pub complex_values: Vec<SetComplexValueInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SetValueInfo {
+    pub idref: String,
+    pub value: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SetComplexValueInfo {
+    pub idref: String,
+    pub items: Vec<String>,
+}
@@ -0,0 +1,926 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::ops::Deref;
+use std::path::Path;
+#[cfg(feature = "network")]
+use std::{
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime},
+};
+
+#[cfg(feature = "network")]
+use crate::utils::sha256_hex;
+
+/// Compression of an input file, decompressed on the fly while reading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Bzip2,
+    Xz,
+    /// Zip archive, e.g. a SCAP 1.3 content bundle
+    Zip,
+}
+
+impl Compression {
+    /// Recognizes the compression by the magic bytes at the start of the
+    /// file, or by the extension of the path.
+    pub fn detect(path: &Path, head: &[u8]) -> Option<Compression> {
+        if head.starts_with(&[0x1f, 0x8b]) {
+            return Some(Compression::Gzip);
+        }
+        if head.starts_with(b"BZh") {
+            return Some(Compression::Bzip2);
+        }
+        if head.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            return Some(Compression::Xz);
+        }
+        if head.starts_with(b"PK\x03\x04") {
+            return Some(Compression::Zip);
+        }
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("gz") => Some(Compression::Gzip),
+            Some("bz2") => Some(Compression::Bzip2),
+            Some("xz") => Some(Compression::Xz),
+            Some("zip") => Some(Compression::Zip),
+            _ => None,
+        }
+    }
+
+    /// Decompressing reader of a gzip, bzip2 or xz stream. Concatenated
+    /// streams are read one after another, like the command line tools do.
+    fn decoder<'a, R: BufRead + 'a>(&self, reader: R) -> Box<dyn BufRead + 'a> {
+        match self {
+            Compression::Gzip => {
+                Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(reader)))
+            }
+            Compression::Bzip2 => {
+                Box::new(BufReader::new(bzip2::bufread::MultiBzDecoder::new(reader)))
+            }
+            Compression::Xz => Box::new(BufReader::new(
+                xz2::bufread::XzDecoder::new_multi_decoder(reader),
+            )),
+            Compression::Zip => unreachable!("zip archives are read by entries"),
+        }
+    }
+}
+
+/// Path that stands for the standard input.
+pub const STDIN: &str = "-";
+
+/// Whether the path stands for the standard input.
+pub fn is_stdin(path: &Path) -> bool {
+    path == Path::new(STDIN)
+}
+
+/// Whether the path is an http(s) URL.
+pub fn is_url(path: &Path) -> bool {
+    path.to_str()
+        .is_some_and(|p| p.starts_with("http://") || p.starts_with("https://"))
+}
+
+#[cfg(feature = "network")]
+/// Limits of downloading a document from a URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchOptions {
+    pub timeout: Duration,
+    /// Maximum size of the document in bytes
+    pub max_size: u64,
+    /// Directory where the downloads are kept, see [`cached_download`]
+    pub cache_dir: Option<PathBuf>,
+}
+
+#[cfg(feature = "network")]
+impl Default for FetchOptions {
+    fn default() -> Self {
+        FetchOptions {
+            timeout: Duration::from_secs(60),
+            max_size: 1 << 30,
+            cache_dir: None,
+        }
+    }
+}
+
+#[cfg(feature = "network")]
+/// Default cache directory of the user, `$XDG_CACHE_HOME/oscapxml` or
+/// `~/.cache/oscapxml`.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CACHE_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+    };
+    Some(base.join("oscapxml"))
+}
+
+#[cfg(feature = "network")]
+/// Document downloaded into a temporary directory, which is removed on drop.
+/// The file keeps the name from the URL, so compressed documents and zip
+/// archives are recognized by the extension too.
+#[derive(Debug)]
+pub struct Download {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+#[cfg(feature = "network")]
+impl Download {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(feature = "network")]
+impl Drop for Download {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[cfg(feature = "network")]
+static DOWNLOADS: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(feature = "network")]
+fn url_file_name(url: &str) -> &str {
+    url.split(['?', '#'])
+        .next()
+        .and_then(|u| u.rsplit('/').next())
+        .filter(|name| !name.is_empty() && *name != "." && *name != "..")
+        .unwrap_or("download.xml")
+}
+
+#[cfg(feature = "network")]
+fn too_large(opts: &FetchOptions) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("The document is larger than {} bytes", opts.max_size),
+    )
+}
+
+#[cfg(feature = "network")]
+fn timed_out(opts: &FetchOptions) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::TimedOut,
+        format!("The download timed out after {:?}", opts.timeout),
+    )
+}
+
+#[cfg(feature = "network")]
+fn request_failed(url: &str, opts: &FetchOptions, error: reqwest::Error) -> io::Error {
+    if error.is_timeout() {
+        timed_out(opts)
+    } else {
+        io::Error::other(format!("Failed to download '{}': {}", url, error))
+    }
+}
+
+#[cfg(feature = "network")]
+/// Date of an `If-Modified-Since` header.
+fn http_date(time: SystemTime) -> String {
+    let time = chrono::DateTime::<chrono::Utc>::from(time);
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+#[cfg(feature = "network")]
+/// Downloads the document into `output`. With `newer_than`, the document is
+/// downloaded only if it was modified after that file, otherwise `output`
+/// isn't created.
+fn fetch(
+    url: &str,
+    output: &Path,
+    opts: &FetchOptions,
+    newer_than: Option<&Path>,
+) -> io::Result<()> {
+    let failed = |error| request_failed(url, opts, error);
+    let client = reqwest::blocking::Client::builder()
+        .timeout(opts.timeout)
+        .build()
+        .map_err(failed)?;
+    let mut request = client.get(url);
+    if let Some(modified) = newer_than.and_then(|file| file.metadata().ok()?.modified().ok()) {
+        request = request.header(reqwest::header::IF_MODIFIED_SINCE, http_date(modified));
+    }
+    let response = request
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(failed)?;
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(());
+    }
+    if response.content_length().unwrap_or(0) > opts.max_size {
+        return Err(too_large(opts));
+    }
+    // The size isn't known in advance for chunked responses.
+    let mut file = File::create(output)?;
+    let copied = io::copy(&mut response.take(opts.max_size + 1), &mut file).map_err(|error| {
+        let inner = error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<reqwest::Error>());
+        match inner {
+            Some(inner) if inner.is_timeout() => timed_out(opts),
+            _ => error,
+        }
+    })?;
+    if copied > opts.max_size {
+        drop(file);
+        let _ = std::fs::remove_file(output);
+        return Err(too_large(opts));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "network")]
+/// Empty temporary directory for the document, named like in the URL.
+fn temp_download(url: &str) -> io::Result<Download> {
+    let dir = std::env::temp_dir().join(format!(
+        "oscapxml-download-{}-{}",
+        process::id(),
+        DOWNLOADS.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&dir)?;
+    Ok(Download {
+        path: dir.join(url_file_name(url)),
+        dir,
+    })
+}
+
+#[cfg(feature = "network")]
+/// Downloads the document into a temporary directory.
+pub fn download(url: &str, opts: &FetchOptions) -> io::Result<Download> {
+    let download = temp_download(url)?;
+    fetch(url, &download.path, opts, None)?;
+    Ok(download)
+}
+
+#[cfg(feature = "network")]
+/// Files of a cached document, named by the SHA-256 of its URL.
+struct CachePaths {
+    path: PathBuf,
+    checksum_path: PathBuf,
+    /// Download in progress
+    partial: PathBuf,
+}
+
+#[cfg(feature = "network")]
+impl CachePaths {
+    fn new(url: &str, cache_dir: &Path) -> CachePaths {
+        let key = sha256_hex(url.as_bytes());
+        CachePaths {
+            path: cache_dir.join(format!("{}-{}", key, url_file_name(url))),
+            checksum_path: cache_dir.join(format!("{}.sha256", key)),
+            partial: cache_dir.join(format!("{}.part", key)),
+        }
+    }
+}
+
+#[cfg(feature = "network")]
+fn is_valid_copy(data: Option<&[u8]>, checksum: Option<&str>) -> bool {
+    match (data, checksum) {
+        (Some(data), Some(checksum)) => sha256_hex(data) == checksum.trim(),
+        _ => false,
+    }
+}
+
+#[cfg(feature = "network")]
+/// Downloads the document into the cache directory unless the cached copy
+/// is up to date. The copy is named by the SHA-256 of the URL and its
+/// content is checked against the SHA-256 stored next to it. When the
+/// download fails, e.g. offline, a valid cached copy is used.
+pub fn cached_download(url: &str, cache_dir: &Path, opts: &FetchOptions) -> io::Result<PathBuf> {
+    std::fs::create_dir_all(cache_dir)?;
+    let CachePaths {
+        path,
+        checksum_path,
+        partial,
+    } = CachePaths::new(url, cache_dir);
+    let valid = is_valid_copy(
+        std::fs::read(&path).ok().as_deref(),
+        std::fs::read_to_string(&checksum_path).ok().as_deref(),
+    );
+    let _ = std::fs::remove_file(&partial);
+    match fetch(url, &partial, opts, valid.then_some(path.as_path())) {
+        Ok(()) if partial.exists() => {
+            let checksum = sha256_hex(&std::fs::read(&partial)?);
+            std::fs::rename(&partial, &path)?;
+            std::fs::write(&checksum_path, checksum)?;
+            Ok(path)
+        }
+        Ok(()) if valid => Ok(path),
+        Ok(()) => Err(io::Error::other(format!(
+            "Failed to download '{}': nothing was received",
+            url
+        ))),
+        Err(_) if valid => {
+            let _ = std::fs::remove_file(&partial);
+            Ok(path)
+        }
+        Err(error) => {
+            let _ = std::fs::remove_file(&partial);
+            Err(error)
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+/// Downloads the document, or returns `None` if it wasn't modified after
+/// `newer_than`.
+async fn request(
+    url: &str,
+    opts: &FetchOptions,
+    newer_than: Option<SystemTime>,
+) -> io::Result<Option<Vec<u8>>> {
+    let failed = |error| request_failed(url, opts, error);
+    let client = reqwest::Client::builder()
+        .timeout(opts.timeout)
+        .build()
+        .map_err(failed)?;
+    let mut request = client.get(url);
+    if let Some(modified) = newer_than {
+        request = request.header(reqwest::header::IF_MODIFIED_SINCE, http_date(modified));
+    }
+    let mut response = request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(failed)?;
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
+    if response.content_length().unwrap_or(0) > opts.max_size {
+        return Err(too_large(opts));
+    }
+    let mut data = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(failed)? {
+        data.extend_from_slice(&chunk);
+        if data.len() as u64 > opts.max_size {
+            return Err(too_large(opts));
+        }
+    }
+    Ok(Some(data))
+}
+
+/// Downloads the document by reqwest without blocking the async runtime.
+/// With a cache directory, the document is cached like by
+/// [`cached_download`]: a valid cached copy is downloaded again only when
+/// it has changed on the server, and it's used when the download fails.
+#[cfg(feature = "async")]
+pub async fn download_async(url: &str, opts: &FetchOptions) -> io::Result<Vec<u8>> {
+    let cache_dir = match &opts.cache_dir {
+        Some(cache_dir) => cache_dir,
+        None => {
+            let data = request(url, opts, None).await?;
+            return data.ok_or_else(|| io::Error::other("Unexpected 304 Not Modified"));
+        }
+    };
+    let CachePaths {
+        path,
+        checksum_path,
+        partial,
+    } = CachePaths::new(url, cache_dir);
+    let data = tokio::fs::read(&path).await.ok();
+    let checksum = tokio::fs::read_to_string(&checksum_path).await.ok();
+    let cached = data.filter(|data| is_valid_copy(Some(data), checksum.as_deref()));
+    let modified = match &cached {
+        Some(_) => tokio::fs::metadata(&path).await?.modified().ok(),
+        None => None,
+    };
+    match (request(url, opts, modified).await, cached) {
+        (Ok(Some(data)), _) => {
+            tokio::fs::create_dir_all(cache_dir).await?;
+            tokio::fs::write(&partial, &data).await?;
+            tokio::fs::rename(&partial, &path).await?;
+            tokio::fs::write(&checksum_path, sha256_hex(&data)).await?;
+            Ok(data)
+        }
+        (Ok(None), Some(cached)) | (Err(_), Some(cached)) => Ok(cached),
+        (Ok(None), None) => Err(io::Error::other(format!(
+            "Failed to download '{}': nothing was received",
+            url
+        ))),
+        (Err(error), None) => Err(error),
+    }
+}
+
+fn detect_file(path: &Path) -> io::Result<Option<Compression>> {
+    let mut head = Vec::with_capacity(6);
+    File::open(path)?.take(6).read_to_end(&mut head)?;
+    Ok(Compression::detect(path, &head))
+}
+
+/// Names of the files in a zip archive.
+pub fn zip_entries(path: &Path) -> io::Result<Vec<String>> {
+    let archive = zip::ZipArchive::new(File::open(path)?)?;
+    Ok(archive
+        .file_names()
+        .filter(|name| !name.ends_with('/'))
+        .map(|name| name.to_string())
+        .collect())
+}
+
+/// Picks the data stream among the files of a content bundle: the file
+/// named like `*-ds.xml`, otherwise the only XML file.
+pub fn find_data_stream(entries: &[String]) -> Option<&str> {
+    let named: Vec<&String> = entries
+        .iter()
+        .filter(|e| e.ends_with("-ds.xml") || e.ends_with("-datastream.xml"))
+        .collect();
+    let xml: Vec<&String> = entries.iter().filter(|e| e.ends_with(".xml")).collect();
+    match (named.as_slice(), xml.as_slice()) {
+        ([entry], _) | ([], [entry]) => Some(entry.as_str()),
+        _ => None,
+    }
+}
+
+/// Maximum size of a file extracted from a zip archive, which is read into
+/// memory.
+const MAX_ENTRY_SIZE: u64 = 1 << 30;
+
+/// Reads the whole input, failing if it's larger than `limit` bytes. The
+/// buffer grows as the data is read, because `size_hint` comes from the
+/// input and can't be trusted.
+fn read_limited<R: Read>(reader: R, size_hint: u64, limit: u64) -> io::Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(size_hint.min(limit).min(1 << 20) as usize);
+    reader.take(limit + 1).read_to_end(&mut data)?;
+    if data.len() as u64 > limit {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("The file is larger than {} bytes", limit),
+        ));
+    }
+    Ok(data)
+}
+
+/// Reader of the content of a file, decompressed on the fly if it's
+/// compressed. Of a zip archive, the data stream is read.
+pub fn open_reader<P: AsRef<Path>>(path: P) -> io::Result<Box<dyn BufRead>> {
+    open_entry_reader(path.as_ref(), None)
+}
+
+/// Like [`open_reader`], but reads the given file of a zip archive.
+pub fn open_entry_reader(path: &Path, entry: Option<&str>) -> io::Result<Box<dyn BufRead>> {
+    if is_stdin(path) && entry.is_none() {
+        return Ok(Box::new(io::stdin().lock()));
+    }
+    if is_url(path) {
+        let input = Input::open_entry(path, entry)?;
+        return Ok(Box::new(io::Cursor::new(input.to_vec())));
+    }
+    let compression = match is_stdin(path) {
+        true => None,
+        false => detect_file(path)?,
+    };
+    if entry.is_some() && compression != Some(Compression::Zip) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Only files of zip archives can be selected",
+        ));
+    }
+    let reader: Box<dyn BufRead> = match compression {
+        Some(Compression::Zip) => {
+            let entries = zip_entries(path)?;
+            let entry = match entry {
+                Some(entry) if entries.iter().any(|e| e == entry) => entry,
+                Some(entry) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("File '{}' not found in the archive", entry),
+                    ))
+                }
+                None => find_data_stream(&entries).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!(
+                            "No single data stream in the archive, the files are: {}",
+                            entries.join(", ")
+                        ),
+                    )
+                })?,
+            };
+            // Entries borrow the archive, so the entry is extracted in
+            // memory; the checksum is verified by the end of the read.
+            let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+            let file = archive.by_name(entry)?;
+            let size = file.size();
+            let data = read_limited(file, size, MAX_ENTRY_SIZE)?;
+            Box::new(io::Cursor::new(data))
+        }
+        Some(compression) => compression.decoder(BufReader::new(File::open(path)?)),
+        None => Box::new(BufReader::new(File::open(path)?)),
+    };
+    Ok(reader)
+}
+
+#[cfg(all(feature = "mmap", unix))]
+mod mapping {
+    use std::fs::File;
+    use std::io;
+    use std::os::unix::io::AsRawFd;
+
+    /// Read-only private mapping of a whole file. The pages are read from
+    /// the file as they're accessed, so a file modified while it's mapped
+    /// may be seen partly modified, and accessing the pages past the end
+    /// of a truncated file kills the process with SIGBUS.
+    pub struct Mapping {
+        ptr: *mut libc::c_void,
+        len: usize,
+    }
+
+    impl Mapping {
+        /// Maps the file, which mustn't be empty.
+        pub fn new(file: &File, len: usize) -> io::Result<Mapping> {
+            // SAFETY: the mapping is read-only and private, the pointer is
+            // checked before use and unmapped only on drop. A private
+            // mapping doesn't copy the pages not written to, the content
+            // may still change, or disappear with a SIGBUS on access, if
+            // another process modifies or truncates the file; mapped files
+            // are expected to be left alone while they are parsed.
+            let ptr = unsafe {
+                libc::mmap(
+                    std::ptr::null_mut(),
+                    len,
+                    libc::PROT_READ,
+                    libc::MAP_PRIVATE,
+                    file.as_raw_fd(),
+                    0,
+                )
+            };
+            if ptr == libc::MAP_FAILED {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Mapping { ptr, len })
+        }
+
+        pub fn as_slice(&self) -> &[u8] {
+            // SAFETY: the mapping covers `len` readable bytes while `self`
+            // is alive, as long as the file isn't truncated meanwhile (see
+            // `new`).
+            unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+        }
+    }
+
+    // SAFETY: the mapping is read-only and owned by `Mapping`, so it can be
+    // read and unmapped from any thread.
+    unsafe impl Send for Mapping {}
+    unsafe impl Sync for Mapping {}
+
+    impl Drop for Mapping {
+        fn drop(&mut self) {
+            // SAFETY: the pointer and length come from a successful mmap.
+            unsafe {
+                libc::munmap(self.ptr, self.len);
+            }
+        }
+    }
+}
+
+enum Content {
+    Owned(Vec<u8>),
+    #[cfg(all(feature = "mmap", unix))]
+    Mapped(mapping::Mapping),
+}
+
+/// Content of an input file, decompressed if it's compressed with gzip,
+/// bzip2 or xz. The path `-` stands for the standard input, which is read as
+/// is, an http(s) URL is downloaded with the default [`FetchOptions`].
+///
+/// With the `mmap` feature, an uncompressed file is mapped into memory
+/// instead of read, so the XML events are parsed directly from the page
+/// cache. The file mustn't be modified or truncated while it's mapped, a
+/// truncated file makes the process crash with SIGBUS.
+pub struct Input {
+    content: Content,
+}
+
+impl Input {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Input> {
+        Input::open_entry(path, None)
+    }
+
+    /// Opens the file, or the given file of a zip archive.
+    pub fn open_entry<P: AsRef<Path>>(path: P, entry: Option<&str>) -> io::Result<Input> {
+        let path = path.as_ref();
+        if is_url(path) {
+            #[cfg(feature = "network")]
+            return Input::open_url(
+                path.to_str().unwrap_or_default(),
+                entry,
+                &FetchOptions::default(),
+            );
+            #[cfg(not(feature = "network"))]
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "URLs can't be opened without the network feature",
+            ));
+        }
+        if is_stdin(path) || entry.is_some() || detect_file(path)?.is_some() {
+            let mut data = Vec::new();
+            open_entry_reader(path, entry)?.read_to_end(&mut data)?;
+            return Ok(Input {
+                content: Content::Owned(data),
+            });
+        }
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len() as usize;
+        #[cfg(all(feature = "mmap", unix))]
+        if len > 0 {
+            let content = Content::Mapped(mapping::Mapping::new(&file, len)?);
+            return Ok(Input { content });
+        }
+        let mut data = Vec::with_capacity(len);
+        file.read_to_end(&mut data)?;
+        Ok(Input {
+            content: Content::Owned(data),
+        })
+    }
+
+    /// Downloads the document, or the zip archive with the given file.
+    #[cfg(feature = "network")]
+    pub fn open_url(url: &str, entry: Option<&str>, opts: &FetchOptions) -> io::Result<Input> {
+        if let Some(cache_dir) = &opts.cache_dir {
+            return Input::open_entry(cached_download(url, cache_dir, opts)?, entry);
+        }
+        let download = download(url, opts)?;
+        Input::open_entry(download.path(), entry)
+    }
+
+    /// Downloads the document, or the zip archive with the given file,
+    /// without blocking the async runtime. Compressed documents and zip
+    /// archives are extracted on a blocking thread.
+    #[cfg(feature = "async")]
+    pub async fn open_url_async(
+        url: &str,
+        entry: Option<&str>,
+        opts: &FetchOptions,
+    ) -> io::Result<Input> {
+        let data = download_async(url, opts).await?;
+        let name = Path::new(url_file_name(url));
+        if entry.is_none() && Compression::detect(name, &data).is_none() {
+            return Ok(Input {
+                content: Content::Owned(data),
+            });
+        }
+        let download = temp_download(url)?;
+        tokio::fs::write(download.path(), &data).await?;
+        let entry = entry.map(String::from);
+        let data = tokio::task::spawn_blocking(move || -> io::Result<Vec<u8>> {
+            let mut data = Vec::new();
+            open_entry_reader(download.path(), entry.as_deref())?.read_to_end(&mut data)?;
+            Ok(data)
+        })
+        .await
+        .map_err(io::Error::other)??;
+        Ok(Input {
+            content: Content::Owned(data),
+        })
+    }
+
+    /// Whether the file is memory-mapped.
+    pub fn is_mapped(&self) -> bool {
+        !matches!(self.content, Content::Owned(_))
+    }
+}
+
+impl Deref for Input {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match &self.content {
+            Content::Owned(data) => data,
+            #[cfg(all(feature = "mmap", unix))]
+            Content::Mapped(mapping) => mapping.as_slice(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_open_input() {
+        let input = Input::open("data/simple.xml").unwrap();
+        assert_eq!(&input[..], &std::fs::read("data/simple.xml").unwrap()[..]);
+        assert_eq!(input.is_mapped(), cfg!(all(feature = "mmap", unix)));
+        assert!(Input::open("data/missing.xml").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "network")]
+    fn test_url_input() {
+        assert!(is_url(Path::new("https://example.org/ssg-rhel9-ds.xml")));
+        assert!(!is_url(Path::new("data/simple.xml")));
+        let opts = FetchOptions {
+            timeout: Duration::from_secs(5),
+            ..FetchOptions::default()
+        };
+        assert!(Input::open_url("http://127.0.0.1:1/simple.xml", None, &opts).is_err());
+    }
+
+    /// Serves the responses to the given number of requests on a local port,
+    /// `304 Not Modified` to the conditional ones.
+    #[cfg(feature = "network")]
+    fn serve(body: &'static [u8], requests: usize) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/simple.xml", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut request = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                while reader.read_line(&mut request).unwrap() > 2 {
+                    if request.ends_with("\r\n\r\n") {
+                        break;
+                    }
+                }
+                let request = request.to_ascii_lowercase();
+                if request.contains("if-modified-since:") {
+                    write!(
+                        stream,
+                        "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n"
+                    )
+                    .unwrap();
+                    continue;
+                }
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .unwrap();
+                stream.write_all(body).unwrap();
+            }
+        });
+        url
+    }
+
+    #[test]
+    #[cfg(feature = "network")]
+    fn test_download() {
+        let data = std::fs::read("data/simple.xml").unwrap();
+        let body: &'static [u8] = data.clone().leak();
+        let url = serve(body, 4);
+        let opts = FetchOptions {
+            timeout: Duration::from_secs(5),
+            ..FetchOptions::default()
+        };
+        let input = Input::open_url(&url, None, &opts).unwrap();
+        assert_eq!(&input[..], &data[..]);
+        let small = FetchOptions {
+            max_size: 100,
+            ..opts.clone()
+        };
+        let error = download(&url, &small).unwrap_err();
+        assert!(error.to_string().contains("larger than 100 bytes"));
+        // The cached copy is downloaded once, then only revalidated.
+        let cache_dir =
+            std::env::temp_dir().join(format!("oscapxml-revalidate-{}", std::process::id()));
+        let path = cached_download(&url, &cache_dir, &opts).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+        assert_eq!(cached_download(&url, &cache_dir, &opts).unwrap(), path);
+        assert!(!CachePaths::new(&url, &cache_dir).partial.exists());
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "network")]
+    fn test_cached_download() {
+        let cache_dir = std::env::temp_dir().join(format!("oscapxml-cache-{}", std::process::id()));
+        let url = "http://127.0.0.1:1/simple.xml";
+        let opts = FetchOptions {
+            timeout: Duration::from_secs(5),
+            ..FetchOptions::default()
+        };
+        assert!(cached_download(url, &cache_dir, &opts).is_err());
+        // The server is unreachable, so the cached copy is used.
+        let key = sha256_hex(url.as_bytes());
+        let data = std::fs::read("data/simple.xml").unwrap();
+        std::fs::write(cache_dir.join(format!("{}-simple.xml", key)), &data).unwrap();
+        std::fs::write(cache_dir.join(format!("{}.sha256", key)), sha256_hex(&data)).unwrap();
+        let path = cached_download(url, &cache_dir, &opts).unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), data);
+        // A corrupted copy isn't.
+        std::fs::write(cache_dir.join(format!("{}.sha256", key)), "0").unwrap();
+        assert!(cached_download(url, &cache_dir, &opts).is_err());
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn test_download_async() {
+        fn is_send<T: Send>(_: &T) {}
+        let cache_dir =
+            std::env::temp_dir().join(format!("oscapxml-async-cache-{}", std::process::id()));
+        let url = "http://127.0.0.1:1/simple.xml";
+        let mut opts = FetchOptions {
+            timeout: Duration::from_secs(5),
+            ..FetchOptions::default()
+        };
+        let parse_opts = crate::options::ParseOptions::default();
+        is_send(&crate::sds::DataStreamCollection::from_url_async(
+            url,
+            &parse_opts,
+            &opts,
+        ));
+        assert!(download_async(url, &opts).await.is_err());
+        // The server is unreachable, so the cached copy is used.
+        opts.cache_dir = Some(cache_dir.clone());
+        let paths = CachePaths::new(url, &cache_dir);
+        let data = std::fs::read("data/simple.xml").unwrap();
+        std::fs::create_dir_all(&cache_dir).unwrap();
+        std::fs::write(&paths.path, &data).unwrap();
+        std::fs::write(&paths.checksum_path, sha256_hex(&data)).unwrap();
+        let input = Input::open_url_async(url, None, &opts).await.unwrap();
+        assert_eq!(&input[..], &data[..]);
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+
+        // The cached copy is downloaded once, then only revalidated.
+        let body = String::from_utf8(data)
+            .unwrap()
+            .replace("id=\"oval:x:def:1\"", "");
+        let body: &'static [u8] = body.into_bytes().leak();
+        let url = serve(body, 2);
+        let parse_opts = crate::options::ParseOptions::lenient();
+        let collection = crate::sds::DataStreamCollection::from_url_async(&url, &parse_opts, &opts)
+            .await
+            .unwrap();
+        assert!(collection
+            .components()
+            .iter()
+            .all(|c| c.oval_definitions().is_none()));
+        assert_eq!(parse_opts.take_warnings().len(), 1);
+        assert_eq!(&download_async(&url, &opts).await.unwrap()[..], body);
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_limited() {
+        let data = read_limited(&b"12345"[..], u64::MAX, 5).unwrap();
+        assert_eq!(data, b"12345");
+        let error = read_limited(&b"123456"[..], 1, 5).unwrap_err();
+        assert_eq!(error.to_string(), "The file is larger than 5 bytes");
+    }
+
+    #[test]
+    fn test_find_data_stream() {
+        let entries: Vec<String> = ["README.txt", "ssg-rhel9-ds.xml", "ssg-rhel9-oval.xml"]
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(find_data_stream(&entries), Some("ssg-rhel9-ds.xml"));
+        assert_eq!(find_data_stream(&entries[2..]), Some("ssg-rhel9-oval.xml"));
+        assert_eq!(find_data_stream(&[]), None);
+        let dir = std::env::temp_dir().join(format!("oscapxml-zip-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::copy("data/simple.xml", dir.join("simple-ds.xml")).unwrap();
+        let archive = dir.join("bundle.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&archive).unwrap());
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        writer.add_directory("docs/", options).unwrap();
+        writer.start_file("docs/README.txt", options).unwrap();
+        writer.write_all(b"Content bundle").unwrap();
+        writer.start_file("simple-ds.xml", options).unwrap();
+        writer
+            .write_all(&std::fs::read("data/simple.xml").unwrap())
+            .unwrap();
+        writer.finish().unwrap();
+        assert_eq!(
+            zip_entries(&archive).unwrap(),
+            vec!["docs/README.txt", "simple-ds.xml"]
+        );
+        let input = Input::open(&archive).unwrap();
+        assert_eq!(&input[..], &std::fs::read("data/simple.xml").unwrap()[..]);
+        let readme = Input::open_entry(&archive, Some("docs/README.txt")).unwrap();
+        assert_eq!(&readme[..], b"Content bundle");
+        assert!(Input::open_entry(&archive, Some("missing.xml")).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_compressed_input() {
+        let path = Path::new("data/simple.xml");
+        assert_eq!(Compression::detect(path, b"<?xml"), None);
+        assert_eq!(
+            Compression::detect(Path::new("feed.xml.bz2"), b""),
+            Some(Compression::Bzip2)
+        );
+        assert_eq!(
+            Compression::detect(path, &[0x1f, 0x8b, 0x08]),
+            Some(Compression::Gzip)
+        );
+        let expected = std::fs::read(path).unwrap();
+        for compressed in ["simple.xml.gz", "simple.xml.bz2", "simple.xml.xz"] {
+            let compressed = Path::new("data/compressed").join(compressed);
+            let input = Input::open(&compressed).unwrap();
+            assert_eq!(&input[..], &expected[..], "{}", compressed.display());
+        }
+        let mut corrupted = std::fs::read("data/compressed/simple.xml.gz").unwrap();
+        corrupted.truncate(corrupted.len() / 2);
+        let truncated =
+            std::env::temp_dir().join(format!("oscapxml-input-{}.xml.gz", std::process::id()));
+        std::fs::write(&truncated, corrupted).unwrap();
+        assert!(Input::open(&truncated).is_err());
+        std::fs::remove_file(&truncated).unwrap();
+    }
+}
@@ -0,0 +1,191 @@
+use serde::Serialize;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::doctor::{describe, Kind};
+use crate::misc;
+use crate::output::{format_size, Table};
+use crate::oval::OvalDefinitions;
+use crate::sds::DataStreamCollection;
+use crate::xccdf::Benchmark;
+
+/// Type and content counts of one file of a content release.
+#[derive(Debug, Default, Serialize)]
+pub struct FileSummary {
+    pub path: String,
+    pub size: u64,
+    /// Description of the root element, e.g. `OVAL definitions`
+    pub document_type: String,
+    pub benchmarks: usize,
+    pub profiles: usize,
+    pub rules: usize,
+    pub oval_definitions: usize,
+    /// Why the file couldn't be read or parsed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The file couldn't be read at all, the error isn't in its content
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub unreadable: bool,
+}
+
+fn collect_xml_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory '{}': {}", dir.display(), e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.is_dir() {
+            collect_xml_files(&path, files)?;
+        } else if path.extension().is_some_and(|e| e == "xml") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Expands the directories to the XML files found in them recursively,
+/// sorted by path. Files given explicitly are kept whatever their name.
+pub fn discover(paths: &[PathBuf]) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut found = Vec::new();
+            collect_xml_files(path, &mut found)?;
+            found.sort();
+            files.extend(found);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+fn count_benchmarks<'a>(
+    summary: &mut FileSummary,
+    benchmarks: impl Iterator<Item = &'a Benchmark>,
+) {
+    for benchmark in benchmarks {
+        summary.benchmarks += 1;
+        summary.profiles += benchmark.profiles.len();
+        summary.rules += benchmark.iter_rules().count();
+    }
+}
+
+fn count_content(summary: &mut FileSummary, data: &[u8]) -> Result<(), String> {
+    summary.size = data.len() as u64;
+    let (root, _) = misc::parse(data).map_err(|e| e.to_string())?;
+    let kind = match describe(&root) {
+        Some((description, kind)) => {
+            summary.document_type = description.to_string();
+            kind
+        }
+        None => {
+            summary.document_type = format!("unknown ({})", root.name());
+            None
+        }
+    };
+    match kind {
+        Some(Kind::Collection) => {
+            let collection = DataStreamCollection::from_xml(&root).map_err(|e| e.to_string())?;
+            count_benchmarks(summary, collection.benchmarks().into_iter());
+            summary.oval_definitions = collection
+                .components()
+                .iter()
+                .filter_map(|c| c.oval_definitions())
+                .map(|o| o.definitions().len())
+                .sum();
+        }
+        Some(Kind::Benchmark) => {
+            let benchmark = Benchmark::from_xml(&root).map_err(|e| e.to_string())?;
+            count_benchmarks(summary, std::iter::once(&benchmark));
+        }
+        Some(Kind::Oval) => {
+            let definitions = OvalDefinitions::from_xml(&root).map_err(|e| e.to_string())?;
+            summary.oval_definitions = definitions.definitions().len();
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Detects the type of the document and counts its content. Problems are
+/// recorded in the summary, so that one broken file doesn't hide the rest
+/// of the release.
+pub fn summarize(path: &Path) -> FileSummary {
+    let mut summary = FileSummary {
+        path: path.display().to_string(),
+        ..FileSummary::default()
+    };
+    let result = match fs::read(path) {
+        Ok(data) => count_content(&mut summary, &data),
+        Err(error) => {
+            summary.unreadable = true;
+            Err(error.to_string())
+        }
+    };
+    if let Err(error) = result {
+        summary.error = Some(error);
+    }
+    summary
+}
+
+/// Table with a row per file.
+pub fn write_summaries<W: Write>(out: &mut W, summaries: &[FileSummary]) -> io::Result<()> {
+    let mut table = Table::new(&[
+        "PATH",
+        "SIZE",
+        "TYPE",
+        "BENCHMARKS",
+        "PROFILES",
+        "RULES",
+        "OVAL",
+    ]);
+    for summary in summaries {
+        let document_type = match &summary.error {
+            Some(error) if summary.document_type.is_empty() => format!("error: {}", error),
+            Some(error) => format!("{} (error: {})", summary.document_type, error),
+            None => summary.document_type.clone(),
+        };
+        table.row(vec![
+            summary.path.clone(),
+            format_size(summary.size as usize),
+            document_type,
+            summary.benchmarks.to_string(),
+            summary.profiles.to_string(),
+            summary.rules.to_string(),
+            summary.oval_definitions.to_string(),
+        ]);
+    }
+    table.write(out, 0)?;
+    match summaries.len() {
+        1 => writeln!(out, "1 file"),
+        count => writeln!(out, "{} files", count),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize() {
+        let files = discover(&[PathBuf::from("data")]).unwrap();
+        assert!(files.contains(&PathBuf::from("data/simple.xml")));
+        let summary = summarize(Path::new("data/simple.xml"));
+        assert_eq!(summary.document_type, "SCAP source data stream collection");
+        assert_eq!(summary.benchmarks, 1);
+        assert_eq!(summary.profiles, 1);
+        assert!(summary.rules > 0);
+        assert!(summary.oval_definitions > 0);
+        assert!(summary.error.is_none());
+
+        let missing = summarize(Path::new("data/missing.xml"));
+        assert!(missing.error.is_some());
+        assert!(missing.unreadable);
+        assert!(!summary.unreadable);
+
+        let mut out = Vec::new();
+        write_summaries(&mut out, &[summary]).unwrap();
+        assert!(String::from_utf8(out).unwrap().ends_with("\n1 file\n"));
+    }
+}
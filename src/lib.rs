@@ -1,4 +1,73 @@
 #![allow(dead_code)]
+pub mod arf;
+pub mod builder;
+pub mod ckl;
+pub mod compose;
+pub mod controls;
+pub mod cpe;
+pub mod cvss;
+pub mod dc;
+pub mod diff;
+pub mod doctor;
+pub mod dsig;
+pub mod error;
+pub mod html;
+pub mod info;
+pub mod input;
+pub mod inventory;
+#[cfg(feature = "validation")]
+mod libxml;
+pub mod locale;
+pub mod matrix;
+pub mod misc;
+pub mod options;
+pub mod output;
+pub mod oval;
+pub mod pack;
+pub mod query;
+pub mod remediation;
+pub mod repair;
+pub mod report;
+pub mod resolve;
+pub mod richtext;
+pub mod scoring;
 pub mod sds;
-pub mod utils;
+pub mod selftest;
+pub mod stats;
+pub mod stream;
+pub mod tailoring;
+pub mod tree;
+pub mod trust;
+pub mod upgrade;
+mod utils;
+#[cfg(feature = "validation")]
+pub mod validate;
+pub mod visit;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod writer;
 pub mod xccdf;
+
+pub use error::Error;
+
+use std::path::Path;
+
+/// Loads a SCAP source data stream file. With the `mmap` feature, the file
+/// is memory-mapped instead of read into memory.
+pub fn load_collection<P: AsRef<Path>>(
+    path: P,
+    opts: &options::ParseOptions,
+) -> Result<sds::DataStreamCollection, Error> {
+    let input = input::Input::open(path)?;
+    parse_collection(&input, opts)
+}
+
+/// Parses a SCAP source data stream from the document bytes, without any
+/// file or network access.
+pub fn parse_collection(
+    data: &[u8],
+    opts: &options::ParseOptions,
+) -> Result<sds::DataStreamCollection, Error> {
+    let (root, _) = misc::parse_with_options(data, opts)?;
+    sds::DataStreamCollection::from_xml_with_options(&root, opts)
+}
@@ -0,0 +1,210 @@
+//! Bindings of the few libxml2 functions needed for the XSD validation.
+
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::path::Path;
+use std::ptr;
+use std::sync::Once;
+
+use crate::validate::Violation;
+
+#[repr(C)]
+struct XmlError {
+    domain: c_int,
+    code: c_int,
+    message: *const c_char,
+    level: c_int,
+    file: *const c_char,
+    line: c_int,
+    str1: *const c_char,
+    str2: *const c_char,
+    str3: *const c_char,
+    int1: c_int,
+    int2: c_int,
+    ctxt: *mut c_void,
+    node: *mut c_void,
+}
+
+type StructuredErrorFunc = extern "C" fn(*mut c_void, *const XmlError);
+
+/// `XML_ERR_ERROR`, warnings are below it.
+const XML_ERR_ERROR: c_int = 2;
+/// `XML_PARSE_NONET | XML_PARSE_BIG_LINES`: no network access, and line
+/// numbers above 65535 are kept.
+const PARSE_OPTIONS: c_int = (1 << 11) | (1 << 22);
+
+#[link(name = "xml2")]
+extern "C" {
+    fn xmlInitParser();
+    fn xmlSetStructuredErrorFunc(ctx: *mut c_void, handler: Option<StructuredErrorFunc>);
+    fn xmlReadMemory(
+        buffer: *const c_char,
+        size: c_int,
+        url: *const c_char,
+        encoding: *const c_char,
+        options: c_int,
+    ) -> *mut c_void;
+    fn xmlFreeDoc(doc: *mut c_void);
+    fn xmlSchemaNewParserCtxt(url: *const c_char) -> *mut c_void;
+    fn xmlSchemaSetParserStructuredErrors(
+        ctxt: *mut c_void,
+        handler: Option<StructuredErrorFunc>,
+        ctx: *mut c_void,
+    );
+    fn xmlSchemaParse(ctxt: *mut c_void) -> *mut c_void;
+    fn xmlSchemaFreeParserCtxt(ctxt: *mut c_void);
+    fn xmlSchemaFree(schema: *mut c_void);
+    fn xmlSchemaNewValidCtxt(schema: *mut c_void) -> *mut c_void;
+    fn xmlSchemaSetValidStructuredErrors(
+        ctxt: *mut c_void,
+        handler: Option<StructuredErrorFunc>,
+        ctx: *mut c_void,
+    );
+    fn xmlSchemaValidateDoc(ctxt: *mut c_void, doc: *mut c_void) -> c_int;
+    fn xmlSchemaFreeValidCtxt(ctxt: *mut c_void);
+}
+
+/// Collects the errors into the [`Errors`] passed as the context.
+extern "C" fn collect(ctx: *mut c_void, error: *const XmlError) {
+    // SAFETY: the context is the boxed `Errors` installing the handler,
+    // which outlives the libxml2 calls, and the error is valid for the
+    // duration of the callback.
+    let (errors, error) = unsafe { (&mut *(ctx as *mut Errors), &*error) };
+    if error.level < XML_ERR_ERROR {
+        return;
+    }
+    let message = match error.message.is_null() {
+        true => String::from("Unknown error"),
+        // SAFETY: libxml2 messages are NUL-terminated strings.
+        false => unsafe { CStr::from_ptr(error.message) }
+            .to_string_lossy()
+            .trim()
+            .to_string(),
+    };
+    errors.violations.push(Violation {
+        line: usize::try_from(error.line).ok().filter(|line| *line > 0),
+        message,
+    });
+}
+
+/// Errors reported by libxml2 on this thread while it's alive, instead of
+/// being printed on the standard error.
+struct Errors {
+    violations: Vec<Violation>,
+}
+
+impl Errors {
+    /// Boxed, so the address given to libxml2 stays the same.
+    fn new() -> Box<Errors> {
+        static INIT: Once = Once::new();
+        // SAFETY: initializes the global state of libxml2 once, before it's
+        // used by any thread.
+        INIT.call_once(|| unsafe { xmlInitParser() });
+        let mut errors = Box::new(Errors {
+            violations: Vec::new(),
+        });
+        // SAFETY: the handler is removed on drop, before the box is freed.
+        unsafe { xmlSetStructuredErrorFunc(errors.context(), Some(collect)) };
+        errors
+    }
+
+    fn context(&mut self) -> *mut c_void {
+        self as *mut Errors as *mut c_void
+    }
+
+    fn take(&mut self) -> Vec<Violation> {
+        std::mem::take(&mut self.violations)
+    }
+
+    /// The collected messages in one line, for a failure.
+    fn describe(&mut self) -> String {
+        let messages: Vec<String> = self.take().into_iter().map(|v| v.message).collect();
+        messages.join("; ")
+    }
+}
+
+impl Drop for Errors {
+    fn drop(&mut self) {
+        // SAFETY: restores the default handler of this thread.
+        unsafe { xmlSetStructuredErrorFunc(ptr::null_mut(), None) };
+    }
+}
+
+/// Compiled XSD schema, with the schemas it includes and imports.
+pub(crate) struct Schema {
+    schema: *mut c_void,
+}
+
+impl Schema {
+    /// Loads the schema file. The included and imported schemas are looked
+    /// up relative to it.
+    pub(crate) fn load(path: &Path) -> Result<Schema, String> {
+        let url = path
+            .to_str()
+            .and_then(|path| CString::new(path).ok())
+            .ok_or_else(|| format!("Invalid schema path '{}'", path.display()))?;
+        let mut errors = Errors::new();
+        // SAFETY: the URL is a valid C string, the parser context is freed
+        // right after the schema is parsed and the schema is owned by `Schema`.
+        let schema = unsafe {
+            let ctxt = xmlSchemaNewParserCtxt(url.as_ptr());
+            if ctxt.is_null() {
+                return Err(format!("Failed to load schema '{}'", path.display()));
+            }
+            xmlSchemaSetParserStructuredErrors(ctxt, Some(collect), errors.context());
+            let schema = xmlSchemaParse(ctxt);
+            xmlSchemaFreeParserCtxt(ctxt);
+            schema
+        };
+        match schema.is_null() {
+            true => Err(format!(
+                "Failed to load schema '{}': {}",
+                path.display(),
+                errors.describe()
+            )),
+            false => Ok(Schema { schema }),
+        }
+    }
+
+    /// Validates the document, the violations are reported with the line
+    /// numbers. Fails if the document isn't well-formed.
+    pub(crate) fn validate(&self, data: &[u8]) -> Result<Vec<Violation>, String> {
+        let size = c_int::try_from(data.len())
+            .map_err(|_| String::from("The document is too large to be validated"))?;
+        let mut errors = Errors::new();
+        // SAFETY: the buffer is valid for `size` bytes, the document and the
+        // validation context are freed before returning.
+        unsafe {
+            let doc = xmlReadMemory(
+                data.as_ptr() as *const c_char,
+                size,
+                ptr::null(),
+                ptr::null(),
+                PARSE_OPTIONS,
+            );
+            if doc.is_null() {
+                return Err(format!("Failed to parse: {}", errors.describe()));
+            }
+            let ctxt = xmlSchemaNewValidCtxt(self.schema);
+            if ctxt.is_null() {
+                xmlFreeDoc(doc);
+                return Err(String::from("Failed to create the validation context"));
+            }
+            xmlSchemaSetValidStructuredErrors(ctxt, Some(collect), errors.context());
+            let result = xmlSchemaValidateDoc(ctxt, doc);
+            xmlSchemaFreeValidCtxt(ctxt);
+            xmlFreeDoc(doc);
+            if result < 0 {
+                return Err(format!("Failed to validate: {}", errors.describe()));
+            }
+        }
+        Ok(errors.take())
+    }
+}
+
+impl Drop for Schema {
+    fn drop(&mut self) {
+        // SAFETY: the schema was returned by `xmlSchemaParse` and isn't used
+        // by any validation context anymore.
+        unsafe { xmlSchemaFree(self.schema) };
+    }
+}
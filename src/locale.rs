@@ -0,0 +1,247 @@
+use chrono::{Datelike, Timelike};
+
+use crate::utils::parse_date_time;
+
+/// Order of the day, month and year in a formatted date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateStyle {
+    /// 1/31/2022
+    MonthDayYear,
+    /// 31/01/2022
+    DaySlashMonth,
+    /// 31.01.2022
+    DayDotMonth,
+    /// 31. 1. 2022
+    DaySpacedMonth,
+    /// 2022/01/31
+    YearMonthDay,
+    /// 2022-01-31
+    Iso,
+}
+
+/// Number and date conventions of a language, used by the text reports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locale {
+    pub tag: String,
+    decimal: char,
+    group: char,
+    /// Space between a number and the percent sign
+    percent_space: bool,
+    date: DateStyle,
+    hour12: bool,
+    /// Right-to-left script, e.g. Arabic or Hebrew
+    pub rtl: bool,
+}
+
+/// Whether the character belongs to a right-to-left script such as Hebrew
+/// or Arabic.
+fn is_rtl_char(c: char) -> bool {
+    matches!(c as u32, 0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF)
+}
+
+impl Locale {
+    /// Locale from a language tag such as `de-DE`, `pt_BR.UTF-8` or `ar`.
+    /// Unknown languages use the ISO 8601 dates and a decimal point.
+    pub fn from_tag(tag: &str) -> Locale {
+        let tag = tag.split('.').next().unwrap_or_default().replace('_', "-");
+        let mut parts = tag.split('-');
+        let language = parts.next().unwrap_or_default().to_lowercase();
+        let region = parts.next().unwrap_or_default().to_uppercase();
+        let (decimal, group, percent_space, date) = match language.as_str() {
+            "en" if region == "US" || region.is_empty() => {
+                ('.', ',', false, DateStyle::MonthDayYear)
+            }
+            "en" => ('.', ',', false, DateStyle::DaySlashMonth),
+            "de" | "nb" | "da" | "fi" | "ru" | "uk" | "pl" | "tr" => {
+                (',', '.', true, DateStyle::DayDotMonth)
+            }
+            "cs" | "sk" => (',', '\u{a0}', true, DateStyle::DaySpacedMonth),
+            "fr" => (',', '\u{202f}', true, DateStyle::DaySlashMonth),
+            "es" | "it" | "pt" | "nl" => (',', '.', false, DateStyle::DaySlashMonth),
+            "sv" => (',', '\u{a0}', true, DateStyle::Iso),
+            "ja" | "zh" | "ko" => ('.', ',', false, DateStyle::YearMonthDay),
+            "he" | "ar" | "fa" => ('.', ',', false, DateStyle::DayDotMonth),
+            _ => ('.', ',', false, DateStyle::Iso),
+        };
+        Locale {
+            rtl: matches!(language.as_str(), "he" | "ar" | "fa"),
+            hour12: language == "en" && (region == "US" || region.is_empty()),
+            tag,
+            decimal,
+            group,
+            percent_space,
+            date,
+        }
+    }
+
+    pub fn format_number(&self, value: f64, decimals: usize) -> String {
+        let text = format!("{:.*}", decimals, value.abs());
+        let (integer, fraction) = match text.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (text.as_str(), None),
+        };
+        let mut grouped = String::new();
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                grouped.push(self.group);
+            }
+            grouped.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            grouped.push(self.decimal);
+            grouped.push_str(fraction);
+        }
+        match value < 0.0 && text.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            true => format!("-{}", grouped),
+            false => grouped,
+        }
+    }
+
+    /// Formats a ratio, e.g. 0.6667 as `66.7%` or `66,7 %`.
+    pub fn format_percent(&self, ratio: f64) -> String {
+        let number = self.format_number(ratio * 100.0, 1);
+        match self.percent_space {
+            true => format!("{}\u{a0}%", number),
+            false => format!("{}%", number),
+        }
+    }
+
+    /// Formats an XCCDF timestamp (`2022-01-31T15:04:05`, optionally with
+    /// fractional seconds and a time zone). Other texts are returned as they are.
+    pub fn format_date(&self, timestamp: &str) -> String {
+        let time = match parse_date_time(timestamp) {
+            Ok(time) => time,
+            Err(_) => return timestamp.to_string(),
+        };
+        let (year, month, day) = (time.year(), time.month(), time.day());
+        let (hour, minute) = (time.hour(), time.minute());
+        let date = match self.date {
+            DateStyle::MonthDayYear => format!("{}/{}/{}", month, day, year),
+            DateStyle::DaySlashMonth => format!("{:02}/{:02}/{}", day, month, year),
+            DateStyle::DayDotMonth => format!("{:02}.{:02}.{}", day, month, year),
+            DateStyle::DaySpacedMonth => format!("{}. {}. {}", day, month, year),
+            DateStyle::YearMonthDay => format!("{}/{:02}/{:02}", year, month, day),
+            DateStyle::Iso => format!("{}-{:02}-{:02}", year, month, day),
+        };
+        let time = match self.hour12 {
+            true => format!(
+                "{}:{:02} {}",
+                (hour + 11) % 12 + 1,
+                minute,
+                if hour < 12 { "AM" } else { "PM" }
+            ),
+            false => format!("{:02}:{:02}", hour, minute),
+        };
+        format!("{} {}", date, time)
+    }
+
+    /// Wraps a text whose direction differs from the locale in Unicode
+    /// directional isolates, so that e.g. a Hebrew title doesn't reorder
+    /// the rest of a left-to-right line.
+    pub fn isolate(&self, text: &str) -> String {
+        let rtl_text = text
+            .chars()
+            .find(|c| c.is_alphabetic())
+            .is_some_and(is_rtl_char);
+        match rtl_text != self.rtl {
+            true => format!("\u{2068}{}\u{2069}", text),
+            false => text.to_string(),
+        }
+    }
+}
+
+/// Text element whose language is given by `xml:lang`.
+pub trait Localized {
+    fn lang(&self) -> Option<&str>;
+}
+
+impl<T: Localized> Localized for &T {
+    fn lang(&self) -> Option<&str> {
+        (*self).lang()
+    }
+}
+
+fn normalize_tag(tag: &str) -> String {
+    tag.replace('_', "-").to_lowercase()
+}
+
+fn primary_language(tag: &str) -> &str {
+    tag.split('-').next().unwrap_or_default()
+}
+
+/// Picks the text in the preferred language. Without an exact match, a text
+/// in the same language of another region is used, then a text without a
+/// language and finally the first one.
+pub fn preferred_text<'a, T: Localized>(texts: &'a [T], lang: Option<&str>) -> Option<&'a T> {
+    if let Some(lang) = lang.map(normalize_tag) {
+        let tags: Vec<Option<String>> = texts.iter().map(|t| t.lang().map(normalize_tag)).collect();
+        let exact = tags
+            .iter()
+            .position(|t| t.as_deref() == Some(lang.as_str()));
+        let primary = tags.iter().position(|t| {
+            t.as_deref()
+                .is_some_and(|t| primary_language(t) == primary_language(&lang))
+        });
+        if let Some(i) = exact.or(primary) {
+            return Some(&texts[i]);
+        }
+    }
+    texts.iter().find(|t| t.lang().is_none()).or(texts.first())
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::from_tag("en-US")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_formatting() {
+        let en = Locale::default();
+        assert_eq!(en.format_number(12345.678, 2), "12,345.68");
+        assert_eq!(en.format_percent(0.6667), "66.7%");
+        assert_eq!(en.format_date("2022-01-31T15:04:05Z"), "1/31/2022 3:04 PM");
+        let de = Locale::from_tag("de_DE.UTF-8");
+        assert_eq!(de.format_number(12345.678, 2), "12.345,68");
+        assert_eq!(de.format_percent(0.6667), "66,7\u{a0}%");
+        assert_eq!(de.format_date("2022-01-31T15:04:05"), "31.01.2022 15:04");
+        assert_eq!(de.format_date("yesterday"), "yesterday");
+        let he = Locale::from_tag("he");
+        assert_eq!(he.isolate("שלום"), "שלום");
+        assert_eq!(en.isolate("שלום"), "\u{2068}שלום\u{2069}");
+        assert_eq!(he.isolate("SSH"), "\u{2068}SSH\u{2069}");
+    }
+
+    struct Text(Option<&'static str>);
+
+    impl Localized for Text {
+        fn lang(&self) -> Option<&str> {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_preferred_text() {
+        let texts = [
+            Text(Some("de")),
+            Text(None),
+            Text(Some("en-GB")),
+            Text(Some("en-US")),
+        ];
+        let pick = |lang| preferred_text(&texts, lang).and_then(|t| t.0);
+        assert_eq!(pick(Some("en_US")), Some("en-US"));
+        assert_eq!(pick(Some("en")), Some("en-GB"));
+        assert_eq!(pick(Some("DE-at")), Some("de"));
+        assert_eq!(pick(Some("fr")), None);
+        assert_eq!(pick(None), None);
+        assert_eq!(
+            preferred_text(&texts[2..], Some("fr")).and_then(|t| t.0),
+            Some("en-GB")
+        );
+        assert!(preferred_text::<Text>(&[], Some("en")).is_none());
+    }
+}
@@ -1,36 +1,2575 @@
 extern crate clap;
 extern crate minidom;
 
-use clap::Parser;
-use minidom::quick_xml;
+use clap::{AppSettings, IntoApp, Parser, Subcommand};
+use indicatif::{ProgressBar, ProgressStyle};
 use minidom::Element;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::{LazyLock, Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+use tracing_subscriber::fmt::format::FmtSpan;
 
+use oscapxml::arf::{self, ArfResults};
+use oscapxml::ckl;
+use oscapxml::compose;
+use oscapxml::controls;
+use oscapxml::cpe::Cpe;
+use oscapxml::cvss;
+use oscapxml::diff;
+use oscapxml::doctor;
+use oscapxml::html;
+use oscapxml::input::{self, FetchOptions, Input};
+use oscapxml::inventory;
+use oscapxml::locale::Locale;
+use oscapxml::matrix;
+use oscapxml::misc;
+use oscapxml::options::{ParseOptions, Progress, ProgressCallback};
+use oscapxml::output::{self, Format};
+use oscapxml::oval;
+use oscapxml::pack;
+use oscapxml::query;
+use oscapxml::remediation::{self, FixSystem};
+use oscapxml::repair::{self, RepairKind, RepairOptions};
+use oscapxml::report;
+use oscapxml::resolve;
+use oscapxml::scoring;
 use oscapxml::sds;
+use oscapxml::selftest;
+use oscapxml::stats;
+use oscapxml::stream::StreamedCollection;
+use oscapxml::tailoring;
+use oscapxml::tree;
+use oscapxml::trust;
+use oscapxml::upgrade;
+use oscapxml::validate;
+use oscapxml::writer;
+use oscapxml::xccdf;
+use oscapxml::Error;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
+#[clap(setting = AppSettings::SubcommandsNegateReqs | AppSettings::ArgsNegateSubcommands)]
 struct Args {
-    /// Path to the SCAP source data stream
-    filepath: String,
+    /// Path or http(s) URL of the SCAP source data stream, `-` or none for
+    /// the standard input. Several paths or directories, searched for XML
+    /// files recursively, give a summary of every file.
+    filepaths: Vec<String>,
+
+    /// Output format
+    #[clap(long, default_value = "text", possible_values = output::REPORT_FORMATS)]
+    format: Format,
+
+    /// Don't color the severities and the missing components
+    #[clap(long)]
+    no_color: bool,
+
+    /// Print approximate memory usage of the parsed model instead of the information
+    #[clap(long)]
+    debug_model: bool,
+
+    /// Report recoverable problems in the content as warnings instead of failing
+    #[clap(long)]
+    lenient: bool,
+
+    /// Report up to this many problems in the content at once instead of
+    /// stopping at the first one
+    #[clap(long, conflicts_with = "lenient")]
+    max_errors: Option<usize>,
+
+    /// Report unknown elements as warnings and skip them instead of failing
+    #[clap(long, conflicts_with = "max-errors")]
+    permissive: bool,
+
+    /// Read the file as a stream and load only the checklist components,
+    /// which needs much less memory for large data streams
+    #[clap(long)]
+    stream: bool,
+
+    /// Preferred language of the titles and descriptions, e.g. `en-US`
+    #[clap(long, global = true)]
+    lang: Option<String>,
+
+    /// File of the zip archive to read, by default the data stream is found
+    /// by its name
+    #[clap(long)]
+    entry: Option<String>,
+
+    /// Seconds to wait for the download when a path is a URL
+    #[clap(long, default_value_t = 60, global = true)]
+    timeout: u64,
+
+    /// Maximum size in bytes of a document downloaded from a URL
+    #[clap(long, default_value_t = 1 << 30, global = true)]
+    max_size: u64,
+
+    /// Download the components the data streams reference by http(s) URLs
+    #[clap(long)]
+    fetch_remote_resources: bool,
+
+    /// Directory of the downloaded remote components, by default
+    /// `~/.cache/oscapxml`
+    #[clap(long)]
+    cache_dir: Option<PathBuf>,
+
+    /// Log what's being parsed and how long it takes, repeated for more
+    /// details. `RUST_LOG` adds more specific filters.
+    #[clap(short, long, parse(from_occurrences), global = true)]
+    verbose: usize,
+
+    /// Log only errors
+    #[clap(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Path where the report will be written instead of the standard output
+    #[clap(long = "output", value_name = "FILE", global = true)]
+    output_file: Option<String>,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
 }
 
-fn main() {
-    let args = Args::parse();
-    let mut reader =
-        quick_xml::Reader::from_file(&args.filepath).expect("Failed to open the input file");
-    let root = Element::from_reader(&mut reader).unwrap();
-    let result = sds::DataStreamCollection::from_xml(&root);
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Fix common conformance problems and write the repaired document
+    Repair {
+        /// Path to the input SCAP source data stream or XCCDF benchmark
+        input: String,
+
+        /// Path where the repaired document will be written
+        output: String,
+
+        /// Repair that won't be performed, can be given multiple times
+        #[clap(long, multiple_occurrences = true, possible_values = [
+            "missing-status",
+            "duplicate-version",
+            "empty-title",
+            "ident-system-casing",
+            "missing-timestamp",
+        ])]
+        disable: Vec<RepairKind>,
+
+        /// Keep the comments and processing instructions preceding the root,
+        /// the components and the benchmarks
+        #[clap(long)]
+        keep_comments: bool,
+    },
+    /// Resolve the benchmarks according to the XCCDF loading model and write the result
+    Resolve {
+        /// Path to the input SCAP source data stream or XCCDF benchmark
+        input: String,
+
+        /// Path where the resolved document will be written
+        output: String,
+
+        /// Apply the refine-rule and refine-value of this profile to the rules and values
+        #[clap(long)]
+        profile: Option<String>,
+
+        /// Keep the comments and processing instructions preceding the root,
+        /// the components and the benchmarks
+        #[clap(long)]
+        keep_comments: bool,
+    },
+    /// Export a XCCDF benchmark as a DISA STIG Viewer checklist (.ckl)
+    ExportCkl {
+        /// Path to the SCAP source data stream or XCCDF benchmark
+        input: String,
+
+        /// Path where the checklist will be written
+        output: String,
+
+        /// ID of the TestResult whose rule results become the finding statuses
+        #[clap(long)]
+        test_result: Option<String>,
+    },
+    /// Check the structure and references of collection-level and item-level signatures
+    Signatures {
+        /// Path to the SCAP source data stream
+        filepath: String,
+
+        /// PEM bundle or directory of PEM files with the certificates trusted to sign content
+        #[clap(long)]
+        trust_store: Option<String>,
+
+        /// Allowed signature or digest algorithm URI, can be given multiple
+        /// times and replaces the default list of SHA-2 based algorithms
+        #[clap(long, multiple_occurrences = true, requires = "trust-store")]
+        allow_algorithm: Vec<String>,
+    },
+    /// List the rules of the benchmark with their severities
+    ListRules {
+        /// Path to the SCAP source data stream or XCCDF benchmark
+        filepath: String,
+
+        /// List only the rules selected by the profile
+        #[clap(long)]
+        profile: Option<String>,
+
+        /// List only the rules of this severity, can be given multiple times
+        #[clap(long, multiple_occurrences = true, possible_values = xccdf::Severity::VALUES)]
+        severity: Vec<xccdf::Severity>,
+
+        /// List only the rules applicable to this CPE platform, e.g.
+        /// `cpe:/o:redhat:enterprise_linux:9`
+        #[clap(long)]
+        platform: Option<String>,
+
+        /// List only the rules selected by default when no profile is given
+        #[clap(long)]
+        selected_only: bool,
+
+        /// Output format
+        #[clap(long, default_value = "text", possible_values = output::REPORT_FORMATS)]
+        format: Format,
+
+        /// Don't color the severities
+        #[clap(long)]
+        no_color: bool,
+
+        /// Language tag such as `de-DE` for the numbers and dates, the
+        /// language of the benchmark by default
+        #[clap(long)]
+        locale: Option<String>,
+    },
+    /// Report counts of profiles, groups, rules, values, check systems,
+    /// fixes and references of the benchmark
+    Stats {
+        /// Path to the SCAP source data stream or XCCDF benchmark
+        filepath: String,
+
+        /// Output format
+        #[clap(long, default_value = "text", possible_values = output::REPORT_FORMATS)]
+        format: Format,
+    },
+    /// Find rules, groups and values and print where they are and which
+    /// profiles use them
+    Search {
+        /// Path to the SCAP source data stream or XCCDF benchmark
+        filepath: String,
+
+        /// Case-insensitive text in the ID, title or description
+        #[clap(long, required_unless_present_any = &["id", "ident"], conflicts_with_all = &["id", "ident"])]
+        query: Option<String>,
+
+        /// Exact ID of a rule, group or value
+        #[clap(long, conflicts_with = "ident")]
+        id: Option<String>,
+
+        /// Ident of a rule, e.g. a CCE
+        #[clap(long)]
+        ident: Option<String>,
+
+        /// Output format
+        #[clap(long, default_value = "text", possible_values = output::REPORT_FORMATS)]
+        format: Format,
+    },
+    /// Evaluate the platforms of the benchmark, profiles and rules against
+    /// the CPE names of a system and show which rules a scan would skip
+    Applicability {
+        /// Path to the SCAP source data stream or XCCDF benchmark
+        filepath: String,
+
+        /// CPE name of the target system, can be repeated
+        #[clap(long, required = true, multiple_occurrences = true)]
+        target_cpe: Vec<Cpe>,
+
+        /// ID of the profile selecting the rules, the default selection otherwise
+        #[clap(long)]
+        profile: Option<String>,
+
+        /// Output format
+        #[clap(long, default_value = "text", possible_values = output::REPORT_FORMATS)]
+        format: Format,
+    },
+    /// Export the benchmark in other formats
+    Export {
+        #[clap(subcommand)]
+        command: ExportCommand,
+    },
+    /// Inspect scan results
+    Results {
+        #[clap(subcommand)]
+        command: ResultsCommand,
+    },
+    /// Convert documents to newer versions of the standards
+    Upgrade {
+        #[clap(subcommand)]
+        command: UpgradeCommand,
+    },
+    /// Edit the components of a source data stream collection
+    Component {
+        #[clap(subcommand)]
+        command: ComponentCommand,
+    },
+    /// Compare documents
+    Diff {
+        #[clap(subcommand)]
+        command: DiffCommand,
+    },
+    /// Inspect profiles
+    Profile {
+        #[clap(subcommand)]
+        command: ProfileCommand,
+    },
+    /// Generate content from the benchmark
+    Generate {
+        #[clap(subcommand)]
+        command: GenerateCommand,
+    },
+    /// Check that the parser accepts valid and rejects invalid embedded content
+    Selftest,
+    /// Diagnose why a file can't be loaded and suggest what to do with it
+    Doctor {
+        /// Path to the file to check
+        filepath: String,
+    },
+    /// Show the data streams, their component-refs with the components they
+    /// resolve to, the catalogs and the signatures as a tree
+    Tree {
+        /// Path to the SCAP source data stream
+        filepath: String,
+    },
+    /// Check the document against the model, the references between its
+    /// components and the Schematron rules
+    Validate {
+        /// Path to the file to validate, or a directory whose data streams
+        /// are all validated into one report
+        filepath: String,
+
+        /// Validate also against the XSD schema of the document
+        #[clap(long)]
+        schema: bool,
+
+        /// Directory with the SCAP schemas laid out like the one of OpenSCAP
+        #[clap(long, default_value = validate::DEFAULT_SCHEMA_DIR)]
+        schema_dir: PathBuf,
+
+        /// Report the SCAP content requirements one by one with PASS or FAIL,
+        /// like `oscap ds sds-validate`
+        #[clap(long)]
+        requirements: bool,
+
+        /// Output format of the requirements and the directory reports
+        #[clap(long, default_value = "text", possible_values = output::REPORT_FORMATS)]
+        format: Format,
+
+        /// Warn about data streams and components whose timestamp is older
+        /// than this many days
+        #[clap(long, value_name = "DAYS")]
+        max_age: Option<u64>,
+    },
+    /// Work with XCCDF tailoring files
+    Tailoring {
+        #[clap(subcommand)]
+        command: TailoringCommand,
+    },
+    /// Analyze and shrink OVAL definitions
+    Oval {
+        #[clap(subcommand)]
+        command: OvalCommand,
+    },
+    /// Create or verify a content pack manifest (scap-content.json)
+    Pack {
+        #[clap(subcommand)]
+        command: PackCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ExportCommand {
+    /// Export a rule × platform matrix of applicable and selected rules
+    Matrix {
+        /// Path to the SCAP source data stream or XCCDF benchmark
+        filepath: String,
+
+        /// Mark the rules selected by the profile instead of the default selection
+        #[clap(long)]
+        profile: Option<String>,
+
+        /// CPE name of a platform column, can be given multiple times, the
+        /// platforms of the benchmark are used by default
+        #[clap(long, multiple_occurrences = true)]
+        platform: Vec<String>,
+
+        /// Export only the rules of this severity, can be given multiple times
+        #[clap(long, multiple_occurrences = true, possible_values = xccdf::Severity::VALUES)]
+        severity: Vec<xccdf::Severity>,
+
+        /// Export only the rules selected by the profile, or by default
+        /// without a profile
+        #[clap(long)]
+        selected_only: bool,
+
+        /// Output format
+        #[clap(long, default_value = "csv", possible_values = ["csv", "json", "yaml", "html"])]
+        format: Format,
+    },
+    /// Map the controls of a compliance standard to the rules referencing them
+    Controls {
+        /// Path to the SCAP source data stream or XCCDF benchmark
+        filepath: String,
+
+        /// Standard whose controls are mapped
+        #[clap(long, possible_values = controls::Standard::VALUES)]
+        standard: controls::Standard,
+
+        /// Map only the rules selected by the profile
+        #[clap(long)]
+        profile: Option<String>,
+
+        /// Output format
+        #[clap(long, default_value = "text", possible_values = ["text", "csv", "json", "yaml", "html"])]
+        format: Format,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ResultsCommand {
+    /// Print the rule results of a TestResult with totals per severity and result
+    Summary {
+        /// Path to the XCCDF results or the SCAP source data stream containing them
+        filepath: String,
+
+        /// ID of the TestResult, the last one is used by default
+        #[clap(long)]
+        test_result: Option<String>,
+
+        /// Output format
+        #[clap(long, default_value = "text", possible_values = output::REPORT_FORMATS)]
+        format: Format,
+
+        /// Don't color the severities and results
+        #[clap(long)]
+        no_color: bool,
+
+        /// Language tag such as `de-DE` for the numbers and dates, the
+        /// language of the benchmark by default
+        #[clap(long)]
+        locale: Option<String>,
+    },
+    /// Print the OVAL definition results and the scanned system, optionally
+    /// next to the XCCDF rule results of the same scan
+    Oval {
+        /// Path to the OVAL results document
+        filepath: String,
+
+        /// Path to the XCCDF results to correlate the definition results with
+        #[clap(long)]
+        xccdf: Option<String>,
+
+        /// ID of the TestResult, the last one is used by default
+        #[clap(long, requires = "xccdf")]
+        test_result: Option<String>,
+
+        /// Output format
+        #[clap(long, default_value = "text", possible_values = output::REPORT_FORMATS)]
+        format: Format,
+    },
+    /// Compute the scores of a TestResult in all XCCDF scoring models and
+    /// compare them with the recorded scores
+    Score {
+        /// Path to the XCCDF results or the SCAP source data stream containing them
+        filepath: String,
+
+        /// ID of the TestResult, the last one is used by default
+        #[clap(long)]
+        test_result: Option<String>,
+
+        /// Output format
+        #[clap(long, default_value = "text", possible_values = output::REPORT_FORMATS)]
+        format: Format,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum UpgradeCommand {
+    /// Convert a XCCDF 1.1 benchmark to XCCDF 1.2
+    Xccdf {
+        /// Path to the XCCDF 1.1 benchmark
+        input: String,
+
+        /// Path where the XCCDF 1.2 benchmark will be written
+        output: String,
+
+        /// Reverse DNS name of the content author used in the XCCDF 1.2
+        /// IDs, e.g. `mil.disa.stig`
+        #[clap(long)]
+        reverse_dns: String,
+    },
+    /// Upgrade the SCAP 1.2 data streams of a source data stream collection
+    /// to SCAP 1.3
+    Scap {
+        /// Path to the SCAP 1.2 source data stream
+        input: String,
+
+        /// Path where the SCAP 1.3 source data stream will be written
+        output: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ComponentCommand {
+    /// Add a component, e.g. an OVAL file, and reference it from a data
+    /// stream
+    Add {
+        /// Path to the source data stream collection
+        input: String,
+
+        /// Path to the content of the component
+        component: String,
+
+        /// Path where the edited collection will be written
+        output: String,
+
+        /// ID of the data stream referencing the component, the first one
+        /// by default
+        #[clap(long)]
+        data_stream: Option<String>,
+
+        /// Name used in the component IDs and in the checklist catalogs,
+        /// the file name of the component by default
+        #[clap(long)]
+        name: Option<String>,
+
+        /// Replace the signatures by a template referencing every component
+        /// with its SHA-256 digest
+        #[clap(long)]
+        digests: bool,
+    },
+    /// Replace the content of the component referenced by a component-ref
+    Replace {
+        /// Path to the source data stream collection
+        input: String,
+
+        /// ID of the component-ref
+        ref_id: String,
+
+        /// Path to the new content of the component
+        component: String,
+
+        /// Path where the edited collection will be written
+        output: String,
+
+        /// Replace the signatures by a template referencing every component
+        /// with its SHA-256 digest
+        #[clap(long)]
+        digests: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum DiffCommand {
+    /// Report the rules whose result changed between two scans, the rules
+    /// evaluated only in one of them and the score delta
+    Results {
+        /// Path to the ARF or the XCCDF results of the old scan
+        old: String,
+
+        /// Path to the ARF or the XCCDF results of the new scan
+        new: String,
+
+        /// ID of the old TestResult, the last one is used by default
+        #[clap(long)]
+        old_test_result: Option<String>,
+
+        /// ID of the new TestResult, the last one is used by default
+        #[clap(long)]
+        new_test_result: Option<String>,
+
+        /// Output format
+        #[clap(long, default_value = "text", possible_values = output::REPORT_FORMATS)]
+        format: Format,
+    },
+    /// Report the rules, profile selections and value defaults that changed
+    /// between two versions of a benchmark
+    Content {
+        /// Path to the old SCAP source data stream or XCCDF benchmark
+        old: String,
+
+        /// Path to the new SCAP source data stream or XCCDF benchmark
+        new: String,
+
+        /// Output format
+        #[clap(long, default_value = "text", possible_values = output::REPORT_FORMATS)]
+        format: Format,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum GenerateCommand {
+    /// Generate a remediation script from the fixes of the rules selected by a profile
+    Fix {
+        /// Path to the SCAP source data stream or XCCDF benchmark
+        filepath: String,
+
+        /// ID of the profile
+        #[clap(long)]
+        profile: String,
+
+        /// Type of the remediation script
+        #[clap(long, default_value = "bash", possible_values = ["bash"])]
+        system: FixSystem,
+    },
+    /// Generate a tailoring file with a profile extending the given one,
+    /// listing the selection of every rule and the value of every Value
+    Tailoring {
+        /// Path to the SCAP source data stream or XCCDF benchmark
+        filepath: String,
+
+        /// ID of the profile to customize
+        #[clap(long)]
+        profile: String,
+    },
+    /// Generate an HTML report of the rule results of a TestResult
+    Report {
+        /// Path to the ARF, the XCCDF results or the SCAP source data stream
+        /// containing them
+        filepath: String,
+
+        /// ID of the TestResult, the last one is used by default
+        #[clap(long)]
+        test_result: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum TailoringCommand {
+    /// Show the selections and values of a tailored profile
+    Apply {
+        /// Path to the SCAP source data stream
+        filepath: String,
+
+        /// Path to the XCCDF tailoring file
+        tailoring: String,
+
+        /// ID of the tailoring profile, required if the tailoring has more profiles
+        #[clap(long)]
+        profile: Option<String>,
+
+        /// Output format
+        #[clap(long, default_value = "text", possible_values = output::REPORT_FORMATS)]
+        format: Format,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum OvalCommand {
+    /// Report the number of OVAL entries and their structural duplicates
+    Stats {
+        /// Path to the SCAP source data stream or OVAL definitions file
+        filepath: String,
+
+        /// Output format
+        #[clap(long, default_value = "text", possible_values = output::REPORT_FORMATS)]
+        format: Format,
+    },
+    /// Merge duplicate tests, objects, states and variables and rewrite references
+    Dedup {
+        /// Path to the input SCAP source data stream or OVAL definitions file
+        input: String,
+
+        /// Path where the deduplicated document will be written
+        output: String,
+
+        /// Keep the comments and processing instructions preceding the root,
+        /// the components and the benchmarks
+        #[clap(long)]
+        keep_comments: bool,
+    },
+    /// List the CVEs checked by the OVAL definitions with their CVSS scores
+    Cves {
+        /// Path to the SCAP source data stream or OVAL definitions file
+        filepath: String,
+
+        /// List only the CVEs with a score of this severity or higher
+        #[clap(long, possible_values = cvss::CvssSeverity::VALUES)]
+        min_severity: Option<cvss::CvssSeverity>,
+
+        /// Sort by the CVE ID or from the highest score
+        #[clap(long, default_value = "id", possible_values = ["id", "score"])]
+        sort: String,
+
+        /// Output format
+        #[clap(long, default_value = "text", possible_values = output::REPORT_FORMATS)]
+        format: Format,
+    },
+    /// Show the external variables bound to the check-exports of the rules
+    Variables {
+        /// Path to the SCAP source data stream or XCCDF benchmark
+        filepath: String,
+
+        /// Path to the OVAL variables document
+        variables: String,
+
+        /// Output format
+        #[clap(long, default_value = "text", possible_values = output::REPORT_FORMATS)]
+        format: Format,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum PackCommand {
+    /// Write the manifest of all data streams in the directory
+    Create {
+        /// Path to the content pack directory
+        directory: String,
+    },
+    /// Check the files in the directory against the manifest
+    Verify {
+        /// Path to the content pack directory
+        directory: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ProfileCommand {
+    /// Show details of a profile including its known extensions
+    Show {
+        /// Path to the SCAP source data stream
+        filepath: String,
+
+        /// ID of the profile
+        profile_id: String,
+
+        /// Show the selections after merging the profiles it extends
+        #[clap(long)]
+        resolved: bool,
+
+        /// Show the rules evaluated with the profile, with their refined
+        /// severities, and the values it uses
+        #[clap(long, conflicts_with = "resolved")]
+        effective: bool,
+    },
+}
+
+/// `-` if the standard input isn't a terminal, so the document can be piped
+/// in without naming a file.
+fn piped_stdin() -> Option<String> {
+    match std::io::stdin().is_terminal() {
+        true => None,
+        false => Some(String::from(input::STDIN)),
+    }
+}
+
+/// Exit codes of the commands, so that scripts can tell the outcomes apart
+/// without parsing the output. Invalid arguments exit with 2.
+#[derive(Clone, Copy)]
+enum Exit {
+    /// The document is invalid, or it doesn't pass the check of the command
+    Failure = 1,
+    /// The document was parsed, but recoverable problems were reported
+    Warnings = 3,
+    /// A file couldn't be read, written or downloaded
+    Io = 4,
+    /// The document isn't of a type the command works with
+    Unsupported = 5,
+}
+
+fn exit(code: Exit) -> ! {
+    process::exit(code as i32)
+}
+
+fn create_output(output: &str) -> File {
+    match File::create(output) {
+        Ok(file) => file,
+        Err(error) => {
+            println!("Failed to write '{}': {}", output, error);
+            exit(Exit::Io);
+        }
+    }
+}
+
+/// Where the reports are written, the standard output or the `--output`
+/// file.
+struct Output {
+    writer: Box<dyn Write + Send>,
+    terminal: bool,
+}
+
+static OUTPUT: OnceLock<Mutex<Output>> = OnceLock::new();
+
+fn output() -> MutexGuard<'static, Output> {
+    let stdout = || {
+        Mutex::new(Output {
+            writer: Box::new(io::stdout()),
+            terminal: io::stdout().is_terminal(),
+        })
+    };
+    OUTPUT.get_or_init(stdout).lock().unwrap()
+}
+
+fn open_output(path: &str) {
+    let output = Output {
+        writer: Box::new(BufWriter::new(create_output(path))),
+        terminal: false,
+    };
+    let _ = OUTPUT.set(Mutex::new(output));
+}
+
+/// Writes to the report output. The commands can exit right after a
+/// report, so every write is flushed.
+fn write_report(write: impl FnOnce(&mut Box<dyn Write + Send>) -> io::Result<()>) {
+    let writer = &mut output().writer;
+    if let Err(error) = write(writer).and_then(|_| writer.flush()) {
+        println!("Failed to write the output: {}", error);
+        exit(Exit::Io);
+    }
+}
+
+/// `println!` to the report output.
+macro_rules! outln {
+    ($($arg:tt)*) => {
+        write_report(|out| writeln!(out, $($arg)*))
+    };
+}
+
+/// Limits of the downloads of the paths that are URLs, set by `--timeout`
+/// and `--max-size` for every command.
+static FETCH: OnceLock<FetchOptions> = OnceLock::new();
+
+fn fetch_options() -> &'static FetchOptions {
+    FETCH.get_or_init(FetchOptions::default)
+}
+
+/// Downloads the document if the path is a URL.
+fn fetch_input(filepath: &str) -> Option<input::Download> {
+    if !input::is_url(Path::new(filepath)) {
+        return None;
+    }
+    match input::download(filepath, fetch_options()) {
+        Ok(download) => Some(download),
+        Err(error) => {
+            println!("Failed to read '{}': {}", filepath, error);
+            exit(Exit::Io);
+        }
+    }
+}
+
+/// Documents at least this large show a progress bar while they're parsed.
+const PROGRESS_THRESHOLD: usize = 16 << 20;
+
+fn progress_bar(length: usize, template: &str) -> ProgressBar {
+    let style = ProgressStyle::with_template(template)
+        .expect("Invalid progress bar template")
+        .progress_chars("=> ");
+    ProgressBar::new(length as u64).with_style(style)
+}
+
+/// Progress bars on the standard error, shown while a large document is
+/// read and then while its components are parsed.
+static PROGRESS: LazyLock<ProgressCallback> = LazyLock::new(|| {
+    let state: Mutex<(bool, Option<ProgressBar>)> = Mutex::new((false, None));
+    ProgressCallback::new(move |progress| {
+        let (large, bar) = &mut *state.lock().unwrap();
+        match progress {
+            Progress::BytesRead { read, total } if total >= PROGRESS_THRESHOLD => {
+                *large = true;
+                let current = bar.get_or_insert_with(|| {
+                    progress_bar(total, "Parsing    [{bar:40}] {bytes}/{total_bytes}")
+                });
+                current.set_position(read as u64);
+                if read == total {
+                    current.finish_and_clear();
+                    *bar = None;
+                }
+            }
+            Progress::ComponentsParsed { parsed, total } if *large => {
+                let current = bar.get_or_insert_with(|| {
+                    progress_bar(total, "Components [{bar:40}] {pos}/{len}")
+                });
+                current.set_position(parsed as u64);
+                if parsed == total {
+                    current.finish_and_clear();
+                    *bar = None;
+                    *large = false;
+                }
+            }
+            _ => {}
+        }
+    })
+});
+
+fn load_root(filepath: &str) -> Element {
+    load_root_with_misc(filepath).0
+}
+
+fn load_root_with_misc(filepath: &str) -> (Element, misc::MiscNodes) {
+    load_entry_with_misc(filepath, None)
+}
+
+/// Reads the file, or the given file of a zip archive. URLs are downloaded
+/// with the limits of the command line.
+fn open_input(filepath: &str, entry: Option<&str>) -> Input {
+    let result = match input::is_url(Path::new(filepath)) {
+        true => Input::open_url(filepath, entry, fetch_options()),
+        false => Input::open_entry(filepath, entry),
+    };
     match result {
-        Ok(data_stream_collection) => {
-            data_stream_collection.print_information();
+        Ok(data) => data,
+        Err(error) => {
+            println!("Failed to read '{}': {}", filepath, error);
+            exit(Exit::Io);
         }
+    }
+}
+
+/// Loads the file, or the given file of a zip archive.
+fn load_entry_with_misc(filepath: &str, entry: Option<&str>) -> (Element, misc::MiscNodes) {
+    let data = open_input(filepath, entry);
+    tracing::info!(path = filepath, bytes = data.len(), "read the document");
+    match misc::parse_with_progress(&data, Some(&PROGRESS)) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            println!("Failed to parse '{}': {}", filepath, error);
+            exit(Exit::Failure);
+        }
+    }
+}
+
+/// Writes the document, with the preserved comments and processing
+/// instructions if `misc` is given.
+fn write_output(root: &Element, misc: Option<&misc::MiscNodes>, output: &str) {
+    let mut file = create_output(output);
+    let result = match misc {
+        Some(misc) => writer::write_document_with_misc(root, misc, &mut file),
+        None => writer::write_document(root, &mut file),
+    };
+    if let Err(error) = result {
+        println!("Failed to write '{}': {}", output, error);
+        exit(Exit::Io);
+    }
+}
+
+fn load_collection(filepath: &str) -> sds::DataStreamCollection {
+    let root = load_root(filepath);
+    parse_collection(filepath, &root)
+}
+
+fn parse_collection(filepath: &str, root: &Element) -> sds::DataStreamCollection {
+    let mut opts = ParseOptions::default();
+    opts.progress = Some(PROGRESS.clone());
+    parse_collection_with_options(filepath, root, &opts)
+}
+
+fn parse_collection_with_options(
+    filepath: &str,
+    root: &Element,
+    opts: &ParseOptions,
+) -> sds::DataStreamCollection {
+    if root.ns() != sds::SCAP12_NS {
+        println!("'{}' isn't a SCAP Source data stream collection", filepath);
+        exit(Exit::Unsupported);
+    }
+    match sds::DataStreamCollection::from_xml_with_options(root, opts) {
+        Ok(data_stream_collection) => data_stream_collection,
         Err(error) => {
             println!(
                 "Failed to parse SCAP Source data stream file '{}': {}",
-                args.filepath, error
+                filepath, error
             );
-            process::exit(1);
+            exit(Exit::Failure);
+        }
+    }
+}
+
+fn print_serialized<T: Serialize>(value: &T, format: Format) {
+    match output::serialize(value, format) {
+        Ok(text) => write_report(|out| out.write_all(text.as_bytes())),
+        Err(error) => {
+            println!("Failed to serialize the output: {}", error);
+            exit(Exit::Failure);
+        }
+    }
+}
+
+/// Runs `f` on the standalone benchmark, or on the first benchmark of the
+/// data stream that matches `filter`.
+fn with_benchmark<T>(
+    filepath: &str,
+    filter: impl Fn(&xccdf::Benchmark) -> bool,
+    f: impl Fn(&xccdf::Benchmark) -> Result<T, String>,
+) -> Result<T, String> {
+    let root = load_root(filepath);
+    if root.is("Benchmark", xccdf::XCCDF12_NS) {
+        match xccdf::Benchmark::from_xml(&root) {
+            Ok(benchmark) => f(&benchmark),
+            Err(error) => Err(format!("Failed to parse XCCDF benchmark: {}", error)),
+        }
+    } else {
+        let data_stream_collection = parse_collection(filepath, &root);
+        match data_stream_collection
+            .benchmarks()
+            .into_iter()
+            .find(|b| filter(b))
+        {
+            Some(benchmark) => f(benchmark),
+            None => Err(String::from("No matching XCCDF benchmark found")),
+        }
+    }
+}
+
+fn print_report(report: &report::Report, format: Format, no_color: bool, locale: Option<String>) {
+    let locale = match locale.or_else(|| report.summary.language.clone()) {
+        Some(tag) => Locale::from_tag(&tag),
+        None => Locale::default(),
+    };
+    match format {
+        Format::Text => {
+            let colored = !no_color && output().terminal;
+            write_report(|out| report.write_text(out, colored, &locale))
+        }
+        format => print_serialized(report, format),
+    }
+}
+
+fn list_rules(
+    filepath: &str,
+    profile_id: Option<String>,
+    selection: matrix::RuleSelection,
+    format: Format,
+    no_color: bool,
+    locale: Option<String>,
+    lang: Option<&str>,
+) {
+    let lang = lang.or(locale.as_deref());
+    let result = with_benchmark(
+        filepath,
+        |b| match &profile_id {
+            Some(profile_id) => b.find_profile(profile_id).is_some(),
+            None => true,
+        },
+        |benchmark| match &profile_id {
+            Some(profile_id) => {
+                let profile = benchmark.resolve_profile(profile_id)?;
+                Ok(report::list_rules(
+                    benchmark,
+                    Some(&profile),
+                    &selection,
+                    lang,
+                ))
+            }
+            None => Ok(report::list_rules(benchmark, None, &selection, lang)),
+        },
+    );
+    match result {
+        Ok(report) => print_report(&report, format, no_color, locale),
+        Err(error) => {
+            println!("Failed to list rules in '{}': {}", filepath, error);
+            exit(Exit::Failure);
+        }
+    }
+}
+
+fn stats(filepath: &str, format: Format) {
+    match with_benchmark(filepath, |_| true, |b| Ok(stats::content_stats(b))) {
+        Ok(stats) if format == Format::Text => write_report(|out| stats.write_text(out)),
+        Ok(stats) => print_serialized(&stats, format),
+        Err(error) => {
+            println!("Failed to compute statistics of '{}': {}", filepath, error);
+            exit(Exit::Failure);
+        }
+    }
+}
+
+fn search(filepath: &str, query: query::SearchQuery, format: Format) {
+    let hits = match with_benchmark(filepath, |_| true, |b| Ok(query::search(b, &query))) {
+        Ok(hits) => hits,
+        Err(error) => {
+            println!("Failed to search '{}': {}", filepath, error);
+            exit(Exit::Failure);
+        }
+    };
+    match format {
+        Format::Text if hits.is_empty() => outln!("No matching rules, groups or values found"),
+        Format::Text => {
+            for hit in hits.iter() {
+                outln!("{} {}", hit.kind, hit.id);
+                if !hit.title.is_empty() {
+                    outln!("  Title: {}", hit.title);
+                }
+                if !hit.path.is_empty() {
+                    outln!("  Path: {}", hit.path.join(" > "));
+                }
+                if !hit.profiles.is_empty() {
+                    outln!("  Profiles: {}", hit.profiles.join(", "));
+                }
+            }
+        }
+        format => print_serialized(&hits, format),
+    }
+    if hits.is_empty() {
+        exit(Exit::Failure);
+    }
+}
+
+fn applicability(filepath: &str, targets: &[Cpe], profile_id: Option<String>, format: Format) {
+    let result = with_benchmark(
+        filepath,
+        |b| match &profile_id {
+            Some(profile_id) => b.find_profile(profile_id).is_some(),
+            None => true,
+        },
+        |benchmark| match &profile_id {
+            Some(profile_id) => {
+                let profile = benchmark.resolve_profile(profile_id)?;
+                Ok(matrix::applicability(benchmark, targets, Some(&profile)))
+            }
+            None => Ok(matrix::applicability(benchmark, targets, None)),
+        },
+    );
+    match result {
+        Ok(applicability) if format == Format::Text => {
+            write_report(|out| applicability.write_text(out))
+        }
+        Ok(applicability) => print_serialized(&applicability, format),
+        Err(error) => {
+            println!(
+                "Failed to evaluate the applicability of '{}': {}",
+                filepath, error
+            );
+            exit(Exit::Failure);
+        }
+    }
+}
+
+fn results_summary(
+    filepath: &str,
+    test_result: Option<String>,
+    format: Format,
+    no_color: bool,
+    locale: Option<String>,
+    lang: Option<&str>,
+) {
+    let lang = lang.or(locale.as_deref());
+    let result = with_benchmark(
+        filepath,
+        |b| b.has_test_results(),
+        |benchmark| report::results_summary(benchmark, test_result.as_deref(), lang),
+    );
+    match result {
+        Ok(report) => print_report(&report, format, no_color, locale),
+        Err(error) => {
+            println!("Failed to summarize results in '{}': {}", filepath, error);
+            exit(Exit::Failure);
+        }
+    }
+}
+
+fn parse_options(lenient: bool, max_errors: Option<usize>, permissive: bool) -> ParseOptions {
+    let mut opts = match (lenient, max_errors) {
+        (_, Some(max_errors)) => ParseOptions::collecting(max_errors),
+        (true, None) => ParseOptions::lenient(),
+        (false, None) => ParseOptions::default(),
+    };
+    opts.permissive = permissive;
+    opts.progress = Some(PROGRESS.clone());
+    opts
+}
+
+/// Loads only the checklist components, the rest of the file is skipped
+/// without building its tree.
+fn load_streamed(
+    filepath: &str,
+    entry: Option<&str>,
+    opts: &ParseOptions,
+) -> sds::DataStreamCollection {
+    let result =
+        StreamedCollection::open_with_options(filepath, entry, opts).and_then(|streamed| {
+            let checklists = streamed.checklist_components();
+            streamed.load(&checklists, opts)
+        });
+    match result {
+        Ok(data_stream_collection) => data_stream_collection,
+        Err(error) => {
+            println!(
+                "Failed to parse SCAP Source data stream file '{}': {}",
+                filepath, error
+            );
+            match error.cause() {
+                Error::Io { .. } => exit(Exit::Io),
+                _ => exit(Exit::Failure),
+            }
+        }
+    }
+}
+
+fn info(filepath: &str, args: &Args) {
+    let opts = parse_options(args.lenient, args.max_errors, args.permissive);
+    let download = fetch_input(filepath);
+    let filepath = download
+        .as_ref()
+        .map_or(filepath, |d| d.path().to_str().unwrap_or_default());
+    let entry = args.entry.as_deref();
+    let mut data_stream_collection = match args.stream {
+        true => load_streamed(filepath, entry, &opts),
+        false => {
+            let root = load_entry_with_misc(filepath, entry).0;
+            parse_collection_with_options(filepath, &root, &opts)
+        }
+    };
+    if args.fetch_remote_resources {
+        let fetch = FetchOptions {
+            cache_dir: args.cache_dir.clone().or_else(input::default_cache_dir),
+            ..fetch_options().clone()
+        };
+        if let Err(error) = data_stream_collection.fetch_remote_components(&opts, &fetch) {
+            println!("Failed to fetch the remote components: {}", error);
+            exit(Exit::Io);
+        }
+    }
+    let warnings = opts.take_warnings();
+    for warning in warnings.iter() {
+        tracing::warn!("{}", warning);
+    }
+    let (format, lang) = (args.format, args.lang.as_deref());
+    if args.debug_model {
+        let stats: Vec<xccdf::MemoryStats> = data_stream_collection
+            .benchmarks()
+            .iter()
+            .map(|b| b.memory_stats())
+            .collect();
+        match format {
+            Format::Text => write_report(|out| stats.iter().try_for_each(|s| s.write_text(out))),
+            format => print_serialized(&stats, format),
+        }
+    } else {
+        match format {
+            Format::Text => {
+                let colored = !args.no_color && output().terminal;
+                write_report(|out| data_stream_collection.write_information(out, lang, colored))
+            }
+            format => print_serialized(&data_stream_collection.info(lang), format),
+        }
+    }
+    if !warnings.is_empty() {
+        exit(Exit::Warnings);
+    }
+}
+
+fn inventory(filepaths: &[String], format: Format) {
+    let paths: Vec<PathBuf> = filepaths.iter().map(PathBuf::from).collect();
+    let files = match inventory::discover(&paths) {
+        Ok(files) => files,
+        Err(error) => {
+            println!("{}", error);
+            exit(Exit::Io);
+        }
+    };
+    let summaries: Vec<inventory::FileSummary> = files
+        .iter()
+        .map(|path| inventory::summarize(path))
+        .collect();
+    match format {
+        Format::Text => write_report(|out| inventory::write_summaries(out, &summaries)),
+        format => print_serialized(&summaries, format),
+    }
+    if summaries.iter().any(|s| s.unreadable) {
+        exit(Exit::Io);
+    }
+    if summaries.iter().any(|s| s.error.is_some()) {
+        exit(Exit::Failure);
+    }
+}
+
+fn export_ckl(input: &str, output: &str, test_result: Option<String>) {
+    let root = load_root(input);
+    let result = if root.is("Benchmark", xccdf::XCCDF12_NS) {
+        match xccdf::Benchmark::from_xml(&root) {
+            Ok(benchmark) => ckl::benchmark_to_ckl(&benchmark, test_result.as_deref()),
+            Err(error) => Err(format!("Failed to parse XCCDF benchmark: {}", error)),
+        }
+    } else {
+        let data_stream_collection = parse_collection(input, &root);
+        match data_stream_collection.benchmarks().first() {
+            Some(benchmark) => ckl::benchmark_to_ckl(benchmark, test_result.as_deref()),
+            None => Err(String::from(
+                "The data stream doesn't contain any XCCDF benchmark",
+            )),
+        }
+    };
+    let checklist = match result {
+        Ok(checklist) => checklist,
+        Err(error) => {
+            println!("Failed to export '{}': {}", input, error);
+            exit(Exit::Failure);
+        }
+    };
+    let mut file = create_output(output);
+    if let Err(error) = writer::write_document(&checklist, &mut file) {
+        println!("Failed to write '{}': {}", output, error);
+        exit(Exit::Io);
+    }
+}
+
+fn export_matrix(
+    filepath: &str,
+    profile_id: Option<String>,
+    platforms: Vec<String>,
+    selection: matrix::RuleSelection,
+    format: Format,
+) {
+    let root = load_root(filepath);
+    let data_stream_collection;
+    let standalone;
+    let benchmark = if root.is("Benchmark", xccdf::XCCDF12_NS) {
+        standalone = match xccdf::Benchmark::from_xml(&root) {
+            Ok(benchmark) => benchmark,
+            Err(error) => {
+                println!("Failed to parse XCCDF benchmark '{}': {}", filepath, error);
+                exit(Exit::Failure);
+            }
+        };
+        Some(&standalone)
+    } else {
+        data_stream_collection = parse_collection(filepath, &root);
+        data_stream_collection
+            .benchmarks()
+            .into_iter()
+            .find(|b| match &profile_id {
+                Some(profile_id) => b.find_profile(profile_id).is_some(),
+                None => true,
+            })
+    };
+    let result = match benchmark {
+        Some(benchmark) => {
+            let dictionary = matrix::dictionary(&root);
+            match &profile_id {
+                Some(profile_id) => benchmark.resolve_profile(profile_id).map(|profile| {
+                    matrix::platform_matrix(
+                        benchmark,
+                        &dictionary,
+                        &platforms,
+                        Some(&profile),
+                        &selection,
+                    )
+                }),
+                None => Ok(matrix::platform_matrix(
+                    benchmark,
+                    &dictionary,
+                    &platforms,
+                    None,
+                    &selection,
+                )),
+            }
+            .map_err(String::from)
+        }
+        None => Err(String::from("No matching XCCDF benchmark found")),
+    };
+    match result {
+        Ok(matrix) if format == Format::Csv => {
+            write_report(|out| out.write_all(matrix.to_csv().as_bytes()))
+        }
+        Ok(matrix) => print_serialized(&matrix, format),
+        Err(error) => {
+            println!("Failed to export the matrix of '{}': {}", filepath, error);
+            exit(Exit::Failure);
+        }
+    }
+}
+
+fn export_controls(
+    filepath: &str,
+    standard: controls::Standard,
+    profile_id: Option<String>,
+    format: Format,
+) {
+    let result = with_benchmark(
+        filepath,
+        |b| match &profile_id {
+            Some(profile_id) => b.find_profile(profile_id).is_some(),
+            None => true,
+        },
+        |benchmark| match &profile_id {
+            Some(profile_id) => {
+                let profile = benchmark.resolve_profile(profile_id)?;
+                Ok(controls::control_mapping(
+                    benchmark,
+                    Some(&profile),
+                    standard,
+                ))
+            }
+            None => Ok(controls::control_mapping(benchmark, None, standard)),
+        },
+    );
+    match result {
+        Ok(mapping) if format == Format::Text => write_report(|out| mapping.write_text(out)),
+        Ok(mapping) if format == Format::Csv => {
+            write_report(|out| out.write_all(mapping.to_csv().as_bytes()))
+        }
+        Ok(mapping) => print_serialized(&mapping, format),
+        Err(error) => {
+            println!("Failed to map the controls of '{}': {}", filepath, error);
+            exit(Exit::Failure);
+        }
+    }
+}
+
+fn signatures(filepath: &str, trust_store: Option<String>, allow_algorithm: Vec<String>) {
+    let root = load_root(filepath);
+    let data_stream_collection = parse_collection(filepath, &root);
+    let policy = trust_store.map(|path| {
+        let store = match trust::TrustStore::load(Path::new(&path)) {
+            Ok(store) => store,
+            Err(error) => {
+                println!("Failed to load the trust store: {}", error);
+                exit(Exit::Failure);
+            }
+        };
+        let mut policy = trust::SigningPolicy::new(store);
+        if !allow_algorithm.is_empty() {
+            policy.allowed_algorithms = allow_algorithm;
+        }
+        policy
+    });
+    let checks = data_stream_collection.check_signatures(&root, policy.as_ref());
+    let mut failed = false;
+    for check in checks.iter() {
+        let signature_id = check.signature_id.as_deref().unwrap_or("without ID");
+        if let Some(signer) = &check.signer {
+            outln!(
+                "{} (signature {}): signed by {}",
+                check.signed_item,
+                signature_id,
+                signer
+            );
+        }
+        if check.problems.is_empty() {
+            outln!("{} (signature {}): OK", check.signed_item, signature_id);
+        } else {
+            failed = true;
+            outln!("{} (signature {}): FAILED", check.signed_item, signature_id);
+            for problem in check.problems.iter() {
+                outln!("  {}", problem);
+            }
+        }
+    }
+    outln!("{} signatures checked", checks.len());
+    if policy.is_none() {
+        outln!("Note: without --trust-store, only the structure of the signatures is checked.");
+    }
+    if failed {
+        exit(Exit::Failure);
+    }
+}
+
+fn profile_show(filepath: &str, profile_id: &str, resolved: bool) {
+    let data_stream_collection = load_collection(filepath);
+    if resolved {
+        match data_stream_collection.resolve_profile(profile_id) {
+            Ok(profile) => write_report(|out| profile.write_selections(out)),
+            Err(error) => {
+                println!("Failed to resolve profile in '{}': {}", filepath, error);
+                exit(Exit::Failure);
+            }
+        }
+        return;
+    }
+    match data_stream_collection.find_profile(profile_id) {
+        Some(profile) => write_report(|out| profile.write_details(out)),
+        None => {
+            println!("Profile '{}' not found in '{}'", profile_id, filepath);
+            exit(Exit::Failure);
+        }
+    }
+}
+
+fn profile_show_effective(filepath: &str, profile_id: &str, lang: Option<&str>) {
+    let result = with_benchmark(
+        filepath,
+        |b| b.find_profile(profile_id).is_some(),
+        |benchmark| {
+            let profile = benchmark
+                .resolve_profile(profile_id)
+                .map_err(|e| e.to_string())?;
+            Ok(report::effective_profile(benchmark, &profile, lang))
+        },
+    );
+    match result {
+        Ok(effective) => write_report(|out| effective.write_text(out)),
+        Err(error) => {
+            println!("Failed to resolve profile in '{}': {}", filepath, error);
+            exit(Exit::Failure);
+        }
+    }
+}
+
+fn tailoring_apply(filepath: &str, tailoring_path: &str, profile: Option<String>, format: Format) {
+    let data_stream_collection = load_collection(filepath);
+    let tailoring = match xccdf::Tailoring::from_xml(&load_root(tailoring_path)) {
+        Ok(tailoring) => tailoring,
+        Err(error) => {
+            println!(
+                "Failed to parse XCCDF tailoring file '{}': {}",
+                tailoring_path, error
+            );
+            exit(Exit::Failure);
+        }
+    };
+    let profile_id = match profile {
+        Some(profile_id) => profile_id,
+        None => match tailoring.profiles() {
+            [profile] => profile.id().to_string(),
+            profiles => {
+                println!("The tailoring contains multiple profiles, choose one with --profile:");
+                for profile in profiles {
+                    println!("* {}", profile.id());
+                }
+                exit(Exit::Failure);
+            }
+        },
+    };
+    let resolved = match data_stream_collection.resolve_tailored_profile(&tailoring, &profile_id) {
+        Ok(resolved) => resolved,
+        Err(error) => {
+            println!("Failed to apply tailoring '{}': {}", tailoring_path, error);
+            exit(Exit::Failure);
+        }
+    };
+    match format {
+        Format::Text => write_report(|out| resolved.write_selections(out)),
+        format => print_serialized(&resolved.info(), format),
+    }
+}
+
+fn oval_stats(filepath: &str, format: Format) {
+    let root = load_root(filepath);
+    let stats = oval::stats(&root);
+    match format {
+        Format::Text => write_report(|out| stats.write_text(out)),
+        format => print_serialized(&stats, format),
+    }
+}
+
+fn oval_dedup(input: &str, output: &str, keep_comments: bool) {
+    let (mut root, misc) = load_root_with_misc(input);
+    let stats = oval::dedup(&mut root);
+    if stats.documents == 0 {
+        println!("'{}' doesn't contain any OVAL definitions", input);
+        exit(Exit::Unsupported);
+    }
+    for section in stats.sections.iter().filter(|s| s.section != "definitions") {
+        outln!(
+            "Merged {} duplicate {}",
+            section.duplicates,
+            section.section
+        );
+    }
+    write_output(&root, Some(&misc).filter(|_| keep_comments), output);
+}
+
+fn oval_cves(filepath: &str, min_severity: Option<cvss::CvssSeverity>, sort: &str, format: Format) {
+    let root = load_root(filepath);
+    let standalone;
+    let data_stream_collection;
+    let documents: Vec<&oval::OvalDefinitions> = if root.is("oval_definitions", oval::OVAL_DEF_NS) {
+        let opts = ParseOptions::default();
+        standalone = match oval::OvalDefinitions::from_xml_with_options(&root, &opts) {
+            Ok(definitions) => definitions,
+            Err(error) => {
+                println!("Failed to parse OVAL definitions '{}': {}", filepath, error);
+                exit(Exit::Failure);
+            }
+        };
+        for warning in opts.take_warnings() {
+            tracing::warn!("{}", warning);
+        }
+        vec![&standalone]
+    } else {
+        data_stream_collection = parse_collection(filepath, &root);
+        data_stream_collection
+            .components()
+            .iter()
+            .filter_map(|c| c.oval_definitions())
+            .collect()
+    };
+    let mut coverage = oval::cve_coverage(&documents);
+    if let Some(min_severity) = min_severity {
+        coverage.retain(|c| c.severity().is_some_and(|s| s >= min_severity));
+    }
+    if sort == "score" {
+        oval::sort_by_score(&mut coverage);
+    }
+    match format {
+        Format::Text => {
+            for cve in coverage.iter() {
+                let score = match &cve.cvss {
+                    Some(cvss) => format!("{:.1} {}", cvss.base_score(), cvss.severity()),
+                    None => String::from("-"),
+                };
+                outln!("{} {} {}", cve.cve, score, cve.definitions.join(", "));
+            }
+        }
+        format => print_serialized(&coverage, format),
+    }
+}
+
+fn oval_variables(filepath: &str, variables_path: &str, format: Format) {
+    let variables = match oval::OvalVariables::from_xml(&load_root(variables_path)) {
+        Ok(variables) => variables,
+        Err(error) => {
+            println!(
+                "Failed to parse OVAL variables '{}': {}",
+                variables_path, error
+            );
+            exit(Exit::Failure);
+        }
+    };
+    let result = with_benchmark(
+        filepath,
+        |_| true,
+        |benchmark| {
+            let bindings = oval::export_bindings(benchmark, &variables);
+            if format == Format::Text && bindings.is_empty() {
+                outln!("No rules with check-exports found");
+            } else if format == Format::Text {
+                for binding in bindings.iter() {
+                    let values = match binding.variable {
+                        Some(variable) => {
+                            format!("{} {}", variable.datatype(), variable.values().join(", "))
+                        }
+                        None => String::from("missing"),
+                    };
+                    outln!(
+                        "{} {} {}: {}",
+                        binding.rule,
+                        binding.value,
+                        binding.export_name,
+                        values
+                    );
+                }
+            } else {
+                print_serialized(&bindings, format);
+            }
+            Ok(bindings.iter().all(|b| b.variable.is_some()))
+        },
+    );
+    match result {
+        Ok(true) => (),
+        Ok(false) => exit(Exit::Failure),
+        Err(error) => {
+            println!("Failed to bind the variables of '{}': {}", filepath, error);
+            exit(Exit::Failure);
+        }
+    }
+}
+
+fn oval_results(
+    filepath: &str,
+    xccdf: Option<String>,
+    test_result: Option<String>,
+    format: Format,
+) {
+    let results = match oval::OvalResults::from_xml(&load_root(filepath)) {
+        Ok(results) => results,
+        Err(error) => {
+            println!("Failed to parse OVAL results '{}': {}", filepath, error);
+            exit(Exit::Failure);
+        }
+    };
+    let xccdf = match xccdf {
+        Some(xccdf) => xccdf,
+        None => {
+            match format {
+                Format::Text => write_report(|out| results.write_text(out)),
+                format => print_serialized(&results, format),
+            }
+            return;
+        }
+    };
+    let result = with_benchmark(
+        &xccdf,
+        |b| b.has_test_results(),
+        |benchmark| {
+            let test_result = benchmark.test_result(test_result.as_deref())?;
+            let correlated = oval::correlate(benchmark, test_result, &results);
+            if format != Format::Text {
+                print_serialized(&correlated, format);
+                return Ok(());
+            }
+            for row in correlated.iter() {
+                let oval_result = row.oval_result.map_or("-", |r| r.as_str());
+                let mark = match row.consistent {
+                    Some(false) => " (inconsistent)",
+                    _ => "",
+                };
+                outln!(
+                    "{:<14} {:<15} {} {}{}",
+                    row.result,
+                    oval_result,
+                    row.rule,
+                    row.definition.unwrap_or("-"),
+                    mark
+                );
+            }
+            Ok(())
+        },
+    );
+    if let Err(error) = result {
+        println!("Failed to correlate the results of '{}': {}", xccdf, error);
+        exit(Exit::Failure);
+    }
+}
+
+fn results_score(filepath: &str, test_result: Option<String>, format: Format) {
+    let result = with_benchmark(
+        filepath,
+        |b| b.has_test_results(),
+        |benchmark| {
+            let test_result = benchmark.test_result(test_result.as_deref())?;
+            Ok(scoring::verify_scores(benchmark, test_result))
+        },
+    );
+    let verification = match result {
+        Ok(verification) => verification,
+        Err(error) => {
+            println!("Failed to score the results in '{}': {}", filepath, error);
+            exit(Exit::Failure);
+        }
+    };
+    match format {
+        Format::Text => write_report(|out| verification.write_text(out)),
+        format => print_serialized(&verification, format),
+    }
+    if verification.has_mismatches() {
+        exit(Exit::Failure);
+    }
+}
+
+fn diff_results(
+    old: &str,
+    new: &str,
+    old_test_result: Option<String>,
+    new_test_result: Option<String>,
+    format: Format,
+) {
+    let result = with_test_result(
+        old,
+        old_test_result.as_deref(),
+        |old_benchmark, old_result| {
+            with_test_result(
+                new,
+                new_test_result.as_deref(),
+                |new_benchmark, new_result| {
+                    Ok(diff::diff_results(
+                        (old_benchmark, old_result),
+                        (new_benchmark, new_result),
+                    ))
+                },
+            )
+        },
+    );
+    match result {
+        Ok(diff) => match format {
+            Format::Text => write_report(|out| diff.write_text(out)),
+            format => print_serialized(&diff, format),
+        },
+        Err(error) => {
+            println!("Failed to compare '{}' and '{}': {}", old, new, error);
+            exit(Exit::Failure);
+        }
+    }
+}
+
+fn diff_content(old: &str, new: &str, format: Format) {
+    let result = with_benchmark(
+        old,
+        |_| true,
+        |old_benchmark| {
+            with_benchmark(
+                new,
+                |_| true,
+                |new_benchmark| diff::diff_content(old_benchmark, new_benchmark),
+            )
+        },
+    );
+    match result {
+        Ok(diff) => match format {
+            Format::Text => write_report(|out| diff.write_text(out)),
+            format => print_serialized(&diff, format),
+        },
+        Err(error) => {
+            println!("Failed to compare '{}' and '{}': {}", old, new, error);
+            exit(Exit::Failure);
+        }
+    }
+}
+
+fn generate_fix(filepath: &str, profile_id: &str, system: FixSystem) {
+    let fix_for = |benchmark: &xccdf::Benchmark| {
+        let profile = benchmark.resolve_profile(profile_id)?;
+        remediation::generate_fix(benchmark, &profile, system)
+    };
+    let root = load_root(filepath);
+    let result = if root.is("Benchmark", xccdf::XCCDF12_NS) {
+        match xccdf::Benchmark::from_xml(&root) {
+            Ok(benchmark) => fix_for(&benchmark),
+            Err(error) => Err(format!("Failed to parse XCCDF benchmark: {}", error)),
+        }
+    } else {
+        let data_stream_collection = parse_collection(filepath, &root);
+        match data_stream_collection
+            .benchmarks()
+            .into_iter()
+            .find(|b| b.find_profile(profile_id).is_some())
+        {
+            Some(benchmark) => fix_for(benchmark),
+            None => Err(format!("Profile '{}' not found", profile_id)),
+        }
+    };
+    let script = match result {
+        Ok(script) => script,
+        Err(error) => {
+            println!("Failed to generate the remediation script: {}", error);
+            exit(Exit::Failure);
+        }
+    };
+    write_report(|out| out.write_all(script.as_bytes()));
+}
+
+fn generate_tailoring(filepath: &str, profile_id: &str) {
+    let result = with_benchmark(
+        filepath,
+        |b| b.find_profile(profile_id).is_some(),
+        |benchmark| {
+            let profile = benchmark
+                .resolve_profile(profile_id)
+                .map_err(|e| e.to_string())?;
+            let skeleton = tailoring::tailoring_skeleton(benchmark, &profile, filepath);
+            writer::to_string(&skeleton).map_err(|e| e.to_string())
+        },
+    );
+    let text = match result {
+        Ok(text) => text,
+        Err(error) => {
+            println!("Failed to generate the tailoring: {}", error);
+            exit(Exit::Failure);
+        }
+    };
+    write_report(|out| out.write_all(text.as_bytes()));
+}
+
+/// Runs `f` on the TestResult with the ID, by default the last one, and the
+/// benchmark it was evaluated against, taken from an ARF or from XCCDF
+/// results.
+fn with_test_result<T>(
+    filepath: &str,
+    id: Option<&str>,
+    f: impl Fn(&xccdf::Benchmark, &xccdf::TestResult) -> Result<T, String>,
+) -> Result<T, String> {
+    let root = load_root(filepath);
+    if root.is("asset-report-collection", arf::ARF_NS) {
+        let arf = ArfResults::from_xml(&root, &ParseOptions::default())
+            .map_err(|error| format!("Failed to parse the ARF: {}", error))?;
+        let test_result = arf.test_result(id)?;
+        match arf.collection().benchmarks().first() {
+            Some(benchmark) => f(benchmark, test_result),
+            None => Err(String::from("The ARF doesn't contain a XCCDF benchmark")),
+        }
+    } else {
+        with_benchmark(
+            filepath,
+            |b| b.has_test_results(),
+            |benchmark| f(benchmark, benchmark.test_result(id)?),
+        )
+    }
+}
+
+fn generate_report(filepath: &str, test_result: Option<String>, lang: Option<&str>) {
+    let result = with_test_result(
+        filepath,
+        test_result.as_deref(),
+        |benchmark, test_result| Ok(html::results_report(benchmark, test_result, lang)),
+    );
+    let report = match result {
+        Ok(report) => report,
+        Err(error) => {
+            println!("Failed to generate the report of '{}': {}", filepath, error);
+            exit(Exit::Failure);
+        }
+    };
+    write_report(|out| out.write_all(report.as_bytes()));
+}
+
+fn selftest() {
+    let results = selftest::run();
+    let failed = results.iter().filter(|r| !r.passed).count();
+    for result in results.iter() {
+        let status = if result.passed { "PASS" } else { "FAIL" };
+        match (&result.message, result.passed) {
+            (Some(message), false) => outln!("{}: {} ({})", status, result.name, message),
+            _ => outln!("{}: {}", status, result.name),
+        }
+    }
+    outln!("{} passed, {} failed", results.len() - failed, failed);
+    if failed > 0 {
+        exit(Exit::Failure);
+    }
+}
+
+fn doctor(filepath: &str) {
+    let data = open_input(filepath, None);
+    let diagnosis = doctor::diagnose(&data, filepath);
+    for finding in diagnosis.findings.iter() {
+        outln!(
+            "[{}] {}: {}",
+            finding.level.as_str(),
+            finding.probe,
+            finding.message
+        );
+    }
+    if !diagnosis.suggestions.is_empty() {
+        outln!("Suggested commands:");
+        for suggestion in diagnosis.suggestions.iter() {
+            outln!("  {}", suggestion);
+        }
+    }
+    if diagnosis.has_errors() {
+        exit(Exit::Failure);
+    }
+}
+
+fn tree(filepath: &str) {
+    let root = load_root(filepath);
+    let data_stream_collection = parse_collection(filepath, &root);
+    let tree = tree::collection_tree(&data_stream_collection, &root);
+    write_report(|out| out.write_all(tree.render().as_bytes()));
+}
+
+fn read_for_validation(filepath: &str) -> (Input, Element) {
+    let data = open_input(filepath, None);
+    match misc::parse(&data) {
+        Ok((root, _)) => (data, root),
+        Err(error) => {
+            println!("Failed to parse '{}': {}", filepath, error);
+            exit(Exit::Failure);
+        }
+    }
+}
+
+fn schema_violations(data: &[u8], root: &Element, schema_dir: &Path) -> Vec<validate::Violation> {
+    let path = match validate::schema_for(root) {
+        Some(path) => schema_dir.join(path),
+        None => {
+            println!("No schema is known for '{}' documents", root.name());
+            exit(Exit::Unsupported);
+        }
+    };
+    match validate::validate_schema(data, &path) {
+        Ok(found) => found,
+        Err(error) => {
+            println!("{}", error);
+            exit(Exit::Failure);
+        }
+    }
+}
+
+fn validate(filepath: &str, schema: bool, schema_dir: &Path, max_age: Option<u64>) {
+    let (data, root) = read_for_validation(filepath);
+    let mut violations = validate::validate_model(&root);
+    if schema {
+        violations.extend(schema_violations(&data, &root, schema_dir));
+    }
+    for violation in violations.iter() {
+        match violation.line {
+            Some(line) => outln!("{}:{}: {}", filepath, line, violation.message),
+            None => outln!("{}: {}", filepath, violation.message),
+        }
+    }
+    if !violations.is_empty() {
+        exit(Exit::Failure);
+    }
+    let outdated = match (max_age, sds::DataStreamCollection::from_xml(&root)) {
+        (Some(max_days), Ok(collection)) => validate::check_age(&collection, max_days),
+        _ => Vec::new(),
+    };
+    for violation in outdated.iter() {
+        outln!("{}: WARNING: {}", filepath, violation.message);
+    }
+    outln!("'{}' is valid", filepath);
+    if !outdated.is_empty() {
+        exit(Exit::Warnings);
+    }
+}
+
+fn validate_requirements(
+    filepath: &str,
+    schema: bool,
+    schema_dir: &Path,
+    format: Format,
+    max_age: Option<u64>,
+) {
+    let (data, root) = read_for_validation(filepath);
+    let collection = parse_collection(filepath, &root);
+    let mut requirements = Vec::new();
+    if schema {
+        requirements.push(validate::Requirement::new(
+            "schema",
+            "the document is valid against the XSD schema",
+            schema_violations(&data, &root, schema_dir),
+        ));
+    }
+    requirements.extend(validate::check_requirements(&collection));
+    if let Some(max_days) = max_age {
+        requirements.push(validate::Requirement::new(
+            "max-age",
+            "the content isn't older than the maximum age",
+            validate::check_age(&collection, max_days),
+        ));
+    }
+    let passed = requirements.iter().all(|r| r.passed());
+    match format {
+        Format::Text => {
+            for requirement in requirements.iter() {
+                let result = if requirement.passed() { "PASS" } else { "FAIL" };
+                outln!(
+                    "[{}] {}: {}",
+                    result,
+                    requirement.name,
+                    requirement.description
+                );
+                for violation in requirement.violations.iter() {
+                    match violation.line {
+                        Some(line) => outln!("    {}:{}: {}", filepath, line, violation.message),
+                        None => outln!("    {}", violation.message),
+                    }
+                }
+            }
+            outln!("Result: {}", if passed { "PASS" } else { "FAIL" });
+        }
+        _ => print_serialized(&requirements, format),
+    }
+    if !passed {
+        exit(Exit::Failure);
+    }
+}
+
+fn validate_directory(
+    directory: &str,
+    schema: bool,
+    schema_dir: &Path,
+    format: Format,
+    max_age: Option<u64>,
+) {
+    let schema_dir = if schema { Some(schema_dir) } else { None };
+    let validation = match validate::validate_content(Path::new(directory), schema_dir, max_age) {
+        Ok(validation) => validation,
+        Err(error) => {
+            println!("{}", error);
+            exit(Exit::Io);
+        }
+    };
+    match format {
+        Format::Text => {
+            for file in validation.files.iter() {
+                let problems = [("ERROR", &file.errors), ("WARNING", &file.warnings)];
+                for (level, violations) in problems {
+                    for violation in violations.iter() {
+                        match violation.line {
+                            Some(line) => {
+                                outln!("{}:{}: {}: {}", file.path, line, level, violation.message)
+                            }
+                            None => outln!("{}: {}: {}", file.path, level, violation.message),
+                        }
+                    }
+                }
+            }
+            outln!(
+                "{} data streams, {} errors, {} warnings",
+                validation.files.len(),
+                validation.errors,
+                validation.warnings
+            );
+        }
+        format => print_serialized(&validation, format),
+    }
+    if !validation.passed() {
+        exit(Exit::Failure);
+    }
+    if validation.warnings > 0 {
+        exit(Exit::Warnings);
+    }
+}
+
+fn pack_create(directory: &str) {
+    let dir = Path::new(directory);
+    let manifest = match pack::create(dir).and_then(|m| pack::write_manifest(dir, &m).map(|_| m)) {
+        Ok(manifest) => manifest,
+        Err(error) => {
+            println!("Failed to create the content pack manifest: {}", error);
+            exit(Exit::Failure);
+        }
+    };
+    for file in manifest.files.iter() {
+        outln!("{} ({} profiles)", file.path, file.profiles.len());
+    }
+    outln!(
+        "{} files written to {}",
+        manifest.files.len(),
+        dir.join(pack::MANIFEST_NAME).display()
+    );
+}
+
+fn pack_verify(directory: &str) {
+    let dir = Path::new(directory);
+    let problems = match pack::read_manifest(dir).and_then(|m| pack::verify(dir, &m)) {
+        Ok(problems) => problems,
+        Err(error) => {
+            println!("Failed to verify the content pack: {}", error);
+            exit(Exit::Failure);
+        }
+    };
+    for problem in problems.iter() {
+        outln!("{}", problem);
+    }
+    if !problems.is_empty() {
+        outln!("{} problems found", problems.len());
+        exit(Exit::Failure);
+    }
+    outln!("Content pack OK");
+}
+
+fn resolve(input: &str, output: &str, profile: Option<&str>, keep_comments: bool) {
+    let (mut root, misc) = load_root_with_misc(input);
+    if let Err(error) = resolve::resolve(&mut root, profile) {
+        println!("Failed to resolve '{}': {}", input, error);
+        exit(Exit::Failure);
+    }
+    write_output(&root, Some(&misc).filter(|_| keep_comments), output);
+}
+
+fn upgrade_xccdf(input: &str, output: &str, reverse_dns: &str) {
+    let upgraded = match upgrade::xccdf_1_1_to_1_2(&load_root(input), reverse_dns) {
+        Ok(upgraded) => upgraded,
+        Err(error) => {
+            println!("Failed to upgrade '{}': {}", input, error);
+            exit(Exit::Failure);
+        }
+    };
+    for note in upgraded.notes.iter() {
+        outln!("{}", note);
+    }
+    write_output(&upgraded.root, None, output);
+}
+
+fn upgrade_scap(input: &str, output: &str) {
+    let mut root = load_root(input);
+    let original = sds::DataStreamCollection::from_xml(&root).ok();
+    let notes = match upgrade::scap_1_2_to_1_3(&mut root) {
+        Ok(notes) => notes,
+        Err(error) => {
+            println!("Failed to upgrade '{}': {}", input, error);
+            exit(Exit::Failure);
+        }
+    };
+    let upgraded = parse_collection(input, &root);
+    let violations = upgrade::scap_1_3_violations(original.as_ref(), &upgraded);
+    if !violations.is_empty() {
+        println!("The upgraded data stream doesn't meet the SCAP 1.3 requirements:");
+        for violation in violations.iter() {
+            println!("* {}", violation);
+        }
+        exit(Exit::Failure);
+    }
+    for note in notes.iter() {
+        outln!("{}", note);
+    }
+    write_output(&root, None, output);
+}
+
+/// Applies the edit to the collection and writes it if it's still a valid
+/// data stream collection. With `digests`, the signatures are replaced by
+/// a template with the digests of the edited components.
+fn edit_components<F>(input: &str, output: &str, digests: bool, edit: F)
+where
+    F: FnOnce(&mut Element) -> Result<Vec<String>, String>,
+{
+    let (mut root, misc) = load_root_with_misc(input);
+    let edited = edit(&mut root).and_then(|mut notes| {
+        if digests {
+            notes.extend(compose::add_signature_references(&mut root)?);
+        }
+        Ok(notes)
+    });
+    let notes = match edited {
+        Ok(notes) => notes,
+        Err(error) => {
+            println!("Failed to edit '{}': {}", input, error);
+            exit(Exit::Failure);
+        }
+    };
+    parse_collection(output, &root);
+    for note in notes.iter() {
+        outln!("{}", note);
+    }
+    write_output(&root, Some(&misc), output);
+}
+
+fn component_add(
+    input: &str,
+    component: &str,
+    output: &str,
+    data_stream: Option<String>,
+    name: Option<String>,
+    digests: bool,
+) {
+    let content = load_root(component);
+    let name = name.unwrap_or_else(|| {
+        Path::new(component).file_name().map_or_else(
+            || component.to_string(),
+            |n| n.to_string_lossy().into_owned(),
+        )
+    });
+    edit_components(input, output, digests, |root| {
+        compose::add_component(root, content, &name, data_stream.as_deref())
+    });
+}
+
+fn component_replace(input: &str, ref_id: &str, component: &str, output: &str, digests: bool) {
+    let content = load_root(component);
+    edit_components(input, output, digests, |root| {
+        compose::replace_component(root, ref_id, content)
+    });
+}
+
+fn repair(input: &str, output: &str, disable: Vec<RepairKind>, keep_comments: bool) {
+    let (mut root, misc) = load_root_with_misc(input);
+    let options = RepairOptions { disabled: disable };
+    let repairs = repair::repair(&mut root, &options);
+    for repair in repairs.iter() {
+        outln!("Repaired ({}): {}", repair.kind, repair.message);
+    }
+    outln!("{} repairs performed", repairs.len());
+    write_output(&root, Some(&misc).filter(|_| keep_comments), output);
+}
+
+/// Logs to the standard error at the level given by `-v` and `-q`, with the
+/// directives of `RUST_LOG` on top of it.
+fn init_logging(verbose: usize, quiet: bool) {
+    let level = match (quiet, verbose) {
+        (true, _) => LevelFilter::ERROR,
+        (false, 0) => LevelFilter::WARN,
+        (false, 1) => LevelFilter::INFO,
+        (false, 2) => LevelFilter::DEBUG,
+        (false, _) => LevelFilter::TRACE,
+    };
+    let filter = EnvFilter::builder()
+        .with_default_directive(level.into())
+        .from_env_lossy();
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::stderr().is_terminal())
+        .with_span_events(FmtSpan::CLOSE)
+        .with_target(false)
+        .init();
+}
+
+fn main() {
+    let args = Args::parse();
+    init_logging(args.verbose, args.quiet);
+    let _ = FETCH.set(FetchOptions {
+        timeout: Duration::from_secs(args.timeout),
+        max_size: args.max_size,
+        cache_dir: None,
+    });
+    if let Some(path) = args.output_file.as_deref() {
+        open_output(path);
+    }
+    match args.command {
+        Some(Command::Repair {
+            input,
+            output,
+            disable,
+            keep_comments,
+        }) => repair(&input, &output, disable, keep_comments),
+        Some(Command::Resolve {
+            input,
+            output,
+            profile,
+            keep_comments,
+        }) => resolve(&input, &output, profile.as_deref(), keep_comments),
+        Some(Command::ExportCkl {
+            input,
+            output,
+            test_result,
+        }) => export_ckl(&input, &output, test_result),
+        Some(Command::Signatures {
+            filepath,
+            trust_store,
+            allow_algorithm,
+        }) => signatures(&filepath, trust_store, allow_algorithm),
+        Some(Command::Profile {
+            command:
+                ProfileCommand::Show {
+                    filepath,
+                    profile_id,
+                    resolved,
+                    effective,
+                },
+        }) => match effective {
+            true => profile_show_effective(&filepath, &profile_id, args.lang.as_deref()),
+            false => profile_show(&filepath, &profile_id, resolved),
+        },
+        Some(Command::Generate {
+            command:
+                GenerateCommand::Fix {
+                    filepath,
+                    profile,
+                    system,
+                },
+        }) => generate_fix(&filepath, &profile, system),
+        Some(Command::Generate {
+            command: GenerateCommand::Tailoring { filepath, profile },
+        }) => generate_tailoring(&filepath, &profile),
+        Some(Command::Generate {
+            command:
+                GenerateCommand::Report {
+                    filepath,
+                    test_result,
+                },
+        }) => generate_report(&filepath, test_result, args.lang.as_deref()),
+        Some(Command::ListRules {
+            filepath,
+            profile,
+            severity,
+            platform,
+            selected_only,
+            format,
+            no_color,
+            locale,
+        }) => {
+            let selection = matrix::RuleSelection {
+                severities: severity,
+                platform,
+                selected_only,
+            };
+            list_rules(
+                &filepath,
+                profile,
+                selection,
+                format,
+                no_color,
+                locale,
+                args.lang.as_deref(),
+            )
+        }
+        Some(Command::Stats { filepath, format }) => stats(&filepath, format),
+        Some(Command::Search {
+            filepath,
+            query,
+            id,
+            ident,
+            format,
+        }) => {
+            let query = match (query, id, ident) {
+                (Some(text), _, _) => query::SearchQuery::Text(text),
+                (_, Some(id), _) => query::SearchQuery::Id(id),
+                (_, _, Some(ident)) => query::SearchQuery::Ident(ident),
+                _ => unreachable!("clap requires one of the queries"),
+            };
+            search(&filepath, query, format)
+        }
+        Some(Command::Applicability {
+            filepath,
+            target_cpe,
+            profile,
+            format,
+        }) => applicability(&filepath, &target_cpe, profile, format),
+        Some(Command::Export {
+            command:
+                ExportCommand::Matrix {
+                    filepath,
+                    profile,
+                    platform,
+                    severity,
+                    selected_only,
+                    format,
+                },
+        }) => {
+            let selection = matrix::RuleSelection {
+                severities: severity,
+                platform: None,
+                selected_only,
+            };
+            export_matrix(&filepath, profile, platform, selection, format)
+        }
+        Some(Command::Export {
+            command:
+                ExportCommand::Controls {
+                    filepath,
+                    standard,
+                    profile,
+                    format,
+                },
+        }) => export_controls(&filepath, standard, profile, format),
+        Some(Command::Results {
+            command:
+                ResultsCommand::Summary {
+                    filepath,
+                    test_result,
+                    format,
+                    no_color,
+                    locale,
+                },
+        }) => results_summary(
+            &filepath,
+            test_result,
+            format,
+            no_color,
+            locale,
+            args.lang.as_deref(),
+        ),
+        Some(Command::Results {
+            command:
+                ResultsCommand::Oval {
+                    filepath,
+                    xccdf,
+                    test_result,
+                    format,
+                },
+        }) => oval_results(&filepath, xccdf, test_result, format),
+        Some(Command::Results {
+            command:
+                ResultsCommand::Score {
+                    filepath,
+                    test_result,
+                    format,
+                },
+        }) => results_score(&filepath, test_result, format),
+        Some(Command::Upgrade {
+            command:
+                UpgradeCommand::Xccdf {
+                    input,
+                    output,
+                    reverse_dns,
+                },
+        }) => upgrade_xccdf(&input, &output, &reverse_dns),
+        Some(Command::Upgrade {
+            command: UpgradeCommand::Scap { input, output },
+        }) => upgrade_scap(&input, &output),
+        Some(Command::Component {
+            command:
+                ComponentCommand::Add {
+                    input,
+                    component,
+                    output,
+                    data_stream,
+                    name,
+                    digests,
+                },
+        }) => component_add(&input, &component, &output, data_stream, name, digests),
+        Some(Command::Component {
+            command:
+                ComponentCommand::Replace {
+                    input,
+                    ref_id,
+                    component,
+                    output,
+                    digests,
+                },
+        }) => component_replace(&input, &ref_id, &component, &output, digests),
+        Some(Command::Diff {
+            command:
+                DiffCommand::Results {
+                    old,
+                    new,
+                    old_test_result,
+                    new_test_result,
+                    format,
+                },
+        }) => diff_results(&old, &new, old_test_result, new_test_result, format),
+        Some(Command::Diff {
+            command: DiffCommand::Content { old, new, format },
+        }) => diff_content(&old, &new, format),
+        Some(Command::Selftest) => selftest(),
+        Some(Command::Doctor { filepath }) => doctor(&filepath),
+        Some(Command::Tree { filepath }) => tree(&filepath),
+        Some(Command::Validate {
+            filepath,
+            schema,
+            schema_dir,
+            format,
+            max_age,
+            ..
+        }) if Path::new(&filepath).is_dir() => {
+            validate_directory(&filepath, schema, &schema_dir, format, max_age)
         }
+        Some(Command::Validate {
+            filepath,
+            schema,
+            schema_dir,
+            requirements: false,
+            max_age,
+            ..
+        }) => validate(&filepath, schema, &schema_dir, max_age),
+        Some(Command::Validate {
+            filepath,
+            schema,
+            schema_dir,
+            requirements: true,
+            format,
+            max_age,
+        }) => validate_requirements(&filepath, schema, &schema_dir, format, max_age),
+        Some(Command::Tailoring {
+            command:
+                TailoringCommand::Apply {
+                    filepath,
+                    tailoring,
+                    profile,
+                    format,
+                },
+        }) => tailoring_apply(&filepath, &tailoring, profile, format),
+        Some(Command::Oval {
+            command: OvalCommand::Stats { filepath, format },
+        }) => oval_stats(&filepath, format),
+        Some(Command::Oval {
+            command:
+                OvalCommand::Dedup {
+                    input,
+                    output,
+                    keep_comments,
+                },
+        }) => oval_dedup(&input, &output, keep_comments),
+        Some(Command::Oval {
+            command:
+                OvalCommand::Cves {
+                    filepath,
+                    min_severity,
+                    sort,
+                    format,
+                },
+        }) => oval_cves(&filepath, min_severity, &sort, format),
+        Some(Command::Oval {
+            command:
+                OvalCommand::Variables {
+                    filepath,
+                    variables,
+                    format,
+                },
+        }) => oval_variables(&filepath, &variables, format),
+        Some(Command::Pack {
+            command: PackCommand::Create { directory },
+        }) => pack_create(&directory),
+        Some(Command::Pack {
+            command: PackCommand::Verify { directory },
+        }) => pack_verify(&directory),
+        None => match args.filepaths.as_slice() {
+            [] => match piped_stdin() {
+                Some(filepath) => info(&filepath, &args),
+                None => Args::into_app()
+                    .error(
+                        clap::ErrorKind::MissingRequiredArgument,
+                        "The path to the SCAP source data stream is required",
+                    )
+                    .exit(),
+            },
+            [filepath] if !Path::new(filepath).is_dir() => info(filepath, &args),
+            filepaths => inventory(filepaths, args.format),
+        },
     }
 }
@@ -0,0 +1,571 @@
+use minidom::Element;
+use serde::Serialize;
+use std::io::{self, Write};
+
+use crate::cpe::Cpe;
+use crate::sds::SCAP12_NS;
+use crate::xccdf::{
+    Benchmark, Group, Platform, PlatformSpecification, ResolvedProfile, Rule, Severity, Title,
+};
+
+pub const CPE_DICT_NS: &str = "http://cpe.mitre.org/dictionary/2.0";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Cell {
+    Selected,
+    Applicable,
+    NotApplicable,
+}
+
+impl Cell {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Cell::Selected => "selected",
+            Cell::Applicable => "applicable",
+            Cell::NotApplicable => "notapplicable",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MatrixPlatform {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MatrixRow {
+    pub id: String,
+    pub title: String,
+    pub cells: Vec<Cell>,
+}
+
+/// Applicability and selection of every rule of the benchmark on every
+/// platform, one cell per rule and platform.
+#[derive(Debug, Serialize)]
+pub struct Matrix {
+    pub platforms: Vec<MatrixPlatform>,
+    pub rules: Vec<MatrixRow>,
+}
+
+/// Whether the CPE name `name` matches the `target` name, attribute by
+/// attribute, e.g. `cpe:/o:redhat:enterprise_linux` matches
+/// `cpe:/o:redhat:enterprise_linux:8`. Names that can't be parsed are
+/// compared component by component.
+fn cpe_matches(name: &str, target: &str) -> bool {
+    match (name.parse::<Cpe>(), target.parse::<Cpe>()) {
+        (Ok(name), Ok(target)) => name.matches(&target),
+        _ => {
+            let mut target_parts = target.split(':');
+            name.split(':')
+                .all(|part| target_parts.next() == Some(part))
+        }
+    }
+}
+
+/// A CPE name that identifies one of the compared products applies only to
+/// that product. Other names, e.g. installed packages, can't be decided from
+/// the content alone and are assumed to apply.
+fn fact_applies(name: &str, products: &[String], target: &str) -> bool {
+    if products.iter().any(|p| cpe_matches(name, p)) {
+        cpe_matches(name, target)
+    } else {
+        true
+    }
+}
+
+/// How the CPE names of the platforms are decided.
+#[derive(Debug, Clone, Copy)]
+enum Facts<'a> {
+    /// The target is one of the compared products, see [`fact_applies`]
+    Product {
+        products: &'a [String],
+        target: &'a str,
+    },
+    /// A system described by the names, as a scanner sees it. Other names
+    /// don't apply.
+    System(&'a [Cpe]),
+}
+
+impl Facts<'_> {
+    fn applies(&self, name: &str) -> bool {
+        match self {
+            Facts::Product { products, target } => fact_applies(name, products, target),
+            Facts::System(targets) => name
+                .parse::<Cpe>()
+                .is_ok_and(|name| targets.iter().any(|t| name.matches(t))),
+        }
+    }
+}
+
+/// Platforms and selection of a group containing a rule.
+type Ancestor<'a> = (&'a [Platform], bool);
+
+struct Evaluator<'a> {
+    specification: Option<&'a PlatformSpecification>,
+    profile: Option<&'a ResolvedProfile<'a>>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn new(benchmark: &'a Benchmark, profile: Option<&'a ResolvedProfile<'a>>) -> Evaluator<'a> {
+        Evaluator {
+            specification: benchmark.platform_specification(),
+            profile,
+        }
+    }
+
+    /// An item without platforms applies everywhere, otherwise at least one
+    /// of its platforms has to apply.
+    /// Check-fact-refs can't be decided without running the checks and
+    /// are assumed to apply.
+    fn applies(&self, platforms: &[Platform], facts: Facts) -> bool {
+        platforms.is_empty()
+            || platforms
+                .iter()
+                .any(|platform| match platform.idref.strip_prefix('#') {
+                    Some(id) => self
+                        .specification
+                        .and_then(|s| s.platform(id))
+                        .is_none_or(|p| {
+                            p.test()
+                                .evaluate(&|name| facts.applies(name), &|_, _, _| true)
+                        }),
+                    None => facts.applies(&platform.idref),
+                })
+    }
+
+    fn is_selected(&self, id: &str, cluster_id: Option<&str>, default: bool) -> bool {
+        match self.profile {
+            Some(profile) => profile.is_selected(id, cluster_id, default),
+            None => default,
+        }
+    }
+
+    /// Whether the rule and all the groups containing it apply.
+    fn rule_applies(&self, rule: &Rule, ancestors: &[Ancestor], facts: Facts) -> bool {
+        self.applies(&rule.platforms, facts)
+            && ancestors.iter().all(|(p, _)| self.applies(p, facts))
+    }
+
+    /// Calls `visit` with every rule and whether it's selected, `ancestors`
+    /// are the platforms and the selection of the groups containing it.
+    fn walk<'b>(
+        &self,
+        rules: &'b [Rule],
+        groups: &'b [Group],
+        ancestors: &mut Vec<Ancestor<'b>>,
+        visit: &mut dyn FnMut(&'b Rule, bool, &[Ancestor<'b>]),
+    ) {
+        for rule in rules.iter() {
+            let selected = ancestors.iter().all(|(_, selected)| *selected)
+                && self.is_selected(&rule.id, rule.cluster_id.as_deref(), rule.selected);
+            visit(rule, selected, ancestors);
+        }
+        for group in groups.iter() {
+            let selected = self.is_selected(&group.id, group.cluster_id.as_deref(), group.selected);
+            ancestors.push((&group.platforms, selected));
+            self.walk(&group.rules, &group.groups, ancestors, visit);
+            ancestors.pop();
+        }
+    }
+}
+
+/// Which rules the listing and export commands include.
+#[derive(Debug, Clone, Default)]
+pub struct RuleSelection {
+    /// Only rules of these severities, all rules if empty. The severities
+    /// are those refined by the profile.
+    pub severities: Vec<Severity>,
+    /// Only rules applicable to this CPE platform
+    pub platform: Option<String>,
+    /// Only rules selected by the profile, or by default without a profile
+    pub selected_only: bool,
+}
+
+impl RuleSelection {
+    fn includes(&self, rule: &Rule, profile: Option<&ResolvedProfile>, selected: bool) -> bool {
+        let severity = profile.map_or(rule.severity, |p| p.severity(rule));
+        (!self.selected_only || selected)
+            && (self.severities.is_empty() || self.severities.contains(&severity))
+    }
+}
+
+/// Rules of the benchmark included by the selection, in the benchmark
+/// order. The platform is compared with the platforms of the benchmark.
+pub fn select_rules<'a>(
+    benchmark: &'a Benchmark,
+    profile: Option<&ResolvedProfile>,
+    selection: &RuleSelection,
+) -> Vec<&'a Rule> {
+    let mut products: Vec<String> = benchmark
+        .platforms
+        .iter()
+        .map(|p| p.idref.to_string())
+        .collect();
+    products.extend(selection.platform.iter().cloned());
+    let evaluator = Evaluator::new(benchmark, profile);
+    let mut rules = Vec::new();
+    evaluator.walk(
+        &benchmark.rules,
+        &benchmark.groups,
+        &mut Vec::new(),
+        &mut |rule, selected, ancestors| {
+            let applicable = selection.platform.as_ref().is_none_or(|target| {
+                let facts = Facts::Product {
+                    products: &products,
+                    target,
+                };
+                evaluator.rule_applies(rule, ancestors, facts)
+            });
+            if applicable && selection.includes(rule, profile, selected) {
+                rules.push(rule);
+            }
+        },
+    );
+    rules
+}
+
+fn first_title(titles: &[Title]) -> String {
+    titles.first().map(|t| t.title.clone()).unwrap_or_default()
+}
+
+/// Titles of the CPE dictionary items in the components of a data stream
+/// collection, by CPE name.
+pub fn dictionary(root: &Element) -> Vec<MatrixPlatform> {
+    let mut items = Vec::new();
+    for component in root.children().filter(|c| c.is("component", SCAP12_NS)) {
+        for list in component
+            .children()
+            .filter(|c| c.is("cpe-list", CPE_DICT_NS))
+        {
+            for item in list.children().filter(|c| c.is("cpe-item", CPE_DICT_NS)) {
+                if let Some(name) = item.attr("name") {
+                    items.push(MatrixPlatform {
+                        name: name.to_string(),
+                        title: item.get_child("title", CPE_DICT_NS).map(|t| t.text()),
+                    });
+                }
+            }
+        }
+    }
+    items
+}
+
+/// Builds the rule × platform matrix. The platforms are `targets` if given,
+/// otherwise the platforms of the benchmark, otherwise the items of the CPE
+/// dictionary. Without a profile the default selection of the benchmark is
+/// used. Only the rules included by `selection` get a row.
+pub fn platform_matrix(
+    benchmark: &Benchmark,
+    dictionary: &[MatrixPlatform],
+    targets: &[String],
+    profile: Option<&ResolvedProfile>,
+    selection: &RuleSelection,
+) -> Matrix {
+    let products: Vec<String> = if !targets.is_empty() {
+        targets.to_vec()
+    } else if !benchmark.platforms.is_empty() {
+        benchmark
+            .platforms
+            .iter()
+            .map(|p| p.idref.to_string())
+            .collect()
+    } else {
+        dictionary.iter().map(|d| d.name.clone()).collect()
+    };
+    let evaluator = Evaluator::new(benchmark, profile);
+    let facts = |target| Facts::Product {
+        products: &products,
+        target,
+    };
+    let mut rows = Vec::new();
+    evaluator.walk(
+        &benchmark.rules,
+        &benchmark.groups,
+        &mut Vec::new(),
+        &mut |rule, selected, ancestors| {
+            let excluded = selection
+                .platform
+                .as_ref()
+                .is_some_and(|target| !evaluator.rule_applies(rule, ancestors, facts(target)));
+            if excluded || !selection.includes(rule, profile, selected) {
+                return;
+            }
+            let cells = products
+                .iter()
+                .map(|target| {
+                    match (
+                        evaluator.rule_applies(rule, ancestors, facts(target)),
+                        selected,
+                    ) {
+                        (false, _) => Cell::NotApplicable,
+                        (true, true) => Cell::Selected,
+                        (true, false) => Cell::Applicable,
+                    }
+                })
+                .collect();
+            rows.push(MatrixRow {
+                id: rule.id.to_string(),
+                title: first_title(&rule.titles),
+                cells,
+            });
+        },
+    );
+    let platforms = products
+        .iter()
+        .map(|name| MatrixPlatform {
+            name: name.clone(),
+            title: dictionary
+                .iter()
+                .find(|d| &d.name == name)
+                .and_then(|d| d.title.clone()),
+        })
+        .collect();
+    Matrix {
+        platforms,
+        rules: rows,
+    }
+}
+
+/// Whether an item applies to the target system.
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemApplicability {
+    pub id: String,
+    pub title: String,
+    pub applicable: bool,
+}
+
+/// Applicability of the benchmark, its profiles and rules to a system.
+#[derive(Debug, Clone, Serialize)]
+pub struct Applicability {
+    pub targets: Vec<String>,
+    pub benchmark: ItemApplicability,
+    pub profiles: Vec<ItemApplicability>,
+    /// The selected rules, a rule doesn't apply if the benchmark doesn't
+    pub rules: Vec<ItemApplicability>,
+}
+
+/// Evaluates the platforms of the benchmark like a scanner would on a
+/// system described by the `targets` CPE names. Only the platforms matched
+/// by one of the names apply. Without a profile the default selection of
+/// the benchmark is used.
+pub fn applicability(
+    benchmark: &Benchmark,
+    targets: &[Cpe],
+    profile: Option<&ResolvedProfile>,
+) -> Applicability {
+    let evaluator = Evaluator::new(benchmark, profile);
+    let facts = Facts::System(targets);
+    let benchmark_applies = evaluator.applies(&benchmark.platforms, facts);
+    let profiles = benchmark
+        .profiles
+        .iter()
+        .map(|p| ItemApplicability {
+            id: p.id.to_string(),
+            title: first_title(&p.titles),
+            applicable: evaluator.applies(&p.platforms, facts),
+        })
+        .collect();
+    let mut rules = Vec::new();
+    evaluator.walk(
+        &benchmark.rules,
+        &benchmark.groups,
+        &mut Vec::new(),
+        &mut |rule, selected, ancestors| {
+            if selected {
+                rules.push(ItemApplicability {
+                    id: rule.id.to_string(),
+                    title: first_title(&rule.titles),
+                    applicable: benchmark_applies && evaluator.rule_applies(rule, ancestors, facts),
+                });
+            }
+        },
+    );
+    Applicability {
+        targets: targets.iter().map(|t| t.to_string()).collect(),
+        benchmark: ItemApplicability {
+            id: benchmark.id.to_string(),
+            title: first_title(&benchmark.titles),
+            applicable: benchmark_applies,
+        },
+        profiles,
+        rules,
+    }
+}
+
+impl Applicability {
+    pub fn write_text<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let status = |applicable| match applicable {
+            true => "applicable",
+            false => "notapplicable",
+        };
+        writeln!(out, "Targets: {}", self.targets.join(", "))?;
+        writeln!(
+            out,
+            "Benchmark {}: {}",
+            self.benchmark.id,
+            status(self.benchmark.applicable)
+        )?;
+        for profile in self.profiles.iter() {
+            writeln!(
+                out,
+                "Profile {}: {}",
+                profile.id,
+                status(profile.applicable)
+            )?;
+        }
+        writeln!(out)?;
+        for rule in self.rules.iter() {
+            writeln!(
+                out,
+                "{:<13} {} {}",
+                status(rule.applicable),
+                rule.id,
+                rule.title
+            )?;
+        }
+        let applicable = self.rules.iter().filter(|r| r.applicable).count();
+        writeln!(out)?;
+        writeln!(
+            out,
+            "{} rules applicable, {} not applicable",
+            applicable,
+            self.rules.len() - applicable
+        )?;
+        Ok(())
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, a quote or a line break.
+pub(crate) fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+impl Matrix {
+    /// Renders the matrix as CSV with a header row of the platform names.
+    pub fn to_csv(&self) -> String {
+        let mut header = vec![String::from("rule"), String::from("title")];
+        header.extend(self.platforms.iter().map(|p| csv_field(&p.name)));
+        let mut csv = header.join(",") + "\n";
+        for row in self.rules.iter() {
+            let mut fields = vec![csv_field(&row.id), csv_field(&row.title)];
+            fields.extend(row.cells.iter().map(|c| c.as_str().to_string()));
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xccdf::{CPE_LANG_NS, XCCDF12_NS};
+
+    fn benchmark() -> Benchmark {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" xmlns:cpe-lang=\"{}\" id=\"b\"><status>draft</status>\
+             <cpe-lang:platform-specification><cpe-lang:platform id=\"not_rhel8\">\
+             <cpe-lang:logical-test operator=\"AND\" negate=\"true\">\
+             <cpe-lang:fact-ref name=\"cpe:/o:redhat:enterprise_linux:8\"/></cpe-lang:logical-test>\
+             </cpe-lang:platform><cpe-lang:platform id=\"machine\">\
+             <cpe-lang:logical-test operator=\"OR\" negate=\"false\">\
+             <cpe-lang:fact-ref name=\"cpe:/a:machine\"/></cpe-lang:logical-test>\
+             </cpe-lang:platform></cpe-lang:platform-specification>\
+             <platform idref=\"cpe:/o:redhat:enterprise_linux:8\"/>\
+             <platform idref=\"cpe:/o:redhat:enterprise_linux:9\"/><version>1</version>\
+             <Rule id=\"r1\" selected=\"true\"><title>Kernel, \"hardened\"</title>\
+             <platform idref=\"#machine\"/></Rule>\
+             <Group id=\"g\"><platform idref=\"#not_rhel8\"/>\
+             <Rule id=\"r2\" selected=\"false\"><title>R2</title></Rule>\
+             <Rule id=\"r3\" selected=\"true\" severity=\"high\"><title>R3</title>\
+             <platform idref=\"cpe:/o:redhat:enterprise_linux:8\"/></Rule></Group></Benchmark>",
+            XCCDF12_NS, CPE_LANG_NS
+        )
+        .parse()
+        .unwrap();
+        Benchmark::from_xml(&el).unwrap()
+    }
+
+    #[test]
+    fn test_platform_matrix() {
+        let benchmark = benchmark();
+        let dictionary = vec![MatrixPlatform {
+            name: String::from("cpe:/o:redhat:enterprise_linux:9"),
+            title: Some(String::from("Red Hat Enterprise Linux 9")),
+        }];
+        let matrix = platform_matrix(
+            &benchmark,
+            &dictionary,
+            &[],
+            None,
+            &RuleSelection::default(),
+        );
+        assert_eq!(
+            matrix.platforms[1].title.as_deref(),
+            Some("Red Hat Enterprise Linux 9")
+        );
+        assert_eq!(
+            matrix.to_csv(),
+            "rule,title,cpe:/o:redhat:enterprise_linux:8,cpe:/o:redhat:enterprise_linux:9\n\
+             r1,\"Kernel, \"\"hardened\"\"\",selected,selected\n\
+             r2,R2,notapplicable,applicable\n\
+             r3,R3,notapplicable,notapplicable\n"
+        );
+    }
+
+    #[test]
+    fn test_select_rules() {
+        let benchmark = benchmark();
+        let ids = |selection: &RuleSelection| -> Vec<String> {
+            select_rules(&benchmark, None, selection)
+                .iter()
+                .map(|r| r.id.to_string())
+                .collect()
+        };
+        let mut selection = RuleSelection {
+            platform: Some(String::from("cpe:/o:redhat:enterprise_linux:9")),
+            ..RuleSelection::default()
+        };
+        assert_eq!(ids(&selection), vec!["r1", "r2"]);
+        selection.selected_only = true;
+        assert_eq!(ids(&selection), vec!["r1"]);
+        let selection = RuleSelection {
+            severities: vec![Severity::High],
+            ..RuleSelection::default()
+        };
+        assert_eq!(ids(&selection), vec!["r3"]);
+    }
+
+    #[test]
+    fn test_applicability() {
+        let benchmark = benchmark();
+        let rules = |targets: &[&str]| -> (bool, Vec<(String, bool)>) {
+            let targets: Vec<Cpe> = targets.iter().map(|t| t.parse().unwrap()).collect();
+            let result = applicability(&benchmark, &targets, None);
+            let rules = result
+                .rules
+                .into_iter()
+                .map(|r| (r.id, r.applicable))
+                .collect();
+            (result.benchmark.applicable, rules)
+        };
+        let (benchmark_applies, result) =
+            rules(&["cpe:/o:redhat:enterprise_linux:9::ga", "cpe:/a:machine"]);
+        assert!(benchmark_applies);
+        assert_eq!(
+            result,
+            vec![(String::from("r1"), true), (String::from("r3"), false)]
+        );
+        let (_, result) = rules(&["cpe:/o:redhat:enterprise_linux:9"]);
+        assert!(!result[0].1);
+        let (benchmark_applies, result) = rules(&["cpe:/o:fedoraproject:fedora:40"]);
+        assert!(!benchmark_applies && result.iter().all(|(_, applicable)| !applicable));
+    }
+}
@@ -0,0 +1,421 @@
+use minidom::quick_xml::events::Event;
+use minidom::quick_xml::Reader;
+use minidom::Element;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Read};
+use std::ops::Range;
+
+use crate::error::Error;
+use crate::options::{ParseOptions, Progress, ProgressCallback};
+
+/// Elements whose preceding comments and processing instructions are kept.
+const ANCHORS: [&str; 3] = ["component", "extended-component", "Benchmark"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MiscNode {
+    Comment(String),
+    ProcessingInstruction(String),
+}
+
+/// Comments and processing instructions removed from a document before
+/// parsing, because the element tree can't hold them.
+#[derive(Debug, Default)]
+pub struct MiscNodes {
+    /// Nodes before the root element
+    pub prolog: Vec<MiscNode>,
+    /// Nodes directly preceding a component, an extended component or a
+    /// benchmark, by the ID of the element
+    pub before: HashMap<String, Vec<MiscNode>>,
+    /// Number of nodes at other places, which are not preserved
+    pub dropped: usize,
+}
+
+fn element_id(event: &minidom::quick_xml::events::BytesStart) -> Option<String> {
+    event
+        .attributes()
+        .filter_map(|a| a.ok())
+        .find(|a| a.key == b"id")
+        .map(|a| String::from_utf8_lossy(&a.value).to_string())
+}
+
+/// Position of the `<` opening the markup read from `start`. Depending on
+/// the preceding event, the reader may have consumed it already.
+fn markup_start(data: &[u8], start: usize) -> usize {
+    if data.get(start) != Some(&b'<') && start > 0 && data[start - 1] == b'<' {
+        start - 1
+    } else {
+        start
+    }
+}
+
+/// Removes the comments and processing instructions from the document and
+/// returns them together with the remaining document.
+pub fn strip(data: &[u8]) -> Result<(Vec<u8>, MiscNodes), String> {
+    let (kept, misc) = strip_interruptible(data, None).map_err(|e| match e {
+        Error::Xml(message) => message,
+        error => error.to_string(),
+    })?;
+    let mut stripped = Vec::with_capacity(data.len());
+    Spliced::new(data, kept).read_to_end(&mut stripped).unwrap();
+    Ok((stripped, misc))
+}
+
+/// Like [`strip`], failing once the parse with `opts` is interrupted. The
+/// remaining document is given by the byte ranges of `data` it consists of,
+/// so that it isn't copied.
+fn strip_interruptible(
+    data: &[u8],
+    opts: Option<&ParseOptions>,
+) -> Result<(Vec<Range<usize>>, MiscNodes), Error> {
+    let mut checked = 0;
+    let mut reader = Reader::from_reader(data);
+    let mut buf = Vec::new();
+    let mut kept = Vec::new();
+    let mut misc = MiscNodes::default();
+    let mut pending = Vec::new();
+    let mut copied = 0;
+    let mut seen_root = false;
+    loop {
+        let start = reader.buffer_position();
+        if let Some(opts) = opts.filter(|_| start - checked >= CHECK_STEP) {
+            checked = start;
+            opts.check_interrupted()?;
+        }
+        let event = reader
+            .read_event(&mut buf)
+            .map_err(|e| Error::Xml(format!("{} at position {}", e, reader.buffer_position())))?;
+        let end = reader.buffer_position();
+        match event {
+            Event::Comment(ref text) | Event::PI(ref text) => {
+                let start = markup_start(data, start);
+                if start > copied {
+                    kept.push(copied..start);
+                }
+                copied = end;
+                let text = String::from_utf8_lossy(text.escaped()).to_string();
+                pending.push(match event {
+                    Event::Comment(_) => MiscNode::Comment(text),
+                    _ => MiscNode::ProcessingInstruction(text),
+                });
+            }
+            Event::Start(ref el) | Event::Empty(ref el) => {
+                if !seen_root {
+                    seen_root = true;
+                    misc.prolog.append(&mut pending);
+                } else if !pending.is_empty() {
+                    let name = String::from_utf8_lossy(el.local_name()).to_string();
+                    match element_id(el) {
+                        Some(id) if ANCHORS.contains(&name.as_str()) => {
+                            misc.before.insert(id, std::mem::take(&mut pending));
+                        }
+                        _ => {
+                            misc.dropped += pending.len();
+                            pending.clear();
+                        }
+                    }
+                }
+            }
+            Event::Text(ref text) if text.escaped().iter().all(|b| b.is_ascii_whitespace()) => (),
+            Event::Decl(_) | Event::DocType(_) => (),
+            Event::Eof => break,
+            _ => {
+                misc.dropped += pending.len();
+                pending.clear();
+            }
+        }
+        buf.clear();
+    }
+    misc.dropped += pending.len();
+    kept.push(copied..data.len());
+    Ok((kept, misc))
+}
+
+/// Reader of the given byte ranges of the data one after another.
+struct Spliced<'a> {
+    data: &'a [u8],
+    ranges: Vec<Range<usize>>,
+    /// Index of the range being read
+    current: usize,
+    position: usize,
+}
+
+impl<'a> Spliced<'a> {
+    fn new(data: &'a [u8], ranges: Vec<Range<usize>>) -> Spliced<'a> {
+        let position = ranges.first().map_or(0, |r| r.start);
+        Spliced {
+            data,
+            ranges,
+            current: 0,
+            position,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.ranges.iter().map(|r| r.len()).sum()
+    }
+}
+
+impl Read for Spliced<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let amt = self.fill_buf()?.read(buf)?;
+        self.consume(amt);
+        Ok(amt)
+    }
+}
+
+impl BufRead for Spliced<'_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while let Some(range) = self.ranges.get(self.current) {
+            if self.position < range.end {
+                return Ok(&self.data[self.position..range.end]);
+            }
+            self.current += 1;
+            if let Some(next) = self.ranges.get(self.current) {
+                self.position = next.start;
+            }
+        }
+        Ok(&[])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.position += amt;
+    }
+}
+
+/// Bytes read between two progress reports.
+const PROGRESS_STEP: usize = 1 << 20;
+/// Bytes read between two checks of the cancellation and the deadline.
+const CHECK_STEP: usize = 1 << 16;
+
+/// Reader reporting the bytes consumed by the XML parser and failing once
+/// the parse is cancelled or its deadline passes.
+struct ProgressReader<'a, R> {
+    inner: R,
+    read: usize,
+    reported: usize,
+    checked: usize,
+    total: usize,
+    callback: Option<&'a ProgressCallback>,
+    opts: Option<&'a ParseOptions>,
+}
+
+impl<R: BufRead> ProgressReader<'_, R> {
+    fn advance(&mut self, amt: usize) {
+        self.read += amt;
+        if let Some(callback) = self.callback {
+            if self.read - self.reported >= PROGRESS_STEP {
+                self.reported = self.read;
+                callback.report(Progress::BytesRead {
+                    read: self.read,
+                    total: self.total,
+                });
+            }
+        }
+    }
+
+    fn check_interrupted(&mut self) -> io::Result<()> {
+        if let Some(opts) = self.opts {
+            if self.read == 0 || self.read - self.checked >= CHECK_STEP {
+                self.checked = self.read;
+                opts.check_interrupted().map_err(io::Error::other)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.check_interrupted()?;
+        let amt = self.inner.read(buf)?;
+        self.advance(amt);
+        Ok(amt)
+    }
+}
+
+impl<R: BufRead> BufRead for ProgressReader<'_, R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.check_interrupted()?;
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        self.advance(amt);
+    }
+}
+
+/// Parses a document that may contain comments and processing instructions.
+pub fn parse(data: &[u8]) -> Result<(Element, MiscNodes), String> {
+    parse_with_progress(data, None)
+}
+
+/// Like [`parse`], reporting the bytes read by the XML parser.
+pub fn parse_with_progress(
+    data: &[u8],
+    progress: Option<&ProgressCallback>,
+) -> Result<(Element, MiscNodes), String> {
+    parse_document(data, progress, None).map_err(|e| match e {
+        Error::Xml(message) => message,
+        error => error.to_string(),
+    })
+}
+
+/// Like [`parse`], reporting the progress to the callback of `opts` and
+/// failing with [`Error::Cancelled`] or [`Error::DeadlineExceeded`] while
+/// the document is read.
+pub fn parse_with_options(data: &[u8], opts: &ParseOptions) -> Result<(Element, MiscNodes), Error> {
+    parse_document(data, opts.progress.as_ref(), Some(opts))
+}
+
+fn parse_document(
+    data: &[u8],
+    progress: Option<&ProgressCallback>,
+    opts: Option<&ParseOptions>,
+) -> Result<(Element, MiscNodes), Error> {
+    let _span = tracing::debug_span!("read", bytes = data.len()).entered();
+    // the document is read in place, a memory-mapped file isn't copied
+    let (kept, misc) = strip_interruptible(data, opts)?;
+    let stripped = Spliced::new(data, kept);
+    let total = stripped.len();
+    let root = Element::from_reader(&mut Reader::from_reader(ProgressReader {
+        inner: stripped,
+        read: 0,
+        reported: 0,
+        checked: 0,
+        total,
+        callback: progress,
+        opts,
+    }));
+    match root {
+        Ok(root) => {
+            // The parser stops at the end of the root element.
+            if let Some(callback) = progress {
+                callback.report(Progress::BytesRead { read: total, total });
+            }
+            Ok((root, misc))
+        }
+        // the interruption surfaces as an I/O error of the XML parser
+        Err(error) => match opts.map(|o| o.check_interrupted()) {
+            Some(Err(interruption)) => Err(interruption),
+            _ => Err(Error::Xml(error.to_string())),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writer;
+
+    #[test]
+    fn test_strip_and_write_back() {
+        let data = "<?xml version=\"1.0\"?>\n<!-- generated by build 42 -->\n\
+             <?xml-stylesheet href=\"style.xsl\"?>\n\
+             <data-stream-collection xmlns=\"http://scap.nist.gov/schema/scap/source/1.2\" id=\"c\">\n\
+             <!-- dropped --><data-stream id=\"d\"/>\n\
+             <!-- benchmark component -->\n<component id=\"x\"><a xmlns=\"urn:a\">t<!-- inner -->ext</a></component>\n\
+             </data-stream-collection>";
+        let (root, misc) = parse(data.as_bytes()).unwrap();
+        assert_eq!(
+            misc.prolog,
+            vec![
+                MiscNode::Comment(String::from(" generated by build 42 ")),
+                MiscNode::ProcessingInstruction(String::from("xml-stylesheet href=\"style.xsl\"")),
+            ]
+        );
+        assert_eq!(
+            misc.before["x"],
+            vec![MiscNode::Comment(String::from(" benchmark component "))]
+        );
+        assert_eq!(misc.dropped, 2);
+        assert_eq!(
+            root.children()
+                .nth(1)
+                .unwrap()
+                .children()
+                .next()
+                .unwrap()
+                .text(),
+            "text"
+        );
+
+        let mut output = Vec::new();
+        writer::write_document_with_misc(&root, &misc, &mut output).unwrap();
+        let text = String::from_utf8(output).unwrap();
+        assert!(text.contains(
+            "?>\n<!-- generated by build 42 -->\n<?xml-stylesheet href=\"style.xsl\"?>\n<ds:"
+        ));
+        assert!(text.contains("<!-- benchmark component --><ds:component id=\"x\">"));
+
+        let (stripped, _) = strip(data.as_bytes()).unwrap();
+        let stripped = String::from_utf8(stripped).unwrap();
+        assert!(!stripped.contains("<!--") && !stripped.contains("<?xml-stylesheet"));
+        assert!(stripped.contains("<a xmlns=\"urn:a\">text</a>"));
+    }
+
+    #[test]
+    fn test_interrupted_reading() {
+        use crate::options::CancellationToken;
+
+        let data = std::fs::read("data/simple.xml").unwrap();
+        let token = CancellationToken::new();
+        let opts = ParseOptions {
+            cancellation: Some(token.clone()),
+            ..ParseOptions::default()
+        };
+        assert!(parse_with_options(&data, &opts).is_ok());
+        token.cancel();
+        assert_eq!(
+            parse_with_options(&data, &opts).unwrap_err(),
+            Error::Cancelled
+        );
+        // the reader checks the deadline before the first byte
+        let mut reader = ProgressReader {
+            inner: &data[..],
+            read: 0,
+            reported: 0,
+            checked: 0,
+            total: data.len(),
+            callback: None,
+            opts: Some(&ParseOptions {
+                deadline: Some(std::time::Instant::now()),
+                ..ParseOptions::default()
+            }),
+        };
+        assert!(reader.fill_buf().is_err());
+    }
+
+    #[test]
+    fn test_progress() {
+        use crate::options::ParseOptions;
+        use std::sync::{Arc, Mutex};
+
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        let opts = ParseOptions {
+            progress: Some(ProgressCallback::new(move |p| sink.lock().unwrap().push(p))),
+            ..ParseOptions::default()
+        };
+        let data = std::fs::read("data/simple.xml").unwrap();
+        crate::parse_collection(&data, &opts).unwrap();
+        let reports = reports.lock().unwrap();
+        assert!(matches!(
+            reports.first(),
+            Some(Progress::BytesRead { read, total }) if read == total
+        ));
+        assert_eq!(
+            reports[1..],
+            [
+                Progress::ComponentsParsed {
+                    parsed: 1,
+                    total: 2
+                },
+                Progress::ComponentsParsed {
+                    parsed: 2,
+                    total: 2
+                }
+            ]
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+
+use crate::error::Error;
+
+/// Policy applied to the text content of simple (non-XHTML) elements such as
+/// titles, statuses, versions or idents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Whitespace {
+    /// Trim the text and collapse inner whitespace runs into a single space.
+    Normalize,
+    /// Keep the text exactly as it appears in the document.
+    Preserve,
+}
+
+/// Flag shared between a parse and another thread that can abort it.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Progress of a parse reported to [`ParseOptions::progress`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress {
+    /// Bytes of the document read by the XML parser
+    BytesRead { read: usize, total: usize },
+    /// Components of the collection parsed into the model
+    ComponentsParsed { parsed: usize, total: usize },
+}
+
+/// Function receiving the progress of a parse, e.g. to draw a progress bar.
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(Progress) + Send + Sync>);
+
+impl ProgressCallback {
+    pub fn new<F: Fn(Progress) + Send + Sync + 'static>(f: F) -> ProgressCallback {
+        ProgressCallback(Arc::new(f))
+    }
+
+    pub fn report(&self, progress: Progress) {
+        (self.0)(progress)
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+/// Options of a parse. The options are shared by reference with the parsers
+/// of all the elements, which record the warnings in them, so they can also
+/// be used by a parse on another thread.
+#[derive(Debug)]
+pub struct ParseOptions {
+    pub whitespace: Whitespace,
+    /// Abort on any problem in the content. Otherwise recoverable problems,
+    /// e.g. a missing status or an unknown attribute value, are collected as
+    /// warnings and the parser continues with a default or drops the
+    /// offending element.
+    pub strict: bool,
+    /// Continue after problems like in the lenient mode, but fail at the end
+    /// with all of them as [`Error::Multiple`]. The parse stops once this
+    /// many problems are found. Takes precedence over `strict`.
+    pub max_errors: Option<usize>,
+    /// Skip unknown child elements and record them as warnings, also in the
+    /// strict mode. Ignored when collecting errors.
+    pub permissive: bool,
+    /// The parse fails with [`Error::DeadlineExceeded`] once this time passes
+    pub deadline: Option<Instant>,
+    /// The parse fails with [`Error::Cancelled`] once the token is cancelled
+    pub cancellation: Option<CancellationToken>,
+    /// Called as the document is read and its components are parsed
+    pub progress: Option<ProgressCallback>,
+    pub(crate) warnings: Mutex<Vec<Error>>,
+    /// Location steps of the element being parsed, used to locate warnings
+    pub(crate) path: Mutex<Vec<String>>,
+}
+
+impl Clone for ParseOptions {
+    fn clone(&self) -> Self {
+        ParseOptions {
+            whitespace: self.whitespace,
+            strict: self.strict,
+            max_errors: self.max_errors,
+            permissive: self.permissive,
+            deadline: self.deadline,
+            cancellation: self.cancellation.clone(),
+            progress: self.progress.clone(),
+            warnings: Mutex::new(self.warnings().clone()),
+            path: Mutex::new(self.path().clone()),
+        }
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            whitespace: Whitespace::Normalize,
+            strict: true,
+            max_errors: None,
+            permissive: false,
+            deadline: None,
+            cancellation: None,
+            progress: None,
+            warnings: Mutex::new(Vec::new()),
+            path: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl ParseOptions {
+    pub fn lenient() -> ParseOptions {
+        ParseOptions {
+            strict: false,
+            ..ParseOptions::default()
+        }
+    }
+
+    pub fn collecting(max_errors: usize) -> ParseOptions {
+        ParseOptions {
+            max_errors: Some(max_errors),
+            ..ParseOptions::default()
+        }
+    }
+
+    pub(crate) fn report(&self, progress: Progress) {
+        if let Some(callback) = &self.progress {
+            callback.report(progress);
+        }
+    }
+
+    /// Takes the warnings collected by the lenient parsing so far.
+    pub fn take_warnings(&self) -> Vec<Error> {
+        std::mem::take(&mut self.warnings())
+    }
+
+    pub(crate) fn warnings(&self) -> MutexGuard<'_, Vec<Error>> {
+        self.warnings.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn path(&self) -> MutexGuard<'_, Vec<String>> {
+        self.path.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Fails with the error in the strict mode, otherwise records it as
+    /// a warning or a collected error located at the element being parsed.
+    pub(crate) fn tolerate(&self, error: Error) -> Result<(), Error> {
+        let collecting = self.max_errors.is_some();
+        if (self.strict && !collecting)
+            || error.is_interruption()
+            || matches!(error, Error::Multiple(_))
+        {
+            return Err(error);
+        }
+        self.warn(error);
+        match self.max_errors {
+            Some(max_errors) if self.warnings().len() >= max_errors => {
+                Err(Error::Multiple(self.take_warnings()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Records the error as a warning located at the element being parsed.
+    pub(crate) fn warn(&self, error: Error) {
+        let error = self
+            .path()
+            .iter()
+            .rev()
+            .fold(error, |error, step| error.within(step));
+        tracing::debug!(%error, "recorded a problem in the content");
+        self.warnings().push(error);
+    }
+
+    /// Fails with the error about an unknown child element, unless the
+    /// parse is permissive, then it's only recorded as a warning.
+    pub(crate) fn unknown_child(&self, error: Error) -> Result<(), Error> {
+        if !self.permissive || self.max_errors.is_some() {
+            return Err(error);
+        }
+        self.warn(error);
+        Ok(())
+    }
+
+    /// Completes the parse of a document in the error collecting mode: the
+    /// result of the outermost parser is replaced by the collected errors,
+    /// if there are any.
+    pub(crate) fn finish<T>(&self, result: Result<T, Error>) -> Result<T, Error> {
+        if self.max_errors.is_none() || !self.path().is_empty() {
+            return result;
+        }
+        let mut errors = self.take_warnings();
+        match result {
+            Err(Error::Multiple(mut rest)) => errors.append(&mut rest),
+            Err(error) => errors.push(error),
+            Ok(value) if errors.is_empty() => return Ok(value),
+            Ok(_) => (),
+        }
+        Err(Error::Multiple(errors))
+    }
+
+    /// Like [`ParseOptions::tolerate`], but continues with `fallback`.
+    pub(crate) fn recover<T>(&self, result: Result<T, Error>, fallback: T) -> Result<T, Error> {
+        match result {
+            Ok(value) => Ok(value),
+            Err(error) => self.tolerate(error).map(|_| fallback),
+        }
+    }
+
+    /// Fails if the parse has been cancelled or its deadline has passed.
+    pub(crate) fn check_interrupted(&self) -> Result<(), Error> {
+        if self.cancellation.as_ref().is_some_and(|c| c.is_cancelled()) {
+            return Err(Error::Cancelled);
+        }
+        if self.deadline.is_some_and(|d| Instant::now() >= d) {
+            return Err(Error::DeadlineExceeded);
+        }
+        Ok(())
+    }
+
+    /// Runs `f` as the parser of the element at location `step` and adds the
+    /// step to the location of its errors and warnings. This is the element
+    /// boundary where cancellation and the deadline are checked.
+    pub(crate) fn within<T>(
+        &self,
+        step: &str,
+        f: impl FnOnce() -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        self.check_interrupted()?;
+        self.path().push(step.to_string());
+        let result = f();
+        self.path().pop();
+        result.map_err(|e| e.within(step))
+    }
+}
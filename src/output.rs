@@ -0,0 +1,212 @@
+use serde::Serialize;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::html;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Yaml,
+    Csv,
+    Html,
+}
+
+/// Formats of the reports the commands print, the ones with tables also
+/// support `csv`.
+pub const REPORT_FORMATS: [&str; 4] = ["text", "json", "yaml", "html"];
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "yaml" => Ok(Format::Yaml),
+            "csv" => Ok(Format::Csv),
+            "html" => Ok(Format::Html),
+            _ => Err(format!(
+                "Unknown output format '{}', expected one of [\"text\", \"json\", \"yaml\", \"csv\", \"html\"]",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Yellow,
+    Green,
+    Blue,
+    Gray,
+}
+
+impl Color {
+    fn ansi_code(&self) -> &'static str {
+        match self {
+            Color::Red => "31",
+            Color::Yellow => "33",
+            Color::Green => "32",
+            Color::Blue => "34",
+            Color::Gray => "90",
+        }
+    }
+}
+
+/// Wraps the text in ANSI color escape sequences if colors are enabled.
+pub fn paint(text: &str, color: Option<Color>, enabled: bool) -> String {
+    match color {
+        Some(color) if enabled => format!("\x1b[{}m{}\x1b[0m", color.ansi_code(), text),
+        _ => text.to_string(),
+    }
+}
+
+/// Human-readable size, e.g. `1.5 KiB`.
+pub(crate) fn format_size(bytes: usize) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Number of characters shown, without the ANSI color escape sequences.
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut escape = false;
+    for c in text.chars() {
+        match c {
+            '\x1b' => escape = true,
+            'm' if escape => escape = false,
+            _ if escape => {}
+            _ => width += 1,
+        }
+    }
+    width
+}
+
+/// Text table with the columns aligned to the widest cell, the cells may be
+/// painted.
+pub struct Table {
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(header: &[&str]) -> Table {
+        Table {
+            header: header.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn row(&mut self, cells: Vec<String>) {
+        self.rows.push(cells);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Writes the header and the rows indented by `indent` spaces, the last
+    /// column isn't padded.
+    pub fn write<W: Write>(&self, out: &mut W, indent: usize) -> io::Result<()> {
+        let mut widths: Vec<usize> = self.header.iter().map(|h| visible_width(h)).collect();
+        for row in self.rows.iter() {
+            for (width, cell) in widths.iter_mut().zip(row.iter()) {
+                *width = (*width).max(visible_width(cell));
+            }
+        }
+        for row in std::iter::once(&self.header).chain(self.rows.iter()) {
+            let mut line = " ".repeat(indent);
+            for (i, cell) in row.iter().enumerate() {
+                line.push_str(cell);
+                if i + 1 < row.len() {
+                    let padding = widths[i] - visible_width(cell) + 2;
+                    line.push_str(&" ".repeat(padding));
+                }
+            }
+            writeln!(out, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Serializes a report into one of the machine-readable formats.
+pub fn serialize<T: Serialize>(value: &T, format: Format) -> Result<String, String> {
+    match format {
+        Format::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+        Format::Yaml => serde_yaml::to_string(value).map_err(|e| e.to_string()),
+        Format::Html => serde_json::to_value(value)
+            .map(|value| html::value_report("oscapxml report", &value))
+            .map_err(|e| e.to_string()),
+        Format::Text | Format::Csv => Err(String::from(
+            "Text and CSV output is rendered by the print functions, not serialized",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Item {
+        id: String,
+        count: u32,
+    }
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!("yaml".parse::<Format>(), Ok(Format::Yaml));
+        assert!("xml".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn test_serialize_yaml() {
+        let item = Item {
+            id: String::from("xccdf_org.example_profile_a"),
+            count: 3,
+        };
+        assert_eq!(
+            serialize(&item, Format::Yaml),
+            Ok(String::from("id: xccdf_org.example_profile_a\ncount: 3\n"))
+        );
+    }
+    #[test]
+    fn test_table() {
+        let mut table = Table::new(&["ID", "RULES", "TITLE"]);
+        table.row(vec![
+            String::from("p1"),
+            String::from("12"),
+            String::from("One"),
+        ]);
+        table.row(vec![
+            String::from("profile_2"),
+            paint("3", Some(Color::Red), true),
+            String::from("Two"),
+        ]);
+        let mut out = Vec::new();
+        table.write(&mut out, 2).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "  ID         RULES  TITLE\n  p1         12     One\n  profile_2  \x1b[31m3\x1b[0m      Two\n"
+        );
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 << 20), "3.0 MiB");
+    }
+}
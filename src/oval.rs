@@ -0,0 +1,1216 @@
+use minidom::Element;
+use minidom::Node;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+use std::sync::Arc;
+
+use crate::cvss::{Cvss, CvssSeverity};
+use crate::error::Error;
+use crate::options::ParseOptions;
+use crate::sds::SCAP12_NS;
+use crate::utils::*;
+use crate::xccdf::{Benchmark, CheckSystem, TestResult};
+
+pub const OVAL_DEF_NS: &str = "http://oval.mitre.org/XMLSchema/oval-definitions-5";
+pub const OVAL_VAR_NS: &str = "http://oval.mitre.org/XMLSchema/oval-variables-5";
+pub const OVAL_RES_NS: &str = "http://oval.mitre.org/XMLSchema/oval-results-5";
+pub const OVAL_SC_NS: &str = "http://oval.mitre.org/XMLSchema/oval-system-characteristics-5";
+
+/// Sections of an OVAL definitions document whose entries can be merged.
+/// Definitions and variables are referenced from outside of the document by
+/// the XCCDF checks and check-exports, so their duplicates are only reported.
+const MERGED_SECTIONS: [&str; 3] = ["states", "objects", "tests"];
+const SECTIONS: [&str; 5] = ["definitions", "tests", "objects", "states", "variables"];
+
+/// Attributes that don't affect the meaning of an OVAL entry.
+const IGNORED_ATTRS: [&str; 3] = ["id", "version", "comment"];
+
+/// OVAL definitions document. Only the definitions are modeled, they are
+/// what the XCCDF checks refer to.
+#[derive(Debug)]
+pub struct OvalDefinitions {
+    pub(crate) definitions: Vec<Definition>,
+    /// Positions of the definitions by ID
+    pub(crate) index: HashMap<Arc<str>, usize>,
+}
+
+impl OvalDefinitions {
+    pub fn from_xml(el: &Element) -> Result<OvalDefinitions, Error> {
+        OvalDefinitions::from_xml_with_options(el, &ParseOptions::default())
+    }
+
+    /// Like [`OvalDefinitions::from_xml`], recording the problems which don't
+    /// fail the parse, e.g. malformed CVSS vectors, as warnings.
+    pub fn from_xml_with_options(
+        el: &Element,
+        opts: &ParseOptions,
+    ) -> Result<OvalDefinitions, Error> {
+        if !el.is("oval_definitions", OVAL_DEF_NS) {
+            return Err(Error::unexpected_element(
+                el.name(),
+                Some("oval_definitions"),
+            ));
+        }
+        let mut definitions = Vec::new();
+        if let Some(section) = el.get_child("definitions", OVAL_DEF_NS) {
+            for child in section.children() {
+                if child.is("definition", OVAL_DEF_NS) {
+                    definitions.push(Definition::from_xml(child, opts)?);
+                }
+            }
+        }
+        let index = definitions
+            .iter()
+            .enumerate()
+            .map(|(i, d)| (d.id.clone(), i))
+            .collect();
+        Ok(OvalDefinitions { definitions, index })
+    }
+
+    pub fn definitions(&self) -> &[Definition] {
+        &self.definitions
+    }
+
+    pub fn definition(&self, id: &str) -> Option<&Definition> {
+        self.index.get(id).map(|&i| &self.definitions[i])
+    }
+}
+
+#[derive(Debug)]
+pub struct Definition {
+    pub(crate) id: Arc<str>,
+    pub(crate) class: Arc<str>,
+    pub(crate) title: Option<String>,
+    /// Platforms of the `affected` metadata
+    pub(crate) platforms: Vec<String>,
+    pub(crate) references: Vec<DefinitionReference>,
+    pub(crate) advisory: Option<Advisory>,
+}
+
+impl Definition {
+    fn from_xml(el: &Element, opts: &ParseOptions) -> Result<Definition, Error> {
+        let id = require_attr(el, "id")?;
+        let class = require_attr(el, "class")?;
+        let metadata = el.get_child("metadata", OVAL_DEF_NS);
+        let title = metadata
+            .and_then(|metadata| metadata.get_child("title", OVAL_DEF_NS))
+            .map(|title| title.text());
+        let mut platforms = Vec::new();
+        let mut references = Vec::new();
+        let mut advisory = None;
+        for child in metadata.iter().flat_map(|m| m.children()) {
+            if child.is("affected", OVAL_DEF_NS) {
+                platforms.extend(
+                    child
+                        .children()
+                        .filter(|p| p.is("platform", OVAL_DEF_NS))
+                        .map(|p| p.text()),
+                );
+            } else if child.is("reference", OVAL_DEF_NS) {
+                references.push(DefinitionReference {
+                    source: require_attr(child, "source")?,
+                    ref_id: require_attr(child, "ref_id")?,
+                    ref_url: get_attr(child, "ref_url"),
+                });
+            } else if child.name() == "advisory" {
+                advisory = Some(Advisory::from_xml(child, opts));
+            }
+        }
+        Ok(Definition {
+            id,
+            class,
+            title,
+            platforms,
+            references,
+            advisory,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// E.g. `compliance`, `inventory` or `vulnerability`
+    pub fn class(&self) -> &str {
+        &self.class
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn platforms(&self) -> &[String] {
+        &self.platforms
+    }
+
+    pub fn references(&self) -> &[DefinitionReference] {
+        &self.references
+    }
+
+    pub fn advisory(&self) -> Option<&Advisory> {
+        self.advisory.as_ref()
+    }
+
+    /// IDs of the CVE references and of the CVEs of the advisory.
+    pub fn cves(&self) -> Vec<&str> {
+        let mut cves: Vec<&str> = self
+            .references
+            .iter()
+            .filter(|r| r.source.eq_ignore_ascii_case("CVE"))
+            .map(|r| r.ref_id.as_ref())
+            .collect();
+        for cve in self.advisory.iter().flat_map(|a| a.cves.iter()) {
+            if !cves.contains(&cve.id.as_str()) {
+                cves.push(&cve.id);
+            }
+        }
+        cves
+    }
+
+    /// CVSS score of the CVE given by the advisory, v3 if available.
+    pub fn cvss(&self, cve: &str) -> Option<&Cvss> {
+        let cve = self.advisory.as_ref()?.cves.iter().find(|c| c.id == cve)?;
+        cve.cvss3.as_ref().or(cve.cvss2.as_ref())
+    }
+}
+
+/// Vendor `advisory` of the definition metadata, e.g. in the Red Hat OVAL
+/// feeds.
+#[derive(Debug)]
+pub struct Advisory {
+    pub(crate) severity: Option<String>,
+    pub(crate) cves: Vec<AdvisoryCve>,
+}
+
+impl Advisory {
+    /// A malformed CVSS vector is a warning, the CVE is kept without it.
+    fn from_xml(el: &Element, opts: &ParseOptions) -> Advisory {
+        let severity = el
+            .children()
+            .find(|c| c.name() == "severity")
+            .map(|c| c.text());
+        let mut cves = Vec::new();
+        for child in el.children().filter(|c| c.name() == "cve") {
+            let cvss = |name: &str| {
+                let vector = child.attr(name)?;
+                match vector.parse() {
+                    Ok(cvss) => Some(cvss),
+                    Err(e) => {
+                        opts.warn(Error::invalid_value(child.name(), Some(name), vector, e));
+                        None
+                    }
+                }
+            };
+            cves.push(AdvisoryCve {
+                id: child.text().trim().to_string(),
+                cvss2: cvss("cvss2"),
+                cvss3: cvss("cvss3"),
+                impact: get_attr(child, "impact"),
+            });
+        }
+        Advisory { severity, cves }
+    }
+
+    /// Severity rating of the vendor, e.g. `Important`
+    pub fn severity(&self) -> Option<&str> {
+        self.severity.as_deref()
+    }
+
+    pub fn cves(&self) -> &[AdvisoryCve] {
+        &self.cves
+    }
+}
+
+#[derive(Debug)]
+pub struct AdvisoryCve {
+    pub(crate) id: String,
+    pub(crate) cvss2: Option<Cvss>,
+    pub(crate) cvss3: Option<Cvss>,
+    pub(crate) impact: Option<Arc<str>>,
+}
+
+impl AdvisoryCve {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn cvss2(&self) -> Option<&Cvss> {
+        self.cvss2.as_ref()
+    }
+
+    pub fn cvss3(&self) -> Option<&Cvss> {
+        self.cvss3.as_ref()
+    }
+
+    pub fn impact(&self) -> Option<&str> {
+        self.impact.as_deref()
+    }
+}
+
+/// `reference` in the metadata of a definition, e.g. to a CVE or an advisory.
+#[derive(Debug)]
+pub struct DefinitionReference {
+    pub(crate) source: Arc<str>,
+    pub(crate) ref_id: Arc<str>,
+    pub(crate) ref_url: Option<Arc<str>>,
+}
+
+impl DefinitionReference {
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn ref_id(&self) -> &str {
+        &self.ref_id
+    }
+
+    pub fn ref_url(&self) -> Option<&str> {
+        self.ref_url.as_deref()
+    }
+}
+
+/// CVE and the definitions checking it, as listed for a vulnerability data
+/// stream.
+#[derive(Debug, Serialize)]
+pub struct CveCoverage {
+    pub cve: String,
+    /// The highest score given by the advisories of the definitions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cvss: Option<Cvss>,
+    pub platforms: Vec<String>,
+    pub definitions: Vec<String>,
+}
+
+impl CveCoverage {
+    pub fn severity(&self) -> Option<CvssSeverity> {
+        self.cvss.as_ref().map(|c| c.severity())
+    }
+}
+
+/// The CVEs referenced by the definitions of the documents, sorted by ID.
+pub fn cve_coverage(documents: &[&OvalDefinitions]) -> Vec<CveCoverage> {
+    let mut coverage: BTreeMap<&str, CveCoverage> = BTreeMap::new();
+    for definition in documents.iter().flat_map(|d| d.definitions.iter()) {
+        for cve in definition.cves() {
+            let entry = coverage.entry(cve).or_insert_with(|| CveCoverage {
+                cve: cve.to_string(),
+                cvss: None,
+                platforms: Vec::new(),
+                definitions: Vec::new(),
+            });
+            if let Some(cvss) = definition.cvss(cve) {
+                if entry
+                    .cvss
+                    .as_ref()
+                    .is_none_or(|c| c.base_score < cvss.base_score)
+                {
+                    entry.cvss = Some(cvss.clone());
+                }
+            }
+            for platform in definition.platforms.iter() {
+                if !entry.platforms.contains(platform) {
+                    entry.platforms.push(platform.clone());
+                }
+            }
+            if !entry.definitions.iter().any(|d| *d == *definition.id) {
+                entry.definitions.push(definition.id.to_string());
+            }
+        }
+    }
+    coverage.into_values().collect()
+}
+
+/// Sorts the CVEs from the highest score, those without a score last.
+pub fn sort_by_score(coverage: &mut [CveCoverage]) {
+    let score = |c: &CveCoverage| c.cvss.as_ref().map_or(-1.0, |c| c.base_score);
+    coverage.sort_by(|a, b| {
+        score(b)
+            .total_cmp(&score(a))
+            .then_with(|| a.cve.cmp(&b.cve))
+    });
+}
+
+/// OVAL variables document giving the values of the external variables of
+/// OVAL definitions.
+#[derive(Debug)]
+pub struct OvalVariables {
+    pub(crate) variables: Vec<ExternalVariable>,
+}
+
+impl OvalVariables {
+    pub fn from_xml(el: &Element) -> Result<OvalVariables, Error> {
+        if !el.is("oval_variables", OVAL_VAR_NS) {
+            return Err(Error::unexpected_element(el.name(), Some("oval_variables")));
+        }
+        let mut variables = Vec::new();
+        if let Some(section) = el.get_child("variables", OVAL_VAR_NS) {
+            for_each_child(section, |child| {
+                if child.is("variable", OVAL_VAR_NS) {
+                    variables.push(ExternalVariable::from_xml(child)?);
+                }
+                Ok(())
+            })?;
+        }
+        Ok(OvalVariables { variables })
+    }
+
+    pub fn variables(&self) -> &[ExternalVariable] {
+        &self.variables
+    }
+
+    pub fn variable(&self, id: &str) -> Option<&ExternalVariable> {
+        self.variables.iter().find(|v| &*v.id == id)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExternalVariable {
+    pub(crate) id: Arc<str>,
+    pub(crate) datatype: Arc<str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) comment: Option<Arc<str>>,
+    pub(crate) values: Vec<String>,
+}
+
+impl ExternalVariable {
+    fn from_xml(el: &Element) -> Result<ExternalVariable, Error> {
+        let id = require_attr(el, "id")?;
+        let datatype = require_attr(el, "datatype")?;
+        let comment = get_attr(el, "comment");
+        let values = el
+            .children()
+            .filter(|c| c.is("value", OVAL_VAR_NS))
+            .map(|c| c.text())
+            .collect();
+        Ok(ExternalVariable {
+            id,
+            datatype,
+            comment,
+            values,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// E.g. `string`, `int` or `boolean`
+    pub fn datatype(&self) -> &str {
+        &self.datatype
+    }
+
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn values(&self) -> &[String] {
+        &self.values
+    }
+}
+
+/// `check-export` of a rule and the external variable it's bound to.
+#[derive(Debug, Serialize)]
+pub struct ExportBinding<'a> {
+    pub rule: &'a str,
+    pub value: &'a str,
+    pub export_name: &'a str,
+    /// `None` if the variables document doesn't define the variable
+    pub variable: Option<&'a ExternalVariable>,
+}
+
+/// Binds the check-exports of the rules of the benchmark to the variables
+/// of the document, in the benchmark order.
+pub fn export_bindings<'a>(
+    benchmark: &'a Benchmark,
+    variables: &'a OvalVariables,
+) -> Vec<ExportBinding<'a>> {
+    let mut bindings = Vec::new();
+    for rule in benchmark.iter_rules() {
+        for check in rule.item.checks() {
+            for export in check.exports() {
+                bindings.push(ExportBinding {
+                    rule: rule.item.id(),
+                    value: export.value_id(),
+                    export_name: export.export_name(),
+                    variable: variables.variable(export.export_name()),
+                });
+            }
+        }
+    }
+    bindings
+}
+
+string_enum!(
+    /// Result of an OVAL definition.
+    DefinitionResult {
+        True => "true",
+        False => "false",
+        Unknown => "unknown",
+        Error => "error",
+        NotEvaluated => "not evaluated",
+        NotApplicable => "not applicable",
+    }
+);
+
+impl DefinitionResult {
+    /// The XCCDF result of a rule checking a definition of the class, e.g.
+    /// a true vulnerability definition fails the rule.
+    pub fn xccdf_result(&self, class: &str) -> &'static str {
+        let issue = matches!(class, "vulnerability" | "patch");
+        match self {
+            DefinitionResult::True if issue => "fail",
+            DefinitionResult::True => "pass",
+            DefinitionResult::False if issue => "pass",
+            DefinitionResult::False => "fail",
+            DefinitionResult::Unknown => "unknown",
+            DefinitionResult::Error => "error",
+            DefinitionResult::NotEvaluated => "notchecked",
+            DefinitionResult::NotApplicable => "notapplicable",
+        }
+    }
+}
+
+/// OVAL results document of a scan, one system per scanned system.
+#[derive(Debug, Serialize)]
+pub struct OvalResults {
+    pub(crate) systems: Vec<ResultSystem>,
+}
+
+impl OvalResults {
+    /// Parses the results, the classes and titles of the definitions are
+    /// taken from the definitions included in the document.
+    pub fn from_xml(el: &Element) -> Result<OvalResults, Error> {
+        if !el.is("oval_results", OVAL_RES_NS) {
+            return Err(Error::unexpected_element(el.name(), Some("oval_results")));
+        }
+        let definitions = el
+            .get_child("oval_definitions", OVAL_DEF_NS)
+            .map(OvalDefinitions::from_xml)
+            .transpose()?;
+        let mut systems = Vec::new();
+        if let Some(results) = el.get_child("results", OVAL_RES_NS) {
+            for_each_child(results, |child| {
+                if child.is("system", OVAL_RES_NS) {
+                    systems.push(ResultSystem::from_xml(child, definitions.as_ref())?);
+                }
+                Ok(())
+            })?;
+        }
+        Ok(OvalResults { systems })
+    }
+
+    pub fn systems(&self) -> &[ResultSystem] {
+        &self.systems
+    }
+
+    /// Result of the definition on the first system with a result for it.
+    pub fn definition(&self, id: &str) -> Option<&DefinitionOutcome> {
+        self.systems.iter().find_map(|s| s.definition(id))
+    }
+
+    pub fn write_text<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        for (i, system) in self.systems.iter().enumerate() {
+            if i > 0 {
+                writeln!(out)?;
+            }
+            match &system.info {
+                Some(info) => {
+                    writeln!(out, "System: {}", info.primary_host_name)?;
+                    writeln!(
+                        out,
+                        "OS: {} {} ({})",
+                        info.os_name, info.os_version, info.architecture
+                    )?;
+                    writeln!(
+                        out,
+                        "Collected objects: {}, items: {}",
+                        info.collected_objects, info.items
+                    )?;
+                }
+                None => writeln!(out, "System: without system characteristics")?,
+            }
+            for result in DefinitionResult::VALUES {
+                let count = system
+                    .definitions
+                    .iter()
+                    .filter(|d| d.result.as_str() == *result)
+                    .count();
+                if count > 0 {
+                    writeln!(out, "{:<15} {}", result, count)?;
+                }
+            }
+            for definition in system.definitions.iter() {
+                let line = format!(
+                    "{:<15} {} {}",
+                    definition.result.as_str(),
+                    definition.id,
+                    definition.title.as_deref().unwrap_or_default()
+                );
+                writeln!(out, "{}", line.trim_end())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Results of the definitions on one system.
+#[derive(Debug, Serialize)]
+pub struct ResultSystem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) info: Option<SystemInfo>,
+    pub(crate) definitions: Vec<DefinitionOutcome>,
+}
+
+impl ResultSystem {
+    fn from_xml(
+        el: &Element,
+        definitions: Option<&OvalDefinitions>,
+    ) -> Result<ResultSystem, Error> {
+        let info = el
+            .get_child("oval_system_characteristics", OVAL_SC_NS)
+            .map(SystemInfo::from_xml);
+        let mut outcomes = Vec::new();
+        if let Some(section) = el.get_child("definitions", OVAL_RES_NS) {
+            for_each_child(section, |child| {
+                if child.is("definition", OVAL_RES_NS) {
+                    let id = require_attr(child, "definition_id")?;
+                    let definition = definitions.and_then(|d| d.definition(&id));
+                    outcomes.push(DefinitionOutcome {
+                        result: require_attr_parsed(child, "result")?,
+                        class: definition.map(|d| d.class.clone()),
+                        title: definition.and_then(|d| d.title.clone()),
+                        id,
+                    });
+                }
+                Ok(())
+            })?;
+        }
+        Ok(ResultSystem {
+            info,
+            definitions: outcomes,
+        })
+    }
+
+    pub fn info(&self) -> Option<&SystemInfo> {
+        self.info.as_ref()
+    }
+
+    pub fn definitions(&self) -> &[DefinitionOutcome] {
+        &self.definitions
+    }
+
+    pub fn definition(&self, id: &str) -> Option<&DefinitionOutcome> {
+        self.definitions.iter().find(|d| &*d.id == id)
+    }
+}
+
+/// Summary of the system characteristics collected by the scan.
+#[derive(Debug, Serialize)]
+pub struct SystemInfo {
+    pub(crate) os_name: String,
+    pub(crate) os_version: String,
+    pub(crate) architecture: String,
+    pub(crate) primary_host_name: String,
+    pub(crate) collected_objects: usize,
+    pub(crate) items: usize,
+}
+
+impl SystemInfo {
+    fn from_xml(el: &Element) -> SystemInfo {
+        let info = el.get_child("system_info", OVAL_SC_NS);
+        let text = |name: &str| {
+            info.and_then(|i| i.get_child(name, OVAL_SC_NS))
+                .map(|c| c.text())
+                .unwrap_or_default()
+        };
+        let count = |name: &str| {
+            el.get_child(name, OVAL_SC_NS)
+                .map_or(0, |section| section.children().count())
+        };
+        SystemInfo {
+            os_name: text("os_name"),
+            os_version: text("os_version"),
+            architecture: text("architecture"),
+            primary_host_name: text("primary_host_name"),
+            collected_objects: count("collected_objects"),
+            items: count("system_data"),
+        }
+    }
+
+    pub fn os_name(&self) -> &str {
+        &self.os_name
+    }
+
+    pub fn os_version(&self) -> &str {
+        &self.os_version
+    }
+
+    pub fn architecture(&self) -> &str {
+        &self.architecture
+    }
+
+    pub fn primary_host_name(&self) -> &str {
+        &self.primary_host_name
+    }
+
+    pub fn collected_objects(&self) -> usize {
+        self.collected_objects
+    }
+
+    pub fn items(&self) -> usize {
+        self.items
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DefinitionOutcome {
+    pub(crate) id: Arc<str>,
+    pub(crate) result: DefinitionResult,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) class: Option<Arc<str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) title: Option<String>,
+}
+
+impl DefinitionOutcome {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn result(&self) -> DefinitionResult {
+        self.result
+    }
+
+    /// The class of the definition, if the document includes it
+    pub fn class(&self) -> Option<&str> {
+        self.class.as_deref()
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// The XCCDF result of a rule checking the definition, definitions of
+    /// unknown class are assumed to be compliance definitions.
+    pub fn xccdf_result(&self) -> &'static str {
+        self.result
+            .xccdf_result(self.class.as_deref().unwrap_or("compliance"))
+    }
+}
+
+/// Rule result of a TestResult with the result of the OVAL definition it
+/// checked.
+#[derive(Debug, Serialize)]
+pub struct CorrelatedResult<'a> {
+    pub rule: &'a str,
+    pub result: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub definition: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oval_result: Option<DefinitionResult>,
+    /// Whether the rule result is the one the definition result maps to,
+    /// `None` without a definition result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consistent: Option<bool>,
+}
+
+/// Pairs the rule results with the OVAL definition results of the same
+/// scan. The definition is taken from the check of the rule result, or of
+/// the rule if the rule result doesn't have one.
+pub fn correlate<'a>(
+    benchmark: &'a Benchmark,
+    test_result: &'a TestResult,
+    results: &'a OvalResults,
+) -> Vec<CorrelatedResult<'a>> {
+    test_result
+        .rule_results()
+        .iter()
+        .map(|rule_result| {
+            let checks = match rule_result.checks() {
+                [] => benchmark
+                    .rule(rule_result.idref())
+                    .map_or(&[][..], |r| r.checks()),
+                checks => checks,
+            };
+            let definition = checks
+                .iter()
+                .filter(|c| *c.system() == CheckSystem::Oval)
+                .flat_map(|c| c.content_refs())
+                .find_map(|r| r.name());
+            let outcome = definition.and_then(|id| results.definition(id));
+            CorrelatedResult {
+                rule: rule_result.idref(),
+                result: rule_result.result(),
+                definition,
+                oval_result: outcome.map(|o| o.result),
+                consistent: outcome.map(|o| o.xccdf_result() == rule_result.result()),
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize)]
+pub struct SectionStats {
+    pub section: String,
+    pub total: usize,
+    pub duplicates: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OvalStats {
+    pub documents: usize,
+    pub sections: Vec<SectionStats>,
+}
+
+impl OvalStats {
+    fn new() -> OvalStats {
+        OvalStats {
+            documents: 0,
+            sections: SECTIONS
+                .iter()
+                .map(|section| SectionStats {
+                    section: section.to_string(),
+                    total: 0,
+                    duplicates: 0,
+                })
+                .collect(),
+        }
+    }
+
+    fn section_mut(&mut self, name: &str) -> &mut SectionStats {
+        self.sections
+            .iter_mut()
+            .find(|s| s.section == name)
+            .unwrap()
+    }
+
+    pub fn write_text<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "OVAL documents: {}", self.documents)?;
+        writeln!(
+            out,
+            "{:<12} {:>10} {:>11}",
+            "Section", "Total", "Duplicates"
+        )?;
+        for section in self.sections.iter() {
+            writeln!(
+                out,
+                "{:<12} {:>10} {:>11}",
+                section.section, section.total, section.duplicates
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes a representation of the element that is equal for structurally
+/// identical entries regardless of their IDs.
+fn structural_key(el: &Element, top: bool, key: &mut String) {
+    key.push('<');
+    key.push_str(&el.ns());
+    key.push(' ');
+    key.push_str(el.name());
+    for (name, value) in el.attrs() {
+        if top && IGNORED_ATTRS.contains(&name) {
+            continue;
+        }
+        key.push_str(&format!(" {}={:?}", name, value));
+    }
+    key.push('>');
+    for node in el.nodes() {
+        match node {
+            Node::Element(child) => structural_key(child, false, key),
+            Node::Text(text) if text.trim().is_empty() => (),
+            Node::Text(text) => key.push_str(&format!("{:?}", text)),
+        }
+    }
+    key.push('/');
+}
+
+/// Maps the ID of every duplicate entry of the section to the ID of the
+/// first structurally identical entry.
+fn find_duplicates(section: &Element) -> HashMap<String, String> {
+    let mut first_by_key: HashMap<String, String> = HashMap::new();
+    let mut duplicates = HashMap::new();
+    for entry in section.children() {
+        let id = match entry.attr("id") {
+            Some(id) => id,
+            None => continue,
+        };
+        let mut key = String::new();
+        structural_key(entry, true, &mut key);
+        match first_by_key.get(&key) {
+            Some(first) => {
+                duplicates.insert(id.to_string(), first.clone());
+            }
+            None => {
+                first_by_key.insert(key, id.to_string());
+            }
+        }
+    }
+    duplicates
+}
+
+/// Rewrites attribute values and texts equal to a replaced ID.
+fn rewrite_references(el: &mut Element, replaced: &HashMap<String, String>) {
+    let attrs: Vec<(String, String)> = el
+        .attrs()
+        .filter_map(|(name, value)| {
+            replaced
+                .get(value)
+                .map(|new| (name.to_string(), new.to_string()))
+        })
+        .collect();
+    for (name, value) in attrs {
+        el.set_attr(name, value);
+    }
+    for node in el.nodes_mut() {
+        match node {
+            Node::Element(child) => rewrite_references(child, replaced),
+            Node::Text(text) => {
+                if let Some(new) = replaced.get(text.trim()) {
+                    *text = new.clone();
+                }
+            }
+        }
+    }
+}
+
+fn oval_documents(root: &mut Element) -> Vec<&mut Element> {
+    if root.is("oval_definitions", OVAL_DEF_NS) {
+        return vec![root];
+    }
+    let mut documents = Vec::new();
+    if root.is("data-stream-collection", SCAP12_NS) {
+        for component in root.children_mut() {
+            if component.is("component", SCAP12_NS) {
+                for content in component.children_mut() {
+                    if content.is("oval_definitions", OVAL_DEF_NS) {
+                        documents.push(content);
+                    }
+                }
+            }
+        }
+    }
+    documents
+}
+
+fn count_entries(document: &Element, stats: &mut OvalStats) {
+    for section in document.children() {
+        if section.has_ns(OVAL_DEF_NS) && SECTIONS.contains(&section.name()) {
+            stats.section_mut(section.name()).total += section.children().count();
+        }
+    }
+}
+
+/// Merges the structurally identical tests, objects and states of an OVAL
+/// definitions document. Merging entries can make the entries that
+/// reference them identical, so it is repeated until nothing changes.
+fn dedup_document(document: &mut Element, stats: &mut OvalStats) {
+    loop {
+        let mut replaced = HashMap::new();
+        for section in document.children_mut() {
+            if !section.has_ns(OVAL_DEF_NS) || !MERGED_SECTIONS.contains(&section.name()) {
+                continue;
+            }
+            let duplicates = find_duplicates(section);
+            if duplicates.is_empty() {
+                continue;
+            }
+            stats.section_mut(section.name()).duplicates += duplicates.len();
+            retain_children(section, |entry| {
+                !entry
+                    .attr("id")
+                    .map(|id| duplicates.contains_key(id))
+                    .unwrap_or(false)
+            });
+            replaced.extend(duplicates);
+            // Rewrite the references before looking at the next section, so
+            // that entries pointing to merged ones can be merged too.
+            break;
+        }
+        if replaced.is_empty() {
+            break;
+        }
+        rewrite_references(document, &replaced);
+    }
+    for section in ["definitions", "variables"] {
+        if let Some(entries) = document.get_child(section, OVAL_DEF_NS) {
+            stats.section_mut(section).duplicates += find_duplicates(entries).len();
+        }
+    }
+}
+
+/// Reports the number of entries and structural duplicates in every OVAL
+/// definitions document of a source data stream or a standalone OVAL file.
+/// The duplicate counts include entries that become identical only after
+/// the entries they reference are merged.
+pub fn stats(root: &Element) -> OvalStats {
+    let mut root = root.clone();
+    dedup(&mut root)
+}
+
+/// Merges duplicate OVAL entries in place, rewriting the references to them.
+/// Returns the statistics of the original documents.
+pub fn dedup(root: &mut Element) -> OvalStats {
+    let mut stats = OvalStats::new();
+    for document in oval_documents(root) {
+        stats.documents += 1;
+        count_entries(document, &mut stats);
+        dedup_document(document, &mut stats);
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document() -> Element {
+        format!(
+            "<oval_definitions xmlns=\"{}\" xmlns:ind=\"{}#independent\">\
+             <definitions><definition id=\"oval:x:def:1\" version=\"1\" class=\"compliance\">\
+             <criteria><criterion test_ref=\"oval:x:tst:1\"/><criterion test_ref=\"oval:x:tst:2\"/></criteria>\
+             </definition></definitions>\
+             <tests><ind:textfilecontent54_test id=\"oval:x:tst:1\" version=\"1\" check=\"all\" comment=\"a\">\
+             <ind:object object_ref=\"oval:x:obj:1\"/></ind:textfilecontent54_test>\
+             <ind:textfilecontent54_test id=\"oval:x:tst:2\" version=\"1\" check=\"all\" comment=\"b\">\
+             <ind:object object_ref=\"oval:x:obj:2\"/></ind:textfilecontent54_test></tests>\
+             <objects><ind:textfilecontent54_object id=\"oval:x:obj:1\" version=\"1\">\
+             <ind:filepath>/etc/x</ind:filepath></ind:textfilecontent54_object>\
+             <ind:textfilecontent54_object id=\"oval:x:obj:2\" version=\"2\">\
+             <ind:filepath>/etc/x</ind:filepath></ind:textfilecontent54_object></objects>\
+             </oval_definitions>",
+            OVAL_DEF_NS, OVAL_DEF_NS
+        )
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = stats(&document());
+        assert_eq!(stats.documents, 1);
+        let objects = stats
+            .sections
+            .iter()
+            .find(|s| s.section == "objects")
+            .unwrap();
+        assert_eq!((objects.total, objects.duplicates), (2, 1));
+        let tests = stats
+            .sections
+            .iter()
+            .find(|s| s.section == "tests")
+            .unwrap();
+        assert_eq!((tests.total, tests.duplicates), (2, 1));
+    }
+
+    #[test]
+    fn test_dedup_rewrites_references() {
+        let mut el = document();
+        dedup(&mut el);
+        let objects = el.get_child("objects", OVAL_DEF_NS).unwrap();
+        assert_eq!(objects.children().count(), 1);
+        let tests = el.get_child("tests", OVAL_DEF_NS).unwrap();
+        assert_eq!(tests.children().count(), 1);
+        let criteria: Vec<&str> = el
+            .get_child("definitions", OVAL_DEF_NS)
+            .and_then(|d| d.children().next())
+            .and_then(|d| d.get_child("criteria", OVAL_DEF_NS))
+            .unwrap()
+            .children()
+            .filter_map(|c| c.attr("test_ref"))
+            .collect();
+        assert_eq!(criteria, vec!["oval:x:tst:1", "oval:x:tst:1"]);
+    }
+
+    #[test]
+    fn test_dedup_keeps_exported_variables() {
+        let mut el: Element = format!(
+            "<data-stream-collection xmlns=\"{}\"><component id=\"xccdf\">\
+             <Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Rule id=\"r\"><check system=\"{}\">\
+             <check-export value-id=\"v1\" export-name=\"oval:x:var:1\"/>\
+             <check-export value-id=\"v2\" export-name=\"oval:x:var:2\"/>\
+             <check-content-ref href=\"#oval\"/></check></Rule></Benchmark></component>\
+             <component id=\"oval\"><oval_definitions xmlns=\"{}\"><variables>\
+             <external_variable id=\"oval:x:var:1\" version=\"1\" datatype=\"int\" comment=\"a\"/>\
+             <external_variable id=\"oval:x:var:2\" version=\"1\" datatype=\"int\" comment=\"b\"/>\
+             </variables></oval_definitions></component></data-stream-collection>",
+            SCAP12_NS,
+            crate::xccdf::XCCDF12_NS,
+            OVAL_DEF_NS,
+            OVAL_DEF_NS
+        )
+        .parse()
+        .unwrap();
+        let stats = dedup(&mut el);
+        let variables = stats
+            .sections
+            .iter()
+            .find(|s| s.section == "variables")
+            .unwrap();
+        assert_eq!((variables.total, variables.duplicates), (2, 1));
+        let oval = el.children().nth(1).unwrap().children().next().unwrap();
+        let ids: Vec<&str> = oval
+            .get_child("variables", OVAL_DEF_NS)
+            .unwrap()
+            .children()
+            .filter_map(|v| v.attr("id"))
+            .collect();
+        assert_eq!(ids, vec!["oval:x:var:1", "oval:x:var:2"]);
+        let exports: Vec<&str> = el
+            .children()
+            .next()
+            .and_then(|c| c.children().next())
+            .and_then(|b| b.children().find(|r| r.name() == "Rule"))
+            .and_then(|r| r.children().next())
+            .unwrap()
+            .children()
+            .filter_map(|e| e.attr("export-name"))
+            .collect();
+        assert_eq!(exports, vec!["oval:x:var:1", "oval:x:var:2"]);
+    }
+
+    #[test]
+    fn test_export_bindings() {
+        let el: Element = format!(
+            "<oval_variables xmlns=\"{}\"><variables>\
+             <variable id=\"oval:x:var:1\" datatype=\"int\" comment=\"umask\">\
+             <value>027</value><value>077</value></variable></variables></oval_variables>",
+            OVAL_VAR_NS
+        )
+        .parse()
+        .unwrap();
+        let variables = OvalVariables::from_xml(&el).unwrap();
+        assert_eq!(variables.variables()[0].values(), ["027", "077"]);
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Rule id=\"r\"><check system=\"{}\">\
+             <check-export value-id=\"v1\" export-name=\"oval:x:var:1\"/>\
+             <check-export value-id=\"v2\" export-name=\"oval:x:var:2\"/>\
+             <check-content-ref href=\"oval.xml\"/></check></Rule></Benchmark>",
+            crate::xccdf::XCCDF12_NS,
+            OVAL_DEF_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let bindings = export_bindings(&benchmark, &variables);
+        assert_eq!(bindings.len(), 2);
+        assert_eq!((bindings[0].rule, bindings[0].value), ("r", "v1"));
+        assert_eq!(bindings[0].variable.map(|v| v.datatype()), Some("int"));
+        assert!(bindings[1].variable.is_none());
+    }
+
+    #[test]
+    fn test_correlate_results() {
+        let el: Element = format!(
+            "<oval_results xmlns=\"{}\"><oval_definitions xmlns=\"{}\"><definitions>\
+             <definition id=\"oval:x:def:1\" version=\"1\" class=\"compliance\">\
+             <metadata><title>Root login</title></metadata></definition>\
+             <definition id=\"oval:x:def:2\" version=\"1\" class=\"vulnerability\"/>\
+             </definitions></oval_definitions><results><system><definitions>\
+             <definition definition_id=\"oval:x:def:1\" result=\"true\" version=\"1\"/>\
+             <definition definition_id=\"oval:x:def:2\" result=\"true\" version=\"1\"/>\
+             </definitions><oval_system_characteristics xmlns=\"{}\"><system_info>\
+             <os_name>Linux</os_name><os_version>6.1</os_version><architecture>x86_64</architecture>\
+             <primary_host_name>host</primary_host_name></system_info><collected_objects>\
+             <object id=\"oval:x:obj:1\" version=\"1\" flag=\"complete\"/></collected_objects>\
+             </oval_system_characteristics></system></results></oval_results>",
+            OVAL_RES_NS, OVAL_DEF_NS, OVAL_SC_NS
+        )
+        .parse()
+        .unwrap();
+        let results = OvalResults::from_xml(&el).unwrap();
+        let system = &results.systems()[0];
+        assert_eq!(system.info().unwrap().primary_host_name(), "host");
+        assert_eq!(system.info().unwrap().collected_objects(), 1);
+        let definition = results.definition("oval:x:def:1").unwrap();
+        assert_eq!(definition.title(), Some("Root login"));
+        assert_eq!(definition.xccdf_result(), "pass");
+        assert_eq!(
+            results.definition("oval:x:def:2").unwrap().xccdf_result(),
+            "fail"
+        );
+
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{xccdf}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Rule id=\"r1\"><check system=\"{oval}\"><check-content-ref href=\"oval.xml\" \
+             name=\"oval:x:def:1\"/></check></Rule><Rule id=\"r2\"/>\
+             <TestResult id=\"t\" end-time=\"2024-01-01T00:00:00\"><benchmark href=\"#b\"/>\
+             <rule-result idref=\"r1\"><result>fail</result></rule-result>\
+             <rule-result idref=\"r2\"><result>pass</result><check system=\"{oval}\">\
+             <check-content-ref href=\"oval.xml\" name=\"oval:x:def:2\"/></check></rule-result>\
+             <score>50</score></TestResult></Benchmark>",
+            xccdf = crate::xccdf::XCCDF12_NS,
+            oval = OVAL_DEF_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let test_result = benchmark.test_result(None).unwrap();
+        let correlated = correlate(&benchmark, test_result, &results);
+        assert_eq!(correlated[0].definition, Some("oval:x:def:1"));
+        assert_eq!(correlated[0].consistent, Some(false));
+        assert_eq!(correlated[1].oval_result, Some(DefinitionResult::True));
+        assert_eq!(correlated[1].consistent, Some(false));
+    }
+
+    #[test]
+    fn test_advisory_cvss() {
+        let el: Element = format!(
+            "<oval_definitions xmlns=\"{}\"><definitions>\
+             <definition id=\"oval:x:def:1\" version=\"1\" class=\"patch\"><metadata>\
+             <title>RHSA</title><advisory from=\"secalert@redhat.com\"><severity>Important</severity>\
+             <cve cvss3=\"7.5/CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:N/A:N\">CVE-2021-2</cve>\
+             <cve cvss2=\"5.0/AV:N/AC:L/Au:N/C:P/I:N/A:N\">CVE-2021-1</cve><cve>CVE-2021-3</cve>\
+             </advisory></metadata></definition>\
+             <definition id=\"oval:x:def:2\" version=\"1\" class=\"patch\"><metadata>\
+             <title>RHSA</title><advisory><cve cvss3=\"9.8/CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H\">\
+             CVE-2021-2</cve></advisory></metadata></definition></definitions></oval_definitions>",
+            OVAL_DEF_NS
+        )
+        .parse()
+        .unwrap();
+        let definitions = OvalDefinitions::from_xml(&el).unwrap();
+        let definition = definitions.definition("oval:x:def:1").unwrap();
+        assert_eq!(definition.advisory().unwrap().severity(), Some("Important"));
+        assert_eq!(
+            definition.cves(),
+            vec!["CVE-2021-2", "CVE-2021-1", "CVE-2021-3"]
+        );
+        let mut coverage = cve_coverage(&[&definitions]);
+        assert_eq!(coverage[1].cvss.as_ref().unwrap().base_score(), 9.8);
+        assert_eq!(coverage[1].definitions, ["oval:x:def:1", "oval:x:def:2"]);
+        sort_by_score(&mut coverage);
+        let order: Vec<(&str, Option<CvssSeverity>)> = coverage
+            .iter()
+            .map(|c| (c.cve.as_str(), c.severity()))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("CVE-2021-2", Some(CvssSeverity::Critical)),
+                ("CVE-2021-1", Some(CvssSeverity::Medium)),
+                ("CVE-2021-3", None)
+            ]
+        );
+
+        let el: Element = format!(
+            "<oval_definitions xmlns=\"{}\"><definitions>\
+             <definition id=\"oval:x:def:1\" version=\"1\" class=\"patch\"><metadata>\
+             <advisory><cve cvss3=\"9.8/AV:N\">CVE-2021-2</cve></advisory></metadata>\
+             </definition></definitions></oval_definitions>",
+            OVAL_DEF_NS
+        )
+        .parse()
+        .unwrap();
+        let opts = ParseOptions::default();
+        let definitions = OvalDefinitions::from_xml_with_options(&el, &opts).unwrap();
+        let cves = definitions.definitions()[0].advisory().unwrap().cves();
+        assert_eq!(cves[0].id(), "CVE-2021-2");
+        assert!(cves[0].cvss3().is_none());
+        assert_eq!(opts.take_warnings().len(), 1);
+    }
+}
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::misc;
+use crate::sds::DataStreamCollection;
+use crate::utils::sha256_hex;
+
+/// Name of the manifest file stored in the root of a content pack directory.
+pub const MANIFEST_NAME: &str = "scap-content.json";
+const MANIFEST_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub files: Vec<ManifestFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ManifestFile {
+    /// Path relative to the pack directory
+    pub path: String,
+    pub sha256: String,
+    /// Platforms (CPE names) the benchmarks in the file apply to
+    pub products: Vec<String>,
+    pub profiles: Vec<String>,
+}
+
+/// Lists the XML files in the pack directory, sorted by name.
+fn xml_files(dir: &Path) -> Result<Vec<String>, String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory '{}': {}", dir.display(), e))?;
+    let mut names = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.ends_with(".xml") && entry.path().is_file() {
+            names.push(name);
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+fn describe_file(dir: &Path, name: &str) -> Result<ManifestFile, String> {
+    let data = fs::read(dir.join(name)).map_err(|e| format!("Failed to read '{}': {}", name, e))?;
+    let (root, _) = misc::parse(&data).map_err(|e| format!("Failed to parse '{}': {}", name, e))?;
+    let collection = DataStreamCollection::from_xml(&root)
+        .map_err(|e| format!("Failed to parse SCAP source data stream '{}': {}", name, e))?;
+    let mut products = Vec::new();
+    let mut profiles = Vec::new();
+    for benchmark in collection.benchmarks() {
+        for platform in benchmark.platforms.iter() {
+            if !products.contains(&platform.idref.to_string()) {
+                products.push(platform.idref.to_string());
+            }
+        }
+        profiles.extend(benchmark.profiles.iter().map(|p| p.id.to_string()));
+    }
+    Ok(ManifestFile {
+        path: name.to_string(),
+        sha256: sha256_hex(&data),
+        products,
+        profiles,
+    })
+}
+
+/// Builds the manifest of all SCAP source data streams in the directory.
+pub fn create(dir: &Path) -> Result<Manifest, String> {
+    let mut files = Vec::new();
+    for name in xml_files(dir)? {
+        files.push(describe_file(dir, &name)?);
+    }
+    Ok(Manifest {
+        version: MANIFEST_VERSION,
+        files,
+    })
+}
+
+pub fn read_manifest(dir: &Path) -> Result<Manifest, String> {
+    let path = dir.join(MANIFEST_NAME);
+    let text = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let manifest: Manifest = serde_json::from_str(&text)
+        .map_err(|e| format!("Failed to parse '{}': {}", path.display(), e))?;
+    if manifest.version != MANIFEST_VERSION {
+        return Err(format!(
+            "Unsupported manifest version {}, expected {}",
+            manifest.version, MANIFEST_VERSION
+        ));
+    }
+    Ok(manifest)
+}
+
+pub fn write_manifest(dir: &Path, manifest: &Manifest) -> Result<(), String> {
+    let path = dir.join(MANIFEST_NAME);
+    let mut text = serde_json::to_string_pretty(manifest).map_err(|e| e.to_string())?;
+    text.push('\n');
+    fs::write(&path, text).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
+
+/// Checks the directory against the manifest and returns the list of
+/// problems: missing or modified files and data streams not in the manifest.
+pub fn verify(dir: &Path, manifest: &Manifest) -> Result<Vec<String>, String> {
+    let mut problems = Vec::new();
+    for file in manifest.files.iter() {
+        match fs::read(dir.join(&file.path)) {
+            Ok(data) => {
+                if sha256_hex(&data) != file.sha256 {
+                    problems.push(format!("{}: digest doesn't match the manifest", file.path));
+                }
+            }
+            Err(error) => problems.push(format!("{}: {}", file.path, error)),
+        }
+    }
+    for name in xml_files(dir)? {
+        if !manifest.files.iter().any(|f| f.path == name) {
+            problems.push(format!("{}: not listed in the manifest", name));
+        }
+    }
+    Ok(problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn pack_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("oscapxml-pack-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::copy("data/simple.xml", dir.join("simple.xml")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_create_and_verify() {
+        let dir = pack_dir("verify");
+        let manifest = create(&dir).unwrap();
+        assert_eq!(manifest.files.len(), 1);
+        assert_eq!(manifest.files[0].path, "simple.xml");
+        assert_eq!(manifest.files[0].sha256.len(), 64);
+        assert!(!manifest.files[0].profiles.is_empty());
+        write_manifest(&dir, &manifest).unwrap();
+        let manifest = read_manifest(&dir).unwrap();
+        assert!(verify(&dir, &manifest).unwrap().is_empty());
+
+        fs::write(dir.join("other.xml"), "<x/>").unwrap();
+        let mut data = fs::read(dir.join("simple.xml")).unwrap();
+        data.push(b'\n');
+        fs::write(dir.join("simple.xml"), data).unwrap();
+        assert_eq!(verify(&dir, &manifest).unwrap().len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,394 @@
+use serde::Serialize;
+
+use crate::info::ProfileInfo;
+use crate::report::RuleRow;
+use crate::xccdf::{Benchmark, Description, ResolvedProfile, Rule, Severity, Title};
+
+const SEVERITY_ORDER: [Severity; 5] = [
+    Severity::High,
+    Severity::Medium,
+    Severity::Low,
+    Severity::Info,
+    Severity::Unknown,
+];
+
+/// Which rules a query returns.
+#[derive(Debug, Clone, Default)]
+pub struct RuleFilter {
+    /// Only rules of these severities, all rules if empty
+    pub severities: Vec<Severity>,
+    /// Case-insensitive substring of the rule ID or title
+    pub text: Option<String>,
+}
+
+impl RuleFilter {
+    fn matches(&self, rule: &Rule) -> bool {
+        if !self.severities.is_empty() && !self.severities.contains(&rule.severity) {
+            return false;
+        }
+        match &self.text {
+            Some(text) => {
+                let text = text.to_lowercase();
+                rule.id.to_lowercase().contains(&text)
+                    || rule
+                        .titles
+                        .iter()
+                        .any(|t| t.title.to_lowercase().contains(&text))
+            }
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RuleSort {
+    /// The order of the rules in the benchmark
+    #[default]
+    Document,
+    Id,
+    /// Highest severity first
+    Severity,
+    Title,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Page {
+    /// Skips `offset` items
+    Offset { offset: usize, limit: usize },
+    /// Continues after the item the cursor of the previous page points to,
+    /// which stays valid when items before it are added or removed
+    After { cursor: String, limit: usize },
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Page::Offset {
+            offset: 0,
+            limit: 50,
+        }
+    }
+}
+
+/// One page of query results. `next_cursor` is set if there are more items.
+#[derive(Debug, Serialize)]
+pub struct ResultPage<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+/// Takes one page of `items`, only the items of the page are converted by
+/// `convert`.
+fn paginate<'a, I, F, T>(
+    items: &[&'a I],
+    page: &Page,
+    id: impl Fn(&I) -> &str,
+    convert: F,
+) -> Result<ResultPage<T>, String>
+where
+    F: Fn(&'a I) -> T,
+{
+    let (start, limit) = match page {
+        Page::Offset { offset, limit } => (*offset, *limit),
+        Page::After { cursor, limit } => match items.iter().position(|i| id(i) == cursor) {
+            Some(position) => (position + 1, *limit),
+            None => return Err(format!("Cursor '{}' doesn't point to any item", cursor)),
+        },
+    };
+    let end = start.saturating_add(limit).min(items.len());
+    let page_items = items.get(start..end).unwrap_or_default();
+    let next_cursor = match page_items.last() {
+        Some(last) if end < items.len() => Some(id(last).to_string()),
+        _ => None,
+    };
+    Ok(ResultPage {
+        items: page_items.iter().map(|i| convert(i)).collect(),
+        next_cursor,
+    })
+}
+
+/// Query over the rules of a benchmark, see [`RuleQuery::run`].
+#[derive(Debug, Clone, Default)]
+pub struct RuleQuery {
+    pub filter: RuleFilter,
+    pub sort: RuleSort,
+    pub page: Page,
+}
+
+impl RuleQuery {
+    /// Returns one page of the matching rules. Only references to the rules
+    /// are collected and sorted, the rows are built for the page only.
+    pub fn run(&self, benchmark: &Benchmark) -> Result<ResultPage<RuleRow>, String> {
+        let mut rules: Vec<&Rule> = benchmark
+            .all_rules()
+            .into_iter()
+            .filter(|r| self.filter.matches(r))
+            .collect();
+        match self.sort {
+            RuleSort::Document => (),
+            RuleSort::Id => rules.sort_by(|a, b| a.id.cmp(&b.id)),
+            RuleSort::Severity => rules.sort_by_key(|r| {
+                SEVERITY_ORDER
+                    .iter()
+                    .position(|s| *s == r.severity)
+                    .unwrap_or(SEVERITY_ORDER.len())
+            }),
+            RuleSort::Title => rules.sort_by_cached_key(|r| title(r).to_lowercase()),
+        }
+        paginate(
+            &rules,
+            &self.page,
+            |r| &r.id,
+            |rule| RuleRow {
+                id: rule.id.to_string(),
+                severity: rule.severity,
+                result: None,
+                title: title(rule).to_string(),
+            },
+        )
+    }
+}
+
+fn title(rule: &Rule) -> &str {
+    rule.titles
+        .first()
+        .map(|t| t.title.as_str())
+        .unwrap_or_default()
+}
+
+/// Returns one page of the profiles of the benchmark in the document order.
+pub fn profiles(benchmark: &Benchmark, page: &Page) -> Result<ResultPage<ProfileInfo>, String> {
+    let profiles: Vec<_> = benchmark.profiles.iter().collect();
+    paginate(
+        &profiles,
+        page,
+        |p| &p.id,
+        |profile| ProfileInfo {
+            id: profile.id.to_string(),
+            title: profile.titles.first().map(|t| t.title.clone()),
+            description: profile.descriptions.first().map(|d| benchmark.render(d)),
+        },
+    )
+}
+
+/// What `search` looks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchQuery {
+    /// Case-insensitive substring of the ID, title or description
+    Text(String),
+    /// Exact ID of a rule, group or value
+    Id(String),
+    /// Text of a rule ident, e.g. a CCE
+    Ident(String),
+}
+
+/// Rule, group or value found by [`search`].
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    /// `Rule`, `Group` or `Value`
+    pub kind: &'static str,
+    pub id: String,
+    /// IDs of the groups containing the item, the outermost first
+    pub path: Vec<String>,
+    pub title: String,
+    /// Profiles selecting the rule, the rules of the group or setting the
+    /// value
+    pub profiles: Vec<String>,
+}
+
+/// Matches the text query against the item.
+fn text_matches(text: &str, id: &str, titles: &[Title], descriptions: &[Description]) -> bool {
+    id.to_lowercase().contains(text)
+        || titles.iter().any(|t| t.title.to_lowercase().contains(text))
+        || descriptions
+            .iter()
+            .any(|d| d.text().to_lowercase().contains(text))
+}
+
+/// Finds the rules, groups and values matching the query. IDs are looked up
+/// in the index of the benchmark, the other queries scan its items.
+pub fn search(benchmark: &Benchmark, query: &SearchQuery) -> Vec<SearchHit> {
+    let mut found: Vec<(&'static str, &str, &[Title])> = Vec::new();
+    match query {
+        SearchQuery::Id(id) => {
+            if let Some(rule) = benchmark.rule(id) {
+                found.push(("Rule", &rule.id, &rule.titles));
+            } else if let Some(group) = benchmark.group(id) {
+                found.push(("Group", &group.id, &group.titles));
+            } else if let Some(value) = benchmark.value(id) {
+                found.push(("Value", &value.id, &value.titles));
+            }
+        }
+        SearchQuery::Text(text) => {
+            let text = text.to_lowercase();
+            for group in benchmark.iter_groups() {
+                let g = group.item;
+                if text_matches(&text, &g.id, &g.titles, &g.descriptions) {
+                    found.push(("Group", &g.id, &g.titles));
+                }
+            }
+            for rule in benchmark.iter_rules() {
+                let r = rule.item;
+                if text_matches(&text, &r.id, &r.titles, &r.descriptions) {
+                    found.push(("Rule", &r.id, &r.titles));
+                }
+            }
+            for value in benchmark.iter_values() {
+                let v = value.item;
+                if text_matches(&text, &v.id, &v.titles, &v.descriptions) {
+                    found.push(("Value", &v.id, &v.titles));
+                }
+            }
+        }
+        SearchQuery::Ident(ident) => {
+            for rule in benchmark.iter_rules() {
+                if rule
+                    .item
+                    .idents
+                    .iter()
+                    .any(|i| i.text.eq_ignore_ascii_case(ident))
+                {
+                    found.push(("Rule", &rule.item.id, &rule.item.titles));
+                }
+            }
+        }
+    }
+    if found.is_empty() {
+        return Vec::new();
+    }
+    let selections: Vec<(ResolvedProfile, Vec<&Rule>)> = benchmark
+        .profiles
+        .iter()
+        .filter_map(|p| {
+            let resolved = benchmark.resolve_profile(&p.id).ok()?;
+            let rules = benchmark.selected_rules(&resolved);
+            Some((resolved, rules))
+        })
+        .collect();
+    found
+        .into_iter()
+        .map(|(kind, id, titles)| {
+            let profiles = selections
+                .iter()
+                .filter(|(profile, rules)| match kind {
+                    "Rule" => rules.iter().any(|r| &*r.id == id),
+                    "Group" => rules.iter().any(|r| {
+                        benchmark
+                            .item_groups(&r.id)
+                            .is_some_and(|groups| groups.iter().any(|g| &*g.id == id))
+                    }),
+                    // The values set by the extended profiles are included.
+                    _ => {
+                        profile.set_values.iter().any(|v| &*v.idref == id)
+                            || profile.set_complex_values.iter().any(|v| &*v.idref == id)
+                            || profile.refine_values.iter().any(|v| &*v.idref == id)
+                    }
+                })
+                .map(|(profile, _)| profile.id.to_string())
+                .collect();
+            SearchHit {
+                kind,
+                id: id.to_string(),
+                path: benchmark
+                    .item_groups(id)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|g| g.id.to_string())
+                    .collect(),
+                title: titles.first().map(|t| t.title.clone()).unwrap_or_default(),
+                profiles,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xccdf::XCCDF12_NS;
+    use minidom::Element;
+
+    #[test]
+    fn test_rule_query_pages() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Rule id=\"r1\" severity=\"low\"><title>SSH timeout</title></Rule>\
+             <Group id=\"g\"><Rule id=\"r2\" severity=\"high\"><title>SSH root login</title></Rule>\
+             <Rule id=\"r3\" severity=\"medium\"><title>Audit</title></Rule>\
+             <Rule id=\"r4\" severity=\"high\"><title>SSH ciphers</title></Rule></Group></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let mut query = RuleQuery {
+            filter: RuleFilter {
+                severities: Vec::new(),
+                text: Some(String::from("ssh")),
+            },
+            sort: RuleSort::Severity,
+            page: Page::Offset {
+                offset: 0,
+                limit: 2,
+            },
+        };
+        let page = query.run(&benchmark).unwrap();
+        let ids: Vec<&str> = page.items.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["r2", "r4"]);
+        assert_eq!(page.next_cursor.as_deref(), Some("r4"));
+
+        query.page = Page::After {
+            cursor: String::from("r4"),
+            limit: 2,
+        };
+        let page = query.run(&benchmark).unwrap();
+        assert_eq!(page.items[0].id, "r1");
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.next_cursor, None);
+
+        query.page = Page::After {
+            cursor: String::from("r3"),
+            limit: 2,
+        };
+        assert!(query.run(&benchmark).is_err());
+    }
+
+    #[test]
+    fn test_search() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Profile id=\"p\"><title>P</title><select idref=\"r2\" selected=\"true\"/>\
+             <set-value idref=\"v\">8</set-value></Profile>\
+             <Profile id=\"p2\" extends=\"p\"><title>P2</title></Profile>\
+             <Value id=\"v\"><title>Minimum password length</title><value>12</value></Value>\
+             <Group id=\"g\"><Group id=\"g2\"><Rule id=\"r1\" selected=\"false\">\
+             <title>Password length</title><ident system=\"https://cce\">CCE-1</ident></Rule>\
+             <Rule id=\"r2\" selected=\"false\"><title>SSH</title>\
+             <description>Disable password login</description></Rule></Group></Group></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let hits = search(&benchmark, &SearchQuery::Text(String::from("PASSWORD")));
+        let ids: Vec<&str> = hits.iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(ids, vec!["r1", "r2", "v"]);
+        assert_eq!(hits[1].path, vec!["g", "g2"]);
+        assert_eq!(hits[1].profiles, vec!["p", "p2"]);
+        assert_eq!(hits[2].title, "Minimum password length");
+        assert!(hits[0].profiles.is_empty());
+
+        let hits = search(&benchmark, &SearchQuery::Ident(String::from("cce-1")));
+        assert_eq!(hits[0].title, "Password length");
+        let hits = search(&benchmark, &SearchQuery::Id(String::from("g2")));
+        assert_eq!(
+            (hits[0].kind, hits[0].path.clone()),
+            ("Group", vec![String::from("g")])
+        );
+        assert_eq!(hits[0].profiles, vec!["p", "p2"]);
+        let hits = search(&benchmark, &SearchQuery::Id(String::from("v")));
+        assert_eq!(hits[0].title, "Minimum password length");
+        // The value is set by p2 too, through the extended profile.
+        assert_eq!(hits[0].profiles, vec!["p", "p2"]);
+        assert!(search(&benchmark, &SearchQuery::Id(String::from("x"))).is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+use std::fmt::Write;
+use std::str::FromStr;
+
+use crate::xccdf::{Benchmark, ResolvedProfile};
+
+const SEPARATOR: &str =
+    "###############################################################################";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixSystem {
+    Bash,
+}
+
+impl FixSystem {
+    /// URN used in the `system` attribute of `xccdf:fix`.
+    pub fn urn(&self) -> &'static str {
+        match self {
+            FixSystem::Bash => "urn:xccdf:fix:script:sh",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FixSystem::Bash => "bash",
+        }
+    }
+}
+
+impl FromStr for FixSystem {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<FixSystem, String> {
+        match s {
+            "bash" => Ok(FixSystem::Bash),
+            _ => Err(format!("Unknown fix system '{}'", s)),
+        }
+    }
+}
+
+fn header(benchmark: &Benchmark, profile: &ResolvedProfile, system: FixSystem) -> String {
+    let title = profile
+        .titles
+        .first()
+        .map(|t| t.title.as_str())
+        .unwrap_or(profile.id);
+    let mut text = String::new();
+    writeln!(text, "#!/usr/bin/env bash").unwrap();
+    writeln!(text, "{}", SEPARATOR).unwrap();
+    writeln!(text, "#").unwrap();
+    writeln!(text, "# Bash Remediation Script for {}", title).unwrap();
+    writeln!(text, "#").unwrap();
+    writeln!(text, "# Profile ID:  {}", profile.id).unwrap();
+    writeln!(text, "# Benchmark ID:  {}", benchmark.id).unwrap();
+    writeln!(text, "# Benchmark Version:  {}", benchmark.version.text).unwrap();
+    writeln!(text, "# XCCDF Version:  1.2").unwrap();
+    writeln!(text, "#").unwrap();
+    writeln!(
+        text,
+        "# This file can be generated by: oscapxml generate fix --profile {} --system {}",
+        profile.id,
+        system.name()
+    )
+    .unwrap();
+    writeln!(text, "#").unwrap();
+    writeln!(text, "{}", SEPARATOR).unwrap();
+    text
+}
+
+/// Concatenates the fixes of the rules selected by the profile into a
+/// single script. Values referenced by the fixes are substituted according
+/// to the profile. Fails if any reference can't be resolved.
+pub fn generate_fix(
+    benchmark: &Benchmark,
+    profile: &ResolvedProfile,
+    system: FixSystem,
+) -> Result<String, String> {
+    let rules = benchmark.selected_rules(profile);
+    let mut script = header(benchmark, profile, system);
+    let mut errors = Vec::new();
+    for (index, rule) in rules.iter().enumerate() {
+        writeln!(script).unwrap();
+        writeln!(script, "{}", SEPARATOR).unwrap();
+        writeln!(
+            script,
+            "# BEGIN fix ({} / {}) for '{}'",
+            index + 1,
+            rules.len(),
+            rule.id
+        )
+        .unwrap();
+        writeln!(script, "{}", SEPARATOR).unwrap();
+        writeln!(
+            script,
+            "(>&2 echo \"Remediating rule {}/{}: '{}'\")",
+            index + 1,
+            rules.len(),
+            rule.id
+        )
+        .unwrap();
+        match rule
+            .fixes
+            .iter()
+            .find(|f| f.system.as_deref() == Some(system.urn()))
+        {
+            Some(fix) => {
+                let substituted =
+                    fix.substitute(|idref| benchmark.value_in_profile(idref, profile), |_| None);
+                match substituted {
+                    Ok(text) => {
+                        script.push_str(text.trim_matches('\n'));
+                        script.push('\n');
+                    }
+                    Err(error) => errors.push(format!("{}: {}", rule.id, error)),
+                }
+            }
+            None => {
+                writeln!(
+                    script,
+                    "(>&2 echo \"FIX FOR THIS RULE '{}' IS MISSING\")",
+                    rule.id
+                )
+                .unwrap();
+            }
+        }
+        writeln!(script, "# END fix for '{}'", rule.id).unwrap();
+    }
+    if errors.is_empty() {
+        Ok(script)
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xccdf::XCCDF12_NS;
+    use minidom::Element;
+
+    fn benchmark(fix: &str) -> Benchmark {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Profile id=\"p\"><title>P</title><select idref=\"r2\" selected=\"false\"/>\
+             <refine-value idref=\"v\" selector=\"strict\"/></Profile>\
+             <Value id=\"v\"><value>5</value><value selector=\"strict\">10</value></Value>\
+             <Rule id=\"r1\"><title>R1</title><fix system=\"urn:xccdf:fix:script:sh\">{}</fix></Rule>\
+             <Rule id=\"r2\"><title>R2</title></Rule>\
+             <Group id=\"g\"><title>G</title><Rule id=\"r3\"><title>R3</title></Rule></Group></Benchmark>",
+            XCCDF12_NS, fix
+        )
+        .parse()
+        .unwrap();
+        Benchmark::from_xml(&el).unwrap()
+    }
+
+    #[test]
+    fn test_generate_fix() {
+        let benchmark = benchmark("\nmax=<sub idref=\"v\"/>\n");
+        let profile = benchmark.resolve_profile("p").unwrap();
+        let script = generate_fix(&benchmark, &profile, FixSystem::Bash).unwrap();
+        assert!(script.starts_with("#!/usr/bin/env bash\n"));
+        assert!(script.contains("# BEGIN fix (1 / 2) for 'r1'\n"));
+        assert!(script.contains("\nmax=10\n# END fix for 'r1'\n"));
+        assert!(script.contains("FIX FOR THIS RULE 'r3' IS MISSING"));
+        assert!(!script.contains("'r2'"));
+    }
+
+    #[test]
+    fn test_generate_fix_unresolved() {
+        let benchmark = benchmark("max=<sub idref=\"unknown\"/>");
+        let profile = benchmark.resolve_profile("p").unwrap();
+        let error = generate_fix(&benchmark, &profile, FixSystem::Bash).unwrap_err();
+        assert!(error.contains("r1: Unresolved references in fix: value 'unknown'"));
+    }
+}
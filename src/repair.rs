@@ -0,0 +1,306 @@
+use minidom::Element;
+use minidom::Node;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::sds::SCAP12_NS;
+use crate::utils::*;
+use crate::xccdf::{KNOWN_IDENT_SYSTEMS, XCCDF12_NS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairKind {
+    MissingStatus,
+    DuplicateVersion,
+    EmptyTitle,
+    IdentSystemCasing,
+    MissingTimestamp,
+}
+
+impl RepairKind {
+    pub const ALL: [RepairKind; 5] = [
+        RepairKind::MissingStatus,
+        RepairKind::DuplicateVersion,
+        RepairKind::EmptyTitle,
+        RepairKind::IdentSystemCasing,
+        RepairKind::MissingTimestamp,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RepairKind::MissingStatus => "missing-status",
+            RepairKind::DuplicateVersion => "duplicate-version",
+            RepairKind::EmptyTitle => "empty-title",
+            RepairKind::IdentSystemCasing => "ident-system-casing",
+            RepairKind::MissingTimestamp => "missing-timestamp",
+        }
+    }
+}
+
+impl fmt::Display for RepairKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for RepairKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<RepairKind, String> {
+        match RepairKind::ALL.iter().find(|kind| kind.name() == s) {
+            Some(kind) => Ok(*kind),
+            None => Err(format!("Unknown repair '{}'", s)),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RepairOptions {
+    pub disabled: Vec<RepairKind>,
+}
+
+impl RepairOptions {
+    pub fn is_enabled(&self, kind: RepairKind) -> bool {
+        !self.disabled.contains(&kind)
+    }
+}
+
+#[derive(Debug)]
+pub struct Repair {
+    pub kind: RepairKind,
+    pub message: String,
+}
+
+struct Repairer<'a> {
+    options: &'a RepairOptions,
+    repairs: Vec<Repair>,
+}
+
+impl<'a> Repairer<'a> {
+    fn log(&mut self, kind: RepairKind, message: String) {
+        self.repairs.push(Repair { kind, message });
+    }
+
+    fn repair_collection(&mut self, root: &mut Element) {
+        for child in root.children_mut() {
+            if child.is("component", SCAP12_NS) || child.is("extended-component", SCAP12_NS) {
+                self.repair_component_timestamp(child);
+            }
+            if child.is("component", SCAP12_NS) {
+                for content in child.children_mut() {
+                    if content.is("Benchmark", XCCDF12_NS) {
+                        self.repair_benchmark(content);
+                    }
+                }
+            }
+        }
+    }
+
+    fn repair_component_timestamp(&mut self, component: &mut Element) {
+        if !self.options.is_enabled(RepairKind::MissingTimestamp)
+            || component.attr("timestamp").is_some()
+        {
+            return;
+        }
+        let timestamp = current_timestamp();
+        let id = component.attr("id").unwrap_or("").to_string();
+        component.set_attr("timestamp", timestamp.clone());
+        self.log(
+            RepairKind::MissingTimestamp,
+            format!(
+                "{} '{}': set timestamp to {}",
+                component.name(),
+                id,
+                timestamp
+            ),
+        );
+    }
+
+    fn repair_benchmark(&mut self, benchmark: &mut Element) {
+        if self.options.is_enabled(RepairKind::MissingStatus)
+            && !benchmark.has_child("status", XCCDF12_NS)
+        {
+            let status = Element::builder("status", XCCDF12_NS)
+                .append("draft")
+                .build();
+            let index = first_element_position(benchmark);
+            insert_child_at(benchmark, index, status);
+            self.log(
+                RepairKind::MissingStatus,
+                format!(
+                    "Benchmark '{}': added status 'draft'",
+                    benchmark.attr("id").unwrap_or("")
+                ),
+            );
+        }
+        self.repair_item(benchmark);
+    }
+
+    fn repair_item(&mut self, item: &mut Element) {
+        let id = item.attr("id").unwrap_or("").to_string();
+        if self.options.is_enabled(RepairKind::DuplicateVersion) {
+            self.repair_duplicate_versions(item, &id);
+        }
+        let options = self.options;
+        for child in item.children_mut() {
+            if !child.has_ns(XCCDF12_NS) {
+                continue;
+            }
+            match child.name() {
+                "title"
+                    if options.is_enabled(RepairKind::EmptyTitle)
+                        && child.text().trim().is_empty() =>
+                {
+                    set_text(child, &id);
+                    self.log(
+                        RepairKind::EmptyTitle,
+                        format!("{}: replaced empty title with the item ID", id),
+                    );
+                }
+                "ident" if options.is_enabled(RepairKind::IdentSystemCasing) => {
+                    let system = child.attr("system").unwrap_or("").to_string();
+                    if let Some((known, _)) = KNOWN_IDENT_SYSTEMS
+                        .iter()
+                        .find(|(k, _)| k.eq_ignore_ascii_case(&system) && *k != system)
+                    {
+                        child.set_attr("system", *known);
+                        self.log(
+                            RepairKind::IdentSystemCasing,
+                            format!("{}: changed ident system '{}' to '{}'", id, system, known),
+                        );
+                    }
+                }
+                "Profile" | "Value" | "Group" | "Rule" => self.repair_item(child),
+                _ => (),
+            }
+        }
+    }
+
+    fn repair_duplicate_versions(&mut self, item: &mut Element, id: &str) {
+        let versions: Vec<&Element> = item
+            .children()
+            .filter(|c| c.is("version", XCCDF12_NS))
+            .collect();
+        if versions.len() < 2 {
+            return;
+        }
+        // The latest version is the one with the newest time stamp, or the
+        // last one in the document order if the time stamps don't decide.
+        // Versions without a valid time stamp come before the others.
+        let time = |version: &Element| version.attr("time").and_then(|t| parse_date_time(t).ok());
+        let mut latest = versions[0];
+        for version in versions.iter().skip(1) {
+            if time(version) >= time(latest) {
+                latest = version;
+            }
+        }
+        let latest = latest.clone();
+        let count = versions.len();
+        let index = item
+            .nodes()
+            .position(|n| matches!(n, Node::Element(e) if e.is("version", XCCDF12_NS)))
+            .unwrap_or(0);
+        while item.remove_child("version", XCCDF12_NS).is_some() {}
+        let text = latest.text();
+        insert_child_at(item, index, latest);
+        self.log(
+            RepairKind::DuplicateVersion,
+            format!(
+                "{}: removed {} duplicate version elements, kept '{}'",
+                id,
+                count - 1,
+                text
+            ),
+        );
+    }
+}
+
+fn first_element_position(el: &Element) -> usize {
+    el.nodes()
+        .position(|n| matches!(n, Node::Element(_)))
+        .unwrap_or(0)
+}
+
+fn set_text(el: &mut Element, text: &str) {
+    for node in el.texts_mut() {
+        node.clear();
+    }
+    el.append_text_node(text);
+}
+
+/// Fixes common conformance problems in a source data stream or a standalone
+/// XCCDF benchmark in place and returns the list of performed repairs.
+pub fn repair(root: &mut Element, options: &RepairOptions) -> Vec<Repair> {
+    let mut repairer = Repairer {
+        options,
+        repairs: Vec::new(),
+    };
+    if root.is("data-stream-collection", SCAP12_NS) {
+        repairer.repair_collection(root);
+    } else if root.is("Benchmark", XCCDF12_NS) {
+        repairer.repair_benchmark(root);
+    }
+    repairer.repairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn benchmark(body: &str) -> Element {
+        format!(
+            "<Benchmark xmlns=\"{}\" id=\"xccdf_org.example_benchmark_test\">{}</Benchmark>",
+            XCCDF12_NS, body
+        )
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_repair_status_and_versions() {
+        let mut el = benchmark(
+            "<version time=\"2021-01-01T00:00:00\">1</version><version time=\"2022-01-01T00:00:00\">2</version><version>0</version>",
+        );
+        let repairs = repair(&mut el, &RepairOptions::default());
+        assert_eq!(repairs.len(), 2);
+        let names: Vec<&str> = el.children().map(|c| c.name()).collect();
+        assert_eq!(names, vec!["status", "version"]);
+        assert_eq!(el.get_child("version", XCCDF12_NS).unwrap().text(), "2");
+    }
+
+    #[test]
+    fn test_repair_versions_in_time_zones() {
+        let mut el = benchmark(
+            "<status>draft</status><version time=\"2022-01-01T01:00:00+02:00\">1</version><version time=\"2021-12-31T23:30:00Z\">2</version><version time=\"yesterday\">3</version>",
+        );
+        assert_eq!(repair(&mut el, &RepairOptions::default()).len(), 1);
+        assert_eq!(el.get_child("version", XCCDF12_NS).unwrap().text(), "2");
+    }
+
+    #[test]
+    fn test_repair_disabled() {
+        let mut el = benchmark("<version>1</version>");
+        let options = RepairOptions {
+            disabled: vec![RepairKind::MissingStatus],
+        };
+        assert!(repair(&mut el, &options).is_empty());
+        assert!(!el.has_child("status", XCCDF12_NS));
+    }
+
+    #[test]
+    fn test_repair_rule_title_and_ident() {
+        let mut el = benchmark(
+            "<status>draft</status><version>1</version><Rule id=\"xccdf_org.example_rule_a\"><title>  </title><ident system=\"HTTP://CCE.MITRE.ORG\">CCE-1</ident></Rule>",
+        );
+        let repairs = repair(&mut el, &RepairOptions::default());
+        assert_eq!(repairs.len(), 2);
+        let rule = el.get_child("Rule", XCCDF12_NS).unwrap();
+        assert_eq!(
+            rule.get_child("title", XCCDF12_NS).unwrap().text(),
+            "xccdf_org.example_rule_a"
+        );
+        assert_eq!(
+            rule.get_child("ident", XCCDF12_NS).unwrap().attr("system"),
+            Some("http://cce.mitre.org")
+        );
+    }
+}
@@ -0,0 +1,617 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use crate::locale::{preferred_text, Locale};
+use crate::matrix::{select_rules, RuleSelection};
+use crate::output::{paint, Color};
+use crate::utils::format_date_time;
+use crate::xccdf::{Benchmark, Group, ResolvedProfile, Role, Rule, Severity, TestResult, Title};
+
+const SEVERITIES: [&str; 5] = ["high", "medium", "low", "info", "unknown"];
+const RESULTS: [&str; 9] = [
+    "pass",
+    "fail",
+    "error",
+    "unknown",
+    "notapplicable",
+    "notchecked",
+    "notselected",
+    "informational",
+    "fixed",
+];
+
+pub(crate) fn severity_color(severity: &str) -> Option<Color> {
+    match severity {
+        "critical" | "high" => Some(Color::Red),
+        "medium" => Some(Color::Yellow),
+        "low" => Some(Color::Blue),
+        _ => None,
+    }
+}
+
+fn result_color(result: &str) -> Option<Color> {
+    match result {
+        "pass" | "fixed" => Some(Color::Green),
+        "fail" => Some(Color::Red),
+        "error" | "unknown" => Some(Color::Yellow),
+        _ => Some(Color::Gray),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RuleRow {
+    pub id: String,
+    pub severity: Severity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    pub title: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScoreSummary {
+    pub system: String,
+    pub value: f64,
+    pub maximum: f64,
+}
+
+/// Footer printed at the end of the text reports and included in the
+/// structured output, so that both carry the same totals.
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub benchmark: String,
+    pub content_version: String,
+    /// Language of the benchmark, the default locale of the text output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// End time of the TestResult
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished: Option<String>,
+    pub total: usize,
+    pub severities: BTreeMap<String, usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub results: Option<BTreeMap<String, usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<ScoreSummary>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FactRow {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub fact_type: String,
+    pub value: String,
+}
+
+/// The TestResult as a whole, only in the structured output of the results
+/// summary, for ingestion by dashboards.
+#[derive(Debug, Serialize)]
+pub struct TestResultDump {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
+    pub targets: Vec<String>,
+    pub target_addresses: Vec<String>,
+    pub target_facts: Vec<FactRow>,
+    pub scores: Vec<ScoreSummary>,
+    /// Result of every rule by its ID
+    pub rule_results: BTreeMap<String, String>,
+}
+
+impl TestResultDump {
+    fn new(test_result: &TestResult) -> TestResultDump {
+        TestResultDump {
+            id: test_result.id.to_string(),
+            profile: test_result.profile.as_deref().map(String::from),
+            start_time: test_result.start_time.as_ref().map(format_date_time),
+            end_time: test_result.end_time.as_ref().map(format_date_time),
+            identity: test_result.identity.clone(),
+            targets: test_result.targets.clone(),
+            target_addresses: test_result.target_addresses.clone(),
+            target_facts: test_result
+                .target_facts
+                .iter()
+                .map(|f| FactRow {
+                    name: f.name.to_string(),
+                    fact_type: f.fact_type.to_string(),
+                    value: f.value.clone(),
+                })
+                .collect(),
+            scores: test_result
+                .scores
+                .iter()
+                .map(|s| ScoreSummary {
+                    system: s.system.to_string(),
+                    value: s.value,
+                    maximum: s.maximum,
+                })
+                .collect(),
+            rule_results: test_result
+                .rule_results
+                .iter()
+                .map(|r| (r.idref.to_string(), r.result.clone()))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub rules: Vec<RuleRow>,
+    pub summary: Summary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test_result: Option<TestResultDump>,
+}
+
+pub(crate) fn count<'a>(values: impl Iterator<Item = &'a str>) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for value in values {
+        *counts.entry(value.to_string()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Orders the counted keys by the given order, unknown keys go last.
+fn ordered<'a>(counts: &'a BTreeMap<String, usize>, order: &[&str]) -> Vec<(&'a str, usize)> {
+    let mut items: Vec<(&str, usize)> = counts.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+    items.sort_by_key(|(key, _)| order.iter().position(|o| o == key).unwrap_or(order.len()));
+    items
+}
+
+fn format_counts(
+    counts: &BTreeMap<String, usize>,
+    order: &[&str],
+    color: fn(&str) -> Option<Color>,
+    colored: bool,
+) -> String {
+    ordered(counts, order)
+        .iter()
+        .map(|(key, value)| format!("{} {}", paint(key, color(key), colored), value))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Counts of the severities from the highest, e.g. `high 2, low 1`.
+pub(crate) fn severity_counts(counts: &BTreeMap<String, usize>, colored: bool) -> String {
+    format_counts(counts, &SEVERITIES, severity_color, colored)
+}
+
+fn summary(
+    benchmark: &Benchmark,
+    rules: &[RuleRow],
+    test_result: Option<&TestResult>,
+    lang: Option<&str>,
+) -> Summary {
+    Summary {
+        benchmark: benchmark.id.to_string(),
+        content_version: benchmark.version.text.clone(),
+        language: lang.map(String::from),
+        finished: test_result
+            .and_then(|t| t.end_time.as_ref())
+            .map(format_date_time),
+        total: rules.len(),
+        severities: count(rules.iter().map(|r| r.severity.as_str())),
+        results: test_result.map(|_| count(rules.iter().filter_map(|r| r.result.as_deref()))),
+        score: test_result
+            .and_then(|t| t.scores.first())
+            .map(|s| ScoreSummary {
+                system: s.system.to_string(),
+                value: s.value,
+                maximum: s.maximum,
+            }),
+    }
+}
+
+/// Title in the preferred language, empty if there is none.
+fn title(titles: &[Title], lang: Option<&str>) -> String {
+    preferred_text(titles, lang)
+        .map(|t| t.title.clone())
+        .unwrap_or_default()
+}
+
+/// Lists the rules of the benchmark included by the selection, with a
+/// profile only the rules it selects. The titles are in the preferred
+/// language, by default the language of the benchmark.
+pub fn list_rules(
+    benchmark: &Benchmark,
+    profile: Option<&ResolvedProfile>,
+    selection: &RuleSelection,
+    lang: Option<&str>,
+) -> Report {
+    let lang = lang.or(benchmark.lang());
+    let rules = match profile {
+        Some(profile) => {
+            let selection = RuleSelection {
+                selected_only: true,
+                ..selection.clone()
+            };
+            select_rules(benchmark, Some(profile), &selection)
+        }
+        None => select_rules(benchmark, None, selection),
+    };
+    let rows: Vec<RuleRow> = rules
+        .iter()
+        .map(|rule| RuleRow {
+            id: rule.id.to_string(),
+            severity: profile.map_or(rule.severity, |p| p.severity(rule)),
+            result: None,
+            title: title(&rule.titles, lang),
+        })
+        .collect();
+    let summary = summary(benchmark, &rows, None, lang);
+    Report {
+        rules: rows,
+        summary,
+        test_result: None,
+    }
+}
+
+/// Summarizes the rule results of a TestResult, by default the last one,
+/// with the titles in the preferred language.
+pub fn results_summary(
+    benchmark: &Benchmark,
+    test_result_id: Option<&str>,
+    lang: Option<&str>,
+) -> Result<Report, String> {
+    let lang = lang.or(benchmark.lang());
+    let test_result = benchmark.test_result(test_result_id)?;
+    let rows: Vec<RuleRow> = test_result
+        .rule_results
+        .iter()
+        .map(|rule_result| {
+            let rule = benchmark.rule(&rule_result.idref);
+            RuleRow {
+                id: rule_result.idref.to_string(),
+                severity: rule.map(|r| r.severity).unwrap_or(Severity::Unknown),
+                result: Some(rule_result.result.clone()),
+                title: rule.map(|r| title(&r.titles, lang)).unwrap_or_default(),
+            }
+        })
+        .collect();
+    let summary = summary(benchmark, &rows, Some(test_result), lang);
+    Ok(Report {
+        rules: rows,
+        summary,
+        test_result: Some(TestResultDump::new(test_result)),
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct EffectiveRule {
+    pub id: String,
+    pub severity: Severity,
+    pub role: Role,
+    pub title: String,
+    /// Evaluated, but left out of the generated documents
+    pub hidden: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EffectiveValue {
+    pub id: String,
+    pub value: String,
+    /// Set or refined by the profile rather than the Value's default
+    pub from_profile: bool,
+}
+
+/// Rules evaluated with a profile and the values used by them.
+#[derive(Debug, Serialize)]
+pub struct EffectiveProfile {
+    pub id: String,
+    pub title: String,
+    pub rules: Vec<EffectiveRule>,
+    pub values: Vec<EffectiveValue>,
+}
+
+impl EffectiveProfile {
+    pub fn write_text<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "Profile ID: {}", self.id)?;
+        writeln!(out, "Title: {}", self.title)?;
+        writeln!(out, "Evaluated rules: {}", self.rules.len())?;
+        for rule in self.rules.iter() {
+            let mut notes = Vec::new();
+            if rule.role != Role::Full {
+                notes.push(rule.role.as_str());
+            }
+            if rule.hidden {
+                notes.push("hidden");
+            }
+            let notes = match notes.is_empty() {
+                true => String::new(),
+                false => format!(" ({})", notes.join(", ")),
+            };
+            writeln!(
+                out,
+                "{:<7} {} {}{}",
+                rule.severity.as_str(),
+                rule.id,
+                rule.title,
+                notes
+            )?;
+        }
+        writeln!(out, "Values: {}", self.values.len())?;
+        for value in self.values.iter() {
+            let mark = if value.from_profile { " (profile)" } else { "" };
+            writeln!(out, "{} = {}{}", value.id, value.value, mark)?;
+        }
+        Ok(())
+    }
+}
+
+/// Walks the group tree like [`Benchmark::selected_rules`], skipping the
+/// abstract groups and rules, which are only bases for extension.
+fn effective_rules<'a>(
+    groups: &'a [Group],
+    rules: &'a [Rule],
+    profile: &ResolvedProfile,
+    effective: &mut Vec<&'a Rule>,
+) {
+    for rule in rules.iter().filter(|r| !r.abstract_) {
+        if profile.is_selected(&rule.id, rule.cluster_id.as_deref(), rule.selected)
+            && profile.role(rule) != Role::Unchecked
+        {
+            effective.push(rule);
+        }
+    }
+    for group in groups.iter().filter(|g| !g.abstract_) {
+        if profile.is_selected(&group.id, group.cluster_id.as_deref(), group.selected) {
+            effective_rules(&group.groups, &group.rules, profile, effective);
+        }
+    }
+}
+
+/// Applies the selections and refinements of the profile over the group
+/// tree. Rules with the `unchecked` role are left out as they aren't
+/// evaluated. The titles are in the preferred language.
+pub fn effective_profile(
+    benchmark: &Benchmark,
+    profile: &ResolvedProfile,
+    lang: Option<&str>,
+) -> EffectiveProfile {
+    let lang = lang.or(benchmark.lang());
+    let mut rules = Vec::new();
+    effective_rules(&benchmark.groups, &benchmark.rules, profile, &mut rules);
+    let rules = rules
+        .into_iter()
+        .map(|rule| EffectiveRule {
+            id: rule.id.to_string(),
+            severity: profile.severity(rule),
+            role: profile.role(rule),
+            title: title(&rule.titles, lang),
+            hidden: rule.hidden,
+        })
+        .collect();
+    let values = benchmark
+        .iter_values()
+        .filter_map(|nested| {
+            let id = &nested.item.id;
+            let value = benchmark.value_in_profile(id, profile).or_else(|| {
+                benchmark
+                    .complex_value_in_profile(id, profile)
+                    .map(|items| format!("[{}]", items.join(", ")))
+            })?;
+            let from_profile = profile.set_values.iter().any(|v| v.idref == *id)
+                || profile.set_complex_values.iter().any(|v| v.idref == *id)
+                || profile.refine_values.iter().any(|v| v.idref == *id);
+            Some(EffectiveValue {
+                id: id.to_string(),
+                value,
+                from_profile,
+            })
+        })
+        .collect();
+    EffectiveProfile {
+        id: profile.id().to_string(),
+        title: preferred_text(&profile.titles, lang)
+            .map(|t| t.title.clone())
+            .unwrap_or_default(),
+        rules,
+        values,
+    }
+}
+
+impl Summary {
+    pub fn write_text<W: Write>(
+        &self,
+        out: &mut W,
+        colored: bool,
+        locale: &Locale,
+    ) -> io::Result<()> {
+        writeln!(out, "{}", "-".repeat(79))?;
+        writeln!(out, "Rules: {}", self.total)?;
+        writeln!(
+            out,
+            "Severity: {}",
+            format_counts(&self.severities, &SEVERITIES, severity_color, colored)
+        )?;
+        if let Some(results) = &self.results {
+            let passed = results.get("pass").copied().unwrap_or(0)
+                + results.get("fixed").copied().unwrap_or(0);
+            writeln!(
+                out,
+                "Results: {} ({} passed)",
+                format_counts(results, &RESULTS, result_color, colored),
+                locale.format_percent(passed as f64 / self.total.max(1) as f64)
+            )?;
+        }
+        if let Some(score) = &self.score {
+            writeln!(
+                out,
+                "Score: {} / {} ({})",
+                locale.format_number(score.value, 2),
+                locale.format_number(score.maximum, 2),
+                score.system
+            )?;
+        }
+        if let Some(finished) = &self.finished {
+            writeln!(out, "Finished: {}", locale.format_date(finished))?;
+        }
+        writeln!(
+            out,
+            "Content: {} version {}",
+            self.benchmark, self.content_version
+        )?;
+        Ok(())
+    }
+}
+
+impl Report {
+    pub fn write_text<W: Write>(
+        &self,
+        out: &mut W,
+        colored: bool,
+        locale: &Locale,
+    ) -> io::Result<()> {
+        for row in self.rules.iter() {
+            let severity = paint(
+                &format!("{:<7}", row.severity.as_str()),
+                severity_color(row.severity.as_str()),
+                colored,
+            );
+            match &row.result {
+                Some(result) => {
+                    let result = paint(&format!("{:<13}", result), result_color(result), colored);
+                    let title = locale.isolate(&row.title);
+                    writeln!(out, "{} {} {} {}", result, severity, row.id, title)?;
+                }
+                None => writeln!(
+                    out,
+                    "{} {} {}",
+                    severity,
+                    row.id,
+                    locale.isolate(&row.title)
+                )?,
+            }
+        }
+        if colored {
+            write_legend(out, self.summary.results.is_some())?;
+        }
+        self.summary.write_text(out, colored, locale)?;
+        Ok(())
+    }
+}
+
+fn write_legend<W: Write>(out: &mut W, with_results: bool) -> io::Result<()> {
+    let severities: Vec<String> = SEVERITIES
+        .iter()
+        .map(|s| paint(s, severity_color(s), true))
+        .collect();
+    writeln!(out, "Legend: severity {}", severities.join(" "))?;
+    if with_results {
+        let results: Vec<String> = RESULTS
+            .iter()
+            .map(|r| paint(r, result_color(r), true))
+            .collect();
+        writeln!(out, "        result {}", results.join(" "))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xccdf::XCCDF12_NS;
+    use minidom::Element;
+
+    fn benchmark() -> Benchmark {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1.5</version>\
+             <Rule id=\"r1\" severity=\"high\"><title>R1</title></Rule>\
+             <Group id=\"g\"><title>G</title><Rule id=\"r2\" severity=\"low\"><title>R2</title></Rule>\
+             <Rule id=\"r3\" severity=\"high\"><title>R3</title></Rule></Group>\
+             <TestResult id=\"t\" end-time=\"2022-01-01T00:00:00\">\
+             <target>host</target><target-facts>\
+             <fact name=\"urn:xccdf:fact:asset:identifier:fqdn\" type=\"string\">host.example.com</fact>\
+             </target-facts>\
+             <rule-result idref=\"r1\"><result>fail</result></rule-result>\
+             <rule-result idref=\"r2\"><result>pass</result></rule-result>\
+             <rule-result idref=\"r3\"><result>pass</result></rule-result>\
+             <score system=\"urn:xccdf:scoring:default\" maximum=\"100\">66.67</score></TestResult>\
+             </Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        Benchmark::from_xml(&el).unwrap()
+    }
+
+    #[test]
+    fn test_list_rules_summary() {
+        let report = list_rules(&benchmark(), None, &RuleSelection::default(), None);
+        let ids: Vec<&str> = report.rules.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["r1", "r2", "r3"]);
+        assert_eq!(report.summary.severities["high"], 2);
+        assert_eq!(report.summary.content_version, "1.5");
+        let json = serde_json::to_value(&report).unwrap();
+        assert!(json["summary"].get("results").is_none());
+        assert!(json.get("test_result").is_none());
+    }
+
+    #[test]
+    fn test_results_summary() {
+        let report = results_summary(&benchmark(), None, None).unwrap();
+        let results = report.summary.results.as_ref().unwrap();
+        assert_eq!((results["pass"], results["fail"]), (2, 1));
+        assert_eq!(report.summary.score.as_ref().unwrap().value, 66.67);
+        assert!(results_summary(&benchmark(), Some("missing"), None).is_err());
+    }
+
+    #[test]
+    fn test_effective_profile() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Profile id=\"p\"><title>P</title><select idref=\"g\" selected=\"true\"/>\
+             <refine-rule idref=\"r2\" severity=\"high\"/>\
+             <refine-rule idref=\"r3\" role=\"unchecked\"/>\
+             <refine-value idref=\"v\" selector=\"strict\"/></Profile>\
+             <Value id=\"v\"><value>027</value><value selector=\"strict\">077</value></Value>\
+             <Value id=\"w\"><value>1</value></Value>\
+             <Rule id=\"r1\" abstract=\"true\"><title>Base</title></Rule>\
+             <Group id=\"g\" selected=\"false\"><title>G</title>\
+             <Rule id=\"r2\" hidden=\"true\"><title>R2</title></Rule>\
+             <Rule id=\"r3\"><title>R3</title></Rule>\
+             <Group id=\"h\" abstract=\"true\"><title>H</title><Rule id=\"r4\"><title>R4</title></Rule></Group>\
+             </Group></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let profile = benchmark.resolve_profile("p").unwrap();
+        let effective = effective_profile(&benchmark, &profile, None);
+        let rules: Vec<(&str, Severity, bool)> = effective
+            .rules
+            .iter()
+            .map(|r| (r.id.as_str(), r.severity, r.hidden))
+            .collect();
+        assert_eq!(rules, vec![("r2", Severity::High, true)]);
+        let values: Vec<(&str, &str, bool)> = effective
+            .values
+            .iter()
+            .map(|v| (v.id.as_str(), v.value.as_str(), v.from_profile))
+            .collect();
+        assert_eq!(values, vec![("v", "077", true), ("w", "1", false)]);
+    }
+
+    #[test]
+    fn test_results_json() {
+        let report = results_summary(&benchmark(), None, None).unwrap();
+        let json = serde_json::to_value(&report).unwrap();
+        let test_result = &json["test_result"];
+        assert_eq!(test_result["id"], "t");
+        assert_eq!(test_result["end_time"], "2022-01-01T00:00:00Z");
+        assert_eq!(test_result["targets"][0], "host");
+        assert_eq!(test_result["target_facts"][0]["type"], "string");
+        assert_eq!(test_result["target_facts"][0]["value"], "host.example.com");
+        assert_eq!(test_result["scores"][0]["value"], 66.67);
+        assert_eq!(test_result["rule_results"]["r1"], "fail");
+        assert_eq!(test_result["rule_results"]["r3"], "pass");
+        assert!(test_result.get("start_time").is_none());
+    }
+}
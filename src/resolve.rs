@@ -0,0 +1,435 @@
+use minidom::Element;
+use std::collections::HashMap;
+
+use crate::sds::SCAP12_NS;
+use crate::utils::*;
+use crate::xccdf::XCCDF12_NS;
+
+const ITEMS: [&str; 4] = ["Rule", "Group", "Value", "Profile"];
+
+/// Properties whose values are added to the inherited ones. The other
+/// properties are inherited only if the extending item doesn't define them,
+/// which for texts is decided per `xml:lang`.
+const APPENDED: [&str; 16] = [
+    "reference",
+    "ident",
+    "platform",
+    "warning",
+    "requires",
+    "conflicts",
+    "fix",
+    "fixtext",
+    "profile-note",
+    "metadata",
+    "impact-metric",
+    "select",
+    "set-value",
+    "set-complex-value",
+    "refine-value",
+    "refine-rule",
+];
+
+/// Attributes that belong to the item itself and are never inherited.
+const NOT_INHERITED_ATTRS: [&str; 4] = ["id", "abstract", "extends", "Id"];
+
+fn is_item(el: &Element) -> bool {
+    el.has_ns(XCCDF12_NS) && ITEMS.contains(&el.name())
+}
+
+fn is_abstract(el: &Element) -> bool {
+    matches!(el.attr("abstract"), Some("true") | Some("1"))
+}
+
+fn collect_items(el: &Element, items: &mut HashMap<String, Element>) {
+    for child in el.children() {
+        if is_item(child) {
+            if let Some(id) = child.attr("id") {
+                items.insert(id.to_string(), child.clone());
+            }
+            if child.name() == "Group" {
+                collect_items(child, items);
+            }
+        }
+    }
+}
+
+/// Order of the property names in the merged item: the order of the
+/// extending item, with the names only the base item has inserted after
+/// the name they follow in the base item.
+fn property_order<'a>(base: &'a Element, derived: &'a Element) -> Vec<&'a str> {
+    let mut order: Vec<&str> = Vec::new();
+    for child in derived.children() {
+        if !order.contains(&child.name()) {
+            order.push(child.name());
+        }
+    }
+    let mut previous: Option<&str> = None;
+    for child in base.children() {
+        let name = child.name();
+        if !order.contains(&name) {
+            let index = match previous {
+                Some(previous) => order.iter().position(|n| *n == previous).unwrap() + 1,
+                None => 0,
+            };
+            order.insert(index, name);
+        }
+        previous = Some(name);
+    }
+    order
+}
+
+/// Merges the properties of `base` into `derived`, which extends it.
+fn merge(base: &Element, derived: &Element) -> Element {
+    let mut builder = Element::builder(derived.name(), derived.ns());
+    for (name, value) in derived.attrs() {
+        if name != "extends" {
+            builder = builder.attr(name, value);
+        }
+    }
+    for (name, value) in base.attrs() {
+        if derived.attr(name).is_none() && !NOT_INHERITED_ATTRS.contains(&name) {
+            builder = builder.attr(name, value);
+        }
+    }
+    let mut children = Vec::new();
+    for name in property_order(base, derived) {
+        let base_children = base.children().filter(|c| c.name() == name);
+        let derived_children: Vec<&Element> =
+            derived.children().filter(|c| c.name() == name).collect();
+        if ITEMS.contains(&name) || name == "signature" {
+            // child items and signatures belong only to the extending item
+            children.extend(derived_children.into_iter().cloned());
+        } else if APPENDED.contains(&name) {
+            children.extend(base_children.cloned());
+            children.extend(derived_children.into_iter().cloned());
+        } else {
+            let langs: Vec<Option<&str>> = derived_children
+                .iter()
+                .map(|c| c.attr("xml:lang"))
+                .collect();
+            children.extend(
+                base_children
+                    .filter(|c| !langs.contains(&c.attr("xml:lang")))
+                    .cloned(),
+            );
+            children.extend(derived_children.into_iter().cloned());
+        }
+    }
+    builder.append_all(children).build()
+}
+
+struct Resolver {
+    items: HashMap<String, Element>,
+    resolved: HashMap<String, Element>,
+}
+
+impl Resolver {
+    fn resolve_item(&mut self, id: &str, stack: &mut Vec<String>) -> Result<Element, String> {
+        if let Some(el) = self.resolved.get(id) {
+            return Ok(el.clone());
+        }
+        if stack.iter().any(|s| s == id) {
+            return Err(format!("Item '{}' is part of an extends loop", id));
+        }
+        let el = match self.items.get(id) {
+            Some(el) => el.clone(),
+            None => return Err(format!("Item '{}' not found", id)),
+        };
+        let result = match el.attr("extends") {
+            Some(base_id) => {
+                stack.push(id.to_string());
+                let base = self.resolve_item(base_id, stack)?;
+                stack.pop();
+                if base.name() != el.name() {
+                    return Err(format!(
+                        "{} '{}' can't extend {} '{}'",
+                        el.name(),
+                        id,
+                        base.name(),
+                        base_id
+                    ));
+                }
+                merge(&base, &el)
+            }
+            None => el,
+        };
+        self.resolved.insert(id.to_string(), result.clone());
+        Ok(result)
+    }
+
+    fn resolve_children(&mut self, el: &mut Element) -> Result<(), String> {
+        for child in el.children_mut() {
+            if !is_item(child) {
+                continue;
+            }
+            if child.attr("extends").is_some() {
+                let id = require_attr(child, "id")?;
+                *child = self.resolve_item(&id, &mut Vec::new())?;
+            }
+            if child.name() == "Group" {
+                self.resolve_children(child)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Properties of a Value chosen by the `selector` of a refine-value.
+const SELECTABLE: [&str; 8] = [
+    "value",
+    "default",
+    "lower-bound",
+    "upper-bound",
+    "match",
+    "choices",
+    "complex-value",
+    "complex-default",
+];
+
+fn without_attr(el: &Element, attr: &str) -> Element {
+    let mut builder = Element::builder(el.name(), el.ns());
+    for (name, value) in el.attrs().filter(|(name, _)| *name != attr) {
+        builder = builder.attr(name, value);
+    }
+    builder.append_all(el.nodes().cloned()).build()
+}
+
+/// Keeps only the `names` children chosen by the selector: the ones with a
+/// matching `selector`, or the ones without a selector if none matches.
+/// The chosen children lose their `selector` and become the defaults.
+fn apply_selector(el: &mut Element, names: &[&str], selector: &str) {
+    for name in names {
+        let matching = el
+            .children()
+            .any(|c| c.name() == *name && c.attr("selector") == Some(selector));
+        let chosen = |c: &Element| match matching {
+            true => c.attr("selector") == Some(selector),
+            false => c.attr("selector").is_none(),
+        };
+        retain_children(el, |c| c.name() != *name || chosen(c));
+        for child in el.children_mut().filter(|c| c.name() == *name) {
+            if child.attr("selector").is_some() {
+                *child = without_attr(child, "selector");
+            }
+        }
+    }
+}
+
+/// Refinements of a profile by the item ID they refer to, later
+/// refinements override the earlier ones of the profiles it extends.
+fn refinements<'a>(profile: &'a Element, name: &str) -> HashMap<&'a str, &'a Element> {
+    profile
+        .children()
+        .filter(|c| c.is(name, XCCDF12_NS))
+        .filter_map(|c| c.attr("idref").map(|idref| (idref, c)))
+        .collect()
+}
+
+/// Applies the refine-rule and refine-value of a profile to the items: the
+/// item's own refinement wins over one of its cluster.
+fn apply_refinements(
+    el: &mut Element,
+    refine_rules: &HashMap<&str, &Element>,
+    refine_values: &HashMap<&str, &Element>,
+) {
+    for child in el.children_mut() {
+        if !is_item(child) || child.name() == "Profile" {
+            continue;
+        }
+        let refinements = match child.name() {
+            "Value" => refine_values,
+            _ => refine_rules,
+        };
+        let refinement = [child.attr("id"), child.attr("cluster-id")]
+            .into_iter()
+            .flatten()
+            .find_map(|id| refinements.get(id).copied());
+        if let Some(refinement) = refinement {
+            let attrs: &[&str] = match child.name() {
+                "Rule" => &["severity", "role", "weight"],
+                "Group" => &["weight"],
+                _ => &["operator"],
+            };
+            for attr in attrs {
+                if let Some(value) = refinement.attr(attr) {
+                    child.set_attr(*attr, value);
+                }
+            }
+            if let Some(selector) = refinement.attr("selector") {
+                match child.name() {
+                    "Rule" => apply_selector(child, &["check"], selector),
+                    "Value" => apply_selector(child, &SELECTABLE, selector),
+                    _ => (),
+                }
+            }
+        }
+        if child.name() == "Group" {
+            apply_refinements(child, refine_rules, refine_values);
+        }
+    }
+}
+
+fn prune_abstract(el: &mut Element) {
+    retain_children(el, |child| !(is_item(child) && is_abstract(child)));
+    for child in el.children_mut() {
+        if child.is("Group", XCCDF12_NS) {
+            prune_abstract(child);
+        }
+    }
+}
+
+/// Transforms a benchmark into its resolved form as described by the XCCDF
+/// loading model: items are merged with the items they extend, the profiles
+/// inherit the selectors and refinements of their parents, abstract items
+/// are removed and the `resolved` attribute is set. Given a profile, its
+/// refine-rule and refine-value are applied to the rules and values.
+pub fn resolve_benchmark(benchmark: &mut Element, profile: Option<&str>) -> Result<(), String> {
+    if !benchmark.is("Benchmark", XCCDF12_NS) {
+        return Err(format!(
+            "Unexpected element '{}', expected xccdf:Benchmark",
+            benchmark.name()
+        ));
+    }
+    let mut items = HashMap::new();
+    collect_items(benchmark, &mut items);
+    let mut resolver = Resolver {
+        items,
+        resolved: HashMap::new(),
+    };
+    resolver.resolve_children(benchmark)?;
+    if let Some(profile_id) = profile {
+        let profile = benchmark
+            .children()
+            .find(|c| c.is("Profile", XCCDF12_NS) && c.attr("id") == Some(profile_id))
+            .ok_or_else(|| format!("Profile '{}' not found", profile_id))?
+            .clone();
+        let refine_rules = refinements(&profile, "refine-rule");
+        let refine_values = refinements(&profile, "refine-value");
+        apply_refinements(benchmark, &refine_rules, &refine_values);
+    }
+    prune_abstract(benchmark);
+    benchmark.set_attr("resolved", "1");
+    Ok(())
+}
+
+/// Resolves a standalone benchmark or every benchmark of a source data stream.
+/// The refinements of the profile are applied to the benchmarks defining it.
+pub fn resolve(root: &mut Element, profile: Option<&str>) -> Result<(), String> {
+    if root.is("Benchmark", XCCDF12_NS) {
+        return resolve_benchmark(root, profile);
+    }
+    if !root.is("data-stream-collection", SCAP12_NS) {
+        return Err(format!(
+            "Unexpected element '{}', expected a data stream collection or a benchmark",
+            root.name()
+        ));
+    }
+    let mut found_profile = false;
+    for component in root.children_mut() {
+        if component.is("component", SCAP12_NS) {
+            for content in component.children_mut() {
+                if content.is("Benchmark", XCCDF12_NS) {
+                    let defines_profile = content
+                        .children()
+                        .any(|c| c.is("Profile", XCCDF12_NS) && c.attr("id") == profile);
+                    resolve_benchmark(content, profile.filter(|_| defines_profile))?;
+                    found_profile |= defines_profile;
+                }
+            }
+        }
+    }
+    match profile {
+        Some(profile) if !found_profile => Err(format!("Profile '{}' not found", profile)),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn benchmark(body: &str) -> Element {
+        format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>{}</Benchmark>",
+            XCCDF12_NS, body
+        )
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_rule_extends() {
+        let mut el = benchmark(
+            "<Rule id=\"base\" abstract=\"true\" severity=\"high\"><title>Base</title>\
+             <ident system=\"s\">1</ident><check system=\"c\"/></Rule>\
+             <Group id=\"g\"><title>G</title><Rule id=\"r\" extends=\"base\"><title>Rule</title>\
+             <ident system=\"s\">2</ident></Rule></Group>",
+        );
+        resolve(&mut el, None).unwrap();
+        assert_eq!(el.attr("resolved"), Some("1"));
+        assert!(el.get_child("Rule", XCCDF12_NS).is_none());
+        let rule = el
+            .get_child("Group", XCCDF12_NS)
+            .and_then(|g| g.get_child("Rule", XCCDF12_NS))
+            .unwrap();
+        assert_eq!(rule.attr("extends"), None);
+        assert_eq!(rule.attr("severity"), Some("high"));
+        assert_eq!(rule.attr("abstract"), None);
+        let names: Vec<&str> = rule.children().map(|c| c.name()).collect();
+        assert_eq!(names, vec!["title", "ident", "ident", "check"]);
+        assert_eq!(rule.get_child("title", XCCDF12_NS).unwrap().text(), "Rule");
+    }
+
+    #[test]
+    fn test_resolve_profile_and_errors() {
+        let mut el = benchmark(
+            "<Profile id=\"p1\"><title>P1</title><select idref=\"r\" selected=\"true\"/></Profile>\
+             <Profile id=\"p2\" extends=\"p1\"><title>P2</title><refine-value idref=\"v\" selector=\"s\"/></Profile>",
+        );
+        resolve(&mut el, None).unwrap();
+        let p2 = el.children().find(|c| c.attr("id") == Some("p2")).unwrap();
+        let names: Vec<&str> = p2.children().map(|c| c.name()).collect();
+        assert_eq!(names, vec!["title", "select", "refine-value"]);
+
+        let mut looped = benchmark(
+            "<Value id=\"a\" extends=\"b\"><title>A</title></Value>\
+             <Value id=\"b\" extends=\"a\"><title>B</title></Value>",
+        );
+        assert!(resolve(&mut looped, None).is_err());
+        let mut mismatched =
+            benchmark("<Value id=\"a\"><title>A</title></Value><Rule id=\"r\" extends=\"a\"/>");
+        assert!(resolve(&mut mismatched, None).is_err());
+    }
+
+    #[test]
+    fn test_resolve_refinements() {
+        let mut el = benchmark(
+            "<Profile id=\"base\"><title>Base</title>\
+             <refine-rule idref=\"r\" severity=\"high\" selector=\"oval\"/></Profile>\
+             <Profile id=\"p\" extends=\"base\"><title>P</title>\
+             <refine-rule idref=\"c\" severity=\"low\" weight=\"2\"/>\
+             <refine-value idref=\"v\" selector=\"strict\" operator=\"greater than\"/></Profile>\
+             <Value id=\"v\" type=\"number\"><title>V</title><value>8</value>\
+             <value selector=\"strict\">12</value><default>8</default></Value>\
+             <Rule id=\"r\" severity=\"medium\" cluster-id=\"c\"><title>R</title>\
+             <check system=\"sce\"/><check system=\"oval\" selector=\"oval\"/></Rule>\
+             <Rule id=\"r2\" cluster-id=\"c\"><title>R2</title></Rule>",
+        );
+        assert!(resolve(&mut el.clone(), Some("missing")).is_err());
+        resolve(&mut el, Some("p")).unwrap();
+        let item = |id: &str| el.children().find(|c| c.attr("id") == Some(id)).unwrap();
+        let rule = item("r");
+        assert_eq!(rule.attr("severity"), Some("high"));
+        assert_eq!(rule.attr("weight"), None);
+        let checks: Vec<&Element> = rule.children().filter(|c| c.name() == "check").collect();
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].attr("system"), Some("oval"));
+        assert_eq!(checks[0].attr("selector"), None);
+        assert_eq!(item("r2").attr("severity"), Some("low"));
+        assert_eq!(item("r2").attr("weight"), Some("2"));
+        let value = item("v");
+        assert_eq!(value.attr("operator"), Some("greater than"));
+        let values: Vec<String> = value.children().map(|c| c.text()).collect();
+        assert_eq!(values, vec!["V", "12", "8"]);
+    }
+}
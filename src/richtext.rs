@@ -0,0 +1,448 @@
+use crate::xccdf::XCCDF12_NS;
+use minidom::Element;
+
+/// Node of a formatted XCCDF text. The XHTML elements used in security
+/// content are recognized, other elements only contribute their content.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Text(String),
+    /// `xccdf:sub` reference to a plain-text or a Value, substituted when
+    /// the text is rendered
+    Sub(String),
+    Break,
+    Emphasis(Vec<Node>),
+    Strong(Vec<Node>),
+    Code(Vec<Node>),
+    Link {
+        href: String,
+        children: Vec<Node>,
+    },
+    Paragraph(Vec<Node>),
+    Preformatted(String),
+    List {
+        ordered: bool,
+        items: Vec<Vec<Node>>,
+    },
+    /// Rows of cells
+    Table(Vec<Vec<Vec<Node>>>),
+    Other(Vec<Node>),
+}
+
+/// Formatted text of a description, rationale or fix text.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RichText {
+    pub(crate) nodes: Vec<Node>,
+}
+
+/// All text inside the element, including the text of its descendants.
+fn all_text(el: &Element) -> String {
+    let mut text = String::new();
+    for node in el.nodes() {
+        match node {
+            minidom::Node::Text(t) => text.push_str(t),
+            minidom::Node::Element(child) => text.push_str(&all_text(child)),
+        }
+    }
+    text
+}
+
+fn descendants<'a>(el: &'a Element, names: &[&str], found: &mut Vec<&'a Element>) {
+    for child in el.children() {
+        if names.contains(&child.name()) {
+            found.push(child);
+        } else {
+            descendants(child, names, found);
+        }
+    }
+}
+
+fn parse_nodes(el: &Element) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    for node in el.nodes() {
+        let child = match node {
+            minidom::Node::Text(text) => {
+                nodes.push(Node::Text(text.clone()));
+                continue;
+            }
+            minidom::Node::Element(child) => child,
+        };
+        nodes.push(match child.name() {
+            "br" => Node::Break,
+            "sub" if child.has_ns(XCCDF12_NS) && child.attr("idref").is_some() => {
+                Node::Sub(child.attr("idref").unwrap_or_default().to_string())
+            }
+            "b" | "strong" => Node::Strong(parse_nodes(child)),
+            "i" | "em" => Node::Emphasis(parse_nodes(child)),
+            "code" | "tt" | "kbd" | "samp" | "var" => Node::Code(parse_nodes(child)),
+            "a" => Node::Link {
+                href: child.attr("href").unwrap_or_default().to_string(),
+                children: parse_nodes(child),
+            },
+            "p" | "div" | "blockquote" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                Node::Paragraph(parse_nodes(child))
+            }
+            "pre" => Node::Preformatted(all_text(child)),
+            "ul" | "ol" => Node::List {
+                ordered: child.name() == "ol",
+                items: child
+                    .children()
+                    .filter(|li| li.name() == "li")
+                    .map(parse_nodes)
+                    .collect(),
+            },
+            "table" => {
+                let mut rows = Vec::new();
+                descendants(child, &["tr"], &mut rows);
+                Node::Table(
+                    rows.iter()
+                        .map(|row| {
+                            let mut cells = Vec::new();
+                            descendants(row, &["td", "th"], &mut cells);
+                            cells.iter().map(|cell| parse_nodes(cell)).collect()
+                        })
+                        .collect(),
+                )
+            }
+            _ => Node::Other(parse_nodes(child)),
+        });
+    }
+    nodes
+}
+
+/// Starts a block on a new line.
+fn start_block(out: &mut String) {
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Characters with a meaning in Markdown inline text, escaped with a
+/// backslash.
+const MARKDOWN_SPECIAL: &[char] = &['\\', '`', '*', '_', '[', ']', '<', '>', '|'];
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if MARKDOWN_SPECIAL.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(match c {
+            '\n' => ' ',
+            c => c,
+        });
+    }
+    escaped
+}
+
+/// Backticks delimiting a code span or a fenced block, longer than any run
+/// of backticks in the text so that it can't be closed early.
+fn backtick_fence(text: &str, min: usize) -> String {
+    let longest = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    "`".repeat(min.max(longest + 1))
+}
+
+/// Only links to web pages, e-mail addresses and relative links are kept,
+/// other schemes like `javascript:` or `data:` are rendered as text.
+fn is_safe_href(href: &str) -> bool {
+    // browsers ignore the whitespace and control characters in the scheme
+    let href: String = href
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_ascii_control())
+        .collect();
+    match href.find([':', '/', '?', '#']) {
+        Some(i) if href[i..].starts_with(':') => {
+            let scheme = href[..i].to_ascii_lowercase();
+            ["http", "https", "mailto"].contains(&scheme.as_str())
+        }
+        _ => true,
+    }
+}
+
+/// Percent-encodes the characters that would end a Markdown link
+/// destination.
+fn markdown_href(href: &str) -> String {
+    href.replace('%', "%25")
+        .replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29")
+        .replace('<', "%3C")
+        .replace('>', "%3E")
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Style {
+    Plain,
+    Markdown,
+    Html,
+}
+
+struct Renderer<'a> {
+    style: Style,
+    resolve: &'a dyn Fn(&str) -> Option<String>,
+}
+
+impl Renderer<'_> {
+    fn render(&self, nodes: &[Node]) -> String {
+        let mut out = String::new();
+        for node in nodes.iter() {
+            self.node(node, &mut out);
+        }
+        out
+    }
+
+    fn text(&self, text: &str) -> String {
+        match self.style {
+            Style::Plain => text.replace('\n', " "),
+            Style::Markdown => escape_markdown(text),
+            Style::Html => escape_html(text),
+        }
+    }
+
+    fn wrap(&self, out: &mut String, children: &[Node], markdown: &str, tag: &str) {
+        let inner = self.render(children);
+        match self.style {
+            Style::Plain => out.push_str(&inner),
+            Style::Markdown => out.push_str(&format!("{}{}{}", markdown, inner, markdown)),
+            Style::Html => out.push_str(&format!("<{}>{}</{}>", tag, inner, tag)),
+        }
+    }
+
+    fn node(&self, node: &Node, out: &mut String) {
+        match node {
+            Node::Text(text) => out.push_str(&self.text(text)),
+            Node::Sub(idref) => {
+                if let Some(text) = (self.resolve)(idref) {
+                    out.push_str(&self.text(&text));
+                }
+            }
+            Node::Break => out.push_str(match self.style {
+                Style::Plain => "\n",
+                Style::Markdown => "  \n",
+                Style::Html => "<br/>",
+            }),
+            Node::Emphasis(children) => self.wrap(out, children, "*", "em"),
+            Node::Strong(children) => self.wrap(out, children, "**", "strong"),
+            Node::Code(children) if self.style == Style::Markdown => {
+                // backslashes aren't escapes in a code span
+                let plain = Renderer {
+                    style: Style::Plain,
+                    resolve: self.resolve,
+                };
+                let code = plain.render(children);
+                let fence = backtick_fence(&code, 1);
+                match code.starts_with('`') || code.ends_with('`') {
+                    true => out.push_str(&format!("{} {} {}", fence, code, fence)),
+                    false => out.push_str(&format!("{}{}{}", fence, code, fence)),
+                }
+            }
+            Node::Code(children) => self.wrap(out, children, "`", "code"),
+            Node::Link { href, children } => {
+                let inner = self.render(children);
+                match self.style {
+                    Style::Plain => out.push_str(&inner),
+                    _ if !is_safe_href(href) => out.push_str(&inner),
+                    Style::Markdown => {
+                        out.push_str(&format!("[{}]({})", inner, markdown_href(href)))
+                    }
+                    Style::Html => {
+                        out.push_str(&format!("<a href=\"{}\">{}</a>", escape_html(href), inner))
+                    }
+                }
+            }
+            Node::Paragraph(children) => {
+                let inner = self.render(children);
+                match self.style {
+                    Style::Html => out.push_str(&format!("<p>{}</p>", inner.trim())),
+                    _ => {
+                        start_block(out);
+                        out.push_str(inner.trim());
+                        out.push('\n');
+                    }
+                }
+            }
+            Node::Preformatted(text) => match self.style {
+                Style::Plain => {
+                    start_block(out);
+                    out.push_str(text);
+                    start_block(out);
+                }
+                Style::Markdown => {
+                    start_block(out);
+                    let fence = backtick_fence(text, 3);
+                    let text = text.trim_matches('\n');
+                    out.push_str(&format!("{}\n{}\n{}\n", fence, text, fence));
+                }
+                Style::Html => out.push_str(&format!("<pre>{}</pre>", escape_html(text))),
+            },
+            Node::List { ordered, items } => {
+                if self.style == Style::Html {
+                    let tag = if *ordered { "ol" } else { "ul" };
+                    out.push_str(&format!("<{}>", tag));
+                    for item in items.iter() {
+                        out.push_str(&format!("<li>{}</li>", self.render(item).trim()));
+                    }
+                    out.push_str(&format!("</{}>", tag));
+                    return;
+                }
+                start_block(out);
+                for (i, item) in items.iter().enumerate() {
+                    match ordered {
+                        true => out.push_str(&format!("{}. ", i + 1)),
+                        false => out.push_str("- "),
+                    }
+                    out.push_str(self.render(item).trim());
+                    out.push('\n');
+                }
+            }
+            Node::Table(rows) => self.table(rows, out),
+            Node::Other(children) => out.push_str(&self.render(children)),
+        }
+    }
+
+    fn table(&self, rows: &[Vec<Vec<Node>>], out: &mut String) {
+        let cells = |row: &Vec<Vec<Node>>| -> Vec<String> {
+            row.iter()
+                .map(|cell| self.render(cell).trim().to_string())
+                .collect()
+        };
+        match self.style {
+            Style::Html => {
+                out.push_str("<table>");
+                for row in rows.iter() {
+                    out.push_str("<tr>");
+                    for cell in cells(row) {
+                        out.push_str(&format!("<td>{}</td>", cell));
+                    }
+                    out.push_str("</tr>");
+                }
+                out.push_str("</table>");
+            }
+            Style::Plain => {
+                start_block(out);
+                for row in rows.iter() {
+                    out.push_str(&cells(row).join(" | "));
+                    out.push('\n');
+                }
+            }
+            Style::Markdown => {
+                start_block(out);
+                for (i, row) in rows.iter().enumerate() {
+                    out.push_str(&format!("| {} |\n", cells(row).join(" | ")));
+                    if i == 0 {
+                        out.push_str(&format!("|{}\n", " --- |".repeat(row.len())));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl RichText {
+    /// Parses the content of an element with XHTML children, e.g. an
+    /// `xccdf:description`.
+    pub fn from_xml(el: &Element) -> RichText {
+        RichText {
+            nodes: parse_nodes(el),
+        }
+    }
+
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    fn render<F: Fn(&str) -> Option<String>>(&self, style: Style, resolve: F) -> String {
+        let renderer = Renderer {
+            style,
+            resolve: &resolve,
+        };
+        let text = renderer.render(&self.nodes);
+        match style {
+            Style::Html => text,
+            _ => text.trim_end_matches('\n').to_string(),
+        }
+    }
+
+    /// Renders the text without formatting. The `xccdf:sub` references
+    /// are substituted by `resolve`, those that can't be resolved are left
+    /// out.
+    pub fn to_text<F: Fn(&str) -> Option<String>>(&self, resolve: F) -> String {
+        self.render(Style::Plain, resolve)
+    }
+
+    pub fn to_markdown<F: Fn(&str) -> Option<String>>(&self, resolve: F) -> String {
+        self.render(Style::Markdown, resolve)
+    }
+
+    pub fn to_html<F: Fn(&str) -> Option<String>>(&self, resolve: F) -> String {
+        self.render(Style::Html, resolve)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rich_text_rendering() {
+        let el: Element = "<description xmlns=\"http://checklists.nist.gov/xccdf/1.2\" \
+             xmlns:h=\"http://www.w3.org/1999/xhtml\">Set <h:code>PermitRootLogin</h:code> \
+             on <sub idref=\"os\"/>:<h:ul><h:li>edit <h:i>sshd_config</h:i></h:li>\
+             <h:li>see <h:a href=\"https://example.org\">docs</h:a></h:li></h:ul>\
+             <h:pre>PermitRootLogin no</h:pre><h:table><h:tr><h:th>Key</h:th><h:th>Value</h:th></h:tr>\
+             <h:tr><h:td>a&lt;b</h:td><h:td>1</h:td></h:tr></h:table></description>"
+            .parse()
+            .unwrap();
+        let text = RichText::from_xml(&el);
+        let resolve = |idref: &str| (idref == "os").then(|| String::from("RHEL"));
+        assert_eq!(
+            text.to_text(resolve),
+            "Set PermitRootLogin on RHEL:\n- edit sshd_config\n- see docs\n\
+             PermitRootLogin no\nKey | Value\na<b | 1"
+        );
+        assert_eq!(
+            text.to_markdown(resolve),
+            "Set `PermitRootLogin` on RHEL:\n- edit *sshd\\_config*\n- see [docs](https://example.org)\n\
+             ```\nPermitRootLogin no\n```\n| Key | Value |\n| --- | --- |\n| a\\<b | 1 |"
+        );
+        assert_eq!(
+            text.to_html(|_| None),
+            "Set <code>PermitRootLogin</code> on :<ul><li>edit <em>sshd_config</em></li>\
+             <li>see <a href=\"https://example.org\">docs</a></li></ul>\
+             <pre>PermitRootLogin no</pre><table><tr><td>Key</td><td>Value</td></tr>\
+             <tr><td>a&lt;b</td><td>1</td></tr></table>"
+        );
+    }
+
+    #[test]
+    fn test_unsafe_content() {
+        let el: Element = "<description xmlns=\"http://checklists.nist.gov/xccdf/1.2\" \
+             xmlns:h=\"http://www.w3.org/1999/xhtml\">*not* [bold](x) \
+             <h:a href=\"JavaScript:alert(1)\">a</h:a> <h:a href=\"java&#9;script:x\">b</h:a> \
+             <h:a href=\"data:text/html,x\">c</h:a> <h:a href=\"mailto:a@example.org\">d</h:a> \
+             <h:a href=\"#rule-r1\">e</h:a> <h:a href=\"docs/a b.html?q=a:b\">f</h:a> \
+             <h:code>a`b</h:code><h:pre>```\n&lt;x&gt;</h:pre></description>"
+            .parse()
+            .unwrap();
+        let text = RichText::from_xml(&el);
+        assert_eq!(
+            text.to_markdown(|_| None),
+            "\\*not\\* \\[bold\\](x) a b c [d](mailto:a@example.org) [e](#rule-r1) \
+             [f](docs/a%20b.html?q=a:b) ``a`b``\n````\n```\n<x>\n````"
+        );
+        let html = text.to_html(|_| None);
+        assert!(html.starts_with("*not* [bold](x) a b c <a href=\"mailto:a@example.org\">d</a> "));
+        assert!(html.contains("<a href=\"#rule-r1\">e</a>"));
+        assert!(!html.contains("script"));
+    }
+}
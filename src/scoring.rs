@@ -0,0 +1,330 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::utils::string_enum;
+use crate::xccdf::{Benchmark, Group, ResolvedProfile, Rule, TestResult};
+
+string_enum!(
+    ScoringModel {
+        Default => "urn:xccdf:scoring:default",
+        Flat => "urn:xccdf:scoring:flat",
+        FlatUnweighted => "urn:xccdf:scoring:flat-unweighted",
+        Absolute => "urn:xccdf:scoring:absolute",
+    }
+);
+
+impl ScoringModel {
+    pub const ALL: [ScoringModel; 4] = [
+        ScoringModel::Default,
+        ScoringModel::Flat,
+        ScoringModel::FlatUnweighted,
+        ScoringModel::Absolute,
+    ];
+}
+
+/// Results of rules that aren't scored by any model.
+const UNSCORED: [&str; 4] = [
+    "notselected",
+    "notapplicable",
+    "informational",
+    "notchecked",
+];
+
+/// Tolerance of the comparison with the recorded scores, which are usually
+/// rounded.
+const TOLERANCE: f64 = 0.01;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelScore {
+    pub(crate) model: ScoringModel,
+    pub(crate) value: f64,
+    pub(crate) maximum: f64,
+}
+
+impl ModelScore {
+    pub fn model(&self) -> ScoringModel {
+        self.model
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn maximum(&self) -> f64 {
+        self.maximum
+    }
+}
+
+/// Rule results of the TestResult and the rule weights of its profile.
+struct Scorer<'a> {
+    results: HashMap<&'a str, &'a str>,
+    profile: Option<ResolvedProfile<'a>>,
+}
+
+impl<'a> Scorer<'a> {
+    fn new(benchmark: &'a Benchmark, test_result: &'a TestResult) -> Scorer<'a> {
+        let mut results = HashMap::new();
+        for rule_result in test_result.rule_results.iter() {
+            results
+                .entry(rule_result.idref.as_ref())
+                .or_insert(rule_result.result.as_str());
+        }
+        let profile = test_result
+            .profile
+            .as_deref()
+            .and_then(|id| benchmark.resolve_profile(id).ok());
+        Scorer { results, profile }
+    }
+
+    /// Whether the scored rule passed, `None` for rules that aren't scored.
+    fn passed(&self, rule: &Rule) -> Option<bool> {
+        match self.results.get(rule.id.as_ref()) {
+            Some(result) if !UNSCORED.contains(result) => Some(matches!(*result, "pass" | "fixed")),
+            _ => None,
+        }
+    }
+
+    fn weight(&self, rule: &Rule) -> f64 {
+        match &self.profile {
+            Some(profile) => profile.weight(rule),
+            None => rule.weight,
+        }
+    }
+
+    /// Score and count of the scored rules of the items, the average of the
+    /// child scores weighted by their weights.
+    fn default_score(&self, groups: &[Group], rules: &[Rule]) -> (f64, usize) {
+        let mut score = 0.0;
+        let mut accumulator = 0.0;
+        let mut count = 0;
+        for rule in rules.iter() {
+            if let Some(passed) = self.passed(rule) {
+                let weight = self.weight(rule);
+                score += if passed { 100.0 * weight } else { 0.0 };
+                accumulator += weight;
+                count += 1;
+            }
+        }
+        for group in groups.iter() {
+            let (group_score, group_count) = self.default_score(&group.groups, &group.rules);
+            if group_count > 0 {
+                score += group_score * group.weight;
+                accumulator += group.weight;
+                count += group_count;
+            }
+        }
+        if accumulator == 0.0 {
+            (0.0, count)
+        } else {
+            (score / accumulator, count)
+        }
+    }
+
+    /// Sums of the weights of the passed and of all scored rules.
+    fn flat_score(&self, benchmark: &Benchmark, weighted: bool) -> (f64, f64) {
+        let mut score = 0.0;
+        let mut maximum = 0.0;
+        for rule in benchmark.all_rules() {
+            if let Some(passed) = self.passed(rule) {
+                let weight = if weighted { self.weight(rule) } else { 1.0 };
+                maximum += weight;
+                if passed {
+                    score += weight;
+                }
+            }
+        }
+        (score, maximum)
+    }
+}
+
+/// Computes the score of the TestResult in the scoring model. The rule
+/// weights are those of the profile the scan was run with.
+pub fn score(benchmark: &Benchmark, test_result: &TestResult, model: ScoringModel) -> ModelScore {
+    let scorer = Scorer::new(benchmark, test_result);
+    let (value, maximum) = match model {
+        ScoringModel::Default => {
+            let (score, _) = scorer.default_score(&benchmark.groups, &benchmark.rules);
+            (score, 100.0)
+        }
+        ScoringModel::Flat => scorer.flat_score(benchmark, true),
+        ScoringModel::FlatUnweighted => scorer.flat_score(benchmark, false),
+        ScoringModel::Absolute => {
+            let (score, maximum) = scorer.flat_score(benchmark, true);
+            (if score == maximum { 1.0 } else { 0.0 }, 1.0)
+        }
+    };
+    ModelScore {
+        model,
+        value,
+        maximum,
+    }
+}
+
+/// Score of a model computed from the rule results next to the one
+/// recorded in the TestResult, if any.
+#[derive(Debug, Serialize)]
+pub struct ScoreCheck {
+    pub(crate) computed: ModelScore,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) recorded: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) recorded_maximum: Option<f64>,
+}
+
+impl ScoreCheck {
+    pub fn computed(&self) -> &ModelScore {
+        &self.computed
+    }
+
+    pub fn recorded(&self) -> Option<f64> {
+        self.recorded
+    }
+
+    /// Whether the recorded score differs from the computed one.
+    pub fn mismatch(&self) -> bool {
+        let differs = |a: f64, b: f64| (a - b).abs() > TOLERANCE;
+        differs(
+            self.recorded.unwrap_or(self.computed.value),
+            self.computed.value,
+        ) || differs(
+            self.recorded_maximum.unwrap_or(self.computed.maximum),
+            self.computed.maximum,
+        )
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScoreVerification {
+    pub(crate) test_result: String,
+    pub(crate) scores: Vec<ScoreCheck>,
+    /// Scoring systems of recorded scores that can't be recomputed
+    pub(crate) unknown_models: Vec<String>,
+}
+
+impl ScoreVerification {
+    pub fn scores(&self) -> &[ScoreCheck] {
+        &self.scores
+    }
+
+    pub fn unknown_models(&self) -> &[String] {
+        &self.unknown_models
+    }
+
+    pub fn has_mismatches(&self) -> bool {
+        self.scores.iter().any(|s| s.mismatch())
+    }
+
+    pub fn write_text<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "TestResult: {}", self.test_result)?;
+        for check in self.scores.iter() {
+            let recorded = match check.recorded {
+                Some(recorded) => format!("{:.2}", recorded),
+                None => String::from("-"),
+            };
+            let mark = if check.mismatch() { " (mismatch)" } else { "" };
+            writeln!(
+                out,
+                "{:<34} {:>8.2} / {:<8.2} recorded {}{}",
+                check.computed.model.as_str(),
+                check.computed.value,
+                check.computed.maximum,
+                recorded,
+                mark
+            )?;
+        }
+        for model in self.unknown_models.iter() {
+            writeln!(out, "{:<34} unknown scoring model", model)?;
+        }
+        Ok(())
+    }
+}
+
+/// Recomputes the scores of all the models and compares them with the
+/// scores recorded in the TestResult.
+pub fn verify_scores(benchmark: &Benchmark, test_result: &TestResult) -> ScoreVerification {
+    let scores = ScoringModel::ALL
+        .iter()
+        .map(|model| {
+            let recorded = test_result
+                .scores
+                .iter()
+                .find(|s| &*s.system == model.as_str());
+            ScoreCheck {
+                computed: score(benchmark, test_result, *model),
+                recorded: recorded.map(|s| s.value),
+                recorded_maximum: recorded.map(|s| s.maximum),
+            }
+        })
+        .collect();
+    let unknown_models = test_result
+        .scores
+        .iter()
+        .filter(|s| s.system.parse::<ScoringModel>().is_err())
+        .map(|s| s.system.to_string())
+        .collect();
+    ScoreVerification {
+        test_result: test_result.id.to_string(),
+        scores,
+        unknown_models,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xccdf::XCCDF12_NS;
+    use minidom::Element;
+
+    fn benchmark() -> Benchmark {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Profile id=\"p\"><title>P</title><refine-rule idref=\"r1\" weight=\"3\"/></Profile>\
+             <Rule id=\"r1\"><title>R1</title></Rule>\
+             <Group id=\"g\" weight=\"2\"><title>G</title>\
+             <Rule id=\"r2\"><title>R2</title></Rule>\
+             <Rule id=\"r3\"><title>R3</title></Rule>\
+             <Rule id=\"r4\"><title>R4</title></Rule></Group>\
+             <Group id=\"empty\"><title>E</title><Rule id=\"r5\"><title>R5</title></Rule></Group>\
+             <TestResult id=\"t\" end-time=\"2024-01-01T00:00:00\"><profile idref=\"p\"/>\
+             <rule-result idref=\"r1\"><result>fail</result></rule-result>\
+             <rule-result idref=\"r2\"><result>pass</result></rule-result>\
+             <rule-result idref=\"r3\"><result>error</result></rule-result>\
+             <rule-result idref=\"r4\"><result>notapplicable</result></rule-result>\
+             <rule-result idref=\"r5\"><result>notselected</result></rule-result>\
+             <score system=\"urn:xccdf:scoring:default\">40</score>\
+             <score system=\"urn:xccdf:scoring:flat\" maximum=\"5\">1</score>\
+             <score system=\"urn:example:custom\">1</score></TestResult></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        Benchmark::from_xml(&el).unwrap()
+    }
+
+    #[test]
+    fn test_scoring_models() {
+        let benchmark = benchmark();
+        let test_result = benchmark.test_result(None).unwrap();
+        let score = |model| {
+            let s = score(&benchmark, test_result, model);
+            (s.value, s.maximum)
+        };
+        // r1 fails with weight 3, the group g scores 50 with weight 2
+        assert_eq!(score(ScoringModel::Default), (20.0, 100.0));
+        assert_eq!(score(ScoringModel::Flat), (1.0, 5.0));
+        assert_eq!(score(ScoringModel::FlatUnweighted), (1.0, 3.0));
+        assert_eq!(score(ScoringModel::Absolute), (0.0, 1.0));
+
+        let verification = verify_scores(&benchmark, test_result);
+        let mismatched: Vec<ScoringModel> = verification
+            .scores
+            .iter()
+            .filter(|c| c.mismatch())
+            .map(|c| c.computed.model)
+            .collect();
+        assert_eq!(mismatched, vec![ScoringModel::Default]);
+        assert_eq!(verification.unknown_models, vec!["urn:example:custom"]);
+        assert!(verification.has_mismatches());
+    }
+}
@@ -1,29 +1,96 @@
+use chrono::{DateTime, FixedOffset};
 use minidom::Element;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+#[cfg(feature = "network")]
+use std::path::Path;
+use std::sync::Arc;
 
-const SCAP12_NS: &str = "http://scap.nist.gov/schema/scap/source/1.2";
-const DSIG_NS: &str = "http://scap.nist.gov/schema/xml-dsig/1.0";
-const CAT_NS: &str = "urn:oasis:names:tc:entity:xmlns:xml:catalog";
+pub const SCAP12_NS: &str = "http://scap.nist.gov/schema/scap/source/1.2";
+pub const CAT_NS: &str = "urn:oasis:names:tc:entity:xmlns:xml:catalog";
+pub const OCIL_NS: &str = "http://scap.nist.gov/schema/ocil/2.0";
 
+use crate::dsig;
+use crate::error::Error;
+use crate::info;
+#[cfg(feature = "network")]
+use crate::input::{self, FetchOptions, Input};
+#[cfg(feature = "network")]
+use crate::misc;
+use crate::options::{ParseOptions, Progress};
+use crate::output::{paint, Color, Table};
+use crate::oval;
+use crate::report::severity_color;
+use crate::trust::SigningPolicy;
 use crate::utils::*;
 use crate::xccdf;
 
+string_enum!(
+    UseCase {
+        Configuration => "CONFIGURATION",
+        Vulnerability => "VULNERABILITY",
+        Inventory => "INVENTORY",
+        Other => "OTHER",
+    }
+);
+
+string_enum!(
+    ScapVersion {
+        V1_0 => "1.0",
+        V1_1 => "1.1",
+        V1_2 => "1.2",
+        V1_3 => "1.3",
+    }
+);
+
 #[derive(Debug)]
 pub struct DataStreamCollection {
-    id: String,
-    schematron_version: String,
-    data_streams: Vec<DataStream>,
-    components: Vec<Component>,
-    extended_components: Vec<ExtendedComponent>,
-    signatures: Vec<Signature>,
+    pub(crate) id: Arc<str>,
+    pub(crate) schematron_version: Arc<str>,
+    pub(crate) data_streams: Vec<DataStream>,
+    pub(crate) components: Vec<Component>,
+    pub(crate) extended_components: Vec<ExtendedComponent>,
+    pub(crate) signatures: Vec<dsig::Signature>,
+    /// Positions of the components and extended components by ID
+    pub(crate) component_index: HashMap<String, usize>,
+    pub(crate) extended_component_index: HashMap<String, usize>,
+}
+
+/// Runs the parse on a blocking thread of the async runtime with a copy of
+/// the options, the warnings are moved back to `opts`.
+#[cfg(feature = "async")]
+async fn parse_blocking<T, F>(opts: &ParseOptions, parse: F) -> Result<T, Error>
+where
+    T: Send + 'static,
+    F: FnOnce(&ParseOptions) -> Result<T, Error> + Send + 'static,
+{
+    let copy = opts.clone();
+    let (result, copy) = tokio::task::spawn_blocking(move || (parse(&copy), copy))
+        .await
+        .map_err(|e| Error::Content(format!("The parse didn't finish: {}", e)))?;
+    *opts.warnings() = copy.take_warnings();
+    result
 }
 
 impl DataStreamCollection {
-    pub fn from_xml(root: &Element) -> Result<DataStreamCollection, String> {
+    pub fn from_xml(root: &Element) -> Result<DataStreamCollection, Error> {
+        DataStreamCollection::from_xml_with_options(root, &ParseOptions::default())
+    }
+
+    pub fn from_xml_with_options(
+        root: &Element,
+        opts: &ParseOptions,
+    ) -> Result<DataStreamCollection, Error> {
+        let _span = tracing::debug_span!("collection", id = root.attr("id")).entered();
+        let result = opts.within(root.name(), || DataStreamCollection::parse(root, opts));
+        opts.finish(result)
+    }
+
+    fn parse(root: &Element, opts: &ParseOptions) -> Result<DataStreamCollection, Error> {
         if root.ns() != SCAP12_NS {
-            return Err(format!(
-                "Wrong namespace '{}', expected '{}",
-                root.ns(),
-                SCAP12_NS
+            return Err(Error::unexpected_element(
+                root.name(),
+                Some(&format!("an element in namespace '{}'", SCAP12_NS)),
             ));
         }
         let id = require_attr(root, "id")?;
@@ -33,89 +100,786 @@ impl DataStreamCollection {
         let mut components = Vec::new();
         let mut extended_components = Vec::new();
         let mut signatures = Vec::new();
-        for child in root.children() {
+        let total = root
+            .children()
+            .filter(|child| child.is("component", SCAP12_NS))
+            .count();
+        let mut parsed = 0;
+        for_each_item(root, opts, |child| {
             if child.is("data-stream", SCAP12_NS) {
-                let data_stream = DataStream::from_xml(child)?;
+                let data_stream = DataStream::from_xml_with_options(child, opts)?;
                 data_streams.push(data_stream);
             } else if child.is("component", SCAP12_NS) {
-                let component = Component::from_xml(child)?;
-                components.push(component);
+                let component = Component::from_xml(child, opts);
+                parsed += 1;
+                opts.report(Progress::ComponentsParsed { parsed, total });
+                components.push(component?);
             } else if child.is("extended-component", SCAP12_NS) {
-                let component = ExtendedComponent::from_xml(child)?;
+                let component = ExtendedComponent::from_xml(child, opts)?;
                 extended_components.push(component);
-            } else if child.is("Signature", DSIG_NS) {
-                let signature = Signature::from_xml(child)?;
+            } else if dsig::is_signature(child) {
+                let signature = dsig::Signature::from_xml(child)?;
                 signatures.push(signature);
             }
+            Ok(())
+        })?;
+        if data_streams.is_empty() {
+            return Err(Error::missing_element(root.name(), "data-stream"));
         }
-        if data_streams.len() < 1 {
-            return Err(String::from("The 'data-stream-collection' element needs to have at least 1 child 'data-stream' element."));
-        }
-        if components.len() < 1 {
-            return Err(String::from("The 'data-stream-collection' element needs to have at least 1 child 'component' element."));
+        if components.is_empty() {
+            return Err(Error::missing_element(root.name(), "component"));
         }
         Ok(DataStreamCollection {
             id,
             schematron_version,
             data_streams,
+            component_index: index_by_id(components.iter().map(|c| c.id.as_ref())),
+            extended_component_index: index_by_id(
+                extended_components.iter().map(|c| c.id.as_ref()),
+            ),
             components,
             extended_components,
             signatures,
         })
     }
 
-    pub fn print_information(&self) {
-        println!("Document type: SCAP Source Data Stream");
+    pub fn benchmarks(&self) -> Vec<&xccdf::Benchmark> {
+        let mut benchmarks = Vec::new();
+        for component in self.components.iter() {
+            if let ComponentContent::XCCDFBenchmark(benchmark) = &component.content {
+                benchmarks.push(benchmark.as_ref());
+            }
+        }
+        benchmarks
+    }
+
+    /// Checks every collection-level and item-level signature and returns
+    /// a report line for each of them. With a policy, the signatures are
+    /// also verified against `root`, the document the collection was
+    /// parsed from, and a collection-level signature fails unless its
+    /// verified references cover every data stream and component.
+    pub fn check_signatures(
+        &self,
+        root: &Element,
+        policy: Option<&SigningPolicy>,
+    ) -> Vec<SignatureCheck> {
+        let mut known_ids: HashSet<&str> = HashSet::new();
+        known_ids.insert(&self.id);
+        known_ids.extend(self.data_streams.iter().map(|ds| ds.id.as_ref()));
+        known_ids.extend(self.components.iter().map(|c| c.id.as_ref()));
+        known_ids.extend(self.extended_components.iter().map(|c| c.id.as_ref()));
+        let benchmarks = self.benchmarks();
+        for benchmark in benchmarks.iter() {
+            known_ids.extend(benchmark.item_ids());
+        }
+        // A collection signature has to cover every data stream and component
+        let mut signed: Vec<&str> = self.data_streams.iter().map(|ds| ds.id.as_ref()).collect();
+        signed.extend(self.components.iter().map(|c| c.id.as_ref()));
+        signed.extend(self.extended_components.iter().map(|c| c.id.as_ref()));
+        let mut checks = Vec::new();
+        let check = |signature: &dsig::Signature, signed: &[&str]| {
+            let mut problems = signature.check_structure(&known_ids);
+            if let Some(policy) = policy {
+                problems.extend(policy.check(signature, root, signed));
+            }
+            problems
+        };
+        for signature in self.signatures.iter() {
+            checks.push(SignatureCheck {
+                signed_item: self.id.to_string(),
+                signature_id: signature.id.as_deref().map(String::from),
+                signer: signer_subject(signature),
+                problems: check(signature, &signed),
+            });
+        }
+        for benchmark in benchmarks.iter() {
+            for (item_id, signature) in benchmark.signatures() {
+                let mut problems = check(signature, &[item_id]);
+                let expected = format!("#{}", item_id);
+                if !signature
+                    .references
+                    .iter()
+                    .any(|r| &*r.uri == expected.as_str() || r.uri.is_empty())
+                {
+                    problems.push(format!(
+                        "signature doesn't reference the signed item '{}'",
+                        item_id
+                    ));
+                }
+                checks.push(SignatureCheck {
+                    signed_item: item_id.to_string(),
+                    signature_id: signature.id.as_deref().map(String::from),
+                    signer: signer_subject(signature),
+                    problems,
+                });
+            }
+        }
+        checks
+    }
+
+    /// Resolves the `xlink:href` of a component-ref. Local references (`#id`)
+    /// point either to a component, an extended component or, when the href
+    /// was produced by a catalog, to another component-ref which is followed.
+    /// Anything else is a reference to a remote resource, unless it was
+    /// downloaded by [`DataStreamCollection::fetch_remote_components`].
+    pub fn find_component_by_href(&self, href: &str) -> Option<ComponentTarget<'_>> {
+        self.find_component_by_href_depth(href, 0)
+    }
+
+    fn find_component_by_href_depth(
+        &self,
+        href: &str,
+        depth: usize,
+    ) -> Option<ComponentTarget<'_>> {
+        let id = match href.strip_prefix('#') {
+            Some(id) => id,
+            None => {
+                return Some(match self.component(href) {
+                    Some(component) => ComponentTarget::Component(component),
+                    None => ComponentTarget::Remote(href.to_string()),
+                })
+            }
+        };
+        if let Some(component) = self.component(id) {
+            return Some(ComponentTarget::Component(component));
+        }
+        if let Some(component) = self.extended_component(id) {
+            return Some(ComponentTarget::ExtendedComponent(component));
+        }
+        // Component-refs can't legitimately form chains longer than the
+        // number of data streams, this protects against reference loops.
+        if depth > self.data_streams.len() {
+            return None;
+        }
+        let component_ref = self
+            .data_streams
+            .iter()
+            .flat_map(|ds| ds.component_refs())
+            .find(|component_ref| &*component_ref.id == id)?;
+        self.find_component_by_href_depth(&component_ref.href, depth + 1)
+    }
+
+    /// Resolves a URI used inside a component through the catalog of the
+    /// component-ref that includes the component.
+    pub fn find_component_by_catalog(
+        &self,
+        catalog: &Catalog,
+        uri: &str,
+    ) -> Option<ComponentTarget<'_>> {
+        let href = catalog.resolve(uri)?;
+        self.find_component_by_href(&href)
+    }
+
+    /// Component-refs whose `xlink:href` fragment doesn't point to any
+    /// component or extended component of the collection.
+    pub fn dangling_refs(&self) -> Vec<DanglingRef> {
+        let mut dangling = Vec::new();
         for ds in self.data_streams.iter() {
-            println!("Stream: {}", ds.id);
-            println!();
-            println!("Checklists:");
-            for checklist in ds.checklists.iter() {
-                println!("Ref-Id: {}", checklist.id);
-                if !checklist.href.starts_with("#") {
-                    println!("Remote checklists aren't supported by this tool");
-                    continue;
+            let lists = [
+                ("dictionaries", &ds.dictionaries),
+                ("checklists", &ds.checklists),
+                ("checks", &ds.checks),
+                ("extended-components", &ds.extended_components),
+            ];
+            for (list, component_refs) in lists {
+                for component_ref in component_refs.iter() {
+                    let id = match component_ref.href.strip_prefix('#') {
+                        Some(id) => id,
+                        None => continue,
+                    };
+                    if self.component(id).is_none() && self.extended_component(id).is_none() {
+                        dangling.push(DanglingRef {
+                            data_stream: ds.id.to_string(),
+                            list,
+                            ref_id: component_ref.id.to_string(),
+                            href: component_ref.href.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+        dangling
+    }
+
+    /// Resolves the distinct `check-content-ref` hrefs of the rules of the
+    /// checklist through its catalog. A reference to a component-ref is
+    /// looked up in the data stream of the checklist.
+    pub fn resolve_check_refs(
+        &self,
+        ds: &DataStream,
+        checklist: &ComponentRef,
+    ) -> Vec<CheckRefResolution<'_>> {
+        let benchmark = match self.find_component_by_href(&checklist.href) {
+            Some(ComponentTarget::Component(component)) => component.benchmark(),
+            _ => None,
+        };
+        let mut hrefs: Vec<&str> = Vec::new();
+        for rule in benchmark.into_iter().flat_map(|b| b.iter_rules()) {
+            for check in rule.item.checks.iter() {
+                for content_ref in check.content_refs.iter() {
+                    if !hrefs.contains(&content_ref.href.as_ref()) {
+                        hrefs.push(&content_ref.href);
+                    }
                 }
-                for component in self.components.iter() {
-                    if &checklist.href[1..] == component.id {
-                        println!("Component ID: {}", component.id);
-                        let content = &component.content;
-                        match content {
-                            ComponentContent::XCCDFBenchmark(benchmark) => {
-                                benchmark.print_information()
+            }
+        }
+        hrefs
+            .into_iter()
+            .map(|href| self.resolve_check_href(ds, checklist, href))
+            .collect()
+    }
+
+    fn resolve_check_href(
+        &self,
+        ds: &DataStream,
+        checklist: &ComponentRef,
+        href: &str,
+    ) -> CheckRefResolution<'_> {
+        let resolved = checklist
+            .catalog
+            .as_ref()
+            .and_then(|catalog| catalog.resolve(href))
+            .unwrap_or_else(|| href.to_string());
+        let component_ref = resolved
+            .strip_prefix('#')
+            .and_then(|id| ds.component_refs().find(|r| &*r.id == id));
+        let target = match component_ref {
+            Some(component_ref) => self.find_component_by_href(&component_ref.href),
+            None => self.find_component_by_href(&resolved),
+        };
+        CheckRefResolution {
+            href: href.to_string(),
+            resolved,
+            target,
+        }
+    }
+
+    /// OVAL checks of the checklist rules whose `check-content-ref/@name`
+    /// isn't a definition of the OVAL component the href resolves to.
+    /// References that don't resolve to an OVAL component aren't checked.
+    pub fn missing_oval_definitions(&self) -> Vec<MissingDefinition> {
+        let mut missing = Vec::new();
+        for ds in self.data_streams.iter() {
+            for checklist in ds.checklists.iter() {
+                let benchmark = match self.find_component_by_href(&checklist.href) {
+                    Some(ComponentTarget::Component(component)) => component.benchmark(),
+                    _ => None,
+                };
+                for rule in benchmark.into_iter().flat_map(|b| b.iter_rules()) {
+                    let checks = rule.item.checks.iter();
+                    for check in checks.filter(|c| c.system == xccdf::CheckSystem::Oval) {
+                        for content_ref in check.content_refs.iter() {
+                            let name = match &content_ref.name {
+                                Some(name) => name,
+                                None => continue,
+                            };
+                            let resolution =
+                                self.resolve_check_href(ds, checklist, &content_ref.href);
+                            let component = match resolution.target {
+                                Some(ComponentTarget::Component(component)) => component,
+                                _ => continue,
+                            };
+                            let definitions = match component.oval_definitions() {
+                                Some(definitions) => definitions,
+                                None => continue,
+                            };
+                            if definitions.definition(name).is_none() {
+                                missing.push(MissingDefinition {
+                                    rule_id: rule.item.id.to_string(),
+                                    definition_id: name.to_string(),
+                                    component_id: component.id.to_string(),
+                                });
                             }
-                            _ => panic!("The component isn't a XCCDF benchmark"),
                         }
                     }
                 }
             }
         }
+        missing
+    }
+
+    /// Downloads the components referenced by http(s) URLs, like oscap
+    /// with `--fetch-remote-resources`, and adds them to the collection.
+    /// They're identified by their URL. Returns the number of downloaded
+    /// components.
+    #[cfg(feature = "network")]
+    pub fn fetch_remote_components(
+        &mut self,
+        opts: &ParseOptions,
+        fetch: &FetchOptions,
+    ) -> Result<usize, Error> {
+        let urls = self.remote_component_urls();
+        for url in urls.iter() {
+            let data = Input::open_url(url, None, fetch)
+                .map_err(|e| Error::Content(format!("Failed to fetch '{}': {}", url, e)))?;
+            let (root, _) = misc::parse(&data).map_err(Error::Xml)?;
+            self.add_remote_component(url, &root, opts)?;
+        }
+        Ok(urls.len())
+    }
+
+    /// Like [`DataStreamCollection::fetch_remote_components`], but the
+    /// components are downloaded without blocking the async runtime and
+    /// parsed on a blocking thread.
+    #[cfg(feature = "async")]
+    pub async fn fetch_remote_components_async(
+        &mut self,
+        opts: &ParseOptions,
+        fetch: &FetchOptions,
+    ) -> Result<usize, Error> {
+        let urls = self.remote_component_urls();
+        for url in urls.iter() {
+            let data = Input::open_url_async(url, None, fetch)
+                .await
+                .map_err(|e| Error::Content(format!("Failed to fetch '{}': {}", url, e)))?;
+            let id: Arc<str> = Arc::from(url.as_str());
+            let component = parse_blocking(opts, move |opts| {
+                let (root, _) = misc::parse(&data).map_err(Error::Xml)?;
+                Component::from_content(id, None, &root, opts)
+            })
+            .await?;
+            self.insert_component(url, component);
+        }
+        Ok(urls.len())
+    }
+
+    /// Downloads the collection without blocking the async runtime and
+    /// parses it on a blocking thread.
+    #[cfg(feature = "async")]
+    pub async fn from_url_async(
+        url: &str,
+        opts: &ParseOptions,
+        fetch: &FetchOptions,
+    ) -> Result<DataStreamCollection, Error> {
+        let data = Input::open_url_async(url, None, fetch).await?;
+        parse_blocking(opts, move |opts| crate::parse_collection(&data, opts)).await
+    }
+
+    /// http(s) URLs of the components that aren't downloaded yet.
+    #[cfg(feature = "network")]
+    fn remote_component_urls(&self) -> Vec<String> {
+        let mut urls: Vec<String> = Vec::new();
+        for component_ref in self.data_streams.iter().flat_map(|ds| ds.component_refs()) {
+            let href: &str = &component_ref.href;
+            if input::is_url(Path::new(href))
+                && !urls.iter().any(|u| u == href)
+                && self.component(href).is_none()
+            {
+                urls.push(href.to_string());
+            }
+        }
+        urls
+    }
+
+    fn add_remote_component(
+        &mut self,
+        url: &str,
+        root: &Element,
+        opts: &ParseOptions,
+    ) -> Result<(), Error> {
+        let component = Component::from_content(Arc::from(url), None, root, opts)?;
+        self.insert_component(url, component);
+        Ok(())
+    }
+
+    fn insert_component(&mut self, url: &str, component: Component) {
+        self.component_index
+            .insert(url.to_string(), self.components.len());
+        self.components.push(component);
+    }
+
+    pub fn find_profile(&self, id: &str) -> Option<&xccdf::Profile> {
+        self.benchmarks()
+            .into_iter()
+            .find_map(|benchmark| benchmark.find_profile(id))
+    }
+
+    /// Resolves a tailoring profile against the first benchmark that
+    /// contains all the profiles it extends.
+    pub fn resolve_tailored_profile<'a>(
+        &'a self,
+        tailoring: &'a xccdf::Tailoring,
+        id: &str,
+    ) -> Result<xccdf::ResolvedProfile<'a>, Error> {
+        let mut first_error = None;
+        for benchmark in self.benchmarks() {
+            match benchmark.resolve_tailored_profile(tailoring, id) {
+                Ok(profile) => return Ok(profile),
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+        Err(first_error.unwrap_or_else(|| {
+            Error::Content(String::from(
+                "The data stream doesn't contain any XCCDF benchmark",
+            ))
+        }))
+    }
+
+    pub fn resolve_profile(&self, id: &str) -> Result<xccdf::ResolvedProfile<'_>, Error> {
+        match self
+            .benchmarks()
+            .into_iter()
+            .find(|benchmark| benchmark.find_profile(id).is_some())
+        {
+            Some(benchmark) => benchmark.resolve_profile(id),
+            None => Err(Error::Content(format!("Profile '{}' not found", id))),
+        }
+    }
+
+    pub fn info(&self, lang: Option<&str>) -> info::CollectionInfo {
+        let mut data_streams = Vec::new();
+        for ds in self.data_streams.iter() {
+            let mut checklists = Vec::new();
+            for checklist in ds.checklists.iter() {
+                let component = match self.find_component_by_href(&checklist.href) {
+                    Some(ComponentTarget::Component(component)) => Some(component),
+                    _ => None,
+                };
+                let benchmark = match component.map(|c| &c.content) {
+                    Some(ComponentContent::XCCDFBenchmark(benchmark)) => Some(benchmark.info(lang)),
+                    _ => None,
+                };
+                let check_refs = self
+                    .resolve_check_refs(ds, checklist)
+                    .into_iter()
+                    .map(|resolution| info::CheckRefInfo {
+                        component_id: resolution.component_id().map(String::from),
+                        href: resolution.href,
+                        resolved: resolution.resolved,
+                    })
+                    .collect();
+                checklists.push(info::ChecklistInfo {
+                    ref_id: checklist.id.to_string(),
+                    href: checklist.href.to_string(),
+                    component_id: component.map(|c| c.id.to_string()),
+                    benchmark,
+                    check_refs,
+                });
+            }
+            let extended_components = ds
+                .extended_components
+                .iter()
+                .map(|component_ref| {
+                    let component = match self.find_component_by_href(&component_ref.href) {
+                        Some(ComponentTarget::ExtendedComponent(component)) => Some(component),
+                        _ => None,
+                    };
+                    info::ExtendedComponentInfo {
+                        ref_id: component_ref.id.to_string(),
+                        href: component_ref.href.to_string(),
+                        component_id: component.map(|c| c.id.to_string()),
+                        element_name: component.and_then(|c| c.element_name().map(String::from)),
+                        element_ns: component.and_then(|c| c.element_ns()),
+                    }
+                })
+                .collect();
+            data_streams.push(info::DataStreamInfo {
+                id: ds.id.to_string(),
+                use_case: ds.use_case.to_string(),
+                scap_version: ds.scap_version.to_string(),
+                checklists,
+                extended_components,
+                vulnerabilities: self.vulnerabilities(ds),
+            });
+        }
+        info::CollectionInfo {
+            document_type: String::from("SCAP Source Data Stream"),
+            id: self.id.to_string(),
+            data_streams,
+            signers: self.signatures.iter().filter_map(signer_subject).collect(),
+        }
+    }
+
+    /// Where a component reference points, painted if it can't be used
+    /// as is.
+    fn target_cell(&self, href: &str, colored: bool) -> String {
+        match self.find_component_by_href(href) {
+            Some(ComponentTarget::Component(component)) => component.id.to_string(),
+            Some(ComponentTarget::ExtendedComponent(component)) => {
+                format!("{} (extended: {})", component.id, component.describe())
+            }
+            Some(ComponentTarget::Remote(url)) => {
+                paint(&format!("{} (remote)", url), Some(Color::Yellow), colored)
+            }
+            None => paint(&format!("{} (not found)", href), Some(Color::Red), colored),
+        }
+    }
+
+    pub fn write_information<W: Write>(
+        &self,
+        out: &mut W,
+        lang: Option<&str>,
+        colored: bool,
+    ) -> io::Result<()> {
+        writeln!(out, "Document type: SCAP Source Data Stream")?;
+        for signer in self.signatures.iter().filter_map(signer_subject) {
+            writeln!(out, "Signed by: {}", signer)?;
+        }
+        for ds in self.data_streams.iter() {
+            writeln!(out)?;
+            writeln!(
+                out,
+                "Stream: {} (SCAP {}, {})",
+                ds.id, ds.scap_version, ds.use_case
+            )?;
+            let sections = [
+                ("checklist", &ds.checklists),
+                ("check", &ds.checks),
+                ("dictionary", &ds.dictionaries),
+                ("extended", &ds.extended_components),
+            ];
+            let mut table = Table::new(&["TYPE", "REF ID", "COMPONENT"]);
+            for (kind, refs) in sections {
+                for component_ref in refs.iter() {
+                    let target = self.target_cell(&component_ref.href, colored);
+                    table.row(vec![kind.to_string(), component_ref.id.to_string(), target]);
+                }
+            }
+            table.write(out, 2)?;
+            let remote = ds.checklists.iter().any(|checklist| {
+                matches!(
+                    self.find_component_by_href(&checklist.href),
+                    Some(ComponentTarget::Remote(_))
+                )
+            });
+            if remote {
+                writeln!(
+                    out,
+                    "Use --fetch-remote-resources to download the remote checklists"
+                )?;
+            }
+            if ds.use_case == UseCase::Vulnerability {
+                writeln!(out)?;
+                self.write_vulnerabilities(out, ds, colored)?;
+                continue;
+            }
+            for checklist in ds.checklists.iter() {
+                if let Some(ComponentTarget::Component(component)) =
+                    self.find_component_by_href(&checklist.href)
+                {
+                    if let ComponentContent::XCCDFBenchmark(benchmark) = &component.content {
+                        writeln!(out)?;
+                        benchmark.write_information(out, lang, colored)?;
+                        self.write_check_refs(out, ds, checklist, colored)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// CVEs checked by the OVAL definitions of the check components of a
+    /// vulnerability data stream, empty for other use cases.
+    pub fn vulnerabilities(&self, ds: &DataStream) -> Vec<oval::CveCoverage> {
+        if ds.use_case != UseCase::Vulnerability {
+            return Vec::new();
+        }
+        let documents: Vec<&oval::OvalDefinitions> = ds
+            .checks
+            .iter()
+            .filter_map(|check| match self.find_component_by_href(&check.href) {
+                Some(ComponentTarget::Component(component)) => component.oval_definitions(),
+                _ => None,
+            })
+            .collect();
+        oval::cve_coverage(&documents)
+    }
+
+    fn write_vulnerabilities<W: Write>(
+        &self,
+        out: &mut W,
+        ds: &DataStream,
+        colored: bool,
+    ) -> io::Result<()> {
+        let vulnerabilities = self.vulnerabilities(ds);
+        writeln!(out, "Vulnerabilities: {}", vulnerabilities.len())?;
+        let mut table = Table::new(&["CVE", "CVSS", "SEVERITY", "PLATFORMS", "DEFINITIONS"]);
+        for vulnerability in vulnerabilities.iter() {
+            let (score, severity) = match &vulnerability.cvss {
+                Some(cvss) => {
+                    let severity = cvss.severity();
+                    let color = severity_color(severity.as_str());
+                    let severity = paint(severity.as_str(), color, colored);
+                    (format!("{:.1}", cvss.base_score()), severity)
+                }
+                None => (String::new(), String::new()),
+            };
+            table.row(vec![
+                vulnerability.cve.clone(),
+                score,
+                severity,
+                vulnerability.platforms.join(", "),
+                vulnerability.definitions.join(", "),
+            ]);
+        }
+        if !table.is_empty() {
+            table.write(out, 2)?;
+        }
+        Ok(())
+    }
+
+    fn write_check_refs<W: Write>(
+        &self,
+        out: &mut W,
+        ds: &DataStream,
+        checklist: &ComponentRef,
+        colored: bool,
+    ) -> io::Result<()> {
+        let resolutions = self.resolve_check_refs(ds, checklist);
+        if resolutions.is_empty() {
+            return Ok(());
+        }
+        writeln!(out)?;
+        let mut table = Table::new(&["CHECK CONTENT", "COMPONENT"]);
+        for resolution in resolutions.iter() {
+            let component = match resolution.component_id() {
+                Some(id) => id.to_string(),
+                None => paint("not found in the data stream", Some(Color::Red), colored),
+            };
+            table.row(vec![resolution.href.clone(), component]);
+        }
+        table.write(out, 2)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn schematron_version(&self) -> &str {
+        &self.schematron_version
+    }
+
+    pub fn data_streams(&self) -> &[DataStream] {
+        &self.data_streams
+    }
+
+    pub fn components(&self) -> &[Component] {
+        &self.components
+    }
+
+    pub fn extended_components(&self) -> &[ExtendedComponent] {
+        &self.extended_components
+    }
+
+    /// Timestamps of the data streams, the components and the extended
+    /// components, each with a description like `component 'id'`.
+    pub fn timestamps(&self) -> Vec<(String, DateTime<FixedOffset>)> {
+        let data_streams = self
+            .data_streams
+            .iter()
+            .map(|ds| (format!("data stream '{}'", ds.id), ds.timestamp));
+        let components = self
+            .components
+            .iter()
+            .map(|c| (format!("component '{}'", c.id), c.timestamp));
+        let extended_components = self
+            .extended_components
+            .iter()
+            .map(|c| (format!("extended component '{}'", c.id), c.timestamp));
+        data_streams
+            .chain(components)
+            .chain(extended_components)
+            .filter_map(|(what, timestamp)| Some((what, timestamp?)))
+            .collect()
+    }
+
+    pub fn component(&self, id: &str) -> Option<&Component> {
+        self.components.get(*self.component_index.get(id)?)
+    }
+
+    pub fn extended_component(&self, id: &str) -> Option<&ExtendedComponent> {
+        self.extended_components
+            .get(*self.extended_component_index.get(id)?)
+    }
+}
+
+/// Maps the IDs to their positions, the first one wins if an ID repeats.
+fn index_by_id<'a>(ids: impl Iterator<Item = &'a str>) -> HashMap<String, usize> {
+    let mut index = HashMap::new();
+    for (i, id) in ids.enumerate() {
+        index.entry(id.to_string()).or_insert(i);
+    }
+    index
+}
+
+fn signer_subject(signature: &dsig::Signature) -> Option<String> {
+    signature.signer().and_then(|c| c.ok()).map(|c| c.subject)
+}
+
+/// Component-ref pointing to a missing component, see
+/// [`DataStreamCollection::dangling_refs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DanglingRef {
+    pub data_stream: String,
+    /// Element containing the component-ref, e.g. `checklists`
+    pub list: &'static str,
+    pub ref_id: String,
+    pub href: String,
+}
+
+/// OVAL definition referenced by a rule but missing from the component, see
+/// [`DataStreamCollection::missing_oval_definitions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingDefinition {
+    pub rule_id: String,
+    pub definition_id: String,
+    pub component_id: String,
+}
+
+/// Where a `check-content-ref` of a checklist leads, see
+/// [`DataStreamCollection::resolve_check_refs`].
+#[derive(Debug)]
+pub struct CheckRefResolution<'a> {
+    pub href: String,
+    /// The href after the catalog mapping
+    pub resolved: String,
+    pub target: Option<ComponentTarget<'a>>,
+}
+
+impl CheckRefResolution<'_> {
+    /// ID of the component or extended component of the collection.
+    pub fn component_id(&self) -> Option<&str> {
+        match &self.target {
+            Some(ComponentTarget::Component(component)) => Some(&component.id),
+            Some(ComponentTarget::ExtendedComponent(component)) => Some(&component.id),
+            _ => None,
+        }
     }
 }
 
 #[derive(Debug)]
-struct DataStream {
-    id: String,
-    use_case: String,
-    scap_version: String,
-    timestamp: Option<String>,
-    dictionaries: Vec<ComponentRef>,
-    checklists: Vec<ComponentRef>,
-    checks: Vec<ComponentRef>,
-    extended_components: Vec<ComponentRef>,
+pub struct SignatureCheck {
+    pub signed_item: String,
+    pub signature_id: Option<String>,
+    /// Subject of the signing certificate
+    pub signer: Option<String>,
+    pub problems: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct DataStream {
+    pub(crate) id: Arc<str>,
+    pub(crate) use_case: UseCase,
+    pub(crate) scap_version: ScapVersion,
+    pub(crate) timestamp: Option<DateTime<FixedOffset>>,
+    pub(crate) dictionaries: Vec<ComponentRef>,
+    pub(crate) checklists: Vec<ComponentRef>,
+    pub(crate) checks: Vec<ComponentRef>,
+    pub(crate) extended_components: Vec<ComponentRef>,
 }
 
 impl DataStream {
-    pub fn from_xml(el: &Element) -> Result<DataStream, String> {
+    pub fn from_xml(el: &Element) -> Result<DataStream, Error> {
+        DataStream::from_xml_with_options(el, &ParseOptions::default())
+    }
+
+    pub fn from_xml_with_options(el: &Element, opts: &ParseOptions) -> Result<DataStream, Error> {
         let id = require_attr(el, "id")?;
-        let use_case = require_attr_options(
-            el,
-            "use-case",
-            vec!["CONFIGURATION", "VULNERABILITY", "INVENTORY", "OTHER"],
-        )?;
-        let scap_version =
-            require_attr_options(el, "scap-version", vec!["1.0", "1.1", "1.2", "1.3"])?;
-        let timestamp = get_attr(el, "timestamp");
+        let use_case = require_attr_parsed(el, "use-case")?;
+        let scap_version = require_attr_parsed(el, "scap-version")?;
+        let timestamp = get_attr_date_time(el, "timestamp", opts)?;
         let dictionaries = DataStream::get_component_ref_vec(el, "dictionaries")?;
         let checklists = DataStream::get_component_ref_vec(el, "checklists")?;
         let checks = DataStream::get_component_ref_vec(el, "checks")?;
@@ -132,98 +896,279 @@ impl DataStream {
         })
     }
 
+    fn component_refs(&self) -> impl Iterator<Item = &ComponentRef> {
+        self.dictionaries
+            .iter()
+            .chain(self.checklists.iter())
+            .chain(self.checks.iter())
+            .chain(self.extended_components.iter())
+    }
+
     fn get_component_ref_vec(
         data_stream_el: &Element,
         component_name: &str,
-    ) -> Result<Vec<ComponentRef>, String> {
+    ) -> Result<Vec<ComponentRef>, Error> {
         let mut component_refs = Vec::new();
         if let Some(component_el) = data_stream_el.get_child(component_name, SCAP12_NS) {
-            for component_ref_el in component_el.children() {
+            for_each_child(component_el, |component_ref_el| {
                 let component_ref = ComponentRef::from_xml(component_ref_el)?;
                 component_refs.push(component_ref);
-            }
+                Ok(())
+            })?;
         }
         Ok(component_refs)
     }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn use_case(&self) -> UseCase {
+        self.use_case
+    }
+
+    pub fn scap_version(&self) -> ScapVersion {
+        self.scap_version
+    }
+
+    pub fn timestamp(&self) -> Option<DateTime<FixedOffset>> {
+        self.timestamp
+    }
+
+    pub fn dictionaries(&self) -> &[ComponentRef] {
+        &self.dictionaries
+    }
+
+    pub fn checklists(&self) -> &[ComponentRef] {
+        &self.checklists
+    }
+
+    pub fn checks(&self) -> &[ComponentRef] {
+        &self.checks
+    }
+
+    pub fn extended_components(&self) -> &[ComponentRef] {
+        &self.extended_components
+    }
 }
 
 #[derive(Debug)]
-enum ComponentContent {
-    XCCDFBenchmark(xccdf::Benchmark),
+pub enum ComponentTarget<'a> {
+    Component(&'a Component),
+    ExtendedComponent(&'a ExtendedComponent),
+    Remote(String),
+}
+
+#[derive(Debug)]
+pub(crate) enum ComponentContent {
+    XCCDFBenchmark(Box<xccdf::Benchmark>),
+    OvalDefinitions(Box<oval::OvalDefinitions>),
     NotImplemented,
 }
 
 #[derive(Debug)]
-struct Component {
-    id: String,
-    timestamp: String,
-    component_name: String,
-    component_ns: String,
-    content: ComponentContent,
+pub struct Component {
+    pub(crate) id: Arc<str>,
+    /// Missing for remote components, and for invalid values in the
+    /// lenient mode
+    pub(crate) timestamp: Option<DateTime<FixedOffset>>,
+    pub(crate) component_name: String,
+    pub(crate) component_ns: String,
+    /// `generator/schema_version` of OVAL and OCIL content
+    pub(crate) schema_version: Option<String>,
+    pub(crate) content: ComponentContent,
 }
 
 impl Component {
-    fn from_xml(el: &Element) -> Result<Component, String> {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn benchmark(&self) -> Option<&xccdf::Benchmark> {
+        match &self.content {
+            ComponentContent::XCCDFBenchmark(benchmark) => Some(benchmark),
+            _ => None,
+        }
+    }
+
+    pub fn oval_definitions(&self) -> Option<&oval::OvalDefinitions> {
+        match &self.content {
+            ComponentContent::OvalDefinitions(definitions) => Some(definitions),
+            _ => None,
+        }
+    }
+
+    fn from_xml(el: &Element, opts: &ParseOptions) -> Result<Component, Error> {
         let id = require_attr(el, "id")?;
-        let timestamp = require_attr(el, "timestamp")?;
+        let timestamp = require_attr_date_time(el, "timestamp", opts)?;
         if let Some(component) = el.children().next() {
-            let component_name = component.name().to_string();
-            let component_ns = component.ns();
-            let mut content = ComponentContent::NotImplemented;
-            if component_ns == xccdf::XCCDF12_NS && component_name == "Benchmark" {
-                content = ComponentContent::XCCDFBenchmark(xccdf::Benchmark::from_xml(component)?);
-            }
-            Ok(Component {
-                id,
-                timestamp,
-                component_name,
-                component_ns,
-                content,
-            })
+            Component::from_content(id, timestamp, component, opts)
         } else {
-            Err(format!("component '{}' doesn't have any child element", id))
+            Err(Error::Content(format!(
+                "component '{}' doesn't have any child element",
+                id
+            )))
         }
     }
+
+    /// Component with the given root element of the content. Remote
+    /// components have no timestamp.
+    fn from_content(
+        id: Arc<str>,
+        timestamp: Option<DateTime<FixedOffset>>,
+        component: &Element,
+        opts: &ParseOptions,
+    ) -> Result<Component, Error> {
+        let _span =
+            tracing::debug_span!("component", id = %id, content = component.name()).entered();
+        let component_name = component.name().to_string();
+        let component_ns = component.ns();
+        let schema_version = component
+            .children()
+            .find(|child| child.name() == "generator")
+            .and_then(|generator| {
+                generator
+                    .children()
+                    .find(|child| child.name() == "schema_version")
+            })
+            .map(|version| version.text().trim().to_string());
+        let mut content = ComponentContent::NotImplemented;
+        if component_ns == xccdf::XCCDF12_NS && component_name == "Benchmark" {
+            content = ComponentContent::XCCDFBenchmark(Box::new(
+                xccdf::Benchmark::from_xml_with_options(component, opts)?,
+            ));
+        } else if component.is("oval_definitions", oval::OVAL_DEF_NS) {
+            let parsed = oval::OvalDefinitions::from_xml_with_options(component, opts)
+                .map(|definitions| ComponentContent::OvalDefinitions(Box::new(definitions)));
+            content = opts.recover(parsed, ComponentContent::NotImplemented)?;
+        }
+        Ok(Component {
+            id,
+            timestamp,
+            component_name,
+            component_ns,
+            schema_version,
+            content,
+        })
+    }
+
+    pub fn timestamp(&self) -> Option<DateTime<FixedOffset>> {
+        self.timestamp
+    }
+
+    /// Local name of the root element of the content, e.g. `Benchmark`.
+    pub fn element_name(&self) -> &str {
+        &self.component_name
+    }
+
+    pub fn element_ns(&self) -> &str {
+        &self.component_ns
+    }
+
+    pub fn schema_version(&self) -> Option<&str> {
+        self.schema_version.as_deref()
+    }
 }
 
+/// Content of an extended component, parsed when its type is known.
 #[derive(Debug)]
-struct ExtendedComponent {
-    id: String,
-    timestamp: String,
+pub enum ExtendedContent {
+    /// CPE applicability language `platform-specification`
+    PlatformSpecification(xccdf::PlatformSpecification),
+    /// Content the parser doesn't know, e.g. vendor data, or none
+    Other,
+}
+
+#[derive(Debug)]
+pub struct ExtendedComponent {
+    pub(crate) id: Arc<str>,
+    /// Missing for invalid values in the lenient mode
+    pub(crate) timestamp: Option<DateTime<FixedOffset>>,
+    /// Root element of the payload as it is in the document, `None` if the
+    /// extended component is empty
+    pub(crate) element: Option<Element>,
+    pub(crate) content: ExtendedContent,
 }
 
 impl ExtendedComponent {
-    fn from_xml(el: &Element) -> Result<ExtendedComponent, String> {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn from_xml(el: &Element, opts: &ParseOptions) -> Result<ExtendedComponent, Error> {
         let id = require_attr(el, "id")?;
-        let timestamp = require_attr(el, "timestamp")?;
-        Ok(ExtendedComponent { id, timestamp })
+        let timestamp = require_attr_date_time(el, "timestamp", opts)?;
+        let element = el.children().next().cloned();
+        let content = match &element {
+            Some(child) if child.is("platform-specification", xccdf::CPE_LANG_NS) => {
+                let parsed = xccdf::PlatformSpecification::from_xml(child)
+                    .map(ExtendedContent::PlatformSpecification);
+                opts.recover(parsed, ExtendedContent::Other)?
+            }
+            _ => ExtendedContent::Other,
+        };
+        Ok(ExtendedComponent {
+            id,
+            timestamp,
+            element,
+            content,
+        })
     }
-}
 
-#[derive(Debug)]
-struct Signature {
-    id: String,
-}
+    pub fn timestamp(&self) -> Option<DateTime<FixedOffset>> {
+        self.timestamp
+    }
 
-impl Signature {
-    fn from_xml(el: &Element) -> Result<Signature, String> {
-        let id = require_attr(el, "id")?;
-        Ok(Signature { id })
+    pub fn element(&self) -> Option<&Element> {
+        self.element.as_ref()
+    }
+
+    /// Local name of the root element of the payload, e.g.
+    /// `platform-specification`.
+    pub fn element_name(&self) -> Option<&str> {
+        self.element.as_ref().map(|el| el.name())
+    }
+
+    pub fn element_ns(&self) -> Option<String> {
+        self.element.as_ref().map(|el| el.ns())
+    }
+
+    pub fn content(&self) -> &ExtendedContent {
+        &self.content
+    }
+
+    pub fn platform_specification(&self) -> Option<&xccdf::PlatformSpecification> {
+        match &self.content {
+            ExtendedContent::PlatformSpecification(specification) => Some(specification),
+            ExtendedContent::Other => None,
+        }
+    }
+
+    /// Root element of the payload and its namespace, e.g.
+    /// `platform-specification in http://cpe.mitre.org/language/2.0`.
+    pub fn describe(&self) -> String {
+        match &self.element {
+            Some(el) => format!("{} in {}", el.name(), el.ns()),
+            None => String::from("empty"),
+        }
     }
 }
 
 #[derive(Debug)]
-struct ComponentRef {
-    id: String,
-    type_: Option<String>,
-    href: String,
-    catalog: Option<Catalog>,
+pub struct ComponentRef {
+    pub(crate) id: Arc<str>,
+    pub(crate) type_: Option<Arc<str>>,
+    pub(crate) href: Arc<str>,
+    pub(crate) catalog: Option<Catalog>,
 }
 
 impl ComponentRef {
-    fn from_xml(el: &Element) -> Result<ComponentRef, String> {
+    fn from_xml(el: &Element) -> Result<ComponentRef, Error> {
         if !el.is("component-ref", SCAP12_NS) {
-            return Err(format!("Unexpected element '{}'", el.name()));
+            return Err(Error::unexpected_element(
+                el.name(),
+                Some("ds:component-ref"),
+            ));
         }
         let id = require_attr(el, "id")?;
         let type_ = get_attr(el, "xlink:type");
@@ -239,46 +1184,78 @@ impl ComponentRef {
             catalog,
         })
     }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn href(&self) -> &str {
+        &self.href
+    }
+
+    pub fn catalog(&self) -> Option<&Catalog> {
+        self.catalog.as_ref()
+    }
 }
 
 #[derive(Debug)]
-struct Catalog {
-    uris: Vec<CatURI>,
-    rewrite_uris: Vec<RewriteURI>,
+pub struct Catalog {
+    pub(crate) uris: Vec<CatURI>,
+    pub(crate) rewrite_uris: Vec<RewriteURI>,
 }
 
 impl Catalog {
-    fn from_xml(el: &Element) -> Result<Catalog, String> {
+    fn from_xml(el: &Element) -> Result<Catalog, Error> {
         if !el.is("catalog", CAT_NS) {
-            return Err(format!("Unexpected element '{}'", el.name()));
+            return Err(Error::unexpected_element(el.name(), Some("cat:catalog")));
         }
         let mut uris = Vec::new();
         let mut rewrite_uris = Vec::new();
-        for child in el.children() {
+        for_each_child(el, |child| {
             if child.is("uri", CAT_NS) {
                 uris.push(CatURI::from_xml(child)?);
             } else if child.is("rewriteURI", CAT_NS) {
                 rewrite_uris.push(RewriteURI::from_xml(child)?);
             } else {
-                return Err(format!(
-                    "Unexpected element '{}', expected either 'uri' or 'rewriteURI'",
-                    el.name()
+                return Err(Error::unexpected_element(
+                    child.name(),
+                    Some("either 'uri' or 'rewriteURI'"),
                 ));
             }
-        }
+            Ok(())
+        })?;
 
         Ok(Catalog { uris, rewrite_uris })
     }
+
+    /// Maps a URI through the catalog. Exact `uri` entries win over
+    /// `rewriteURI` entries, of which the longest matching prefix is used.
+    pub fn resolve(&self, uri: &str) -> Option<String> {
+        if let Some(entry) = self.uris.iter().find(|entry| &*entry.name == uri) {
+            return Some(entry.uri.to_string());
+        }
+        self.rewrite_uris
+            .iter()
+            .filter(|rewrite| uri.starts_with(&*rewrite.uri_start_string))
+            .max_by_key(|rewrite| rewrite.uri_start_string.len())
+            .map(|rewrite| {
+                format!(
+                    "{}{}",
+                    rewrite.rewrite_prefix,
+                    &uri[rewrite.uri_start_string.len()..]
+                )
+            })
+    }
 }
 
 #[derive(Debug)]
-struct CatURI {
-    name: String,
-    uri: String,
+pub struct CatURI {
+    pub(crate) name: Arc<str>,
+    pub(crate) uri: Arc<str>,
 }
 
 impl CatURI {
-    fn from_xml(el: &Element) -> Result<CatURI, String> {
+    fn from_xml(el: &Element) -> Result<CatURI, Error> {
         let name = require_attr(el, "name")?;
         let uri = require_attr(el, "uri")?;
         Ok(CatURI { name, uri })
@@ -286,13 +1263,13 @@ impl CatURI {
 }
 
 #[derive(Debug)]
-struct RewriteURI {
-    uri_start_string: String,
-    rewrite_prefix: String,
+pub struct RewriteURI {
+    pub(crate) uri_start_string: Arc<str>,
+    pub(crate) rewrite_prefix: Arc<str>,
 }
 
 impl RewriteURI {
-    fn from_xml(el: &Element) -> Result<RewriteURI, String> {
+    fn from_xml(el: &Element) -> Result<RewriteURI, Error> {
         let uri_start_string = require_attr(el, "uriStartString")?;
         let rewrite_prefix = require_attr(el, "rewritePrefix")?;
         Ok(RewriteURI {
@@ -301,3 +1278,302 @@ impl RewriteURI {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::misc;
+
+    fn collection() -> DataStreamCollection {
+        let el: Element = format!(
+            "<data-stream-collection xmlns=\"{ns}\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             xmlns:cat=\"{cat}\" id=\"c\" schematron-version=\"1.3\">\
+             <data-stream id=\"d\" use-case=\"OTHER\" scap-version=\"1.3\">\
+             <checklists><component-ref id=\"cref-xccdf\" xlink:href=\"#xccdf\">\
+             <cat:catalog><cat:uri name=\"oval.xml\" uri=\"#cref-oval\"/>\
+             <cat:rewriteURI uriStartString=\"https://example.org/\" rewritePrefix=\"#cref-\"/>\
+             </cat:catalog></component-ref></checklists>\
+             <checks><component-ref id=\"cref-oval\" xlink:href=\"#oval\"/>\
+             <component-ref id=\"cref-remote\" xlink:href=\"https://example.org/oval.xml\"/></checks>\
+             </data-stream>\
+             <component id=\"xccdf\" timestamp=\"2022-01-01T00:00:00\"><foo xmlns=\"urn:example\"/></component>\
+             <component id=\"oval\" timestamp=\"2022-01-01T00:00:00\"><foo xmlns=\"urn:example\"/></component>\
+             <extended-component id=\"ext\" timestamp=\"2022-01-01T00:00:00\"/>\
+             </data-stream-collection>",
+            ns = SCAP12_NS,
+            cat = CAT_NS
+        )
+        .parse()
+        .unwrap();
+        DataStreamCollection::from_xml(&el).unwrap()
+    }
+
+    #[test]
+    fn test_find_component_by_href() {
+        let collection = collection();
+        assert!(matches!(
+            collection.find_component_by_href("#xccdf"),
+            Some(ComponentTarget::Component(c)) if c.id() == "xccdf"
+        ));
+        assert!(matches!(
+            collection.find_component_by_href("#ext"),
+            Some(ComponentTarget::ExtendedComponent(c)) if c.id() == "ext"
+        ));
+        assert!(matches!(
+            collection.find_component_by_href("#cref-oval"),
+            Some(ComponentTarget::Component(c)) if c.id() == "oval"
+        ));
+        assert!(matches!(
+            collection.find_component_by_href("https://example.org/oval.xml"),
+            Some(ComponentTarget::Remote(url)) if url == "https://example.org/oval.xml"
+        ));
+        assert!(collection.find_component_by_href("#missing").is_none());
+    }
+
+    #[test]
+    fn test_write_information() {
+        let mut out = Vec::new();
+        collection()
+            .write_information(&mut out, None, true)
+            .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("Stream: d (SCAP 1.3, OTHER)\n  TYPE       REF ID       COMPONENT\n"));
+        assert!(text.contains("  check      cref-oval    oval\n"));
+        assert!(text.contains(
+            "  check      cref-remote  \x1b[33mhttps://example.org/oval.xml (remote)\x1b[0m\n"
+        ));
+    }
+
+    #[test]
+    fn test_resolve_check_refs() {
+        let data = std::fs::read("data/simple.xml").unwrap();
+        let (root, _) = misc::parse(&data).unwrap();
+        let collection = DataStreamCollection::from_xml(&root).unwrap();
+        let ds = &collection.data_streams[0];
+        let resolutions = collection.resolve_check_refs(ds, &ds.checklists[0]);
+        assert_eq!(resolutions.len(), 1);
+        assert_eq!(resolutions[0].href, "test_single_rule.oval.xml");
+        assert_eq!(
+            resolutions[0].resolved,
+            "#scap_org.open-scap_cref_test_single_rule.oval.xml"
+        );
+        assert_eq!(
+            resolutions[0].component_id(),
+            Some("scap_org.open-scap_comp_test_single_rule.oval.xml")
+        );
+    }
+
+    #[test]
+    fn test_missing_oval_definitions() {
+        let data = std::fs::read_to_string("data/simple.xml").unwrap();
+        let (root, _) = misc::parse(data.as_bytes()).unwrap();
+        let collection = DataStreamCollection::from_xml(&root).unwrap();
+        let oval = collection
+            .component("scap_org.open-scap_comp_test_single_rule.oval.xml")
+            .and_then(|c| c.oval_definitions())
+            .unwrap();
+        assert_eq!(oval.definitions().len(), 1);
+        assert!(collection.missing_oval_definitions().is_empty());
+
+        let data = data.replace("name=\"oval:x:def:1\"", "name=\"oval:x:def:2\"");
+        let (root, _) = misc::parse(data.as_bytes()).unwrap();
+        let collection = DataStreamCollection::from_xml(&root).unwrap();
+        assert_eq!(
+            collection.missing_oval_definitions(),
+            vec![MissingDefinition {
+                rule_id: String::from("xccdf_com.example.www_rule_test-pass"),
+                definition_id: String::from("oval:x:def:2"),
+                component_id: String::from("scap_org.open-scap_comp_test_single_rule.oval.xml"),
+            }]
+        );
+
+        // Malformed OVAL definitions are a warning in the lenient mode.
+        let data = data.replace("id=\"oval:x:def:1\"", "");
+        let (root, _) = misc::parse(data.as_bytes()).unwrap();
+        assert!(DataStreamCollection::from_xml(&root).is_err());
+        let opts = ParseOptions::lenient();
+        let collection = DataStreamCollection::from_xml_with_options(&root, &opts).unwrap();
+        let component = collection
+            .component("scap_org.open-scap_comp_test_single_rule.oval.xml")
+            .unwrap();
+        assert!(component.oval_definitions().is_none());
+        assert_eq!(component.element_name(), "oval_definitions");
+        assert_eq!(opts.take_warnings().len(), 1);
+    }
+
+    #[test]
+    fn test_vulnerabilities() {
+        let el: Element = format!(
+            "<data-stream-collection xmlns=\"{ns}\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             id=\"c\" schematron-version=\"1.3\">\
+             <data-stream id=\"d\" use-case=\"VULNERABILITY\" scap-version=\"1.3\">\
+             <checks><component-ref id=\"cref-oval\" xlink:href=\"#oval\"/></checks></data-stream>\
+             <component id=\"oval\" timestamp=\"2022-01-01T00:00:00\">\
+             <oval_definitions xmlns=\"{oval}\"><definitions>\
+             <definition id=\"oval:x:def:2\" version=\"1\" class=\"patch\"><metadata><title>b</title>\
+             <affected family=\"unix\"><platform>RHEL 9</platform></affected>\
+             <reference source=\"CVE\" ref_id=\"CVE-2021-44228\"/></metadata></definition>\
+             <definition id=\"oval:x:def:1\" version=\"1\" class=\"patch\"><metadata><title>a</title>\
+             <affected family=\"unix\"><platform>RHEL 8</platform></affected>\
+             <reference source=\"CVE\" ref_id=\"CVE-2021-44228\"/>\
+             <reference source=\"CVE\" ref_id=\"CVE-2014-0160\"/></metadata></definition>\
+             </definitions></oval_definitions></component></data-stream-collection>",
+            ns = SCAP12_NS,
+            oval = oval::OVAL_DEF_NS
+        )
+        .parse()
+        .unwrap();
+        let vulnerable = DataStreamCollection::from_xml(&el).unwrap();
+        let vulnerabilities = vulnerable.vulnerabilities(&vulnerable.data_streams()[0]);
+        assert_eq!(vulnerabilities.len(), 2);
+        assert_eq!(vulnerabilities[0].cve, "CVE-2014-0160");
+        assert_eq!(vulnerabilities[1].platforms, ["RHEL 9", "RHEL 8"]);
+        assert_eq!(
+            vulnerabilities[1].definitions,
+            ["oval:x:def:2", "oval:x:def:1"]
+        );
+        assert!(collection()
+            .vulnerabilities(&collection().data_streams()[0])
+            .is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "network")]
+    fn test_remote_components() {
+        let mut collection = collection();
+        let url = "https://example.org/oval.xml";
+        let root: Element = "<foo xmlns=\"urn:example\"/>".parse().unwrap();
+        collection
+            .add_remote_component(url, &root, &ParseOptions::default())
+            .unwrap();
+        assert!(matches!(
+            collection.find_component_by_href(url),
+            Some(ComponentTarget::Component(c)) if c.id() == url && c.element_name() == "foo"
+        ));
+        // Already fetched, so nothing is downloaded.
+        let fetched = collection
+            .fetch_remote_components(&ParseOptions::default(), &FetchOptions::default())
+            .unwrap();
+        assert_eq!(fetched, 0);
+    }
+
+    #[test]
+    fn test_dangling_refs() {
+        assert!(collection().dangling_refs().is_empty());
+        let el: Element = format!(
+            "<data-stream-collection xmlns=\"{ns}\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             id=\"c\" schematron-version=\"1.3\">\
+             <data-stream id=\"d\" use-case=\"OTHER\" scap-version=\"1.3\">\
+             <checks><component-ref id=\"cref-oval\" xlink:href=\"#oval\"/>\
+             <component-ref id=\"cref-missing\" xlink:href=\"#missing\"/></checks></data-stream>\
+             <component id=\"oval\" timestamp=\"2022-01-01T00:00:00\"><foo xmlns=\"urn:example\"/></component>\
+             </data-stream-collection>",
+            ns = SCAP12_NS
+        )
+        .parse()
+        .unwrap();
+        let collection = DataStreamCollection::from_xml(&el).unwrap();
+        assert_eq!(
+            collection.dangling_refs(),
+            vec![DanglingRef {
+                data_stream: String::from("d"),
+                list: "checks",
+                ref_id: String::from("cref-missing"),
+                href: String::from("#missing"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_indexed_lookups() {
+        let collection = collection();
+        assert_eq!(collection.component("oval").map(|c| c.id()), Some("oval"));
+        assert!(collection.component("ext").is_none());
+        assert_eq!(
+            collection.extended_component("ext").map(|c| c.id()),
+            Some("ext")
+        );
+    }
+
+    #[test]
+    fn test_extended_content() {
+        let el: Element = format!(
+            "<data-stream-collection xmlns=\"{ns}\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             id=\"c\" schematron-version=\"1.3\">\
+             <data-stream id=\"d\" use-case=\"OTHER\" scap-version=\"1.3\"><extended-components>\
+             <component-ref id=\"cref-cpe\" xlink:href=\"#cpe\"/>\
+             <component-ref id=\"cref-vendor\" xlink:href=\"#vendor\"/></extended-components>\
+             </data-stream>\
+             <component id=\"x\" timestamp=\"2022-01-01T00:00:00\"><foo xmlns=\"urn:example\"/></component>\
+             <extended-component id=\"cpe\" timestamp=\"2022-01-01T00:00:00\">\
+             <platform-specification xmlns=\"{cpe}\"><platform id=\"machine\">\
+             <logical-test operator=\"AND\" negate=\"false\"><fact-ref name=\"cpe:/a:machine\"/>\
+             </logical-test></platform></platform-specification></extended-component>\
+             <extended-component id=\"vendor\" timestamp=\"2022-01-01T00:00:00\">\
+             <data xmlns=\"urn:vendor\">x</data></extended-component>\
+             </data-stream-collection>",
+            ns = SCAP12_NS,
+            cpe = xccdf::CPE_LANG_NS
+        )
+        .parse()
+        .unwrap();
+        let collection = DataStreamCollection::from_xml(&el).unwrap();
+        let cpe = collection.extended_component("cpe").unwrap();
+        assert_eq!(cpe.element_name(), Some("platform-specification"));
+        let specification = cpe.platform_specification().unwrap();
+        assert!(specification.platform("machine").is_some());
+        let vendor = collection.extended_component("vendor").unwrap();
+        assert!(vendor.platform_specification().is_none());
+        assert_eq!(vendor.describe(), "data in urn:vendor");
+        assert_eq!(vendor.element().map(|e| e.text()).as_deref(), Some("x"));
+
+        let info = serde_json::to_value(collection.info(None)).unwrap();
+        let extended = &info["data_streams"][0]["extended_components"];
+        assert_eq!(extended[0]["element_name"], "platform-specification");
+        assert_eq!(extended[1]["element_ns"], "urn:vendor");
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let collection = collection();
+        let timestamps = collection.timestamps();
+        assert_eq!(timestamps.len(), 3);
+        assert_eq!(timestamps[0].0, "component 'xccdf'");
+        assert_eq!(timestamps[0].1.timestamp(), 1640995200);
+
+        let el: Element = format!(
+            "<data-stream-collection xmlns=\"{}\" id=\"c\" schematron-version=\"1.3\">\
+             <data-stream id=\"d\" use-case=\"OTHER\" scap-version=\"1.3\"/>\
+             <component id=\"x\" timestamp=\"yesterday\"><foo xmlns=\"urn:example\"/></component>\
+             </data-stream-collection>",
+            SCAP12_NS
+        )
+        .parse()
+        .unwrap();
+        assert!(DataStreamCollection::from_xml(&el).is_err());
+        let opts = ParseOptions::lenient();
+        let collection = DataStreamCollection::from_xml_with_options(&el, &opts).unwrap();
+        assert_eq!(collection.components()[0].timestamp(), None);
+        assert_eq!(opts.take_warnings().len(), 1);
+    }
+
+    #[test]
+    fn test_find_component_by_catalog() {
+        let collection = collection();
+        let catalog = collection.data_streams[0].checklists[0]
+            .catalog
+            .as_ref()
+            .unwrap();
+        assert!(matches!(
+            collection.find_component_by_catalog(catalog, "oval.xml"),
+            Some(ComponentTarget::Component(c)) if c.id() == "oval"
+        ));
+        assert!(matches!(
+            collection.find_component_by_catalog(catalog, "https://example.org/oval"),
+            Some(ComponentTarget::Component(c)) if c.id() == "oval"
+        ));
+        assert!(collection
+            .find_component_by_catalog(catalog, "other.xml")
+            .is_none());
+    }
+}
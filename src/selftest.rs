@@ -0,0 +1,190 @@
+use minidom::Element;
+
+use crate::sds::DataStreamCollection;
+use crate::xccdf::{Benchmark, Tailoring};
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Benchmark,
+    DataStream,
+    Tailoring,
+}
+
+struct Case {
+    name: &'static str,
+    kind: Kind,
+    valid: bool,
+    xml: &'static str,
+}
+
+const CASES: [Case; 13] = [
+    Case {
+        name: "minimal benchmark",
+        kind: Kind::Benchmark,
+        valid: true,
+        xml: r##"<Benchmark xmlns="http://checklists.nist.gov/xccdf/1.2" id="xccdf_org.example_benchmark_b">
+            <status>draft</status><version>1</version></Benchmark>"##,
+    },
+    Case {
+        name: "multiple languages",
+        kind: Kind::Benchmark,
+        valid: true,
+        xml: r##"<Benchmark xmlns="http://checklists.nist.gov/xccdf/1.2" id="xccdf_org.example_benchmark_b">
+            <status>draft</status><title xml:lang="en-US">Title</title><title xml:lang="cs-CZ">Název</title>
+            <description xml:lang="en-US">Text</description><description xml:lang="de-DE">Text</description>
+            <version>1</version></Benchmark>"##,
+    },
+    Case {
+        name: "complex check",
+        kind: Kind::Benchmark,
+        valid: true,
+        xml: r##"<Benchmark xmlns="http://checklists.nist.gov/xccdf/1.2" id="xccdf_org.example_benchmark_b">
+            <status>draft</status><version>1</version>
+            <Rule id="xccdf_org.example_rule_r" severity="medium"><title>R</title>
+            <complex-check operator="AND" negate="false">
+            <check system="http://oval.mitre.org/XMLSchema/oval-definitions-5"><check-content-ref href="oval.xml" name="oval:x:def:1"/></check>
+            <complex-check operator="OR"><check system="http://scap.nist.gov/schema/ocil/2"><check-content-ref href="ocil.xml"/></check></complex-check>
+            </complex-check></Rule></Benchmark>"##,
+    },
+    Case {
+        name: "clusters",
+        kind: Kind::Benchmark,
+        valid: true,
+        xml: r##"<Benchmark xmlns="http://checklists.nist.gov/xccdf/1.2" id="xccdf_org.example_benchmark_b">
+            <status>draft</status><version>1</version>
+            <Profile id="xccdf_org.example_profile_p"><title>P</title><select idref="web" selected="true"/></Profile>
+            <Group id="xccdf_org.example_group_g" cluster-id="web"><title>G</title>
+            <Rule id="xccdf_org.example_rule_r" cluster-id="web" selected="false"><title>R</title></Rule></Group>
+            </Benchmark>"##,
+    },
+    Case {
+        name: "extending profile",
+        kind: Kind::Benchmark,
+        valid: true,
+        xml: r##"<Benchmark xmlns="http://checklists.nist.gov/xccdf/1.2" id="xccdf_org.example_benchmark_b">
+            <status>draft</status><version>1</version>
+            <Profile id="xccdf_org.example_profile_base" abstract="true"><title>Base</title></Profile>
+            <Profile id="xccdf_org.example_profile_p" extends="xccdf_org.example_profile_base"><title>P</title>
+            <select idref="xccdf_org.example_rule_r" selected="1"/></Profile>
+            <Rule id="xccdf_org.example_rule_r"><title>R</title></Rule></Benchmark>"##,
+    },
+    Case {
+        name: "tailored values",
+        kind: Kind::Tailoring,
+        valid: true,
+        xml: r##"<Tailoring xmlns="http://checklists.nist.gov/xccdf/1.2" id="xccdf_org.example_tailoring_t">
+            <benchmark href="ds.xml"/><version time="2022-01-01T00:00:00">1</version>
+            <Profile id="xccdf_org.example_profile_tailored" extends="xccdf_org.example_profile_p"><title>T</title>
+            <set-value idref="xccdf_org.example_value_v">42</set-value>
+            <refine-value idref="xccdf_org.example_value_w" selector="strict"/></Profile></Tailoring>"##,
+    },
+    Case {
+        name: "minimal data stream",
+        kind: Kind::DataStream,
+        valid: true,
+        xml: r##"<data-stream-collection xmlns="http://scap.nist.gov/schema/scap/source/1.2" xmlns:xlink="http://www.w3.org/1999/xlink"
+            id="scap_org.example_collection_c" schematron-version="1.3">
+            <data-stream id="scap_org.example_datastream_d" scap-version="1.3" use-case="OTHER">
+            <checklists><component-ref id="scap_org.example_cref_x" xlink:href="#scap_org.example_comp_x"/></checklists></data-stream>
+            <component id="scap_org.example_comp_x" timestamp="2022-01-01T00:00:00">
+            <Benchmark xmlns="http://checklists.nist.gov/xccdf/1.2" id="xccdf_org.example_benchmark_b">
+            <status>draft</status><version>1</version></Benchmark></component></data-stream-collection>"##,
+    },
+    Case {
+        name: "missing status",
+        kind: Kind::Benchmark,
+        valid: false,
+        xml: r##"<Benchmark xmlns="http://checklists.nist.gov/xccdf/1.2" id="xccdf_org.example_benchmark_b">
+            <version>1</version></Benchmark>"##,
+    },
+    Case {
+        name: "duplicate version",
+        kind: Kind::Benchmark,
+        valid: false,
+        xml: r##"<Benchmark xmlns="http://checklists.nist.gov/xccdf/1.2" id="xccdf_org.example_benchmark_b">
+            <status>draft</status><version>1</version><version>2</version></Benchmark>"##,
+    },
+    Case {
+        name: "invalid select",
+        kind: Kind::Benchmark,
+        valid: false,
+        xml: r##"<Benchmark xmlns="http://checklists.nist.gov/xccdf/1.2" id="xccdf_org.example_benchmark_b">
+            <status>draft</status><version>1</version>
+            <Profile id="xccdf_org.example_profile_p"><title>P</title><select idref="r" selected="maybe"/></Profile>
+            </Benchmark>"##,
+    },
+    Case {
+        name: "XCCDF 1.1 namespace",
+        kind: Kind::Benchmark,
+        valid: false,
+        xml: r##"<Benchmark xmlns="http://checklists.nist.gov/xccdf/1.1" id="b">
+            <status>draft</status><version>1</version></Benchmark>"##,
+    },
+    Case {
+        name: "tailoring without profile",
+        kind: Kind::Tailoring,
+        valid: false,
+        xml: r##"<Tailoring xmlns="http://checklists.nist.gov/xccdf/1.2" id="xccdf_org.example_tailoring_t">
+            <version time="2022-01-01T00:00:00">1</version></Tailoring>"##,
+    },
+    Case {
+        name: "data stream without components",
+        kind: Kind::DataStream,
+        valid: false,
+        xml: r##"<data-stream-collection xmlns="http://scap.nist.gov/schema/scap/source/1.2"
+            id="scap_org.example_collection_c" schematron-version="1.3">
+            <data-stream id="scap_org.example_datastream_d" scap-version="1.3" use-case="OTHER"/>
+            </data-stream-collection>"##,
+    },
+];
+
+#[derive(Debug)]
+pub struct CaseResult {
+    pub name: &'static str,
+    pub passed: bool,
+    /// Parser error of the case, or the reason why it failed
+    pub message: Option<String>,
+}
+
+fn parse(kind: Kind, xml: &str) -> Result<(), String> {
+    let el: Element = xml.parse().map_err(|e| format!("{}", e))?;
+    match kind {
+        Kind::Benchmark => Benchmark::from_xml(&el).map(|_| ()),
+        Kind::DataStream => DataStreamCollection::from_xml(&el).map(|_| ()),
+        Kind::Tailoring => Tailoring::from_xml(&el).map(|_| ()),
+    }
+    .map_err(String::from)
+}
+
+/// Parses the embedded valid and invalid snippets and checks that the
+/// parser accepts exactly the valid ones.
+pub fn run() -> Vec<CaseResult> {
+    CASES
+        .iter()
+        .map(|case| {
+            let (passed, message) = match (parse(case.kind, case.xml), case.valid) {
+                (Ok(()), true) => (true, None),
+                (Err(error), false) => (true, Some(error)),
+                (Ok(()), false) => (false, Some(String::from("accepted invalid content"))),
+                (Err(error), true) => (false, Some(error)),
+            };
+            CaseResult {
+                name: case.name,
+                passed,
+                message,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selftest_passes() {
+        for result in run() {
+            assert!(result.passed, "{}: {:?}", result.name, result.message);
+        }
+    }
+}
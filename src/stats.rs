@@ -0,0 +1,140 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use crate::report::count;
+use crate::xccdf::Benchmark;
+
+#[derive(Debug, Serialize)]
+pub struct ProfileRules {
+    pub profile: String,
+    pub rules: usize,
+}
+
+/// Counts of the items of a benchmark, used to compare releases of content.
+#[derive(Debug, Serialize)]
+pub struct ContentStats {
+    pub benchmark: String,
+    pub version: String,
+    pub profiles: usize,
+    pub groups: usize,
+    pub rules: usize,
+    pub values: usize,
+    pub rules_by_severity: BTreeMap<String, usize>,
+    /// Rules selected by each profile, the profiles that can't be resolved
+    /// are left out
+    pub rules_per_profile: Vec<ProfileRules>,
+    /// Checks by their check system
+    pub check_systems: BTreeMap<String, usize>,
+    /// Fixes by their remediation system
+    pub fix_systems: BTreeMap<String, usize>,
+    /// References of the rules by their href, which identifies the standard
+    pub references: BTreeMap<String, usize>,
+}
+
+pub fn content_stats(benchmark: &Benchmark) -> ContentStats {
+    let rules: Vec<_> = benchmark.iter_rules().map(|r| r.item).collect();
+    ContentStats {
+        benchmark: benchmark.id().to_string(),
+        version: benchmark.version().text().to_string(),
+        profiles: benchmark.profiles().len(),
+        groups: benchmark.iter_groups().count(),
+        rules: rules.len(),
+        values: benchmark.iter_values().count(),
+        rules_by_severity: count(rules.iter().map(|r| r.severity().as_str())),
+        rules_per_profile: benchmark
+            .profiles()
+            .iter()
+            .filter_map(|profile| {
+                let resolved = benchmark.resolve_profile(profile.id()).ok()?;
+                Some(ProfileRules {
+                    profile: profile.id().to_string(),
+                    rules: benchmark.selected_rules(&resolved).len(),
+                })
+            })
+            .collect(),
+        check_systems: count(
+            rules
+                .iter()
+                .flat_map(|r| r.checks().iter().map(|c| c.system().as_str())),
+        ),
+        fix_systems: count(
+            rules
+                .iter()
+                .flat_map(|r| r.fixes().iter().map(|f| f.system().unwrap_or("none"))),
+        ),
+        references: count(
+            rules
+                .iter()
+                .flat_map(|r| r.references().iter().map(|r| r.href().unwrap_or("none"))),
+        ),
+    }
+}
+
+fn write_counts<W: Write>(
+    out: &mut W,
+    title: &str,
+    counts: &BTreeMap<String, usize>,
+) -> io::Result<()> {
+    writeln!(out, "{}:", title)?;
+    for (key, count) in counts.iter() {
+        writeln!(out, "  {:<60} {:>6}", key, count)?;
+    }
+    Ok(())
+}
+
+impl ContentStats {
+    pub fn write_text<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(
+            out,
+            "Benchmark: {} (version {})",
+            self.benchmark, self.version
+        )?;
+        writeln!(out, "Profiles: {}", self.profiles)?;
+        writeln!(out, "Groups: {}", self.groups)?;
+        writeln!(out, "Rules: {}", self.rules)?;
+        writeln!(out, "Values: {}", self.values)?;
+        write_counts(out, "Rules by severity", &self.rules_by_severity)?;
+        writeln!(out, "Rules per profile:")?;
+        for profile in self.rules_per_profile.iter() {
+            writeln!(out, "  {:<60} {:>6}", profile.profile, profile.rules)?;
+        }
+        write_counts(out, "Check systems", &self.check_systems)?;
+        write_counts(out, "Fix systems", &self.fix_systems)?;
+        write_counts(out, "References", &self.references)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xccdf::XCCDF12_NS;
+    use minidom::Element;
+
+    #[test]
+    fn test_content_stats() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>2</version>\
+             <Profile id=\"p\"><title>P</title><select idref=\"r1\" selected=\"true\"/></Profile>\
+             <Group id=\"g\"><Rule id=\"r1\" severity=\"high\" selected=\"false\">\
+             <reference href=\"https://cis\">1.1</reference>\
+             <fix system=\"urn:xccdf:fix:script:sh\">true</fix>\
+             <check system=\"http://oval.mitre.org/XMLSchema/oval-definitions-5\"/></Rule>\
+             <Rule id=\"r2\" severity=\"high\" selected=\"false\"/></Group></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let stats = content_stats(&Benchmark::from_xml(&el).unwrap());
+        assert_eq!((stats.profiles, stats.groups, stats.rules), (1, 1, 2));
+        assert_eq!(stats.rules_by_severity["high"], 2);
+        assert_eq!(stats.rules_per_profile[0].rules, 1);
+        assert_eq!(
+            stats.check_systems["http://oval.mitre.org/XMLSchema/oval-definitions-5"],
+            1
+        );
+        assert_eq!(stats.fix_systems["urn:xccdf:fix:script:sh"], 1);
+        assert_eq!(stats.references["https://cis"], 1);
+    }
+}
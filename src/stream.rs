@@ -0,0 +1,345 @@
+use chrono::{DateTime, FixedOffset};
+use minidom::quick_xml::events::{BytesStart, Event};
+use minidom::quick_xml::Reader;
+use minidom::Element;
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::error::Error;
+use crate::input;
+use crate::options::ParseOptions;
+use crate::sds::{DataStream, DataStreamCollection, SCAP12_NS};
+use crate::utils::*;
+
+/// Namespaces in scope by their prefix, `None` is the default namespace.
+type Prefixes = HashMap<Option<String>, String>;
+
+/// Builds the element from its start tag, without the content. The
+/// namespaces it declares are added to `prefixes`.
+fn start_element<R: BufRead>(
+    reader: &Reader<R>,
+    event: &BytesStart,
+    prefixes: &mut Prefixes,
+) -> Result<Element, Error> {
+    let mut declared = Vec::new();
+    let mut attrs = Vec::new();
+    for attr in event.attributes() {
+        let attr = attr?;
+        let key = String::from_utf8_lossy(attr.key).to_string();
+        let value = attr.unescape_and_decode_value(reader)?;
+        if key == "xmlns" {
+            declared.push((None, value));
+        } else if let Some(prefix) = key.strip_prefix("xmlns:") {
+            declared.push((Some(prefix.to_string()), value));
+        } else {
+            attrs.push((key, value));
+        }
+    }
+    for (prefix, ns) in declared.iter() {
+        prefixes.insert(prefix.clone(), ns.clone());
+    }
+    let qname = String::from_utf8_lossy(event.name()).to_string();
+    let (prefix, name) = match qname.split_once(':') {
+        Some((prefix, name)) => (Some(prefix.to_string()), name),
+        None => (None, qname.as_str()),
+    };
+    let ns = match prefixes.get(&prefix) {
+        Some(ns) => ns.clone(),
+        None => return Err(Error::Xml(format!("No namespace declared for '{}'", qname))),
+    };
+    let mut builder = Element::builder(name, ns);
+    for (prefix, ns) in declared {
+        builder = builder.prefix(prefix, ns)?;
+    }
+    for (key, value) in attrs {
+        builder = builder.attr(key, value);
+    }
+    Ok(builder.build())
+}
+
+/// Reads the content of `el` up to its end tag. Cancellation and the
+/// deadline are checked at every child element.
+fn read_content<R: BufRead>(
+    reader: &mut Reader<R>,
+    mut el: Element,
+    prefixes: &Prefixes,
+    opts: &ParseOptions,
+) -> Result<Element, Error> {
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match reader.read_event(&mut buf)? {
+            Event::Start(event) => {
+                opts.check_interrupted()?;
+                let mut scope = prefixes.clone();
+                let child = start_element(reader, &event, &mut scope)?;
+                el.append_child(read_content(reader, child, &scope, opts)?);
+            }
+            Event::Empty(event) => {
+                opts.check_interrupted()?;
+                let child = start_element(reader, &event, &mut prefixes.clone())?;
+                el.append_child(child);
+            }
+            Event::Text(event) => {
+                let text = event.unescape_and_decode(reader)?;
+                if !text.is_empty() {
+                    el.append_text_node(text);
+                }
+            }
+            Event::CData(event) => el.append_text_node(String::from_utf8_lossy(&event)),
+            Event::End(_) => return Ok(el),
+            Event::Eof => return Err(Error::Xml(String::from("Unexpected end of document"))),
+            _ => (),
+        }
+    }
+}
+
+/// Skips the content of an element up to its end tag.
+fn skip_content<R: BufRead>(reader: &mut Reader<R>, opts: &ParseOptions) -> Result<(), Error> {
+    let mut buf = Vec::new();
+    let mut depth = 0;
+    loop {
+        buf.clear();
+        match reader.read_event(&mut buf)? {
+            Event::Start(_) => {
+                opts.check_interrupted()?;
+                depth += 1;
+            }
+            Event::End(_) if depth == 0 => return Ok(()),
+            Event::End(_) => depth -= 1,
+            Event::Eof => return Err(Error::Xml(String::from("Unexpected end of document"))),
+            _ => (),
+        }
+    }
+}
+
+/// Component of a streamed collection. Only its attributes are read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentEntry {
+    pub(crate) id: Arc<str>,
+    pub(crate) timestamp: DateTime<FixedOffset>,
+    pub(crate) extended: bool,
+}
+
+impl ComponentEntry {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn timestamp(&self) -> DateTime<FixedOffset> {
+        self.timestamp
+    }
+
+    pub fn is_extended(&self) -> bool {
+        self.extended
+    }
+}
+
+/// Reads the data stream collection with only the components for which
+/// `keep` returns true, the content of the others is skipped. Returns the
+/// root element and all the components.
+fn scan<R: BufRead, F: Fn(&ComponentEntry) -> bool>(
+    reader: &mut Reader<R>,
+    keep: F,
+    opts: &ParseOptions,
+) -> Result<(Element, Vec<ComponentEntry>), Error> {
+    let mut buf = Vec::new();
+    let mut prefixes = Prefixes::new();
+    let (mut root, empty) = loop {
+        buf.clear();
+        match reader.read_event(&mut buf)? {
+            Event::Start(event) => break (start_element(reader, &event, &mut prefixes)?, false),
+            Event::Empty(event) => break (start_element(reader, &event, &mut prefixes)?, true),
+            Event::Eof => return Err(Error::Xml(String::from("The document is empty"))),
+            _ => (),
+        }
+    };
+    let mut components = Vec::new();
+    if empty {
+        return Ok((root, components));
+    }
+    loop {
+        buf.clear();
+        let (event, empty) = match reader.read_event(&mut buf)? {
+            Event::Start(event) => (event.into_owned(), false),
+            Event::Empty(event) => (event.into_owned(), true),
+            Event::End(_) => break,
+            Event::Eof => return Err(Error::Xml(String::from("Unexpected end of document"))),
+            _ => continue,
+        };
+        opts.check_interrupted()?;
+        let mut scope = prefixes.clone();
+        let child = start_element(reader, &event, &mut scope)?;
+        let extended = child.is("extended-component", SCAP12_NS);
+        if extended || child.is("component", SCAP12_NS) {
+            let timestamp = require_attr(&child, "timestamp")?;
+            let entry = ComponentEntry {
+                id: require_attr(&child, "id")?,
+                timestamp: parse_date_time(&timestamp).map_err(|e| {
+                    Error::invalid_value(child.name(), Some("timestamp"), &timestamp, e)
+                })?,
+                extended,
+            };
+            let wanted = keep(&entry);
+            components.push(entry);
+            if !wanted {
+                if !empty {
+                    skip_content(reader, opts)?;
+                }
+                continue;
+            }
+        }
+        match empty {
+            true => root.append_child(child),
+            false => root.append_child(read_content(reader, child, &scope, opts)?),
+        };
+    }
+    Ok((root, components))
+}
+
+fn open_reader(path: &Path, entry: Option<&str>) -> Result<Reader<Box<dyn BufRead>>, Error> {
+    Ok(Reader::from_reader(input::open_entry_reader(path, entry)?))
+}
+
+/// Data stream collection read from a file without loading its components.
+/// Only the data streams and the component IDs are kept in memory, the
+/// components asked for are read from the file again.
+#[derive(Debug)]
+pub struct StreamedCollection {
+    pub(crate) path: PathBuf,
+    /// File of a zip archive the collection is read from
+    pub(crate) entry: Option<String>,
+    pub(crate) id: Arc<str>,
+    pub(crate) data_streams: Vec<DataStream>,
+    pub(crate) components: Vec<ComponentEntry>,
+}
+
+impl StreamedCollection {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<StreamedCollection, Error> {
+        StreamedCollection::open_entry(path, None)
+    }
+
+    /// Opens the collection stored as the given file of a zip archive.
+    pub fn open_entry<P: AsRef<Path>>(
+        path: P,
+        entry: Option<&str>,
+    ) -> Result<StreamedCollection, Error> {
+        StreamedCollection::open_with_options(path, entry, &ParseOptions::default())
+    }
+
+    /// Like [`StreamedCollection::open_entry`], failing once the scan is
+    /// cancelled or its deadline passes.
+    pub fn open_with_options<P: AsRef<Path>>(
+        path: P,
+        entry: Option<&str>,
+        opts: &ParseOptions,
+    ) -> Result<StreamedCollection, Error> {
+        let path = path.as_ref().to_path_buf();
+        if input::is_stdin(&path) || input::is_url(&path) {
+            return Err(Error::Content(String::from(
+                "The standard input and URLs can't be read as a stream, they're read twice",
+            )));
+        }
+        let (root, components) = scan(&mut open_reader(&path, entry)?, |_| false, opts)?;
+        if !root.is("data-stream-collection", SCAP12_NS) {
+            return Err(Error::unexpected_element(
+                root.name(),
+                Some("ds:data-stream-collection"),
+            ));
+        }
+        let id = require_attr(&root, "id")?;
+        let mut data_streams = Vec::new();
+        for_each_child(&root, |child| {
+            if child.is("data-stream", SCAP12_NS) {
+                data_streams.push(DataStream::from_xml(child)?);
+            }
+            Ok(())
+        })?;
+        Ok(StreamedCollection {
+            path,
+            entry: entry.map(|e| e.to_string()),
+            id,
+            data_streams,
+            components,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn data_streams(&self) -> &[DataStream] {
+        &self.data_streams
+    }
+
+    pub fn components(&self) -> &[ComponentEntry] {
+        &self.components
+    }
+
+    /// IDs of the components referenced as checklists by the data streams.
+    pub fn checklist_components(&self) -> Vec<&str> {
+        self.data_streams
+            .iter()
+            .flat_map(|ds| ds.checklists().iter())
+            .filter_map(|checklist| checklist.href().strip_prefix('#'))
+            .filter(|id| self.components.iter().any(|c| &*c.id == *id))
+            .collect()
+    }
+
+    /// Reads the collection from the file again with only the components
+    /// with the given IDs.
+    pub fn load(&self, ids: &[&str], opts: &ParseOptions) -> Result<DataStreamCollection, Error> {
+        for id in ids.iter() {
+            if !self.components.iter().any(|c| &*c.id == *id) {
+                return Err(Error::Content(format!("Component '{}' not found", id)));
+            }
+        }
+        let (root, _) = scan(
+            &mut open_reader(&self.path, self.entry.as_deref())?,
+            |c| ids.contains(&c.id.as_ref()),
+            opts,
+        )?;
+        DataStreamCollection::from_xml_with_options(&root, opts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streamed_collection() {
+        let streamed = StreamedCollection::open("data/simple.xml").unwrap();
+        assert_eq!(streamed.data_streams().len(), 1);
+        assert!(streamed.components().len() > 1);
+        let checklists = streamed.checklist_components();
+        assert_eq!(checklists.len(), 1);
+        let collection = streamed
+            .load(&checklists, &ParseOptions::default())
+            .unwrap();
+        assert_eq!(collection.components().len(), 1);
+        assert_eq!(collection.benchmarks().len(), 1);
+        assert!(streamed
+            .load(&["missing"], &ParseOptions::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_interrupted_scan() {
+        let opts = ParseOptions {
+            deadline: Some(std::time::Instant::now()),
+            ..ParseOptions::default()
+        };
+        let error =
+            StreamedCollection::open_with_options("data/simple.xml", None, &opts).unwrap_err();
+        assert_eq!(error, Error::DeadlineExceeded);
+        let streamed = StreamedCollection::open("data/simple.xml").unwrap();
+        let checklists = streamed.checklist_components();
+        assert!(streamed
+            .load(&checklists, &opts)
+            .unwrap_err()
+            .is_interruption());
+    }
+}
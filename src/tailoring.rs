@@ -0,0 +1,159 @@
+use minidom::Element;
+
+use crate::utils::current_timestamp;
+use crate::xccdf::{Benchmark, Group, ResolvedProfile, Rule, XCCDF12_NS};
+
+/// Reverse DNS name and short name of an `xccdf_<reverse DNS>_profile_<name>`
+/// profile ID.
+fn split_profile_id(id: &str) -> Option<(&str, &str)> {
+    id.strip_prefix("xccdf_")?.split_once("_profile_")
+}
+
+fn select(id: &str, selected: bool) -> Element {
+    Element::builder("select", XCCDF12_NS)
+        .attr("idref", id)
+        .attr("selected", if selected { "true" } else { "false" })
+        .build()
+}
+
+/// Selections of the groups and rules in the benchmark order.
+fn add_selects(
+    groups: &[Group],
+    rules: &[Rule],
+    profile: &ResolvedProfile,
+    selects: &mut Vec<Element>,
+) {
+    for rule in rules.iter().filter(|r| !r.abstract_) {
+        let selected = profile.is_selected(&rule.id, rule.cluster_id.as_deref(), rule.selected);
+        selects.push(select(&rule.id, selected));
+    }
+    for group in groups.iter().filter(|g| !g.abstract_) {
+        let selected = profile.is_selected(&group.id, group.cluster_id.as_deref(), group.selected);
+        selects.push(select(&group.id, selected));
+        add_selects(&group.groups, &group.rules, profile, selects);
+    }
+}
+
+/// Builds a Tailoring with a profile extending the given one, which
+/// explicitly selects or unselects every group and rule and sets every
+/// Value as the profile does, to be edited by the administrator.
+pub fn tailoring_skeleton(
+    benchmark: &Benchmark,
+    profile: &ResolvedProfile,
+    benchmark_href: &str,
+) -> Element {
+    let (tailoring_id, short_name) = match split_profile_id(profile.id()) {
+        Some((namespace, name)) => (format!("xccdf_{}_tailoring_{}", namespace, name), name),
+        None => (
+            String::from("xccdf_oscapxml_tailoring_default"),
+            profile.id(),
+        ),
+    };
+    let title = profile
+        .titles
+        .first()
+        .map_or(short_name, |t| t.title.as_str());
+
+    let mut selects = Vec::new();
+    add_selects(&benchmark.groups, &benchmark.rules, profile, &mut selects);
+    let mut tailored = Element::builder("Profile", XCCDF12_NS)
+        .attr("id", format!("{}_customized", profile.id()))
+        .attr("extends", profile.id())
+        .append(
+            Element::builder("title", XCCDF12_NS)
+                .append(format!("{} [CUSTOMIZED]", title))
+                .build(),
+        )
+        .append_all(selects);
+    for nested in benchmark.iter_values() {
+        let value = nested.item;
+        let set_value = match benchmark.value_in_profile(&value.id, profile) {
+            Some(text) => Element::builder("set-value", XCCDF12_NS)
+                .attr("idref", value.id.as_ref())
+                .append(text),
+            None => match benchmark.complex_value_in_profile(&value.id, profile) {
+                Some(items) => Element::builder("set-complex-value", XCCDF12_NS)
+                    .attr("idref", value.id.as_ref())
+                    .append_all(
+                        items
+                            .into_iter()
+                            .map(|item| Element::builder("item", XCCDF12_NS).append(item).build()),
+                    ),
+                None => continue,
+            },
+        };
+        tailored = tailored.append(set_value.build());
+    }
+
+    Element::builder("Tailoring", XCCDF12_NS)
+        .attr("id", tailoring_id)
+        .append(
+            Element::builder("benchmark", XCCDF12_NS)
+                .attr("href", benchmark_href)
+                .build(),
+        )
+        .append(
+            Element::builder("status", XCCDF12_NS)
+                .append("incomplete")
+                .build(),
+        )
+        .append(
+            Element::builder("version", XCCDF12_NS)
+                .attr("time", current_timestamp())
+                .append("1")
+                .build(),
+        )
+        .append(tailored.build())
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writer;
+    use crate::xccdf::Tailoring;
+
+    #[test]
+    fn test_tailoring_skeleton() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"xccdf_org.example_benchmark_b\"><status>draft</status>\
+             <version>1</version>\
+             <Profile id=\"xccdf_org.example_profile_base\"><title>Base</title>\
+             <select idref=\"xccdf_org.example_rule_r2\" selected=\"false\"/>\
+             <set-value idref=\"xccdf_org.example_value_v\">077</set-value></Profile>\
+             <Value id=\"xccdf_org.example_value_v\"><value>027</value></Value>\
+             <Rule id=\"xccdf_org.example_rule_r1\"><title>R1</title></Rule>\
+             <Group id=\"xccdf_org.example_group_g\" selected=\"false\"><title>G</title>\
+             <Rule id=\"xccdf_org.example_rule_r2\"><title>R2</title></Rule></Group>\
+             </Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let profile = benchmark
+            .resolve_profile("xccdf_org.example_profile_base")
+            .unwrap();
+        let skeleton = tailoring_skeleton(&benchmark, &profile, "ssg-ds.xml");
+        let text = writer::to_string(&skeleton).unwrap();
+        assert!(text.contains("_tailoring_base"));
+        assert!(text.contains("Base [CUSTOMIZED]"));
+
+        let parsed: Element = text.lines().nth(1).unwrap().parse().unwrap();
+        let tailoring = Tailoring::from_xml(&parsed).unwrap();
+        let tailored = benchmark
+            .resolve_tailored_profile(&tailoring, "xccdf_org.example_profile_base_customized")
+            .unwrap();
+        let selected: Vec<&str> = benchmark
+            .selected_rules(&tailored)
+            .iter()
+            .map(|r| r.id.as_ref())
+            .collect();
+        assert_eq!(selected, vec!["xccdf_org.example_rule_r1"]);
+        assert_eq!(tailored.selected_ids(), vec!["xccdf_org.example_rule_r1"]);
+        assert_eq!(
+            benchmark.value_in_profile("xccdf_org.example_value_v", &tailored),
+            Some(String::from("077"))
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use minidom::Element;
+use std::collections::HashMap;
+
+use crate::doctor::describe;
+use crate::dsig::DSIG_NS;
+use crate::output::format_size;
+use crate::sds::{ComponentRef, ComponentTarget, DataStreamCollection, SCAP12_NS};
+
+/// Node of the structure tree, rendered with its children below it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub label: String,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    fn new(label: impl Into<String>) -> Node {
+        Node {
+            label: label.into(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Renders the node and its descendants as an ASCII tree.
+    pub fn render(&self) -> String {
+        let mut text = format!("{}\n", self.label);
+        self.render_children("", &mut text);
+        text
+    }
+
+    fn render_children(&self, prefix: &str, text: &mut String) {
+        for (i, child) in self.children.iter().enumerate() {
+            let last = i + 1 == self.children.len();
+            text.push_str(prefix);
+            text.push_str(if last { "`-- " } else { "|-- " });
+            text.push_str(&child.label);
+            text.push('\n');
+            let prefix = format!("{}{}", prefix, if last { "    " } else { "|   " });
+            child.render_children(&prefix, text);
+        }
+    }
+}
+
+/// Type and serialized size of the components, by ID.
+fn component_summaries(root: &Element) -> HashMap<&str, String> {
+    let mut summaries = HashMap::new();
+    for el in root.children().filter(|c| c.is("component", SCAP12_NS)) {
+        let id = match el.attr("id") {
+            Some(id) => id,
+            None => continue,
+        };
+        let kind = match el.children().next() {
+            Some(content) => match describe(content) {
+                Some((description, _)) => description.to_string(),
+                None => content.name().to_string(),
+            },
+            None => String::from("empty"),
+        };
+        let mut data = Vec::new();
+        let summary = match el.write_to(&mut data) {
+            Ok(()) => format!("{}, {}", kind, format_size(data.len())),
+            Err(_) => kind,
+        };
+        summaries.insert(id, summary);
+    }
+    summaries
+}
+
+fn component_ref_node(
+    collection: &DataStreamCollection,
+    summaries: &HashMap<&str, String>,
+    component_ref: &ComponentRef,
+) -> Node {
+    let mut node = Node::new(format!(
+        "component-ref {} -> {}",
+        component_ref.id, component_ref.href
+    ));
+    let target = match collection.find_component_by_href(&component_ref.href) {
+        Some(ComponentTarget::Component(component)) => match summaries.get(component.id()) {
+            Some(summary) => format!("component {} ({})", component.id(), summary),
+            None => format!("component {}", component.id()),
+        },
+        Some(ComponentTarget::ExtendedComponent(component)) => {
+            format!(
+                "extended-component {} ({})",
+                component.id(),
+                component.describe()
+            )
+        }
+        Some(ComponentTarget::Remote(url)) => format!("remote {}", url),
+        None => String::from("missing component"),
+    };
+    node.children.push(Node::new(target));
+    if let Some(catalog) = &component_ref.catalog {
+        let mut catalog_node = Node::new("catalog");
+        for uri in catalog.uris.iter() {
+            catalog_node
+                .children
+                .push(Node::new(format!("{} -> {}", uri.name, uri.uri)));
+        }
+        for rewrite in catalog.rewrite_uris.iter() {
+            catalog_node.children.push(Node::new(format!(
+                "rewrite {} -> {}",
+                rewrite.uri_start_string, rewrite.rewrite_prefix
+            )));
+        }
+        node.children.push(catalog_node);
+    }
+    node
+}
+
+/// Structure of the collection: the data streams with their component-refs
+/// and the components they resolve to, the catalogs and the signatures.
+/// `root` is the element the collection was parsed from, used for the sizes
+/// of the components.
+pub fn collection_tree(collection: &DataStreamCollection, root: &Element) -> Node {
+    let summaries = component_summaries(root);
+    let mut tree = Node::new(format!(
+        "data-stream-collection {} (schematron {})",
+        collection.id(),
+        collection.schematron_version()
+    ));
+    for ds in collection.data_streams() {
+        let mut ds_node = Node::new(format!(
+            "data-stream {} ({}, SCAP {})",
+            ds.id, ds.use_case, ds.scap_version
+        ));
+        let sections = [
+            ("dictionaries", &ds.dictionaries),
+            ("checklists", &ds.checklists),
+            ("checks", &ds.checks),
+            ("extended-components", &ds.extended_components),
+        ];
+        for (name, refs) in sections.into_iter().filter(|(_, refs)| !refs.is_empty()) {
+            let mut section = Node::new(name);
+            for component_ref in refs.iter() {
+                let node = component_ref_node(collection, &summaries, component_ref);
+                section.children.push(node);
+            }
+            ds_node.children.push(section);
+        }
+        tree.children.push(ds_node);
+    }
+    let signatures = root.children().filter(|c| c.is("Signature", DSIG_NS));
+    for (el, signature) in signatures.zip(collection.signatures.iter()) {
+        let mut node = Node::new(format!(
+            "Signature {}",
+            signature.id.as_deref().unwrap_or("without ID")
+        ));
+        if let Some(Ok(signer)) = signature.signer() {
+            node.children
+                .push(Node::new(format!("signed by {}", signer.subject)));
+        }
+        for reference in signature.references.iter() {
+            node.children
+                .push(Node::new(format!("reference {}", reference.uri)));
+        }
+        let mut data = Vec::new();
+        if el.write_to(&mut data).is_ok() {
+            node.label = format!("{} ({})", node.label, format_size(data.len()));
+        }
+        tree.children.push(node);
+    }
+    tree
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::misc;
+
+    #[test]
+    fn test_collection_tree() {
+        let data = std::fs::read("data/simple.xml").unwrap();
+        let (root, _) = misc::parse(&data).unwrap();
+        let collection = DataStreamCollection::from_xml(&root).unwrap();
+        let text = collection_tree(&collection, &root).render();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].starts_with("data-stream-collection "));
+        assert_eq!(
+            lines[1],
+            "`-- data-stream scap_org.open-scap_datastream_simple (OTHER, SCAP 1.3)"
+        );
+        assert_eq!(lines[2], "    |-- checklists");
+        assert!(lines[3].starts_with(
+            "    |   `-- component-ref scap_org.open-scap_cref_test_single_rule.xccdf.xml -> #"
+        ));
+        assert!(lines[4].starts_with(
+            "    |       |-- component scap_org.open-scap_comp_test_single_rule.xccdf.xml \
+             (XCCDF 1.2 benchmark, "
+        ));
+        assert!(text.contains("catalog\n"));
+    }
+}
@@ -0,0 +1,378 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use minidom::Element;
+use ring::signature;
+use std::fs;
+use std::path::Path;
+use x509_parser::prelude::*;
+
+use crate::dsig::{self, Signature};
+use crate::utils::sha256_hex;
+
+/// Signature and digest algorithms accepted when no other list is given.
+/// SHA-1 based algorithms are deliberately missing.
+pub const DEFAULT_ALLOWED_ALGORITHMS: [&str; 8] = [
+    dsig::RSA_SHA256,
+    dsig::RSA_SHA384,
+    dsig::RSA_SHA512,
+    dsig::ECDSA_SHA256,
+    dsig::ECDSA_SHA384,
+    dsig::SHA256,
+    dsig::SHA384,
+    dsig::SHA512,
+];
+
+pub(crate) fn encode_base64(data: &[u8]) -> String {
+    STANDARD.encode(data)
+}
+
+pub(crate) fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    let text: String = text.split_whitespace().collect();
+    STANDARD
+        .decode(text)
+        .map_err(|e| format!("invalid base64: {}", e))
+}
+
+/// X.509 certificate reduced to what the signing policy needs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Certificate {
+    pub subject: String,
+    pub issuer: String,
+    /// SHA-256 of the DER encoding, in hex
+    pub fingerprint: String,
+    pub(crate) der: Vec<u8>,
+}
+
+impl Certificate {
+    pub fn from_der(der: &[u8]) -> Result<Certificate, String> {
+        let (rest, certificate) =
+            X509Certificate::from_der(der).map_err(|e| format!("invalid certificate: {}", e))?;
+        if !rest.is_empty() {
+            return Err(String::from("trailing data after the certificate"));
+        }
+        Ok(Certificate {
+            subject: certificate.subject().to_string(),
+            issuer: certificate.issuer().to_string(),
+            fingerprint: sha256_hex(der),
+            der: der.to_vec(),
+        })
+    }
+
+    pub fn from_base64(text: &str) -> Result<Certificate, String> {
+        Certificate::from_der(&decode_base64(text)?)
+    }
+
+    fn parsed(&self) -> X509Certificate<'_> {
+        // the DER was checked by from_der
+        X509Certificate::from_der(&self.der).unwrap().1
+    }
+
+    fn check_validity(&self) -> Result<(), String> {
+        let validity = self.parsed().validity().clone();
+        match validity.is_valid() {
+            true => Ok(()),
+            false => Err(format!(
+                "certificate '{}' is valid only from {} to {}",
+                self.subject, validity.not_before, validity.not_after
+            )),
+        }
+    }
+
+    /// Whether the certificate names `issuer` as its issuer. The signature
+    /// is checked by [`Certificate::check_issued_by`].
+    fn names_issuer(&self, issuer: &Certificate) -> bool {
+        self.parsed().issuer().as_raw() == issuer.parsed().subject().as_raw()
+    }
+
+    /// Checks that `issuer` is a CA which signed this certificate.
+    fn check_issued_by(&self, issuer: &Certificate) -> Result<(), String> {
+        let parsed = issuer.parsed();
+        if !parsed.is_ca() {
+            return Err(format!(
+                "issuer '{}' of '{}' isn't a CA",
+                issuer.subject, self.subject
+            ));
+        }
+        self.parsed()
+            .verify_signature(Some(parsed.public_key()))
+            .map_err(|e| {
+                format!(
+                    "signature of '{}' by '{}' doesn't verify: {}",
+                    self.subject, issuer.subject, e
+                )
+            })
+    }
+
+    /// Verifies an XML signature value of `data` made with the key of the
+    /// certificate.
+    pub fn verify(&self, algorithm: &str, data: &[u8], signature: &[u8]) -> Result<(), String> {
+        let verification: &dyn signature::VerificationAlgorithm = match algorithm {
+            dsig::RSA_SHA256 => &signature::RSA_PKCS1_2048_8192_SHA256,
+            dsig::RSA_SHA384 => &signature::RSA_PKCS1_2048_8192_SHA384,
+            dsig::RSA_SHA512 => &signature::RSA_PKCS1_2048_8192_SHA512,
+            dsig::ECDSA_SHA256 => &signature::ECDSA_P256_SHA256_FIXED,
+            dsig::ECDSA_SHA384 => &signature::ECDSA_P384_SHA384_FIXED,
+            _ => return Err(format!("signature method '{}' isn't supported", algorithm)),
+        };
+        let parsed = self.parsed();
+        let key = &parsed.public_key().subject_public_key.data;
+        signature::UnparsedPublicKey::new(verification, key)
+            .verify(data, signature)
+            .map_err(|_| {
+                format!(
+                    "signature value doesn't verify with the key of '{}'",
+                    self.subject
+                )
+            })
+    }
+}
+
+/// Certificates the organization trusts to sign content.
+#[derive(Debug, Default)]
+pub struct TrustStore {
+    pub certificates: Vec<Certificate>,
+}
+
+impl TrustStore {
+    pub fn from_pem(text: &str) -> Result<TrustStore, String> {
+        let mut certificates = Vec::new();
+        let mut rest = text;
+        while let Some(start) = rest.find("-----BEGIN CERTIFICATE-----") {
+            let body = &rest[start + "-----BEGIN CERTIFICATE-----".len()..];
+            let end = match body.find("-----END CERTIFICATE-----") {
+                Some(end) => end,
+                None => return Err(String::from("unterminated PEM certificate")),
+            };
+            certificates.push(Certificate::from_base64(&body[..end])?);
+            rest = &body[end..];
+        }
+        Ok(TrustStore { certificates })
+    }
+
+    /// Loads a PEM bundle or every `.pem`, `.crt` and `.cer` file of a directory.
+    pub fn load(path: &Path) -> Result<TrustStore, String> {
+        let mut files = Vec::new();
+        if path.is_dir() {
+            let entries = fs::read_dir(path)
+                .map_err(|e| format!("Failed to read directory '{}': {}", path.display(), e))?;
+            for entry in entries {
+                let file = entry.map_err(|e| e.to_string())?.path();
+                let extension = file.extension().and_then(|e| e.to_str()).unwrap_or("");
+                if ["pem", "crt", "cer"].contains(&extension) {
+                    files.push(file);
+                }
+            }
+            files.sort();
+        } else {
+            files.push(path.to_path_buf());
+        }
+        let mut store = TrustStore::default();
+        for file in files {
+            let text = fs::read_to_string(&file)
+                .map_err(|e| format!("Failed to read '{}': {}", file.display(), e))?;
+            let loaded = TrustStore::from_pem(&text)
+                .map_err(|e| format!("Failed to parse '{}': {}", file.display(), e))?;
+            store.certificates.extend(loaded.certificates);
+        }
+        if store.certificates.is_empty() {
+            return Err(format!(
+                "No certificates found in the trust store '{}'",
+                path.display()
+            ));
+        }
+        Ok(store)
+    }
+
+    /// Checks that the first certificate of `chain` is in the store or
+    /// issued by a certificate in it, directly or through the other
+    /// certificates of `chain`. Every signature on the path is verified and
+    /// every certificate has to be valid now.
+    pub fn verify_chain(&self, chain: &[Certificate]) -> Result<(), String> {
+        let mut current = match chain.first() {
+            Some(certificate) => certificate,
+            None => return Err(String::from("no certificate to verify")),
+        };
+        // every certificate of the chain can be used once
+        for _ in 0..chain.len() {
+            current.check_validity()?;
+            if self.certificates.iter().any(|c| c.der == current.der) {
+                return Ok(());
+            }
+            let anchors: Vec<&Certificate> = self
+                .certificates
+                .iter()
+                .filter(|c| current.names_issuer(c))
+                .collect();
+            if !anchors.is_empty() {
+                let mut errors = Vec::new();
+                for anchor in anchors {
+                    match current.check_issued_by(anchor).and(anchor.check_validity()) {
+                        Ok(()) => return Ok(()),
+                        Err(error) => errors.push(error),
+                    }
+                }
+                return Err(errors.join("; "));
+            }
+            let next = chain[1..]
+                .iter()
+                .find(|c| *c != current && current.names_issuer(c));
+            current = match next {
+                Some(next) => {
+                    current.check_issued_by(next)?;
+                    next
+                }
+                None => {
+                    return Err(format!(
+                        "issuer '{}' of '{}' isn't in the trust store",
+                        current.issuer, current.subject
+                    ))
+                }
+            };
+        }
+        Err(String::from("certificate chain loops"))
+    }
+}
+
+pub struct SigningPolicy {
+    pub trust_store: TrustStore,
+    pub allowed_algorithms: Vec<String>,
+}
+
+impl SigningPolicy {
+    pub fn new(trust_store: TrustStore) -> SigningPolicy {
+        SigningPolicy {
+            trust_store,
+            allowed_algorithms: DEFAULT_ALLOWED_ALGORITHMS
+                .iter()
+                .map(|a| a.to_string())
+                .collect(),
+        }
+    }
+
+    fn is_allowed(&self, algorithm: &str) -> bool {
+        self.allowed_algorithms.iter().any(|a| a == algorithm)
+    }
+
+    /// Checks that the signature uses allowed algorithms, that its digests
+    /// and signature value verify against `root`, the document containing
+    /// it, that the elements with the IDs of `signed` are covered by its
+    /// verified references and that its signing certificate chains up to
+    /// the trust store.
+    pub fn check(&self, signature: &Signature, root: &Element, signed: &[&str]) -> Vec<String> {
+        let mut problems = Vec::new();
+        if !self.is_allowed(&signature.signature_method) {
+            problems.push(format!(
+                "signature method '{}' isn't allowed",
+                signature.signature_method
+            ));
+        }
+        for reference in signature.references.iter() {
+            if !self.is_allowed(&reference.digest_method) {
+                problems.push(format!(
+                    "reference '{}' uses digest method '{}' which isn't allowed",
+                    reference.uri, reference.digest_method
+                ));
+            }
+        }
+        let chain = match signature.chain() {
+            Ok(chain) if chain.is_empty() => {
+                problems.push(String::from(
+                    "signature doesn't include the signing certificate",
+                ));
+                return problems;
+            }
+            Ok(chain) => chain,
+            Err(error) => {
+                problems.push(format!("signing certificate: {}", error));
+                return problems;
+            }
+        };
+        problems.extend(signature.verify(root, &chain[0], signed));
+        if let Err(error) = self.trust_store.verify_chain(&chain) {
+            problems.push(format!(
+                "signer '{}' isn't trusted by the trust store: {}",
+                chain[0].subject, error
+            ));
+        }
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pem_body(path: &str) -> String {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .filter(|l| !l.starts_with("-----"))
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_chain() {
+        let store = TrustStore::load(Path::new("data/trust/ca.pem")).unwrap();
+        assert_eq!(store.certificates.len(), 1);
+        let ca = &store.certificates[0];
+        assert_eq!(ca.subject, "C=US, O=Example Org, CN=Example Root");
+        assert_eq!(ca.subject, ca.issuer);
+        let signer = Certificate::from_base64(&pem_body("data/trust/signer.pem")).unwrap();
+        assert_eq!(signer.subject, "O=Example Org, CN=Content Signer");
+        assert_eq!(store.verify_chain(std::slice::from_ref(&signer)), Ok(()));
+        assert!(TrustStore::default().verify_chain(&[signer]).is_err());
+        // same issuer name, signed by another key
+        let forged = Certificate::from_base64(&pem_body("data/untrusted/forged.pem")).unwrap();
+        assert_eq!(forged.issuer, ca.subject);
+        let error = store.verify_chain(&[forged]).unwrap_err();
+        assert!(error.contains("doesn't verify"), "{}", error);
+    }
+
+    #[test]
+    fn test_policy_check() {
+        let signed = fs::read_to_string("data/trust/signed.xml").unwrap();
+        let components = [
+            "scap_org.open-scap_comp_test_single_rule.xccdf.xml",
+            "scap_org.open-scap_comp_test_single_rule.oval.xml",
+        ];
+        let check_signed = |text: &str, policy: &SigningPolicy, signed: &[&str]| {
+            let root: Element = text.parse().unwrap();
+            let signature = root.children().find(|c| dsig::is_signature(c)).unwrap();
+            policy.check(&Signature::from_xml(signature).unwrap(), &root, signed)
+        };
+        let check = |text: &str, policy: &SigningPolicy| check_signed(text, policy, &components);
+        let mut policy = SigningPolicy::new(TrustStore::load(Path::new("data/trust")).unwrap());
+        assert!(check(&signed, &policy).is_empty());
+
+        let tampered = signed.replace("This rule always passes", "This rule always fails");
+        assert_eq!(
+            check(&tampered, &policy),
+            vec![
+                "reference '#scap_org.open-scap_comp_test_single_rule.xccdf.xml': digest doesn't match the referenced content",
+                "'scap_org.open-scap_comp_test_single_rule.xccdf.xml' isn't covered by a verified reference"
+            ]
+        );
+        let forged = signed.replace(
+            &pem_body("data/trust/signer.pem"),
+            &pem_body("data/untrusted/forged.pem"),
+        );
+        let problems = check(&forged, &policy);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("signer 'O=Example Org, CN=Content Signer' isn't trusted"));
+
+        // the data stream is outside the references
+        assert_eq!(
+            check_signed(&signed, &policy, &["scap_org.open-scap_datastream_simple"]),
+            vec!["'scap_org.open-scap_datastream_simple' isn't covered by a verified reference"]
+        );
+
+        policy.allowed_algorithms = vec![dsig::SHA256.to_string()];
+        assert_eq!(
+            check(&signed, &policy),
+            vec![format!(
+                "signature method '{}' isn't allowed",
+                dsig::RSA_SHA256
+            )]
+        );
+    }
+}
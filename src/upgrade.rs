@@ -0,0 +1,316 @@
+use minidom::{Element, Node};
+use std::collections::HashMap;
+#[cfg(feature = "validation")]
+use std::collections::HashSet;
+
+#[cfg(feature = "validation")]
+use crate::sds::DataStreamCollection;
+use crate::sds::SCAP12_NS;
+use crate::utils::current_timestamp;
+#[cfg(feature = "validation")]
+use crate::validate;
+use crate::xccdf::XCCDF12_NS;
+
+pub const XCCDF11_NS: &str = "http://checklists.nist.gov/xccdf/1.1";
+
+/// Items whose IDs follow the `xccdf_<reverse DNS>_<kind>_<name>` format
+/// in XCCDF 1.2, with the kind used in the ID.
+const ID_KINDS: [(&str, &str); 6] = [
+    ("Benchmark", "benchmark"),
+    ("Profile", "profile"),
+    ("Group", "group"),
+    ("Rule", "rule"),
+    ("Value", "value"),
+    ("TestResult", "testresult"),
+];
+
+/// Attributes holding the IDs of items, `requires` and `conflicts` hold a
+/// whitespace-separated list of them.
+const REFERENCE_ATTRS: [&str; 3] = ["idref", "extends", "value-id"];
+
+/// Elements of XCCDF 1.1 that have no XCCDF 1.2 counterpart.
+const REMOVED: [&str; 2] = ["platform-definitions", "Platform-Specification"];
+
+/// Result of the conversion, with notes on the content that was changed
+/// beyond the namespace or dropped.
+#[derive(Debug)]
+pub struct Upgraded {
+    pub root: Element,
+    pub notes: Vec<String>,
+}
+
+fn is_valid_reverse_dns(reverse_dns: &str) -> bool {
+    !reverse_dns.is_empty()
+        && reverse_dns
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+}
+
+fn collect_ids(el: &Element, reverse_dns: &str, ids: &mut HashMap<String, String>) {
+    if el.ns() != XCCDF11_NS {
+        return;
+    }
+    let kind = ID_KINDS.iter().find(|(name, _)| *name == el.name());
+    if let (Some((_, kind)), Some(id)) = (kind, el.attr("id")) {
+        if !id.starts_with("xccdf_") {
+            ids.insert(
+                id.to_string(),
+                format!("xccdf_{}_{}_{}", reverse_dns, kind, id),
+            );
+        }
+    }
+    for child in el.children() {
+        collect_ids(child, reverse_dns, ids);
+    }
+}
+
+fn convert(el: &Element, ids: &HashMap<String, String>, notes: &mut Vec<String>) -> Element {
+    let rename = |value: &str| ids.get(value).cloned().unwrap_or_else(|| value.to_string());
+    let ns = match el.ns() == XCCDF11_NS {
+        true => XCCDF12_NS.to_string(),
+        false => el.ns(),
+    };
+    let is_xccdf = el.ns() == XCCDF11_NS;
+    let is_item = is_xccdf && ID_KINDS.iter().any(|(name, _)| *name == el.name());
+    let mut builder = Element::builder(el.name(), ns);
+    for (name, value) in el.attrs() {
+        let value = match name {
+            "id" if is_item => rename(value),
+            "idref" if is_xccdf && matches!(el.name(), "requires" | "conflicts") => value
+                .split_whitespace()
+                .map(rename)
+                .collect::<Vec<String>>()
+                .join(" "),
+            name if is_xccdf && REFERENCE_ATTRS.contains(&name) => rename(value),
+            _ => value.to_string(),
+        };
+        builder = builder.attr(name, value);
+    }
+    for node in el.nodes() {
+        match node {
+            Node::Element(child) if child.ns() == XCCDF11_NS && REMOVED.contains(&child.name()) => {
+                notes.push(format!(
+                    "Removed '{}' of '{}', use CPE applicability language in 'platform' instead",
+                    child.name(),
+                    el.attr("id").unwrap_or(el.name())
+                ));
+            }
+            Node::Element(child) => builder = builder.append(convert(child, ids, notes)),
+            Node::Text(text) => builder = builder.append(text.as_str()),
+        }
+    }
+    builder.build()
+}
+
+/// Converts a XCCDF 1.1 benchmark to XCCDF 1.2: moves the elements to the
+/// XCCDF 1.2 namespace, changes the IDs of the items to the
+/// `xccdf_<reverse DNS>_<kind>_<name>` format and updates the references
+/// to them. Content in other namespaces, such as XHTML or Dublin Core, is
+/// kept as it is.
+pub fn xccdf_1_1_to_1_2(root: &Element, reverse_dns: &str) -> Result<Upgraded, String> {
+    if !root.is("Benchmark", XCCDF11_NS) {
+        return Err(format!(
+            "Expected a XCCDF 1.1 Benchmark, found '{}' in namespace '{}'",
+            root.name(),
+            root.ns()
+        ));
+    }
+    if !is_valid_reverse_dns(reverse_dns) {
+        return Err(format!(
+            "Invalid reverse DNS name '{}', expected letters, digits, dots and hyphens",
+            reverse_dns
+        ));
+    }
+    let mut ids = HashMap::new();
+    collect_ids(root, reverse_dns, &mut ids);
+    let mut notes = Vec::new();
+    let root = convert(root, &ids, &mut notes);
+    if !ids.is_empty() {
+        notes.insert(0, format!("Changed {} item IDs", ids.len()));
+    }
+    Ok(Upgraded { root, notes })
+}
+
+/// Rewrites the SCAP version of a source data stream collection and its
+/// SCAP 1.2 data streams to 1.3. The data streams get the current
+/// timestamp, and the attributes required by SCAP 1.3 that are missing are
+/// added: the timestamp of the components and the use case of the data
+/// streams, which becomes `OTHER`. Returns the notes on the changes.
+pub fn scap_1_2_to_1_3(root: &mut Element) -> Result<Vec<String>, String> {
+    if !root.is("data-stream-collection", SCAP12_NS) {
+        return Err(format!(
+            "Expected a source data stream collection, found '{}' in namespace '{}'",
+            root.name(),
+            root.ns()
+        ));
+    }
+    let mut notes = Vec::new();
+    let timestamp = current_timestamp();
+    for child in root.children_mut() {
+        let id = child.attr("id").unwrap_or_default().to_string();
+        match child.name() {
+            "data-stream" => {
+                match child.attr("scap-version") {
+                    Some("1.2") => {}
+                    Some("1.3") => continue,
+                    version => {
+                        return Err(format!(
+                            "data stream '{}' has scap-version {}, only SCAP 1.2 can be upgraded",
+                            id,
+                            version.unwrap_or("none")
+                        ))
+                    }
+                }
+                child.set_attr("scap-version", "1.3");
+                child.set_attr("timestamp", timestamp.as_str());
+                notes.push(format!("data stream '{}': set scap-version to 1.3", id));
+                if child.attr("use-case").is_none() {
+                    child.set_attr("use-case", "OTHER");
+                    notes.push(format!("data stream '{}': set use-case to OTHER", id));
+                }
+            }
+            "component" | "extended-component" if child.attr("timestamp").is_none() => {
+                child.set_attr("timestamp", timestamp.as_str());
+                notes.push(format!("{} '{}': set timestamp", child.name(), id));
+            }
+            _ => {}
+        }
+    }
+    if root.attr("schematron-version") != Some("1.3") {
+        root.set_attr("schematron-version", "1.3");
+        notes.push(String::from("set schematron-version to 1.3"));
+    }
+    Ok(notes)
+}
+
+/// Violations of the data stream requirements that the upgraded collection
+/// has and the original one didn't, e.g. OVAL versions SCAP 1.3 doesn't
+/// allow. Without the original, which can't be parsed when it lacks
+/// required attributes, all the violations are returned.
+#[cfg(feature = "validation")]
+pub fn scap_1_3_violations(
+    original: Option<&DataStreamCollection>,
+    upgraded: &DataStreamCollection,
+) -> Vec<String> {
+    let known: HashSet<String> = original
+        .map(validate::check_requirements)
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|r| r.violations.into_iter().map(|v| v.message))
+        .collect();
+    let mut violations = Vec::new();
+    for requirement in validate::check_requirements(upgraded) {
+        for violation in requirement.violations {
+            if !known.contains(&violation.message) {
+                violations.push(format!("{}: {}", requirement.name, violation.message));
+            }
+        }
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xccdf::Benchmark;
+
+    #[test]
+    fn test_xccdf_1_1_to_1_2() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" xmlns:h=\"http://www.w3.org/1999/xhtml\" id=\"RHEL_9_STIG\">\
+             <status>accepted</status><title>STIG</title><version>1</version>\
+             <platform-definitions/>\
+             <Profile id=\"MAC-1\"><title>MAC-1</title><select idref=\"V-1\" selected=\"true\"/>\
+             <refine-value idref=\"umask\" selector=\"strict\"/></Profile>\
+             <Value id=\"umask\"><value>027</value><value selector=\"strict\">077</value></Value>\
+             <Group id=\"V-1\"><title>SRG</title>\
+             <Rule id=\"SV-1r1_rule\" severity=\"high\"><title>Umask</title>\
+             <description>Set <h:code>umask</h:code>.</description>\
+             <requires idref=\"SV-2r1_rule other\"/>\
+             <fix>umask <sub idref=\"umask\"/></fix>\
+             <check system=\"http://oval.mitre.org/XMLSchema/oval-definitions-5\">\
+             <check-export value-id=\"umask\" export-name=\"oval:x:var:1\"/>\
+             <check-content-ref href=\"oval.xml\" name=\"oval:x:def:1\"/></check></Rule>\
+             <Rule id=\"SV-2r1_rule\"><title>Other</title></Rule></Group></Benchmark>",
+            XCCDF11_NS
+        )
+        .parse()
+        .unwrap();
+        let upgraded = xccdf_1_1_to_1_2(&el, "mil.disa.stig").unwrap();
+        assert_eq!(upgraded.notes.len(), 2);
+        let benchmark = Benchmark::from_xml(&upgraded.root).unwrap();
+        assert_eq!(benchmark.id(), "xccdf_mil.disa.stig_benchmark_RHEL_9_STIG");
+        let rule = benchmark
+            .rule("xccdf_mil.disa.stig_rule_SV-1r1_rule")
+            .unwrap();
+        assert_eq!(rule.descriptions()[0].text(), "Set umask.");
+        assert_eq!(
+            rule.requires()[0].idrefs(),
+            vec!["xccdf_mil.disa.stig_rule_SV-2r1_rule", "other"]
+        );
+        assert_eq!(
+            rule.checks()[0].exports()[0].value_id(),
+            "xccdf_mil.disa.stig_value_umask"
+        );
+        let profile = benchmark
+            .resolve_profile("xccdf_mil.disa.stig_profile_MAC-1")
+            .unwrap();
+        assert_eq!(
+            profile.selected_ids(),
+            vec!["xccdf_mil.disa.stig_group_V-1"]
+        );
+        assert_eq!(
+            benchmark.value_in_profile("xccdf_mil.disa.stig_value_umask", &profile),
+            Some(String::from("077"))
+        );
+        assert!(xccdf_1_1_to_1_2(&upgraded.root, "mil.disa.stig").is_err());
+        assert!(xccdf_1_1_to_1_2(&el, "mil_disa").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "validation")]
+    fn test_scap_1_2_to_1_3() {
+        let collection = |oval_version: &str| -> Element {
+            format!(
+                "<data-stream-collection xmlns=\"{}\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+                 id=\"scap_org.example_collection_c\" schematron-version=\"1.2\">\
+                 <data-stream id=\"scap_org.example_datastream_d\" scap-version=\"1.2\" use-case=\"OTHER\">\
+                 <checks><component-ref id=\"scap_org.example_cref_o\" xlink:href=\"#scap_org.example_comp_o\"/></checks>\
+                 </data-stream>\
+                 <component id=\"scap_org.example_comp_o\">\
+                 <oval_definitions xmlns=\"http://oval.mitre.org/XMLSchema/oval-definitions-5\">\
+                 <generator><schema_version>{}</schema_version></generator></oval_definitions>\
+                 </component></data-stream-collection>",
+                SCAP12_NS, oval_version
+            )
+            .parse()
+            .unwrap()
+        };
+        let original = collection("5.10.1");
+        let mut root = original.clone();
+        let notes = scap_1_2_to_1_3(&mut root).unwrap();
+        assert_eq!(notes.len(), 3);
+        assert_eq!(root.attr("schematron-version"), Some("1.3"));
+        let component = root.get_child("component", SCAP12_NS).unwrap();
+        assert!(component.attr("timestamp").is_some());
+        let upgraded = DataStreamCollection::from_xml(&root).unwrap();
+        let data_stream = &upgraded.data_streams()[0];
+        assert_eq!(data_stream.scap_version().as_str(), "1.3");
+
+        // the component needs a timestamp for the original to be parsed
+        let mut original = original;
+        original
+            .get_child_mut("component", SCAP12_NS)
+            .unwrap()
+            .set_attr("timestamp", "2024-01-01T00:00:00");
+        let original = DataStreamCollection::from_xml(&original).unwrap();
+        assert!(scap_1_3_violations(Some(&original), &upgraded).is_empty());
+
+        let mut root = collection("5.12");
+        scap_1_2_to_1_3(&mut root).unwrap();
+        let upgraded = DataStreamCollection::from_xml(&root).unwrap();
+        let violations = scap_1_3_violations(Some(&original), &upgraded);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].starts_with("check-versions: "));
+    }
+}
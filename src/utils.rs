@@ -1,81 +1,281 @@
+use crate::error::Error;
+use crate::options::{ParseOptions, Whitespace};
+use crate::richtext::RichText;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
 use minidom::Element;
 use minidom::Node;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
 
-pub fn get_attr(el: &Element, attr: &str) -> Option<String> {
-    match el.attr(attr) {
-        Some(val) => Some(val.to_string()),
-        None => None,
+/// Value of the attribute, shared with the equal values of the other
+/// attributes, see [`intern`].
+pub fn get_attr(el: &Element, attr: &str) -> Option<Arc<str>> {
+    el.attr(attr).map(intern)
+}
+
+/// Defines a fieldless enum of the allowed values of an attribute or element
+/// with `FromStr`, `Display` and `Serialize` using the XML spelling.
+macro_rules! string_enum {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident => $value:literal),+ $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant),+
+        }
+
+        impl $name {
+            pub const VALUES: &'static [&'static str] = &[$($value),+];
+
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $value),+
+                }
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<$name, String> {
+                match s {
+                    $($value => Ok($name::$variant),)+
+                    _ => Err(format!("expected one of {:?}", $name::VALUES)),
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+    };
+}
+pub(crate) use string_enum;
+
+fn parse_attr<T>(el: &Element, name: &str, val: &str) -> Result<T, Error>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    val.parse()
+        .map_err(|e| Error::invalid_value(el.name(), Some(name), val, e))
+}
+
+pub fn get_attr_default<T>(el: &Element, name: &str, default: T) -> Result<T, Error>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    match el.attr(name) {
+        Some(val) => parse_attr(el, name, val),
+        None => Ok(default),
     }
 }
 
-pub fn get_attr_default<T: std::str::FromStr>(
+/// Like [`get_attr_default`], but in the lenient mode an invalid value is
+/// replaced by the default and reported as a warning.
+pub fn get_attr_lenient<T>(
     el: &Element,
     name: &str,
     default: T,
-) -> Result<T, String> {
-    let value = match el.attr(name) {
-        Some(val) => match val.parse() {
-            Ok(x) => x,
-            Err(_) => {
-                return Err(format!(
-                    "Element '{}' attribute '{}' can't parse value '{}'.",
-                    el.name(),
-                    name,
-                    val
-                ))
-            }
-        },
-        None => default,
-    };
-    Ok(value)
-}
-
-pub fn get_attr_default_bool(el: &Element, name: &str, default: bool) -> Result<bool, String> {
-    let value = match el.attr(name) {
-        Some(val) => match val {
-            "true" | "1" => true,
-            "false" | "0" => false,
-            _ => {
-                return Err(format!(
-                    "Element '{}' attribute '{}' can't parse value '{}'.",
-                    el.name(),
-                    name,
-                    val
-                ))
-            }
-        },
-        None => default,
-    };
-    Ok(value)
+    opts: &ParseOptions,
+) -> Result<T, Error>
+where
+    T: std::str::FromStr + Clone,
+    T::Err: std::fmt::Display,
+{
+    opts.recover(get_attr_default(el, name, default.clone()), default)
 }
 
-pub fn get_attr_default_options(
+/// Optional attribute, in the lenient mode an invalid value is reported as
+/// a warning and treated as missing.
+pub fn get_attr_optional<T>(
     el: &Element,
     name: &str,
-    default: String,
+    opts: &ParseOptions,
+) -> Result<Option<T>, Error>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let parsed = el
+        .attr(name)
+        .map(|val| parse_attr(el, name, val))
+        .transpose();
+    opts.recover(parsed, None)
+}
+
+/// Boolean variant of [`get_attr_lenient`].
+pub fn get_attr_lenient_bool(
+    el: &Element,
+    name: &str,
+    default: bool,
+    opts: &ParseOptions,
+) -> Result<bool, Error> {
+    opts.recover(get_attr_default_bool(el, name, default), default)
+}
+
+pub fn require_attr_parsed<T>(el: &Element, name: &str) -> Result<T, Error>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let val = require_attr(el, name)?;
+    parse_attr(el, name, &val)
+}
+
+pub fn get_attr_default_bool(el: &Element, name: &str, default: bool) -> Result<bool, Error> {
+    match el.attr(name) {
+        Some("true") | Some("1") => Ok(true),
+        Some("false") | Some("0") => Ok(false),
+        Some(val) => Err(Error::invalid_value(
+            el.name(),
+            Some(name),
+            val,
+            "expected a boolean",
+        )),
+        None => Ok(default),
+    }
+}
+
+fn check_option<T: AsRef<str>>(
+    el: &Element,
+    name: &str,
+    val: T,
     options: Vec<&str>,
-) -> Result<String, String> {
-    let val = get_attr_default(el, name, default)?;
-    if options.contains(&&val[..]) {
+) -> Result<T, Error> {
+    if options.contains(&val.as_ref()) {
         return Ok(val);
     }
-    Err(format!(
-        "Element '{}' attribute '{}'='{}', but expected one of {:?}",
+    Err(Error::invalid_value(
         el.name(),
-        name,
-        val,
-        options
+        Some(name),
+        val.as_ref(),
+        format!("expected one of {:?}", options),
     ))
 }
 
-pub fn require_attr(el: &Element, attr: &str) -> Result<String, String> {
-    match el.attr(attr) {
-        Some(val) => Ok(val.to_string()),
-        None => Err(format!(
-            "Element '{}' doesn't have required '{}' attribute",
-            el.name(),
-            attr
-        )),
+pub fn get_attr_default_options(
+    el: &Element,
+    name: &str,
+    default: String,
+    options: Vec<&str>,
+) -> Result<String, Error> {
+    let val = get_attr_default(el, name, default)?;
+    check_option(el, name, val, options)
+}
+
+/// Location step of a child element in an error path: `name[@id='…']` for
+/// elements with an ID, `name[n]` if there are more siblings of the same
+/// name, otherwise just the name.
+fn path_step(el: &Element, position: usize, count: usize) -> String {
+    match el.attr("id") {
+        Some(id) => format!("{}[@id='{}']", el.name(), id),
+        None if count > 1 => format!("{}[{}]", el.name(), position),
+        None => el.name().to_string(),
+    }
+}
+
+/// Calls `f` for every child element of `el` and adds the location of the
+/// child to the errors it returns.
+pub fn for_each_child<F>(el: &Element, mut f: F) -> Result<(), Error>
+where
+    F: FnMut(&Element) -> Result<(), Error>,
+{
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for child in el.children() {
+        *counts.entry(child.name()).or_default() += 1;
+    }
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    for child in el.children() {
+        let position = positions.entry(child.name()).or_default();
+        *position += 1;
+        f(child).map_err(|e| e.within(&path_step(child, *position, counts[child.name()])))?;
+    }
+    Ok(())
+}
+
+/// Like [`for_each_child`], but in the lenient mode a child that fails to
+/// parse is dropped and the error is recorded as a warning.
+pub fn for_each_item<F>(el: &Element, opts: &ParseOptions, mut f: F) -> Result<(), Error>
+where
+    F: FnMut(&Element) -> Result<(), Error>,
+{
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for child in el.children() {
+        *counts.entry(child.name()).or_default() += 1;
+    }
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    for child in el.children() {
+        let position = positions.entry(child.name()).or_default();
+        *position += 1;
+        let step = path_step(child, *position, counts[child.name()]);
+        let _span = tracing::trace_span!("item", step = %step).entered();
+        if let Err(error) = opts.within(&step, || f(child)) {
+            opts.tolerate(error)?;
+        }
+    }
+    Ok(())
+}
+
+/// Shared copies of the strings repeated throughout a document, e.g. the
+/// IDs, the languages or the ident systems.
+#[derive(Debug, Default)]
+pub struct Interner {
+    strings: HashSet<Arc<str>>,
+    /// Number of strings after the last removal of the unused ones
+    kept: usize,
+}
+
+impl Interner {
+    pub fn intern(&mut self, text: &str) -> Arc<str> {
+        if let Some(shared) = self.strings.get(text) {
+            return shared.clone();
+        }
+        // the strings no longer used by any model are dropped once the set
+        // doubles, so that it doesn't grow with every parsed document
+        if self.strings.len() >= 2 * self.kept.max(1024) {
+            self.strings.retain(|s| Arc::strong_count(s) > 1);
+            self.kept = self.strings.len();
+        }
+        let shared: Arc<str> = Arc::from(text);
+        self.strings.insert(shared.clone());
+        shared
+    }
+
+    /// Number of distinct strings.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+thread_local! {
+    static INTERNER: RefCell<Interner> = RefCell::default();
+}
+
+/// Shares the string with the equal strings interned on the thread.
+pub fn intern(text: &str) -> Arc<str> {
+    INTERNER.with(|interner| interner.borrow_mut().intern(text))
+}
+
+pub fn require_attr(el: &Element, attr: &str) -> Result<Arc<str>, Error> {
+    match get_attr(el, attr) {
+        Some(val) => Ok(val),
+        None => Err(Error::missing_attribute(el.name(), attr)),
     }
 }
 
@@ -83,32 +283,173 @@ pub fn require_attr_options(
     el: &Element,
     attr: &str,
     options: Vec<&str>,
-) -> Result<String, String> {
+) -> Result<Arc<str>, Error> {
     let val = require_attr(el, attr)?;
-    if options.contains(&&val[..]) {
-        return Ok(val);
+    check_option(el, attr, val, options)
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+pub fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Text content of a simple (non-XHTML) element with the whitespace policy applied.
+pub fn get_text(el: &Element, opts: &ParseOptions) -> String {
+    let text = el.text();
+    match opts.whitespace {
+        Whitespace::Normalize => normalize_whitespace(&text),
+        Whitespace::Preserve => text,
     }
-    Err(format!(
-        "Element '{}' attribute '{}'='{}', but expected one of {:?}",
-        el.name(),
-        attr,
-        val,
-        options
-    ))
 }
 
-pub fn html_to_string(el: &Element) -> String {
-    let mut text = String::new();
-    for node in el.nodes() {
-        match node {
-            Node::Text(x) => text.push_str(&x.replace("\n", " ")),
-            Node::Element(x) => match x.name() {
-                "br" => text.push('\n'),
-                _ => text.push_str(&x.text().replace("\n", " ")),
-            },
-        }
+/// Inserts `child` so that it becomes the node at position `index` of `el`.
+pub fn insert_child_at(el: &mut Element, index: usize, child: Element) {
+    el.append_child(child);
+    let mut nodes: Vec<&mut Node> = el.nodes_mut().collect();
+    let last = nodes.len() - 1;
+    for i in (index.min(last)..last).rev() {
+        let (head, tail) = nodes.split_at_mut(i + 1);
+        std::mem::swap(head[i], tail[0]);
     }
-    text
+}
+
+/// Attributes of the element other than `known`, kept so that content the
+/// model doesn't represent isn't lost.
+pub fn other_attributes(el: &Element, known: &[&str]) -> Vec<(String, String)> {
+    el.attrs()
+        .filter(|(name, _)| !known.contains(name))
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Removes the child elements for which `keep` returns false. Text nodes are
+/// preserved.
+pub fn retain_children<F: Fn(&Element) -> bool>(el: &mut Element, keep: F) {
+    let mut builder = Element::builder(el.name(), el.ns());
+    for (name, value) in el.attrs() {
+        builder = builder.attr(name, value);
+    }
+    let nodes: Vec<Node> = el
+        .nodes()
+        .filter(|node| match node {
+            Node::Element(child) => keep(child),
+            Node::Text(_) => true,
+        })
+        .cloned()
+        .collect();
+    *el = builder.append_all(nodes).build();
+}
+
+/// Current UTC time formatted as an `xsd:dateTime` value.
+pub fn current_timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Formats seconds since the Unix epoch as an `xsd:dateTime` in UTC.
+pub fn format_timestamp(unix_secs: u64) -> String {
+    i64::try_from(unix_secs)
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .unwrap_or_default()
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string()
+}
+
+/// Whether the text is an `xsd` time zone, e.g. `Z` or `+01:00`.
+fn is_time_zone(zone: &str) -> bool {
+    zone == "Z"
+        || zone.len() == 6
+            && matches!(zone.as_bytes()[0], b'+' | b'-')
+            && FixedOffset::from_str(zone).is_ok()
+}
+
+/// Parses an `xsd:dateTime`, e.g. `2021-02-01T08:07:06+01:00`. The time
+/// zone is optional, values without it are taken as UTC.
+pub fn parse_date_time(text: &str) -> Result<DateTime<FixedOffset>, String> {
+    let split = text
+        .rfind(['Z', '+', '-'])
+        .filter(|&i| i > 10 && is_time_zone(&text[i..]));
+    let (local, zone) = match split {
+        Some(i) => text.split_at(i),
+        None => (text, "Z"),
+    };
+    let local = NaiveDateTime::parse_from_str(local, "%Y-%m-%dT%H:%M:%S%.f")
+        .map_err(|e| format!("expected an xsd:dateTime, {}", e))?;
+    let offset = match zone {
+        "Z" => FixedOffset::east_opt(0),
+        zone => FixedOffset::from_str(zone).ok(),
+    };
+    offset
+        .and_then(|offset| local.and_local_timezone(offset).single())
+        .ok_or_else(|| format!("invalid time zone '{}'", zone))
+}
+
+/// Parses an `xsd:date`, e.g. `2021-02-01`. The optional time zone is
+/// ignored.
+pub fn parse_date(text: &str) -> Result<NaiveDate, String> {
+    let (date, zone) = text.split_at(text.len().min(10));
+    if !zone.is_empty() && !is_time_zone(zone) {
+        return Err(format!("invalid time zone '{}'", zone));
+    }
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| format!("expected an xsd:date, {}", e))
+}
+
+/// Formats the time as an `xsd:dateTime`, with `Z` for UTC.
+pub fn format_date_time(time: &DateTime<FixedOffset>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+fn parse_time_attr<T>(
+    el: &Element,
+    name: &str,
+    parse: fn(&str) -> Result<T, String>,
+    opts: &ParseOptions,
+) -> Result<Option<T>, Error> {
+    let parsed = el
+        .attr(name)
+        .map(|val| parse(val).map_err(|e| Error::invalid_value(el.name(), Some(name), val, e)))
+        .transpose();
+    opts.recover(parsed, None)
+}
+
+/// Optional `xsd:dateTime` attribute, in the lenient mode an invalid value
+/// is reported as a warning and treated as missing.
+pub fn get_attr_date_time(
+    el: &Element,
+    name: &str,
+    opts: &ParseOptions,
+) -> Result<Option<DateTime<FixedOffset>>, Error> {
+    parse_time_attr(el, name, parse_date_time, opts)
+}
+
+/// Required variant of [`get_attr_date_time`]. The value is `None` only
+/// if it's invalid and the parse is lenient.
+pub fn require_attr_date_time(
+    el: &Element,
+    name: &str,
+    opts: &ParseOptions,
+) -> Result<Option<DateTime<FixedOffset>>, Error> {
+    require_attr(el, name)?;
+    get_attr_date_time(el, name, opts)
+}
+
+/// Optional `xsd:date` attribute, see [`get_attr_date_time`].
+pub fn get_attr_date(
+    el: &Element,
+    name: &str,
+    opts: &ParseOptions,
+) -> Result<Option<NaiveDate>, Error> {
+    parse_time_attr(el, name, parse_date, opts)
+}
+
+pub fn html_to_string(el: &Element) -> String {
+    RichText::from_xml(el).to_text(|_| None)
 }
 
 #[cfg(test)]
@@ -118,7 +459,7 @@ mod tests {
     #[test]
     fn test_get_attr() {
         let el: Element = "<person xmlns=\"people\" name=\"John\">".parse().unwrap();
-        assert_eq!(get_attr(&el, "name"), Some(String::from("John")));
+        assert_eq!(get_attr(&el, "name"), Some(Arc::from("John")));
     }
 
     #[test]
@@ -143,17 +484,29 @@ mod tests {
         assert_eq!(val4, Ok(String::from("17")));
     }
 
+    #[test]
+    fn test_interner_drops_unused() {
+        let mut interner = Interner::default();
+        let kept = interner.intern("kept");
+        for i in 0..2048 {
+            interner.intern(&i.to_string());
+        }
+        // the unused numbers were dropped when the set reached 2048 strings
+        assert_eq!(interner.len(), 2);
+        assert!(Arc::ptr_eq(&kept, &interner.intern("kept")));
+    }
+
     #[test]
     fn test_require_attr() {
         let el: Element = "<person xmlns=\"people\" name=\"John\">".parse().unwrap();
-        assert_eq!(require_attr(&el, "name"), Ok(String::from("John")));
+        assert_eq!(require_attr(&el, "name"), Ok(Arc::from("John")));
     }
 
     #[test]
     fn test_require_attr_missing() {
         let el: Element = "<person xmlns=\"people\">".parse().unwrap();
         assert_eq!(
-            require_attr(&el, "name"),
+            require_attr(&el, "name").map_err(String::from),
             Err(String::from(
                 "Element 'person' doesn't have required 'name' attribute"
             ))
@@ -165,14 +518,94 @@ mod tests {
         let el: Element = "<person xmlns=\"people\" name=\"John\">".parse().unwrap();
         assert_eq!(
             require_attr_options(&el, "name", vec!["John", "Peter"]),
-            Ok(String::from("John"))
+            Ok(Arc::from("John"))
         );
     }
 
     #[test]
     fn test_require_attr_options_wrong() {
         let el: Element = "<person xmlns=\"people\" name=\"Albert\">".parse().unwrap();
-        assert_eq!(require_attr_options(&el, "name", vec!["John", "Peter"]), Err(String::from("Element 'person' attribute 'name'='Albert', but expected one of [\"John\", \"Peter\"]")));
+        assert_eq!(require_attr_options(&el, "name", vec!["John", "Peter"]).map_err(String::from), Err(String::from("Element 'person' attribute 'name'='Albert', but expected one of [\"John\", \"Peter\"]")));
+    }
+
+    string_enum!(Color {
+        Red => "red",
+        Blue => "blue",
+    });
+
+    #[test]
+    fn test_require_attr_parsed_enum() {
+        let el: Element = "<car xmlns=\"cars\" color=\"red\">".parse().unwrap();
+        assert_eq!(require_attr_parsed(&el, "color"), Ok(Color::Red));
+        assert_eq!(get_attr_default(&el, "paint", Color::Blue), Ok(Color::Blue));
+        let el: Element = "<car xmlns=\"cars\" color=\"green\">".parse().unwrap();
+        assert_eq!(
+            require_attr_parsed::<Color>(&el, "color"),
+            Err(Error::invalid_value(
+                "car",
+                Some("color"),
+                "green",
+                "expected one of [\"red\", \"blue\"]"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_get_text() {
+        let el: Element = "<title xmlns=\"xccdf\">\n    Configure   the\n    firewall\n  </title>"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            get_text(&el, &ParseOptions::default()),
+            String::from("Configure the firewall")
+        );
+        let opts = ParseOptions {
+            whitespace: Whitespace::Preserve,
+            ..ParseOptions::default()
+        };
+        assert_eq!(
+            get_text(&el, &opts),
+            String::from("\n    Configure   the\n    firewall\n  ")
+        );
+    }
+
+    #[test]
+    fn test_insert_child_at() {
+        let mut el: Element = "<root xmlns=\"ns\"><b/><c/></root>".parse().unwrap();
+        insert_child_at(&mut el, 0, Element::bare("a", "ns"));
+        let names: Vec<&str> = el.children().map(|c| c.name()).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), String::from("1970-01-01T00:00:00Z"));
+        assert_eq!(
+            format_timestamp(1612163226),
+            String::from("2021-02-01T07:07:06Z")
+        );
+    }
+
+    #[test]
+    fn test_parse_date_time() {
+        let time = parse_date_time("2021-02-01T08:07:06+01:00").unwrap();
+        assert_eq!(time.timestamp(), 1612163226);
+        assert_eq!(format_date_time(&time), "2021-02-01T08:07:06+01:00");
+        let time = parse_date_time("2021-02-01T07:07:06").unwrap();
+        assert_eq!(format_date_time(&time), "2021-02-01T07:07:06Z");
+        let time = parse_date_time("2021-02-01T08:07:06.123Z").unwrap();
+        assert_eq!(format_date_time(&time), "2021-02-01T08:07:06.123Z");
+        assert!(parse_date_time("2021-02-01T08:07:06-05:00").is_ok());
+        assert!(parse_date_time("2021-02-01 08:07:06").is_err());
+        assert!(parse_date_time("2021-13-01T08:07:06").is_err());
+        assert!(parse_date_time("2021-02-01T08:07:06+1").is_err());
+
+        assert_eq!(
+            parse_date("2021-02-01Z").unwrap(),
+            NaiveDate::from_ymd_opt(2021, 2, 1).unwrap()
+        );
+        assert!(parse_date("2021-02-30").is_err());
+        assert!(parse_date("yesterday").is_err());
     }
 
     #[test]
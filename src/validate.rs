@@ -0,0 +1,982 @@
+use chrono::{Days, Utc};
+use minidom::Element;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cpe::Cpe;
+use crate::inventory;
+use crate::libxml::Schema;
+use crate::matrix::{dictionary, CPE_DICT_NS};
+use crate::misc;
+use crate::oval::OVAL_DEF_NS;
+use crate::sds::{
+    ComponentRef, ComponentTarget, DataStreamCollection, ScapVersion, UseCase, OCIL_NS, SCAP12_NS,
+};
+use crate::utils::format_date_time;
+use crate::xccdf::{Benchmark, Tailoring, XCCDF12_NS};
+
+/// Where OpenSCAP installs the SCAP schemas.
+pub const DEFAULT_SCHEMA_DIR: &str = "/usr/share/openscap/schemas";
+
+/// Problem found in the document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Violation {
+    /// Line of the document, when the validator reports it
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Violation {
+    fn new(message: String) -> Violation {
+        Violation {
+            line: None,
+            message,
+        }
+    }
+}
+
+/// Schema of the document by its root element, relative to the schema
+/// directory laid out like the one of OpenSCAP.
+pub fn schema_for(root: &Element) -> Option<PathBuf> {
+    let ns = root.ns();
+    let path = match root.name() {
+        "data-stream-collection" if ns == SCAP12_NS => {
+            let version = match root.attr("schematron-version") {
+                Some("1.2") => "1.2",
+                _ => "1.3",
+            };
+            format!("sds/{0}/scap-source-data-stream_{0}.xsd", version)
+        }
+        "Benchmark" | "Tailoring" if ns == XCCDF12_NS => String::from("xccdf/1.2/xccdf_1.2.xsd"),
+        "oval_definitions" if ns == OVAL_DEF_NS => {
+            let version = root
+                .get_child("generator", OVAL_DEF_NS)
+                .and_then(|generator| {
+                    generator
+                        .children()
+                        .find(|child| child.name() == "schema_version")
+                })
+                .map(|version| version.text().trim().to_string())
+                .unwrap_or_else(|| String::from("5.11.2"));
+            format!("oval/{}/oval-definitions-schema.xsd", version)
+        }
+        _ => return None,
+    };
+    Some(PathBuf::from(path))
+}
+
+/// Validates the document against the XSD schema by libxml2. Fails if the
+/// schema can't be used or the document isn't well-formed.
+pub fn validate_schema(data: &[u8], schema: &Path) -> Result<Vec<Violation>, String> {
+    if !schema.exists() {
+        return Err(format!("Schema '{}' not found", schema.display()));
+    }
+    Schema::load(schema)?.validate(data)
+}
+
+/// Loads the document into the model and checks the references between the
+/// components of a data stream collection.
+pub fn validate_model(root: &Element) -> Vec<Violation> {
+    let result = match root.name() {
+        "data-stream-collection" => DataStreamCollection::from_xml(root).map(Some),
+        "Benchmark" => {
+            return match Benchmark::from_xml(root) {
+                Ok(benchmark) => {
+                    let mut violations = dependency_violations(&benchmark);
+                    violations.extend(benchmark_id_violations(&benchmark));
+                    violations.extend(ident_violations(&benchmark));
+                    violations.extend(platform_violations(&benchmark));
+                    violations
+                }
+                Err(error) => vec![Violation::new(error.to_string())],
+            }
+        }
+        "Tailoring" => Tailoring::from_xml(root).map(|_| None),
+        name => {
+            return vec![Violation::new(format!(
+                "Validation of '{}' documents isn't supported",
+                name
+            ))]
+        }
+    };
+    match result {
+        Ok(Some(collection)) => {
+            let mut violations = reference_violations(&collection);
+            violations.extend(id_violations(&collection));
+            for benchmark in collection.benchmarks() {
+                violations.extend(ident_violations(benchmark));
+                violations.extend(platform_violations(benchmark));
+            }
+            for item in dictionary(root) {
+                if let Err(error) = item.name.parse::<Cpe>() {
+                    violations.push(Violation::new(format!(
+                        "CPE dictionary item '{}' isn't a valid CPE name: {}",
+                        item.name, error
+                    )));
+                }
+            }
+            violations.extend(schematron_violations(&collection));
+            violations
+        }
+        Ok(None) => Vec::new(),
+        Err(error) => vec![Violation::new(error.to_string())],
+    }
+}
+
+/// `requires` and `conflicts` of the groups and rules pointing to missing
+/// items.
+pub fn dependency_violations(benchmark: &Benchmark) -> Vec<Violation> {
+    benchmark
+        .missing_dependencies()
+        .into_iter()
+        .map(|missing| {
+            Violation::new(format!(
+                "'{}' {} missing item '{}'",
+                missing.item_id, missing.kind, missing.idref
+            ))
+        })
+        .collect()
+}
+
+/// IDs used by more than one element, `ids` are pairs of the ID and the
+/// kind of the element.
+fn duplicate_id_violations(ids: Vec<(&str, &'static str)>) -> Vec<Violation> {
+    let mut kinds: HashMap<&str, Vec<&'static str>> = HashMap::new();
+    let mut order = Vec::new();
+    for (id, kind) in ids {
+        let used = kinds.entry(id).or_default();
+        if used.is_empty() {
+            order.push(id);
+        }
+        used.push(kind);
+    }
+    let mut violations = Vec::new();
+    for id in order {
+        let used = &kinds[id];
+        if used.len() < 2 {
+            continue;
+        }
+        let message = match used.iter().all(|kind| *kind == used[0]) {
+            true => format!("{} ID '{}' is used {} times", used[0], id, used.len()),
+            false => format!("ID '{}' is used by {}", id, used.join(", ")),
+        };
+        violations.push(Violation::new(message));
+    }
+    violations
+}
+
+/// Profiles, groups, rules and values with duplicate IDs or IDs reused
+/// across item types.
+pub fn benchmark_id_violations(benchmark: &Benchmark) -> Vec<Violation> {
+    let mut ids = vec![(benchmark.id(), "Benchmark")];
+    ids.extend(benchmark.profiles().iter().map(|p| (p.id(), "Profile")));
+    ids.extend(benchmark.iter_values().map(|v| (v.item.id(), "Value")));
+    ids.extend(benchmark.iter_groups().map(|g| (g.item.id(), "Group")));
+    ids.extend(benchmark.iter_rules().map(|r| (r.item.id(), "Rule")));
+    duplicate_id_violations(ids)
+}
+
+/// Idents of the rules that don't have the format of their system, e.g. a
+/// CCE without the check digit.
+pub fn ident_violations(benchmark: &Benchmark) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for rule in benchmark.iter_rules() {
+        for ident in rule.item.idents().iter().filter(|i| !i.is_valid()) {
+            violations.push(Violation::new(format!(
+                "rule '{}' has {} ident '{}' in an invalid format",
+                rule.item.id(),
+                ident.kind(),
+                ident.text()
+            )));
+        }
+    }
+    violations
+}
+
+/// Platforms of the benchmark and its items that aren't valid CPE names or
+/// reference a missing platform of the platform specification, and invalid
+/// CPE names in the platform specification.
+pub fn platform_violations(benchmark: &Benchmark) -> Vec<Violation> {
+    let mut platforms = vec![("Benchmark", benchmark.id(), benchmark.platforms())];
+    platforms.extend(
+        benchmark
+            .profiles()
+            .iter()
+            .map(|p| ("Profile", p.id(), p.platforms())),
+    );
+    platforms.extend(
+        benchmark
+            .iter_groups()
+            .map(|g| ("Group", g.item.id(), g.item.platforms())),
+    );
+    platforms.extend(
+        benchmark
+            .iter_rules()
+            .map(|r| ("Rule", r.item.id(), r.item.platforms())),
+    );
+    let specification = benchmark.platform_specification();
+    let mut violations = Vec::new();
+    for (kind, id, platforms) in platforms {
+        for platform in platforms {
+            if let Some(platform_id) = platform.idref().strip_prefix('#') {
+                if specification
+                    .and_then(|s| s.platform(platform_id))
+                    .is_none()
+                {
+                    violations.push(Violation::new(format!(
+                        "{} '{}' references platform '{}' missing in the platform specification",
+                        kind,
+                        id,
+                        platform.idref()
+                    )));
+                }
+            } else if let Err(error) = platform.cpe() {
+                violations.push(Violation::new(format!(
+                    "{} '{}' has platform '{}' that isn't a valid CPE name: {}",
+                    kind,
+                    id,
+                    platform.idref(),
+                    error
+                )));
+            }
+        }
+    }
+    for platform in specification.map_or(&[][..], |s| s.platforms()) {
+        for name in platform.test().fact_names() {
+            if let Err(error) = name.parse::<Cpe>() {
+                violations.push(Violation::new(format!(
+                    "Platform '{}' has fact-ref '{}' that isn't a valid CPE name: {}",
+                    platform.id(),
+                    name,
+                    error
+                )));
+            }
+        }
+    }
+    violations
+}
+
+/// Colliding IDs of the data streams, component-refs and components of
+/// the collection and duplicate IDs in its benchmarks.
+pub fn id_violations(collection: &DataStreamCollection) -> Vec<Violation> {
+    let mut ids = Vec::new();
+    for ds in collection.data_streams() {
+        ids.push((ds.id(), "data-stream"));
+        let refs = ds
+            .dictionaries()
+            .iter()
+            .chain(ds.checklists())
+            .chain(ds.checks())
+            .chain(ds.extended_components());
+        ids.extend(refs.map(|r| (r.id(), "component-ref")));
+    }
+    ids.extend(
+        collection
+            .components()
+            .iter()
+            .map(|c| (c.id(), "component")),
+    );
+    ids.extend(
+        collection
+            .extended_components()
+            .iter()
+            .map(|c| (c.id(), "extended-component")),
+    );
+    let mut violations = duplicate_id_violations(ids);
+    for benchmark in collection.benchmarks() {
+        violations.extend(benchmark_id_violations(benchmark));
+    }
+    violations
+}
+
+/// Component-refs pointing to missing components, rules checking missing
+/// OVAL definitions and dependencies on missing items.
+pub fn reference_violations(collection: &DataStreamCollection) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for dangling in collection.dangling_refs() {
+        violations.push(Violation::new(format!(
+            "component-ref '{}' in the {} of data stream '{}' points to missing component '{}'",
+            dangling.ref_id,
+            dangling.list,
+            dangling.data_stream,
+            dangling.href.trim_start_matches('#')
+        )));
+    }
+    for missing in collection.missing_oval_definitions() {
+        violations.push(Violation::new(format!(
+            "rule '{}' checks OVAL definition '{}', which isn't in component '{}'",
+            missing.rule_id, missing.definition_id, missing.component_id
+        )));
+    }
+    for benchmark in collection.benchmarks() {
+        violations.extend(dependency_violations(benchmark));
+    }
+    violations
+}
+
+/// Whether the ID has the form `scap_<namespace>_<kind>_<name>` required
+/// for the IDs of the source data stream elements.
+fn is_scap_id(id: &str, kind: &str) -> bool {
+    is_structured_id(id, "scap", kind)
+}
+
+/// Whether the ID has the form `<prefix>_<namespace>_<kind>_<name>`.
+fn is_structured_id(id: &str, prefix: &str, kind: &str) -> bool {
+    let rest = match id
+        .strip_prefix(prefix)
+        .and_then(|r| r.strip_prefix('_'))
+        .and_then(|r| r.split_once('_'))
+    {
+        Some((namespace, rest)) if !namespace.is_empty() => rest,
+        _ => return false,
+    };
+    rest.strip_prefix(kind)
+        .and_then(|r| r.strip_prefix('_'))
+        .is_some_and(|name| !name.is_empty())
+}
+
+/// Checks that the component-refs of a list point to components with the
+/// expected content.
+fn check_content(
+    collection: &DataStreamCollection,
+    list: &str,
+    component_refs: &[ComponentRef],
+    expected: &[(&str, &str)],
+    violations: &mut Vec<Violation>,
+) {
+    for component_ref in component_refs.iter() {
+        let component = match collection.find_component_by_href(component_ref.href()) {
+            Some(ComponentTarget::Component(component)) => component,
+            _ => continue,
+        };
+        if !expected
+            .iter()
+            .any(|&(name, ns)| component.element_name() == name && component.element_ns() == ns)
+        {
+            violations.push(Violation::new(format!(
+                "component-ref '{}' in the {} points to '{}' content",
+                component_ref.id(),
+                list,
+                component.element_name()
+            )));
+        }
+    }
+}
+
+/// Checks the most important Schematron rules of the source data stream:
+/// the ID formats, the content of the component lists, the components
+/// required by the use case and the timestamps.
+pub fn schematron_violations(collection: &DataStreamCollection) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let mut check_id = |id: &str, kind: &str, what: &str| {
+        if !is_scap_id(id, kind) {
+            violations.push(Violation::new(format!(
+                "{} ID '{}' doesn't match 'scap_<namespace>_{}_<name>'",
+                what, id, kind
+            )));
+        }
+    };
+    check_id(collection.id(), "collection", "collection");
+    for ds in collection.data_streams() {
+        check_id(ds.id(), "datastream", "data stream");
+        let lists = [
+            ds.dictionaries(),
+            ds.checklists(),
+            ds.checks(),
+            ds.extended_components(),
+        ];
+        for component_ref in lists.iter().flat_map(|list| list.iter()) {
+            check_id(component_ref.id(), "cref", "component-ref");
+        }
+    }
+    for component in collection.components() {
+        check_id(component.id(), "comp", "component");
+    }
+    for component in collection.extended_components() {
+        check_id(component.id(), "ecomp", "extended component");
+    }
+
+    if !matches!(collection.schematron_version(), "1.2" | "1.3") {
+        violations.push(Violation::new(format!(
+            "unknown schematron-version '{}'",
+            collection.schematron_version()
+        )));
+    }
+    for ds in collection.data_streams() {
+        let name = format!("data stream '{}'", ds.id());
+        check_content(
+            collection,
+            "dictionaries",
+            ds.dictionaries(),
+            &[("cpe-list", CPE_DICT_NS)],
+            &mut violations,
+        );
+        check_content(
+            collection,
+            "checklists",
+            ds.checklists(),
+            &[("Benchmark", XCCDF12_NS)],
+            &mut violations,
+        );
+        check_content(
+            collection,
+            "checks",
+            ds.checks(),
+            &[("oval_definitions", OVAL_DEF_NS), ("ocil", OCIL_NS)],
+            &mut violations,
+        );
+        let required: &[(&str, &[ComponentRef])] = match ds.use_case() {
+            UseCase::Configuration => &[("checklist", ds.checklists()), ("check", ds.checks())],
+            UseCase::Vulnerability | UseCase::Inventory => &[("check", ds.checks())],
+            UseCase::Other => &[],
+        };
+        for (what, list) in required.iter() {
+            if list.is_empty() {
+                violations.push(Violation::new(format!(
+                    "{} with use-case {} requires at least one {}",
+                    name,
+                    ds.use_case(),
+                    what
+                )));
+            }
+        }
+    }
+
+    // Time zones are ignored, so only the dates after tomorrow are reported.
+    let tomorrow = Utc::now().date_naive() + Days::new(1);
+    for (what, timestamp) in collection.timestamps() {
+        if timestamp.date_naive() > tomorrow {
+            violations.push(Violation::new(format!(
+                "{} has timestamp '{}' in the future",
+                what,
+                format_date_time(&timestamp)
+            )));
+        }
+    }
+    violations
+}
+
+/// Finds the data streams and the components whose timestamp is more than
+/// `max_days` days old, e.g. to warn about content that hasn't been updated.
+pub fn check_age(collection: &DataStreamCollection, max_days: u64) -> Vec<Violation> {
+    let now = Utc::now();
+    let mut violations = Vec::new();
+    for (what, timestamp) in collection.timestamps() {
+        let days = now.signed_duration_since(timestamp).num_days();
+        if days > max_days as i64 {
+            violations.push(Violation::new(format!(
+                "{} is {} days old (timestamp '{}')",
+                what,
+                days,
+                format_date_time(&timestamp)
+            )));
+        }
+    }
+    violations
+}
+
+/// Requirement of the SCAP content and the problems that break it.
+#[derive(Debug, Serialize)]
+pub struct Requirement {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub violations: Vec<Violation>,
+}
+
+impl Requirement {
+    pub fn new(
+        name: &'static str,
+        description: &'static str,
+        violations: Vec<Violation>,
+    ) -> Requirement {
+        Requirement {
+            name,
+            description,
+            violations,
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// OVAL versions that can be used with the SCAP version.
+fn oval_versions(scap_version: ScapVersion) -> &'static [&'static str] {
+    const SCAP12: &[&str] = &[
+        "5.3", "5.4", "5.5", "5.6", "5.7", "5.8", "5.9", "5.10", "5.10.1",
+    ];
+    const SCAP13: &[&str] = &[
+        "5.3", "5.4", "5.5", "5.6", "5.7", "5.8", "5.9", "5.10", "5.10.1", "5.11", "5.11.1",
+        "5.11.2",
+    ];
+    match scap_version {
+        ScapVersion::V1_3 => SCAP13,
+        _ => SCAP12,
+    }
+}
+
+fn scap_version_violations(collection: &DataStreamCollection) -> Vec<Violation> {
+    collection
+        .data_streams()
+        .iter()
+        .filter(|ds| ds.scap_version().to_string() != collection.schematron_version())
+        .map(|ds| {
+            Violation::new(format!(
+                "data stream '{}' has scap-version {}, but the collection has schematron-version {}",
+                ds.id(),
+                ds.scap_version(),
+                collection.schematron_version()
+            ))
+        })
+        .collect()
+}
+
+/// SCAP 1.2 requires a CPE dictionary for the configuration and inventory
+/// use cases, SCAP 1.3 makes it optional.
+fn dictionary_violations(collection: &DataStreamCollection) -> Vec<Violation> {
+    collection
+        .data_streams()
+        .iter()
+        .filter(|ds| ds.scap_version() != ScapVersion::V1_3)
+        .filter(|ds| matches!(ds.use_case(), UseCase::Configuration | UseCase::Inventory))
+        .filter(|ds| ds.dictionaries().is_empty())
+        .map(|ds| {
+            Violation::new(format!(
+                "data stream '{}' with use-case {} requires a CPE dictionary in SCAP {}",
+                ds.id(),
+                ds.use_case(),
+                ds.scap_version()
+            ))
+        })
+        .collect()
+}
+
+fn xccdf_id_violations(collection: &DataStreamCollection) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let mut check = |id: &str, kind: &str| {
+        if !is_structured_id(id, "xccdf", kind) {
+            violations.push(Violation::new(format!(
+                "{} ID '{}' doesn't match 'xccdf_<namespace>_{}_<name>'",
+                kind, id, kind
+            )));
+        }
+    };
+    for benchmark in collection.benchmarks() {
+        check(benchmark.id(), "benchmark");
+        for profile in benchmark.profiles() {
+            check(profile.id(), "profile");
+        }
+        for group in benchmark.iter_groups() {
+            check(group.item.id(), "group");
+        }
+        for rule in benchmark.iter_rules() {
+            check(rule.item.id(), "rule");
+        }
+        for value in benchmark.iter_values() {
+            check(value.item.id(), "value");
+        }
+    }
+    violations
+}
+
+/// OVAL and OCIL components referenced by the data streams must use
+/// versions allowed by their SCAP version.
+fn check_version_violations(collection: &DataStreamCollection) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for ds in collection.data_streams() {
+        for component_ref in ds.checks() {
+            let component = match collection.find_component_by_href(component_ref.href()) {
+                Some(ComponentTarget::Component(component)) => component,
+                _ => continue,
+            };
+            let version = component.schema_version().unwrap_or("none");
+            let allowed = match (component.element_name(), component.element_ns()) {
+                ("oval_definitions", OVAL_DEF_NS) => oval_versions(ds.scap_version()),
+                ("ocil", OCIL_NS) => &["2.0"],
+                _ => continue,
+            };
+            if !allowed.contains(&version) {
+                violations.push(Violation::new(format!(
+                    "component '{}' uses {} version {}, SCAP {} allows {}",
+                    component.id(),
+                    component.element_name(),
+                    version,
+                    ds.scap_version(),
+                    allowed.join(", ")
+                )));
+            }
+        }
+    }
+    violations
+}
+
+/// Checks the source data stream like `oscap ds sds-validate`, each
+/// requirement passes or fails on its own.
+pub fn check_requirements(collection: &DataStreamCollection) -> Vec<Requirement> {
+    vec![
+        Requirement::new(
+            "references",
+            "component-refs and checks point to existing content",
+            reference_violations(collection),
+        ),
+        Requirement::new(
+            "unique-ids",
+            "IDs of the collection and its benchmarks are unique",
+            id_violations(collection),
+        ),
+        Requirement::new(
+            "ident-formats",
+            "CCE, CVE, CPE, CCI and DISA idents have the format of their system",
+            collection
+                .benchmarks()
+                .into_iter()
+                .flat_map(ident_violations)
+                .collect(),
+        ),
+        Requirement::new(
+            "schematron",
+            "source data stream Schematron rules",
+            schematron_violations(collection),
+        ),
+        Requirement::new(
+            "scap-version",
+            "the data streams have the SCAP version of the collection",
+            scap_version_violations(collection),
+        ),
+        Requirement::new(
+            "dictionaries",
+            "CPE dictionaries required by the use case",
+            dictionary_violations(collection),
+        ),
+        Requirement::new(
+            "xccdf-ids",
+            "XCCDF IDs follow the xccdf_<namespace>_<type>_<name> convention",
+            xccdf_id_violations(collection),
+        ),
+        Requirement::new(
+            "check-versions",
+            "OVAL and OCIL versions allowed by the SCAP version",
+            check_version_violations(collection),
+        ),
+    ]
+}
+
+/// Validation result of one data stream of a content directory.
+#[derive(Debug, Serialize)]
+pub struct FileValidation {
+    pub path: String,
+    /// Problems found by the model and schema validation
+    pub errors: Vec<Violation>,
+    /// Other SCAP content requirements the data stream doesn't meet
+    pub warnings: Vec<Violation>,
+}
+
+/// Validation results of all the data streams in a directory tree.
+#[derive(Debug, Serialize)]
+pub struct ContentValidation {
+    pub files: Vec<FileValidation>,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+impl ContentValidation {
+    pub fn passed(&self) -> bool {
+        self.errors == 0
+    }
+}
+
+/// Validates the file if it's a source data stream collection, a file
+/// which can't be read or parsed is reported with the error.
+fn validate_content_file(
+    path: &Path,
+    schema_dir: Option<&Path>,
+    max_age: Option<u64>,
+) -> Option<FileValidation> {
+    let mut validation = FileValidation {
+        path: path.display().to_string(),
+        errors: Vec::new(),
+        warnings: Vec::new(),
+    };
+    let parsed =
+        fs::read(path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| match misc::parse(&data) {
+                Ok((root, _)) => Ok((data, root)),
+                Err(error) => Err(error.to_string()),
+            });
+    let (data, root) = match parsed {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            validation.errors.push(Violation::new(error));
+            return Some(validation);
+        }
+    };
+    if !root.is("data-stream-collection", SCAP12_NS) {
+        return None;
+    }
+    validation.errors = validate_model(&root);
+    if let Some(schema_dir) = schema_dir {
+        let schema = schema_for(&root).map(|p| schema_dir.join(p));
+        match schema.map(|schema| validate_schema(&data, &schema)) {
+            Some(Ok(violations)) => validation.errors.extend(violations),
+            Some(Err(error)) => validation.errors.push(Violation::new(error)),
+            None => {}
+        }
+    }
+    if let Ok(collection) = DataStreamCollection::from_xml(&root) {
+        for requirement in check_requirements(&collection) {
+            for violation in requirement.violations {
+                if !validation.errors.contains(&violation) {
+                    validation.warnings.push(violation);
+                }
+            }
+        }
+        if let Some(max_days) = max_age {
+            validation.warnings.extend(check_age(&collection, max_days));
+        }
+    }
+    Some(validation)
+}
+
+/// Validates every source data stream found in the directory tree, against
+/// the schemas too if `schema_dir` is given. Other XML documents are
+/// skipped. Content older than `max_age` days is reported as a warning.
+pub fn validate_content(
+    dir: &Path,
+    schema_dir: Option<&Path>,
+    max_age: Option<u64>,
+) -> Result<ContentValidation, String> {
+    let files: Vec<FileValidation> = inventory::discover(&[dir.to_path_buf()])?
+        .iter()
+        .filter_map(|path| validate_content_file(path, schema_dir, max_age))
+        .collect();
+    Ok(ContentValidation {
+        errors: files.iter().map(|f| f.errors.len()).sum(),
+        warnings: files.iter().map(|f| f.warnings.len()).sum(),
+        files,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_for() {
+        let root: Element = format!(
+            "<data-stream-collection xmlns=\"{}\" schematron-version=\"1.2\"/>",
+            SCAP12_NS
+        )
+        .parse()
+        .unwrap();
+        assert_eq!(
+            schema_for(&root),
+            Some(PathBuf::from("sds/1.2/scap-source-data-stream_1.2.xsd"))
+        );
+        let root: Element = format!(
+            "<oval_definitions xmlns=\"{}\"><generator><schema_version>5.11.1</schema_version>\
+             </generator></oval_definitions>",
+            OVAL_DEF_NS
+        )
+        .parse()
+        .unwrap();
+        assert_eq!(
+            schema_for(&root),
+            Some(PathBuf::from("oval/5.11.1/oval-definitions-schema.xsd"))
+        );
+        let root: Element = "<foo xmlns=\"urn:x\"/>".parse().unwrap();
+        assert_eq!(schema_for(&root), None);
+    }
+
+    #[test]
+    fn test_schematron_violations() {
+        assert!(is_scap_id("scap_org.open-scap_comp_oval.xml", "comp"));
+        assert!(!is_scap_id("scap__comp_oval.xml", "comp"));
+        assert!(!is_scap_id("scap_org_cref_x", "comp"));
+
+        let data = std::fs::read("data/simple.xml").unwrap();
+        let (root, _) = crate::misc::parse(&data).unwrap();
+        let collection = DataStreamCollection::from_xml(&root).unwrap();
+        assert!(schematron_violations(&collection).is_empty());
+
+        let root: Element = format!(
+            "<data-stream-collection xmlns=\"{}\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             id=\"scap_x_collection_c\" schematron-version=\"1.3\">\
+             <data-stream id=\"scap_x_datastream_d\" use-case=\"CONFIGURATION\" scap-version=\"1.3\">\
+             <checks><component-ref id=\"scap_x_cref_c\" xlink:href=\"#c\"/></checks></data-stream>\
+             <component id=\"c\" timestamp=\"9999-01-01T00:00:00\">\
+             <foo xmlns=\"urn:example\"/></component></data-stream-collection>",
+            SCAP12_NS
+        )
+        .parse()
+        .unwrap();
+        let collection = DataStreamCollection::from_xml(&root).unwrap();
+        let messages: Vec<String> = schematron_violations(&collection)
+            .into_iter()
+            .map(|v| v.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "component ID 'c' doesn't match 'scap_<namespace>_comp_<name>'",
+                "component-ref 'scap_x_cref_c' in the checks points to 'foo' content",
+                "data stream 'scap_x_datastream_d' with use-case CONFIGURATION requires at least one checklist",
+                "component 'c' has timestamp '9999-01-01T00:00:00Z' in the future",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_age() {
+        let data = std::fs::read("data/simple.xml").unwrap();
+        let (root, _) = crate::misc::parse(&data).unwrap();
+        let collection = DataStreamCollection::from_xml(&root).unwrap();
+        let violations = check_age(&collection, 30);
+        assert_eq!(violations.len(), collection.timestamps().len());
+        assert!(violations[0]
+            .message
+            .ends_with("days old (timestamp '2021-02-01T08:07:06+01:00')"));
+        assert!(check_age(&collection, 1_000_000).is_empty());
+    }
+
+    #[test]
+    fn test_platform_violations() {
+        let root: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status>\
+             <platform idref=\"cpe:/o:redhat:enterprise_linux:9\"/><version>1</version>\
+             <Rule id=\"r\"><platform idref=\"#machine\"/><platform idref=\"rhel9\"/></Rule>\
+             </Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let messages: Vec<String> = validate_model(&root)
+            .into_iter()
+            .map(|v| v.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Rule 'r' references platform '#machine' missing in the platform specification",
+                "Rule 'r' has platform 'rhel9' that isn't a valid CPE name: \
+                  'rhel9' doesn't start with 'cpe:/' or 'cpe:2.3:'"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_id_violations() {
+        let root: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Profile id=\"x\"><title>P</title></Profile><Value id=\"v\"><value>1</value></Value>\
+             <Group id=\"g\"><Rule id=\"x\"/><Rule id=\"r\"/></Group><Rule id=\"r\"/></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let messages: Vec<String> = validate_model(&root)
+            .into_iter()
+            .map(|v| v.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "ID 'x' is used by Profile, Rule",
+                "Rule ID 'r' is used 2 times"
+            ]
+        );
+
+        let data = std::fs::read_to_string("data/simple.xml").unwrap().replace(
+            "scap_org.open-scap_comp_test_single_rule.xccdf.xml\"",
+            "scap_org.open-scap_comp_test_single_rule.oval.xml\"",
+        );
+        let (root, _) = crate::misc::parse(data.as_bytes()).unwrap();
+        let collection = DataStreamCollection::from_xml(&root).unwrap();
+        let messages: Vec<String> = id_violations(&collection)
+            .into_iter()
+            .map(|v| v.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "component ID 'scap_org.open-scap_comp_test_single_rule.oval.xml' is used 2 times"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_requirements() {
+        let data = std::fs::read_to_string("data/simple.xml").unwrap();
+        let (root, _) = crate::misc::parse(data.as_bytes()).unwrap();
+        let collection = DataStreamCollection::from_xml(&root).unwrap();
+        let requirements = check_requirements(&collection);
+        assert!(requirements.iter().all(|r| r.passed()));
+
+        let data = data
+            .replace("scap-version=\"1.3\"", "scap-version=\"1.2\"")
+            .replace(
+                "id=\"xccdf_com.example.www_rule_test-pass\"",
+                "id=\"rule1\"",
+            );
+        let (root, _) = crate::misc::parse(data.as_bytes()).unwrap();
+        let collection = DataStreamCollection::from_xml(&root).unwrap();
+        let failed: Vec<&str> = check_requirements(&collection)
+            .iter()
+            .filter(|r| !r.passed())
+            .map(|r| r.name)
+            .collect();
+        assert_eq!(failed, vec!["scap-version", "xccdf-ids", "check-versions"]);
+    }
+
+    #[test]
+    fn test_validate_schema() {
+        let dir = std::env::temp_dir().join(format!("oscapxml-xsd-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let schema = dir.join("test.xsd");
+        std::fs::write(
+            &schema,
+            "<xs:schema xmlns:xs=\"http://www.w3.org/2001/XMLSchema\">\
+             <xs:element name=\"a\"><xs:complexType><xs:sequence>\
+             <xs:element name=\"b\" type=\"xs:int\"/></xs:sequence></xs:complexType></xs:element>\
+             </xs:schema>",
+        )
+        .unwrap();
+        let violations = validate_schema(b"<a>\n<b>1</b>\n</a>", &schema).unwrap();
+        assert!(violations.is_empty());
+        let violations = validate_schema(b"<a>\n<b>x</b>\n</a>", &schema).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].line, Some(2));
+        assert!(violations[0].message.starts_with("Element 'b'"));
+        assert!(validate_schema(b"<a>", &schema).is_err());
+        assert!(validate_schema(b"<a/>", &dir.join("missing.xsd")).is_err());
+        std::fs::write(
+            &schema,
+            "<xs:schema xmlns:xs=\"http://www.w3.org/2001/XMLSchema\">",
+        )
+        .unwrap();
+        assert!(validate_schema(b"<a/>", &schema).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_validate_content() {
+        let dir = std::env::temp_dir().join(format!("oscapxml-validate-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::copy("data/simple.xml", dir.join("nested/simple-ds.xml")).unwrap();
+        fs::write(dir.join("broken-ds.xml"), "<data-stream-collection").unwrap();
+        fs::write(dir.join("other.xml"), "<x xmlns=\"urn:example\"/>").unwrap();
+        let validation = validate_content(&dir, None, None).unwrap();
+        let paths: Vec<&str> = validation.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths.len(), 2);
+        assert!(paths[0].ends_with("broken-ds.xml"));
+        assert!(paths[1].ends_with("simple-ds.xml"));
+        assert_eq!(validation.files[0].errors.len(), 1);
+        assert!(validation.files[1].errors.is_empty());
+        assert!(!validation.passed());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,115 @@
+use crate::xccdf::{Benchmark, Group, Profile, Rule, Value};
+
+/// Callbacks for [`Benchmark::walk`]. All of them do nothing by default, so
+/// a visitor implements only those for the items it's interested in.
+/// `groups` are the groups containing the item, the outermost first.
+pub trait BenchmarkVisitor {
+    fn visit_profile(&mut self, _profile: &Profile) {}
+
+    fn visit_value(&mut self, _value: &Value, _groups: &[&Group]) {}
+
+    fn visit_rule(&mut self, _rule: &Rule, _groups: &[&Group]) {}
+
+    /// Called before the items of the group are visited.
+    fn visit_group(&mut self, _group: &Group, _groups: &[&Group]) {}
+
+    /// Called after the items of the group are visited.
+    fn leave_group(&mut self, _group: &Group, _groups: &[&Group]) {}
+}
+
+fn walk_items<'a, V: BenchmarkVisitor + ?Sized>(
+    values: &'a [Value],
+    rules: &'a [Rule],
+    groups: &'a [Group],
+    path: &mut Vec<&'a Group>,
+    visitor: &mut V,
+) {
+    for value in values.iter() {
+        visitor.visit_value(value, path);
+    }
+    for rule in rules.iter() {
+        visitor.visit_rule(rule, path);
+    }
+    for group in groups.iter() {
+        visitor.visit_group(group, path);
+        path.push(group);
+        walk_items(group.values(), group.rules(), group.groups(), path, visitor);
+        path.pop();
+        visitor.leave_group(group, path);
+    }
+}
+
+impl Benchmark {
+    /// Visits the profiles, then the values, rules and groups in the
+    /// benchmark order, the items of a group directly after the group.
+    pub fn walk<V: BenchmarkVisitor + ?Sized>(&self, visitor: &mut V) {
+        for profile in self.profiles().iter() {
+            visitor.visit_profile(profile);
+        }
+        walk_items(
+            self.values(),
+            self.rules(),
+            self.groups(),
+            &mut Vec::new(),
+            visitor,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xccdf::XCCDF12_NS;
+    use minidom::Element;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+    }
+
+    impl BenchmarkVisitor for Recorder {
+        fn visit_profile(&mut self, profile: &Profile) {
+            self.events.push(format!("profile {}", profile.id()));
+        }
+
+        fn visit_rule(&mut self, rule: &Rule, groups: &[&Group]) {
+            self.events
+                .push(format!("rule {} in {}", rule.id(), groups.len()));
+        }
+
+        fn visit_group(&mut self, group: &Group, _groups: &[&Group]) {
+            self.events.push(format!("group {}", group.id()));
+        }
+
+        fn leave_group(&mut self, group: &Group, _groups: &[&Group]) {
+            self.events.push(format!("end {}", group.id()));
+        }
+    }
+
+    #[test]
+    fn test_walk() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Profile id=\"p\"><title>P</title></Profile><Rule id=\"r1\"/>\
+             <Group id=\"g1\"><Group id=\"g2\"><Rule id=\"r2\"/></Group></Group></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let mut recorder = Recorder::default();
+        benchmark.walk(&mut recorder);
+        assert_eq!(
+            recorder.events,
+            vec![
+                "profile p",
+                "rule r1 in 0",
+                "group g1",
+                "group g2",
+                "rule r2 in 2",
+                "end g2",
+                "end g1",
+            ]
+        );
+    }
+}
@@ -0,0 +1,58 @@
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::options::ParseOptions;
+use crate::output::{self, Format};
+use crate::sds::DataStreamCollection;
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, JsError> {
+    output::serialize(value, Format::Json).map_err(|e| JsError::new(&e))
+}
+
+/// Source data stream collection parsed in the browser. The reports are
+/// returned as the same JSON the command line tool prints with
+/// `--format json`.
+#[wasm_bindgen]
+pub struct ScapDocument {
+    collection: DataStreamCollection,
+}
+
+#[wasm_bindgen]
+impl ScapDocument {
+    /// Parses the content of a file, e.g. one the user selected, given as
+    /// an `Uint8Array`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: &[u8]) -> Result<ScapDocument, JsError> {
+        let collection = crate::parse_collection(data, &ParseOptions::default())?;
+        Ok(ScapDocument { collection })
+    }
+
+    /// Data streams, benchmarks and profiles, with the titles in the given
+    /// language.
+    pub fn info(&self, lang: Option<String>) -> Result<String, JsError> {
+        to_json(&self.collection.info(lang.as_deref()))
+    }
+
+    /// Selected rules and values set by the profile.
+    pub fn profile(&self, id: &str) -> Result<String, JsError> {
+        let profile = self.collection.resolve_profile(id)?;
+        to_json(&profile.info())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scap_document() {
+        let data = std::fs::read("data/simple.xml").unwrap();
+        let document = ScapDocument::new(&data).unwrap();
+        let info = document.info(None).unwrap();
+        assert!(info.contains("\"xccdf_com.example.www_benchmark_dummy\""));
+        let profile = document
+            .profile("xccdf_com.example.www_profile_test_single_rule")
+            .unwrap();
+        assert!(profile.contains("xccdf_com.example.www_rule_test-pass"));
+    }
+}
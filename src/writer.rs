@@ -0,0 +1,603 @@
+use minidom::quick_xml::events::Event;
+use minidom::quick_xml::Reader;
+use minidom::Element;
+use minidom::Node;
+use std::cell::OnceCell;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use crate::misc::{MiscNode, MiscNodes};
+
+/// Prefixes used for namespaces commonly found in SCAP content.
+const KNOWN_PREFIXES: [(&str, &str); 20] = [
+    ("ds", "http://scap.nist.gov/schema/scap/source/1.2"),
+    ("xccdf-1.2", "http://checklists.nist.gov/xccdf/1.2"),
+    ("xccdf", "http://checklists.nist.gov/xccdf/1.1"),
+    ("oval", "http://oval.mitre.org/XMLSchema/oval-common-5"),
+    (
+        "oval-def",
+        "http://oval.mitre.org/XMLSchema/oval-definitions-5",
+    ),
+    (
+        "ind-def",
+        "http://oval.mitre.org/XMLSchema/oval-definitions-5#independent",
+    ),
+    (
+        "unix-def",
+        "http://oval.mitre.org/XMLSchema/oval-definitions-5#unix",
+    ),
+    (
+        "linux-def",
+        "http://oval.mitre.org/XMLSchema/oval-definitions-5#linux",
+    ),
+    (
+        "win-def",
+        "http://oval.mitre.org/XMLSchema/oval-definitions-5#windows",
+    ),
+    ("cpe-dict", "http://cpe.mitre.org/dictionary/2.0"),
+    ("cpe-lang", "http://cpe.mitre.org/language/2.0"),
+    ("ocil", "http://scap.nist.gov/schema/ocil/2.0"),
+    ("cat", "urn:oasis:names:tc:entity:xmlns:xml:catalog"),
+    ("xlink", XLINK_NS),
+    ("xsi", "http://www.w3.org/2001/XMLSchema-instance"),
+    ("dc", DC_NS),
+    ("xhtml", XHTML_NS),
+    ("dsig", "http://www.w3.org/2000/09/xmldsig#"),
+    (
+        "arf",
+        "http://scap.nist.gov/schema/asset-reporting-format/1.1",
+    ),
+    ("core", "http://scap.nist.gov/schema/reporting-core/1.1"),
+];
+
+pub const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
+pub const XHTML_NS: &str = "http://www.w3.org/1999/xhtml";
+pub const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+
+/// Key of a namespaced attribute in the `{namespace}name` form. The element
+/// builder only knows plain attribute names, the writer replaces the
+/// namespace with the prefix bound to it on the root element.
+pub fn qualified_attr(ns: &str, name: &str) -> String {
+    format!("{{{}}}{}", ns, name)
+}
+
+fn split_qualified_attr(key: &str) -> Option<(&str, &str)> {
+    key.strip_prefix('{')?.split_once('}')
+}
+
+/// Namespaces bound to prefixes, the `None` prefix is the default namespace.
+type Bindings = BTreeMap<Option<String>, String>;
+
+/// Prefix of a parsed element and the namespace declarations in scope at it.
+struct Scope {
+    /// The prefix of the element name, `None` if no prefix in scope is
+    /// bound to its namespace
+    prefix: Option<Option<String>>,
+    bindings: Rc<Bindings>,
+}
+
+/// Namespace declarations of a parsed document. minidom keeps them private,
+/// so they're read back from its serialization, which declares them on the
+/// same elements as the parsed document.
+struct Scopes {
+    /// By the address of the element in the tree, which is borrowed as long
+    /// as the scopes are used
+    by_element: HashMap<*const Element, Scope>,
+}
+
+fn preorder<'a>(el: &'a Element, elements: &mut Vec<&'a Element>) {
+    elements.push(el);
+    for child in el.children() {
+        preorder(child, elements);
+    }
+}
+
+impl Scopes {
+    fn collect(root: &Element) -> io::Result<Scopes> {
+        let invalid =
+            |e: &dyn std::fmt::Display| io::Error::new(io::ErrorKind::InvalidData, e.to_string());
+        let mut data = Vec::new();
+        root.write_to(&mut data).map_err(|e| invalid(&e))?;
+        let mut elements = Vec::new();
+        preorder(root, &mut elements);
+        let mut elements = elements.into_iter();
+        let mut by_element = HashMap::new();
+        let mut stack = vec![Rc::new(Bindings::new())];
+        let mut reader = Reader::from_reader(data.as_slice());
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            let (event, empty) = match reader.read_event(&mut buf).map_err(|e| invalid(&e))? {
+                Event::Start(event) => (event, false),
+                Event::Empty(event) => (event, true),
+                Event::End(_) => {
+                    stack.pop();
+                    continue;
+                }
+                Event::Eof => break,
+                _ => continue,
+            };
+            let mut bindings = stack[stack.len() - 1].clone();
+            for attr in event.attributes().with_checks(false) {
+                let attr = attr.map_err(|e| invalid(&e))?;
+                let prefix = match attr.key {
+                    b"xmlns" => None,
+                    key => match key.strip_prefix(b"xmlns:") {
+                        Some(prefix) => Some(String::from_utf8_lossy(prefix).into_owned()),
+                        None => continue,
+                    },
+                };
+                let ns = attr
+                    .unescape_and_decode_value(&reader)
+                    .map_err(|e| invalid(&e))?;
+                Rc::make_mut(&mut bindings).insert(prefix, ns);
+            }
+            let el = elements
+                .next()
+                .ok_or_else(|| invalid(&"serialized document doesn't match the tree"))?;
+            let name = String::from_utf8_lossy(event.name());
+            let tag_prefix = name.split_once(':').map(|(prefix, _)| prefix.to_string());
+            let ns = el.ns();
+            let bound = |prefix: &Option<String>| {
+                bindings.get(prefix).map(String::as_str).unwrap_or_default() == ns
+            };
+            let prefix = match bound(&tag_prefix) {
+                true => Some(tag_prefix),
+                false => bindings.keys().find(|p| p.is_some() && bound(p)).cloned(),
+            };
+            if !empty {
+                stack.push(bindings.clone());
+            }
+            by_element.insert(el as *const Element, Scope { prefix, bindings });
+        }
+        Ok(Scopes { by_element })
+    }
+
+    fn get(&self, el: &Element) -> Option<&Scope> {
+        self.by_element.get(&(el as *const Element))
+    }
+
+    /// Namespace bound to `prefix` at the element.
+    fn resolve(&self, el: &Element, prefix: &str) -> Option<&str> {
+        let scope = self.get(el)?;
+        scope
+            .bindings
+            .get(&Some(prefix.to_string()))
+            .map(String::as_str)
+    }
+}
+
+struct Namespaces<'a> {
+    by_ns: BTreeMap<String, String>,
+    root: &'a Element,
+    /// Read on the first prefixed attribute
+    scopes: OnceCell<Scopes>,
+    /// The elements and attributes keep the prefixes of the parsed document,
+    /// the prefixes of `by_ns` are only used for the namespaces not declared
+    /// in it.
+    original: bool,
+}
+
+impl<'a> Namespaces<'a> {
+    fn collect(root: &'a Element) -> io::Result<Namespaces<'a>> {
+        let mut namespaces = Namespaces {
+            by_ns: BTreeMap::new(),
+            root,
+            scopes: OnceCell::new(),
+            original: false,
+        };
+        namespaces.add_element(root)?;
+        Ok(namespaces)
+    }
+
+    fn collect_original(root: &'a Element) -> io::Result<Namespaces<'a>> {
+        let mut namespaces = Namespaces::collect(root)?;
+        namespaces.original = true;
+        namespaces.scopes()?;
+        Ok(namespaces)
+    }
+
+    fn scopes(&self) -> io::Result<&Scopes> {
+        if let Some(scopes) = self.scopes.get() {
+            return Ok(scopes);
+        }
+        let scopes = Scopes::collect(self.root)?;
+        Ok(self.scopes.get_or_init(|| scopes))
+    }
+
+    /// Namespace of an attribute prefix, declared in the document or known.
+    fn resolve(&self, el: &Element, prefix: &str) -> io::Result<String> {
+        if let Some(ns) = self.scopes()?.resolve(el, prefix) {
+            return Ok(ns.to_string());
+        }
+        match KNOWN_PREFIXES.iter().find(|(p, _)| *p == prefix) {
+            Some((_, ns)) => Ok(ns.to_string()),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Attribute prefix '{}' isn't bound to a namespace", prefix),
+            )),
+        }
+    }
+
+    fn add_element(&mut self, el: &Element) -> io::Result<()> {
+        self.add_namespace(&el.ns());
+        for (key, _) in el.attrs() {
+            if let Some((ns, _)) = split_qualified_attr(key) {
+                self.add_namespace(ns);
+            } else if let Some((prefix, _)) = key.split_once(':') {
+                if prefix == "xml" {
+                    continue;
+                }
+                let ns = self.resolve(el, prefix)?;
+                self.add_namespace(&ns);
+            }
+        }
+        for child in el.children() {
+            self.add_element(child)?;
+        }
+        Ok(())
+    }
+
+    fn add_namespace(&mut self, ns: &str) {
+        if ns.is_empty() || self.by_ns.contains_key(ns) {
+            return;
+        }
+        let prefix = match KNOWN_PREFIXES.iter().find(|(_, n)| *n == ns) {
+            Some((p, _)) => p.to_string(),
+            None => format!("ns{}", self.by_ns.len()),
+        };
+        self.by_ns.insert(ns.to_string(), prefix);
+    }
+
+    /// Prefix of the element name, `None` without a prefix.
+    fn element_prefix(&self, el: &Element) -> Option<String> {
+        let original = match self.original {
+            true => self.scopes.get().and_then(|s| s.get(el)),
+            false => None,
+        };
+        match original.and_then(|scope| scope.prefix.clone()) {
+            Some(prefix) => prefix,
+            None => self.by_ns.get(&el.ns()).cloned(),
+        }
+    }
+
+    fn qualified_name(&self, el: &Element) -> String {
+        match self.element_prefix(el) {
+            Some(prefix) => format!("{}:{}", prefix, el.name()),
+            None => el.name().to_string(),
+        }
+    }
+
+    /// Namespace and qualified name of an attribute of the element.
+    fn attribute(&self, el: &Element, key: &str) -> io::Result<(String, String)> {
+        if let Some((ns, name)) = split_qualified_attr(key) {
+            let declared = match self.original {
+                true => self.scopes()?.get(el).and_then(|scope| {
+                    let mut bindings = scope.bindings.iter();
+                    bindings.find_map(|(p, n)| p.as_ref().filter(|_| n == ns))
+                }),
+                false => None,
+            };
+            let prefix = declared.unwrap_or(&self.by_ns[ns]);
+            return Ok((ns.to_string(), format!("{}:{}", prefix, name)));
+        }
+        match key.split_once(':') {
+            Some(("xml", _)) => Ok((XML_NS.to_string(), key.to_string())),
+            Some((prefix, name)) => {
+                let ns = self.resolve(el, prefix)?;
+                let qualified = match self.original {
+                    true => key.to_string(),
+                    false => format!("{}:{}", self.by_ns[&ns], name),
+                };
+                Ok((ns, qualified))
+            }
+            None => Ok((String::new(), key.to_string())),
+        }
+    }
+}
+
+pub fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\r', "&#13;")
+}
+
+/// Whitespace other than spaces is written as character references, because
+/// parsers normalize it to spaces in attribute values.
+pub fn escape_attr(value: &str) -> String {
+    escape_text(value)
+        .replace('"', "&quot;")
+        .replace('\n', "&#10;")
+        .replace('\t', "&#9;")
+}
+
+fn write_misc<W: Write>(node: &MiscNode, writer: &mut W) -> io::Result<()> {
+    match node {
+        MiscNode::Comment(text) => write!(writer, "<!--{}-->", text),
+        MiscNode::ProcessingInstruction(text) => write!(writer, "<?{}?>", text),
+    }
+}
+
+fn write_element<W: Write>(
+    el: &Element,
+    namespaces: &Namespaces,
+    misc: &MiscNodes,
+    is_root: bool,
+    writer: &mut W,
+) -> io::Result<()> {
+    let name = namespaces.qualified_name(el);
+    write!(writer, "<{}", name)?;
+    if is_root {
+        for (ns, prefix) in namespaces.by_ns.iter() {
+            write!(writer, " xmlns:{}=\"{}\"", prefix, escape_attr(ns))?;
+        }
+    }
+    for (key, value) in el.attrs() {
+        let (_, qualified) = namespaces.attribute(el, key)?;
+        write!(writer, " {}=\"{}\"", qualified, escape_attr(value))?;
+    }
+    if el.nodes().next().is_none() {
+        return write!(writer, "/>");
+    }
+    write!(writer, ">")?;
+    for node in el.nodes() {
+        match node {
+            Node::Element(child) => {
+                let preceding = child.attr("id").and_then(|id| misc.before.get(id));
+                for node in preceding.into_iter().flatten() {
+                    write_misc(node, writer)?;
+                }
+                write_element(child, namespaces, misc, false, writer)?
+            }
+            Node::Text(text) => write!(writer, "{}", escape_text(text))?,
+        }
+    }
+    write!(writer, "</{}>", name)
+}
+
+/// Serializes the element tree as a standalone XML document. Every namespace
+/// is bound to a prefix declared on the root element, including the
+/// namespaces of mixed content such as XHTML in descriptions and Dublin Core
+/// in metadata, and of attributes given by `qualified_attr`.
+pub fn write_document<W: Write>(root: &Element, writer: &mut W) -> io::Result<()> {
+    write_document_with_misc(root, &MiscNodes::default(), writer)
+}
+
+/// Like `write_document`, but puts back the comments and processing
+/// instructions removed when the document was loaded.
+pub fn write_document_with_misc<W: Write>(
+    root: &Element,
+    misc: &MiscNodes,
+    writer: &mut W,
+) -> io::Result<()> {
+    let namespaces = Namespaces::collect(root)?;
+    writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    for node in misc.prolog.iter() {
+        write_misc(node, writer)?;
+        writeln!(writer)?;
+    }
+    write_element(root, &namespaces, misc, true, writer)?;
+    writeln!(writer)
+}
+
+const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+
+fn c14n_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\r', "&#xD;")
+}
+
+fn c14n_attr(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('"', "&quot;")
+        .replace('\t', "&#x9;")
+        .replace('\n', "&#xA;")
+        .replace('\r', "&#xD;")
+}
+
+fn write_canonical<W: Write>(
+    el: &Element,
+    namespaces: &Namespaces,
+    excluded: Option<&Element>,
+    rendered: &Bindings,
+    writer: &mut W,
+) -> io::Result<()> {
+    let prefix = namespaces.element_prefix(el);
+    let name = namespaces.qualified_name(el);
+    // Namespaces visibly used by the element and its attributes, by prefix
+    let mut used = Bindings::new();
+    used.insert(prefix, el.ns());
+    let mut attrs = Vec::new();
+    for (key, value) in el.attrs() {
+        let (ns, qualified) = namespaces.attribute(el, key)?;
+        let local = match qualified.split_once(':') {
+            Some(("xml", local)) => local.to_string(),
+            Some((prefix, local)) => {
+                used.insert(Some(prefix.to_string()), ns.clone());
+                local.to_string()
+            }
+            None => qualified.clone(),
+        };
+        attrs.push((ns, local, qualified, value));
+    }
+    attrs.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+    write!(writer, "<{}", name)?;
+    // A declaration is rendered unless the output ancestors already did
+    let mut rendered = rendered.clone();
+    for (prefix, ns) in used {
+        if rendered
+            .get(&prefix)
+            .map(String::as_str)
+            .unwrap_or_default()
+            == ns
+        {
+            continue;
+        }
+        match &prefix {
+            Some(prefix) => write!(writer, " xmlns:{}=\"{}\"", prefix, c14n_attr(&ns))?,
+            None => write!(writer, " xmlns=\"{}\"", c14n_attr(&ns))?,
+        }
+        rendered.insert(prefix, ns);
+    }
+    for (_, _, qualified, value) in attrs {
+        write!(writer, " {}=\"{}\"", qualified, c14n_attr(value))?;
+    }
+    write!(writer, ">")?;
+    for node in el.nodes() {
+        match node {
+            Node::Element(child) if excluded.is_some_and(|e| std::ptr::eq(e, child)) => (),
+            Node::Element(child) => {
+                write_canonical(child, namespaces, excluded, &rendered, writer)?
+            }
+            Node::Text(text) => write!(writer, "{}", c14n_text(text))?,
+        }
+    }
+    write!(writer, "</{}>", name)
+}
+
+/// Exclusive XML canonicalization (`xml-exc-c14n#`, without comments) of
+/// `el`, a descendant of `root`, with the namespace prefixes `root` is
+/// written with. This is the form signatures digest a referenced element in.
+pub fn canonicalize(root: &Element, el: &Element) -> io::Result<Vec<u8>> {
+    let namespaces = Namespaces::collect(root)?;
+    let mut buffer = Vec::new();
+    write_canonical(el, &namespaces, None, &Bindings::new(), &mut buffer)?;
+    Ok(buffer)
+}
+
+/// Like [`canonicalize`], but for a document as it was parsed: the elements
+/// and attributes keep the prefixes declared in it, e.g. a default namespace
+/// or the prefixes of the tool which signed it. The `excluded` descendant is
+/// left out, as the enveloped signature transform does.
+pub fn canonicalize_parsed(
+    root: &Element,
+    el: &Element,
+    excluded: Option<&Element>,
+) -> io::Result<Vec<u8>> {
+    let namespaces = Namespaces::collect_original(root)?;
+    let mut buffer = Vec::new();
+    write_canonical(el, &namespaces, excluded, &Bindings::new(), &mut buffer)?;
+    Ok(buffer)
+}
+
+pub fn to_string(root: &Element) -> io::Result<String> {
+    let mut buffer = Vec::new();
+    write_document(root, &mut buffer)?;
+    String::from_utf8(buffer).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_document_round_trip() {
+        let el: Element = "<data-stream-collection xmlns=\"http://scap.nist.gov/schema/scap/source/1.2\" xmlns:xlink=\"http://www.w3.org/1999/xlink\"><component-ref xlink:href=\"#a&amp;b\">x &lt; y</component-ref><foo xmlns=\"urn:example\"/></data-stream-collection>"
+            .parse()
+            .unwrap();
+        let text = to_string(&el).unwrap();
+        assert!(
+            text.contains("<ds:component-ref xlink:href=\"#a&amp;b\">x &lt; y</ds:component-ref>")
+        );
+        assert!(text.contains("xmlns:ns2=\"urn:example\""));
+        let parsed: Element = text.lines().nth(1).unwrap().parse().unwrap();
+        assert_eq!(parsed, el);
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let root: Element = "<data-stream-collection xmlns=\"http://scap.nist.gov/schema/scap/source/1.2\" xmlns:xlink=\"http://www.w3.org/1999/xlink\"><component-ref xlink:href=\"#a\" id=\"r\">x &gt; y&#13;<catalog/></component-ref></data-stream-collection>"
+            .parse()
+            .unwrap();
+        let component_ref = root.children().next().unwrap();
+        let canonical = canonicalize(&root, component_ref).unwrap();
+        assert_eq!(
+            String::from_utf8(canonical).unwrap(),
+            "<ds:component-ref xmlns:ds=\"http://scap.nist.gov/schema/scap/source/1.2\" \
+             xmlns:xlink=\"http://www.w3.org/1999/xlink\" id=\"r\" xlink:href=\"#a\">\
+             x &gt; y&#xD;<ds:catalog></ds:catalog></ds:component-ref>"
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_parsed() {
+        let root: Element = "<data-stream-collection xmlns=\"http://scap.nist.gov/schema/scap/source/1.2\" xmlns:l=\"http://www.w3.org/1999/xlink\"><component-ref l:href=\"#a\" id=\"r\"><x:catalog xmlns:x=\"urn:oasis:names:tc:entity:xmlns:xml:catalog\"><x:uri l:type=\"simple\"/></x:catalog><Signature xmlns=\"http://www.w3.org/2000/09/xmldsig#\"/></component-ref></data-stream-collection>"
+            .parse()
+            .unwrap();
+        let component_ref = root.children().next().unwrap();
+        let signature = component_ref.children().nth(1).unwrap();
+        let canonical = canonicalize_parsed(&root, component_ref, Some(signature)).unwrap();
+        assert_eq!(
+            String::from_utf8(canonical).unwrap(),
+            "<component-ref xmlns=\"http://scap.nist.gov/schema/scap/source/1.2\" \
+             xmlns:l=\"http://www.w3.org/1999/xlink\" id=\"r\" l:href=\"#a\">\
+             <x:catalog xmlns:x=\"urn:oasis:names:tc:entity:xmlns:xml:catalog\">\
+             <x:uri l:type=\"simple\"></x:uri></x:catalog></component-ref>"
+        );
+        let canonical = canonicalize_parsed(&root, signature, None).unwrap();
+        assert_eq!(
+            String::from_utf8(canonical).unwrap(),
+            "<Signature xmlns=\"http://www.w3.org/2000/09/xmldsig#\"></Signature>"
+        );
+    }
+
+    #[test]
+    fn test_write_declared_attribute_prefix() {
+        let el: Element = "<Benchmark xmlns=\"http://checklists.nist.gov/xccdf/1.2\" xmlns:h=\"urn:example\"><rule h:note=\"x\"/></Benchmark>"
+            .parse()
+            .unwrap();
+        let text = to_string(&el).unwrap();
+        assert!(text.contains("xmlns:ns1=\"urn:example\""));
+        assert!(text.contains("<xccdf-1.2:rule ns1:note=\"x\"/>"));
+        let parsed: Element = text.lines().nth(1).unwrap().parse().unwrap();
+        assert_eq!(
+            parsed.children().next().unwrap().attr("ns1:note"),
+            Some("x")
+        );
+    }
+
+    #[test]
+    fn test_write_mixed_content() {
+        let xccdf = "http://checklists.nist.gov/xccdf/1.2";
+        let el = Element::builder("Benchmark", xccdf)
+            .append(
+                Element::builder("description", xccdf)
+                    .append("Run ")
+                    .append(
+                        Element::builder("code", XHTML_NS)
+                            .attr("class", "cmd")
+                            .append("ls"),
+                    )
+                    .append(" now."),
+            )
+            .append(
+                Element::builder("metadata", xccdf)
+                    .append(Element::builder("creator", DC_NS).append("Example")),
+            )
+            .append(
+                Element::builder("check-content-ref", xccdf)
+                    .attr(qualified_attr(XLINK_NS, "href"), "#oval")
+                    .attr("name", "a\nb"),
+            )
+            .build();
+        let text = to_string(&el).unwrap();
+        assert!(text.contains(
+            "<xccdf-1.2:description>Run <xhtml:code class=\"cmd\">ls</xhtml:code> now.</xccdf-1.2:description>"
+        ));
+        assert!(text.contains("<dc:creator>Example</dc:creator>"));
+        assert!(text.contains(" xlink:href=\"#oval\""));
+        assert!(text.contains(" name=\"a&#10;b\""));
+        let parsed: Element = text.lines().nth(1).unwrap().parse().unwrap();
+        let description = parsed.get_child("description", xccdf).unwrap();
+        assert!(description.has_child("code", XHTML_NS));
+        let reference = parsed.get_child("check-content-ref", xccdf).unwrap();
+        assert_eq!(reference.attr("xlink:href"), Some("#oval"));
+        assert_eq!(reference.attr("name"), Some("a\nb"));
+    }
+}
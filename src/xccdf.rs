@@ -1,44 +1,194 @@
+use crate::cpe::Cpe;
+use crate::dc::{self, DcElement};
+use crate::dsig;
+use crate::error::Error;
+use crate::info;
+use crate::locale::{preferred_text, Localized};
+use crate::options::ParseOptions;
+use crate::output::Table;
+use crate::report::{count, severity_counts};
+use crate::resolve;
+use crate::richtext::RichText;
 use crate::utils::*;
+use chrono::{DateTime, FixedOffset, NaiveDate};
 use minidom::Element;
+use minidom::Node;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+use std::sync::Arc;
 
 pub const XCCDF12_NS: &str = "http://checklists.nist.gov/xccdf/1.2";
+pub const CPE_LANG_NS: &str = "http://cpe.mitre.org/language/2.0";
+
+/// Children in foreign namespaces are vendor extensions, with the exception
+/// of the CPE platform specification that the schema itself allows.
+fn is_extension(el: &Element) -> bool {
+    !el.has_ns(XCCDF12_NS) && !el.is("platform-specification", CPE_LANG_NS)
+}
+
+/// Attributes of the groups and rules in the model.
+const ITEM_ATTRIBUTES: &[&str] = &[
+    "id",
+    "abstract",
+    "extends",
+    "hidden",
+    "prohibitChanges",
+    "selected",
+    "weight",
+    "cluster-id",
+];
+
+const RULE_ATTRIBUTES: &[&str] = &[
+    "id",
+    "abstract",
+    "extends",
+    "hidden",
+    "prohibitChanges",
+    "selected",
+    "weight",
+    "cluster-id",
+    "role",
+    "severity",
+    "multiple",
+];
+
+string_enum!(
+    /// Severity of a rule, `unknown` if not given.
+    Severity {
+        Unknown => "unknown",
+        Info => "info",
+        Low => "low",
+        Medium => "medium",
+        High => "high",
+    }
+);
+
+string_enum!(
+    /// Role of a rule in the scoring.
+    Role {
+        Full => "full",
+        Unscored => "unscored",
+        Unchecked => "unchecked",
+    }
+);
+
+string_enum!(
+    /// Maturity of a benchmark or an item given by `xccdf:status`.
+    Status {
+        Incomplete => "incomplete",
+        Draft => "draft",
+        Interim => "interim",
+        Accepted => "accepted",
+        Deprecated => "deprecated",
+    }
+);
+
+/// Checking engine given by the `system` attribute of `xccdf:check`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CheckSystem {
+    Oval,
+    Ocil,
+    Sce,
+    Other(String),
+}
+
+impl CheckSystem {
+    pub fn as_str(&self) -> &str {
+        match self {
+            CheckSystem::Oval => "http://oval.mitre.org/XMLSchema/oval-definitions-5",
+            CheckSystem::Ocil => "http://scap.nist.gov/schema/ocil/2",
+            CheckSystem::Sce => "http://open-scap.org/page/SCE",
+            CheckSystem::Other(system) => system,
+        }
+    }
+}
+
+impl std::str::FromStr for CheckSystem {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<CheckSystem, String> {
+        let system = match s {
+            "http://oval.mitre.org/XMLSchema/oval-definitions-5" => CheckSystem::Oval,
+            "http://scap.nist.gov/schema/ocil/2" | "http://scap.nist.gov/schema/ocil/2.0" => {
+                CheckSystem::Ocil
+            }
+            "http://open-scap.org/page/SCE" => CheckSystem::Sce,
+            _ => CheckSystem::Other(s.to_string()),
+        };
+        Ok(system)
+    }
+}
+
+impl std::fmt::Display for CheckSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
 #[derive(Debug)]
 pub struct Benchmark {
-    id: String,
-    resolved: bool,
-    style: Option<String>,
-    style_href: Option<String>,
-    statuses: Vec<Status>,
-    titles: Vec<Title>,
-    descriptions: Vec<Description>,
-    notices: Vec<Notice>,
-    front_matters: Vec<FrontMatter>,
-    rear_matters: Vec<RearMatter>,
-    references: Vec<Reference>,
-    plain_texts: Vec<PlainText>,
-    platform_specification: Option<PlatformSpecification>,
-    platforms: Vec<Platform>,
-    version: Version,
-    metadata: Vec<Metadata>,
-    models: Vec<Model>,
-    profiles: Vec<Profile>,
-    values: Vec<Value>,
-    groups: Vec<Group>,
-    rules: Vec<Rule>,
-    test_results: Vec<TestResult>,
+    pub(crate) id: Arc<str>,
+    pub(crate) resolved: bool,
+    /// Language of the content, the `xml:lang` attribute
+    pub(crate) lang: Option<Arc<str>>,
+    pub(crate) style: Option<Arc<str>>,
+    pub(crate) style_href: Option<Arc<str>>,
+    pub(crate) statuses: Vec<ItemStatus>,
+    pub(crate) titles: Vec<Title>,
+    pub(crate) descriptions: Vec<Description>,
+    pub(crate) notices: Vec<Notice>,
+    pub(crate) front_matters: Vec<FrontMatter>,
+    pub(crate) rear_matters: Vec<RearMatter>,
+    pub(crate) references: Vec<Reference>,
+    pub(crate) plain_texts: Vec<PlainText>,
+    pub(crate) platform_specification: Option<PlatformSpecification>,
+    pub(crate) platforms: Vec<Platform>,
+    pub(crate) version: Version,
+    pub(crate) metadata: Vec<Metadata>,
+    pub(crate) models: Vec<Model>,
+    pub(crate) profiles: Vec<Profile>,
+    pub(crate) values: Vec<Value>,
+    pub(crate) groups: Vec<Group>,
+    pub(crate) rules: Vec<Rule>,
+    pub(crate) test_results: Vec<TestResult>,
+    pub(crate) signature: Option<dsig::Signature>,
+    /// Children the model doesn't represent: foreign-namespace extensions
+    /// and, in the permissive mode, unknown elements
+    pub(crate) extensions: Vec<Element>,
+    /// Attributes the model doesn't represent
+    pub(crate) other_attributes: Vec<(String, String)>,
+    pub(crate) index: ItemIndex,
 }
 
 impl Benchmark {
-    pub fn from_xml(benchmark_el: &Element) -> Result<Benchmark, String> {
+    pub fn from_xml(benchmark_el: &Element) -> Result<Benchmark, Error> {
+        Benchmark::from_xml_with_options(benchmark_el, &ParseOptions::default())
+    }
+
+    pub fn from_xml_with_options(
+        benchmark_el: &Element,
+        opts: &ParseOptions,
+    ) -> Result<Benchmark, Error> {
+        let _span = tracing::debug_span!("benchmark", id = benchmark_el.attr("id")).entered();
+        let result = opts.within(benchmark_el.name(), || Benchmark::parse(benchmark_el, opts));
+        opts.finish(result)
+    }
+
+    fn parse(benchmark_el: &Element, opts: &ParseOptions) -> Result<Benchmark, Error> {
         if !benchmark_el.is("Benchmark", XCCDF12_NS) {
-            return Err(format!(
-                "Unexpected element '{}', expected xccdf:Benchmark",
-                benchmark_el.name()
+            return Err(Error::unexpected_element(
+                benchmark_el.name(),
+                Some("xccdf:Benchmark"),
             ));
         }
         let id = require_attr(benchmark_el, "id")?;
-        let resolved = get_attr_default_bool(benchmark_el, "resolved", false)?;
+        let other_attributes = other_attributes(
+            benchmark_el,
+            &["id", "resolved", "xml:lang", "style", "style-href"],
+        );
+        let resolved = get_attr_lenient_bool(benchmark_el, "resolved", false, opts)?;
+        let lang = get_attr(benchmark_el, "xml:lang");
         let style = get_attr(benchmark_el, "style");
         let style_href = get_attr(benchmark_el, "style-href");
         let mut statuses = Vec::new();
@@ -59,47 +209,73 @@ impl Benchmark {
         let mut groups = Vec::new();
         let mut rules = Vec::new();
         let mut test_results = Vec::new();
-        for child in benchmark_el.children() {
+        let mut signature = None;
+        let mut extensions = Vec::new();
+        for_each_item(benchmark_el, opts, |child| {
+            if is_extension(child) {
+                extensions.push(child.clone());
+                return Ok(());
+            }
             match child.name() {
-                "status" => statuses.push(Status::from_xml(child)?),
-                "title" => titles.push(Title::from_xml(child)?),
+                "status" => statuses.push(ItemStatus::from_xml(child, opts)?),
+                "signature" => match signature {
+                    Some(_) => {
+                        return Err(Error::duplicate_element(benchmark_el.name(), "signature"))
+                    }
+                    None => signature = Some(dsig::from_xccdf_signature(child)?),
+                },
+                "title" => titles.push(Title::from_xml(child, opts)?),
                 "description" => descriptions.push(Description::from_xml(child)?),
                 "notice" => notices.push(Notice::from_xml(child)?),
                 "front-matter" => front_matters.push(FrontMatter::from_xml(child)?),
                 "rear-matter" => rear_matters.push(RearMatter::from_xml(child)?),
-                "reference" => references.push(Reference::from_xml(child)?),
+                "reference" => references.push(Reference::from_xml(child, opts)?),
                 "plain-text" => plain_texts.push(PlainText::from_xml(child)?),
                 "platform-specification" => match platform_specification {
-                    Some(_) => return Err(format!("Duplicate platform elements")),
+                    Some(_) => {
+                        return Err(Error::duplicate_element(
+                            benchmark_el.name(),
+                            "platform-specification",
+                        ))
+                    }
                     None => platform_specification = Some(PlatformSpecification::from_xml(child)?),
                 },
                 "platform" => platforms.push(Platform::from_xml(child)?),
                 "version" => match version {
-                    Some(_) => return Err(format!("Duplicate version elements")),
-                    None => version = Some(Version::from_xml(child)?),
+                    Some(_) => {
+                        return Err(Error::duplicate_element(benchmark_el.name(), "version"))
+                    }
+                    None => version = Some(Version::from_xml(child, opts)?),
                 },
-                "metadata" => metadata.push(Metadata::from_xml(child)?),
+                "metadata" => metadata.push(Metadata::from_xml(child, opts)?),
                 "model" => models.push(Model::from_xml(child)?),
-                "Profile" => profiles.push(Profile::from_xml(child)?),
-                "Value" => values.push(Value::from_xml(child)?),
-                "Group" => groups.push(Group::from_xml(child)?),
-                "Rule" => rules.push(Rule::from_xml(child)?),
-                "TestResult" => test_results.push(TestResult::from_xml(child)?),
+                "Profile" => profiles.push(Profile::from_xml(child, opts)?),
+                "Value" => values.push(Value::from_xml(child, opts)?),
+                "Group" => groups.push(Group::from_xml(child, opts)?),
+                "Rule" => rules.push(Rule::from_xml(child, opts)?),
+                "TestResult" => test_results.push(TestResult::from_xml(child, opts)?),
                 _ => {
-                    return Err(format!("unexpected element {}", child.name()));
+                    opts.unknown_child(Error::unexpected_element(child.name(), None))?;
+                    extensions.push(child.clone());
                 }
             }
-        }
-        if statuses.len() == 0 {
-            return Err(format!("xccdf:Benchmark {}: missing status element", id));
+            Ok(())
+        })?;
+        if statuses.is_empty() {
+            opts.tolerate(Error::missing_element(benchmark_el.name(), "status"))?;
         }
         let version = match version {
             Some(x) => x,
-            None => return Err(format!("xccdf:Benchmark {}: missing version element", id)),
+            None => {
+                opts.tolerate(Error::missing_element(benchmark_el.name(), "version"))?;
+                Version::default()
+            }
         };
+        let index = ItemIndex::build(&profiles, &values, &groups, &rules);
         Ok(Benchmark {
             id,
             resolved,
+            lang,
             style,
             style_href,
             statuses,
@@ -120,357 +296,2172 @@ impl Benchmark {
             groups,
             rules,
             test_results,
+            signature,
+            extensions,
+            other_attributes,
+            index,
         })
     }
 
-    pub fn print_information(&self) {
-        println!("Benchmark ID: {}", self.id);
-        if self.profiles.len() > 0 {
-            println!("Profiles:");
-            for profile in self.profiles.iter() {
-                let title = match profile.titles.get(0) {
-                    Some(t) => &t.title,
-                    None => "Unknown",
-                };
-                let description = match profile.descriptions.get(0) {
-                    Some(d) => &d.text,
-                    None => "Unknown",
-                };
-                println!("* {}", title);
-                println!("ID: {}", profile.id);
-                println!("{}", description);
-                println!();
+    /// Parses the benchmark after transforming it into the resolved form,
+    /// see [`resolve::resolve_benchmark`].
+    pub fn resolve(benchmark_el: &Element, profile: Option<&str>) -> Result<Benchmark, Error> {
+        let mut resolved = benchmark_el.clone();
+        resolve::resolve_benchmark(&mut resolved, profile).map_err(Error::Content)?;
+        Benchmark::from_xml(&resolved)
+    }
+
+    pub fn has_test_results(&self) -> bool {
+        !self.test_results.is_empty()
+    }
+
+    /// Rule with the ID, anywhere in the benchmark.
+    pub fn rule(&self, id: &str) -> Option<&Rule> {
+        let location = self.index.rules.get(id)?;
+        self.locate(location, &self.rules, |g| &g.rules)
+    }
+
+    /// Value with the ID, anywhere in the benchmark.
+    pub fn value(&self, id: &str) -> Option<&Value> {
+        let location = self.index.values.get(id)?;
+        self.locate(location, &self.values, |g| &g.values)
+    }
+
+    /// Group with the ID, anywhere in the benchmark.
+    pub fn group(&self, id: &str) -> Option<&Group> {
+        let location = self.index.groups.get(id)?;
+        self.locate(location, &self.groups, |g| &g.groups)
+    }
+
+    /// Groups containing the rule, group or value with the ID, the
+    /// outermost first.
+    pub fn item_groups(&self, id: &str) -> Option<Vec<&Group>> {
+        let location = self
+            .index
+            .rules
+            .get(id)
+            .or_else(|| self.index.groups.get(id))
+            .or_else(|| self.index.values.get(id))?;
+        let mut groups = Vec::new();
+        let mut list = &self.groups;
+        for i in location.groups.iter() {
+            let group = list.get(*i)?;
+            groups.push(group);
+            list = &group.groups;
+        }
+        Some(groups)
+    }
+
+    fn locate<'a, T>(
+        &'a self,
+        location: &ItemLocation,
+        top: &'a [T],
+        items: fn(&'a Group) -> &'a [T],
+    ) -> Option<&'a T> {
+        let list = match location.groups.split_first() {
+            Some((first, rest)) => {
+                let mut group = self.groups.get(*first)?;
+                for i in rest.iter() {
+                    group = group.groups.get(*i)?;
+                }
+                items(group)
             }
+            None => top,
+        };
+        list.get(location.index)
+    }
+
+    /// All rules of the benchmark in the benchmark order.
+    pub(crate) fn all_rules(&self) -> Vec<&Rule> {
+        self.iter_rules().map(|r| r.item).collect()
+    }
+
+    /// All groups of the benchmark, each followed by the groups it contains.
+    pub fn iter_groups(&self) -> Groups<'_> {
+        Groups {
+            stack: vec![self.groups.iter()],
+            path: Vec::new(),
         }
     }
-}
 
-#[derive(Debug)]
-struct Status {
-    date: Option<String>,
-    status: String,
-}
+    /// All rules of the benchmark in the benchmark order, together with the
+    /// groups containing them.
+    pub fn iter_rules(&self) -> impl Iterator<Item = Nested<'_, Rule>> {
+        self.iter_items(&self.rules, |g| &g.rules)
+    }
+
+    /// All values of the benchmark, together with the groups containing them.
+    pub fn iter_values(&self) -> impl Iterator<Item = Nested<'_, Value>> {
+        self.iter_items(&self.values, |g| &g.values)
+    }
+
+    fn iter_items<'a, T>(
+        &'a self,
+        top: &'a [T],
+        items: fn(&'a Group) -> &'a [T],
+    ) -> impl Iterator<Item = Nested<'a, T>> {
+        let top = top.iter().map(|item| Nested {
+            item,
+            groups: Vec::new(),
+        });
+        let nested = self.iter_groups().flat_map(move |group| {
+            let mut groups = group.groups;
+            groups.push(group.item);
+            items(group.item).iter().map(move |item| Nested {
+                item,
+                groups: groups.clone(),
+            })
+        });
+        top.chain(nested)
+    }
+
+    /// Rules selected by the profile in the benchmark order. A rule is
+    /// selected only if all the groups containing it are selected too.
+    pub fn selected_rules<'a>(&'a self, profile: &ResolvedProfile) -> Vec<&'a Rule> {
+        fn walk_group<'a>(group: &'a Group, profile: &ResolvedProfile, rules: &mut Vec<&'a Rule>) {
+            if !profile.is_selected(&group.id, group.cluster_id.as_deref(), group.selected) {
+                return;
+            }
+            walk_items(&group.rules, &group.groups, profile, rules);
+        }
+        fn walk_items<'a>(
+            items: &'a [Rule],
+            groups: &'a [Group],
+            profile: &ResolvedProfile,
+            rules: &mut Vec<&'a Rule>,
+        ) {
+            for rule in items.iter() {
+                if profile.is_selected(&rule.id, rule.cluster_id.as_deref(), rule.selected) {
+                    rules.push(rule);
+                }
+            }
+            for group in groups.iter() {
+                walk_group(group, profile, rules);
+            }
+        }
+        let mut rules = Vec::new();
+        walk_items(&self.rules, &self.groups, profile, &mut rules);
+        rules
+    }
 
-impl Status {
-    pub fn from_xml(el: &Element) -> Result<Status, String> {
-        let date = get_attr(el, "date");
-        let status = el.text();
-        let allowed_statuses = vec!["incomplete", "draft", "interim", "accepted", "deprecated"];
-        if !allowed_statuses.contains(&&status[..]) {
-            return Err(format!("Unexpected xccdf:status value: '{}", status));
+    /// Value of a Value after applying the set-value and refine-value
+    /// selectors of the profile.
+    pub fn value_in_profile(&self, id: &str, profile: &ResolvedProfile) -> Option<String> {
+        if let Some(set_value) = profile.set_values.iter().find(|s| &*s.idref == id) {
+            return Some(set_value.text.clone());
         }
-        Ok(Status { date, status })
+        let selector = profile
+            .refine_values
+            .iter()
+            .find(|r| &*r.idref == id)
+            .and_then(|r| r.selector.as_deref());
+        self.value(id)
+            .and_then(|value| value.value_for(selector))
+            .map(|value| value.to_string())
     }
-}
 
-#[derive(Debug)]
-struct Title {
-    title: String,
-}
+    /// Items of a complex Value after applying the set-complex-value and
+    /// refine-value selectors of the profile.
+    pub fn complex_value_in_profile(
+        &self,
+        id: &str,
+        profile: &ResolvedProfile,
+    ) -> Option<Vec<String>> {
+        if let Some(set_value) = profile.set_complex_values.iter().find(|s| &*s.idref == id) {
+            return Some(set_value.items.clone());
+        }
+        let selector = profile
+            .refine_values
+            .iter()
+            .find(|r| &*r.idref == id)
+            .and_then(|r| r.selector.as_deref());
+        self.value(id)
+            .and_then(|value| value.complex_value_for(selector))
+            .map(|items| items.to_vec())
+    }
 
-impl Title {
-    pub fn from_xml(el: &Element) -> Result<Title, String> {
-        let title = el.text();
-        Ok(Title { title })
+    pub fn find_plain_text(&self, id: &str) -> Option<&str> {
+        self.plain_texts
+            .iter()
+            .find(|p| &*p.id == id)
+            .map(|p| p.text.as_str())
     }
-}
 
-#[derive(Debug)]
-struct Description {
-    text: String,
-}
+    /// Renders the description with the `sub` references to plain-text
+    /// definitions replaced by their text.
+    pub fn render(&self, description: &Description) -> String {
+        description
+            .content
+            .to_text(|idref| self.find_plain_text(idref).map(|text| text.to_string()))
+    }
 
-impl Description {
-    pub fn from_xml(el: &Element) -> Result<Description, String> {
-        let text = html_to_string(el);
-        Ok(Description { text })
+    /// Information about the benchmark with the titles and descriptions in
+    /// the preferred language, by default the language of the benchmark.
+    pub fn info(&self, lang: Option<&str>) -> info::BenchmarkInfo {
+        let lang = lang.or(self.lang());
+        let profiles = self
+            .profiles
+            .iter()
+            .map(|profile| info::ProfileInfo {
+                id: profile.id.to_string(),
+                title: preferred_text(&profile.titles, lang).map(|t| t.title.clone()),
+                description: preferred_text(&profile.descriptions, lang).map(|d| self.render(d)),
+            })
+            .collect();
+        info::BenchmarkInfo {
+            id: self.id.to_string(),
+            profiles,
+        }
     }
-}
 
-#[derive(Debug)]
-struct Notice {
-    id: String,
-    text: String,
-}
+    /// Table of the profiles with the number of rules they select.
+    pub fn write_information<W: Write>(
+        &self,
+        out: &mut W,
+        lang: Option<&str>,
+        colored: bool,
+    ) -> io::Result<()> {
+        let lang = lang.or(self.lang());
+        writeln!(out, "Benchmark: {}", self.id)?;
+        let mut table = Table::new(&["PROFILE ID", "RULES", "SEVERITY", "TITLE"]);
+        for profile in self.profiles.iter() {
+            let title = preferred_text(&profile.titles, lang).map_or("Unknown", |t| &t.title);
+            let (rules, severities) = match self.resolve_profile(&profile.id) {
+                Ok(resolved) => {
+                    let rules = self.selected_rules(&resolved);
+                    let severities = count(rules.iter().map(|r| resolved.severity(r).as_str()));
+                    (
+                        rules.len().to_string(),
+                        severity_counts(&severities, colored),
+                    )
+                }
+                Err(_) => (String::from("?"), String::new()),
+            };
+            table.row(vec![
+                profile.id.to_string(),
+                rules,
+                severities,
+                title.to_string(),
+            ]);
+        }
+        if !table.is_empty() {
+            table.write(out, 2)?;
+        }
+        Ok(())
+    }
 
-impl Notice {
-    pub fn from_xml(el: &Element) -> Result<Notice, String> {
-        let id = require_attr(el, "id")?;
-        let text = el.text();
-        Ok(Notice { id, text })
+    pub fn id(&self) -> &str {
+        &self.id
     }
-}
 
-#[derive(Debug)]
-struct FrontMatter {
-    text: String,
-}
+    pub fn is_resolved(&self) -> bool {
+        self.resolved
+    }
 
-impl FrontMatter {
-    pub fn from_xml(el: &Element) -> Result<FrontMatter, String> {
-        let text = el.text();
-        Ok(FrontMatter { text })
+    /// Language of the texts given by `xml:lang`.
+    pub fn lang(&self) -> Option<&str> {
+        self.lang.as_deref()
     }
-}
 
-#[derive(Debug)]
-struct RearMatter {
-    text: String,
-}
+    pub fn statuses(&self) -> &[ItemStatus] {
+        &self.statuses
+    }
 
-impl RearMatter {
-    pub fn from_xml(el: &Element) -> Result<RearMatter, String> {
-        let text = el.text();
-        Ok(RearMatter { text })
+    pub fn titles(&self) -> &[Title] {
+        &self.titles
     }
-}
 
-#[derive(Debug)]
-struct Reference {
-    text: String,
-}
+    pub fn descriptions(&self) -> &[Description] {
+        &self.descriptions
+    }
 
-impl Reference {
-    pub fn from_xml(el: &Element) -> Result<Reference, String> {
-        let text = el.text();
-        Ok(Reference { text })
+    pub fn notices(&self) -> &[Notice] {
+        &self.notices
     }
-}
 
-#[derive(Debug)]
-struct PlainText {
-    text: String,
-}
+    pub fn front_matters(&self) -> &[FrontMatter] {
+        &self.front_matters
+    }
 
-impl PlainText {
-    pub fn from_xml(el: &Element) -> Result<PlainText, String> {
-        let text = el.text();
-        Ok(PlainText { text })
+    pub fn rear_matters(&self) -> &[RearMatter] {
+        &self.rear_matters
     }
-}
 
-#[derive(Debug)]
-struct PlatformSpecification {
-    text: String,
-}
+    pub fn references(&self) -> &[Reference] {
+        &self.references
+    }
 
-impl PlatformSpecification {
-    pub fn from_xml(el: &Element) -> Result<PlatformSpecification, String> {
-        let text = el.text();
-        Ok(PlatformSpecification { text })
+    pub fn platforms(&self) -> &[Platform] {
+        &self.platforms
+    }
+
+    pub fn platform_specification(&self) -> Option<&PlatformSpecification> {
+        self.platform_specification.as_ref()
+    }
+
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
+    pub fn profiles(&self) -> &[Profile] {
+        &self.profiles
+    }
+
+    /// Values directly in the benchmark, not in its groups.
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    pub fn groups(&self) -> &[Group] {
+        &self.groups
+    }
+
+    /// Rules directly in the benchmark, not in its groups.
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    pub fn test_results(&self) -> &[TestResult] {
+        &self.test_results
+    }
+
+    /// The TestResult with the ID, or the last one without an ID.
+    pub fn test_result(&self, id: Option<&str>) -> Result<&TestResult, String> {
+        match id {
+            Some(id) => self
+                .test_results
+                .iter()
+                .find(|t| &*t.id == id)
+                .ok_or_else(|| format!("TestResult '{}' not found", id)),
+            None => self
+                .test_results
+                .last()
+                .ok_or_else(|| String::from("The benchmark doesn't contain any TestResult")),
+        }
+    }
+
+    pub fn extensions(&self) -> &[Element] {
+        &self.extensions
+    }
+
+    pub fn other_attributes(&self) -> &[(String, String)] {
+        &self.other_attributes
     }
 }
 
-#[derive(Debug)]
-struct Platform {
-    idref: String,
+#[derive(Debug, Serialize)]
+pub struct KindStats {
+    pub kind: String,
+    pub count: usize,
+    pub bytes: usize,
 }
 
-impl Platform {
-    pub fn from_xml(el: &Element) -> Result<Platform, String> {
-        let idref = require_attr(el, "idref")?;
-        Ok(Platform { idref })
-    }
+#[derive(Debug, Serialize)]
+pub struct TextBlob {
+    pub item_id: String,
+    pub field: String,
+    pub bytes: usize,
 }
 
-#[derive(Debug)]
-struct Version {
-    text: String,
+/// Approximate memory footprint of a parsed benchmark. Byte sizes count the
+/// in-memory size of the item structs plus the text they own, so they are
+/// a lower bound of the real allocation.
+#[derive(Debug, Serialize)]
+pub struct MemoryStats {
+    pub benchmark_id: String,
+    pub kinds: Vec<KindStats>,
+    pub total_bytes: usize,
+    pub largest_text: Option<TextBlob>,
 }
 
-impl Version {
-    pub fn from_xml(el: &Element) -> Result<Version, String> {
-        let text = el.text();
-        Ok(Version { text })
+impl MemoryStats {
+    pub fn write_text<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "Benchmark ID: {}", self.benchmark_id)?;
+        writeln!(out, "{:<12} {:>8} {:>12}", "Kind", "Count", "Bytes")?;
+        for kind in self.kinds.iter() {
+            writeln!(
+                out,
+                "{:<12} {:>8} {:>12}",
+                kind.kind, kind.count, kind.bytes
+            )?;
+        }
+        writeln!(out, "{:<12} {:>8} {:>12}", "Total", "", self.total_bytes)?;
+        if let Some(blob) = &self.largest_text {
+            writeln!(
+                out,
+                "Largest text: {} bytes in {} of '{}'",
+                blob.bytes, blob.field, blob.item_id
+            )?;
+        }
+        Ok(())
     }
 }
 
-#[derive(Debug)]
-struct Metadata {
-    contributors: Vec<String>,
-    publishers: Vec<String>,
-    creators: Vec<String>,
-    sources: Vec<String>,
+struct MemoryStatsCollector {
+    kinds: Vec<KindStats>,
+    largest_text: Option<TextBlob>,
 }
 
-impl Metadata {
-    pub fn from_xml(el: &Element) -> Result<Metadata, String> {
-        let mut contributors = Vec::new();
-        let mut publishers = Vec::new();
-        let mut creators = Vec::new();
-        let mut sources = Vec::new();
-        for child in el.children() {
-            match child.name() {
-                "contributor" => contributors.push(child.text()),
-                "publisher" => publishers.push(child.text()),
-                "creator" => creators.push(child.text()),
-                "source" => sources.push(child.text()),
-                _ => (),
+impl MemoryStatsCollector {
+    fn add(&mut self, kind: &str, bytes: usize) {
+        match self.kinds.iter_mut().find(|k| k.kind == kind) {
+            Some(stats) => {
+                stats.count += 1;
+                stats.bytes += bytes;
             }
+            None => self.kinds.push(KindStats {
+                kind: kind.to_string(),
+                count: 1,
+                bytes,
+            }),
         }
-        Ok(Metadata {
-            contributors,
-            publishers,
-            creators,
-            sources,
-        })
     }
-}
 
-#[derive(Debug)]
-struct Model {
-    text: String,
-}
+    fn text(&mut self, item_id: &str, field: &str, text: &str) -> usize {
+        let is_largest = match &self.largest_text {
+            Some(blob) => text.len() > blob.bytes,
+            None => !text.is_empty(),
+        };
+        if is_largest {
+            self.largest_text = Some(TextBlob {
+                item_id: item_id.to_string(),
+                field: field.to_string(),
+                bytes: text.len(),
+            });
+        }
+        text.len()
+    }
 
-impl Model {
-    pub fn from_xml(el: &Element) -> Result<Model, String> {
-        let text = el.text();
-        Ok(Model { text })
+    fn common_texts(
+        &mut self,
+        id: &str,
+        titles: &[Title],
+        descriptions: &[Description],
+        references: &[Reference],
+    ) -> usize {
+        let mut bytes = id.len();
+        for title in titles.iter() {
+            bytes += self.text(id, "title", &title.title);
+        }
+        for description in descriptions.iter() {
+            bytes += self.text(id, "description", &description.text());
+        }
+        for reference in references.iter() {
+            bytes += self.text(id, "reference", &reference.text);
+        }
+        bytes
+    }
+
+    fn profile(&mut self, profile: &Profile) {
+        let bytes = std::mem::size_of::<Profile>()
+            + self.common_texts(
+                &profile.id,
+                &profile.titles,
+                &profile.descriptions,
+                &profile.references,
+            )
+            + profile.selects.iter().map(|s| s.idref.len()).sum::<usize>();
+        self.add("Profile", bytes);
+    }
+
+    fn value(&mut self, value: &Value) {
+        let bytes = std::mem::size_of::<Value>()
+            + self.common_texts(&value.id, &value.titles, &value.descriptions, &[]);
+        self.add("Value", bytes);
+    }
+
+    fn group(&mut self, group: &Group) {
+        let mut bytes = std::mem::size_of::<Group>()
+            + self.common_texts(
+                &group.id,
+                &group.titles,
+                &group.descriptions,
+                &group.references,
+            );
+        for rationale in group.rationales.iter() {
+            bytes += self.text(&group.id, "rationale", &rationale.text());
+        }
+        self.add("Group", bytes);
+        for value in group.values.iter() {
+            self.value(value);
+        }
+        for child in group.groups.iter() {
+            self.group(child);
+        }
+        for rule in group.rules.iter() {
+            self.rule(rule);
+        }
+    }
+
+    fn rule(&mut self, rule: &Rule) {
+        let mut bytes = std::mem::size_of::<Rule>()
+            + self.common_texts(&rule.id, &rule.titles, &rule.descriptions, &rule.references);
+        for rationale in rule.rationales.iter() {
+            bytes += self.text(&rule.id, "rationale", &rationale.text());
+        }
+        for ident in rule.idents.iter() {
+            bytes += ident.system.len() + self.text(&rule.id, "ident", &ident.text);
+        }
+        for fixtext in rule.fixtexts.iter() {
+            bytes += self.text(&rule.id, "fixtext", &fixtext.text());
+        }
+        for fix in rule.fixes.iter() {
+            bytes += self.text(&rule.id, "fix", &fix.text());
+        }
+        for check in rule.checks.iter() {
+            bytes += self.text(&rule.id, "check", &check.text);
+        }
+        self.add("Rule", bytes);
     }
 }
 
+/// Indices of the groups leading to an item, the outermost first, and the
+/// index of the item in the last of them.
 #[derive(Debug)]
-struct Profile {
-    // attributes
-    id: String,
-    prohibit_changes: bool,
-    abstract_: bool,
-    note_tag: Option<String>,
-    extends: Option<String>,
-    // child elements
-    statuses: Vec<Status>,
-    version: Option<Version>,
-    titles: Vec<Title>,
-    descriptions: Vec<Description>,
-    references: Vec<Reference>,
-    platforms: Vec<Platform>,
-    selects: Vec<Select>,
-    set_complex_values: Vec<SetComplexValue>,
-    set_values: Vec<SetValue>,
-    refine_values: Vec<RefineValue>,
-    refine_rules: Vec<RefineRule>,
+pub(crate) struct ItemLocation {
+    groups: Vec<usize>,
+    index: usize,
 }
 
-impl Profile {
-    pub fn from_xml(el: &Element) -> Result<Profile, String> {
-        let id = require_attr(el, "id")?;
-        let prohibit_changes = get_attr_default_bool(el, "prohibitChanges", false)?;
-        let abstract_ = get_attr_default(el, "abstract", false)?;
-        let note_tag = get_attr(el, "note-tag");
-        let extends = get_attr(el, "extends");
-        let mut statuses = Vec::new();
-        let mut version = None;
-        let mut titles = Vec::new();
-        let mut descriptions = Vec::new();
-        let mut references = Vec::new();
-        let mut platforms = Vec::new();
-        let mut selects = Vec::new();
-        let mut set_complex_values = Vec::new();
-        let mut set_values = Vec::new();
-        let mut refine_values = Vec::new();
-        let mut refine_rules = Vec::new();
-        for child in el.children() {
-            match child.name() {
-                "status" => statuses.push(Status::from_xml(child)?),
-                "version" => match version {
-                    Some(_) => return Err(format!("Duplicate version elements")),
-                    None => version = Some(Version::from_xml(child)?),
-                },
-                "title" => titles.push(Title::from_xml(child)?),
-                "description" => descriptions.push(Description::from_xml(child)?),
-                "reference" => references.push(Reference::from_xml(child)?),
-                "platform" => platforms.push(Platform::from_xml(child)?),
-                "select" => selects.push(Select::from_xml(child)?),
-                "set-complex-value" => set_complex_values.push(SetComplexValue::from_xml(child)?),
-                "set-value" => set_values.push(SetValue::from_xml(child)?),
-                "refine-value" => refine_values.push(RefineValue::from_xml(child)?),
-                "refine-rule" => refine_rules.push(RefineRule::from_xml(child)?),
-                _ => {
-                    return Err(format!(
-                        "Profile '{}': unexpected element '{}'",
-                        id,
-                        child.name()
-                    ));
-                }
-            }
+/// Locations of the items of a benchmark by ID, built after parsing so
+/// that cross-references are resolved without scanning the whole tree.
+/// The first item wins if an ID is duplicated.
+#[derive(Debug, Default)]
+pub(crate) struct ItemIndex {
+    profiles: HashMap<Arc<str>, usize>,
+    values: HashMap<Arc<str>, ItemLocation>,
+    groups: HashMap<Arc<str>, ItemLocation>,
+    rules: HashMap<Arc<str>, ItemLocation>,
+}
+
+impl ItemIndex {
+    fn build(
+        profiles: &[Profile],
+        values: &[Value],
+        groups: &[Group],
+        rules: &[Rule],
+    ) -> ItemIndex {
+        let mut index = ItemIndex::default();
+        for (i, profile) in profiles.iter().enumerate() {
+            index.profiles.entry(profile.id.clone()).or_insert(i);
+        }
+        index.add(values, groups, rules, &mut Vec::new());
+        index
+    }
+
+    fn add(&mut self, values: &[Value], groups: &[Group], rules: &[Rule], path: &mut Vec<usize>) {
+        let location = |index| ItemLocation {
+            groups: path.clone(),
+            index,
+        };
+        for (i, value) in values.iter().enumerate() {
+            self.values
+                .entry(value.id.clone())
+                .or_insert_with(|| location(i));
         }
-        if titles.len() == 0 {
-            return Err(format!("Profile '{}' doesn't have any title", id));
+        for (i, rule) in rules.iter().enumerate() {
+            self.rules
+                .entry(rule.id.clone())
+                .or_insert_with(|| location(i));
+        }
+        for (i, group) in groups.iter().enumerate() {
+            self.groups
+                .entry(group.id.clone())
+                .or_insert_with(|| location(i));
+        }
+        for (i, group) in groups.iter().enumerate() {
+            path.push(i);
+            self.add(&group.values, &group.groups, &group.rules, path);
+            path.pop();
         }
-        Ok(Profile {
-            id,
-            prohibit_changes,
-            abstract_,
-            note_tag,
-            extends,
-            statuses,
-            version,
-            titles,
-            descriptions,
-            references,
-            platforms,
-            selects,
-            set_complex_values,
-            set_values,
-            refine_values,
-            refine_rules,
-        })
     }
 }
 
-#[derive(Debug)]
-struct Value {
-    id: String,
+/// Item of a benchmark found by a recursive iterator, e.g.
+/// [`Benchmark::iter_rules`].
+#[derive(Debug, Clone)]
+pub struct Nested<'a, T> {
+    pub item: &'a T,
+    /// Groups containing the item, the outermost first
+    pub groups: Vec<&'a Group>,
 }
 
-impl Value {
-    pub fn from_xml(el: &Element) -> Result<Value, String> {
-        let id = require_attr(el, "id")?;
-        Ok(Value { id })
+impl<'a, T> Nested<'a, T> {
+    /// The group directly containing the item.
+    pub fn parent(&self) -> Option<&'a Group> {
+        self.groups.last().copied()
     }
 }
 
-#[derive(Debug)]
-struct Group {
-    // attributes
-    id: String,
-    abstract_: bool,
-    cluster_id: Option<String>,
-    extends: Option<String>,
-    hidden: bool,
-    prohibit_changes: bool,
-    selected: bool,
-    weight: f64,
-    // children
-    statuses: Vec<Status>,
-    version: Option<Version>,
-    titles: Vec<Title>,
-    descriptions: Vec<Description>,
-    warnings: Vec<Warning>,
-    questions: Vec<Question>,
-    references: Vec<Reference>,
-    metadata: Vec<Metadata>,
-    rationales: Vec<Rationale>,
-    platforms: Vec<Platform>,
-    requires: Vec<Requires>,
-    conflicts: Vec<Conflicts>,
-    values: Vec<Value>,
-    groups: Vec<Group>,
-    rules: Vec<Rule>,
+/// Depth-first iterator over the groups of a benchmark, see
+/// [`Benchmark::iter_groups`].
+pub struct Groups<'a> {
+    stack: Vec<std::slice::Iter<'a, Group>>,
+    path: Vec<&'a Group>,
 }
 
-impl Group {
-    pub fn from_xml(el: &Element) -> Result<Group, String> {
-        let id = require_attr(el, "id")?;
-        let abstract_ = get_attr_default_bool(el, "abstract", false)?;
-        let extends = get_attr(el, "extends");
-        let hidden = get_attr_default_bool(el, "hidden", false)?;
-        let prohibit_changes = get_attr_default_bool(el, "prohibitChanges", false)?;
-        let selected = get_attr_default_bool(el, "selected", true)?;
-        let weight = get_attr_default(el, "weight", 1.0)?;
-        let cluster_id = get_attr(el, "cluster-id");
+impl<'a> Iterator for Groups<'a> {
+    type Item = Nested<'a, Group>;
 
-        let mut statuses = Vec::new();
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.last_mut()?.next() {
+                Some(group) => {
+                    let groups = self.path.clone();
+                    self.path.push(group);
+                    self.stack.push(group.groups.iter());
+                    return Some(Nested {
+                        item: group,
+                        groups,
+                    });
+                }
+                None => {
+                    self.stack.pop();
+                    self.path.pop();
+                }
+            }
+        }
+    }
+}
+
+fn collect_group_signatures<'a>(
+    group: &'a Group,
+    signatures: &mut Vec<(&'a str, &'a dsig::Signature)>,
+) {
+    if let Some(signature) = &group.signature {
+        signatures.push((&group.id, signature));
+    }
+    for value in group.values.iter() {
+        if let Some(signature) = &value.signature {
+            signatures.push((&value.id, signature));
+        }
+    }
+    for rule in group.rules.iter() {
+        if let Some(signature) = &rule.signature {
+            signatures.push((&rule.id, signature));
+        }
+    }
+    for child in group.groups.iter() {
+        collect_group_signatures(child, signatures);
+    }
+}
+
+fn collect_group_ids<'a>(group: &'a Group, ids: &mut Vec<&'a str>) {
+    ids.push(&group.id);
+    ids.extend(group.values.iter().map(|v| v.id.as_ref()));
+    ids.extend(group.rules.iter().map(|r| r.id.as_ref()));
+    for child in group.groups.iter() {
+        collect_group_ids(child, ids);
+    }
+}
+
+impl Benchmark {
+    /// IDs of the benchmark and all items it contains.
+    pub fn item_ids(&self) -> Vec<&str> {
+        let mut ids = vec![self.id.as_ref()];
+        ids.extend(self.profiles.iter().map(|p| p.id.as_ref()));
+        ids.extend(self.values.iter().map(|v| v.id.as_ref()));
+        ids.extend(self.rules.iter().map(|r| r.id.as_ref()));
+        for group in self.groups.iter() {
+            collect_group_ids(group, &mut ids);
+        }
+        ids.extend(self.test_results.iter().map(|t| t.id.as_ref()));
+        ids
+    }
+
+    /// `requires` and `conflicts` of the groups and rules that point to
+    /// items missing from the benchmark.
+    pub fn missing_dependencies(&self) -> Vec<MissingDependency> {
+        let ids: HashSet<&str> = self.item_ids().into_iter().collect();
+        let mut missing = Vec::new();
+        let mut check = |item_id: &str, requires: &[Requires], conflicts: &[Conflicts]| {
+            let idrefs = requires
+                .iter()
+                .flat_map(|r| r.idrefs.iter().map(|idref| ("requires", idref.as_str())))
+                .chain(conflicts.iter().map(|c| ("conflicts", &*c.idref)));
+            for (kind, idref) in idrefs {
+                if !ids.contains(idref) {
+                    missing.push(MissingDependency {
+                        item_id: item_id.to_string(),
+                        kind,
+                        idref: idref.to_string(),
+                    });
+                }
+            }
+        };
+        for group in self.iter_groups() {
+            check(&group.item.id, &group.item.requires, &group.item.conflicts);
+        }
+        for rule in self.iter_rules() {
+            check(&rule.item.id, &rule.item.requires, &rule.item.conflicts);
+        }
+        missing
+    }
+
+    /// Item-level signatures paired with the ID of the signed item.
+    pub fn signatures(&self) -> Vec<(&str, &dsig::Signature)> {
+        let mut signatures = Vec::new();
+        if let Some(signature) = &self.signature {
+            signatures.push((self.id.as_ref(), signature));
+        }
+        for profile in self.profiles.iter() {
+            if let Some(signature) = &profile.signature {
+                signatures.push((&profile.id, signature));
+            }
+        }
+        for value in self.values.iter() {
+            if let Some(signature) = &value.signature {
+                signatures.push((&value.id, signature));
+            }
+        }
+        for rule in self.rules.iter() {
+            if let Some(signature) = &rule.signature {
+                signatures.push((&rule.id, signature));
+            }
+        }
+        for group in self.groups.iter() {
+            collect_group_signatures(group, &mut signatures);
+        }
+        for test_result in self.test_results.iter() {
+            if let Some(signature) = &test_result.signature {
+                signatures.push((&test_result.id, signature));
+            }
+        }
+        signatures
+    }
+
+    pub fn find_profile(&self, id: &str) -> Option<&Profile> {
+        self.profiles.get(*self.index.profiles.get(id)?)
+    }
+
+    /// Merges the profile with the profiles it extends, directly or
+    /// transitively. Fails if a parent profile is missing or if the
+    /// `extends` attributes form a loop.
+    pub fn resolve_profile(&self, id: &str) -> Result<ResolvedProfile<'_>, Error> {
+        ResolvedProfile::resolve(id, |id| self.find_profile(id))
+    }
+
+    /// Resolves a profile of the tailoring, which can extend the profiles of
+    /// this benchmark. Profiles of the tailoring shadow the benchmark ones.
+    pub fn resolve_tailored_profile<'a>(
+        &'a self,
+        tailoring: &'a Tailoring,
+        id: &str,
+    ) -> Result<ResolvedProfile<'a>, Error> {
+        ResolvedProfile::resolve(id, |id| {
+            tailoring.find_profile(id).or_else(|| self.find_profile(id))
+        })
+    }
+
+    pub fn memory_stats(&self) -> MemoryStats {
+        let mut collector = MemoryStatsCollector {
+            kinds: Vec::new(),
+            largest_text: None,
+        };
+        let bytes = std::mem::size_of::<Benchmark>()
+            + collector.common_texts(&self.id, &self.titles, &self.descriptions, &self.references);
+        collector.add("Benchmark", bytes);
+        for profile in self.profiles.iter() {
+            collector.profile(profile);
+        }
+        for value in self.values.iter() {
+            collector.value(value);
+        }
+        for group in self.groups.iter() {
+            collector.group(group);
+        }
+        for rule in self.rules.iter() {
+            collector.rule(rule);
+        }
+        for test_result in self.test_results.iter() {
+            collector.add(
+                "TestResult",
+                std::mem::size_of::<TestResult>() + test_result.id.len(),
+            );
+        }
+        let total_bytes = collector.kinds.iter().map(|k| k.bytes).sum();
+        MemoryStats {
+            benchmark_id: self.id.to_string(),
+            kinds: collector.kinds,
+            total_bytes,
+            largest_text: collector.largest_text,
+        }
+    }
+}
+
+/// Dependency of a group or rule on a missing item, see
+/// [`Benchmark::missing_dependencies`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingDependency {
+    pub item_id: String,
+    /// `requires` or `conflicts`
+    pub kind: &'static str,
+    pub idref: String,
+}
+
+#[derive(Debug)]
+pub struct ItemStatus {
+    pub(crate) date: Option<NaiveDate>,
+    pub(crate) status: Status,
+}
+
+impl ItemStatus {
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> Result<ItemStatus, Error> {
+        let date = get_attr_date(el, "date", opts)?;
+        let text = get_text(el, opts);
+        let status = match text.parse() {
+            Ok(status) => status,
+            Err(error) => return Err(Error::invalid_value(el.name(), None, &text, error)),
+        };
+        Ok(ItemStatus { date, status })
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    pub fn date(&self) -> Option<NaiveDate> {
+        self.date
+    }
+}
+
+#[derive(Debug)]
+pub struct Title {
+    pub(crate) title: String,
+    pub(crate) lang: Option<Arc<str>>,
+}
+
+impl Title {
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> Result<Title, Error> {
+        let title = get_text(el, opts);
+        let lang = get_attr(el, "xml:lang");
+        Ok(Title { title, lang })
+    }
+
+    pub fn text(&self) -> &str {
+        &self.title
+    }
+
+    pub fn lang(&self) -> Option<&str> {
+        self.lang.as_deref()
+    }
+}
+
+impl Localized for Title {
+    fn lang(&self) -> Option<&str> {
+        self.lang.as_deref()
+    }
+}
+
+#[derive(Debug)]
+pub struct Description {
+    pub(crate) content: RichText,
+    pub(crate) lang: Option<Arc<str>>,
+}
+
+impl Description {
+    pub fn from_xml(el: &Element) -> Result<Description, Error> {
+        let content = RichText::from_xml(el);
+        let lang = get_attr(el, "xml:lang");
+        Ok(Description { content, lang })
+    }
+
+    pub fn lang(&self) -> Option<&str> {
+        self.lang.as_deref()
+    }
+
+    pub fn content(&self) -> &RichText {
+        &self.content
+    }
+
+    /// Text of the description without the substituted references, use
+    /// [`Benchmark::render`] to substitute them.
+    pub fn text(&self) -> String {
+        self.content.to_text(|_| None)
+    }
+}
+
+impl Localized for FixText {
+    fn lang(&self) -> Option<&str> {
+        self.lang.as_deref()
+    }
+}
+
+impl Localized for Description {
+    fn lang(&self) -> Option<&str> {
+        self.lang.as_deref()
+    }
+}
+
+/// Legal notice of a benchmark. The element is kept whole because its
+/// XHTML formatting matters when the notice is rendered in a guide.
+#[derive(Debug)]
+pub struct Notice {
+    pub(crate) id: Arc<str>,
+    pub(crate) lang: Option<Arc<str>>,
+    pub(crate) body: Element,
+}
+
+impl Notice {
+    pub fn from_xml(el: &Element) -> Result<Notice, Error> {
+        let id = require_attr(el, "id")?;
+        let lang = get_attr(el, "xml:lang");
+        Ok(Notice {
+            id,
+            lang,
+            body: el.clone(),
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn lang(&self) -> Option<&str> {
+        self.lang.as_deref()
+    }
+
+    /// The `xccdf:notice` element, its children are the XHTML content.
+    pub fn body(&self) -> &Element {
+        &self.body
+    }
+
+    /// The notice as plain text.
+    pub fn text(&self) -> String {
+        html_to_string(&self.body)
+    }
+}
+
+/// `xccdf:front-matter` of a benchmark, kept whole with its XHTML children.
+#[derive(Debug)]
+pub struct FrontMatter {
+    pub(crate) body: Element,
+}
+
+impl FrontMatter {
+    pub fn from_xml(el: &Element) -> Result<FrontMatter, Error> {
+        Ok(FrontMatter { body: el.clone() })
+    }
+
+    /// The `xccdf:front-matter` element, its children are the XHTML content.
+    pub fn body(&self) -> &Element {
+        &self.body
+    }
+
+    /// The text without the markup.
+    pub fn text(&self) -> String {
+        html_to_string(&self.body)
+    }
+}
+
+/// `xccdf:rear-matter` of a benchmark, kept whole with its XHTML children.
+#[derive(Debug)]
+pub struct RearMatter {
+    pub(crate) body: Element,
+}
+
+impl RearMatter {
+    pub fn from_xml(el: &Element) -> Result<RearMatter, Error> {
+        Ok(RearMatter { body: el.clone() })
+    }
+
+    /// The `xccdf:rear-matter` element, its children are the XHTML content.
+    pub fn body(&self) -> &Element {
+        &self.body
+    }
+
+    /// The text without the markup.
+    pub fn text(&self) -> String {
+        html_to_string(&self.body)
+    }
+}
+
+#[derive(Debug)]
+pub struct Reference {
+    pub(crate) href: Option<Arc<str>>,
+    /// Text outside of the Dublin Core children
+    pub(crate) text: String,
+    pub(crate) dc: Vec<DcElement>,
+}
+
+impl Reference {
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> Result<Reference, Error> {
+        let href = get_attr(el, "href");
+        let text = get_text(el, opts);
+        let dc = dc::parse_children(el, opts);
+        Ok(Reference { href, text, dc })
+    }
+
+    pub fn href(&self) -> Option<&str> {
+        self.href.as_deref()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn dc_elements(&self) -> &[DcElement] {
+        &self.dc
+    }
+
+    /// Text of the first Dublin Core child with the local name, e.g.
+    /// `identifier` for the control ID of a CIS or NIST mapping.
+    pub fn dc(&self, name: &str) -> Option<&str> {
+        dc::first(&self.dc, name)
+    }
+}
+
+#[derive(Debug)]
+pub struct PlainText {
+    pub(crate) id: Arc<str>,
+    pub(crate) text: String,
+}
+
+impl PlainText {
+    pub fn from_xml(el: &Element) -> Result<PlainText, Error> {
+        let id = require_attr(el, "id")?;
+        let text = el.text();
+        Ok(PlainText { id, text })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+string_enum!(
+    /// Operator of a `cpe-lang:logical-test`.
+    LogicalOperator {
+        And => "AND",
+        Or => "OR",
+    }
+);
+
+/// Test of a CPE applicability language expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlatformTest {
+    /// `cpe-lang:fact-ref`, true if the CPE name applies to the system
+    FactRef(Arc<str>),
+    /// `cpe-lang:check-fact-ref`, decided by a check, e.g. an OVAL
+    /// definition
+    CheckFactRef {
+        system: Arc<str>,
+        href: Arc<str>,
+        id_ref: Arc<str>,
+    },
+    Logical(LogicalTest),
+}
+
+impl PlatformTest {
+    pub fn from_xml(el: &Element) -> Result<PlatformTest, Error> {
+        if !el.has_ns(CPE_LANG_NS) {
+            return Err(Error::unexpected_element(el.name(), None));
+        }
+        match el.name() {
+            "fact-ref" => Ok(PlatformTest::FactRef(require_attr(el, "name")?)),
+            "check-fact-ref" => Ok(PlatformTest::CheckFactRef {
+                system: require_attr(el, "system")?,
+                href: require_attr(el, "href")?,
+                id_ref: require_attr(el, "id-ref")?,
+            }),
+            "logical-test" => Ok(PlatformTest::Logical(LogicalTest::from_xml(el)?)),
+            name => Err(Error::unexpected_element(name, None)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogicalTest {
+    pub(crate) operator: LogicalOperator,
+    pub(crate) negate: bool,
+    pub(crate) tests: Vec<PlatformTest>,
+}
+
+impl LogicalTest {
+    pub fn from_xml(el: &Element) -> Result<LogicalTest, Error> {
+        let operator = require_attr_parsed(el, "operator")?;
+        let negate = get_attr_default_bool(el, "negate", false)?;
+        let mut tests = Vec::new();
+        for_each_child(el, |child| {
+            tests.push(PlatformTest::from_xml(child)?);
+            Ok(())
+        })?;
+        Ok(LogicalTest {
+            operator,
+            negate,
+            tests,
+        })
+    }
+
+    pub fn operator(&self) -> LogicalOperator {
+        self.operator
+    }
+
+    pub fn negate(&self) -> bool {
+        self.negate
+    }
+
+    pub fn tests(&self) -> &[PlatformTest] {
+        &self.tests
+    }
+
+    /// Evaluates the expression with `fact` deciding the fact-refs and
+    /// `check` the check-fact-refs.
+    pub fn evaluate<F, C>(&self, fact: &F, check: &C) -> bool
+    where
+        F: Fn(&str) -> bool,
+        C: Fn(&str, &str, &str) -> bool,
+    {
+        let result = |test: &PlatformTest| match test {
+            PlatformTest::FactRef(name) => fact(name),
+            PlatformTest::CheckFactRef {
+                system,
+                href,
+                id_ref,
+            } => check(system, href, id_ref),
+            PlatformTest::Logical(test) => test.evaluate(fact, check),
+        };
+        let result = match self.operator {
+            LogicalOperator::And => self.tests.iter().all(result),
+            LogicalOperator::Or => self.tests.iter().any(result),
+        };
+        result != self.negate
+    }
+
+    /// Names of all the fact-refs of the expression.
+    pub fn fact_names(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for test in self.tests.iter() {
+            match test {
+                PlatformTest::FactRef(name) => names.push(name.as_ref()),
+                PlatformTest::CheckFactRef { .. } => (),
+                PlatformTest::Logical(test) => names.extend(test.fact_names()),
+            }
+        }
+        names
+    }
+}
+
+/// `cpe-lang:platform`, a named applicability expression referenced by
+/// `#id` from the XCCDF platforms.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpePlatform {
+    pub(crate) id: Arc<str>,
+    pub(crate) titles: Vec<String>,
+    pub(crate) remarks: Vec<String>,
+    pub(crate) test: LogicalTest,
+}
+
+impl CpePlatform {
+    pub fn from_xml(el: &Element) -> Result<CpePlatform, Error> {
+        let id = require_attr(el, "id")?;
+        let mut titles = Vec::new();
+        let mut remarks = Vec::new();
+        let mut test = None;
+        for_each_child(el, |child| {
+            if !child.has_ns(CPE_LANG_NS) {
+                return Ok(());
+            }
+            match child.name() {
+                "title" => titles.push(child.text()),
+                "remark" => remarks.push(child.text()),
+                "logical-test" => match test {
+                    Some(_) => return Err(Error::duplicate_element(el.name(), "logical-test")),
+                    None => test = Some(LogicalTest::from_xml(child)?),
+                },
+                name => return Err(Error::unexpected_element(name, None)),
+            }
+            Ok(())
+        })?;
+        let test = test.ok_or_else(|| Error::missing_element(el.name(), "logical-test"))?;
+        Ok(CpePlatform {
+            id,
+            titles,
+            remarks,
+            test,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn titles(&self) -> &[String] {
+        &self.titles
+    }
+
+    pub fn remarks(&self) -> &[String] {
+        &self.remarks
+    }
+
+    pub fn test(&self) -> &LogicalTest {
+        &self.test
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlatformSpecification {
+    pub(crate) platforms: Vec<CpePlatform>,
+}
+
+impl PlatformSpecification {
+    pub fn from_xml(el: &Element) -> Result<PlatformSpecification, Error> {
+        let mut platforms = Vec::new();
+        for_each_child(el, |child| {
+            if child.is("platform", CPE_LANG_NS) {
+                platforms.push(CpePlatform::from_xml(child)?);
+            }
+            Ok(())
+        })?;
+        Ok(PlatformSpecification { platforms })
+    }
+
+    pub fn platforms(&self) -> &[CpePlatform] {
+        &self.platforms
+    }
+
+    /// The platform with the given ID, without the leading `#`.
+    pub fn platform(&self, id: &str) -> Option<&CpePlatform> {
+        self.platforms.iter().find(|p| &*p.id == id)
+    }
+}
+
+#[derive(Debug)]
+pub struct Platform {
+    pub(crate) idref: Arc<str>,
+}
+
+impl Platform {
+    pub fn from_xml(el: &Element) -> Result<Platform, Error> {
+        let idref = require_attr(el, "idref")?;
+        Ok(Platform { idref })
+    }
+
+    pub fn idref(&self) -> &str {
+        &self.idref
+    }
+
+    /// The CPE name of the platform, `None` if the idref points to a
+    /// platform of the platform specification.
+    pub fn cpe(&self) -> Result<Option<Cpe>, String> {
+        match self.idref.starts_with('#') {
+            true => Ok(None),
+            false => self.idref.parse().map(Some),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Version {
+    pub(crate) text: String,
+}
+
+impl Version {
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> Result<Version, Error> {
+        let text = get_text(el, opts);
+        Ok(Version { text })
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// `xccdf:metadata`, usually Dublin Core. Children in other namespaces
+/// are kept as they are.
+#[derive(Debug)]
+pub struct Metadata {
+    pub(crate) dc: Vec<DcElement>,
+    pub(crate) other: Vec<Element>,
+}
+
+impl Metadata {
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> Result<Metadata, Error> {
+        let mut dc = Vec::new();
+        let mut other = Vec::new();
+        for child in el.children() {
+            if dc::is_dublin_core(child) {
+                dc.push(DcElement::from_xml(child, opts));
+            } else {
+                other.push(child.clone());
+            }
+        }
+        Ok(Metadata { dc, other })
+    }
+
+    pub fn dc_elements(&self) -> &[DcElement] {
+        &self.dc
+    }
+
+    /// Texts of all the Dublin Core elements with the local name, e.g.
+    /// all `dc:contributor` elements.
+    pub fn dc(&self, name: &str) -> Vec<&str> {
+        self.dc
+            .iter()
+            .filter(|e| e.name == name)
+            .map(|e| e.text.as_str())
+            .collect()
+    }
+
+    pub fn other(&self) -> &[Element] {
+        &self.other
+    }
+}
+
+#[derive(Debug)]
+pub struct Model {
+    pub(crate) text: String,
+}
+
+impl Model {
+    pub fn from_xml(el: &Element) -> Result<Model, Error> {
+        let text = el.text();
+        Ok(Model { text })
+    }
+}
+
+#[derive(Debug)]
+pub struct Profile {
+    // attributes
+    pub(crate) id: Arc<str>,
+    pub(crate) prohibit_changes: bool,
+    pub(crate) abstract_: bool,
+    pub(crate) note_tag: Option<Arc<str>>,
+    pub(crate) extends: Option<Arc<str>>,
+    // child elements
+    pub(crate) statuses: Vec<ItemStatus>,
+    pub(crate) version: Option<Version>,
+    pub(crate) titles: Vec<Title>,
+    pub(crate) descriptions: Vec<Description>,
+    pub(crate) references: Vec<Reference>,
+    pub(crate) platforms: Vec<Platform>,
+    pub(crate) selects: Vec<Select>,
+    pub(crate) set_complex_values: Vec<SetComplexValue>,
+    pub(crate) set_values: Vec<SetValue>,
+    pub(crate) refine_values: Vec<RefineValue>,
+    pub(crate) refine_rules: Vec<RefineRule>,
+    pub(crate) signature: Option<dsig::Signature>,
+    /// Children the model doesn't represent: foreign-namespace extensions
+    /// and, in the permissive mode, unknown elements
+    pub(crate) extensions: Vec<Element>,
+    /// Attributes the model doesn't represent
+    pub(crate) other_attributes: Vec<(String, String)>,
+}
+
+impl Profile {
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> Result<Profile, Error> {
+        let id = require_attr(el, "id")?;
+        let other_attributes = other_attributes(
+            el,
+            &["id", "prohibitChanges", "abstract", "note-tag", "extends"],
+        );
+        let prohibit_changes = get_attr_lenient_bool(el, "prohibitChanges", false, opts)?;
+        let abstract_ = get_attr_lenient(el, "abstract", false, opts)?;
+        let note_tag = get_attr(el, "note-tag");
+        let extends = get_attr(el, "extends");
+        let mut statuses = Vec::new();
+        let mut version = None;
+        let mut titles = Vec::new();
+        let mut descriptions = Vec::new();
+        let mut references = Vec::new();
+        let mut platforms = Vec::new();
+        let mut selects = Vec::new();
+        let mut set_complex_values = Vec::new();
+        let mut set_values = Vec::new();
+        let mut refine_values = Vec::new();
+        let mut refine_rules = Vec::new();
+        let mut signature = None;
+        let mut extensions = Vec::new();
+        for_each_item(el, opts, |child| {
+            if is_extension(child) {
+                extensions.push(child.clone());
+                return Ok(());
+            }
+            match child.name() {
+                "status" => statuses.push(ItemStatus::from_xml(child, opts)?),
+                "signature" => match signature {
+                    Some(_) => return Err(Error::duplicate_element(el.name(), "signature")),
+                    None => signature = Some(dsig::from_xccdf_signature(child)?),
+                },
+                "version" => match version {
+                    Some(_) => return Err(Error::duplicate_element(el.name(), "version")),
+                    None => version = Some(Version::from_xml(child, opts)?),
+                },
+                "title" => titles.push(Title::from_xml(child, opts)?),
+                "description" => descriptions.push(Description::from_xml(child)?),
+                "reference" => references.push(Reference::from_xml(child, opts)?),
+                "platform" => platforms.push(Platform::from_xml(child)?),
+                "select" => selects.push(Select::from_xml(child, opts)?),
+                "set-complex-value" => {
+                    set_complex_values.push(SetComplexValue::from_xml(child, opts)?)
+                }
+                "set-value" => set_values.push(SetValue::from_xml(child)?),
+                "refine-value" => refine_values.push(RefineValue::from_xml(child, opts)?),
+                "refine-rule" => refine_rules.push(RefineRule::from_xml(child, opts)?),
+                _ => {
+                    opts.unknown_child(Error::unexpected_element(child.name(), None))?;
+                    extensions.push(child.clone());
+                }
+            }
+            Ok(())
+        })?;
+        if titles.is_empty() {
+            opts.tolerate(Error::missing_element(el.name(), "title"))?;
+        }
+        Ok(Profile {
+            id,
+            prohibit_changes,
+            abstract_,
+            note_tag,
+            extends,
+            statuses,
+            version,
+            titles,
+            descriptions,
+            references,
+            platforms,
+            selects,
+            set_complex_values,
+            set_values,
+            refine_values,
+            refine_rules,
+            signature,
+            extensions,
+            other_attributes,
+        })
+    }
+
+    pub fn is_abstract(&self) -> bool {
+        self.abstract_
+    }
+
+    pub fn prohibit_changes(&self) -> bool {
+        self.prohibit_changes
+    }
+
+    pub fn extends(&self) -> Option<&str> {
+        self.extends.as_deref()
+    }
+
+    pub fn statuses(&self) -> &[ItemStatus] {
+        &self.statuses
+    }
+
+    pub fn version(&self) -> Option<&Version> {
+        self.version.as_ref()
+    }
+
+    pub fn titles(&self) -> &[Title] {
+        &self.titles
+    }
+
+    pub fn descriptions(&self) -> &[Description] {
+        &self.descriptions
+    }
+
+    pub fn references(&self) -> &[Reference] {
+        &self.references
+    }
+
+    pub fn platforms(&self) -> &[Platform] {
+        &self.platforms
+    }
+
+    pub fn selects(&self) -> &[Select] {
+        &self.selects
+    }
+
+    pub fn set_values(&self) -> &[SetValue] {
+        &self.set_values
+    }
+
+    pub fn set_complex_values(&self) -> &[SetComplexValue] {
+        &self.set_complex_values
+    }
+
+    pub fn refine_values(&self) -> &[RefineValue] {
+        &self.refine_values
+    }
+
+    pub fn refine_rules(&self) -> &[RefineRule] {
+        &self.refine_rules
+    }
+}
+
+/// Well-known SCAP Security Guide extensions found in profiles. They are
+/// recognized by the local name of the foreign-namespace element.
+#[derive(Debug, PartialEq)]
+pub enum ProfileExtension {
+    Stability(String),
+    Hidden(bool),
+    Derivative(Option<String>),
+}
+
+impl Profile {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn extensions(&self) -> &[Element] {
+        &self.extensions
+    }
+
+    pub fn other_attributes(&self) -> &[(String, String)] {
+        &self.other_attributes
+    }
+
+    pub fn known_extensions(&self) -> Vec<ProfileExtension> {
+        let mut known = Vec::new();
+        for el in self.extensions.iter() {
+            let text = normalize_whitespace(&el.text());
+            match el.name() {
+                "stability" => known.push(ProfileExtension::Stability(text)),
+                "hidden" => known.push(ProfileExtension::Hidden(text != "false" && text != "0")),
+                "derivative" if text.is_empty() => known.push(ProfileExtension::Derivative(None)),
+                "derivative" => known.push(ProfileExtension::Derivative(Some(text))),
+                _ => (),
+            }
+        }
+        known
+    }
+
+    pub fn write_details<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "Profile ID: {}", self.id)?;
+        if let Some(title) = self.titles.first() {
+            writeln!(out, "Title: {}", title.title)?;
+        }
+        if let Some(description) = self.descriptions.first() {
+            writeln!(out, "Description: {}", description.text())?;
+        }
+        if let Some(extends) = &self.extends {
+            writeln!(out, "Extends: {}", extends)?;
+        }
+        if self.abstract_ {
+            writeln!(out, "Abstract: yes")?;
+        }
+        let selected = self.selects.iter().filter(|s| s.selected).count();
+        writeln!(out, "Selected items: {}", selected)?;
+        for extension in self.known_extensions() {
+            match extension {
+                ProfileExtension::Stability(stability) => {
+                    writeln!(out, "Stability: {}", stability)?
+                }
+                ProfileExtension::Hidden(hidden) => {
+                    writeln!(out, "Hidden: {}", if hidden { "yes" } else { "no" })?
+                }
+                ProfileExtension::Derivative(Some(origin)) => {
+                    writeln!(out, "Derivative of: {}", origin)?
+                }
+                ProfileExtension::Derivative(None) => writeln!(out, "Derivative: yes")?,
+            }
+        }
+        let unknown: Vec<&Element> = self
+            .extensions
+            .iter()
+            .filter(|el| !["stability", "hidden", "derivative"].contains(&el.name()))
+            .collect();
+        if !unknown.is_empty() {
+            writeln!(out, "Other extensions:")?;
+            for el in unknown {
+                writeln!(out, "* {{{}}}{}", el.ns(), el.name())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Profile with the properties inherited through `extends` merged in. The
+/// selectors of the extending profile come after the ones of its parent, so
+/// for every item only the last selector is kept.
+#[derive(Debug)]
+pub struct ResolvedProfile<'a> {
+    pub(crate) id: &'a str,
+    /// IDs of the profiles that were merged, from the base profile to this one
+    pub(crate) chain: Vec<&'a str>,
+    pub(crate) titles: Vec<&'a Title>,
+    pub(crate) descriptions: Vec<&'a Description>,
+    pub(crate) references: Vec<&'a Reference>,
+    pub(crate) platforms: Vec<&'a Platform>,
+    pub(crate) selects: Vec<&'a Select>,
+    pub(crate) set_values: Vec<&'a SetValue>,
+    pub(crate) set_complex_values: Vec<&'a SetComplexValue>,
+    pub(crate) refine_values: Vec<&'a RefineValue>,
+    pub(crate) refine_rules: Vec<&'a RefineRule>,
+}
+
+/// Appends `items` to `merged`, an item replaces an earlier one with the same key.
+fn merge_by_key<'a, T, F>(merged: &mut Vec<&'a T>, items: &'a [T], key: F)
+where
+    F: Fn(&T) -> &str,
+{
+    for item in items.iter() {
+        merged.retain(|m| key(m) != key(item));
+        merged.push(item);
+    }
+}
+
+impl<'a> ResolvedProfile<'a> {
+    /// Merges the profile with the profiles it extends, directly or
+    /// transitively. Fails if a parent profile is missing or if the
+    /// `extends` attributes form a loop.
+    fn resolve<F>(id: &str, find_profile: F) -> Result<ResolvedProfile<'a>, Error>
+    where
+        F: Fn(&str) -> Option<&'a Profile>,
+    {
+        let mut chain: Vec<&Profile> = Vec::new();
+        let mut current = id;
+        loop {
+            let profile = match find_profile(current) {
+                Some(profile) => profile,
+                None if chain.is_empty() => {
+                    return Err(Error::Content(format!("Profile '{}' not found", id)))
+                }
+                None => {
+                    return Err(Error::Content(format!(
+                        "Profile '{}' extends profile '{}' which doesn't exist",
+                        chain[chain.len() - 1].id,
+                        current
+                    )))
+                }
+            };
+            if chain.iter().any(|p| p.id == profile.id) {
+                return Err(Error::Content(format!(
+                    "Profile '{}' is part of an extends loop",
+                    profile.id
+                )));
+            }
+            chain.push(profile);
+            match &profile.extends {
+                Some(parent) => current = parent,
+                None => break,
+            }
+        }
+        chain.reverse();
+        Ok(ResolvedProfile::new(&chain))
+    }
+
+    fn new(chain: &[&'a Profile]) -> ResolvedProfile<'a> {
+        let mut resolved = ResolvedProfile {
+            id: &chain[chain.len() - 1].id,
+            chain: chain.iter().map(|p| p.id.as_ref()).collect(),
+            titles: Vec::new(),
+            descriptions: Vec::new(),
+            references: Vec::new(),
+            platforms: Vec::new(),
+            selects: Vec::new(),
+            set_values: Vec::new(),
+            set_complex_values: Vec::new(),
+            refine_values: Vec::new(),
+            refine_rules: Vec::new(),
+        };
+        for profile in chain.iter() {
+            // title and description are replaced, references and platforms
+            // are appended, the selectors override the inherited ones
+            if !profile.titles.is_empty() {
+                resolved.titles = profile.titles.iter().collect();
+            }
+            if !profile.descriptions.is_empty() {
+                resolved.descriptions = profile.descriptions.iter().collect();
+            }
+            resolved.references.extend(profile.references.iter());
+            merge_by_key(&mut resolved.platforms, &profile.platforms, |p| &p.idref);
+            merge_by_key(&mut resolved.selects, &profile.selects, |s| &s.idref);
+            merge_by_key(&mut resolved.set_values, &profile.set_values, |s| &s.idref);
+            merge_by_key(
+                &mut resolved.set_complex_values,
+                &profile.set_complex_values,
+                |s| &s.idref,
+            );
+            merge_by_key(&mut resolved.refine_values, &profile.refine_values, |r| {
+                &r.idref
+            });
+            merge_by_key(&mut resolved.refine_rules, &profile.refine_rules, |r| {
+                &r.idref
+            });
+        }
+        resolved
+    }
+
+    pub fn id(&self) -> &str {
+        self.id
+    }
+
+    /// Whether an item is selected: a selector of the item itself wins over
+    /// a selector of its cluster, otherwise the item's default applies.
+    pub(crate) fn is_selected(&self, id: &str, cluster_id: Option<&str>, default: bool) -> bool {
+        if let Some(select) = self.selects.iter().find(|s| *s.idref == *id) {
+            return select.selected;
+        }
+        match cluster_id.and_then(|c| self.selects.iter().find(|s| *s.idref == *c)) {
+            Some(select) => select.selected,
+            None => default,
+        }
+    }
+
+    /// Refinement of a rule: a refine-rule of the rule itself wins over one
+    /// of its cluster.
+    pub fn refine_rule(&self, rule: &Rule) -> Option<&'a RefineRule> {
+        let find = |idref: &str| {
+            self.refine_rules
+                .iter()
+                .find(|r| &*r.idref == idref)
+                .copied()
+        };
+        find(&rule.id).or_else(|| rule.cluster_id.as_deref().and_then(find))
+    }
+
+    /// Severity of the rule after applying the refine-rule of the profile.
+    pub fn severity(&self, rule: &Rule) -> Severity {
+        self.refine_rule(rule)
+            .and_then(|r| r.severity)
+            .unwrap_or(rule.severity)
+    }
+
+    /// Role of the rule after applying the refine-rule of the profile.
+    pub fn role(&self, rule: &Rule) -> Role {
+        self.refine_rule(rule)
+            .and_then(|r| r.role)
+            .unwrap_or(rule.role)
+    }
+
+    /// Weight of the rule after applying the refine-rule of the profile.
+    pub fn weight(&self, rule: &Rule) -> f64 {
+        self.refine_rule(rule)
+            .and_then(|r| r.weight)
+            .unwrap_or(rule.weight)
+    }
+
+    /// IDs of the items selected by the resolved profile.
+    pub fn selected_ids(&self) -> Vec<&str> {
+        self.selects
+            .iter()
+            .filter(|s| s.selected)
+            .map(|s| s.idref.as_ref())
+            .collect()
+    }
+
+    pub fn info(&self) -> info::ResolvedProfileInfo {
+        let unselected = self
+            .selects
+            .iter()
+            .filter(|s| !s.selected)
+            .map(|s| s.idref.to_string())
+            .collect();
+        let values = self
+            .set_values
+            .iter()
+            .map(|v| info::SetValueInfo {
+                idref: v.idref.to_string(),
+                value: v.text.clone(),
+            })
+            .collect();
+        let complex_values = self
+            .set_complex_values
+            .iter()
+            .map(|v| info::SetComplexValueInfo {
+                idref: v.idref.to_string(),
+                items: v.items.clone(),
+            })
+            .collect();
+        info::ResolvedProfileInfo {
+            id: self.id.to_string(),
+            title: self.titles.first().map(|t| t.title.clone()),
+            inheritance: self.chain.iter().map(|id| id.to_string()).collect(),
+            selected: self
+                .selected_ids()
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+            unselected,
+            values,
+            complex_values,
+        }
+    }
+
+    pub fn write_selections<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "Profile ID: {}", self.id)?;
+        if let Some(title) = self.titles.first() {
+            writeln!(out, "Title: {}", title.title)?;
+        }
+        writeln!(out, "Inheritance: {}", self.chain.join(" -> "))?;
+        let selected = self.selected_ids();
+        writeln!(out, "Selected items: {}", selected.len())?;
+        for idref in selected {
+            writeln!(out, "* {}", idref)?;
+        }
+        let unselected: Vec<&str> = self
+            .selects
+            .iter()
+            .filter(|s| !s.selected)
+            .map(|s| s.idref.as_ref())
+            .collect();
+        if !unselected.is_empty() {
+            writeln!(out, "Unselected items: {}", unselected.len())?;
+            for idref in unselected {
+                writeln!(out, "* {}", idref)?;
+            }
+        }
+        for set_value in self.set_values.iter() {
+            writeln!(out, "Value {} = {}", set_value.idref, set_value.text)?;
+        }
+        for set_value in self.set_complex_values.iter() {
+            writeln!(
+                out,
+                "Value {} = [{}]",
+                set_value.idref,
+                set_value.items.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// XCCDF Tailoring document, which holds profiles customizing the profiles
+/// of a separately distributed benchmark.
+#[derive(Debug)]
+pub struct Tailoring {
+    pub(crate) id: Arc<str>,
+    pub(crate) benchmark_href: Option<Arc<str>>,
+    pub(crate) statuses: Vec<ItemStatus>,
+    pub(crate) version: Version,
+    pub(crate) profiles: Vec<Profile>,
+    pub(crate) signature: Option<dsig::Signature>,
+    /// Children the model doesn't represent: foreign-namespace extensions
+    /// and, in the permissive mode, unknown elements
+    pub(crate) extensions: Vec<Element>,
+    /// Attributes the model doesn't represent
+    pub(crate) other_attributes: Vec<(String, String)>,
+}
+
+impl Tailoring {
+    pub fn from_xml(el: &Element) -> Result<Tailoring, Error> {
+        Tailoring::from_xml_with_options(el, &ParseOptions::default())
+    }
+
+    pub fn from_xml_with_options(el: &Element, opts: &ParseOptions) -> Result<Tailoring, Error> {
+        let result = opts.within(el.name(), || Tailoring::parse(el, opts));
+        opts.finish(result)
+    }
+
+    fn parse(el: &Element, opts: &ParseOptions) -> Result<Tailoring, Error> {
+        if !el.is("Tailoring", XCCDF12_NS) {
+            return Err(Error::unexpected_element(
+                el.name(),
+                Some("xccdf:Tailoring"),
+            ));
+        }
+        let id = require_attr(el, "id")?;
+        let other_attributes = other_attributes(el, &["id"]);
+        let mut benchmark_href = None;
+        let mut statuses = Vec::new();
+        let mut version = None;
+        let mut profiles = Vec::new();
+        let mut signature = None;
+        let mut extensions = Vec::new();
+        for_each_item(el, opts, |child| {
+            if is_extension(child) {
+                extensions.push(child.clone());
+                return Ok(());
+            }
+            match child.name() {
+                "status" => statuses.push(ItemStatus::from_xml(child, opts)?),
+                "benchmark" => benchmark_href = Some(require_attr(child, "href")?),
+                "version" => match version {
+                    Some(_) => return Err(Error::duplicate_element(el.name(), "version")),
+                    None => version = Some(Version::from_xml(child, opts)?),
+                },
+                "Profile" => profiles.push(Profile::from_xml(child, opts)?),
+                "signature" => match signature {
+                    Some(_) => return Err(Error::duplicate_element(el.name(), "signature")),
+                    None => signature = Some(dsig::from_xccdf_signature(child)?),
+                },
+                "dc-status" | "metadata" => (),
+                _ => {
+                    opts.unknown_child(Error::unexpected_element(child.name(), None))?;
+                    extensions.push(child.clone());
+                }
+            }
+            Ok(())
+        })?;
+        let version = match version {
+            Some(version) => version,
+            None => {
+                opts.tolerate(Error::missing_element(el.name(), "version"))?;
+                Version::default()
+            }
+        };
+        if profiles.is_empty() {
+            opts.tolerate(Error::missing_element(el.name(), "Profile"))?;
+        }
+        Ok(Tailoring {
+            id,
+            benchmark_href,
+            statuses,
+            version,
+            profiles,
+            signature,
+            extensions,
+            other_attributes,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn profiles(&self) -> &[Profile] {
+        &self.profiles
+    }
+
+    pub fn extensions(&self) -> &[Element] {
+        &self.extensions
+    }
+
+    pub fn other_attributes(&self) -> &[(String, String)] {
+        &self.other_attributes
+    }
+
+    pub fn find_profile(&self, id: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| &*p.id == id)
+    }
+
+    pub fn benchmark_href(&self) -> Option<&str> {
+        self.benchmark_href.as_deref()
+    }
+
+    pub fn statuses(&self) -> &[ItemStatus] {
+        &self.statuses
+    }
+
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+}
+
+/// Children of a Value the model doesn't represent, kept as they are.
+const VALUE_CHILDREN: &[&str] = &[
+    "status",
+    "version",
+    "warning",
+    "question",
+    "reference",
+    "metadata",
+    "default",
+    "complex-default",
+    "match",
+    "lower-bound",
+    "upper-bound",
+    "choices",
+    "source",
+];
+
+#[derive(Debug)]
+pub struct Value {
+    pub(crate) id: Arc<str>,
+    pub(crate) type_: Arc<str>,
+    pub(crate) titles: Vec<Title>,
+    pub(crate) descriptions: Vec<Description>,
+    pub(crate) values: Vec<SelectedValue>,
+    pub(crate) complex_values: Vec<SelectedComplexValue>,
+    pub(crate) signature: Option<dsig::Signature>,
+    /// Children the model doesn't represent: foreign-namespace extensions,
+    /// the other XCCDF children such as the bounds and the choices and, in
+    /// the permissive mode, unknown elements
+    pub(crate) extensions: Vec<Element>,
+    /// Attributes the model doesn't represent
+    pub(crate) other_attributes: Vec<(String, String)>,
+}
+
+/// `xccdf:value` of a Value, the one without a selector is the default.
+#[derive(Debug)]
+pub struct SelectedValue {
+    pub(crate) selector: Option<Arc<str>>,
+    pub(crate) text: String,
+}
+
+/// `xccdf:complex-value` of a Value, a list of items.
+#[derive(Debug)]
+pub struct SelectedComplexValue {
+    pub(crate) selector: Option<Arc<str>>,
+    pub(crate) items: Vec<String>,
+}
+
+impl SelectedComplexValue {
+    pub fn selector(&self) -> Option<&str> {
+        self.selector.as_deref()
+    }
+
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+}
+
+/// Falls back to the default when the selector is missing or unknown, and
+/// to the first candidate if there isn't any default.
+fn select_by<'a, T>(
+    candidates: &'a [T],
+    selector: Option<&str>,
+    selector_of: fn(&T) -> Option<&str>,
+) -> Option<&'a T> {
+    let matching = |wanted: Option<&str>| candidates.iter().find(|c| selector_of(c) == wanted);
+    selector
+        .and_then(|selector| matching(Some(selector)))
+        .or_else(|| matching(None))
+        .or_else(|| candidates.first())
+}
+
+impl Value {
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> Result<Value, Error> {
+        let id = require_attr(el, "id")?;
+        let other_attributes = other_attributes(el, &["id", "type"]);
+        let type_ = get_attr(el, "type").unwrap_or_else(|| intern("string"));
+        let mut titles = Vec::new();
+        let mut descriptions = Vec::new();
+        let mut values = Vec::new();
+        let mut complex_values = Vec::new();
+        let mut signature = None;
+        let mut extensions = Vec::new();
+        for_each_item(el, opts, |child| {
+            if is_extension(child) {
+                extensions.push(child.clone());
+                return Ok(());
+            }
+            let selector = get_attr(child, "selector").filter(|s| !s.is_empty());
+            match child.name() {
+                "title" => titles.push(Title::from_xml(child, opts)?),
+                "description" => descriptions.push(Description::from_xml(child)?),
+                "value" => values.push(SelectedValue {
+                    selector,
+                    text: child.text(),
+                }),
+                "complex-value" => complex_values.push(SelectedComplexValue {
+                    selector,
+                    items: parse_items(child, opts, &mut extensions)?,
+                }),
+                "signature" => match signature {
+                    Some(_) => return Err(Error::duplicate_element(el.name(), "signature")),
+                    None => signature = Some(dsig::from_xccdf_signature(child)?),
+                },
+                name if VALUE_CHILDREN.contains(&name) => extensions.push(child.clone()),
+                _ => {
+                    opts.unknown_child(Error::unexpected_element(child.name(), None))?;
+                    extensions.push(child.clone());
+                }
+            }
+            Ok(())
+        })?;
+        Ok(Value {
+            id,
+            type_,
+            titles,
+            descriptions,
+            values,
+            complex_values,
+            signature,
+            extensions,
+            other_attributes,
+        })
+    }
+
+    /// Value for the selector. Falls back to the default value when the
+    /// selector is missing or unknown, and to the first value if there
+    /// isn't any default.
+    pub fn value_for(&self, selector: Option<&str>) -> Option<&str> {
+        select_by(&self.values, selector, |v| v.selector.as_deref()).map(|v| v.text.as_str())
+    }
+
+    /// Complex value for the selector, with the same fallbacks as
+    /// [`Value::value_for`].
+    pub fn complex_value_for(&self, selector: Option<&str>) -> Option<&[String]> {
+        select_by(&self.complex_values, selector, |v| v.selector.as_deref())
+            .map(|v| v.items.as_slice())
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn value_type(&self) -> &str {
+        &self.type_
+    }
+
+    pub fn titles(&self) -> &[Title] {
+        &self.titles
+    }
+
+    pub fn descriptions(&self) -> &[Description] {
+        &self.descriptions
+    }
+
+    pub fn values(&self) -> &[SelectedValue] {
+        &self.values
+    }
+
+    pub fn complex_values(&self) -> &[SelectedComplexValue] {
+        &self.complex_values
+    }
+
+    pub fn extensions(&self) -> &[Element] {
+        &self.extensions
+    }
+
+    pub fn other_attributes(&self) -> &[(String, String)] {
+        &self.other_attributes
+    }
+}
+
+#[derive(Debug)]
+pub struct Group {
+    // attributes
+    pub(crate) id: Arc<str>,
+    pub(crate) abstract_: bool,
+    pub(crate) cluster_id: Option<Arc<str>>,
+    pub(crate) extends: Option<Arc<str>>,
+    pub(crate) hidden: bool,
+    pub(crate) prohibit_changes: bool,
+    pub(crate) selected: bool,
+    pub(crate) weight: f64,
+    // children
+    pub(crate) statuses: Vec<ItemStatus>,
+    pub(crate) version: Option<Version>,
+    pub(crate) titles: Vec<Title>,
+    pub(crate) descriptions: Vec<Description>,
+    pub(crate) warnings: Vec<Warning>,
+    pub(crate) questions: Vec<Question>,
+    pub(crate) references: Vec<Reference>,
+    pub(crate) metadata: Vec<Metadata>,
+    pub(crate) rationales: Vec<Rationale>,
+    pub(crate) platforms: Vec<Platform>,
+    pub(crate) requires: Vec<Requires>,
+    pub(crate) conflicts: Vec<Conflicts>,
+    pub(crate) values: Vec<Value>,
+    pub(crate) groups: Vec<Group>,
+    pub(crate) rules: Vec<Rule>,
+    pub(crate) signature: Option<dsig::Signature>,
+    /// Children the model doesn't represent: foreign-namespace extensions
+    /// and, in the permissive mode, unknown elements
+    pub(crate) extensions: Vec<Element>,
+    /// Attributes the model doesn't represent
+    pub(crate) other_attributes: Vec<(String, String)>,
+}
+
+impl Group {
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> Result<Group, Error> {
+        let id = require_attr(el, "id")?;
+        let other_attributes = other_attributes(el, ITEM_ATTRIBUTES);
+        let abstract_ = get_attr_lenient_bool(el, "abstract", false, opts)?;
+        let extends = get_attr(el, "extends");
+        let hidden = get_attr_lenient_bool(el, "hidden", false, opts)?;
+        let prohibit_changes = get_attr_lenient_bool(el, "prohibitChanges", false, opts)?;
+        let selected = get_attr_lenient_bool(el, "selected", true, opts)?;
+        let weight = get_attr_lenient(el, "weight", 1.0, opts)?;
+        let cluster_id = get_attr(el, "cluster-id");
+
+        let mut statuses = Vec::new();
         let mut version = None;
         let mut titles = Vec::new();
         let mut descriptions = Vec::new();
@@ -486,35 +2477,43 @@ impl Group {
         let mut groups = Vec::new();
         let mut rules = Vec::new();
 
-        for child in el.children() {
+        let mut signature = None;
+        let mut extensions = Vec::new();
+        for_each_item(el, opts, |child| {
+            if is_extension(child) {
+                extensions.push(child.clone());
+                return Ok(());
+            }
             match child.name() {
-                "status" => statuses.push(Status::from_xml(child)?),
+                "status" => statuses.push(ItemStatus::from_xml(child, opts)?),
+                "signature" => match signature {
+                    Some(_) => return Err(Error::duplicate_element(el.name(), "signature")),
+                    None => signature = Some(dsig::from_xccdf_signature(child)?),
+                },
                 "version" => match version {
-                    Some(_) => return Err(format!("Duplicate version elements")),
-                    None => version = Some(Version::from_xml(child)?),
+                    Some(_) => return Err(Error::duplicate_element(el.name(), "version")),
+                    None => version = Some(Version::from_xml(child, opts)?),
                 },
-                "title" => titles.push(Title::from_xml(child)?),
+                "title" => titles.push(Title::from_xml(child, opts)?),
                 "description" => descriptions.push(Description::from_xml(child)?),
                 "warning" => warnings.push(Warning::from_xml(child)?),
-                "question" => questions.push(Question::from_xml(child)?),
-                "reference" => references.push(Reference::from_xml(child)?),
-                "metadata" => metadata.push(Metadata::from_xml(child)?),
+                "question" => questions.push(Question::from_xml(child, opts)?),
+                "reference" => references.push(Reference::from_xml(child, opts)?),
+                "metadata" => metadata.push(Metadata::from_xml(child, opts)?),
                 "rationale" => rationales.push(Rationale::from_xml(child)?),
                 "platform" => platforms.push(Platform::from_xml(child)?),
                 "requires" => requires.push(Requires::from_xml(child)?),
                 "conflicts" => conflicts.push(Conflicts::from_xml(child)?),
-                "Value" => values.push(Value::from_xml(child)?),
-                "Group" => groups.push(Group::from_xml(child)?),
-                "Rule" => rules.push(Rule::from_xml(child)?),
+                "Value" => values.push(Value::from_xml(child, opts)?),
+                "Group" => groups.push(Group::from_xml(child, opts)?),
+                "Rule" => rules.push(Rule::from_xml(child, opts)?),
                 _ => {
-                    return Err(format!(
-                        "Rule '{}': unexpected element '{}'",
-                        id,
-                        child.name()
-                    ));
+                    opts.unknown_child(Error::unexpected_element(child.name(), None))?;
+                    extensions.push(child.clone());
                 }
             }
-        }
+            Ok(())
+        })?;
         Ok(Group {
             id,
             abstract_,
@@ -539,68 +2538,161 @@ impl Group {
             values,
             groups,
             rules,
+            signature,
+            extensions,
+            other_attributes,
         })
     }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn is_abstract(&self) -> bool {
+        self.abstract_
+    }
+
+    pub fn cluster_id(&self) -> Option<&str> {
+        self.cluster_id.as_deref()
+    }
+
+    pub fn extends(&self) -> Option<&str> {
+        self.extends.as_deref()
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub fn prohibit_changes(&self) -> bool {
+        self.prohibit_changes
+    }
+
+    /// Default selection, profiles can change it.
+    pub fn selected(&self) -> bool {
+        self.selected
+    }
+
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    pub fn statuses(&self) -> &[ItemStatus] {
+        &self.statuses
+    }
+
+    pub fn version(&self) -> Option<&Version> {
+        self.version.as_ref()
+    }
+
+    pub fn titles(&self) -> &[Title] {
+        &self.titles
+    }
+
+    pub fn descriptions(&self) -> &[Description] {
+        &self.descriptions
+    }
+
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    pub fn references(&self) -> &[Reference] {
+        &self.references
+    }
+
+    pub fn rationales(&self) -> &[Rationale] {
+        &self.rationales
+    }
+
+    pub fn platforms(&self) -> &[Platform] {
+        &self.platforms
+    }
+
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    pub fn groups(&self) -> &[Group] {
+        &self.groups
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    pub fn requires(&self) -> &[Requires] {
+        &self.requires
+    }
+
+    pub fn conflicts(&self) -> &[Conflicts] {
+        &self.conflicts
+    }
+
+    pub fn extensions(&self) -> &[Element] {
+        &self.extensions
+    }
+
+    pub fn other_attributes(&self) -> &[(String, String)] {
+        &self.other_attributes
+    }
 }
 
 #[derive(Debug)]
-struct Rule {
+pub struct Rule {
     // attributes
-    id: String,
-    abstract_: bool,
-    cluster_id: Option<String>,
-    extends: Option<String>,
-    hidden: bool,
-    prohibit_changes: bool,
-    selected: bool,
-    weight: f64,
-    role: String,
-    severity: String,
-    multiple: bool,
+    pub(crate) id: Arc<str>,
+    pub(crate) abstract_: bool,
+    pub(crate) cluster_id: Option<Arc<str>>,
+    pub(crate) extends: Option<Arc<str>>,
+    pub(crate) hidden: bool,
+    pub(crate) prohibit_changes: bool,
+    pub(crate) selected: bool,
+    pub(crate) weight: f64,
+    pub(crate) role: Role,
+    pub(crate) severity: Severity,
+    pub(crate) multiple: bool,
     // children
-    statuses: Vec<Status>,
-    version: Option<Version>,
-    titles: Vec<Title>,
-    descriptions: Vec<Description>,
-    warnings: Vec<Warning>,
-    questions: Vec<Question>,
-    references: Vec<Reference>,
-    metadata: Vec<Metadata>,
-    rationales: Vec<Rationale>,
-    platforms: Vec<Platform>,
-    requires: Vec<Requires>,
-    conflicts: Vec<Conflicts>,
-    idents: Vec<Ident>,
-    profile_notes: Vec<ProfileNote>,
-    fixtexts: Vec<FixText>,
-    fixes: Vec<Fix>,
-    checks: Vec<Check>,
-    complex_checks: Vec<ComplexCheck>,
+    pub(crate) statuses: Vec<ItemStatus>,
+    pub(crate) version: Option<Version>,
+    pub(crate) titles: Vec<Title>,
+    pub(crate) descriptions: Vec<Description>,
+    pub(crate) warnings: Vec<Warning>,
+    pub(crate) questions: Vec<Question>,
+    pub(crate) references: Vec<Reference>,
+    pub(crate) metadata: Vec<Metadata>,
+    pub(crate) rationales: Vec<Rationale>,
+    pub(crate) platforms: Vec<Platform>,
+    pub(crate) requires: Vec<Requires>,
+    pub(crate) conflicts: Vec<Conflicts>,
+    pub(crate) idents: Vec<Ident>,
+    pub(crate) profile_notes: Vec<ProfileNote>,
+    pub(crate) fixtexts: Vec<FixText>,
+    pub(crate) fixes: Vec<Fix>,
+    pub(crate) checks: Vec<Check>,
+    pub(crate) complex_checks: Vec<ComplexCheck>,
+    pub(crate) signature: Option<dsig::Signature>,
+    /// Children the model doesn't represent: foreign-namespace extensions
+    /// and, in the permissive mode, unknown elements
+    pub(crate) extensions: Vec<Element>,
+    /// Attributes the model doesn't represent
+    pub(crate) other_attributes: Vec<(String, String)>,
 }
 
 impl Rule {
-    pub fn from_xml(el: &Element) -> Result<Rule, String> {
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> Result<Rule, Error> {
         let id = require_attr(el, "id")?;
-        let abstract_ = get_attr_default_bool(el, "abstract", false)?;
+        let other_attributes = other_attributes(el, RULE_ATTRIBUTES);
+        let abstract_ = get_attr_lenient_bool(el, "abstract", false, opts)?;
         let extends = get_attr(el, "extends");
-        let hidden = get_attr_default_bool(el, "hidden", false)?;
-        let prohibit_changes = get_attr_default_bool(el, "prohibitChanges", false)?;
-        let selected = get_attr_default_bool(el, "selected", true)?;
-        let weight = get_attr_default(el, "weight", 1.0)?;
+        let hidden = get_attr_lenient_bool(el, "hidden", false, opts)?;
+        let prohibit_changes = get_attr_lenient_bool(el, "prohibitChanges", false, opts)?;
+        let selected = get_attr_lenient_bool(el, "selected", true, opts)?;
+        let weight = get_attr_lenient(el, "weight", 1.0, opts)?;
         let cluster_id = get_attr(el, "cluster-id");
-        let role = get_attr_default_options(
-            el,
-            "rule",
-            String::from("full"),
-            vec!["full", "unscored", "unchecked"],
-        )?;
-        let severity = get_attr_default_options(
-            el,
-            "severity",
-            String::from("unknown"),
-            vec!["unknown", "info", "low", "medium", "high"],
-        )?;
-        let multiple = get_attr_default_bool(el, "multiple", false)?;
+        let role = get_attr_lenient(el, "role", Role::Full, opts)?;
+        let severity = get_attr_lenient(el, "severity", Severity::Unknown, opts)?;
+        let multiple = get_attr_lenient_bool(el, "multiple", false, opts)?;
         let mut statuses = Vec::new();
         let mut version = None;
         let mut titles = Vec::new();
@@ -619,38 +2711,46 @@ impl Rule {
         let mut fixes = Vec::new();
         let mut checks = Vec::new();
         let mut complex_checks = Vec::new();
-        for child in el.children() {
+        let mut signature = None;
+        let mut extensions = Vec::new();
+        for_each_item(el, opts, |child| {
+            if is_extension(child) {
+                extensions.push(child.clone());
+                return Ok(());
+            }
             match child.name() {
-                "status" => statuses.push(Status::from_xml(child)?),
+                "status" => statuses.push(ItemStatus::from_xml(child, opts)?),
+                "signature" => match signature {
+                    Some(_) => return Err(Error::duplicate_element(el.name(), "signature")),
+                    None => signature = Some(dsig::from_xccdf_signature(child)?),
+                },
                 "version" => match version {
-                    Some(_) => return Err(format!("Duplicate version elements")),
-                    None => version = Some(Version::from_xml(child)?),
+                    Some(_) => return Err(Error::duplicate_element(el.name(), "version")),
+                    None => version = Some(Version::from_xml(child, opts)?),
                 },
-                "title" => titles.push(Title::from_xml(child)?),
+                "title" => titles.push(Title::from_xml(child, opts)?),
                 "description" => descriptions.push(Description::from_xml(child)?),
                 "warning" => warnings.push(Warning::from_xml(child)?),
-                "question" => questions.push(Question::from_xml(child)?),
-                "reference" => references.push(Reference::from_xml(child)?),
-                "metadata" => metadata.push(Metadata::from_xml(child)?),
+                "question" => questions.push(Question::from_xml(child, opts)?),
+                "reference" => references.push(Reference::from_xml(child, opts)?),
+                "metadata" => metadata.push(Metadata::from_xml(child, opts)?),
                 "rationale" => rationales.push(Rationale::from_xml(child)?),
                 "platform" => platforms.push(Platform::from_xml(child)?),
                 "requires" => requires.push(Requires::from_xml(child)?),
                 "conflicts" => conflicts.push(Conflicts::from_xml(child)?),
-                "ident" => idents.push(Ident::from_xml(child)?),
+                "ident" => idents.push(Ident::from_xml(child, opts)?),
                 "profile-note" => profile_notes.push(ProfileNote::from_xml(child)?),
                 "fixtext" => fixtexts.push(FixText::from_xml(child)?),
                 "fix" => fixes.push(Fix::from_xml(child)?),
                 "check" => checks.push(Check::from_xml(child)?),
                 "complex-check" => complex_checks.push(ComplexCheck::from_xml(child)?),
                 _ => {
-                    return Err(format!(
-                        "Rule '{}': unexpected element '{}'",
-                        id,
-                        child.name()
-                    ));
+                    opts.unknown_child(Error::unexpected_element(child.name(), None))?;
+                    extensions.push(child.clone());
                 }
             }
-        }
+            Ok(())
+        })?;
         Ok(Rule {
             id,
             abstract_,
@@ -681,161 +2781,923 @@ impl Rule {
             fixes,
             checks,
             complex_checks,
+            signature,
+            extensions,
+            other_attributes,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn is_abstract(&self) -> bool {
+        self.abstract_
+    }
+
+    pub fn cluster_id(&self) -> Option<&str> {
+        self.cluster_id.as_deref()
+    }
+
+    pub fn extends(&self) -> Option<&str> {
+        self.extends.as_deref()
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub fn prohibit_changes(&self) -> bool {
+        self.prohibit_changes
+    }
+
+    /// Default selection, profiles can change it.
+    pub fn selected(&self) -> bool {
+        self.selected
+    }
+
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    pub fn statuses(&self) -> &[ItemStatus] {
+        &self.statuses
+    }
+
+    pub fn version(&self) -> Option<&Version> {
+        self.version.as_ref()
+    }
+
+    pub fn titles(&self) -> &[Title] {
+        &self.titles
+    }
+
+    pub fn descriptions(&self) -> &[Description] {
+        &self.descriptions
+    }
+
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    pub fn references(&self) -> &[Reference] {
+        &self.references
+    }
+
+    pub fn rationales(&self) -> &[Rationale] {
+        &self.rationales
+    }
+
+    pub fn platforms(&self) -> &[Platform] {
+        &self.platforms
+    }
+
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn idents(&self) -> &[Ident] {
+        &self.idents
+    }
+
+    /// Identifiers of the idents of the kind.
+    pub fn idents_of(&self, kind: IdentSystem) -> Vec<&str> {
+        self.idents
+            .iter()
+            .filter(|i| i.kind() == kind)
+            .map(|i| i.text())
+            .collect()
+    }
+
+    /// The CCE of the rule.
+    pub fn cce(&self) -> Option<&str> {
+        self.idents_of(IdentSystem::Cce).first().copied()
+    }
+
+    pub fn cves(&self) -> Vec<&str> {
+        self.idents_of(IdentSystem::Cve)
+    }
+
+    pub fn fixtexts(&self) -> &[FixText] {
+        &self.fixtexts
+    }
+
+    pub fn fixes(&self) -> &[Fix] {
+        &self.fixes
+    }
+
+    pub fn checks(&self) -> &[Check] {
+        &self.checks
+    }
+
+    pub fn requires(&self) -> &[Requires] {
+        &self.requires
+    }
+
+    pub fn conflicts(&self) -> &[Conflicts] {
+        &self.conflicts
+    }
+
+    pub fn extensions(&self) -> &[Element] {
+        &self.extensions
+    }
+
+    pub fn other_attributes(&self) -> &[(String, String)] {
+        &self.other_attributes
+    }
+}
+
+#[derive(Debug)]
+pub struct TestResult {
+    pub(crate) id: Arc<str>,
+    pub(crate) start_time: Option<DateTime<FixedOffset>>,
+    /// Missing only for an invalid value in the lenient mode
+    pub(crate) end_time: Option<DateTime<FixedOffset>>,
+    /// ID of the profile the scan was run with
+    pub(crate) profile: Option<Arc<str>>,
+    pub(crate) identity: Option<String>,
+    pub(crate) targets: Vec<String>,
+    pub(crate) target_addresses: Vec<String>,
+    pub(crate) target_facts: Vec<TargetFact>,
+    pub(crate) rule_results: Vec<RuleResult>,
+    pub(crate) scores: Vec<Score>,
+    pub(crate) signature: Option<dsig::Signature>,
+    /// Children the model doesn't represent: foreign-namespace extensions
+    /// and, in the permissive mode, unknown elements
+    pub(crate) extensions: Vec<Element>,
+    /// Attributes the model doesn't represent
+    pub(crate) other_attributes: Vec<(String, String)>,
+}
+
+/// `fact` of the target system collected by the scanner, e.g.
+/// `urn:xccdf:fact:asset:identifier:fqdn`.
+#[derive(Debug)]
+pub struct TargetFact {
+    pub(crate) name: Arc<str>,
+    pub(crate) fact_type: Arc<str>,
+    pub(crate) value: String,
+}
+
+impl TargetFact {
+    pub fn from_xml(el: &Element) -> Result<TargetFact, Error> {
+        let name = require_attr(el, "name")?;
+        let fact_type = get_attr(el, "type").unwrap_or_else(|| intern("boolean"));
+        let value = el.text();
+        Ok(TargetFact {
+            name,
+            fact_type,
+            value,
         })
     }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// `boolean`, `string` or `number`
+    pub fn fact_type(&self) -> &str {
+        &self.fact_type
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
 }
 
 #[derive(Debug)]
-struct TestResult {
-    id: String,
+pub struct Score {
+    pub(crate) system: Arc<str>,
+    pub(crate) maximum: f64,
+    pub(crate) value: f64,
+}
+
+impl Score {
+    pub fn from_xml(el: &Element) -> Result<Score, Error> {
+        let system = get_attr(el, "system").unwrap_or_else(|| intern("urn:xccdf:scoring:default"));
+        let maximum = get_attr_default(el, "maximum", 100.0)?;
+        let value = match el.text().trim().parse() {
+            Ok(value) => value,
+            Err(error) => return Err(Error::invalid_value(el.name(), None, &el.text(), error)),
+        };
+        Ok(Score {
+            system,
+            maximum,
+            value,
+        })
+    }
+
+    pub fn system(&self) -> &str {
+        &self.system
+    }
+
+    pub fn maximum(&self) -> f64 {
+        self.maximum
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
 }
 
 impl TestResult {
-    pub fn from_xml(el: &Element) -> Result<TestResult, String> {
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> Result<TestResult, Error> {
         let id = require_attr(el, "id")?;
-        Ok(TestResult { id })
+        let other_attributes = other_attributes(el, &["id", "start-time", "end-time"]);
+        let start_time = get_attr_date_time(el, "start-time", opts)?;
+        let end_time = require_attr_date_time(el, "end-time", opts)?;
+        let mut profile = None;
+        let mut identity = None;
+        let mut targets = Vec::new();
+        let mut target_addresses = Vec::new();
+        let mut target_facts = Vec::new();
+        let mut rule_results = Vec::new();
+        let mut scores = Vec::new();
+        let mut signature = None;
+        let mut extensions = Vec::new();
+        for_each_item(el, opts, |child| {
+            if is_extension(child) {
+                extensions.push(child.clone());
+            } else if child.is("profile", XCCDF12_NS) {
+                profile = Some(require_attr(child, "idref")?);
+            } else if child.is("identity", XCCDF12_NS) {
+                identity = Some(child.text());
+            } else if child.is("target", XCCDF12_NS) {
+                targets.push(child.text());
+            } else if child.is("target-address", XCCDF12_NS) {
+                target_addresses.push(child.text());
+            } else if child.is("target-facts", XCCDF12_NS) {
+                for fact in child.children().filter(|f| f.is("fact", XCCDF12_NS)) {
+                    target_facts.push(TargetFact::from_xml(fact)?);
+                }
+            } else if child.is("rule-result", XCCDF12_NS) {
+                rule_results.push(RuleResult::from_xml(child, opts)?);
+            } else if child.is("score", XCCDF12_NS) {
+                scores.push(Score::from_xml(child)?);
+            } else if child.is("signature", XCCDF12_NS) {
+                if signature.is_some() {
+                    return Err(Error::duplicate_element(el.name(), "signature"));
+                }
+                signature = Some(dsig::from_xccdf_signature(child)?);
+            }
+            Ok(())
+        })?;
+        Ok(TestResult {
+            id,
+            start_time,
+            end_time,
+            profile,
+            identity,
+            targets,
+            target_addresses,
+            target_facts,
+            rule_results,
+            scores,
+            signature,
+            extensions,
+            other_attributes,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn start_time(&self) -> Option<DateTime<FixedOffset>> {
+        self.start_time
+    }
+
+    pub fn end_time(&self) -> Option<DateTime<FixedOffset>> {
+        self.end_time
+    }
+
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// The account the scan was run as
+    pub fn identity(&self) -> Option<&str> {
+        self.identity.as_deref()
+    }
+
+    /// Names of the scanned systems
+    pub fn targets(&self) -> &[String] {
+        &self.targets
+    }
+
+    pub fn target_addresses(&self) -> &[String] {
+        &self.target_addresses
+    }
+
+    pub fn target_facts(&self) -> &[TargetFact] {
+        &self.target_facts
+    }
+
+    pub fn rule_results(&self) -> &[RuleResult] {
+        &self.rule_results
+    }
+
+    pub fn scores(&self) -> &[Score] {
+        &self.scores
+    }
+
+    pub fn extensions(&self) -> &[Element] {
+        &self.extensions
+    }
+
+    pub fn other_attributes(&self) -> &[(String, String)] {
+        &self.other_attributes
+    }
+}
+
+#[derive(Debug)]
+pub struct RuleResult {
+    pub(crate) idref: Arc<str>,
+    pub(crate) time: Option<Arc<str>>,
+    pub(crate) result: String,
+    /// The checks that were run, with the names of the checked definitions
+    pub(crate) checks: Vec<Check>,
+}
+
+impl RuleResult {
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> Result<RuleResult, Error> {
+        let idref = require_attr(el, "idref")?;
+        let time = get_attr(el, "time");
+        let result = match el.get_child("result", XCCDF12_NS) {
+            Some(result_el) => get_text(result_el, opts),
+            None => return Err(Error::missing_element(el.name(), "result")),
+        };
+        let allowed_results = [
+            "pass",
+            "fail",
+            "error",
+            "unknown",
+            "notapplicable",
+            "notchecked",
+            "notselected",
+            "informational",
+            "fixed",
+        ];
+        if !allowed_results.contains(&&result[..]) {
+            return Err(Error::invalid_value(
+                "result",
+                None,
+                &result,
+                format!("expected one of {:?}", allowed_results),
+            ));
+        }
+        let mut checks = Vec::new();
+        for child in el.children() {
+            if child.is("check", XCCDF12_NS) {
+                checks.push(Check::from_xml(child)?);
+            }
+        }
+        Ok(RuleResult {
+            idref,
+            time,
+            result,
+            checks,
+        })
+    }
+
+    pub fn idref(&self) -> &str {
+        &self.idref
+    }
+
+    pub fn time(&self) -> Option<&str> {
+        self.time.as_deref()
+    }
+
+    pub fn result(&self) -> &str {
+        &self.result
+    }
+
+    pub fn checks(&self) -> &[Check] {
+        &self.checks
+    }
+}
+
+/// `xccdf:remark` of a profile selector.
+#[derive(Debug)]
+pub struct Remark {
+    pub(crate) text: String,
+    pub(crate) lang: Option<Arc<str>>,
+}
+
+impl Remark {
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> Result<Remark, Error> {
+        let text = get_text(el, opts);
+        let lang = get_attr(el, "xml:lang");
+        Ok(Remark { text, lang })
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn lang(&self) -> Option<&str> {
+        self.lang.as_deref()
     }
 }
 
+/// Parses the `xccdf:remark` children, the only children of the selectors.
+/// Remarks of a profile selector, the foreign-namespace children are added
+/// to the extensions.
+fn parse_remarks(
+    el: &Element,
+    opts: &ParseOptions,
+    extensions: &mut Vec<Element>,
+) -> Result<Vec<Remark>, Error> {
+    let mut remarks = Vec::new();
+    for_each_item(el, opts, |child| {
+        match child.name() {
+            _ if is_extension(child) => extensions.push(child.clone()),
+            "remark" if child.has_ns(XCCDF12_NS) => remarks.push(Remark::from_xml(child, opts)?),
+            _ => opts.unknown_child(Error::unexpected_element(
+                child.name(),
+                Some("xccdf:remark"),
+            ))?,
+        }
+        Ok(())
+    })?;
+    Ok(remarks)
+}
+
 #[derive(Debug)]
-struct Select {
-    idref: String,
-    selected: bool,
+pub struct Select {
+    pub(crate) idref: Arc<str>,
+    pub(crate) selected: bool,
+    pub(crate) remarks: Vec<Remark>,
+    pub(crate) extensions: Vec<Element>,
 }
 
 impl Select {
-    pub fn from_xml(el: &Element) -> Result<Select, String> {
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> Result<Select, Error> {
         let idref = require_attr(el, "idref")?;
-        let selected = require_attr(el, "selected")?.parse().unwrap();
-        Ok(Select { idref, selected })
+        require_attr(el, "selected")?;
+        let selected = get_attr_default_bool(el, "selected", false)?;
+        let mut extensions = Vec::new();
+        let remarks = parse_remarks(el, opts, &mut extensions)?;
+        Ok(Select {
+            idref,
+            selected,
+            remarks,
+            extensions,
+        })
+    }
+
+    pub fn idref(&self) -> &str {
+        &self.idref
+    }
+
+    pub fn selected(&self) -> bool {
+        self.selected
+    }
+
+    pub fn remarks(&self) -> &[Remark] {
+        &self.remarks
+    }
+
+    pub fn extensions(&self) -> &[Element] {
+        &self.extensions
     }
 }
 
-#[derive(Debug)]
-struct SetComplexValue {
-    text: String,
+/// `xccdf:set-complex-value`, a list of items replacing the value.
+#[derive(Debug)]
+pub struct SetComplexValue {
+    pub(crate) idref: Arc<str>,
+    pub(crate) items: Vec<String>,
+    pub(crate) extensions: Vec<Element>,
+}
+
+impl SetComplexValue {
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> Result<SetComplexValue, Error> {
+        let idref = require_attr(el, "idref")?;
+        let mut extensions = Vec::new();
+        let items = parse_items(el, opts, &mut extensions)?;
+        Ok(SetComplexValue {
+            idref,
+            items,
+            extensions,
+        })
+    }
+
+    pub fn idref(&self) -> &str {
+        &self.idref
+    }
+
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+
+    pub fn extensions(&self) -> &[Element] {
+        &self.extensions
+    }
 }
 
-impl SetComplexValue {
-    pub fn from_xml(el: &Element) -> Result<SetComplexValue, String> {
-        let text = el.text();
-        Ok(SetComplexValue { text })
-    }
+/// Texts of the `xccdf:item` children of a complex value, the
+/// foreign-namespace children are added to the extensions.
+fn parse_items(
+    el: &Element,
+    opts: &ParseOptions,
+    extensions: &mut Vec<Element>,
+) -> Result<Vec<String>, Error> {
+    let mut items = Vec::new();
+    for_each_item(el, opts, |child| {
+        match child.name() {
+            _ if is_extension(child) => extensions.push(child.clone()),
+            "item" if child.has_ns(XCCDF12_NS) => items.push(child.text()),
+            _ => opts.unknown_child(Error::unexpected_element(child.name(), Some("xccdf:item")))?,
+        }
+        Ok(())
+    })?;
+    Ok(items)
 }
 
 #[derive(Debug)]
-struct SetValue {
-    text: String,
+pub struct SetValue {
+    pub(crate) idref: Arc<str>,
+    pub(crate) text: String,
 }
 
 impl SetValue {
-    pub fn from_xml(el: &Element) -> Result<SetValue, String> {
+    pub fn from_xml(el: &Element) -> Result<SetValue, Error> {
+        let idref = require_attr(el, "idref")?;
         let text = el.text();
-        Ok(SetValue { text })
+        Ok(SetValue { idref, text })
+    }
+
+    pub fn idref(&self) -> &str {
+        &self.idref
+    }
+
+    pub fn value(&self) -> &str {
+        &self.text
     }
 }
 
+string_enum!(
+    /// Comparison of a Value given by the `operator` attribute.
+    ValueOperator {
+        Equals => "equals",
+        NotEqual => "not equal",
+        GreaterThan => "greater than",
+        LessThan => "less than",
+        GreaterThanOrEqual => "greater than or equal",
+        LessThanOrEqual => "less than or equal",
+        PatternMatch => "pattern match",
+    }
+);
+
 #[derive(Debug)]
-struct RefineValue {
-    text: String,
+pub struct RefineValue {
+    pub(crate) idref: Arc<str>,
+    pub(crate) selector: Option<Arc<str>>,
+    pub(crate) operator: Option<ValueOperator>,
+    pub(crate) remarks: Vec<Remark>,
+    pub(crate) extensions: Vec<Element>,
 }
 
 impl RefineValue {
-    pub fn from_xml(el: &Element) -> Result<RefineValue, String> {
-        let text = el.text();
-        Ok(RefineValue { text })
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> Result<RefineValue, Error> {
+        let idref = require_attr(el, "idref")?;
+        let selector = get_attr(el, "selector");
+        let operator = get_attr_optional(el, "operator", opts)?;
+        let mut extensions = Vec::new();
+        let remarks = parse_remarks(el, opts, &mut extensions)?;
+        Ok(RefineValue {
+            idref,
+            selector,
+            operator,
+            remarks,
+            extensions,
+        })
+    }
+
+    pub fn idref(&self) -> &str {
+        &self.idref
+    }
+
+    pub fn selector(&self) -> Option<&str> {
+        self.selector.as_deref()
+    }
+
+    pub fn operator(&self) -> Option<ValueOperator> {
+        self.operator
+    }
+
+    pub fn remarks(&self) -> &[Remark] {
+        &self.remarks
+    }
+
+    pub fn extensions(&self) -> &[Element] {
+        &self.extensions
     }
 }
 
+/// `xccdf:refine-rule`, the attributes that aren't given keep the values of
+/// the rule.
 #[derive(Debug)]
-struct RefineRule {
-    text: String,
+pub struct RefineRule {
+    pub(crate) idref: Arc<str>,
+    pub(crate) selector: Option<Arc<str>>,
+    pub(crate) weight: Option<f64>,
+    pub(crate) severity: Option<Severity>,
+    pub(crate) role: Option<Role>,
+    pub(crate) remarks: Vec<Remark>,
+    pub(crate) extensions: Vec<Element>,
 }
 
 impl RefineRule {
-    pub fn from_xml(el: &Element) -> Result<RefineRule, String> {
-        let text = el.text();
-        Ok(RefineRule { text })
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> Result<RefineRule, Error> {
+        let idref = require_attr(el, "idref")?;
+        let selector = get_attr(el, "selector");
+        let weight = get_attr_optional(el, "weight", opts)?;
+        let severity = get_attr_optional(el, "severity", opts)?;
+        let role = get_attr_optional(el, "role", opts)?;
+        let mut extensions = Vec::new();
+        let remarks = parse_remarks(el, opts, &mut extensions)?;
+        Ok(RefineRule {
+            idref,
+            selector,
+            weight,
+            severity,
+            role,
+            remarks,
+            extensions,
+        })
+    }
+
+    pub fn idref(&self) -> &str {
+        &self.idref
+    }
+
+    pub fn selector(&self) -> Option<&str> {
+        self.selector.as_deref()
+    }
+
+    pub fn weight(&self) -> Option<f64> {
+        self.weight
+    }
+
+    pub fn severity(&self) -> Option<Severity> {
+        self.severity
+    }
+
+    pub fn role(&self) -> Option<Role> {
+        self.role
+    }
+
+    pub fn remarks(&self) -> &[Remark] {
+        &self.remarks
+    }
+
+    pub fn extensions(&self) -> &[Element] {
+        &self.extensions
     }
 }
 
 #[derive(Debug)]
-struct Warning {
-    text: String,
+pub struct Warning {
+    pub(crate) text: String,
 }
 
 impl Warning {
-    pub fn from_xml(el: &Element) -> Result<Warning, String> {
+    pub fn from_xml(el: &Element) -> Result<Warning, Error> {
         let text = el.text();
         Ok(Warning { text })
     }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
 }
 
 #[derive(Debug)]
-struct Question {
-    text: String,
+pub struct Question {
+    pub(crate) text: String,
 }
 
 impl Question {
-    pub fn from_xml(el: &Element) -> Result<Question, String> {
-        let text = el.text();
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> Result<Question, Error> {
+        let text = get_text(el, opts);
         Ok(Question { text })
     }
 }
 
 #[derive(Debug)]
-struct Rationale {
-    text: String,
+pub struct Rationale {
+    pub(crate) content: RichText,
 }
 
 impl Rationale {
-    pub fn from_xml(el: &Element) -> Result<Rationale, String> {
-        let text = el.text();
-        Ok(Rationale { text })
+    pub fn from_xml(el: &Element) -> Result<Rationale, Error> {
+        let content = RichText::from_xml(el);
+        Ok(Rationale { content })
+    }
+
+    pub fn content(&self) -> &RichText {
+        &self.content
+    }
+
+    pub fn text(&self) -> String {
+        self.content.to_text(|_| None)
     }
 }
 
+/// Items of which at least one must be selected for the item to be
+/// selected.
 #[derive(Debug)]
-struct Requires {
-    text: String,
+pub struct Requires {
+    pub(crate) idrefs: Vec<String>,
 }
 
 impl Requires {
-    pub fn from_xml(el: &Element) -> Result<Requires, String> {
-        let text = el.text();
-        Ok(Requires { text })
+    pub fn from_xml(el: &Element) -> Result<Requires, Error> {
+        let idrefs = require_attr(el, "idref")?
+            .split_whitespace()
+            .map(|idref| idref.to_string())
+            .collect();
+        Ok(Requires { idrefs })
+    }
+
+    pub fn idrefs(&self) -> &[String] {
+        &self.idrefs
     }
 }
 
 #[derive(Debug)]
-struct Conflicts {
-    idref: String,
+pub struct Conflicts {
+    pub(crate) idref: Arc<str>,
 }
 
 impl Conflicts {
-    pub fn from_xml(el: &Element) -> Result<Conflicts, String> {
+    pub fn from_xml(el: &Element) -> Result<Conflicts, Error> {
         let idref = require_attr(el, "idref")?;
         Ok(Conflicts { idref })
     }
+
+    pub fn idref(&self) -> &str {
+        &self.idref
+    }
+}
+
+/// Kind of identifier recognized by the ident system URI. The legacy DISA
+/// system is used for both rule and vulnerability IDs, they're told apart
+/// by the identifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IdentSystem {
+    Cce,
+    Cve,
+    Cpe,
+    Cci,
+    DisaRuleId,
+    DisaVulnId,
+    Other,
+}
+
+/// Whether `text` is `prefix` followed by `min` or more digits.
+fn is_numbered(text: &str, prefix: &str, min: usize) -> bool {
+    text.strip_prefix(prefix)
+        .is_some_and(|n| n.len() >= min && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Ident systems in the spelling used by the SCAP content ecosystem.
+pub const KNOWN_IDENT_SYSTEMS: [(&str, IdentSystem); 6] = [
+    ("http://cce.mitre.org", IdentSystem::Cce),
+    ("https://nvd.nist.gov/cce/index.cfm", IdentSystem::Cce),
+    ("https://ncp.nist.gov/cce", IdentSystem::Cce),
+    ("http://cve.mitre.org", IdentSystem::Cve),
+    ("http://iase.disa.mil/cci", IdentSystem::Cci),
+    ("https://public.cyber.mil/stigs/cci/", IdentSystem::Cci),
+];
+
+impl IdentSystem {
+    /// Recognizes one of [`KNOWN_IDENT_SYSTEMS`], in any letter case, or
+    /// else the system by the words of its URI, e.g. `https://ncp.nist.gov/cce`
+    /// or `http://cve.mitre.org`.
+    pub fn recognize(system: &str, text: &str) -> IdentSystem {
+        let known = KNOWN_IDENT_SYSTEMS
+            .iter()
+            .find(|(uri, _)| uri.eq_ignore_ascii_case(system));
+        if let Some((_, kind)) = known {
+            return *kind;
+        }
+        let system = system.to_lowercase();
+        let words: Vec<&str> = system.split(|c: char| !c.is_ascii_alphanumeric()).collect();
+        let has = |word: &str| words.contains(&word);
+        if has("cce") {
+            IdentSystem::Cce
+        } else if has("cve") {
+            IdentSystem::Cve
+        } else if has("cpe") {
+            IdentSystem::Cpe
+        } else if has("cci") {
+            IdentSystem::Cci
+        } else if has("legacy") && (system.contains("cyber.mil") || system.contains("disa.mil")) {
+            match text.starts_with("SV-") {
+                true => IdentSystem::DisaRuleId,
+                false => IdentSystem::DisaVulnId,
+            }
+        } else {
+            IdentSystem::Other
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IdentSystem::Cce => "CCE",
+            IdentSystem::Cve => "CVE",
+            IdentSystem::Cpe => "CPE",
+            IdentSystem::Cci => "CCI",
+            IdentSystem::DisaRuleId => "DISA Rule ID",
+            IdentSystem::DisaVulnId => "DISA Vuln ID",
+            IdentSystem::Other => "other",
+        }
+    }
+
+    /// Whether the identifier has the format of the system, e.g.
+    /// `CCE-80644-8` or `CVE-2021-44228`. Identifiers of unknown systems are
+    /// always valid.
+    pub fn is_valid(&self, id: &str) -> bool {
+        match self {
+            IdentSystem::Cce => id
+                .strip_prefix("CCE-")
+                .and_then(|rest| rest.split_once('-'))
+                .is_some_and(|(number, check)| {
+                    (1..=5).contains(&number.len())
+                        && number.bytes().all(|b| b.is_ascii_digit())
+                        && check.len() == 1
+                        && check.bytes().all(|b| b.is_ascii_digit())
+                }),
+            IdentSystem::Cve => id
+                .strip_prefix("CVE-")
+                .and_then(|rest| rest.split_once('-'))
+                .is_some_and(|(year, number)| {
+                    year.len() == 4 && is_numbered(year, "", 4) && is_numbered(number, "", 4)
+                }),
+            IdentSystem::Cpe => id.starts_with("cpe:/") || id.starts_with("cpe:2.3:"),
+            IdentSystem::Cci => is_numbered(id, "CCI-", 6) && id.len() == 10,
+            IdentSystem::DisaRuleId => {
+                let id = id.strip_suffix("_rule").unwrap_or(id);
+                let id = match id.rsplit_once('r') {
+                    Some((number, revision)) if is_numbered(revision, "", 1) => number,
+                    _ => id,
+                };
+                is_numbered(id, "SV-", 1)
+            }
+            IdentSystem::DisaVulnId => is_numbered(id, "V-", 1),
+            IdentSystem::Other => true,
+        }
+    }
+}
+
+impl std::fmt::Display for IdentSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
 }
 
 #[derive(Debug)]
-struct Ident {
-    text: String,
-    system: String,
+pub struct Ident {
+    pub(crate) text: String,
+    pub(crate) system: Arc<str>,
 }
 
 impl Ident {
-    pub fn from_xml(el: &Element) -> Result<Ident, String> {
-        let text = el.text();
+    pub fn from_xml(el: &Element, opts: &ParseOptions) -> Result<Ident, Error> {
+        let text = get_text(el, opts);
         let system = require_attr(el, "system")?;
         Ok(Ident { text, system })
     }
+
+    pub fn system(&self) -> &str {
+        &self.system
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Kind of the identifier recognized by the system.
+    pub fn kind(&self) -> IdentSystem {
+        IdentSystem::recognize(&self.system, &self.text)
+    }
+
+    /// Whether the identifier has the format of its system.
+    pub fn is_valid(&self) -> bool {
+        self.kind().is_valid(&self.text)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::options::CancellationToken;
 
     #[test]
     fn test_ident_from_xml_ok() {
@@ -843,83 +3705,1034 @@ mod tests {
             .attr("system", "https://gov.cz")
             .append("AC-24")
             .build();
-        let real = Ident::from_xml(&e);
+        let real = Ident::from_xml(&e, &ParseOptions::default());
         assert!(real.is_ok());
         let real = real.unwrap();
         let expected = Ident {
             text: String::from("AC-24"),
-            system: String::from("https://gov.cz"),
+            system: Arc::from("https://gov.cz"),
         };
         assert_eq!(real.system, expected.system);
         assert_eq!(real.text, expected.text);
     }
 
+    #[test]
+    fn test_memory_stats() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Group id=\"g\"><title>G</title><Rule id=\"r1\"><title>R1</title>\
+             <description>A long description</description></Rule><Rule id=\"r2\"/></Group></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let stats = Benchmark::from_xml(&el).unwrap().memory_stats();
+        let rules = stats.kinds.iter().find(|k| k.kind == "Rule").unwrap();
+        assert_eq!(rules.count, 2);
+        let largest = stats.largest_text.unwrap();
+        assert_eq!(largest.item_id, "r1");
+        assert_eq!(largest.field, "description");
+        assert_eq!(largest.bytes, 18);
+    }
+
+    #[test]
+    fn test_profile_extensions() {
+        let el: Element = format!(
+            "<Profile xmlns=\"{}\" xmlns:ssg=\"urn:example:ssg\" id=\"p\"><title>P</title>\
+             <ssg:stability>stable</ssg:stability><ssg:hidden/><ssg:custom/></Profile>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let profile = Profile::from_xml(&el, &ParseOptions::default()).unwrap();
+        assert_eq!(profile.extensions().len(), 3);
+        assert_eq!(
+            profile.known_extensions(),
+            vec![
+                ProfileExtension::Stability(String::from("stable")),
+                ProfileExtension::Hidden(true)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_foreign_children() {
+        let el: Element = format!(
+            "<Value xmlns=\"{}\" xmlns:v=\"urn:example:vendor\" id=\"v\">\
+             <v:value>vendor</v:value><value>1</value></Value>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let value = Value::from_xml(&el, &ParseOptions::default()).unwrap();
+        assert_eq!(value.values().len(), 1);
+        assert_eq!(value.value_for(None), Some("1"));
+        assert_eq!(value.extensions()[0].name(), "value");
+        let el: Element = format!(
+            "<set-complex-value xmlns=\"{}\" xmlns:v=\"urn:example:vendor\" idref=\"v\">\
+             <item>a</item><v:item>b</v:item></set-complex-value>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let set = SetComplexValue::from_xml(&el, &ParseOptions::default()).unwrap();
+        assert_eq!(set.items(), ["a"]);
+        assert_eq!(set.extensions()[0].text(), "b");
+        let el: Element = format!(
+            "<select xmlns=\"{}\" xmlns:v=\"urn:example:vendor\" idref=\"r\" selected=\"true\">\
+             <remark>why</remark><v:remark>ticket</v:remark></select>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let select = Select::from_xml(&el, &ParseOptions::default()).unwrap();
+        assert_eq!(select.remarks().len(), 1);
+        assert_eq!(select.extensions()[0].text(), "ticket");
+    }
+
+    #[test]
+    fn test_value_children() {
+        let el: Element = format!(
+            "<Value xmlns=\"{}\" id=\"v\" type=\"number\"><title>V</title><value>1</value>\
+             <default>1</default><lower-bound>0</lower-bound><frobnicate/></Value>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let error = Value::from_xml(&el, &ParseOptions::default()).unwrap_err();
+        assert!(error.to_string().contains("'frobnicate'"), "{}", error);
+        let opts = ParseOptions {
+            permissive: true,
+            ..ParseOptions::default()
+        };
+        let value = Value::from_xml(&el, &opts).unwrap();
+        let kept: Vec<&str> = value.extensions().iter().map(|e| e.name()).collect();
+        assert_eq!(kept, ["default", "lower-bound", "frobnicate"]);
+        assert_eq!(opts.take_warnings().len(), 1);
+    }
+
+    #[test]
+    fn test_resolve_profile() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Profile id=\"base\"><title>Base</title><select idref=\"r1\" selected=\"true\"/>\
+             <select idref=\"r2\" selected=\"true\"/><set-value idref=\"v\">1</set-value></Profile>\
+             <Profile id=\"child\" extends=\"base\"><title>Child</title>\
+             <select idref=\"r2\" selected=\"false\"/><select idref=\"r3\" selected=\"true\"/>\
+             <set-value idref=\"v\">2</set-value></Profile>\
+             <Profile id=\"loop1\" extends=\"loop2\"><title>L1</title></Profile>\
+             <Profile id=\"loop2\" extends=\"loop1\"><title>L2</title></Profile>\
+             <Profile id=\"orphan\" extends=\"missing\"><title>O</title></Profile></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let resolved = benchmark.resolve_profile("child").unwrap();
+        assert_eq!(resolved.chain, vec!["base", "child"]);
+        assert_eq!(resolved.titles[0].title, "Child");
+        assert_eq!(resolved.selected_ids(), vec!["r1", "r3"]);
+        assert_eq!(resolved.set_values.len(), 1);
+        assert_eq!(resolved.set_values[0].text, "2");
+        assert!(benchmark.resolve_profile("loop1").is_err());
+        assert!(benchmark.resolve_profile("orphan").is_err());
+        assert!(benchmark.resolve_profile("missing").is_err());
+    }
+
+    #[test]
+    fn test_refine_rule() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Profile id=\"p\"><title>P</title>\
+             <select idref=\"r1\" selected=\"true\"><remark xml:lang=\"en\">Required</remark></select>\
+             <refine-rule idref=\"web\" severity=\"low\" weight=\"2.5\"/>\
+             <refine-rule idref=\"r1\" severity=\"high\" role=\"unscored\"/>\
+             <refine-value idref=\"v\" selector=\"strict\" operator=\"less than\"/></Profile>\
+             <Rule id=\"r1\" cluster-id=\"web\" severity=\"medium\"/>\
+             <Rule id=\"r2\" cluster-id=\"web\" severity=\"medium\"/></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let profile = benchmark.find_profile("p").unwrap();
+        assert_eq!(profile.selects[0].remarks[0].text, "Required");
+        assert_eq!(profile.selects[0].remarks[0].lang.as_deref(), Some("en"));
+        assert_eq!(
+            profile.refine_values[0].operator,
+            Some(ValueOperator::LessThan)
+        );
+        let resolved = benchmark.resolve_profile("p").unwrap();
+        let r1 = benchmark.rule("r1").unwrap();
+        let r2 = benchmark.rule("r2").unwrap();
+        assert_eq!(resolved.severity(r1), Severity::High);
+        assert_eq!(resolved.role(r1), Role::Unscored);
+        assert_eq!(resolved.weight(r1), 1.0);
+        assert_eq!(resolved.severity(r2), Severity::Low);
+        assert_eq!(resolved.weight(r2), 2.5);
+    }
+
+    #[test]
+    fn test_complex_values() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Profile id=\"base\"><title>Base</title><refine-value idref=\"ciphers\" selector=\"strict\"/>\
+             </Profile><Profile id=\"p\" extends=\"base\"><title>P</title>\
+             <set-complex-value idref=\"ports\"><item>22</item><item>443</item></set-complex-value>\
+             </Profile><Value id=\"ports\" type=\"number\"><complex-value><item>80</item></complex-value>\
+             </Value><Value id=\"ciphers\"><complex-value><item>aes</item><item>des</item></complex-value>\
+             <complex-value selector=\"strict\"><item>aes</item></complex-value></Value></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let resolved = benchmark.resolve_profile("p").unwrap();
+        assert_eq!(
+            benchmark.complex_value_in_profile("ports", &resolved),
+            Some(vec![String::from("22"), String::from("443")])
+        );
+        assert_eq!(
+            benchmark.complex_value_in_profile("ciphers", &resolved),
+            Some(vec![String::from("aes")])
+        );
+        assert_eq!(
+            benchmark.value("ciphers").unwrap().complex_value_for(None),
+            Some(&[String::from("aes"), String::from("des")][..])
+        );
+    }
+
+    #[test]
+    fn test_reference_dublin_core() {
+        let el: Element = format!(
+            "<Rule xmlns=\"{}\" xmlns:dc=\"{}\" id=\"r\">\
+             <reference href=\"https://www.cisecurity.org/\">\
+             <dc:title>CIS Benchmark</dc:title><dc:identifier>5.2.1</dc:identifier></reference>\
+             <reference href=\"https://example.org/nist\">AC-6</reference></Rule>",
+            XCCDF12_NS,
+            crate::writer::DC_NS
+        )
+        .parse()
+        .unwrap();
+        let rule = Rule::from_xml(&el, &ParseOptions::default()).unwrap();
+        let cis = &rule.references[0];
+        assert_eq!(cis.href(), Some("https://www.cisecurity.org/"));
+        assert_eq!(cis.text(), "");
+        assert_eq!(cis.dc("identifier"), Some("5.2.1"));
+        assert_eq!(cis.dc("title"), Some("CIS Benchmark"));
+        assert_eq!(rule.references[1].text(), "AC-6");
+        assert!(rule.references[1].dc_elements().is_empty());
+    }
+
+    #[test]
+    fn test_metadata() {
+        let el: Element = format!(
+            "<metadata xmlns=\"{}\" xmlns:dc=\"{}\" xmlns:dcterms=\"{}\" \
+             xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\">\
+             <dc:publisher>SSG</dc:publisher><dc:contributor>A</dc:contributor>\
+             <dc:contributor>B</dc:contributor><dc:rights xml:lang=\"en\">BSD</dc:rights>\
+             <dcterms:modified xsi:type=\"dcterms:W3CDTF\">2022-01-31</dcterms:modified>\
+             <build xmlns=\"urn:example\" id=\"42\"/></metadata>",
+            XCCDF12_NS,
+            crate::writer::DC_NS,
+            dc::DCTERMS_NS
+        )
+        .parse()
+        .unwrap();
+        let metadata = Metadata::from_xml(&el, &ParseOptions::default()).unwrap();
+        assert_eq!(metadata.dc("contributor"), vec!["A", "B"]);
+        assert_eq!(metadata.dc("publisher"), vec!["SSG"]);
+        assert_eq!(metadata.dc[3].lang(), Some("en"));
+        let modified = &metadata.dc[4];
+        assert!(modified.is_term());
+        assert_eq!(modified.scheme(), Some("dcterms:W3CDTF"));
+        assert_eq!(metadata.other()[0].attr("id"), Some("42"));
+    }
+
+    #[test]
+    fn test_notice_markup() {
+        let el: Element = format!(
+            "<notice xmlns=\"{}\" xmlns:h=\"http://www.w3.org/1999/xhtml\" id=\"n\" \
+             xml:lang=\"en-US\">Use <h:b>only</h:b> for testing.<h:br/>See LICENSE.</notice>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let notice = Notice::from_xml(&el).unwrap();
+        assert_eq!(notice.lang(), Some("en-US"));
+        assert_eq!(notice.text(), "Use only for testing.\nSee LICENSE.");
+        let bold = notice.body().children().next().unwrap();
+        assert_eq!((bold.name(), bold.text().as_str()), ("b", "only"));
+    }
+
+    #[test]
+    fn test_front_matter_markup() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" xmlns:h=\"http://www.w3.org/1999/xhtml\" id=\"b\">\
+             <status>draft</status><front-matter><h:h1>Guide</h:h1><h:ul><h:li>One</h:li>\
+             </h:ul></front-matter><rear-matter>End</rear-matter><version>1</version></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let names: Vec<&str> = benchmark.front_matters()[0]
+            .body()
+            .children()
+            .map(|c| c.name())
+            .collect();
+        assert_eq!(names, vec!["h1", "ul"]);
+        assert_eq!(benchmark.rear_matters()[0].text(), "End");
+    }
+
+    #[test]
+    fn test_resolve_tailored_profile() {
+        let benchmark: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Profile id=\"base\"><title>Base</title><select idref=\"r1\" selected=\"true\"/></Profile>\
+             </Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let tailoring: Element = format!(
+            "<Tailoring xmlns=\"{}\" id=\"t\"><benchmark href=\"ds.xml\"/><version time=\"2022-01-01T00:00:00\">1</version>\
+             <Profile id=\"tailored\" extends=\"base\"><title>Tailored</title>\
+             <select idref=\"r2\" selected=\"true\"/><set-value idref=\"v\">5</set-value></Profile></Tailoring>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&benchmark).unwrap();
+        let tailoring = Tailoring::from_xml(&tailoring).unwrap();
+        assert_eq!(tailoring.benchmark_href.as_deref(), Some("ds.xml"));
+        let resolved = benchmark
+            .resolve_tailored_profile(&tailoring, "tailored")
+            .unwrap();
+        assert_eq!(resolved.selected_ids(), vec!["r1", "r2"]);
+        assert_eq!(resolved.info().values[0].value, "5");
+        assert!(benchmark.resolve_profile("tailored").is_err());
+    }
+
+    #[test]
+    fn test_fix_tokens() {
+        let el: Element = format!(
+            "<fix xmlns=\"{}\" system=\"urn:xccdf:fix:script:sh\">echo <sub idref=\"v\"/> &gt; \
+             <instance context=\"file\"/></fix>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let fix = Fix::from_xml(&el).unwrap();
+        assert_eq!(
+            fix.tokens,
+            vec![
+                FixToken::Text(String::from("echo ")),
+                FixToken::ValueRef(Arc::from("v")),
+                FixToken::Text(String::from(" > ")),
+                FixToken::InstanceRef(Arc::from("file")),
+            ]
+        );
+        let script = fix.substitute(|_| Some(String::from("1")), |_| Some(String::from("/f")));
+        assert_eq!(script.unwrap(), "echo 1 > /f");
+        let error = fix.substitute(|_| None, |_| None).unwrap_err().to_string();
+        assert!(error.contains("value 'v'") && error.contains("instance 'file'"));
+    }
+
+    #[test]
+    fn test_render_plain_text() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status>\
+             <plain-text id=\"os\">Red Hat Enterprise Linux</plain-text><version>1</version>\
+             <Profile id=\"p\"><title>P</title><description>Hardening of <sub idref=\"os\" use=\"legacy\"/> \
+             and <sub idref=\"missing\"/>.</description></Profile></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let description = &benchmark.profiles[0].descriptions[0];
+        assert_eq!(
+            benchmark.render(description),
+            "Hardening of Red Hat Enterprise Linux and ."
+        );
+        assert_eq!(description.text(), "Hardening of  and .");
+    }
+
+    #[test]
+    fn test_rule_signature() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Rule id=\"r\"><signature><Signature xmlns=\"{}\"><SignedInfo>\
+             <SignatureMethod Algorithm=\"http://www.w3.org/2001/04/xmldsig-more#rsa-sha256\"/>\
+             <Reference URI=\"#r\"><DigestMethod Algorithm=\"http://www.w3.org/2001/04/xmlenc#sha256\"/>\
+             <DigestValue>abc=</DigestValue></Reference></SignedInfo><SignatureValue>ZGVm</SignatureValue>\
+             </Signature></signature></Rule></Benchmark>",
+            XCCDF12_NS,
+            dsig::DSIG_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let signatures = benchmark.signatures();
+        assert_eq!(signatures.len(), 1);
+        assert_eq!(signatures[0].0, "r");
+        assert_eq!(&*signatures[0].1.references[0].uri, "#r");
+    }
+
+    #[test]
+    fn test_value_signatures() {
+        let signature = |id: &str| {
+            format!(
+                "<signature><Signature xmlns=\"{}\"><SignedInfo>\
+                 <SignatureMethod Algorithm=\"http://www.w3.org/2001/04/xmldsig-more#rsa-sha256\"/>\
+                 <Reference URI=\"#{}\"><DigestMethod Algorithm=\"http://www.w3.org/2001/04/xmlenc#sha256\"/>\
+                 <DigestValue>abc=</DigestValue></Reference></SignedInfo><SignatureValue>ZGVm</SignatureValue>\
+                 </Signature></signature>",
+                dsig::DSIG_NS,
+                id
+            )
+        };
+        let text = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Value id=\"v1\"><value>1</value>{}</Value>\
+             <Group id=\"g\"><Value id=\"v2\"><value>2</value>{}</Value></Group></Benchmark>",
+            XCCDF12_NS,
+            signature("v1"),
+            signature("v2")
+        );
+        let el: Element = text.parse().unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let signatures: Vec<(&str, &str)> = benchmark
+            .signatures()
+            .into_iter()
+            .map(|(id, signature)| (id, &*signature.references[0].uri))
+            .collect();
+        assert_eq!(signatures, vec![("v1", "#v1"), ("v2", "#v2")]);
+
+        let duplicate: Element = text
+            .replace(
+                "</Value><Group",
+                &format!("{}</Value><Group", signature("v1")),
+            )
+            .parse()
+            .unwrap();
+        assert!(Benchmark::from_xml(&duplicate).is_err());
+    }
+
+    #[test]
+    fn test_error_path() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Profile id=\"p\"><title>P</title><select idref=\"r1\" selected=\"true\"/>\
+             <select selected=\"true\"/></Profile></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let error = Benchmark::from_xml(&el).unwrap_err();
+        assert_eq!(error.path(), Some("Benchmark/Profile[@id='p']/select[2]"));
+        assert_eq!(error.cause(), &Error::missing_attribute("select", "idref"));
+        assert_eq!(
+            error.to_string(),
+            "Benchmark/Profile[@id='p']/select[2]: \
+             Element 'select' doesn't have required 'idref' attribute"
+        );
+    }
+
+    #[test]
+    fn test_lenient_parsing() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><version>1</version><bogus/>\
+             <Group id=\"g\"><Rule id=\"r\" severity=\"urgent\"><title>R</title></Rule></Group>\
+             </Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        assert!(Benchmark::from_xml(&el).is_err());
+        let opts = ParseOptions::lenient();
+        let benchmark = Benchmark::from_xml_with_options(&el, &opts).unwrap();
+        assert_eq!(benchmark.rule("r").unwrap().severity, Severity::Unknown);
+        let warnings: Vec<String> = opts.take_warnings().iter().map(|w| w.to_string()).collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "Benchmark/bogus: Unexpected element 'bogus'",
+                "Benchmark/Group[@id='g']/Rule[@id='r']: Element 'Rule' attribute \
+                 'severity'='urgent', but expected one of [\"unknown\", \"info\", \"low\", \"medium\", \"high\"]",
+                "Benchmark: Element 'Benchmark' is missing 'status' element",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collect_errors() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>final</status><version>1</version>\
+             <Rule id=\"r1\" selected=\"maybe\"/><Rule/><Rule id=\"r3\"/></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let errors = match Benchmark::from_xml_with_options(&el, &ParseOptions::collecting(10)) {
+            Err(Error::Multiple(errors)) => errors,
+            result => panic!("unexpected result {:?}", result),
+        };
+        let paths: Vec<&str> = errors.iter().filter_map(|e| e.path()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "Benchmark/status",
+                "Benchmark/Rule[@id='r1']",
+                "Benchmark/Rule[2]",
+                "Benchmark"
+            ]
+        );
+        let errors = match Benchmark::from_xml_with_options(&el, &ParseOptions::collecting(2)) {
+            Err(Error::Multiple(errors)) => errors,
+            result => panic!("unexpected result {:?}", result),
+        };
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn test_interned_references() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Profile id=\"p1\"><title>P1</title><select idref=\"r\" selected=\"true\"/></Profile>\
+             <Profile id=\"p2\"><title>P2</title><select idref=\"r\" selected=\"false\"/></Profile>\
+             <Rule id=\"r\"/></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let first = &benchmark.profiles[0].selects[0].idref;
+        let second = &benchmark.profiles[1].selects[0].idref;
+        assert!(Arc::ptr_eq(first, second));
+        assert!(Arc::ptr_eq(first, &benchmark.rules[0].id));
+    }
+
+    #[test]
+    fn test_other_attributes() {
+        let el: Element = format!(
+            "<Rule xmlns=\"{}\" xmlns:v=\"urn:example:vendor\" id=\"r\" severity=\"low\" \
+             v:origin=\"scanner\" Id=\"sig-target\"/>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let rule = Rule::from_xml(&el, &ParseOptions::default()).unwrap();
+        let mut attributes = rule.other_attributes().to_vec();
+        attributes.sort();
+        assert_eq!(
+            attributes,
+            vec![
+                (String::from("Id"), String::from("sig-target")),
+                (String::from("v:origin"), String::from("scanner")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_permissive_parsing() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Rule id=\"r\"><frobnicate/></Rule></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        assert!(Benchmark::from_xml(&el).is_err());
+        let opts = ParseOptions {
+            permissive: true,
+            ..ParseOptions::default()
+        };
+        let benchmark = Benchmark::from_xml_with_options(&el, &opts).unwrap();
+        assert_eq!(benchmark.rules()[0].extensions()[0].name(), "frobnicate");
+        let warnings = opts.take_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].to_string().contains("'frobnicate'"));
+    }
+
+    #[test]
+    fn test_interrupted_parsing() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Rule id=\"r\"/></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let token = CancellationToken::new();
+        let opts = ParseOptions {
+            cancellation: Some(token.clone()),
+            ..ParseOptions::lenient()
+        };
+        assert!(Benchmark::from_xml_with_options(&el, &opts).is_ok());
+        token.cancel();
+        let error = Benchmark::from_xml_with_options(&el, &opts).unwrap_err();
+        assert_eq!(error, Error::Cancelled);
+        let opts = ParseOptions {
+            deadline: Some(std::time::Instant::now()),
+            ..ParseOptions::default()
+        };
+        let error = Benchmark::from_xml_with_options(&el, &opts).unwrap_err();
+        assert!(error.is_interruption());
+    }
+
+    #[test]
+    fn test_recursive_iterators() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Value id=\"v1\"><value>1</value></Value><Rule id=\"r1\"/>\
+             <Group id=\"g1\"><Rule id=\"r2\"/><Group id=\"g2\"><Value id=\"v2\">\
+             <value>2</value></Value><Rule id=\"r3\"/></Group></Group>\
+             <Group id=\"g3\"><Rule id=\"r4\"/></Group></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let groups: Vec<_> = benchmark.iter_groups().map(|g| g.item.id()).collect();
+        assert_eq!(groups, vec!["g1", "g2", "g3"]);
+        let rules: Vec<(&str, Vec<&str>)> = benchmark
+            .iter_rules()
+            .map(|r| (r.item.id(), r.groups.iter().map(|g| g.id()).collect()))
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                ("r1", vec![]),
+                ("r2", vec!["g1"]),
+                ("r3", vec!["g1", "g2"]),
+                ("r4", vec!["g3"]),
+            ]
+        );
+        let v2 = benchmark
+            .iter_values()
+            .find(|v| v.item.id() == "v2")
+            .unwrap();
+        assert_eq!(v2.parent().map(|g| g.id()), Some("g2"));
+    }
+
+    #[test]
+    fn test_indexed_lookups() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Profile id=\"p\"><title>P</title></Profile><Rule id=\"r1\"/>\
+             <Group id=\"g1\"><Group id=\"g2\"><Value id=\"v\"><value>1</value></Value>\
+             <Rule id=\"r2\" severity=\"high\"/></Group><Rule id=\"r3\"/></Group></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        assert_eq!(benchmark.rule("r2").unwrap().severity, Severity::High);
+        assert_eq!(benchmark.rule("r3").map(|r| r.id()), Some("r3"));
+        assert_eq!(benchmark.group("g2").map(|g| g.id()), Some("g2"));
+        assert_eq!(benchmark.value("v").map(|v| v.id()), Some("v"));
+        assert_eq!(benchmark.find_profile("p").map(|p| p.id()), Some("p"));
+        assert!(benchmark.rule("g1").is_none());
+    }
+
+    #[test]
+    fn test_missing_dependencies() {
+        let el: Element = format!(
+            "<Benchmark xmlns=\"{}\" id=\"b\"><status>draft</status><version>1</version>\
+             <Group id=\"g\"><requires idref=\"r1  missing1\"/><Rule id=\"r1\"/>\
+             <Rule id=\"r2\"><conflicts idref=\"r1\"/><conflicts idref=\"missing2\"/></Rule>\
+             </Group></Benchmark>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let benchmark = Benchmark::from_xml(&el).unwrap();
+        let group = benchmark.group("g").unwrap();
+        assert_eq!(group.requires()[0].idrefs(), ["r1", "missing1"]);
+        assert_eq!(
+            benchmark.missing_dependencies(),
+            vec![
+                MissingDependency {
+                    item_id: String::from("g"),
+                    kind: "requires",
+                    idref: String::from("missing1"),
+                },
+                MissingDependency {
+                    item_id: String::from("r2"),
+                    kind: "conflicts",
+                    idref: String::from("missing2"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ident_systems() {
+        let kind = |system: &str, text: &str| IdentSystem::recognize(system, text);
+        assert_eq!(kind("https://ncp.nist.gov/cce", ""), IdentSystem::Cce);
+        assert_eq!(kind("http://cve.mitre.org", ""), IdentSystem::Cve);
+        assert_eq!(
+            kind("https://public.cyber.mil/stigs/cci/", ""),
+            IdentSystem::Cci
+        );
+        assert_eq!(
+            kind("http://cyber.mil/legacy", "SV-1r2_rule"),
+            IdentSystem::DisaRuleId
+        );
+        assert_eq!(
+            kind("http://cyber.mil/legacy", "V-1"),
+            IdentSystem::DisaVulnId
+        );
+        assert_eq!(kind("HTTP://IASE.DISA.MIL/CCI", ""), IdentSystem::Cci);
+        assert_eq!(kind("https://example.org/access", ""), IdentSystem::Other);
+        assert!(IdentSystem::Cce.is_valid("CCE-80644-8"));
+        assert!(!IdentSystem::Cce.is_valid("CCE-1"));
+        assert!(IdentSystem::Cve.is_valid("CVE-2021-44228"));
+        assert!(!IdentSystem::Cve.is_valid("CVE-21-44228"));
+        assert!(IdentSystem::Cci.is_valid("CCI-000366"));
+        assert!(IdentSystem::DisaRuleId.is_valid("SV-230221r743913_rule"));
+        assert!(!IdentSystem::DisaVulnId.is_valid("V-x"));
+
+        let el: Element = format!(
+            "<Rule xmlns=\"{}\" id=\"r\"><ident system=\"https://ncp.nist.gov/cce\">CCE-80644-8</ident>\
+             <ident system=\"http://cve.mitre.org\">CVE-2021-1</ident>\
+             <ident system=\"http://cve.mitre.org\">CVE-2021-44228</ident></Rule>",
+            XCCDF12_NS
+        )
+        .parse()
+        .unwrap();
+        let rule = Rule::from_xml(&el, &ParseOptions::default()).unwrap();
+        assert_eq!(rule.cce(), Some("CCE-80644-8"));
+        assert_eq!(rule.cves(), vec!["CVE-2021-1", "CVE-2021-44228"]);
+        assert!(!rule.idents()[1].is_valid());
+    }
+
+    #[test]
+    fn test_platform_specification() {
+        let el: Element = format!(
+            "<platform-specification xmlns=\"{}\"><platform id=\"rhel_no_machine\">\
+             <title>RHEL, not a machine</title><logical-test operator=\"AND\" negate=\"false\">\
+             <fact-ref name=\"cpe:/o:redhat:enterprise_linux\"/>\
+             <logical-test operator=\"OR\" negate=\"true\"><fact-ref name=\"cpe:/a:machine\"/>\
+             <check-fact-ref system=\"http://oval.mitre.org/XMLSchema/oval-definitions-5\" \
+             href=\"oval.xml\" id-ref=\"oval:x:def:1\"/></logical-test></logical-test></platform>\
+             </platform-specification>",
+            CPE_LANG_NS
+        )
+        .parse()
+        .unwrap();
+        let specification = PlatformSpecification::from_xml(&el).unwrap();
+        let platform = specification.platform("rhel_no_machine").unwrap();
+        assert_eq!(platform.titles(), ["RHEL, not a machine"]);
+        let test = platform.test();
+        assert_eq!(test.operator(), LogicalOperator::And);
+        assert_eq!(
+            test.fact_names(),
+            vec!["cpe:/o:redhat:enterprise_linux", "cpe:/a:machine"]
+        );
+        let facts = |names: &'static [&'static str]| move |name: &str| names.contains(&name);
+        let no_check = |_: &str, _: &str, _: &str| false;
+        assert!(test.evaluate(&facts(&["cpe:/o:redhat:enterprise_linux"]), &no_check));
+        assert!(!test.evaluate(
+            &facts(&["cpe:/o:redhat:enterprise_linux", "cpe:/a:machine"]),
+            &no_check
+        ));
+        assert!(
+            !test.evaluate(&facts(&["cpe:/o:redhat:enterprise_linux"]), &|_, _, id| id
+                == "oval:x:def:1")
+        );
+
+        let el: Element = format!(
+            "<platform-specification xmlns=\"{}\"><platform id=\"p\"/></platform-specification>",
+            CPE_LANG_NS
+        )
+        .parse()
+        .unwrap();
+        assert!(PlatformSpecification::from_xml(&el).is_err());
+    }
+
     #[test]
     fn test_ident_from_xml_err() {
         let f = Element::builder("ident", XCCDF12_NS)
             .attr("wrong_attribute_name", "https://gov.cz")
             .append("AC-24")
             .build();
-        let real = Ident::from_xml(&f);
+        let real = Ident::from_xml(&f, &ParseOptions::default());
         assert!(real.is_err());
     }
 }
 
 #[derive(Debug)]
-struct ProfileNote {
-    text: String,
+pub struct ProfileNote {
+    pub(crate) text: String,
 }
 
 impl ProfileNote {
-    pub fn from_xml(el: &Element) -> Result<ProfileNote, String> {
+    pub fn from_xml(el: &Element) -> Result<ProfileNote, Error> {
         let text = el.text();
         Ok(ProfileNote { text })
     }
 }
 
 #[derive(Debug)]
-struct FixText {
-    text: String,
+pub struct FixText {
+    pub(crate) lang: Option<Arc<str>>,
+    pub(crate) content: RichText,
 }
 
 impl FixText {
-    pub fn from_xml(el: &Element) -> Result<FixText, String> {
-        let text = el.text();
-        Ok(FixText { text })
+    pub fn from_xml(el: &Element) -> Result<FixText, Error> {
+        let lang = get_attr(el, "xml:lang");
+        let content = RichText::from_xml(el);
+        Ok(FixText { lang, content })
+    }
+
+    pub fn lang(&self) -> Option<&str> {
+        self.lang.as_deref()
+    }
+
+    pub fn content(&self) -> &RichText {
+        &self.content
+    }
+
+    pub fn text(&self) -> String {
+        self.content.to_text(|_| None)
     }
 }
 
 #[derive(Debug)]
-struct Fix {
-    text: String,
+pub struct Fix {
+    pub(crate) id: Option<Arc<str>>,
+    pub(crate) system: Option<Arc<str>>,
+    pub(crate) platform: Option<Arc<str>>,
+    pub(crate) reboot: bool,
+    pub(crate) strategy: Arc<str>,
+    pub(crate) disruption: Arc<str>,
+    pub(crate) complexity: Arc<str>,
+    pub(crate) tokens: Vec<FixToken>,
+}
+
+/// Part of the content of a fix, the `sub` and `instance` placeholders are
+/// kept apart from the script text so that they can be substituted.
+#[derive(Debug, PartialEq)]
+pub enum FixToken {
+    Text(String),
+    /// `xccdf:sub`, refers to the ID of a Value
+    ValueRef(Arc<str>),
+    /// `xccdf:instance`, refers to the instance name of the rule result
+    InstanceRef(Arc<str>),
 }
 
 impl Fix {
-    pub fn from_xml(el: &Element) -> Result<Fix, String> {
-        let text = el.text();
-        Ok(Fix { text })
+    pub fn from_xml(el: &Element) -> Result<Fix, Error> {
+        let id = get_attr(el, "id");
+        let system = get_attr(el, "system");
+        let platform = get_attr(el, "platform");
+        let reboot = get_attr_default_bool(el, "reboot", false)?;
+        let strategy = get_attr(el, "strategy").unwrap_or_else(|| intern("unknown"));
+        let disruption = get_attr(el, "disruption").unwrap_or_else(|| intern("unknown"));
+        let complexity = get_attr(el, "complexity").unwrap_or_else(|| intern("unknown"));
+        let mut tokens = Vec::new();
+        for node in el.nodes() {
+            match node {
+                Node::Text(text) => match tokens.last_mut() {
+                    Some(FixToken::Text(last)) => last.push_str(text),
+                    _ => tokens.push(FixToken::Text(text.clone())),
+                },
+                Node::Element(child) if child.is("sub", XCCDF12_NS) => {
+                    tokens.push(FixToken::ValueRef(require_attr(child, "idref")?))
+                }
+                Node::Element(child) if child.is("instance", XCCDF12_NS) => {
+                    let context = get_attr(child, "context").unwrap_or_else(|| intern("undefined"));
+                    tokens.push(FixToken::InstanceRef(context))
+                }
+                Node::Element(child) => {
+                    return Err(Error::unexpected_element(
+                        child.name(),
+                        Some("'sub' or 'instance'"),
+                    ))
+                }
+            }
+        }
+        Ok(Fix {
+            id,
+            system,
+            platform,
+            reboot,
+            strategy,
+            disruption,
+            complexity,
+            tokens,
+        })
+    }
+
+    /// Script text without the placeholders.
+    pub fn text(&self) -> String {
+        self.tokens
+            .iter()
+            .filter_map(|token| match token {
+                FixToken::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Builds the script, replacing every `sub` with the value returned by
+    /// `value_of` and every `instance` with the result of `instance_of`.
+    /// Fails with the list of references that couldn't be resolved.
+    pub fn substitute<V, I>(&self, value_of: V, instance_of: I) -> Result<String, Error>
+    where
+        V: Fn(&str) -> Option<String>,
+        I: Fn(&str) -> Option<String>,
+    {
+        let mut script = String::new();
+        let mut unresolved = Vec::new();
+        for token in self.tokens.iter() {
+            match token {
+                FixToken::Text(text) => script.push_str(text),
+                FixToken::ValueRef(idref) => match value_of(idref) {
+                    Some(value) => script.push_str(&value),
+                    None => unresolved.push(format!("value '{}'", idref)),
+                },
+                FixToken::InstanceRef(context) => match instance_of(context) {
+                    Some(instance) => script.push_str(&instance),
+                    None => unresolved.push(format!("instance '{}'", context)),
+                },
+            }
+        }
+        if unresolved.is_empty() {
+            Ok(script)
+        } else {
+            Err(Error::Content(format!(
+                "Unresolved references in fix: {}",
+                unresolved.join(", ")
+            )))
+        }
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    pub fn system(&self) -> Option<&str> {
+        self.system.as_deref()
+    }
+
+    pub fn platform(&self) -> Option<&str> {
+        self.platform.as_deref()
+    }
+
+    pub fn reboot(&self) -> bool {
+        self.reboot
     }
 }
 
 #[derive(Debug)]
-struct Check {
-    text: String,
+pub struct Check {
+    pub(crate) system: CheckSystem,
+    pub(crate) text: String,
+    pub(crate) exports: Vec<CheckExport>,
+    pub(crate) content_refs: Vec<CheckContentRef>,
 }
 
 impl Check {
-    pub fn from_xml(el: &Element) -> Result<Check, String> {
+    pub fn from_xml(el: &Element) -> Result<Check, Error> {
+        let system = require_attr_parsed(el, "system")?;
         let text = el.text();
-        Ok(Check { text })
+        let mut exports = Vec::new();
+        let mut content_refs = Vec::new();
+        for child in el.children() {
+            if child.is("check-export", XCCDF12_NS) {
+                exports.push(CheckExport::from_xml(child)?);
+            } else if child.is("check-content-ref", XCCDF12_NS) {
+                content_refs.push(CheckContentRef::from_xml(child)?);
+            }
+        }
+        Ok(Check {
+            system,
+            text,
+            exports,
+            content_refs,
+        })
+    }
+
+    pub fn system(&self) -> &CheckSystem {
+        &self.system
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn exports(&self) -> &[CheckExport] {
+        &self.exports
+    }
+
+    pub fn content_refs(&self) -> &[CheckContentRef] {
+        &self.content_refs
+    }
+}
+
+/// Binding of a Value to a variable of the check content, e.g. an OVAL
+/// external variable.
+#[derive(Debug)]
+pub struct CheckExport {
+    pub(crate) value_id: Arc<str>,
+    pub(crate) export_name: Arc<str>,
+}
+
+impl CheckExport {
+    pub fn from_xml(el: &Element) -> Result<CheckExport, Error> {
+        let value_id = require_attr(el, "value-id")?;
+        let export_name = require_attr(el, "export-name")?;
+        Ok(CheckExport {
+            value_id,
+            export_name,
+        })
+    }
+
+    pub fn value_id(&self) -> &str {
+        &self.value_id
+    }
+
+    pub fn export_name(&self) -> &str {
+        &self.export_name
+    }
+}
+
+/// Reference to the check content, e.g. an OVAL definition in a component
+/// of the data stream, which is found through the catalog of the checklist.
+#[derive(Debug)]
+pub struct CheckContentRef {
+    pub(crate) href: Arc<str>,
+    pub(crate) name: Option<Arc<str>>,
+}
+
+impl CheckContentRef {
+    pub fn from_xml(el: &Element) -> Result<CheckContentRef, Error> {
+        let href = require_attr(el, "href")?;
+        let name = get_attr(el, "name");
+        Ok(CheckContentRef { href, name })
+    }
+
+    pub fn href(&self) -> &str {
+        &self.href
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
     }
 }
 
 #[derive(Debug)]
-struct ComplexCheck {
-    text: String,
+pub struct ComplexCheck {
+    pub(crate) text: String,
 }
 
 impl ComplexCheck {
-    pub fn from_xml(el: &Element) -> Result<ComplexCheck, String> {
+    pub fn from_xml(el: &Element) -> Result<ComplexCheck, Error> {
         let text = el.text();
         Ok(ComplexCheck { text })
     }
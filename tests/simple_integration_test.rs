@@ -10,3 +10,26 @@ fn test_simple() {
     let result = sds::DataStreamCollection::from_xml(&root);
     assert!(result.is_ok());
 }
+
+#[test]
+fn test_public_accessors() {
+    let mut reader = quick_xml::Reader::from_file("data/simple.xml").unwrap();
+    let root = Element::from_reader(&mut reader).unwrap();
+    let collection = sds::DataStreamCollection::from_xml(&root).unwrap();
+    let data_stream = &collection.data_streams()[0];
+    assert_eq!(data_stream.id(), "scap_org.open-scap_datastream_simple");
+    assert_eq!(data_stream.scap_version(), sds::ScapVersion::V1_3);
+    assert_eq!(collection.components().len(), 2);
+    let benchmark = collection.benchmarks()[0];
+    assert_eq!(benchmark.id(), "xccdf_com.example.www_benchmark_dummy");
+    assert_eq!(benchmark.lang(), Some("en-US"));
+    let profile = &benchmark.profiles()[0];
+    assert_eq!(
+        profile.id(),
+        "xccdf_com.example.www_profile_test_single_rule"
+    );
+    assert!(!profile.titles()[0].text().is_empty());
+    let rule = &benchmark.rules()[0];
+    assert_eq!(rule.id(), "xccdf_com.example.www_rule_test-pass");
+    assert!(rule.selected());
+}